@@ -1,6 +1,6 @@
 use std::{env, path::Path};
 
-use flapigen::{CppConfig, CppOptional, CppStrView, CppVariant, LanguageConfig};
+use flapigen::{CatchPanics, CppConfig, CppOptional, CppStrView, CppVariant, LanguageConfig};
 
 fn main() {
     env_logger::init();
@@ -27,6 +27,8 @@ fn main() {
         cfg
     };
 
+    let cpp_cfg = cpp_cfg.catch_panics(CatchPanics::Message);
+
     let swig_gen = flapigen::Generator::new(LanguageConfig::CppConfig(cpp_cfg))
         .rustfmt_bindings(true)
         .remove_not_generated_files_from_output_directory(true);
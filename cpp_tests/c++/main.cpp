@@ -5,6 +5,7 @@
 #include <cstdio>
 #include <cstring>
 #include <array>
+#include <vector>
 #include <functional>
 #include <limits>
 #include <iostream>
@@ -12,6 +13,7 @@
 #include <thread>
 #include <chrono>
 #include <mutex>
+#include <future>
 #include <gtest/gtest.h>
 
 #include "rust_interface/CheckPrimitiveTypesClass.hpp"
@@ -44,6 +46,11 @@
 #include "rust_interface/ThreadSafeObserver.hpp"
 #include "rust_interface/TestMultiThreadCallback.hpp"
 #include "rust_interface/Session.hpp"
+#include "rust_interface/TestCatchPanics.hpp"
+#include "rust_interface/TestEnumWithData.hpp"
+#include "rust_interface/TestAsync.hpp"
+#include "rust_interface/TestClosures.hpp"
+#include "rust_interface/TestIterators.hpp"
 
 using namespace rust;
 
@@ -1114,6 +1121,117 @@ TEST(SmartPtrCopy, smokeTest)
               static_cast<const SessionOpaque *>(session3));
 }
 
+TEST(TestCatchPanics, smokeTest)
+{
+    bool have_exception = false;
+    try {
+        TestCatchPanics::panic_with_message("panic from Rust");
+    } catch (const std::runtime_error &ex) {
+        have_exception = true;
+        EXPECT_NE(nullptr, std::strstr(ex.what(), "panic from Rust"));
+    }
+    EXPECT_TRUE(have_exception);
+}
+
+#if (defined(HAS_STDCXX_17) && !defined(NO_HAVE_STD17_VARIANT)                                     \
+     && !defined(NO_HAVE_STD17_OPTIONAL))                                                          \
+    || defined(USE_BOOST)
+TEST(TestEnumWithData, smokeTest)
+{
+    NetworkEvent event = TestEnumWithData::connected(42, "localhost");
+#ifdef HAS_STDCXX_17
+    const NetworkEventConnected *connected = std::get_if<NetworkEventConnected>(&event);
+#endif // HAS_STDCXX_17
+#ifdef USE_BOOST
+    const NetworkEventConnected *connected = boost::get<NetworkEventConnected>(&event);
+#endif // USE_BOOST
+    ASSERT_TRUE(nullptr != connected);
+    EXPECT_EQ(42u, connected->id);
+    EXPECT_EQ("localhost", connected->addr);
+
+    EXPECT_EQ("Connected { id: 42, addr: \"localhost\" }",
+              TestEnumWithData::describe(event).to_std_string());
+    EXPECT_EQ("Failed(\"timeout\")",
+              TestEnumWithData::describe(NetworkEventFailed{ "timeout" }).to_std_string());
+    EXPECT_EQ("Closed", TestEnumWithData::describe(NetworkEventClosed{}).to_std_string());
+
+    EXPECT_FALSE(!!TestEnumWithData::last_event(false));
+    auto last = TestEnumWithData::last_event(true);
+    ASSERT_TRUE(!!last);
+#ifdef HAS_STDCXX_17
+    EXPECT_TRUE(nullptr != std::get_if<NetworkEventClosed>(&*last));
+#endif // HAS_STDCXX_17
+#ifdef USE_BOOST
+    EXPECT_TRUE(nullptr != boost::get<NetworkEventClosed>(&*last));
+#endif // USE_BOOST
+}
+#endif
+
+TEST(TestAsync, smokeTest)
+{
+    TestAsync loader("item");
+    EXPECT_EQ("item5", loader.load(5).get().to_std_string());
+    EXPECT_EQ(5, TestAsync::sum(2, 3).get());
+
+    std::promise<int32_t> promise;
+    std::future<int32_t> result = promise.get_future();
+    AsyncTask task = TestAsync::sum(40, 2, [&promise](int32_t ret) { promise.set_value(ret); });
+    EXPECT_EQ(42, result.get());
+
+#if defined(HAS_STDCXX_17) && !defined(NO_HAVE_STD17_VARIANT)
+    auto ok = TestAsync::check_positive(1).get();
+    ASSERT_TRUE(nullptr != std::get_if<int32_t>(&ok));
+    EXPECT_EQ(1, std::get<int32_t>(ok));
+    auto err = TestAsync::check_positive(-1).get();
+    ASSERT_TRUE(nullptr != std::get_if<RustString>(&err));
+    EXPECT_EQ("not positive: -1", std::get<RustString>(err).to_std_string());
+#endif // HAS_STDCXX_17
+}
+
+TEST(TestClosures, smokeTest)
+{
+    std::vector<int32_t> args;
+    TestClosures::call_n_times(3, [&args](int32_t i) { args.push_back(i); });
+    EXPECT_EQ((std::vector<int32_t>{ 0, 1, 2 }), args);
+
+    std::atomic<int32_t> result{ 0 };
+    TestClosures::call_from_thread(21, [&result](int32_t x) { result = x; });
+    EXPECT_EQ(42, result.load());
+
+    std::vector<std::string> seen;
+    int32_t n = TestClosures::count_matches("a bb ccc", [&seen](auto word, int32_t i) {
+        seen.emplace_back(word);
+        return i != 1;
+    });
+    EXPECT_EQ(2, n);
+    EXPECT_EQ((std::vector<std::string>{ "a", "bb", "ccc" }), seen);
+}
+
+TEST(TestIterators, smokeTest)
+{
+    TestIterators numbers(4);
+    std::vector<int32_t> squares;
+    for (int32_t x : numbers.iter()) {
+        squares.push_back(x);
+    }
+    EXPECT_EQ((std::vector<int32_t>{ 0, 1, 4, 9 }), squares);
+
+    auto it = TestIterators(0).iter();
+    EXPECT_TRUE(it.begin() == it.end());
+
+    std::vector<std::string> words;
+    for (RustString &word : TestIterators::words("a bb ccc")) {
+        words.emplace_back(word.to_std_string());
+    }
+    EXPECT_EQ((std::vector<std::string>{ "a", "bb", "ccc" }), words);
+
+    auto manual = TestIterators::words("x");
+    auto first = manual.next();
+    ASSERT_TRUE(!!first);
+    EXPECT_EQ("x", first->to_std_string());
+    EXPECT_FALSE(!!manual.next());
+}
+
 int main(int argc, char *argv[])
 {
     ::testing::InitGoogleTest(&argc, argv);
@@ -3,7 +3,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use flapigen::{JavaConfig, JavaReachabilityFence, LanguageConfig};
+use flapigen::{CatchPanics, JavaConfig, JavaReachabilityFence, LanguageConfig};
 
 fn main() {
     env_logger::init();
@@ -23,7 +23,8 @@ fn main() {
         JavaReachabilityFence::Std
     } else {
         JavaReachabilityFence::GenerateFence(8)
-    });
+    })
+    .catch_panics(CatchPanics::Message);
 
     let in_src = Path::new("src").join("java_glue.rs.in");
     let test_opt_rsc = Path::new("src").join("test_optional.rs.in");
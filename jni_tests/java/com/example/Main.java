@@ -44,6 +44,13 @@ import com.example.rust.DropCounter;
 import com.example.rust.LongOperation;
 import com.example.rust.TestReturnInCallback;
 import com.example.rust.ReturnInCallbackTester;
+import com.example.rust.TestCatchPanics;
+import com.example.rust.NetworkEvent;
+import com.example.rust.TestEnumWithData;
+import com.example.rust.TestAsync;
+import com.example.rust.TestClosures;
+import com.example.rust.TestIterators;
+import com.example.rust.IterString;
 
 class Main {
     public static void main(String[] args) {
@@ -135,6 +142,11 @@ class Main {
 	    testPrematureGc();
             testPartialEq();
             testReturnInCallback();
+            testCatchPanics();
+            testEnumWithData();
+            testAsync();
+            testClosures();
+            testIterators();
         } catch (Throwable ex) {
             ex.printStackTrace();
             System.exit(-1);
@@ -776,4 +788,94 @@ class Main {
         TestReturnInCallback cb = new JavaTestReturnInCallback();
         ReturnInCallbackTester.run(cb);
     }
+
+    private static void testCatchPanics() {
+        boolean haveException = false;
+        try {
+            TestCatchPanics.panic_with_message("panic from Rust");
+        } catch (RuntimeException ex) {
+            haveException = true;
+            assert ex.getMessage().contains("panic from Rust");
+        }
+        assert haveException;
+    }
+
+    private static void testEnumWithData() {
+        NetworkEvent event = TestEnumWithData.connected(42, "localhost");
+        assert event instanceof NetworkEvent.Connected;
+        NetworkEvent.Connected connected = (NetworkEvent.Connected) event;
+        assert connected.id == 42;
+        assert connected.addr.equals("localhost");
+        assert event.equals(new NetworkEvent.Connected(42, "localhost"));
+
+        assert TestEnumWithData.describe(event).equals("Connected { id: 42, addr: \"localhost\" }");
+        assert TestEnumWithData.describe(new NetworkEvent.Failed("timeout")).equals("Failed(\"timeout\")");
+        assert TestEnumWithData.describe(new NetworkEvent.Closed()).equals("Closed");
+
+        assert !TestEnumWithData.last_event(false).isPresent();
+        assert TestEnumWithData.last_event(true).get() instanceof NetworkEvent.Closed;
+    }
+
+    private static void testAsync() throws Exception {
+        TestAsync loader = new TestAsync("item");
+        assert loader.load(5).get().equals("item5");
+        assert TestAsync.sum(2, 3).get() == 5;
+        assert TestAsync.sum(40, 2).thenApply(x -> x + 1).get() == 43;
+
+        assert TestAsync.check_positive(1).get() == 1;
+        boolean haveException = false;
+        try {
+            TestAsync.check_positive(-1).get();
+        } catch (java.util.concurrent.ExecutionException ex) {
+            haveException = true;
+            assert ex.getCause().getMessage().equals("not positive: -1");
+        }
+        assert haveException;
+    }
+
+    private static void testClosures() {
+        java.util.ArrayList<Integer> args = new java.util.ArrayList<Integer>();
+        TestClosures.call_n_times(3, i -> args.add(i));
+        assert args.equals(java.util.Arrays.asList(0, 1, 2));
+
+        java.util.concurrent.atomic.AtomicInteger result = new java.util.concurrent.atomic.AtomicInteger();
+        TestClosures.call_from_thread(21, x -> result.set(x));
+        assert result.get() == 42;
+
+        java.util.ArrayList<String> seen = new java.util.ArrayList<String>();
+        int n = TestClosures.count_matches("a bb ccc", (word, i) -> {
+            seen.add(word);
+            return i != 1;
+        });
+        assert n == 2;
+        assert seen.equals(java.util.Arrays.asList("a", "bb", "ccc"));
+    }
+
+    private static void testIterators() {
+        TestIterators numbers = new TestIterators(4);
+        java.util.ArrayList<Integer> squares = new java.util.ArrayList<Integer>();
+        for (int x : numbers.iter()) {
+            squares.add(x);
+        }
+        assert squares.equals(java.util.Arrays.asList(0, 1, 4, 9));
+        assert !new TestIterators(0).iter().hasNext();
+
+        long count = java.util.stream.StreamSupport.stream(numbers.iter().spliterator(), false)
+            .filter(x -> x > 0)
+            .count();
+        assert count == 3;
+
+        IterString words = TestIterators.words("a bb ccc");
+        assert words.next().equals("a");
+        assert words.next().equals("bb");
+        assert words.next().equals("ccc");
+        assert !words.hasNext();
+        boolean haveException = false;
+        try {
+            words.next();
+        } catch (java.util.NoSuchElementException ex) {
+            haveException = true;
+        }
+        assert haveException;
+    }
 }
@@ -0,0 +1,107 @@
+
+/// Function that polls futures of `async fn` methods to completion
+type SwigAsyncExecutor = fn(
+    ::std::pin::Pin<Box<dyn ::std::future::Future<Output = ()> + Send + 'static>>,
+);
+
+/// State of future, shared between executor and handle in foreign code
+pub struct SwigAsyncTask {
+    cancelled: ::std::sync::atomic::AtomicBool,
+    waker: ::std::sync::Mutex<Option<::std::task::Waker>>,
+}
+
+impl SwigAsyncTask {
+    /// Drop future without completion, on the next poll by executor
+    #[allow(dead_code)]
+    fn cancel(&self) {
+        self.cancelled
+            .store(true, ::std::sync::atomic::Ordering::SeqCst);
+        let waker = self
+            .waker
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Future that drops wrapped future as soon as task is cancelled
+struct SwigCancellable<F> {
+    future: Option<::std::pin::Pin<Box<F>>>,
+    task: ::std::sync::Arc<SwigAsyncTask>,
+}
+
+impl<F: ::std::future::Future<Output = ()>> ::std::future::Future for SwigCancellable<F> {
+    type Output = ();
+    fn poll(
+        mut self: ::std::pin::Pin<&mut Self>,
+        cx: &mut ::std::task::Context,
+    ) -> ::std::task::Poll<()> {
+        // waker stored before check of flag, so `cancel` can not be lost
+        *self
+            .task
+            .waker
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = Some(cx.waker().clone());
+        if self
+            .task
+            .cancelled
+            .load(::std::sync::atomic::Ordering::SeqCst)
+        {
+            self.future = None;
+            return ::std::task::Poll::Ready(());
+        }
+        let ready = match self.future.as_mut() {
+            Some(future) => future.as_mut().poll(cx).is_ready(),
+            None => true,
+        };
+        if ready {
+            self.future = None;
+            ::std::task::Poll::Ready(())
+        } else {
+            ::std::task::Poll::Pending
+        }
+    }
+}
+
+/// Default executor: poll every future in its own thread
+#[allow(dead_code)]
+fn swig_async_default_executor(
+    future: ::std::pin::Pin<Box<dyn ::std::future::Future<Output = ()> + Send + 'static>>,
+) {
+    struct ThreadWaker(::std::thread::Thread);
+    impl ::std::task::Wake for ThreadWaker {
+        fn wake(self: ::std::sync::Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+    ::std::thread::spawn(move || {
+        let mut future = future;
+        let waker = ::std::task::Waker::from(::std::sync::Arc::new(ThreadWaker(
+            ::std::thread::current(),
+        )));
+        let mut cx = ::std::task::Context::from_waker(&waker);
+        while future.as_mut().poll(&mut cx).is_pending() {
+            ::std::thread::park();
+        }
+    });
+}
+
+/// Pass `future` to executor, returned task can be used to cancel it
+fn swig_async_spawn<F>(future: F) -> ::std::sync::Arc<SwigAsyncTask>
+where
+    F: ::std::future::Future<Output = ()> + Send + 'static,
+{
+    let task = ::std::sync::Arc::new(SwigAsyncTask {
+        cancelled: ::std::sync::atomic::AtomicBool::new(false),
+        waker: ::std::sync::Mutex::new(None),
+    });
+    let executor: SwigAsyncExecutor = swig_async_default_executor;
+    executor(Box::pin(SwigCancellable {
+        future: Some(Box::pin(future)),
+        task: task.clone(),
+    }));
+    task
+}
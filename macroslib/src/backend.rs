@@ -0,0 +1,264 @@
+//! API to implement generator of bindings for language,
+//! that `flapigen` doesn't support itself.
+//!
+//! Implementation of [`LanguageBackend`] is passed to `Generator`
+//! via `LanguageConfig::Custom`. `Generator` parses `foreign_class!`,
+//! `foreign_enum!`, `foreign_callback!` and `foreign_typemap!`, and then
+//! calls [`LanguageBackend::expand_items`], that should generate Rust code
+//! and write code for foreign language with help of [`BackendContext`].
+
+use std::path::{Path, PathBuf};
+
+use proc_macro2::TokenStream;
+use rustc_hash::FxHashSet;
+use syn::Type;
+
+use crate::{
+    extension::{extend_foreign_class, extend_foreign_enum, ExtHandlers},
+    file_cache::FileWriteCache,
+    typemap::{ast::TypeName, utils::remove_files_if, MapToForeignFlag, TypeMap},
+    LanguageGenerator, SourceCode,
+};
+
+pub use crate::{
+    error::{DiagnosticError, Result, SourceIdSpan},
+    source_registry::SourceId,
+    types::{
+        FieldAccessor, FieldAccessorKind, ForeignClassInfo, ForeignEnumInfo, ForeignEnumItem,
+        ForeignEnumItemField, ForeignEnumItemFields, ForeignInterface, ForeignInterfaceMethod,
+        ForeignMethod, ItemToExpand, MethodAccess, MethodVariant, NamedArg, SelfTypeVariant,
+    },
+};
+/// Direction of conversion: `Outgoing` is from Rust to foreign language,
+/// `Incoming` is from foreign language to Rust
+pub use petgraph::Direction;
+/// Versions of crates, that are used in API
+pub use {proc_macro2, syn};
+
+/// Generator of bindings for some language
+pub trait LanguageBackend {
+    /// Code with `foreign_typemap!` rules for this language,
+    /// pairs of name of code for error messages and code itself,
+    /// the same as arguments of `Generator::merge_type_map`
+    fn include_typemaps(&self) -> Vec<(String, String)>;
+
+    /// Generate code for all items, returns Rust code,
+    /// code for foreign language should be written by backend itself
+    fn expand_items(
+        &self,
+        ctx: &mut BackendContext,
+        items: Vec<ItemToExpand>,
+    ) -> Result<Vec<TokenStream>>;
+
+    /// Process the whole generated Rust code before writing it to file
+    fn post_process_code(
+        &self,
+        _pointer_target_width: usize,
+        generated_code: Vec<u8>,
+    ) -> Result<Vec<u8>> {
+        Ok(generated_code)
+    }
+}
+
+/// Foreign type, that Rust type is mapped to by `foreign_typemap!` rules
+#[derive(Debug, Clone)]
+pub struct MappedForeignType {
+    /// Name of type in foreign language
+    pub name: String,
+    /// Rust type, that corresponds to foreign type,
+    /// value of original type should be converted to it via `convert_rust_types`
+    /// (and then to `ForeignConvCode::intermediate_ty` if rule has code on foreign side)
+    pub rust_ty: Type,
+    /// Code on foreign side, if rule has it
+    pub foreign_conv: Option<ForeignConvCode>,
+}
+
+/// Code to convert value on foreign side, for example
+/// `"$out = new java.util.Date($p);"` in `foreign_typemap!`
+#[derive(Debug, Clone)]
+pub struct ForeignConvCode {
+    /// Rust type, that is passed through FFI before conversion on foreign side
+    pub intermediate_ty: Type,
+    /// Code with `{from_var}` and `{to_var}` placeholders
+    pub code: String,
+}
+
+/// Access to type map and helpers of `Generator` for [`LanguageBackend`]
+pub struct BackendContext<'a> {
+    conv_map: &'a mut TypeMap,
+    pointer_target_width: usize,
+    remove_not_generated_files: bool,
+    ext_handlers: ExtHandlers<'a>,
+    generated_files: FxHashSet<PathBuf>,
+    rust_code: Vec<TokenStream>,
+}
+
+impl<'a> BackendContext<'a> {
+    /// Size of pointer in bits for target
+    pub fn pointer_target_width(&self) -> usize {
+        self.pointer_target_width
+    }
+
+    /// Find class with such `self_type`
+    pub fn find_foreign_class(&self, self_type: &Type) -> Option<&ForeignClassInfo> {
+        self.conv_map
+            .find_foreigner_class_with_such_this_type(self_type, |_, fc| fc.self_type().cloned())
+    }
+
+    /// Find foreign type for Rust type, possibly through chain of conversions.
+    /// `calc_this_type_for_method` returns type of `this` for methods of class,
+    /// for example `self_type` of class.
+    pub fn map_through_conversation_to_foreign<F: Fn(&ForeignClassInfo) -> Option<Type>>(
+        &mut self,
+        rust_ty: &Type,
+        direction: Direction,
+        build_for_sp: SourceIdSpan,
+        calc_this_type_for_method: F,
+    ) -> Option<MappedForeignType> {
+        let rust_ty = self
+            .conv_map
+            .find_or_alloc_rust_type(rust_ty, build_for_sp.0);
+        let ftype = self.conv_map.map_through_conversation_to_foreign(
+            rust_ty.to_idx(),
+            direction,
+            MapToForeignFlag::FullSearch,
+            build_for_sp,
+            |_, fc| calc_this_type_for_method(fc),
+        )?;
+        let ftype = &self.conv_map[ftype];
+        let rule = match direction {
+            Direction::Outgoing => ftype.into_from_rust.as_ref(),
+            Direction::Incoming => ftype.from_into_rust.as_ref(),
+        }?;
+        Some(MappedForeignType {
+            name: ftype.typename().to_string(),
+            rust_ty: self.conv_map[rule.rust_ty].ty.clone(),
+            foreign_conv: rule.intermediate.as_ref().map(|x| ForeignConvCode {
+                intermediate_ty: self.conv_map[x.intermediate_ty].ty.clone(),
+                code: x.conv_code.as_str().to_string(),
+            }),
+        })
+    }
+
+    /// Code to convert value of type `from` in variable `in_var_name`
+    /// into `to` in variable `out_var_name`, `function_ret_type` is type
+    /// returned by function, that code is placed into,
+    /// it is used by rules that return errors
+    pub fn convert_rust_types(
+        &mut self,
+        from: &Type,
+        to: &Type,
+        in_var_name: &str,
+        out_var_name: &str,
+        function_ret_type: &str,
+        build_for_sp: SourceIdSpan,
+    ) -> Result<String> {
+        let from = self.conv_map.find_or_alloc_rust_type(from, build_for_sp.0);
+        let to = self.conv_map.find_or_alloc_rust_type(to, build_for_sp.0);
+        let (mut deps, code) = self.conv_map.convert_rust_types(
+            from.to_idx(),
+            to.to_idx(),
+            in_var_name,
+            out_var_name,
+            function_ret_type,
+            build_for_sp,
+        )?;
+        self.rust_code.append(&mut deps);
+        Ok(code)
+    }
+
+    /// Add foreign type, that corresponds to `rust_ty` in both directions,
+    /// for example for enum or callback
+    pub fn add_foreign_type(
+        &mut self,
+        foreign_name: &str,
+        rust_ty: &Type,
+        sp: SourceIdSpan,
+    ) -> Result<()> {
+        let rust_ty = self.conv_map.find_or_alloc_rust_type(rust_ty, sp.0);
+        self.conv_map
+            .add_foreign(rust_ty, TypeName::new(foreign_name.to_string(), sp))?;
+        Ok(())
+    }
+
+    /// Create file, that content is written only if it changes,
+    /// see `remove_not_generated_files`
+    pub fn create_file<P: Into<PathBuf>>(&mut self, path: P) -> FileWriteCache {
+        FileWriteCache::new(path, &mut self.generated_files)
+    }
+
+    /// If `Generator` is configured to remove not generated files, remove files
+    /// with name ending with `suffix` in `output_dir`, that were not created
+    /// via `create_file`
+    pub fn remove_not_generated_files(&self, output_dir: &Path, suffix: &str) -> Result<()> {
+        if !self.remove_not_generated_files {
+            return Ok(());
+        }
+        let generated_files = &self.generated_files;
+        remove_files_if(output_dir, |path| {
+            match path.file_name().and_then(|x| x.to_str()) {
+                Some(name) => name.ends_with(suffix) && !generated_files.contains(path),
+                None => false,
+            }
+        })
+        .map_err(DiagnosticError::map_any_err_to_our_err)
+    }
+
+    /// Call handlers registered via `Generator::register_class_attribute_callback`
+    /// and `Generator::register_method_attribute_callback`,
+    /// derives and attributes from `reserved_*` are handled by backend itself
+    pub fn extend_foreign_class(
+        &self,
+        class: &ForeignClassInfo,
+        cnt: &mut Vec<u8>,
+        reserved_class_derives: &[&str],
+        reserved_method_attrs: &[&str],
+    ) -> Result<()> {
+        extend_foreign_class(
+            class,
+            cnt,
+            reserved_class_derives,
+            reserved_method_attrs,
+            self.ext_handlers.class_ext_handlers,
+            self.ext_handlers.method_ext_handlers,
+        )
+    }
+
+    /// Call handlers registered via `Generator::register_enum_attribute_callback`
+    pub fn extend_foreign_enum(&self, fenum: &ForeignEnumInfo, cnt: &mut Vec<u8>) -> Result<()> {
+        extend_foreign_enum(fenum, cnt, self.ext_handlers.enum_ext_handlers)
+    }
+}
+
+impl LanguageGenerator for Box<dyn LanguageBackend> {
+    fn expand_items(
+        &self,
+        conv_map: &mut TypeMap,
+        pointer_target_width: usize,
+        _code: &[SourceCode],
+        items: Vec<ItemToExpand>,
+        remove_not_generated_files: bool,
+        ext_handlers: ExtHandlers,
+    ) -> Result<Vec<TokenStream>> {
+        let mut ctx = BackendContext {
+            conv_map,
+            pointer_target_width,
+            remove_not_generated_files,
+            ext_handlers,
+            generated_files: FxHashSet::default(),
+            rust_code: vec![],
+        };
+        let mut code = (**self).expand_items(&mut ctx, items)?;
+        code.append(&mut ctx.rust_code);
+        Ok(code)
+    }
+
+    fn post_proccess_code(
+        &self,
+        _conv_map: &mut TypeMap,
+        pointer_target_width: usize,
+        generated_code: Vec<u8>,
+    ) -> Result<Vec<u8>> {
+        (**self).post_process_code(pointer_target_width, generated_code)
+    }
+}
@@ -0,0 +1,288 @@
+//! Closures as arguments of `foreign_class!` methods.
+//! For every closure signature callback with one method `call` is generated,
+//! like it was described by `foreign_callback!`, so foreign side can pass
+//! lambda, and boxed callback is converted to boxed closure on Rust side.
+
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned, ToTokens};
+use rustc_hash::{FxHashMap, FxHashSet};
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    code_parse::parse_foreign_interface,
+    error::{invalid_src_id_span, DiagnosticError, Result},
+    source_registry::SourceId,
+    typemap::{
+        ast::{normalize_type, DisplayToTokens},
+        TypeConvCode, TypeMap, FROM_VAR_TEMPLATE, TO_VAR_TEMPLATE,
+    },
+    types::{FnArg, ForeignClassInfo, ForeignInterface},
+};
+
+/// Callbacks generated for closures of all `foreign_class!`,
+/// closures with the same arguments and output share callback
+#[derive(Default)]
+pub(crate) struct ClosureCallbacks {
+    /// signature -> name of callback
+    callbacks: FxHashMap<String, String>,
+    names: FxHashSet<String>,
+    /// closure types that have conversion from callback
+    converted: FxHashSet<String>,
+}
+
+/// Callback for new closure signature and Rust trait, that it implements
+pub(crate) struct ClosureCallback {
+    pub(crate) interface: ForeignInterface,
+    pub(crate) trait_code: TokenStream,
+}
+
+impl ClosureCallbacks {
+    /// Replace `impl Fn*` arguments with `Box<dyn Fn*>`, register conversion
+    /// of callbacks to closures and return callbacks for not seen before signatures
+    pub(crate) fn expand_class(
+        &mut self,
+        conv_map: &mut TypeMap,
+        fclass: &mut ForeignClassInfo,
+    ) -> Result<Vec<ClosureCallback>> {
+        let src_id = fclass.src_id;
+        let mut ret = vec![];
+        for method in &mut fclass.methods {
+            for arg in &mut method.fn_decl.inputs {
+                let named_arg = match arg {
+                    FnArg::Default(ref mut named_arg) => named_arg,
+                    FnArg::SelfArg(..) => continue,
+                };
+                if conv_map.has_rule_to_get_from_foreign(&named_arg.ty) {
+                    continue;
+                }
+                if let Type::ImplTrait(ref impl_trait) = named_arg.ty {
+                    if closure_bound(&impl_trait.bounds).is_some() {
+                        let bounds = &impl_trait.bounds;
+                        let span = impl_trait.span();
+                        named_arg.ty = parse_spanned!(span, Box<dyn #bounds>)
+                            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+                    }
+                }
+                let bounds = match boxed_trait_object_bounds(&named_arg.ty) {
+                    Some(bounds) => bounds,
+                    None => continue,
+                };
+                let args = match closure_bound(bounds) {
+                    Some(args) => args,
+                    None => continue,
+                };
+                check_closure_bounds(src_id, bounds)?;
+
+                let (inputs, output) = (&args.inputs, &args.output);
+                let signature = normalize_type(&parse_type! { fn(#inputs) #output });
+                let name = match self.callbacks.get(signature) {
+                    Some(name) => name.clone(),
+                    None => {
+                        let name = self.new_callback_name(&args.inputs, &args.output);
+                        ret.push(generate_callback(src_id, &name, &named_arg.ty, args)?);
+                        self.callbacks.insert(signature.to_string(), name.clone());
+                        name
+                    }
+                };
+
+                let closure_ty = conv_map.find_or_alloc_rust_type(&named_arg.ty, src_id);
+                if self
+                    .converted
+                    .insert(closure_ty.normalized_name.to_string())
+                {
+                    let trait_name = callback_trait_name(&name);
+                    let boxed_callback_ty =
+                        conv_map.find_or_alloc_rust_type_no_src_id(&parse_type! {
+                            Box<dyn #trait_name>
+                        });
+                    let arg_names = (0..args.inputs.len())
+                        .map(|i| format!("a{}", i))
+                        .collect::<Vec<_>>();
+                    let args_with_types = arg_names
+                        .iter()
+                        .zip(args.inputs.iter())
+                        .map(|(name, ty)| format!("{}: {}", name, DisplayToTokens(ty)))
+                        .collect::<Vec<_>>();
+                    conv_map.add_conversation_rule(
+                        boxed_callback_ty.to_idx(),
+                        closure_ty.to_idx(),
+                        TypeConvCode::new2(
+                            format!(
+                                "let {to_var}: {closure_ty} = Box::new(move |{args_with_types}| {output} {{
+                                    {from_var}.call({args})
+                                }});",
+                                to_var = TO_VAR_TEMPLATE,
+                                from_var = FROM_VAR_TEMPLATE,
+                                closure_ty = DisplayToTokens(&closure_ty.ty),
+                                args_with_types = args_with_types.join(", "),
+                                output = DisplayToTokens(output),
+                                args = arg_names.join(", "),
+                            ),
+                            invalid_src_id_span(),
+                        )
+                        .into(),
+                    );
+                }
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Name derived from types of arguments and output,
+    /// like `FnI32StrToBool` for `Fn(i32, &str) -> bool`
+    fn new_callback_name(
+        &mut self,
+        inputs: &syn::punctuated::Punctuated<Type, syn::Token![,]>,
+        output: &syn::ReturnType,
+    ) -> String {
+        let mut name = "Fn".to_string();
+        for ty in inputs {
+            mangle_tokens(ty.to_token_stream(), &mut name);
+        }
+        if let syn::ReturnType::Type(_, ref ty) = output {
+            name.push_str("To");
+            mangle_tokens(ty.to_token_stream(), &mut name);
+        }
+        let mut unique_name = name.clone();
+        let mut idx = 2;
+        while self.names.contains(&unique_name) {
+            unique_name = format!("{}{}", name, idx);
+            idx += 1;
+        }
+        self.names.insert(unique_name.clone());
+        unique_name
+    }
+}
+
+fn callback_trait_name(name: &str) -> Ident {
+    Ident::new(&format!("Swig{}", name), Span::call_site())
+}
+
+fn generate_callback(
+    src_id: SourceId,
+    name: &str,
+    closure_ty: &Type,
+    args: &syn::ParenthesizedGenericArguments,
+) -> Result<ClosureCallback> {
+    let span = closure_ty.span();
+    let name_ident = Ident::new(name, span);
+    let trait_name = callback_trait_name(name);
+    let arg_names = (0..args.inputs.len())
+        .map(|i| Ident::new(&format!("a{}", i), span))
+        .collect::<Vec<_>>();
+    let arg_types = args.inputs.iter().collect::<Vec<_>>();
+    let output = &args.output;
+    let callback = quote_spanned! { span =>
+        /// Rust closure, that calls foreign function or lambda
+        callback #name_ident {
+            self_type #trait_name;
+            call = #trait_name::call(&self, #(#arg_names: #arg_types),*) #output;
+        }
+    };
+    let mut interface = parse_foreign_interface(src_id, callback)?;
+    interface.is_closure = true;
+    let trait_code = quote! {
+        trait #trait_name: Send {
+            fn call(&self, #(#arg_names: #arg_types),*) #output;
+        }
+    };
+    Ok(ClosureCallback {
+        interface,
+        trait_code,
+    })
+}
+
+/// Bounds of `T` in `Box<dyn T>`
+pub(crate) fn boxed_trait_object_bounds(
+    ty: &Type,
+) -> Option<&syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>> {
+    let path = match ty {
+        Type::Path(syn::TypePath { qself: None, path }) => path,
+        _ => return None,
+    };
+    let last = path.segments.last()?;
+    if last.ident != "Box" {
+        return None;
+    }
+    let generic_args = match last.arguments {
+        syn::PathArguments::AngleBracketed(ref args) if args.args.len() == 1 => &args.args[0],
+        _ => return None,
+    };
+    match generic_args {
+        syn::GenericArgument::Type(Type::TraitObject(trait_object)) => Some(&trait_object.bounds),
+        _ => None,
+    }
+}
+
+/// Arguments of `Fn`, `FnMut` or `FnOnce` bound
+fn closure_bound(
+    bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>,
+) -> Option<&syn::ParenthesizedGenericArguments> {
+    bounds.iter().find_map(|bound| match bound {
+        syn::TypeParamBound::Trait(trait_bound) => {
+            let last = trait_bound.path.segments.last()?;
+            match last.arguments {
+                syn::PathArguments::Parenthesized(ref args)
+                    if last.ident == "Fn" || last.ident == "FnMut" || last.ident == "FnOnce" =>
+                {
+                    Some(args)
+                }
+                _ => None,
+            }
+        }
+        syn::TypeParamBound::Lifetime(_) => None,
+    })
+}
+
+fn check_closure_bounds(
+    src_id: SourceId,
+    bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>,
+) -> Result<()> {
+    for bound in bounds {
+        if let syn::TypeParamBound::Trait(trait_bound) = bound {
+            let is_closure = matches!(trait_bound.path.segments.last(),
+                                      Some(last) if last.ident == "Fn"
+                                      || last.ident == "FnMut"
+                                      || last.ident == "FnOnce");
+            if !is_closure && !trait_bound.path.is_ident("Send") {
+                return Err(DiagnosticError::new(
+                    src_id,
+                    trait_bound.span(),
+                    "Supported only Send trait and lifetimes as additional bounds of closure",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn mangle_tokens(tokens: TokenStream, name: &mut String) {
+    let mut after_apostrophe = false;
+    for tt in tokens {
+        match tt {
+            TokenTree::Ident(ident) => {
+                if after_apostrophe {
+                    after_apostrophe = false;
+                    continue;
+                }
+                let ident = ident.to_string();
+                if ident == "dyn" {
+                    continue;
+                }
+                let mut chars = ident.chars();
+                if let Some(first) = chars.next() {
+                    name.extend(first.to_uppercase());
+                    name.push_str(chars.as_str());
+                }
+            }
+            TokenTree::Punct(punct) => after_apostrophe = punct.as_char() == '\'',
+            TokenTree::Group(group) => {
+                mangle_tokens(group.stream(), name);
+                if group.delimiter() == proc_macro2::Delimiter::Bracket {
+                    name.push_str("Slice");
+                }
+            }
+            TokenTree::Literal(_) => {}
+        }
+    }
+}
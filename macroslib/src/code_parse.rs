@@ -21,7 +21,8 @@ use crate::{
     source_registry::SourceId,
     typemap::ast::{normalize_type, DisplayToTokens},
     types::{
-        FnArg, ForeignClassInfo, ForeignEnumInfo, ForeignEnumItem, ForeignInterface,
+        FieldAccessor, FieldAccessorKind, FnArg, ForeignClassInfo, ForeignEnumInfo,
+        ForeignEnumItem, ForeignEnumItemField, ForeignEnumItemFields, ForeignInterface,
         ForeignInterfaceMethod, ForeignMethod, MethodAccess, MethodVariant, NamedArg, SelfTypeDesc,
         SelfTypeVariant,
     },
@@ -34,7 +35,11 @@ pub(crate) fn parse_foreigner_class(
     tokens: TokenStream,
 ) -> Result<ForeignClassInfo> {
     match config {
-        LanguageConfig::CppConfig(_) => {
+        LanguageConfig::CppConfig(_)
+        | LanguageConfig::SwiftConfig(_)
+        | LanguageConfig::GoConfig(_)
+        | LanguageConfig::DartConfig(_)
+        | LanguageConfig::CConfig(_) => {
             let mut class: CppClass =
                 syn::parse2(tokens).map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
             class.0.src_id = src_id;
@@ -52,6 +57,42 @@ pub(crate) fn parse_foreigner_class(
             class.0.src_id = src_id;
             Ok(class.0)
         }
+        LanguageConfig::CSharpConfig(_) => {
+            let mut class: CSharpClass =
+                syn::parse2(tokens).map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+            class.0.src_id = src_id;
+            Ok(class.0)
+        }
+        LanguageConfig::NodeConfig(_) => {
+            let mut class: NodeClass =
+                syn::parse2(tokens).map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+            class.0.src_id = src_id;
+            Ok(class.0)
+        }
+        LanguageConfig::LuaConfig(_) => {
+            let mut class: LuaClass =
+                syn::parse2(tokens).map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+            class.0.src_id = src_id;
+            Ok(class.0)
+        }
+        LanguageConfig::RubyConfig(_) => {
+            let mut class: RubyClass =
+                syn::parse2(tokens).map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+            class.0.src_id = src_id;
+            Ok(class.0)
+        }
+        LanguageConfig::WasmConfig(_) => {
+            let mut class: WasmClass =
+                syn::parse2(tokens).map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+            class.0.src_id = src_id;
+            Ok(class.0)
+        }
+        LanguageConfig::Custom(_) => {
+            let mut class: CustomClass =
+                syn::parse2(tokens).map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+            class.0.src_id = src_id;
+            Ok(class.0)
+        }
     }
 }
 
@@ -99,11 +140,71 @@ impl Parse for PythonClass {
     }
 }
 
+struct CSharpClass(ForeignClassInfo);
+
+impl Parse for CSharpClass {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(CSharpClass(do_parse_foreigner_class(
+            Language::CSharp,
+            input,
+        )?))
+    }
+}
+
+struct NodeClass(ForeignClassInfo);
+
+impl Parse for NodeClass {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(NodeClass(do_parse_foreigner_class(Language::Node, input)?))
+    }
+}
+
+struct LuaClass(ForeignClassInfo);
+
+impl Parse for LuaClass {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(LuaClass(do_parse_foreigner_class(Language::Lua, input)?))
+    }
+}
+
+struct RubyClass(ForeignClassInfo);
+
+impl Parse for RubyClass {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(RubyClass(do_parse_foreigner_class(Language::Ruby, input)?))
+    }
+}
+
+struct WasmClass(ForeignClassInfo);
+
+impl Parse for WasmClass {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(WasmClass(do_parse_foreigner_class(Language::Wasm, input)?))
+    }
+}
+
+struct CustomClass(ForeignClassInfo);
+
+impl Parse for CustomClass {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(CustomClass(do_parse_foreigner_class(
+            Language::Custom,
+            input,
+        )?))
+    }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 enum Language {
     Cpp,
     Java,
     Python,
+    CSharp,
+    Node,
+    Lua,
+    Ruby,
+    Wasm,
+    Custom,
 }
 
 mod kw {
@@ -116,6 +217,8 @@ mod kw {
     custom_keyword!(empty);
     custom_keyword!(interface);
     custom_keyword!(callback);
+    custom_keyword!(field);
+    custom_keyword!(readonly);
 }
 
 struct Attrs {
@@ -189,7 +292,7 @@ fn parse_doc_comments(input: ParseStream) -> syn::Result<Vec<String>> {
     Ok(doc_comments)
 }
 
-fn do_parse_foreigner_class(_lang: Language, input: ParseStream) -> syn::Result<ForeignClassInfo> {
+fn do_parse_foreigner_class(lang: Language, input: ParseStream) -> syn::Result<ForeignClassInfo> {
     let Attrs {
         doc_comments: class_doc_comments,
         mut derive_list,
@@ -238,6 +341,55 @@ fn do_parse_foreigner_class(_lang: Language, input: ParseStream) -> syn::Result<
             access = MethodAccess::Protected;
         }
 
+        if content.peek(kw::field) || content.peek(kw::readonly) {
+            let readonly = content.parse::<Option<kw::readonly>>()?.is_some();
+            content.parse::<kw::field>()?;
+            let field_name: Ident = content.parse()?;
+            content.parse::<Token![:]>()?;
+            let field_ty: Type = content.parse()?;
+            content.parse::<Token![;]>()?;
+            let getter_name = Ident::new(&format!("get_{}", field_name), field_name.span());
+            let getter: syn::ItemFn = parse_quote! {
+                fn #getter_name(&self) -> #field_ty {
+                    ::std::clone::Clone::clone(&this.#field_name)
+                }
+            };
+            let mut accessors = vec![(FieldAccessorKind::Getter, getter)];
+            if !readonly {
+                let setter_name = Ident::new(&format!("set_{}", field_name), field_name.span());
+                let setter: syn::ItemFn = parse_quote! {
+                    fn #setter_name(&mut self, #field_name: #field_ty) {
+                        this.#field_name = #field_name;
+                    }
+                };
+                accessors.push((FieldAccessorKind::Setter, setter));
+            }
+            for (kind, accessor) in accessors {
+                let self_variant = match kind {
+                    FieldAccessorKind::Getter => SelfTypeVariant::Rptr,
+                    FieldAccessorKind::Setter => SelfTypeVariant::RptrMut,
+                };
+                let field_accessor = FieldAccessor {
+                    kind,
+                    name: field_name.clone(),
+                };
+                methods.push(ForeignMethod {
+                    variant: MethodVariant::Method(self_variant),
+                    rust_id: accessor.sig.ident.clone().into(),
+                    name_alias: field_accessor_alias(lang, &field_accessor),
+                    fn_decl: accessor.sig.try_into()?,
+                    access,
+                    doc_comments: method_doc_comments.clone(),
+                    inline_block: Some(*accessor.block),
+                    unknown_attrs: method_unknown_attrs.clone(),
+                    field_accessor: Some(field_accessor),
+                    is_async: false,
+                });
+            }
+            continue;
+        }
+
+        let async_token = content.parse::<Option<Token![async]>>()?;
         let (func_type_name, func_type_name_span): (String, Span) = if content.peek(Token![fn]) {
             let token = content.parse::<Token![fn]>()?;
             (FN.into(), token.span())
@@ -246,6 +398,14 @@ fn do_parse_foreigner_class(_lang: Language, input: ParseStream) -> syn::Result<
             (id.to_string(), id.span())
         };
         debug!("may be func_type_name {:?}", func_type_name);
+        if let Some(async_token) = async_token {
+            if func_type_name != FN {
+                return Err(syn::Error::new(
+                    async_token.span(),
+                    "async is supported only for methods declared with \"fn\"",
+                ));
+            }
+        }
         if func_type_name == "self_type" {
             rust_self_type = Some(content.parse::<Type>()?);
             debug!("self_type: {:?}", rust_self_type);
@@ -323,6 +483,8 @@ fn do_parse_foreigner_class(_lang: Language, input: ParseStream) -> syn::Result<
                 access,
                 doc_comments: method_doc_comments,
                 unknown_attrs: method_unknown_attrs,
+                field_accessor: None,
+                is_async: false,
             });
             has_dummy_constructor = true;
             continue;
@@ -495,6 +657,25 @@ fn do_parse_foreigner_class(_lang: Language, input: ParseStream) -> syn::Result<
                 constructor_ret_type = Some((*ret_type).clone());
             }
         }
+        let (field_accessor, method_unknown_attrs) = parse_accessor_attrs(
+            &func_name,
+            func_name_alias.as_ref(),
+            func_type,
+            &fn_args,
+            &out_type,
+            method_unknown_attrs,
+        )?;
+        if let (Some(async_token), Some(_)) = (async_token, field_accessor.as_ref()) {
+            return Err(syn::Error::new(
+                async_token.span(),
+                "getter or setter can not be async",
+            ));
+        }
+        if func_name_alias.is_none() {
+            if let Some(ref field_accessor) = field_accessor {
+                func_name_alias = field_accessor_alias(lang, field_accessor);
+            }
+        }
         let span = func_name.span();
         methods.push(ForeignMethod {
             variant: func_type,
@@ -509,6 +690,8 @@ fn do_parse_foreigner_class(_lang: Language, input: ParseStream) -> syn::Result<
             doc_comments: method_doc_comments,
             inline_block,
             unknown_attrs: method_unknown_attrs,
+            field_accessor,
+            is_async: async_token.is_some(),
         });
     }
 
@@ -572,9 +755,102 @@ fn do_parse_foreigner_class(_lang: Language, input: ParseStream) -> syn::Result<
         foreign_code: foreigner_code,
         doc_comments: class_doc_comments,
         derive_list,
+        iterator_type: None,
     })
 }
 
+/// Java way to access property is `getX`/`setX` methods,
+/// other languages use name of Rust function or property syntax
+fn field_accessor_alias(lang: Language, field_accessor: &FieldAccessor) -> Option<Ident> {
+    if lang != Language::Java {
+        return None;
+    }
+    let prefix = match field_accessor.kind {
+        FieldAccessorKind::Getter => "get",
+        FieldAccessorKind::Setter => "set",
+    };
+    Some(Ident::new(
+        &format!("{}_{}", prefix, field_accessor.name).to_mixed_case(),
+        field_accessor.name.span(),
+    ))
+}
+
+/// Handle `#[getter]` and `#[setter]` attributes of method,
+/// name of property is name of method without `get_`/`set_` prefix
+fn parse_accessor_attrs(
+    func_name: &syn::Path,
+    func_name_alias: Option<&Ident>,
+    func_type: MethodVariant,
+    fn_args: &[FnArg],
+    out_type: &syn::ReturnType,
+    unknown_attrs: Vec<String>,
+) -> syn::Result<(Option<FieldAccessor>, Vec<String>)> {
+    let mut kind = None;
+    let mut rest_attrs = Vec::with_capacity(unknown_attrs.len());
+    for attr in unknown_attrs {
+        let attr_kind = match attr.as_str() {
+            "getter" => FieldAccessorKind::Getter,
+            "setter" => FieldAccessorKind::Setter,
+            _ => {
+                rest_attrs.push(attr);
+                continue;
+            }
+        };
+        if kind.is_some() {
+            return Err(syn::Error::new(
+                func_name.span(),
+                "method can have only one of #[getter] and #[setter] attributes",
+            ));
+        }
+        kind = Some(attr_kind);
+    }
+    let kind = match kind {
+        Some(x) => x,
+        None => return Ok((None, rest_attrs)),
+    };
+    let is_method = matches!(func_type, MethodVariant::Method(_));
+    match kind {
+        FieldAccessorKind::Getter => {
+            if !is_method || fn_args.len() != 1 || *out_type == syn::ReturnType::Default {
+                return Err(syn::Error::new(
+                    func_name.span(),
+                    "#[getter] method should accept only self and return value",
+                ));
+            }
+        }
+        FieldAccessorKind::Setter => {
+            if !is_method || fn_args.len() != 2 || *out_type != syn::ReturnType::Default {
+                return Err(syn::Error::new(
+                    func_name.span(),
+                    "#[setter] method should accept self and new value and return nothing",
+                ));
+            }
+        }
+    }
+    let method_name = match func_name_alias {
+        Some(alias) => alias.clone(),
+        None => match func_name.segments.last() {
+            Some(seg) => seg.ident.clone(),
+            None => {
+                return Err(syn::Error::new(
+                    func_name.span(),
+                    "method name should not be empty",
+                ))
+            }
+        },
+    };
+    let method_name_str = method_name.to_string();
+    let prefix = match kind {
+        FieldAccessorKind::Getter => "get_",
+        FieldAccessorKind::Setter => "set_",
+    };
+    let name = match method_name_str.strip_prefix(prefix) {
+        Some(name) if !name.is_empty() => Ident::new(name, method_name.span()),
+        _ => method_name,
+    };
+    Ok((Some(FieldAccessor { kind, name }), rest_attrs))
+}
+
 impl TryFrom<syn::Signature> for crate::types::FnDecl {
     type Error = syn::Error;
     fn try_from(x: syn::Signature) -> std::result::Result<Self, Self::Error> {
@@ -694,11 +970,13 @@ impl Parse for ForeignEnumInfoParser {
             let f_item_name = item_parser.parse::<Ident>()?;
             item_parser.parse::<Token![=]>()?;
             let item_name = item_parser.call(syn::Path::parse_mod_style)?;
+            let fields = parse_foreign_enum_item_fields(&item_parser)?;
             item_parser.parse::<Token![,]>()?;
 
             items.push(ForeignEnumItem {
                 name: f_item_name,
                 rust_name: item_name,
+                fields,
                 doc_comments,
             });
         }
@@ -713,6 +991,40 @@ impl Parse for ForeignEnumInfoParser {
     }
 }
 
+/// Parse `{ a: i32, b: String }` or `(i32, String)` after path of enum variant
+fn parse_foreign_enum_item_fields(input: ParseStream) -> syn::Result<ForeignEnumItemFields> {
+    if input.peek(syn::token::Paren) {
+        let content;
+        parenthesized!(content in input);
+        let types = content.parse_terminated::<Type, Token![,]>(Type::parse)?;
+        let fields = types
+            .into_iter()
+            .enumerate()
+            .map(|(i, ty)| ForeignEnumItemField {
+                name: Ident::new(&format!("field{}", i), ty.span()),
+                ty,
+            })
+            .collect();
+        Ok(ForeignEnumItemFields::Unnamed(fields))
+    } else if input.peek(syn::token::Brace) {
+        let content;
+        braced!(content in input);
+        let mut fields = vec![];
+        while !content.is_empty() {
+            let name: Ident = content.parse()?;
+            content.parse::<Token![:]>()?;
+            let ty: Type = content.parse()?;
+            fields.push(ForeignEnumItemField { name, ty });
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        Ok(ForeignEnumItemFields::Named(fields))
+    } else {
+        Ok(ForeignEnumItemFields::Unit)
+    }
+}
+
 struct ForeignInterfaceParser(ForeignInterface);
 
 impl Parse for ForeignInterfaceParser {
@@ -809,6 +1121,7 @@ impl Parse for ForeignInterfaceParser {
             self_type,
             doc_comments: interface_doc_comments,
             items,
+            is_closure: false,
         }))
     }
 }
@@ -878,6 +1191,156 @@ mod tests {
         assert_eq!("MyEnum", enum_.name.to_string());
     }
 
+    #[test]
+    fn test_parse_foreign_enum_with_data() {
+        let _ = env_logger::try_init();
+        let mac: syn::Macro = parse_quote! {
+            foreign_enum!(enum Event {
+                Connected = Event::Connected { id: u64, addr: String },
+                Failed = Event::Failed(String),
+                Closed = Event::Closed,
+            })
+        };
+        let enum_ = parse_foreign_enum(SourceId::none(), mac.tokens).unwrap();
+        assert!(!enum_.is_c_like());
+        let fields: Vec<_> = enum_
+            .items
+            .iter()
+            .map(|item| {
+                item.fields
+                    .as_slice()
+                    .iter()
+                    .map(|f| format!("{}: {}", f.name, DisplayToTokens(&f.ty)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                vec!["id: u64".to_string(), "addr: String".to_string()],
+                vec!["field0: String".to_string()],
+                vec![],
+            ],
+            fields
+        );
+    }
+
+    #[test]
+    fn test_parse_foreign_class_with_fields() {
+        let _ = env_logger::try_init();
+        let mac: syn::Macro = parse_quote! {
+            foreign_class!(class Point {
+                self_type Point;
+                constructor Point::new() -> Point;
+                field x: f64;
+                readonly field id: u32;
+                #[getter]
+                fn Point::get_len(&self) -> f64;
+                #[setter]
+                fn Point::set_len(&mut self, len: f64);
+            })
+        };
+        let accessors = |class: &ForeignClassInfo| {
+            class
+                .methods
+                .iter()
+                .filter_map(|m| {
+                    m.field_accessor
+                        .as_ref()
+                        .map(|a| (m.short_name(), a.kind, a.name.to_string()))
+                })
+                .collect::<Vec<_>>()
+        };
+        let class: CppClass = test_parse(mac.tokens.clone());
+        assert_eq!(
+            vec![
+                (
+                    "get_x".to_string(),
+                    FieldAccessorKind::Getter,
+                    "x".to_string()
+                ),
+                (
+                    "set_x".to_string(),
+                    FieldAccessorKind::Setter,
+                    "x".to_string()
+                ),
+                (
+                    "get_id".to_string(),
+                    FieldAccessorKind::Getter,
+                    "id".to_string()
+                ),
+                (
+                    "get_len".to_string(),
+                    FieldAccessorKind::Getter,
+                    "len".to_string()
+                ),
+                (
+                    "set_len".to_string(),
+                    FieldAccessorKind::Setter,
+                    "len".to_string()
+                ),
+            ],
+            accessors(&class.0)
+        );
+        assert_eq!(
+            MethodVariant::Method(SelfTypeVariant::RptrMut),
+            class.0.methods[2].variant
+        );
+        assert!(class.0.methods.iter().all(|m| m.unknown_attrs.is_empty()));
+
+        let class: JavaClass = test_parse(mac.tokens);
+        assert_eq!(
+            vec!["getX", "setX", "getId", "getLen", "setLen"],
+            accessors(&class.0)
+                .into_iter()
+                .map(|(name, _, _)| name)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse_foreign_class_with_async_methods() {
+        let _ = env_logger::try_init();
+        let mac: syn::Macro = parse_quote! {
+            foreign_class!(class Foo {
+                self_type Foo;
+                constructor Foo::new() -> Foo;
+                async fn Foo::load(&self, id: u32) -> String;
+                fn Foo::sync_load(&self, id: u32) -> String;
+                async fn sum(a: i32, b: i32) -> i32 {
+                    a + b
+                }
+            })
+        };
+        let class: CppClass = test_parse(mac.tokens);
+        assert_eq!(
+            vec![false, true, false, true],
+            class
+                .0
+                .methods
+                .iter()
+                .map(|m| m.is_async)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            MethodVariant::Method(SelfTypeVariant::Rptr),
+            class.0.methods[1].variant
+        );
+        assert_eq!(MethodVariant::StaticMethod, class.0.methods[3].variant);
+
+        let mac: syn::Macro = parse_quote! {
+            foreign_class!(class Foo {
+                self_type Foo;
+                async constructor Foo::new() -> Foo;
+            })
+        };
+        let err = syn::parse2::<CppClass>(mac.tokens)
+            .err()
+            .expect("async constructor should be rejected");
+        assert!(err
+            .to_string()
+            .contains("async is supported only for methods"));
+    }
+
     #[test]
     fn test_parse_foreign_class_with_copy_derive() {
         let _ = env_logger::try_init();
@@ -0,0 +1,243 @@
+//! `async fn` methods: C function passes Rust future to executor
+//! and returns `RustAsyncTask`, result is passed to completion callback.
+//! C++ wrapper has two overloads: one returns `std::future`,
+//! another one takes `std::function` and returns cancellable `AsyncTask`
+
+use std::io::Write;
+
+use crate::{
+    cpp::{CppContext, CppForeignMethodSignature},
+    error::{panic_on_syn_error, DiagnosticError, Result},
+    file_cache::FileWriteCache,
+    types::ForeignMethod,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::cpp) const C_HEADER: &str = "rust_async.h";
+pub(in crate::cpp) const CPP_HEADER: &str = "rust_async_task.hpp";
+
+/// Write helper headers and Rust code shared by all `async fn` methods
+pub(in crate::cpp) fn generate_runtime(ctx: &mut CppContext) -> Result<()> {
+    let mut headers = vec![(C_HEADER, include_str!("rust_async.h"))];
+    if !ctx.cfg.c_headers_only {
+        headers.push((CPP_HEADER, include_str!("rust_async_task.hpp")));
+    }
+    for (name, code) in headers {
+        let src_path = ctx.cfg.output_dir.join(name);
+        let mut src_file = FileWriteCache::new(&src_path, ctx.generated_foreign_files);
+        src_file
+            .write_all(
+                code.replace("RUST_SWIG_USER_NAMESPACE", &ctx.cfg.namespace_name)
+                    .as_bytes(),
+            )
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+        src_file.update_file_if_necessary().map_err(|err| {
+            DiagnosticError::map_any_err_to_our_err(format!(
+                "update of {} failed: {}",
+                src_path.display(),
+                err
+            ))
+        })?;
+    }
+
+    ctx.rust_code.push(crate::async_include_code(
+        ctx.cfg.async_executor.as_deref(),
+    )?);
+    let code = include_str!("cpp-async-include.rs");
+    ctx.rust_code.push(
+        syn::parse_str(code)
+            .unwrap_or_else(|err| panic_on_syn_error("cpp-async-include.rs", code.into(), err)),
+    );
+    Ok(())
+}
+
+/// Parameters of C function after arguments of method
+pub(in crate::cpp) fn c_callback_params(f_method: &CppForeignMethodSignature) -> String {
+    let c_ret_type = f_method.output.as_ref().name.as_str();
+    if c_ret_type == "void" {
+        "void (*on_complete)(void *opaque), void (*free_opaque)(void *opaque), void *opaque"
+            .to_string()
+    } else {
+        format!(
+            "void (*on_complete)(void *opaque, {} ret), \
+             void (*free_opaque)(void *opaque), void *opaque",
+            c_ret_type
+        )
+    }
+}
+
+/// Parameters of Rust function after arguments of method
+pub(in crate::cpp) fn rust_callback_params(c_ret_type: &str) -> String {
+    format!(
+        "swig_on_complete: extern \"C\" fn(opaque: *mut ::std::os::raw::c_void{}), \
+         swig_free_opaque: Option<extern \"C\" fn(opaque: *mut ::std::os::raw::c_void)>, \
+         swig_opaque: *mut ::std::os::raw::c_void",
+        if c_ret_type == "()" {
+            String::new()
+        } else {
+            format!(", ret: {}", c_ret_type)
+        }
+    )
+}
+
+/// Rust code after conversion of arguments: spawn future
+/// and return handle of task, result is converted to C type after completion
+/// in thread of executor
+pub(in crate::cpp) fn spawn_future_code(
+    method: &ForeignMethod,
+    ret_name: &str,
+    real_output_typename: &str,
+    c_ret_type: &str,
+    convert_output_code: &str,
+) -> String {
+    let complete = if c_ret_type == "()" {
+        "        swig_on_complete(swig_callback.opaque());".to_string()
+    } else {
+        format!(
+            r#"
+        let {ret_name}: {c_ret_type} = (move || -> {c_ret_type} {{
+{convert_output_code}
+            {ret_name}
+        }})();
+        swig_on_complete(swig_callback.opaque(), {ret_name});"#,
+            ret_name = ret_name,
+            c_ret_type = c_ret_type,
+            convert_output_code = convert_output_code,
+        )
+    };
+    format!(
+        r#"
+    let swig_fut = {call};
+    let swig_task = swig_async_spawn(async move {{
+        let mut {ret_name}: {real_output_typename} = swig_fut.await;
+{complete}
+    }});
+    ::std::sync::Arc::into_raw(swig_task)"#,
+        call = method.generate_code_to_call_rust_func(),
+        ret_name = ret_name,
+        real_output_typename = real_output_typename,
+        complete = complete,
+    )
+}
+
+/// Names and code of C++ method, that are not changed between overloads
+pub(in crate::cpp) struct CppAsyncMethod<'a> {
+    /// "template<bool OWN_DATA>\n    inline " or "inline "
+    pub impl_prefix: &'a str,
+    /// `Foo<OWN_DATA>` or `Foo`
+    pub class_name: &'a str,
+    pub method_name: &'a str,
+    /// "static " for static method
+    pub decl_prefix: &'a str,
+    /// "const " for method with `&self`
+    pub const_if_readonly: &'a str,
+    pub cpp_args_with_types: &'a str,
+    pub conv_args_code: &'a str,
+    pub c_func_name: &'a str,
+    /// `this->self_` and converted arguments, every one with trailing comma
+    pub c_args: &'a str,
+    pub c_ret_type: &'a str,
+    pub cpp_ret_type: &'a str,
+    /// C++ code with `return`, that converts C value in `ret_name`
+    pub convert_ret_for_cpp: &'a str,
+    pub ret_name: &'a str,
+    pub unique_name: &'a dyn Fn(&str) -> String,
+}
+
+/// Declarations for class and inline implementations of both overloads
+pub(in crate::cpp) fn cpp_method_code(m: &CppAsyncMethod) -> (String, String) {
+    let is_void = m.c_ret_type == "void";
+    let cpp_ret_type = if is_void { "void" } else { m.cpp_ret_type };
+    let ret_param = if is_void {
+        String::new()
+    } else {
+        format!(", {} {}", m.c_ret_type, m.ret_name)
+    };
+    let cpp_value = if is_void {
+        String::new()
+    } else {
+        format!(
+            r#"[&]() -> {cpp_ret_type} {{
+{convert_ret_for_cpp}
+                }}()"#,
+            cpp_ret_type = cpp_ret_type,
+            convert_ret_for_cpp = m.convert_ret_for_cpp,
+        )
+    };
+    let callback_arg = (m.unique_name)("on_complete");
+    let callback_type = format!(
+        "std::function<void({})>",
+        if is_void { "" } else { cpp_ret_type }
+    );
+    let comma_cpp_args = if m.cpp_args_with_types.is_empty() {
+        String::new()
+    } else {
+        format!("{}, ", m.cpp_args_with_types)
+    };
+    let promise = (m.unique_name)("promise");
+    let future = (m.unique_name)("future");
+    let callback = (m.unique_name)("callback");
+
+    let decl = format!(
+        r#"
+    {decl_prefix}std::future<{cpp_ret_type}> {method_name}({cpp_args_with_types}) {const_if_readonly}noexcept;
+
+    {decl_prefix}AsyncTask {method_name}({comma_cpp_args}{callback_type} {callback_arg}) {const_if_readonly}noexcept;
+"#,
+        decl_prefix = m.decl_prefix,
+        cpp_ret_type = cpp_ret_type,
+        method_name = m.method_name,
+        cpp_args_with_types = m.cpp_args_with_types,
+        comma_cpp_args = comma_cpp_args,
+        callback_type = callback_type,
+        callback_arg = callback_arg,
+        const_if_readonly = m.const_if_readonly,
+    );
+    let inline_impl = format!(
+        r#"
+    {impl_prefix}std::future<{cpp_ret_type}> {class_name}::{method_name}({cpp_args_with_types}) {const_if_readonly}noexcept
+    {{
+{conv_args_code}
+        auto {promise} = new std::promise<{cpp_ret_type}>();
+        std::future<{cpp_ret_type}> {future} = {promise}->get_future();
+        rust_async_task_free({c_func_name}({c_args}
+            [](void *opaque{ret_param}) {{
+                static_cast<std::promise<{cpp_ret_type}> *>(opaque)->set_value({cpp_value});
+            }},
+            [](void *opaque) {{ delete static_cast<std::promise<{cpp_ret_type}> *>(opaque); }},
+            {promise}));
+        return {future};
+    }}
+
+    {impl_prefix}AsyncTask {class_name}::{method_name}({comma_cpp_args}{callback_type} {callback_arg}) {const_if_readonly}noexcept
+    {{
+{conv_args_code}
+        auto {callback} = new {callback_type}(std::move({callback_arg}));
+        return AsyncTask({c_func_name}({c_args}
+            [](void *opaque{ret_param}) {{
+                (*static_cast<{callback_type} *>(opaque))({cpp_value});
+            }},
+            [](void *opaque) {{ delete static_cast<{callback_type} *>(opaque); }},
+            {callback}));
+    }}
+"#,
+        impl_prefix = m.impl_prefix,
+        class_name = m.class_name,
+        method_name = m.method_name,
+        cpp_args_with_types = m.cpp_args_with_types,
+        comma_cpp_args = comma_cpp_args,
+        const_if_readonly = m.const_if_readonly,
+        conv_args_code = m.conv_args_code,
+        cpp_ret_type = cpp_ret_type,
+        promise = promise,
+        future = future,
+        callback = callback,
+        callback_arg = callback_arg,
+        callback_type = callback_type,
+        c_func_name = m.c_func_name,
+        c_args = m.c_args.trim_end(),
+        ret_param = ret_param,
+        cpp_value = cpp_value,
+    );
+    (decl, inline_impl)
+}
@@ -0,0 +1,39 @@
+/// Opaque data of C caller of async method with function to free it,
+/// `free_opaque` is called exactly once, even if future is cancelled
+struct SwigAsyncCallback {
+    free_opaque: Option<extern "C" fn(opaque: *mut ::std::os::raw::c_void)>,
+    opaque: *mut ::std::os::raw::c_void,
+}
+
+/// Caller is responsible for thread safety of `opaque`,
+/// it is documented in `rust_async.h`
+unsafe impl Send for SwigAsyncCallback {}
+
+impl SwigAsyncCallback {
+    #[allow(dead_code)]
+    fn opaque(&self) -> *mut ::std::os::raw::c_void {
+        self.opaque
+    }
+}
+
+impl Drop for SwigAsyncCallback {
+    fn drop(&mut self) {
+        if let Some(free_opaque) = self.free_opaque {
+            free_opaque(self.opaque);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_async_task_cancel(task: *const SwigAsyncTask) {
+    if let Some(task) = unsafe { task.as_ref() } {
+        task.cancel();
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_async_task_free(task: *mut SwigAsyncTask) {
+    if !task.is_null() {
+        drop(unsafe { ::std::sync::Arc::from_raw(task) });
+    }
+}
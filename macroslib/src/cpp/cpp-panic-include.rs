@@ -0,0 +1,53 @@
+type SwigPanicCallback =
+    extern "C" fn(msg: *const ::std::os::raw::c_char, opaque: *mut ::std::os::raw::c_void);
+
+/// Callback and its `opaque` argument, see `rust_panic.h`
+static SWIG_PANIC_CALLBACK: ::std::sync::Mutex<Option<(SwigPanicCallback, usize)>> =
+    ::std::sync::Mutex::new(None);
+
+thread_local! {
+    /// Message of panic, if the last call of generated function in this thread panicked
+    static SWIG_LAST_PANIC: ::std::cell::RefCell<Option<::std::ffi::CString>> =
+        ::std::cell::RefCell::new(None);
+}
+
+#[no_mangle]
+pub extern "C" fn flapigen_set_panic_callback(
+    callback: Option<SwigPanicCallback>,
+    opaque: *mut ::std::os::raw::c_void,
+) {
+    let mut guard = SWIG_PANIC_CALLBACK
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    *guard = callback.map(|callback| (callback, opaque as usize));
+}
+
+#[no_mangle]
+pub extern "C" fn flapigen_last_panic_message() -> *const ::std::os::raw::c_char {
+    SWIG_LAST_PANIC.with(|last| match *last.borrow() {
+        Some(ref msg) => msg.as_ptr(),
+        None => ::std::ptr::null(),
+    })
+}
+
+/// Call `f`, if it panics remember panic message for `flapigen_last_panic_message`,
+/// pass it to callback and return zero-initialized value,
+/// C types can not have invalid zero values
+#[allow(dead_code)]
+fn swig_c_catch_panic<R, F: FnOnce() -> R>(with_backtrace: bool, f: F) -> R {
+    SWIG_LAST_PANIC.with(|last| *last.borrow_mut() = None);
+    match swig_catch_panic(with_backtrace, f) {
+        Ok(ret) => ret,
+        Err(msg) => {
+            let c_msg = ::std::ffi::CString::new(msg).unwrap_or_default();
+            let callback = *SWIG_PANIC_CALLBACK
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            if let Some((callback, opaque)) = callback {
+                callback(c_msg.as_ptr(), opaque as *mut ::std::os::raw::c_void);
+            }
+            SWIG_LAST_PANIC.with(|last| *last.borrow_mut() = Some(c_msg));
+            unsafe { ::std::mem::zeroed() }
+        }
+    }
+}
@@ -38,6 +38,168 @@ pub(in crate::cpp) fn doc_comments_to_c_comments(
     comments
 }
 
+/// Doxygen style `///` comments, used in headers of enums with data
+pub(in crate::cpp) fn doc_comments_to_cpp_doc_comments(doc_comments: &[String]) -> String {
+    let mut comments = String::new();
+    for comment in doc_comments {
+        let comment = comment.trim();
+        if comment.is_empty() {
+            comments.push_str("///\n");
+        } else {
+            writeln!(&mut comments, "/// {}", comment).unwrap();
+        }
+    }
+    comments
+}
+
+/// The same as `doc_comments_to_c_comments`, but C89 has no `//` comments,
+/// so `/* */` is used
+pub(in crate::cpp) fn doc_comments_to_c89_comments(
+    doc_comments: &[String],
+    class_comments: bool,
+) -> String {
+    let indent = if class_comments { "" } else { "    " };
+    let lines: Vec<String> = doc_comments
+        .iter()
+        .map(|x| x.trim().replace("*/", "* /"))
+        .collect();
+    match lines.len() {
+        0 => String::new(),
+        1 => format!("{}/** {} */", indent, lines[0]),
+        _ => {
+            let mut comments = format!("{}/**", indent);
+            for line in &lines {
+                if line.is_empty() {
+                    write!(&mut comments, "\n{} *", indent).unwrap();
+                } else {
+                    write!(&mut comments, "\n{} * {}", indent, line).unwrap();
+                }
+            }
+            write!(&mut comments, "\n{} */", indent).unwrap();
+            comments
+        }
+    }
+}
+
+/// Comments for C header, C++ comments are not used in C only mode
+pub(in crate::cpp) fn doc_comments_for_c_header(
+    ctx: &CppContext,
+    doc_comments: &[String],
+    class_comments: bool,
+) -> String {
+    if ctx.cfg.c_headers_only {
+        doc_comments_to_c89_comments(doc_comments, class_comments)
+    } else {
+        doc_comments_to_c_comments(doc_comments, class_comments)
+    }
+}
+
+/// Includes that are valid for C code, C++ headers are filtered out,
+/// `stdint.h` is skipped because of it is always included
+pub(in crate::cpp) fn c_only_includes(req_includes: &[SmolStr]) -> String {
+    let mut includes = String::new();
+    for inc in req_includes {
+        if (inc.ends_with(".h\"") || inc.ends_with(".h>")) && inc != "<stdint.h>" {
+            writeln!(&mut includes, "#include {}", inc).expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+    }
+    includes
+}
+
+/// Opaque types of classes that used in signatures, sorted
+pub(in crate::cpp) fn c_list_used_opaque_types(
+    methods: &[CppForeignMethodSignature],
+) -> Vec<String> {
+    let mut types = Vec::new();
+    for m in methods {
+        for ti in m.input.iter().chain(std::iter::once(&m.output)) {
+            let name = ti
+                .base
+                .name
+                .split(|c: char| c == '*' || c.is_whitespace())
+                .find(|x| !x.is_empty() && *x != "const" && *x != "struct");
+            if let Some(name) = name {
+                if name.ends_with("Opaque") && !types.iter().any(|x: &String| x == name) {
+                    types.push(name.to_string());
+                }
+            }
+        }
+    }
+    types.sort();
+    types
+}
+
+/// `typedef` for opaque type, guarded because of several headers
+/// can declare the same type and C99 prohibits typedef redefinition
+pub(in crate::cpp) fn c_opaque_typedef(c_class_type: &str) -> String {
+    format!(
+        r#"#ifndef {c_class_type}_DEFINED
+#define {c_class_type}_DEFINED
+typedef struct {c_class_type} {c_class_type};
+#endif
+"#,
+        c_class_type = c_class_type
+    )
+}
+
+/// Remove `#ifdef __cplusplus` blocks, except `extern "C"` guards,
+/// so C header does not contain C++ code from `foreign_code!`
+pub(in crate::cpp) fn strip_cpp_only_code(code: &str) -> String {
+    fn is_extern_c_guard(lines: &[&str]) -> bool {
+        lines.iter().all(|x| {
+            let x = x.trim();
+            x.is_empty() || x == "extern \"C\" {" || x == "}" || x.starts_with("} //")
+        })
+    }
+    let mut ret = String::with_capacity(code.len());
+    let mut lines = code.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() != "#ifdef __cplusplus" {
+            ret.push_str(line);
+            ret.push('\n');
+            continue;
+        }
+        let mut cpp_part = Vec::new();
+        let mut c_part = Vec::new();
+        let mut in_else = false;
+        let mut depth = 0;
+        for line in lines.by_ref() {
+            let directive = line.trim_start();
+            if directive.starts_with("#if") {
+                depth += 1;
+            } else if directive.starts_with("#endif") {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            } else if depth == 0 && directive.starts_with("#else") {
+                in_else = true;
+                continue;
+            }
+            if in_else {
+                c_part.push(line);
+            } else {
+                cpp_part.push(line);
+            }
+        }
+        if is_extern_c_guard(&cpp_part) {
+            ret.push_str(line);
+            ret.push('\n');
+            for x in &cpp_part {
+                // C89 has no `//` comments
+                ret.push_str(if x.starts_with("} //") { "}" } else { x });
+                ret.push('\n');
+            }
+            ret.push_str("#endif\n");
+        }
+        for x in &c_part {
+            ret.push_str(x);
+            ret.push('\n');
+        }
+    }
+    ret
+}
+
 pub(in crate::cpp) fn c_generate_args_with_types<'a, NI>(
     f_method: &CppForeignMethodSignature,
     name_iter: NI,
@@ -61,7 +223,11 @@ where
 }
 
 pub(in crate::cpp) fn c_class_type(class: &ForeignClassInfo) -> String {
-    format!("{}Opaque", class.name)
+    c_class_type_name(&class.name.to_string())
+}
+
+pub(crate) fn c_class_type_name(class_name: &str) -> String {
+    format!("{}Opaque", class_name)
 }
 
 pub(in crate::cpp) fn cpp_generate_args_with_types<'a, NI: Iterator<Item = &'a str>>(
@@ -167,6 +333,10 @@ pub(in crate::cpp) fn cpp_header_name_for_enum(enum_info: &ForeignEnumInfo) -> S
     format!("{}.hpp", enum_info.name)
 }
 
+pub(in crate::cpp) fn c_header_name_for_enum(enum_info: &ForeignEnumInfo) -> String {
+    format!("c_{}.h", enum_info.name)
+}
+
 pub(in crate::cpp) fn cpp_list_required_includes(
     methods: &mut [CppForeignMethodSignature],
 ) -> Vec<SmolStr> {
@@ -336,6 +506,11 @@ fn check_{struct_name}_{field_name}_type_fn(s: &{struct_name}) -> &{field_type}
             .expect(WRITE_TO_MEM_FAILED_MSG);
     }
     mem_out.write_all(b"};\n").expect(WRITE_TO_MEM_FAILED_MSG);
+    if ctx.cfg.c_headers_only {
+        // signatures of functions may use type name without `struct`/`union`
+        writeln!(&mut mem_out, "typedef {} {};", s_id, ctype.name())
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
 
     writeln!(
         &mut rust_layout_test,
@@ -10,12 +10,12 @@ use syn::{spanned::Spanned, Ident, Type};
 
 use crate::{
     cpp::{
-        c_func_name, cpp_code, do_c_func_name, map_type::map_type, CppContext,
+        async_fn, c_func_name, cpp_code, do_c_func_name, map_type::map_type, CppContext,
         CppForeignMethodSignature, CppForeignTypeInfo, MethodContext,
     },
     error::{panic_on_syn_error, DiagnosticError, Result},
     extension::extend_foreign_class,
-    file_cache::FileWriteCache,
+    file_cache::{FileOperationsRegistrator, FileWriteCache, NoNeedFsOpsRegistration},
     namegen::new_unique_name,
     typemap::{
         ast::{list_lifetimes, strip_lifetimes},
@@ -29,9 +29,13 @@ use crate::{
         TO_VAR_TYPE_TEMPLATE,
     },
     types::{ForeignClassInfo, MethodAccess, MethodVariant, SelfTypeVariant},
-    KNOWN_CLASS_DERIVES, PLAIN_CLASS, SMART_PTR_COPY_TRAIT, WRITE_TO_MEM_FAILED_MSG,
+    CatchPanics, KNOWN_CLASS_DERIVES, PLAIN_CLASS, SMART_PTR_COPY_TRAIT, WRITE_TO_MEM_FAILED_MSG,
 };
 
+/// Header with input iterator used by classes generated for Rust iterators
+const ITERATOR_HEADER: &str = "rust_input_iterator.hpp";
+const PANIC_HEADER: &str = "rust_panic.h";
+
 pub(in crate::cpp) fn generate(ctx: &mut CppContext, class: &ForeignClassInfo) -> Result<()> {
     debug!(
         "generate: begin for {}, this_type_for_method {:?}",
@@ -63,6 +67,25 @@ May be you need to use `private constructor = empty;` syntax?",
     let my_self_cpp = format!("\"{}\"", cpp_code::cpp_header_name(class));
     let my_self_c = format!("\"{}\"", cpp_code::c_header_name(class));
     req_includes.retain(|el| *el != my_self_cpp && *el != my_self_c);
+    if class.methods.iter().any(|m| m.is_async) {
+        let mut async_includes = vec![format!("\"{}\"", async_fn::C_HEADER)];
+        if !ctx.cfg.c_headers_only {
+            async_includes.push("<functional>".into());
+            async_includes.push("<future>".into());
+            async_includes.push(format!("\"{}\"", async_fn::CPP_HEADER));
+        }
+        for inc in async_includes {
+            if !req_includes.iter().any(|x| *x == inc) {
+                req_includes.push(inc.into());
+            }
+        }
+    }
+    if class.is_iterator() && !ctx.cfg.c_headers_only {
+        req_includes.push(format!("\"{}\"", ITERATOR_HEADER).into());
+    }
+    if ctx.cfg.catch_panics.is_some() {
+        req_includes.push(format!("\"{}\"", PANIC_HEADER).into());
+    }
     do_generate(ctx, class, &req_includes, &m_sigs)?;
     Ok(())
 }
@@ -77,10 +100,16 @@ fn do_generate(
 
     let c_path = ctx.cfg.output_dir.join(cpp_code::c_header_name(class));
     let mut c_include_f = FileWriteCache::new(&c_path, ctx.generated_foreign_files);
+    // in C only mode C++ code is generated as usual, but not written
+    let cpp_fs_reg: &mut dyn FileOperationsRegistrator = if ctx.cfg.c_headers_only {
+        &mut NoNeedFsOpsRegistration
+    } else {
+        ctx.generated_foreign_files
+    };
     let cpp_path = ctx.cfg.output_dir.join(cpp_code::cpp_header_name(class));
-    let mut cpp_include_f = FileWriteCache::new(&cpp_path, ctx.generated_foreign_files);
+    let mut cpp_include_f = FileWriteCache::new(&cpp_path, cpp_fs_reg);
     let cpp_fwd_path = ctx.cfg.output_dir.join(format!("{}_fwd.hpp", class.name));
-    let mut cpp_fwd_f = FileWriteCache::new(&cpp_fwd_path, ctx.generated_foreign_files);
+    let mut cpp_fwd_f = FileWriteCache::new(&cpp_fwd_path, cpp_fs_reg);
 
     macro_rules! map_write_err {
         ($file_path:ident) => {
@@ -97,7 +126,17 @@ fn do_generate(
     let c_class_type = cpp_code::c_class_type(class);
     let class_doc_comments = cpp_code::doc_comments_to_c_comments(&class.doc_comments, true);
 
-    generte_c_header_preamble(ctx, &class_doc_comments, &c_class_type, &mut c_include_f);
+    if ctx.cfg.c_headers_only {
+        generate_c89_header_preamble(
+            class,
+            req_includes,
+            methods_sign,
+            &c_class_type,
+            &mut c_include_f,
+        );
+    } else {
+        generte_c_header_preamble(ctx, &class_doc_comments, &c_class_type, &mut c_include_f);
+    }
     let plain_class = need_plain_class(class);
     let class_name = if !plain_class {
         format!("{}Wrapper", class.name)
@@ -109,6 +148,7 @@ fn do_generate(
         .methods
         .iter()
         .all(|x| x.variant == MethodVariant::StaticMethod);
+    let (noexcept, check_panic) = cpp_panic_handling(ctx.cfg.catch_panics, 8);
 
     generate_cpp_header_preamble(
         ctx,
@@ -179,8 +219,21 @@ May be you need to use `private constructor = empty;` syntax?",
     let mut inline_impl = String::new();
 
     for (method, f_method) in class.methods.iter().zip(methods_sign) {
+        let c_doc_comments = if ctx.cfg.c_headers_only
+            && method.variant == MethodVariant::Constructor
+            && !method.is_dummy_constructor()
+        {
+            let mut doc_comments = method.doc_comments.clone();
+            doc_comments.push(format!(
+                "Returned object must be freed with {}_delete",
+                class.name
+            ));
+            cpp_code::doc_comments_to_c89_comments(&doc_comments, false)
+        } else {
+            cpp_code::doc_comments_for_c_header(ctx, &method.doc_comments, false)
+        };
         c_include_f
-            .write_all(cpp_code::doc_comments_to_c_comments(&method.doc_comments, false).as_bytes())
+            .write_all(c_doc_comments.as_bytes())
             .expect(WRITE_TO_MEM_FAILED_MSG);
 
         let method_access = match method.access {
@@ -206,6 +259,12 @@ May be you need to use `private constructor = empty;` syntax?",
         } else {
             format!(", {}", c_args_with_types)
         };
+        // in C `f()` means function with unspecified arguments
+        let c_args_with_types = if c_args_with_types.is_empty() && ctx.cfg.c_headers_only {
+            "void".to_string()
+        } else {
+            c_args_with_types
+        };
         let have_args_except_self = if let MethodVariant::Method(_) = method.variant {
             method.fn_decl.inputs.len() > 1
         } else {
@@ -288,6 +347,7 @@ May be you need to use `private constructor = empty;` syntax?",
             decl_func_args: &rust_args_with_types,
             real_output_typename: &real_output_typename,
             ret_name: &ret_name,
+            catch_panics: ctx.cfg.catch_panics,
         };
 
         let method_name = method.short_name().as_str().to_string();
@@ -353,6 +413,100 @@ May be you need to use `private constructor = empty;` syntax?",
             String::new()
         };
 
+        if method.is_async {
+            if input_to_output_arg.is_some() {
+                return Err(DiagnosticError::new(
+                    class.src_id,
+                    method.rust_id.span(),
+                    "async method can not have argument with 'intput_to_output' tag",
+                ));
+            }
+            let (mut c_params, mut c_args, decl_prefix, const_if_readonly) = match method.variant {
+                MethodVariant::StaticMethod => (String::new(), String::new(), "static ", ""),
+                MethodVariant::Method(ref self_variant) => {
+                    let const_if_readonly = if self_variant.is_read_only() {
+                        "const "
+                    } else {
+                        ""
+                    };
+                    (
+                        format!("{}{} * const self", const_if_readonly, c_class_type),
+                        "this->self_, ".to_string(),
+                        "",
+                        const_if_readonly,
+                    )
+                }
+                MethodVariant::Constructor => {
+                    return Err(DiagnosticError::new(
+                        class.src_id,
+                        method.rust_id.span(),
+                        "constructor can not be async",
+                    ));
+                }
+            };
+            write!(
+                &mut c_params,
+                "{}, {}",
+                comma_c_args_with_types,
+                async_fn::c_callback_params(f_method)
+            )
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+            writeln!(
+                c_include_f,
+                r#"
+    RustAsyncTask *{c_func_name}({c_params});"#,
+                c_func_name = c_func_name,
+                c_params = c_params.trim_start_matches(", "),
+            )
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+
+            if have_args_except_self {
+                write!(&mut c_args, "{}, ", cpp_args_for_c).expect(WRITE_TO_MEM_FAILED_MSG);
+            }
+            let (impl_prefix, impl_class_name) = if !plain_class {
+                (
+                    "template<bool OWN_DATA>\n    inline ",
+                    format!("{}<OWN_DATA>", class_name),
+                )
+            } else {
+                ("inline ", class_name.clone())
+            };
+            let (decl, impl_code) = async_fn::cpp_method_code(&async_fn::CppAsyncMethod {
+                impl_prefix,
+                class_name: &impl_class_name,
+                method_name: &method_name,
+                decl_prefix,
+                const_if_readonly,
+                cpp_args_with_types: &cpp_args_with_types,
+                conv_args_code: &conv_args_code,
+                c_func_name: &c_func_name,
+                c_args: &c_args,
+                c_ret_type: &f_method.output.as_ref().name,
+                cpp_ret_type: &cpp_ret_type,
+                convert_ret_for_cpp: &convert_ret_for_cpp,
+                ret_name: &ret_name,
+                unique_name: &|name| new_unique_name(&known_names, name).to_string(),
+            });
+            cpp_include_f
+                .write_all(decl.as_bytes())
+                .expect(WRITE_TO_MEM_FAILED_MSG);
+            inline_impl.push_str(&impl_code);
+
+            if let MethodVariant::Method(self_variant) = method.variant {
+                ctx.rust_code.append(&mut generate_method(
+                    ctx.conv_map,
+                    &method_ctx,
+                    class,
+                    self_variant,
+                    &this_type_for_method,
+                )?);
+            } else {
+                ctx.rust_code
+                    .append(&mut generate_static_method(ctx.conv_map, &method_ctx)?);
+            }
+            continue;
+        }
+
         match method.variant {
             MethodVariant::StaticMethod => {
                 writeln!(
@@ -368,7 +522,8 @@ May be you need to use `private constructor = empty;` syntax?",
                 writeln!(
                     cpp_include_f,
                     r#"
-    static {cpp_ret_type} {method_name}({cpp_args_with_types}) noexcept;"#,
+    static {cpp_ret_type} {method_name}({cpp_args_with_types}) {noexcept};"#,
+                    noexcept = noexcept,
                     method_name = method_name,
                     cpp_ret_type = cpp_ret_type,
                     cpp_args_with_types = cpp_args_with_types,
@@ -380,9 +535,10 @@ May be you need to use `private constructor = empty;` syntax?",
                         &mut inline_impl,
                         r#"
     template<bool OWN_DATA>
-    inline {cpp_ret_type} {class_name}<OWN_DATA>::{method_name}({cpp_args_with_types}) noexcept
+    inline {cpp_ret_type} {class_name}<OWN_DATA>::{method_name}({cpp_args_with_types}) {noexcept}
     {{
 {conv_args_code}"#,
+                        noexcept = noexcept,
                         cpp_ret_type = cpp_ret_type,
                         class_name = class_name,
                         method_name = method_name,
@@ -393,9 +549,10 @@ May be you need to use `private constructor = empty;` syntax?",
                     write!(
                         &mut inline_impl,
                         r#"
-    inline {cpp_ret_type} {class_name}::{method_name}({cpp_args_with_types}) noexcept
+    inline {cpp_ret_type} {class_name}::{method_name}({cpp_args_with_types}) {noexcept}
     {{
 {conv_args_code}"#,
+                        noexcept = noexcept,
                         cpp_ret_type = cpp_ret_type,
                         class_name = class_name,
                         method_name = method_name,
@@ -409,9 +566,10 @@ May be you need to use `private constructor = empty;` syntax?",
                     writeln!(
                         &mut inline_impl,
                         r#"
-        {c_ret_type} {ret} = {c_func_name}({cpp_args_for_c});
+        {c_ret_type} {ret} = {c_func_name}({cpp_args_for_c});{check_panic}
 {convert_ret_for_cpp}
     }}"#,
+                        check_panic = check_panic,
                         c_ret_type = f_method.output.as_ref().name,
                         convert_ret_for_cpp = convert_ret_for_cpp,
                         cpp_args_for_c = cpp_args_for_c,
@@ -423,8 +581,9 @@ May be you need to use `private constructor = empty;` syntax?",
                     writeln!(
                         &mut inline_impl,
                         r#"
-        {c_func_name}({cpp_args_for_c});{input_to_output}
+        {c_func_name}({cpp_args_for_c});{check_panic}{input_to_output}
     }}"#,
+                        check_panic = check_panic,
                         c_func_name = c_func_name,
                         cpp_args_for_c = cpp_args_for_c,
                         input_to_output = input_to_output_ret_code
@@ -455,7 +614,8 @@ May be you need to use `private constructor = empty;` syntax?",
                 writeln!(
                     cpp_include_f,
                     r#"
-    {cpp_ret_type} {method_name}({cpp_args_with_types}) {const_if_readonly}noexcept;"#,
+    {cpp_ret_type} {method_name}({cpp_args_with_types}) {const_if_readonly}{noexcept};"#,
+                    noexcept = noexcept,
                     method_name = method_name,
                     cpp_ret_type = cpp_ret_type,
                     cpp_args_with_types = cpp_args_with_types,
@@ -466,9 +626,10 @@ May be you need to use `private constructor = empty;` syntax?",
                 if !plain_class {
                     write!(&mut inline_impl, r#"
     template<bool OWN_DATA>
-    inline {cpp_ret_type} {class_name}<OWN_DATA>::{method_name}({cpp_args_with_types}) {const_if_readonly}noexcept
+    inline {cpp_ret_type} {class_name}<OWN_DATA>::{method_name}({cpp_args_with_types}) {const_if_readonly}{noexcept}
     {{
 {conv_args_code}"#,
+                           noexcept = noexcept,
                            cpp_args_with_types = cpp_args_with_types,
                            method_name = method_name,
                            class_name = class_name,
@@ -478,9 +639,10 @@ May be you need to use `private constructor = empty;` syntax?",
                     )
                 } else {
                     write!(&mut inline_impl, r#"
-    inline {cpp_ret_type} {class_name}::{method_name}({cpp_args_with_types}) {const_if_readonly}noexcept
+    inline {cpp_ret_type} {class_name}::{method_name}({cpp_args_with_types}) {const_if_readonly}{noexcept}
     {{
 {conv_args_code}"#,
+                           noexcept = noexcept,
                            cpp_args_with_types = cpp_args_with_types,
                            method_name = method_name,
                            class_name = class_name,
@@ -495,9 +657,10 @@ May be you need to use `private constructor = empty;` syntax?",
                     writeln!(
                         &mut inline_impl,
                         r#"
-        {c_ret_type} {ret} = {c_func_name}(this->self_{cpp_args_for_c});
+        {c_ret_type} {ret} = {c_func_name}(this->self_{cpp_args_for_c});{check_panic}
 {convert_ret_for_cpp}
     }}"#,
+                        check_panic = check_panic,
                         convert_ret_for_cpp = convert_ret_for_cpp,
                         c_ret_type = f_method.output.as_ref().name,
                         c_func_name = c_func_name,
@@ -513,8 +676,9 @@ May be you need to use `private constructor = empty;` syntax?",
                     writeln!(
                         &mut inline_impl,
                         r#"
-        {c_func_name}(this->self_{cpp_args_for_c});{input_to_output}
+        {c_func_name}(this->self_{cpp_args_for_c});{check_panic}{input_to_output}
     }}"#,
+                        check_panic = check_panic,
                         c_func_name = c_func_name,
                         cpp_args_for_c = if !have_args_except_self {
                             String::new()
@@ -558,14 +722,16 @@ May be you need to use `private constructor = empty;` syntax?",
                     writeln!(
                         cpp_include_f,
                         r#"
-    {class_name}({cpp_args_with_types}) noexcept
+    {class_name}({cpp_args_with_types}) {noexcept}
     {{
 {conv_args_code}
-        this->self_ = {c_func_name}({cpp_args_for_c});
+        this->self_ = {c_func_name}({cpp_args_for_c});{check_panic}
         if (this->self_ == nullptr) {{
             std::abort();
         }}
     }}"#,
+                        noexcept = noexcept,
+                        check_panic = check_panic,
                         c_func_name = c_func_name,
                         cpp_args_with_types = cpp_args_with_types,
                         class_name = class_name,
@@ -604,18 +770,23 @@ May be you need to use `private constructor = empty;` syntax?",
 
         let unpack_code = unpack_from_heap_pointer(&this_type, "this", false);
         let c_destructor_name = format!("{}_delete", class.name);
+        let body = format!(
+            r#"
+{unpack_code}
+    drop(this);"#,
+            unpack_code = unpack_code,
+        );
         let code = format!(
             r#"
 #[allow(unused_variables, unused_mut, non_snake_case, unused_unsafe)]
 #[no_mangle]
 pub extern "C" fn {c_destructor_name}(this: *mut {this_type}) {{
-{unpack_code}
-    drop(this);
+{body}
 }}
 "#,
             c_destructor_name = c_destructor_name,
-            unpack_code = unpack_code,
             this_type = this_type_for_method,
+            body = catch_panics_in_body(ctx.cfg.catch_panics, body),
         );
         debug!("we generate and parse code: {}", code);
         ctx.rust_code.push(
@@ -625,10 +796,15 @@ pub extern "C" fn {c_destructor_name}(this: *mut {this_type}) {{
         );
         writeln!(
             c_include_f,
-            r#"
+            r#"{doc_comments}
     void {c_destructor_name}(const {c_class_type} *self);"#,
             c_class_type = c_class_type,
             c_destructor_name = c_destructor_name,
+            doc_comments = if ctx.cfg.c_headers_only {
+                "\n    /** Free object, it can not be used after this call */"
+            } else {
+                ""
+            },
         )
         .expect(WRITE_TO_MEM_FAILED_MSG);
 
@@ -676,6 +852,24 @@ private:
     )
     .expect(WRITE_TO_MEM_FAILED_MSG);
 
+    if let Some(f_method) = methods_sign.last().filter(|_| class.is_iterator()) {
+        // the last method of class of Rust iterator is `next`
+        let optional_type = match f_method.output.cpp_converter {
+            Some(ref conv) => conv.typename.clone(),
+            None => f_method.output.as_ref().name.clone(),
+        };
+        writeln!(
+            cpp_include_f,
+            r#"
+    using iterator = RustInputIterator<{class_name}, {optional_type}>;
+    iterator begin() {noexcept} {{ return iterator(*this); }}
+    iterator end() noexcept {{ return iterator(); }}"#,
+            noexcept = noexcept,
+            class_name = class_name,
+            optional_type = optional_type,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
     if !class.foreign_code.is_empty() {
         writeln!(cpp_include_f, "\n{}", class.foreign_code).expect(WRITE_TO_MEM_FAILED_MSG);
     }
@@ -761,18 +955,22 @@ class {class_name};
     }
     .expect(WRITE_TO_MEM_FAILED_MSG);
 
-    cpp_fwd_f
-        .update_file_if_necessary()
-        .map_err(map_write_err!(cpp_fwd_path))?;
     c_include_f
         .update_file_if_necessary()
         .map_err(map_write_err!(c_path))?;
+    if ctx.cfg.c_headers_only {
+        return Ok(());
+    }
+    cpp_fwd_f
+        .update_file_if_necessary()
+        .map_err(map_write_err!(cpp_fwd_path))?;
 
     let mut cnt = cpp_include_f.take_content();
     extend_foreign_class(
         class,
         &mut cnt,
         &KNOWN_CLASS_DERIVES,
+        &[],
         ctx.class_ext_handlers,
         ctx.method_ext_handlers,
     )?;
@@ -799,33 +997,56 @@ fn generate_static_method(conv_map: &mut TypeMap, mc: &MethodContext) -> Result<
         mc.ret_name,
         &c_ret_type,
     )?;
+    let func_ret_type = func_ret_type(mc, c_ret_type);
     let (deps_code_in, convert_input_code) = foreign_to_rust_convert_method_inputs(
         conv_map,
         mc.class.src_id,
         mc.method,
         mc.f_method,
         mc.method.arg_names_without_self(),
-        &c_ret_type,
+        &func_ret_type,
     )?;
+    let body = if mc.method.is_async {
+        format!(
+            r#"
+{async_callback}
+{convert_input_code}{spawn_future}"#,
+            async_callback = ASYNC_CALLBACK_CODE,
+            convert_input_code = convert_input_code,
+            spawn_future = async_fn::spawn_future_code(
+                mc.method,
+                mc.ret_name,
+                mc.real_output_typename,
+                c_ret_type,
+                &convert_output_code
+            ),
+        )
+    } else {
+        format!(
+            r#"
+{convert_input_code}
+    let mut {ret_name}: {real_output_typename} = {call};
+{convert_output_code}
+    {ret_name}"#,
+            convert_input_code = convert_input_code,
+            convert_output_code = convert_output_code,
+            real_output_typename = mc.real_output_typename,
+            call = mc.method.generate_code_to_call_rust_func(),
+            ret_name = mc.ret_name,
+        )
+    };
     let code = format!(
         r#"
 #[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe)]
 #[no_mangle]
-pub extern "C" fn {func_name}({decl_func_args}) -> {c_ret_type} {{
-{convert_input_code}
-    let mut {ret_name}: {real_output_typename} = {call};
-{convert_output_code}
-    {ret_name}
+pub extern "C" fn {func_name}({decl_func_args}) -> {func_ret_type} {{
+{body}
 }}
 "#,
         func_name = mc.c_func_name,
-        decl_func_args = mc.decl_func_args,
-        c_ret_type = c_ret_type,
-        convert_input_code = convert_input_code,
-        convert_output_code = convert_output_code,
-        real_output_typename = mc.real_output_typename,
-        call = mc.method.generate_code_to_call_rust_func(),
-        ret_name = mc.ret_name,
+        decl_func_args = decl_func_args(mc, c_ret_type),
+        func_ret_type = func_ret_type,
+        body = catch_panics_in_body(mc.catch_panics, body),
     );
     let mut gen_code = deps_code_in;
     gen_code.append(&mut deps_code_out);
@@ -849,13 +1070,14 @@ fn generate_method(
         .as_ref()
         .correspoding_rust_type
         .typename();
+    let func_ret_type = func_ret_type(mc, c_ret_type);
     let (deps_code_in, convert_input_code) = foreign_to_rust_convert_method_inputs(
         conv_map,
         mc.class.src_id,
         mc.method,
         mc.f_method,
         mc.method.arg_names_without_self(),
-        &c_ret_type,
+        &func_ret_type,
     )?;
     let (mut deps_code_out, convert_output_code) = foreign_from_rust_convert_method_output(
         conv_map,
@@ -880,14 +1102,33 @@ fn generate_method(
         to_ty.to_idx(),
         "this",
         "this",
-        &c_ret_type,
+        &func_ret_type,
         (mc.class.src_id, mc.method.span()),
     )?;
-    let code = format!(
-        r#"
-#[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe)]
-#[no_mangle]
-pub extern "C" fn {func_name}(this: *mut {this_type}, {decl_func_args}) -> {c_ret_type} {{
+    let body = if mc.method.is_async {
+        format!(
+            r#"
+{async_callback}
+{convert_input_code}
+    let this: {this_type_ref} = unsafe {{
+        this.as_mut().unwrap()
+    }};
+{convert_this}{spawn_future}"#,
+            async_callback = ASYNC_CALLBACK_CODE,
+            convert_input_code = convert_input_code,
+            this_type_ref = from_ty,
+            convert_this = convert_this,
+            spawn_future = async_fn::spawn_future_code(
+                mc.method,
+                mc.ret_name,
+                mc.real_output_typename,
+                c_ret_type,
+                &convert_output_code
+            ),
+        )
+    } else {
+        format!(
+            r#"
 {convert_input_code}
     let this: {this_type_ref} = unsafe {{
         this.as_mut().unwrap()
@@ -895,20 +1136,29 @@ pub extern "C" fn {func_name}(this: *mut {this_type}, {decl_func_args}) -> {c_re
 {convert_this}
     let mut {ret_name}: {real_output_typename} = {call};
 {convert_output_code}
-    {ret_name}
+    {ret_name}"#,
+            convert_input_code = convert_input_code,
+            this_type_ref = from_ty,
+            convert_this = convert_this,
+            convert_output_code = convert_output_code,
+            real_output_typename = mc.real_output_typename,
+            call = mc.method.generate_code_to_call_rust_func(),
+            ret_name = mc.ret_name,
+        )
+    };
+    let code = format!(
+        r#"
+#[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe)]
+#[no_mangle]
+pub extern "C" fn {func_name}(this: *mut {this_type}, {decl_func_args}) -> {func_ret_type} {{
+{body}
 }}
 "#,
         func_name = mc.c_func_name,
-        decl_func_args = mc.decl_func_args,
-        convert_input_code = convert_input_code,
-        c_ret_type = c_ret_type,
-        this_type_ref = from_ty,
+        decl_func_args = decl_func_args(mc, c_ret_type),
+        func_ret_type = func_ret_type,
         this_type = this_type_for_method,
-        convert_this = convert_this,
-        convert_output_code = convert_output_code,
-        real_output_typename = mc.real_output_typename,
-        call = mc.method.generate_code_to_call_rust_func(),
-        ret_name = mc.ret_name,
+        body = catch_panics_in_body(mc.catch_panics, body),
     );
 
     let mut gen_code = deps_code_in;
@@ -948,26 +1198,31 @@ fn generate_constructor(
         (mc.class.src_id, mc.method.span()),
     )?;
 
-    let code = format!(
+    let body = format!(
         r#"
-#[allow(unused_variables, unused_mut, non_snake_case, unused_unsafe)]
-#[no_mangle]
-pub extern "C" fn {func_name}({decl_func_args}) -> *const ::std::os::raw::c_void {{
 {convert_input_code}
     let this: {real_output_typename} = {call};
 {convert_this}
 {box_this}
-    this as *const ::std::os::raw::c_void
-}}
-"#,
-        func_name = mc.c_func_name,
+    this as *const ::std::os::raw::c_void"#,
         convert_this = convert_this,
-        decl_func_args = mc.decl_func_args,
         convert_input_code = convert_input_code,
         box_this = code_box_this,
         real_output_typename = construct_ret_type,
         call = mc.method.generate_code_to_call_rust_func(),
     );
+    let code = format!(
+        r#"
+#[allow(unused_variables, unused_mut, non_snake_case, unused_unsafe)]
+#[no_mangle]
+pub extern "C" fn {func_name}({decl_func_args}) -> *const ::std::os::raw::c_void {{
+{body}
+}}
+"#,
+        func_name = mc.c_func_name,
+        decl_func_args = mc.decl_func_args,
+        body = catch_panics_in_body(mc.catch_panics, body),
+    );
     let mut gen_code = deps_code_in;
     gen_code.append(&mut deps_this);
     gen_code
@@ -977,6 +1232,63 @@ pub extern "C" fn {func_name}({decl_func_args}) -> *const ::std::os::raw::c_void
     Ok(gen_code)
 }
 
+/// Created before conversion of arguments, so `free_opaque` is called
+/// even if conversion panics
+const ASYNC_CALLBACK_CODE: &str = r#"    let swig_callback = SwigAsyncCallback {
+        free_opaque: swig_free_opaque,
+        opaque: swig_opaque,
+    };"#;
+
+/// Return type of generated C function, `async fn` returns handle of task
+fn func_ret_type(mc: &MethodContext, c_ret_type: &str) -> String {
+    if mc.method.is_async {
+        "*const SwigAsyncTask".to_string()
+    } else {
+        c_ret_type.to_string()
+    }
+}
+
+/// Arguments of generated C function except `this`,
+/// `async fn` also has completion callback
+fn decl_func_args(mc: &MethodContext, c_ret_type: &str) -> String {
+    if mc.method.is_async {
+        format!(
+            "{}{}",
+            mc.decl_func_args,
+            async_fn::rust_callback_params(c_ret_type)
+        )
+    } else {
+        mc.decl_func_args.to_string()
+    }
+}
+
+/// Wrap body of generated C function into `swig_c_catch_panic`,
+/// if `CppConfig::catch_panics` is used
+fn catch_panics_in_body(catch_panics: Option<CatchPanics>, body: String) -> String {
+    match catch_panics {
+        Some(catch_panics) => format!(
+            "    swig_c_catch_panic({}, || {{{}\n    }})",
+            catch_panics.with_backtrace(),
+            body
+        ),
+        None => body,
+    }
+}
+
+/// `noexcept` specification of C++ wrapper and code that checks panic after call
+/// of C function: with `CppConfig::catch_panics` C function reports panic
+/// via `flapigen_last_panic_message`, so C++ wrapper throws exception
+fn cpp_panic_handling(catch_panics: Option<CatchPanics>, indent: usize) -> (&'static str, String) {
+    if catch_panics.is_some() {
+        (
+            "noexcept(false)",
+            format!("\n{:indent$}flapigen_check_panic();", "", indent = indent),
+        )
+    } else {
+        ("noexcept", String::new())
+    }
+}
+
 fn write_methods_impls(
     file: &mut FileWriteCache,
     namespace_name: &str,
@@ -992,7 +1304,7 @@ fn write_methods_impls(
     )
 }
 
-fn find_suitable_foreign_types_for_methods(
+pub(crate) fn find_suitable_foreign_types_for_methods(
     ctx: &mut CppContext,
     class: &ForeignClassInfo,
 ) -> Result<Vec<CppForeignMethodSignature>> {
@@ -1081,6 +1393,57 @@ extern "C" {{
     .expect(WRITE_TO_MEM_FAILED_MSG);
 }
 
+/// Preamble of self-contained C header for C only mode,
+/// opaque types are declared in every header that uses them,
+/// because of headers of classes can include each other
+fn generate_c89_header_preamble(
+    class: &ForeignClassInfo,
+    req_includes: &[SmolStr],
+    methods_sign: &[CppForeignMethodSignature],
+    c_class_type: &str,
+    c_include_f: &mut FileWriteCache,
+) {
+    let mut class_doc_comments = class.doc_comments.clone();
+    if class
+        .methods
+        .iter()
+        .any(|m| m.variant == MethodVariant::Constructor)
+    {
+        if !class_doc_comments.is_empty() {
+            class_doc_comments.push(String::new());
+        }
+        class_doc_comments.push(format!(
+            "Opaque handle of Rust object, must be freed with {}_delete",
+            class.name
+        ));
+    }
+    writeln!(
+        c_include_f,
+        r##"/* Automatically generated by flapigen */
+#pragma once
+
+/* for (u)intX_t types */
+#include <stdint.h>
+{includes}
+#ifdef __cplusplus
+extern "C" {{
+#endif
+
+{doc_comments}
+{typedef}"##,
+        includes = cpp_code::c_only_includes(req_includes),
+        doc_comments = cpp_code::doc_comments_to_c89_comments(&class_doc_comments, true),
+        typedef = cpp_code::c_opaque_typedef(c_class_type),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for opaque in cpp_code::c_list_used_opaque_types(methods_sign) {
+        if opaque != c_class_type {
+            write!(c_include_f, "{}", cpp_code::c_opaque_typedef(&opaque))
+                .expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+    }
+}
+
 fn generate_cpp_header_preamble(
     ctx: &mut CppContext,
     class: &ForeignClassInfo,
@@ -1256,6 +1619,8 @@ fn genearte_copy_stuff(
     } else {
         class.name.to_string().into()
     };
+    let (noexcept, check_panic_in_constructor) = cpp_panic_handling(ctx.cfg.catch_panics, 13);
+    let (_, check_panic_in_assign) = cpp_panic_handling(ctx.cfg.catch_panics, 16);
 
     if class.copy_derived() {
         let pos = class
@@ -1283,26 +1648,29 @@ fn genearte_copy_stuff(
         writeln!(
             cpp_include_f,
             r#"
-    {class_name}(const {class_name}& o) noexcept {{
+    {class_name}(const {class_name}& o) {noexcept} {{
          {own_data_static_assert}
          if (o.self_ != nullptr) {{
-             self_ = {c_clone_func}(o.self_);
+             self_ = {c_clone_func}(o.self_);{check_panic_in_constructor}
          }} else {{
              self_ = nullptr;
          }}
     }}
-    {class_name} &operator=(const {class_name}& o) noexcept {{
+    {class_name} &operator=(const {class_name}& o) {noexcept} {{
         {own_data_static_assert}
         if (this != &o) {{
             free_mem(this->self_);
             if (o.self_ != nullptr) {{
-                self_ = {c_clone_func}(o.self_);
+                self_ = {c_clone_func}(o.self_);{check_panic_in_assign}
             }} else {{
                 self_ = nullptr;
             }}
         }}
         return *this;
     }}"#,
+            noexcept = noexcept,
+            check_panic_in_constructor = check_panic_in_constructor,
+            check_panic_in_assign = check_panic_in_assign,
             own_data_static_assert = if !plain_class {
                 "static_assert(OWN_DATA, \"copy possible only if class own data\");"
             } else {
@@ -1340,14 +1708,21 @@ fn genearte_copy_stuff(
         let this_type_ty = this_type.to_type_without_lifetimes();
         let this_type_for_method_ty = this_type_for_method.to_type_without_lifetimes();
 
+        let mut body = quote! {
+            #unpack_code
+            let ret: #this_type_ty = this.clone();
+            ::std::mem::forget(this);
+            SwigForeignClass::box_object(ret)
+        };
+        if let Some(catch_panics) = ctx.cfg.catch_panics {
+            let with_backtrace = catch_panics.with_backtrace();
+            body = quote! { swig_c_catch_panic(#with_backtrace, || { #body }) };
+        }
         ctx.rust_code.push(quote! {
             #[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe)]
             #[no_mangle]
             pub extern "C" fn #clone_fn_name(this: *const #this_type_for_method_ty) -> *mut ::std::os::raw::c_void {
-                #unpack_code
-                let ret: #this_type_ty = this.clone();
-                ::std::mem::forget(this);
-                SwigForeignClass::box_object(ret)
+                #body
             }
         });
         writeln!(
@@ -1361,26 +1736,29 @@ fn genearte_copy_stuff(
         writeln!(
             cpp_include_f,
             r#"
-    {class_name}(const {class_name}& o) noexcept {{
+    {class_name}(const {class_name}& o) {noexcept} {{
          {own_data_static_assert}
          if (o.self_ != nullptr) {{
-             self_ = {c_clone_func}(o.self_);
+             self_ = {c_clone_func}(o.self_);{check_panic_in_constructor}
          }} else {{
              self_ = nullptr;
          }}
     }}
-    {class_name} &operator=(const {class_name}& o) noexcept {{
+    {class_name} &operator=(const {class_name}& o) {noexcept} {{
         {own_data_static_assert}
         if (this != &o) {{
             free_mem(this->self_);
             if (o.self_ != nullptr) {{
-                self_ = {c_clone_func}(o.self_);
+                self_ = {c_clone_func}(o.self_);{check_panic_in_assign}
             }} else {{
                 self_ = nullptr;
             }}
         }}
         return *this;
     }}"#,
+            noexcept = noexcept,
+            check_panic_in_constructor = check_panic_in_constructor,
+            check_panic_in_assign = check_panic_in_assign,
             own_data_static_assert = if !plain_class {
                 "static_assert(OWN_DATA, \"copy possible only if class own data\");"
             } else {
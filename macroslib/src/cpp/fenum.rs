@@ -1,20 +1,24 @@
 use log::trace;
+use petgraph::Direction;
+use proc_macro2::{Span, TokenStream};
 use quote::quote;
+use rustc_hash::FxHashSet;
+use smol_str::SmolStr;
 use std::{io::Write, rc::Rc};
-use syn::Type;
+use syn::{parse_quote, spanned::Spanned, Ident, Type};
 
 use crate::{
-    cpp::{cpp_code, CppContext},
-    error::{invalid_src_id_span, DiagnosticError, Result},
+    cpp::{cpp_code, map_type, CppContext, CppForeignTypeInfo},
+    error::{invalid_src_id_span, panic_on_syn_error, DiagnosticError, Result},
     extension::extend_foreign_enum,
     file_cache::FileWriteCache,
     typemap::{
         ast::{parse_ty_with_given_span, TypeName},
-        ty::{ForeignConversationIntermediate, ForeignConversationRule, ForeignTypeS},
-        TypeConvCode, FROM_VAR_TEMPLATE,
+        ty::{ForeignConversationIntermediate, ForeignConversationRule, ForeignTypeS, RustType},
+        CItem, CItems, TypeConvCode, TypeConvEdge, FROM_VAR_TEMPLATE, TO_VAR_TEMPLATE,
     },
-    types::ForeignEnumInfo,
-    WRITE_TO_MEM_FAILED_MSG,
+    types::{ForeignEnumInfo, ForeignEnumItem, ForeignEnumItemField},
+    CppVariant, WRITE_TO_MEM_FAILED_MSG,
 };
 
 pub(in crate::cpp) fn generate_enum(ctx: &mut CppContext, fenum: &ForeignEnumInfo) -> Result<()> {
@@ -26,6 +30,10 @@ pub(in crate::cpp) fn generate_enum(ctx: &mut CppContext, fenum: &ForeignEnumInf
         ));
     }
 
+    if !fenum.is_c_like() {
+        return generate_enum_with_data(ctx, fenum);
+    }
+
     trace!("enum_ti: {}", fenum.name);
     let enum_name = &fenum.name;
     let enum_ti: Type = parse_ty_with_given_span(&enum_name.to_string(), fenum.name.span())
@@ -36,8 +44,15 @@ pub(in crate::cpp) fn generate_enum(ctx: &mut CppContext, fenum: &ForeignEnumInf
         fenum.src_id,
     );
 
-    generate_c_code_for_enum(ctx, fenum)
-        .map_err(|err| DiagnosticError::new(fenum.src_id, fenum.span(), err))?;
+    let enum_header = if ctx.cfg.c_headers_only {
+        generate_c89_code_for_enum(ctx, fenum)
+            .map_err(|err| DiagnosticError::new(fenum.src_id, fenum.span(), err))?;
+        cpp_code::c_header_name_for_enum(fenum)
+    } else {
+        generate_c_code_for_enum(ctx, fenum)
+            .map_err(|err| DiagnosticError::new(fenum.src_id, fenum.span(), err))?;
+        cpp_code::cpp_header_name_for_enum(fenum)
+    };
     generate_rust_trait_for_enum(ctx, fenum)?;
 
     let u32_rty = ctx
@@ -46,9 +61,7 @@ pub(in crate::cpp) fn generate_enum(ctx: &mut CppContext, fenum: &ForeignEnumInf
 
     let enum_ftype = ForeignTypeS {
         name: TypeName::new(fenum.name.to_string(), (fenum.src_id, fenum.name.span())),
-        provides_by_module: vec![
-            format!("\"{}\"", cpp_code::cpp_header_name_for_enum(fenum)).into()
-        ],
+        provides_by_module: vec![format!("\"{}\"", enum_header).into()],
         into_from_rust: Some(ForeignConversationRule {
             rust_ty: enum_rty.to_idx(),
             intermediate: Some(ForeignConversationIntermediate {
@@ -143,6 +156,57 @@ enum {enum_name} {{"#,
     Ok(())
 }
 
+/// C enum for C only mode, the value of item is its index
+/// like in C++ code, but in C API enum is passed as `uint32_t`
+fn generate_c89_code_for_enum(
+    ctx: &mut CppContext,
+    enum_info: &ForeignEnumInfo,
+) -> std::result::Result<(), DiagnosticError> {
+    let c_path = ctx
+        .cfg
+        .output_dir
+        .join(cpp_code::c_header_name_for_enum(enum_info));
+    let mut file = FileWriteCache::new(&c_path, ctx.generated_foreign_files);
+
+    writeln!(
+        file,
+        r#"/* Automatically generated by flapigen */
+#pragma once
+
+{doc_comments}
+enum {enum_name} {{"#,
+        enum_name = enum_info.name,
+        doc_comments = cpp_code::doc_comments_to_c89_comments(&enum_info.doc_comments, true),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    for (i, item) in enum_info.items.iter().enumerate() {
+        let mut doc_comments = cpp_code::doc_comments_to_c89_comments(&item.doc_comments, false);
+        if !doc_comments.is_empty() {
+            doc_comments.push('\n');
+        }
+        writeln!(
+            file,
+            "{doc_comments}    {item_name} = {index}{separator}",
+            item_name = item.name,
+            index = i,
+            doc_comments = doc_comments,
+            // C89 does not allow comma after last enumerator
+            separator = if i == enum_info.items.len() - 1 {
+                ""
+            } else {
+                ","
+            },
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(file, "}};").expect(WRITE_TO_MEM_FAILED_MSG);
+
+    file.update_file_if_necessary()
+        .map_err(DiagnosticError::map_any_err_to_our_err)?;
+    Ok(())
+}
+
 fn generate_rust_trait_for_enum(ctx: &mut CppContext, enum_info: &ForeignEnumInfo) -> Result<()> {
     let mut arms_to_u32 = Vec::with_capacity(enum_info.items.len());
     let mut arms_from_u32 = Vec::with_capacity(enum_info.items.len());
@@ -175,3 +239,674 @@ fn generate_rust_trait_for_enum(ctx: &mut CppContext, enum_info: &ForeignEnumInf
 
     Ok(())
 }
+
+struct CppEnumField<'a> {
+    field: &'a ForeignEnumItemField,
+    rust_ty: RustType,
+    /// Type of field in C struct for output
+    c_ty: RustType,
+    /// Type of field in C++ struct
+    cpp_ty: String,
+    /// C++ expression to convert C field into `cpp_ty`
+    from_c: String,
+    /// Type of field in C struct for input and C++ expression
+    /// to convert field of C++ struct into it
+    into_c: Option<(RustType, String)>,
+    includes: Vec<SmolStr>,
+}
+
+/// Enum with data is mapped to `std::variant` (or `boost::variant`) of
+/// struct per item, to pass it via C API tagged union is used
+fn generate_enum_with_data(ctx: &mut CppContext, fenum: &ForeignEnumInfo) -> Result<()> {
+    let enum_ti: Type = parse_ty_with_given_span(&fenum.name.to_string(), fenum.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(fenum.src_id, err))?;
+    let enum_rty = ctx.conv_map.find_or_alloc_rust_type(&enum_ti, fenum.src_id);
+
+    let mut items_fields = Vec::with_capacity(fenum.items.len());
+    let mut into_c_error = None;
+    for item in &fenum.items {
+        let mut fields = Vec::with_capacity(item.fields.as_slice().len());
+        for field in item.fields.as_slice() {
+            let field = map_enum_field(ctx, fenum, field)?;
+            if field.into_c.is_none() && into_c_error.is_none() {
+                into_c_error = Some(format!("{}.{}", item.name, field.field.name));
+            }
+            fields.push(field);
+        }
+        items_fields.push(fields);
+    }
+    if let Some(field) = into_c_error.as_ref() {
+        println!(
+            "cargo:warning=enum {} can not be used as input, because of field {}",
+            fenum.name, field
+        );
+    }
+    let input_supported = into_c_error.is_none();
+    // C types for input and output are the same, if there is no types
+    // like `&str` vs `String`
+    let same_c_types = items_fields.iter().flatten().all(|f| match f.into_c {
+        Some((ref c_ty, _)) => c_ty.normalized_name == f.c_ty.normalized_name,
+        None => true,
+    });
+
+    let c_header = cpp_code::c_header_name_for_enum(fenum);
+    generate_c_tag_for_enum(ctx, fenum, &c_header);
+    let c_out_name = format!("C{}", fenum.name);
+    let c_in_name = if same_c_types {
+        c_out_name.clone()
+    } else {
+        format!("C{}In", fenum.name)
+    };
+    let mut c_items =
+        c_items_for_enum_with_data(fenum, &items_fields, &c_out_name, |f| f.c_ty.ty.clone());
+    if input_supported && !same_c_types {
+        c_items.extend(c_items_for_enum_with_data(
+            fenum,
+            &items_fields,
+            &c_in_name,
+            |f| {
+                f.into_c
+                    .as_ref()
+                    .map(|x| x.0.ty.clone())
+                    .expect("Internal error: no input type for field")
+            },
+        ));
+    }
+    super::merge_c_types(
+        ctx,
+        CItems {
+            header_name: c_header.clone().into(),
+            items: c_items,
+        },
+        super::MergeCItemsFlags::DefineAlsoRustType,
+        fenum.src_id,
+    )?;
+    let c_out_rty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&parse_ty(&c_out_name), fenum.src_id);
+    generate_rust_code_for_enum_with_data(
+        ctx,
+        fenum,
+        &items_fields,
+        &c_out_name,
+        if input_supported {
+            Some(&c_in_name)
+        } else {
+            None
+        },
+    )?;
+
+    let enum_header = if ctx.cfg.c_headers_only {
+        c_header.clone()
+    } else {
+        generate_cpp_code_for_enum_with_data(
+            ctx,
+            fenum,
+            &items_fields,
+            &c_header,
+            &c_out_name,
+            if input_supported {
+                Some(&c_in_name)
+            } else {
+                None
+            },
+        )?;
+        cpp_code::cpp_header_name_for_enum(fenum)
+    };
+
+    ctx.conv_map.add_conversation_rule(
+        enum_rty.to_idx(),
+        c_out_rty.to_idx(),
+        TypeConvEdge::new(
+            TypeConvCode::new2(
+                format!(
+                    "let mut {to_var}: {c_type} = <{c_type}>::swig_from({from_var});",
+                    to_var = TO_VAR_TEMPLATE,
+                    from_var = FROM_VAR_TEMPLATE,
+                    c_type = c_out_name,
+                ),
+                invalid_src_id_span(),
+            ),
+            None,
+        ),
+    );
+    let from_into_rust = if input_supported {
+        let c_in_rty = ctx
+            .conv_map
+            .find_or_alloc_rust_type(&parse_ty(&c_in_name), fenum.src_id);
+        ctx.conv_map.add_conversation_rule(
+            c_in_rty.to_idx(),
+            enum_rty.to_idx(),
+            TypeConvEdge::new(
+                TypeConvCode::new2(
+                    format!(
+                        "let mut {to_var}: {enum_type} = <{enum_type}>::swig_from({from_var});",
+                        to_var = TO_VAR_TEMPLATE,
+                        from_var = FROM_VAR_TEMPLATE,
+                        enum_type = fenum.name,
+                    ),
+                    invalid_src_id_span(),
+                ),
+                None,
+            ),
+        );
+        Some(ForeignConversationRule {
+            rust_ty: enum_rty.to_idx(),
+            intermediate: Some(ForeignConversationIntermediate {
+                input_to_output: false,
+                intermediate_ty: c_in_rty.to_idx(),
+                conv_code: Rc::new(TypeConvCode::new(
+                    format!("internal::to_c({})", FROM_VAR_TEMPLATE),
+                    invalid_src_id_span(),
+                )),
+            }),
+        })
+    } else {
+        None
+    };
+
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(fenum.name.to_string(), (fenum.src_id, fenum.name.span())),
+        provides_by_module: vec![format!("\"{}\"", enum_header).into()],
+        into_from_rust: Some(ForeignConversationRule {
+            rust_ty: enum_rty.to_idx(),
+            intermediate: Some(ForeignConversationIntermediate {
+                input_to_output: false,
+                intermediate_ty: c_out_rty.to_idx(),
+                conv_code: Rc::new(TypeConvCode::new(
+                    format!("internal::from_c({})", FROM_VAR_TEMPLATE),
+                    invalid_src_id_span(),
+                )),
+            }),
+        }),
+        from_into_rust,
+        name_prefix: None,
+    })?;
+    Ok(())
+}
+
+fn parse_ty(name: &str) -> Type {
+    syn::parse_str(name)
+        .unwrap_or_else(|err| panic_on_syn_error("Internal: C type for enum", name.into(), err))
+}
+
+fn map_enum_field<'a>(
+    ctx: &mut CppContext,
+    fenum: &ForeignEnumInfo,
+    field: &'a ForeignEnumItemField,
+) -> Result<CppEnumField<'a>> {
+    let field_span = (fenum.src_id, field.ty.span());
+    let rust_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&field.ty, fenum.src_id);
+    let out = map_type(ctx, &rust_ty, Direction::Outgoing, field_span)?;
+    let mut includes = out.provides_by_module.clone();
+    let (mut cpp_ty, mut from_c) = cpp_type_and_expr(&out).ok_or_else(|| {
+        DiagnosticError::new2(
+            field_span,
+            format!(
+                "C++ code to convert type '{}' is too complex to use it as field of enum",
+                rust_ty
+            ),
+        )
+    })?;
+    if cpp_ty == "RustString" {
+        // user should be able to create item of enum from C++ code,
+        // so use std::string instead of RustString
+        cpp_ty = "std::string".into();
+        from_c = format!("{}.to_std_string()", from_c);
+        includes.push("<string>".into());
+    }
+
+    let into_c = match map_type(ctx, &rust_ty, Direction::Incoming, field_span) {
+        Ok(input) => cpp_type_and_expr(&input).and_then(|(in_cpp_ty, in_expr)| {
+            let arg = if in_cpp_ty == cpp_ty {
+                FROM_VAR_TEMPLATE.to_string()
+            } else if cpp_ty == "std::string" && in_cpp_ty.ends_with("string_view") {
+                format!("{}{{{}}}", in_cpp_ty, FROM_VAR_TEMPLATE)
+            } else {
+                return None;
+            };
+            includes.extend_from_slice(&input.provides_by_module);
+            Some((
+                input.base.correspoding_rust_type.clone(),
+                in_expr.replace(FROM_VAR_TEMPLATE, &arg),
+            ))
+        }),
+        Err(err) => {
+            trace!("map_enum_field: no input for {}: {}", rust_ty, err);
+            None
+        }
+    };
+    Ok(CppEnumField {
+        field,
+        rust_ty,
+        c_ty: out.base.correspoding_rust_type.clone(),
+        cpp_ty,
+        from_c,
+        into_c,
+        includes,
+    })
+}
+
+/// C++ type and C++ expression to convert C type to it (or from it),
+/// `None` if conversation requires several statements
+fn cpp_type_and_expr(fti: &CppForeignTypeInfo) -> Option<(String, String)> {
+    match fti.cpp_converter {
+        Some(ref conv) => {
+            if conv
+                .converter
+                .params()
+                .iter()
+                .any(|p| p.as_str() != FROM_VAR_TEMPLATE)
+            {
+                return None;
+            }
+            Some((
+                conv.typename.to_string(),
+                conv.converter.as_str().to_string(),
+            ))
+        }
+        None => {
+            let name = fti.base.name.as_str();
+            let name = name
+                .strip_prefix("struct ")
+                .or_else(|| name.strip_prefix("union "))
+                .unwrap_or(name);
+            Some((name.to_string(), FROM_VAR_TEMPLATE.to_string()))
+        }
+    }
+}
+
+fn c_item_struct_name(c_enum_name: &str, item: &ForeignEnumItem) -> String {
+    format!("{}{}", c_enum_name, item.name)
+}
+
+fn c_items_for_enum_with_data<F: Fn(&CppEnumField) -> Type>(
+    fenum: &ForeignEnumInfo,
+    items_fields: &[Vec<CppEnumField>],
+    c_enum_name: &str,
+    field_type: F,
+) -> Vec<CItem> {
+    let mut c_items = Vec::with_capacity(fenum.items.len() + 2);
+    let mut union_fields = Vec::with_capacity(fenum.items.len());
+    for (item, fields) in fenum.items.iter().zip(items_fields) {
+        if fields.is_empty() {
+            continue;
+        }
+        let struct_name = Ident::new(&c_item_struct_name(c_enum_name, item), Span::call_site());
+        let names = fields.iter().map(|f| &f.field.name);
+        let types = fields.iter().map(&field_type);
+        c_items.push(CItem::Struct(parse_quote! {
+            #[repr(C)]
+            #[derive(Clone, Copy)]
+            pub struct #struct_name {
+                #(#names: #types),*
+            }
+        }));
+        let item_name = &item.name;
+        union_fields.push(quote! { #item_name: #struct_name });
+    }
+    // enum without fields at all is C-like enum
+    assert!(!union_fields.is_empty());
+    let c_enum_ident = Ident::new(c_enum_name, Span::call_site());
+    let union_name = Ident::new(&format!("{}Data", c_enum_name), Span::call_site());
+    c_items.push(CItem::Union(parse_quote! {
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        #[allow(non_snake_case)]
+        pub union #union_name {
+            #(#union_fields),*
+        }
+    }));
+    c_items.push(CItem::Struct(parse_quote! {
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        pub struct #c_enum_ident {
+            tag: u32,
+            data: #union_name,
+        }
+    }));
+    c_items
+}
+
+fn generate_c_tag_for_enum(ctx: &mut CppContext, fenum: &ForeignEnumInfo, c_header: &str) {
+    let tag_name = format!("C{}Tag", fenum.name);
+    let module_name: SmolStr = c_header.into();
+    let common_files = &mut ctx.common_files;
+    let c_header_f = file_for_module!(ctx, common_files, module_name);
+    let tag_id = format!("enum {}", tag_name);
+    if c_header_f.is_item_defined(&tag_id) {
+        return;
+    }
+    writeln!(
+        c_header_f,
+        "\n/* values of `tag` field of C{enum_name} */\nenum {tag_name} {{",
+        enum_name = fenum.name,
+        tag_name = tag_name,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for (i, item) in fenum.items.iter().enumerate() {
+        writeln!(
+            c_header_f,
+            "    {tag_name}_{item_name} = {index}{separator}",
+            tag_name = tag_name,
+            item_name = item.name,
+            index = i,
+            // C89 does not allow comma after last enumerator
+            separator = if i == fenum.items.len() - 1 { "" } else { "," },
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(c_header_f, "}};").expect(WRITE_TO_MEM_FAILED_MSG);
+    c_header_f.define_item(tag_id);
+}
+
+fn generate_rust_code_for_enum_with_data(
+    ctx: &mut CppContext,
+    fenum: &ForeignEnumInfo,
+    items_fields: &[Vec<CppEnumField>],
+    c_out_name: &str,
+    c_in_name: Option<&str>,
+) -> Result<()> {
+    let rust_enum_name = &fenum.name;
+    let c_out_ident = Ident::new(c_out_name, Span::call_site());
+    let c_out_data = Ident::new(&format!("{}Data", c_out_name), Span::call_site());
+
+    let mut arms_to_c = Vec::with_capacity(fenum.items.len());
+    for (i, (item, fields)) in fenum.items.iter().zip(items_fields).enumerate() {
+        let tag = i as u32;
+        let pattern = item.fields.rust_pattern(&item.rust_name);
+        if fields.is_empty() {
+            arms_to_c.push(quote! {
+                #pattern => #c_out_ident { tag: #tag, data: unsafe { ::std::mem::zeroed() } }
+            });
+            continue;
+        }
+        let mut conv_code = Vec::with_capacity(fields.len());
+        for f in fields {
+            let name = f.field.name.to_string();
+            let (mut deps, code) = ctx.conv_map.convert_rust_types(
+                f.rust_ty.to_idx(),
+                f.c_ty.to_idx(),
+                &name,
+                &name,
+                "#error",
+                (fenum.src_id, f.field.ty.span()),
+            )?;
+            ctx.rust_code.append(&mut deps);
+            conv_code.push(code);
+        }
+        let conv_code: TokenStream = syn::parse_str(&conv_code.concat()).unwrap_or_else(|err| {
+            panic_on_syn_error("Internal: enum field conversation", conv_code.concat(), err)
+        });
+        let struct_name = Ident::new(&c_item_struct_name(c_out_name, item), Span::call_site());
+        let item_name = &item.name;
+        let names = fields.iter().map(|f| &f.field.name);
+        arms_to_c.push(quote! {
+            #pattern => {
+                #conv_code
+                #c_out_ident {
+                    tag: #tag,
+                    data: #c_out_data { #item_name: #struct_name { #(#names),* } },
+                }
+            }
+        });
+    }
+    ctx.rust_code.push(quote! {
+        #[allow(unused_mut)]
+        impl SwigFrom<#rust_enum_name> for #c_out_ident {
+            fn swig_from(x: #rust_enum_name) -> Self {
+                match x {
+                    #(#arms_to_c),*
+                }
+            }
+        }
+    });
+
+    let c_in_name = match c_in_name {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+    let c_in_ident = Ident::new(c_in_name, Span::call_site());
+    let mut arms_from_c = Vec::with_capacity(fenum.items.len());
+    for (i, (item, fields)) in fenum.items.iter().zip(items_fields).enumerate() {
+        let tag = i as u32;
+        let pattern = item.fields.rust_pattern(&item.rust_name);
+        if fields.is_empty() {
+            arms_from_c.push(quote! { #tag => #pattern });
+            continue;
+        }
+        let mut conv_code = Vec::with_capacity(fields.len());
+        for f in fields {
+            let name = f.field.name.to_string();
+            let c_ty = &f
+                .into_c
+                .as_ref()
+                .expect("Internal error: no input type for field")
+                .0;
+            let (mut deps, code) = ctx.conv_map.convert_rust_types(
+                c_ty.to_idx(),
+                f.rust_ty.to_idx(),
+                &name,
+                &name,
+                "#error",
+                (fenum.src_id, f.field.ty.span()),
+            )?;
+            ctx.rust_code.append(&mut deps);
+            conv_code.push(code);
+        }
+        let conv_code: TokenStream = syn::parse_str(&conv_code.concat()).unwrap_or_else(|err| {
+            panic_on_syn_error("Internal: enum field conversation", conv_code.concat(), err)
+        });
+        let struct_name = Ident::new(&c_item_struct_name(c_in_name, item), Span::call_site());
+        let item_name = &item.name;
+        let names = fields.iter().map(|f| &f.field.name);
+        arms_from_c.push(quote! {
+            #tag => {
+                let #struct_name { #(#names),* } = unsafe { x.data.#item_name };
+                #conv_code
+                #pattern
+            }
+        });
+    }
+    ctx.rust_code.push(quote! {
+        #[allow(unused_mut)]
+        impl SwigFrom<#c_in_ident> for #rust_enum_name {
+            fn swig_from(x: #c_in_ident) -> Self {
+                match x.tag {
+                    #(#arms_from_c),*
+                    ,
+                    _ => panic!(concat!("{} not expected for ", stringify!(#rust_enum_name)), x.tag),
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+fn generate_cpp_code_for_enum_with_data(
+    ctx: &mut CppContext,
+    fenum: &ForeignEnumInfo,
+    items_fields: &[Vec<CppEnumField>],
+    c_header: &str,
+    c_out_name: &str,
+    c_in_name: Option<&str>,
+) -> std::result::Result<(), DiagnosticError> {
+    let c_path = ctx
+        .cfg
+        .output_dir
+        .join(cpp_code::cpp_header_name_for_enum(fenum));
+    let mut file = FileWriteCache::new(&c_path, ctx.generated_foreign_files);
+    let (variant_include, variant_type, variant_index, variant_get) = match ctx.cfg.cpp_variant {
+        CppVariant::Std17 => ("<variant>", "std::variant", "index()", "std::get"),
+        CppVariant::Boost => (
+            "<boost/variant.hpp>",
+            "boost::variant",
+            "which()",
+            "boost::get",
+        ),
+    };
+
+    let mut includes = FxHashSet::<SmolStr>::default();
+    for f in items_fields.iter().flatten() {
+        includes.extend(f.includes.iter().cloned());
+    }
+    includes.insert(format!("\"{}\"", c_header).into());
+    includes.insert(variant_include.into());
+    includes.remove(&SmolStr::from(format!(
+        "\"{}\"",
+        cpp_code::cpp_header_name_for_enum(fenum)
+    )));
+    let mut includes: Vec<_> = includes.into_iter().collect();
+    includes.sort();
+
+    writeln!(
+        file,
+        "// Automatically generated by flapigen\n#pragma once\n\n#include <cassert>\n#include <utility>"
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for inc in &includes {
+        writeln!(file, "#include {}", inc).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(file, "\nnamespace {} {{", ctx.cfg.namespace_name).expect(WRITE_TO_MEM_FAILED_MSG);
+
+    let mut item_types = Vec::with_capacity(fenum.items.len());
+    for (item, fields) in fenum.items.iter().zip(items_fields) {
+        let item_type = format!("{}{}", fenum.name, item.name);
+        let doc_comments = cpp_code::doc_comments_to_cpp_doc_comments(&item.doc_comments);
+        write!(file, "{}struct {} {{", doc_comments, item_type).expect(WRITE_TO_MEM_FAILED_MSG);
+        if fields.is_empty() {
+            writeln!(file, "}};").expect(WRITE_TO_MEM_FAILED_MSG);
+        } else {
+            writeln!(file).expect(WRITE_TO_MEM_FAILED_MSG);
+            for f in fields {
+                writeln!(file, "    {} {};", f.cpp_ty, f.field.name)
+                    .expect(WRITE_TO_MEM_FAILED_MSG);
+            }
+            writeln!(file, "}};").expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+        item_types.push(item_type);
+    }
+    let doc_comments = cpp_code::doc_comments_to_cpp_doc_comments(&fenum.doc_comments);
+    writeln!(
+        file,
+        "\n{doc_comments}using {enum_name} = {variant}<{items}>;\n\nnamespace internal {{",
+        doc_comments = doc_comments,
+        enum_name = fenum.name,
+        variant = variant_type,
+        items = item_types.join(", "),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    writeln!(
+        file,
+        "inline {enum_name} from_c(const {c_type} &x)\n{{\n    switch (x.tag) {{",
+        enum_name = fenum.name,
+        c_type = c_out_name,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for (i, ((item, fields), item_type)) in fenum
+        .items
+        .iter()
+        .zip(items_fields)
+        .zip(&item_types)
+        .enumerate()
+    {
+        let args: Vec<String> = fields
+            .iter()
+            .map(|f| {
+                f.from_c.replace(
+                    FROM_VAR_TEMPLATE,
+                    &format!("x.data.{}.{}", item.name, f.field.name),
+                )
+            })
+            .collect();
+        if i == fenum.items.len() - 1 {
+            writeln!(file, "    default:\n        assert(x.tag == {});", i)
+        } else {
+            writeln!(file, "    case {}:", i)
+        }
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(
+            file,
+            "        return {item_type}{{{args}}};",
+            item_type = item_type,
+            args = args.join(", ")
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(file, "    }}\n}}").expect(WRITE_TO_MEM_FAILED_MSG);
+
+    if let Some(c_in_name) = c_in_name {
+        writeln!(
+            file,
+            r#"/// pointers in result are valid only while `x` is alive
+inline {c_type} to_c(const {enum_name} &x)
+{{
+    {c_type} ret;
+    switch (x.{index}) {{"#,
+            enum_name = fenum.name,
+            c_type = c_in_name,
+            index = variant_index,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        for (i, ((item, fields), item_type)) in fenum
+            .items
+            .iter()
+            .zip(items_fields)
+            .zip(&item_types)
+            .enumerate()
+        {
+            if i == fenum.items.len() - 1 {
+                writeln!(
+                    file,
+                    "    default: {{\n        assert(x.{} == {});",
+                    variant_index, i
+                )
+            } else {
+                writeln!(file, "    case {}: {{", i)
+            }
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+            writeln!(file, "        ret.tag = {};", i).expect(WRITE_TO_MEM_FAILED_MSG);
+            if !fields.is_empty() {
+                writeln!(
+                    file,
+                    "        const auto &v = {get}<{item_type}>(x);",
+                    get = variant_get,
+                    item_type = item_type
+                )
+                .expect(WRITE_TO_MEM_FAILED_MSG);
+            }
+            for f in fields {
+                let (_, into_c) = f
+                    .into_c
+                    .as_ref()
+                    .expect("Internal error: no input type for field");
+                writeln!(
+                    file,
+                    "        ret.data.{item_name}.{field} = {expr};",
+                    item_name = item.name,
+                    field = f.field.name,
+                    expr = into_c.replace(FROM_VAR_TEMPLATE, &format!("v.{}", f.field.name)),
+                )
+                .expect(WRITE_TO_MEM_FAILED_MSG);
+            }
+            writeln!(file, "        break;\n    }}").expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+        writeln!(file, "    }}\n    return ret;\n}}").expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(
+        file,
+        "}} // namespace internal\n}} // namespace {}",
+        ctx.cfg.namespace_name
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    let mut cnt = file.take_content();
+    extend_foreign_enum(fenum, &mut cnt, ctx.enum_ext_handlers)?;
+    file.replace_content(cnt);
+    file.update_file_if_necessary()
+        .map_err(DiagnosticError::map_any_err_to_our_err)?;
+    Ok(())
+}
@@ -11,7 +11,7 @@ use crate::{
         CppForeignTypeInfo,
     },
     error::{invalid_src_id_span, panic_on_syn_error, DiagnosticError, Result},
-    file_cache::FileWriteCache,
+    file_cache::{FileOperationsRegistrator, FileWriteCache, NoNeedFsOpsRegistration},
     namegen::new_unique_name,
     typemap::{
         ast::{parse_ty_with_given_span, DisplayToTokens, TypeName},
@@ -84,12 +84,18 @@ pub(in crate::cpp) fn generate_interface(
     params.push(tmp_name);
     let conv_code = TypeConvCode::with_params(conv_code, invalid_src_id_span(), params);
 
+    let (cpp_type_name, cpp_type_header) = if interface.is_closure {
+        (cpp_function_type(&f_methods[0]), "<functional>")
+    } else {
+        (format!("std::unique_ptr<{}>", interface.name), "<memory>")
+    };
     ctx.conv_map.alloc_foreign_type(ForeignTypeS {
-        name: TypeName::new(
-            format!("std::unique_ptr<{}>", interface.name),
-            interface.src_id_span(),
-        ),
-        provides_by_module: vec![cpp_abs_class_header, "<memory>".into(), "<utility>".into()],
+        name: TypeName::new(cpp_type_name, interface.src_id_span()),
+        provides_by_module: vec![
+            cpp_abs_class_header,
+            cpp_type_header.into(),
+            "<utility>".into(),
+        ],
         into_from_rust: None,
         from_into_rust: Some(ForeignConversationRule {
             rust_ty: boxed_trait_rust_ty.to_idx(),
@@ -312,7 +318,7 @@ impl Drop for {struct_with_funcs} {{
     Ok(())
 }
 
-fn find_suitable_ftypes_for_interace_methods(
+pub(crate) fn find_suitable_ftypes_for_interace_methods(
     ctx: &mut CppContext,
     interace: &ForeignInterface,
 ) -> Result<Vec<CppForeignMethodSignature>> {
@@ -372,33 +378,71 @@ fn cpp_code_generate_interface(
     let c_path = ctx.cfg.output_dir.join(&c_interface_struct_header);
     let mut file_c = FileWriteCache::new(&c_path, ctx.generated_foreign_files);
     let cpp_path = ctx.cfg.output_dir.join(cpp_interface_header(interface));
-    let mut file_cpp = FileWriteCache::new(&cpp_path, ctx.generated_foreign_files);
+    let cpp_fs_reg: &mut dyn FileOperationsRegistrator = if ctx.cfg.c_headers_only {
+        &mut NoNeedFsOpsRegistration
+    } else {
+        ctx.generated_foreign_files
+    };
+    let mut file_cpp = FileWriteCache::new(&cpp_path, cpp_fs_reg);
     let interface_comments = cpp_code::doc_comments_to_c_comments(&interface.doc_comments, true);
 
-    writeln!(
-        file_c,
-        r#"// Automatically generated by flapigen
+    if ctx.cfg.c_headers_only {
+        let mut opaque_typedefs = String::new();
+        for opaque in cpp_code::c_list_used_opaque_types(f_methods) {
+            opaque_typedefs.push('\n');
+            opaque_typedefs.push_str(&cpp_code::c_opaque_typedef(&opaque));
+        }
+        writeln!(
+            file_c,
+            r#"/* Automatically generated by flapigen */
+#pragma once
+
+/* for (u)intX_t types */
+#include <stdint.h>
+{includes}{opaque_typedefs}
+{doc_comments}
+struct C_{interface_name} {{
+    void *opaque;
+    /** called by Rust side when callback not need anymore */
+    void (*C_{interface_name}_deref)(void *opaque);"#,
+            interface_name = interface.name,
+            includes = cpp_code::c_only_includes(req_includes),
+            opaque_typedefs = opaque_typedefs,
+            doc_comments = cpp_code::doc_comments_to_c89_comments(&interface.doc_comments, true),
+        )
+    } else {
+        writeln!(
+            file_c,
+            r#"// Automatically generated by flapigen
 #pragma once
 {doc_comments}
 struct C_{interface_name} {{
     void *opaque;
     //! call by Rust side when callback not need anymore
     void (*C_{interface_name}_deref)(void *opaque);"#,
-        interface_name = interface.name,
-        doc_comments = interface_comments
-    )
+            interface_name = interface.name,
+            doc_comments = interface_comments
+        )
+    }
     .expect(WRITE_TO_MEM_FAILED_MSG);
 
+    // closure is passed as `std::function`, and callback as subclass of abstract class
+    let cpp_self_type = if interface.is_closure {
+        cpp_function_type(&f_methods[0])
+    } else {
+        interface.name.to_string()
+    };
     let mut cpp_virtual_methods = String::new();
     let mut cpp_static_reroute_methods = format!(
         r#"
     static void c_{interface_name}_deref(void *opaque)
     {{
-        auto p = static_cast<{interface_name} *>(opaque);
+        auto p = static_cast<{cpp_self_type} *>(opaque);
         delete p;
     }}
 "#,
-        interface_name = interface.name
+        interface_name = interface.name,
+        cpp_self_type = cpp_self_type,
     );
     let mut cpp_fill_c_interface_struct = format!(
         r#"
@@ -426,14 +470,14 @@ struct C_{interface_name} {{
                     .replace(FROM_VAR_TEMPLATE, &ret_name);
                 (out_conv.typename.clone(), conv_code)
             } else {
-                (c_ret_type.clone(), String::new())
+                (c_ret_type.clone(), ret_name.to_string())
             };
         writeln!(
             file_c,
             r#"{doc_comments}
     {c_ret_type} (*{method_name})({single_args_with_types}void *opaque);"#,
             method_name = method.name,
-            doc_comments = cpp_code::doc_comments_to_c_comments(&method.doc_comments, false),
+            doc_comments = cpp_code::doc_comments_for_c_header(ctx, &method.doc_comments, false),
             single_args_with_types = cpp_code::c_generate_args_with_types(
                 f_method,
                 method.arg_names_without_self(),
@@ -457,6 +501,11 @@ struct C_{interface_name} {{
 
         let (conv_args_code, call_input_args) =
             cpp_code::convert_args(f_method, &mut known_names, method.arg_names_without_self())?;
+        let call = if interface.is_closure {
+            format!("(*{})", interface_ptr)
+        } else {
+            format!("{}->{}", interface_ptr, method.name)
+        };
 
         write!(
             &mut cpp_static_reroute_methods,
@@ -464,7 +513,7 @@ struct C_{interface_name} {{
     static {c_ret_type} c_{method_name}({single_args_with_types}void *{opaque})
     {{
         assert({opaque} != nullptr);
-        auto {p} = static_cast<{interface_name} *>({opaque});
+        auto {p} = static_cast<{cpp_self_type} *>({opaque});
 {conv_args_code}"#,
             c_ret_type = c_ret_type,
             method_name = method.name,
@@ -475,7 +524,7 @@ struct C_{interface_name} {{
             ),
             opaque = opaque_name,
             p = interface_ptr,
-            interface_name = interface.name,
+            cpp_self_type = cpp_self_type,
             conv_args_code = conv_args_code,
         )
         .expect(WRITE_TO_MEM_FAILED_MSG);
@@ -484,10 +533,9 @@ struct C_{interface_name} {{
             writeln!(
                 &mut cpp_static_reroute_methods,
                 r#"
-        {p}->{method_name}({input_args});
+        {call}({input_args});
     }}"#,
-                p = interface_ptr,
-                method_name = method.name,
+                call = call,
                 input_args = call_input_args,
             )
             .expect(WRITE_TO_MEM_FAILED_MSG);
@@ -495,14 +543,13 @@ struct C_{interface_name} {{
             writeln!(
                 &mut cpp_static_reroute_methods,
                 r#"
-        auto {ret} = {p}->{method_name}({input_args});
+        auto {ret} = {call}({input_args});
         return {cpp_out_conv};
     }}"#,
                 ret = ret_name,
-                method_name = method.name,
+                call = call,
                 input_args = call_input_args,
                 cpp_out_conv = cpp_out_conv,
-                p = interface_ptr,
             )
             .expect(WRITE_TO_MEM_FAILED_MSG);
         }
@@ -526,13 +573,43 @@ struct C_{interface_name} {{
         writeln!(&mut includes, r#"#include {}"#, inc).expect(WRITE_TO_MEM_FAILED_MSG);
     }
 
+    let (std_include, class_head) = if interface.is_closure {
+        (
+            "#include <functional> //for std::function\n#include <utility>",
+            format!(
+                r#"    static C_{interface_name} to_c_interface({cpp_self_type} f)
+    {{
+        assert(f != nullptr);
+        C_{interface_name} ret;
+        ret.opaque = new {cpp_self_type}(std::move(f));"#,
+                interface_name = interface.name,
+                cpp_self_type = cpp_self_type,
+            ),
+        )
+    } else {
+        (
+            "#include <memory> //for std::unique_ptr",
+            format!(
+                r#"    virtual ~{interface_name}() noexcept {{}}
+{virtual_methods}
+
+    static C_{interface_name} to_c_interface(std::unique_ptr<{interface_name}> p)
+    {{
+        assert(p != nullptr);
+        C_{interface_name} ret;
+        ret.opaque = p.release();"#,
+                interface_name = interface.name,
+                virtual_methods = cpp_virtual_methods,
+            ),
+        )
+    };
     writeln!(
         file_cpp,
         r##"// Automatically generated by flapigen
 #pragma once
 
 #include <cassert>
-#include <memory> //for std::unique_ptr
+{std_include}
 
 {includes}
 #include "{c_interface_struct_header}"
@@ -541,14 +618,7 @@ namespace {namespace_name} {{
 {doc_comments}
 class {interface_name} {{
 public:
-    virtual ~{interface_name}() noexcept {{}}
-{virtual_methods}
-
-    static C_{interface_name} to_c_interface(std::unique_ptr<{interface_name}> p)
-    {{
-        assert(p != nullptr);
-        C_{interface_name} ret;
-        ret.opaque = p.release();
+{class_head}
 {cpp_fill_c_interface_struct}
         return ret;
     }}
@@ -557,10 +627,11 @@ private:
 }};
 }} // namespace {namespace_name}"##,
         interface_name = interface.name,
+        std_include = std_include,
         includes = includes,
         doc_comments = interface_comments,
         c_interface_struct_header = c_interface_struct_header,
-        virtual_methods = cpp_virtual_methods,
+        class_head = class_head,
         static_reroute_methods = cpp_static_reroute_methods,
         cpp_fill_c_interface_struct = cpp_fill_c_interface_struct,
         namespace_name = ctx.cfg.namespace_name,
@@ -570,6 +641,9 @@ private:
     file_c
         .update_file_if_necessary()
         .map_err(DiagnosticError::map_any_err_to_our_err)?;
+    if ctx.cfg.c_headers_only {
+        return Ok(());
+    }
     file_cpp
         .update_file_if_necessary()
         .map_err(DiagnosticError::map_any_err_to_our_err)?;
@@ -577,7 +651,25 @@ private:
     Ok(())
 }
 
-fn c_interface_header(interface: &ForeignInterface) -> String {
+/// `std::function` type, that accepts C++ types of closure arguments
+fn cpp_function_type(f_method: &CppForeignMethodSignature) -> String {
+    let cpp_type = |f_type_info: &CppForeignTypeInfo| match f_type_info.cpp_converter {
+        Some(ref conv) => conv.typename.clone(),
+        None => f_type_info.as_ref().name.clone(),
+    };
+    let args = f_method
+        .input
+        .iter()
+        .map(|x| cpp_type(x).to_string())
+        .collect::<Vec<_>>();
+    format!(
+        "std::function<{}({})>",
+        cpp_type(&f_method.output),
+        args.join(", ")
+    )
+}
+
+pub(in crate::cpp) fn c_interface_header(interface: &ForeignInterface) -> String {
     format!("c_{}.h", interface.name)
 }
 
@@ -16,7 +16,7 @@ use crate::{
     TypeMap,
 };
 
-pub(in crate::cpp) fn map_type(
+pub(crate) fn map_type(
     ctx: &mut CppContext,
     arg_ty: &RustType,
     direction: Direction,
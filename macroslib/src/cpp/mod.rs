@@ -2,12 +2,26 @@ macro_rules! file_for_module {
     ($ctx:ident, $common_files:ident, $module_name:ident) => {{
         let output_dir = &$ctx.cfg.output_dir;
         let target_pointer_width = $ctx.target_pointer_width;
+        let c_headers_only = $ctx.cfg.c_headers_only;
         let generated_foreign_files = &mut $ctx.generated_foreign_files;
         $common_files
             .entry($module_name.clone())
             .or_insert_with(|| {
                 let c_header_path = output_dir.join($module_name.as_str());
                 let mut c_header_f = FileWriteCache::new(&c_header_path, *generated_foreign_files);
+                if c_headers_only {
+                    write!(
+                        &mut c_header_f,
+                        r##"/* Automatically generated by flapigen */
+#pragma once
+
+/* for (u)intX_t types */
+#include <stdint.h>
+"##
+                    )
+                    .expect("write to memory failed, no free mem?");
+                    return c_header_f;
+                }
                 write!(
                     &mut c_header_f,
                     r##"// Automatically generated by flapigen
@@ -29,6 +43,7 @@ static_assert(sizeof(uintptr_t) == sizeof(uint8_t) * {sizeof_usize},
     }};
 }
 
+mod async_fn;
 mod cpp_code;
 mod fclass;
 mod fenum;
@@ -36,7 +51,17 @@ mod finterface;
 mod map_class_self_type;
 mod map_type;
 
-use std::{io::Write, mem, path::PathBuf, rc::Rc};
+pub(crate) use self::{
+    cpp_code::c_class_type_name, fclass::find_suitable_foreign_types_for_methods,
+    finterface::find_suitable_ftypes_for_interace_methods, map_type::map_type,
+};
+
+use std::{
+    io::Write,
+    mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
 use log::{debug, trace};
 use proc_macro2::TokenStream;
@@ -46,7 +71,7 @@ use strum::IntoEnumIterator;
 use syn::spanned::Spanned;
 
 use crate::{
-    cpp::{map_class_self_type::register_typemap_for_self_type, map_type::map_type},
+    cpp::map_class_self_type::register_typemap_for_self_type,
     error::{invalid_src_id_span, DiagnosticError, Result},
     extension::{ClassExtHandlers, EnumExtHandlers, ExtHandlers, MethodExtHandlers},
     file_cache::FileWriteCache,
@@ -61,22 +86,22 @@ use crate::{
         CItem, CItems, ForeignTypeInfo, TypeConvCode, TypeMapConvRuleInfo,
     },
     types::{ForeignClassInfo, ForeignMethod, ItemToExpand, MethodAccess, MethodVariant},
-    CppConfig, CppOptional, CppStrView, CppVariant, LanguageGenerator, SourceCode, TypeMap,
-    SMART_PTR_COPY_TRAIT, WRITE_TO_MEM_FAILED_MSG,
+    CConfig, CatchPanics, CppConfig, CppOptional, CppStrView, CppVariant, LanguageGenerator,
+    SourceCode, TypeMap, SMART_PTR_COPY_TRAIT, WRITE_TO_MEM_FAILED_MSG,
 };
 
 #[derive(Debug)]
-struct CppConverter {
-    typename: SmolStr,
+pub(crate) struct CppConverter {
+    pub(crate) typename: SmolStr,
     converter: Rc<TypeConvCode>,
 }
 
 #[derive(Debug)]
-struct CppForeignTypeInfo {
-    base: ForeignTypeInfo,
+pub(crate) struct CppForeignTypeInfo {
+    pub(crate) base: ForeignTypeInfo,
     provides_by_module: Vec<SmolStr>,
     input_to_output: bool,
-    pub(in crate::cpp) cpp_converter: Option<CppConverter>,
+    pub(crate) cpp_converter: Option<CppConverter>,
 }
 
 impl ForeignTypeInfoT for CppForeignTypeInfo {
@@ -190,9 +215,9 @@ impl AsRef<ForeignTypeInfo> for CppForeignTypeInfo {
     }
 }
 
-struct CppForeignMethodSignature {
-    output: CppForeignTypeInfo,
-    input: Vec<CppForeignTypeInfo>,
+pub(crate) struct CppForeignMethodSignature {
+    pub(crate) output: CppForeignTypeInfo,
+    pub(crate) input: Vec<CppForeignTypeInfo>,
 }
 
 impl From<ForeignTypeInfo> for CppForeignTypeInfo {
@@ -227,6 +252,7 @@ struct MethodContext<'a> {
     decl_func_args: &'a str,
     real_output_typename: &'a str,
     ret_name: &'a str,
+    catch_panics: Option<CatchPanics>,
 }
 
 impl CppConfig {
@@ -303,9 +329,9 @@ impl CppConfig {
     }
 }
 
-struct CppContext<'a> {
+pub(crate) struct CppContext<'a> {
     cfg: &'a CppConfig,
-    conv_map: &'a mut TypeMap,
+    pub(crate) conv_map: &'a mut TypeMap,
     target_pointer_width: usize,
     rust_code: &'a mut Vec<TokenStream>,
     common_files: &'a mut FxHashMap<SmolStr, FileWriteCache>,
@@ -325,9 +351,97 @@ impl LanguageGenerator for CppConfig {
         remove_not_generated_files: bool,
         ext_handlers: ExtHandlers,
     ) -> Result<Vec<TokenStream>> {
+        let mut generated_foreign_files = FxHashSet::default();
+        self.expand_items_with_hook(
+            conv_map,
+            target_pointer_width,
+            code,
+            items,
+            remove_not_generated_files,
+            ext_handlers,
+            &mut generated_foreign_files,
+            |_, _| Ok(()),
+        )
+    }
+}
+
+impl LanguageGenerator for CConfig {
+    fn expand_items(
+        &self,
+        conv_map: &mut TypeMap,
+        target_pointer_width: usize,
+        code: &[SourceCode],
+        items: Vec<ItemToExpand>,
+        remove_not_generated_files: bool,
+        ext_handlers: ExtHandlers,
+    ) -> Result<Vec<TokenStream>> {
+        let umbrella_header_path = self.output_dir.join(format!("{}.h", self.library_name));
+        let mut generated_c_files = FxHashSet::default();
+        generated_c_files.insert(umbrella_header_path.clone());
+        let mut headers = Vec::with_capacity(items.len());
+
+        let mut cpp_cfg =
+            CppConfig::new(self.output_dir.clone(), self.library_name.clone()).c_headers_only();
+        cpp_cfg.catch_panics = self.catch_panics;
+        cpp_cfg.async_executor.clone_from(&self.async_executor);
+        let ret = cpp_cfg.expand_items_with_hook(
+            conv_map,
+            target_pointer_width,
+            code,
+            items,
+            remove_not_generated_files,
+            ext_handlers,
+            &mut generated_c_files,
+            |_, item| {
+                headers.push(match item {
+                    ItemToExpand::Class(ref fclass) => cpp_code::c_header_name(fclass),
+                    ItemToExpand::Enum(ref fenum) => cpp_code::c_header_name_for_enum(fenum),
+                    ItemToExpand::Interface(ref finterface) => {
+                        finterface::c_interface_header(finterface)
+                    }
+                });
+                Ok(())
+            },
+        )?;
+
+        let mut header_f = FileWriteCache::new(&umbrella_header_path, &mut generated_c_files);
+        header_f
+            .write_all(b"/* Automatically generated by flapigen */\n#pragma once\n\n")
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+        for header in &headers {
+            writeln!(&mut header_f, "#include \"{}\"", header).expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+        header_f.update_file_if_necessary().map_err(|err| {
+            DiagnosticError::map_any_err_to_our_err(format!(
+                "write to {} failed: {}",
+                umbrella_header_path.display(),
+                err
+            ))
+        })?;
+        Ok(ret)
+    }
+}
+
+impl CppConfig {
+    /// Generate C and C++ code, `on_item` is called after each item,
+    /// so languages that wrap C API can generate own code on top of it
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn expand_items_with_hook<F>(
+        &self,
+        conv_map: &mut TypeMap,
+        target_pointer_width: usize,
+        code: &[SourceCode],
+        items: Vec<ItemToExpand>,
+        remove_not_generated_files: bool,
+        ext_handlers: ExtHandlers,
+        generated_foreign_files: &mut FxHashSet<PathBuf>,
+        mut on_item: F,
+    ) -> Result<Vec<TokenStream>>
+    where
+        F: FnMut(&mut CppContext, &ItemToExpand) -> Result<()>,
+    {
         let mut ret = Vec::with_capacity(items.len());
         let mut files = FxHashMap::<SmolStr, FileWriteCache>::default();
-        let mut generated_foreign_files = FxHashSet::default();
         {
             let mut ctx = CppContext {
                 cfg: self,
@@ -335,12 +449,15 @@ impl LanguageGenerator for CppConfig {
                 target_pointer_width,
                 rust_code: &mut ret,
                 common_files: &mut files,
-                generated_foreign_files: &mut generated_foreign_files,
+                generated_foreign_files,
                 class_ext_handlers: ext_handlers.class_ext_handlers,
                 method_ext_handlers: ext_handlers.method_ext_handlers,
                 enum_ext_handlers: ext_handlers.enum_ext_handlers,
             };
             init(&mut ctx, code)?;
+            if ItemToExpand::has_async_methods(&items) {
+                async_fn::generate_runtime(&mut ctx)?;
+            }
             for item in &items {
                 if let ItemToExpand::Class(ref fclass) = item {
                     self.register_class(ctx.conv_map, fclass)?;
@@ -348,17 +465,23 @@ impl LanguageGenerator for CppConfig {
             }
             for item in items {
                 match item {
-                    ItemToExpand::Class(fclass) => fclass::generate(&mut ctx, &fclass)?,
-                    ItemToExpand::Enum(fenum) => fenum::generate_enum(&mut ctx, &fenum)?,
-                    ItemToExpand::Interface(finterface) => {
-                        finterface::generate_interface(&mut ctx, &finterface)?
+                    ItemToExpand::Class(ref fclass) => fclass::generate(&mut ctx, fclass)?,
+                    ItemToExpand::Enum(ref fenum) => fenum::generate_enum(&mut ctx, fenum)?,
+                    ItemToExpand::Interface(ref finterface) => {
+                        finterface::generate_interface(&mut ctx, finterface)?
                     }
                 }
+                on_item(&mut ctx, &item)?;
             }
         }
 
-        for (module_name, c_header_f) in files {
+        for (module_name, mut c_header_f) in files {
             let c_header_path = self.output_dir.join(module_name.as_str());
+            if self.c_headers_only {
+                let cnt = String::from_utf8(c_header_f.take_content())
+                    .map_err(DiagnosticError::map_any_err_to_our_err)?;
+                c_header_f.replace_content(cpp_code::strip_cpp_only_code(&cnt).into_bytes());
+            }
             c_header_f.update_file_if_necessary().map_err(|err| {
                 DiagnosticError::map_any_err_to_our_err(format!(
                     "write to {} failed: {}",
@@ -384,7 +507,61 @@ impl LanguageGenerator for CppConfig {
     }
 }
 
-fn c_func_name(class: &ForeignClassInfo, method: &ForeignMethod) -> String {
+/// Write C header that includes all other C headers, for languages
+/// that use C API without C++ wrappers
+pub(crate) fn write_c_umbrella_header(
+    umbrella_header_path: &Path,
+    generated_c_files: &mut FxHashSet<PathBuf>,
+    class_names: &[String],
+    interface_names: &[String],
+) -> Result<()> {
+    let mut common_c_headers: Vec<String> = generated_c_files
+        .iter()
+        .filter(|path| {
+            path.extension().map(|ext| ext == "h").unwrap_or(false)
+                && path.as_path() != umbrella_header_path
+        })
+        .filter_map(|path| path.file_name().and_then(|x| x.to_str()))
+        .filter(|name| !name.starts_with("c_"))
+        .map(str::to_string)
+        .collect();
+    common_c_headers.sort();
+
+    let mut header_f = FileWriteCache::new(umbrella_header_path, generated_c_files);
+    write!(
+        &mut header_f,
+        r#"// Automatically generated by flapigen
+#pragma once
+
+"#
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for header in &common_c_headers {
+        writeln!(&mut header_f, "#include \"{}\"", header).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(&mut header_f).expect(WRITE_TO_MEM_FAILED_MSG);
+    for class_name in class_names {
+        writeln!(
+            &mut header_f,
+            "typedef struct {opaque} {opaque};",
+            opaque = c_class_type_name(class_name)
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(&mut header_f).expect(WRITE_TO_MEM_FAILED_MSG);
+    for name in interface_names.iter().chain(class_names.iter()) {
+        writeln!(&mut header_f, "#include \"c_{}.h\"", name).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    header_f.update_file_if_necessary().map_err(|err| {
+        DiagnosticError::map_any_err_to_our_err(format!(
+            "write to {} failed: {}",
+            umbrella_header_path.display(),
+            err
+        ))
+    })
+}
+
+pub(crate) fn c_func_name(class: &ForeignClassInfo, method: &ForeignMethod) -> String {
     do_c_func_name(class, method.access, &method.short_name())
 }
 
@@ -0,0 +1,27 @@
+/* Automatically generated by flapigen */
+#pragma once
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * Handle of Rust future, started by async method.
+ * Completion callback passed to method is called at most once in thread
+ * of executor, `free_opaque` is called exactly once, after completion
+ * or after future is dropped because of cancel
+ */
+typedef struct RustAsyncTask RustAsyncTask;
+
+/**
+ * Drop Rust future without completion, if it is not completed yet,
+ * completion callback may be still called if it is running at the moment
+ */
+void rust_async_task_cancel(const RustAsyncTask *task);
+
+/** Free handle, this doesn't cancel future, `task` may be NULL */
+void rust_async_task_free(RustAsyncTask *task);
+
+#ifdef __cplusplus
+}
+#endif
@@ -0,0 +1,45 @@
+// Automatically generated by flapigen
+#pragma once
+
+#include "rust_async.h"
+
+namespace RUST_SWIG_USER_NAMESPACE {
+/**
+ * Owner of `RustAsyncTask`, returned by async methods
+ * with completion callback. Destructor doesn't cancel future
+ */
+class AsyncTask final {
+public:
+    explicit AsyncTask(RustAsyncTask *task) noexcept
+        : task_(task)
+    {
+    }
+    AsyncTask(const AsyncTask &) = delete;
+    AsyncTask &operator=(const AsyncTask &) = delete;
+    AsyncTask(AsyncTask &&o) noexcept
+        : task_(o.task_)
+    {
+        o.task_ = nullptr;
+    }
+    AsyncTask &operator=(AsyncTask &&o) noexcept
+    {
+        if (this != &o) {
+            rust_async_task_free(task_);
+            task_ = o.task_;
+            o.task_ = nullptr;
+        }
+        return *this;
+    }
+    ~AsyncTask() noexcept { rust_async_task_free(task_); }
+    /// Drop Rust future, see `rust_async_task_cancel`
+    void cancel() noexcept
+    {
+        if (task_ != nullptr) {
+            rust_async_task_cancel(task_);
+        }
+    }
+
+private:
+    RustAsyncTask *task_;
+};
+} // namespace RUST_SWIG_USER_NAMESPACE
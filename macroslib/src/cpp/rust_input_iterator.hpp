@@ -0,0 +1,54 @@
+#pragma once
+
+#include <cstddef> //ptrdiff_t
+#include <iterator>
+#include <utility> //std::declval
+
+/**
+ * Input iterator over class generated for Rust iterator,
+ * `Iter::next` returns `Optional`, empty one marks the end of iteration.
+ * It allows to use such class in range-based `for`.
+ * `Iter::next` can throw exception, if panics are caught.
+ */
+template <typename Iter, typename Optional> class RustInputIterator final {
+public:
+    using iterator_category = std::input_iterator_tag;
+    using value_type = typename Optional::value_type;
+    using difference_type = ptrdiff_t;
+    using pointer = value_type *;
+    using reference = value_type &;
+
+    RustInputIterator() noexcept
+        : iter(nullptr)
+    {
+    }
+    explicit RustInputIterator(Iter &it) noexcept(noexcept(std::declval<Iter &>().next()))
+        : iter(&it)
+    {
+        next();
+    }
+
+    reference operator*() noexcept { return *this->cur; }
+    pointer operator->() noexcept { return &*this->cur; }
+
+    RustInputIterator &operator++() noexcept(noexcept(std::declval<Iter &>().next()))
+    {
+        next();
+        return *this;
+    }
+
+    bool operator==(const RustInputIterator &o) const noexcept { return this->iter == o.iter; }
+    bool operator!=(const RustInputIterator &o) const noexcept { return !operator==(o); }
+
+private:
+    void next() noexcept(noexcept(std::declval<Iter &>().next()))
+    {
+        this->cur = this->iter->next();
+        if (!this->cur) {
+            this->iter = nullptr;
+        }
+    }
+
+    Iter *iter;
+    Optional cur;
+};
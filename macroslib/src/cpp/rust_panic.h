@@ -0,0 +1,43 @@
+/* Automatically generated by flapigen */
+#pragma once
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * Called when Rust code panics, `msg` is UTF-8 panic message,
+ * it is valid only during call. After callback returns, Rust function
+ * returns zero-initialized value. Callback must not throw C++ exceptions.
+ */
+typedef void (*RustPanicCallback)(const char *msg, void *opaque);
+
+/**
+ * Set callback for panics in Rust code, `opaque` is passed to it as is.
+ * Callback is optional, C++ wrappers throw exception in case of panic anyway.
+ */
+void flapigen_set_panic_callback(RustPanicCallback callback, void *opaque);
+
+/**
+ * UTF-8 message of panic, if the last called Rust function in the current thread
+ * panicked, or NULL otherwise. It is valid until the next call of Rust function
+ * in the same thread.
+ */
+const char *flapigen_last_panic_message(void);
+
+#ifdef __cplusplus
+}
+
+#include <stdexcept>
+
+/**
+ * Throw `std::runtime_error` with panic message,
+ * if the last called Rust function in the current thread panicked.
+ */
+inline void flapigen_check_panic()
+{
+    if (const char *msg = flapigen_last_panic_message()) {
+        throw std::runtime_error(msg);
+    }
+}
+#endif
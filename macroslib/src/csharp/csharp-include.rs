@@ -0,0 +1,737 @@
+mod swig_foreign_types_map {}
+
+#[allow(dead_code)]
+mod internal_aliases {
+    /// C# `string` passed with `CharSet.Unicode`: null terminated UTF-16
+    pub type CSharpUtf16Str = *const u16;
+}
+
+foreign_typemap!(
+    (r_type) ();
+    (f_type) "void";
+);
+
+foreign_typemap!(
+    (r_type) i8;
+    (f_type) "sbyte";
+);
+
+foreign_typemap!(
+    (r_type) u8;
+    (f_type) "byte";
+);
+
+foreign_typemap!(
+    (r_type) i16;
+    (f_type) "short";
+);
+
+foreign_typemap!(
+    (r_type) u16;
+    (f_type) "ushort";
+);
+
+foreign_typemap!(
+    (r_type) i32;
+    (f_type) "int";
+);
+
+foreign_typemap!(
+    (r_type) u32;
+    (f_type) "uint";
+);
+
+foreign_typemap!(
+    (r_type) i64;
+    (f_type) "long";
+);
+
+foreign_typemap!(
+    (r_type) u64;
+    (f_type) "ulong";
+);
+
+foreign_typemap!(
+    (r_type) f32;
+    (f_type) "float";
+);
+
+foreign_typemap!(
+    (r_type) f64;
+    (f_type) "double";
+);
+
+foreign_typemap!(
+    (r_type) * mut ::std::os::raw::c_void;
+    (f_type) "IntPtr";
+);
+
+foreign_typemap!(
+    (r_type) * const ::std::os::raw::c_void;
+    (f_type, unique_prefix = "/*const*/") "/*const*/IntPtr";
+);
+
+foreign_typemap!(
+    ($p:r_type) usize => u64 {
+        $out = $p as u64;
+    };
+    ($p:r_type) usize <= u64 {
+        $out = <usize as ::std::convert::TryFrom<u64>>::try_from($p)
+            .expect("invalid ulong, in ulong => usize conversation");
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) isize => i64 {
+        $out = $p as i64;
+    };
+    ($p:r_type) isize <= i64 {
+        $out = <isize as ::std::convert::TryFrom<i64>>::try_from($p)
+            .expect("invalid long, in long => isize conversation");
+    };
+);
+
+foreign_typemap!(
+    ($pin:r_type) bool => u8 {
+        $out = if $pin { 1 } else { 0 };
+    };
+    ($pin:f_type) => "bool" "($pin != 0)";
+    ($pin:r_type) bool <= u8 {
+        $out = $pin != 0;
+    };
+    ($pin:f_type) <= "bool" "($pin ? (byte)1 : (byte)0)";
+);
+
+#[allow(dead_code)]
+pub trait SwigForeignClass {
+    fn c_class_name() -> *const ::std::os::raw::c_char;
+    fn box_object(x: Self) -> *mut ::std::os::raw::c_void;
+    fn unbox_object(p: *mut ::std::os::raw::c_void) -> Self;
+}
+
+#[allow(dead_code)]
+pub trait SwigForeignEnum {
+    fn as_u32(&self) -> u32;
+    fn from_u32(_: u32) -> Self;
+}
+
+#[allow(unused_macros)]
+macro_rules! swig_c_str {
+    ($lit:expr) => {
+        concat!($lit, "\0").as_ptr() as *const ::std::os::raw::c_char
+    };
+}
+
+#[allow(dead_code)]
+#[swig_code = "let mut {to_var}: {to_var_type} = {from_var}.swig_into();"]
+trait SwigInto<T> {
+    fn swig_into(self) -> T;
+}
+
+#[allow(dead_code)]
+#[swig_code = "let mut {to_var}: {to_var_type} = <{to_var_type}>::swig_from({from_var});"]
+trait SwigFrom<T> {
+    fn swig_from(_: T) -> Self;
+}
+
+impl<T: SwigForeignEnum> SwigFrom<T> for u32 {
+    fn swig_from(x: T) -> u32 {
+        x.as_u32()
+    }
+}
+
+impl<T: SwigForeignEnum> SwigFrom<u32> for T {
+    fn swig_from(x: u32) -> T {
+        T::from_u32(x)
+    }
+}
+
+foreign_typemap!(
+    ($p:r_type) <T> Arc<Mutex<T>> => &Mutex<T> {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &Mutex<T> => MutexGuard<T> {
+        $out = $p.lock().unwrap();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> MutexGuard<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> MutexGuard<T> => &mut T {
+        $out = &mut $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Rc<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &Rc<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &RefCell<T> => Ref<T> {
+        $out = $p.borrow();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &RefCell<T> => RefMut<T> {
+        $out = $p.borrow_mut();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Ref<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> RefMut<T> => &mut T {
+        $out = &mut $p;
+    };
+);
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CRustStrView {
+    data: *const u8,
+    len: usize,
+}
+
+#[allow(dead_code)]
+impl CRustStrView {
+    fn from_str(s: &str) -> CRustStrView {
+        CRustStrView {
+            data: s.as_ptr(),
+            len: s.len(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+pub struct CRustString {
+    data: *const u8,
+    len: usize,
+    capacity: usize,
+}
+
+#[allow(dead_code)]
+impl CRustString {
+    pub fn from_string(s: String) -> CRustString {
+        let mut s = ::std::mem::ManuallyDrop::new(s);
+        CRustString {
+            data: s.as_mut_ptr(),
+            len: s.len(),
+            capacity: s.capacity(),
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn csharp_rust_string_free(x: CRustString) {
+    let s = unsafe { String::from_raw_parts(x.data as *mut u8, x.len, x.capacity) };
+    drop(s);
+}
+
+#[allow(dead_code)]
+fn swig_utf16_str_to_string(p: internal_aliases::CSharpUtf16Str) -> String {
+    assert!(!p.is_null());
+    let len = (0..).take_while(|i| unsafe { *p.add(*i) } != 0).count();
+    let utf16 = unsafe { ::std::slice::from_raw_parts(p, len) };
+    String::from_utf16(utf16).expect("invalid UTF-16 in string from C#")
+}
+
+foreign_typemap!(
+    foreign_code!(module = "RustString.cs";
+                  r##"
+namespace $RUST_SWIG_USER_NAMESPACE
+{
+    [StructLayout(LayoutKind.Sequential)]
+    internal struct RustStrView
+    {
+        internal IntPtr data;
+        internal UIntPtr len;
+
+        internal string ToManaged()
+        {
+            int n = checked((int)len.ToUInt64());
+            if (n == 0) {
+                return string.Empty;
+            }
+            byte[] buf = new byte[n];
+            Marshal.Copy(data, buf, 0, n);
+            return Encoding.UTF8.GetString(buf);
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    internal struct RustString
+    {
+        internal IntPtr data;
+        internal UIntPtr len;
+        internal UIntPtr capacity;
+
+        internal string ToManaged()
+        {
+            try {
+                RustStrView view;
+                view.data = data;
+                view.len = len;
+                return view.ToManaged();
+            } finally {
+                csharp_rust_string_free(this);
+            }
+        }
+
+        [DllImport("$RUST_SWIG_NATIVE_LIB", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void csharp_rust_string_free(RustString s);
+    }
+}
+"##);
+    (r_type) CRustStrView;
+    (f_type) "RustStrView";
+);
+
+foreign_typemap!(
+    (r_type) CRustString;
+    (f_type) "RustString";
+);
+
+foreign_typemap!(
+    ($p:r_type) String <= internal_aliases::CSharpUtf16Str {
+        $out = swig_utf16_str_to_string($p);
+    };
+    ($p:f_type) <= "string";
+    ($p:r_type) String => CRustString {
+        $out = CRustString::from_string($p);
+    };
+    ($p:f_type) => "string" "$p.ToManaged()";
+);
+
+foreign_typemap!(
+    ($p:r_type) &str => CRustStrView {
+        $out = CRustStrView::from_str($p);
+    };
+    ($p:f_type, unique_prefix = "/*str*/") => "/*str*/string" "$p.ToManaged()";
+);
+
+foreign_typemap!(
+    ($p:r_type) String => &str {
+        $out = $p.as_str();
+    };
+);
+
+#[allow(dead_code)]
+#[repr(C)]
+pub struct CRustOption<T> {
+    val: ::std::mem::MaybeUninit<T>,
+    is_some: u8,
+}
+
+foreign_typemap!(
+    generic_alias!(CSharpOpt = swig_concat_idents!(RustOption, swig_i_type!(T)));
+    foreign_code!(module = "RustOption.cs";
+                  r##"
+namespace $RUST_SWIG_USER_NAMESPACE
+{
+    [StructLayout(LayoutKind.Sequential)]
+    internal struct CSharpOpt!()
+    {
+        internal swig_f_type!(T) val;
+        internal byte is_some;
+    }
+}
+"##);
+    (r_type) <T: SwigTypeIsReprC> CRustOption<T>;
+    (f_type) "CSharpOpt!()";
+);
+
+foreign_typemap!(
+    ($p:r_type) Option<String> => CRustOption<CRustString> {
+        $out = match $p {
+            Some(s) => CRustOption {
+                val: ::std::mem::MaybeUninit::new(CRustString::from_string(s)),
+                is_some: 1,
+            },
+            None => CRustOption {
+                val: ::std::mem::MaybeUninit::zeroed(),
+                is_some: 0,
+            },
+        };
+    };
+    ($p:f_type, unique_prefix = "/*opt*/") => "/*opt*/string" "($p.is_some != 0) ? $p.val.ToManaged() : null";
+    ($p:r_type) Option<String> <= internal_aliases::CSharpUtf16Str {
+        $out = if !$p.is_null() {
+            Some(swig_utf16_str_to_string($p))
+        } else {
+            None
+        };
+    };
+    ($p:f_type, unique_prefix = "/*opt*/") <= "/*opt*/string";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T: SwigForeignClass> Option<T> => swig_i_type!(T) {
+        $out = match $p {
+            Some(x) => {
+                swig_from_rust_to_i_type!(T, x, ret)
+                ret
+            }
+            None => ::std::ptr::null_mut(),
+        };
+    };
+    ($p:f_type, unique_prefix = "/*opt*/") => "/*opt*/swig_f_type!(T)"
+        "($p != IntPtr.Zero) ? swig_foreign_from_i_type!(T, $p) : null";
+    ($p:r_type) <T: SwigForeignClass> Option<T> <= swig_i_type!(T) {
+        $out = if !$p.is_null() {
+            swig_from_i_type_to_rust!(T, $p, ret)
+            Some(ret)
+        } else {
+            None
+        };
+    };
+    ($p:f_type, unique_prefix = "/*opt*/") <= "/*opt*/swig_f_type!(T)"
+        "($p != null) ? swig_foreign_to_i_type!(T, $p) : IntPtr.Zero";
+);
+
+foreign_typemap!(
+    generic_alias!(CSharpOpt = swig_concat_idents!(RustOption, swig_i_type!(T)));
+    ($p:r_type) <T> Option<T> => CRustOption<swig_i_type!(T)> {
+        $out = match $p {
+            Some(x) => {
+                swig_from_rust_to_i_type!(T, x, val)
+                CRustOption {
+                    val: ::std::mem::MaybeUninit::new(val),
+                    is_some: 1,
+                }
+            }
+            None => CRustOption {
+                val: ::std::mem::MaybeUninit::zeroed(),
+                is_some: 0,
+            },
+        };
+    };
+    ($p:f_type) => "swig_f_type!(T)?"
+        "($p.is_some != 0) ? (swig_f_type!(T)?)swig_foreign_from_i_type!(T, $p.val) : null";
+    ($p:r_type) <T> Option<T> <= CRustOption<swig_i_type!(T)> {
+        $out = if $p.is_some != 0 {
+            let val = unsafe { $p.val.assume_init() };
+            swig_from_i_type_to_rust!(T, val, ret)
+            Some(ret)
+        } else {
+            None
+        };
+    };
+    ($p:f_type) <= "swig_f_type!(T)?" r#"            $out = default(CSharpOpt!());
+            if ($p.HasValue) {
+                $out.val = swig_foreign_to_i_type!(T, $p.Value);
+                $out.is_some = 1;
+            }
+"#;
+);
+
+#[allow(dead_code)]
+#[repr(C)]
+pub struct CRustResult<T> {
+    ok: ::std::mem::MaybeUninit<T>,
+    err: ::std::mem::MaybeUninit<CRustString>,
+    is_ok: u8,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+pub struct CRustObjectResult {
+    ok: *mut ::std::os::raw::c_void,
+    err: ::std::mem::MaybeUninit<CRustString>,
+    is_ok: u8,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+pub struct CRustVoidResult {
+    err: ::std::mem::MaybeUninit<CRustString>,
+    is_ok: u8,
+}
+
+foreign_typemap!(
+    foreign_code!(module = "RustResult.cs";
+                  r##"
+namespace $RUST_SWIG_USER_NAMESPACE
+{
+    /// Error returned by Rust code as Result::Err
+    public class RustException : Exception
+    {
+        public RustException(string message) : base(message)
+        {
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    internal struct RustVoidResult
+    {
+        internal RustString err;
+        internal byte is_ok;
+
+        internal void Check()
+        {
+            if (is_ok == 0) {
+                throw new RustException(err.ToManaged());
+            }
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    internal struct RustObjectResult
+    {
+        internal IntPtr ok;
+        internal RustString err;
+        internal byte is_ok;
+    }
+}
+"##);
+    (r_type) CRustVoidResult;
+    (f_type) "RustVoidResult";
+);
+
+foreign_typemap!(
+    (r_type) CRustObjectResult;
+    (f_type) "RustObjectResult";
+);
+
+foreign_typemap!(
+    ($p:r_type) Result<(), String> => CRustVoidResult {
+        $out = match $p {
+            Ok(()) => CRustVoidResult {
+                err: ::std::mem::MaybeUninit::zeroed(),
+                is_ok: 1,
+            },
+            Err(err) => CRustVoidResult {
+                err: ::std::mem::MaybeUninit::new(CRustString::from_string(err)),
+                is_ok: 0,
+            },
+        };
+    };
+    ($p:f_type, unique_prefix = "/*Result*/") => "/*Result*/void" "$p.Check()";
+);
+
+foreign_typemap!(
+    generic_alias!(CSharpResult = swig_concat_idents!(RustResult, swig_i_type!(T)));
+    foreign_code!(module = "RustResult.cs";
+                  r##"
+namespace $RUST_SWIG_USER_NAMESPACE
+{
+    [StructLayout(LayoutKind.Sequential)]
+    internal struct CSharpResult!()
+    {
+        internal swig_f_type!(T) ok;
+        internal RustString err;
+        internal byte is_ok;
+    }
+}
+"##);
+    (r_type) <T: SwigTypeIsReprC> CRustResult<T>;
+    (f_type) "CSharpResult!()";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T: SwigForeignClass> Result<T, String> => CRustObjectResult {
+        $out = match $p {
+            Ok(x) => {
+                swig_from_rust_to_i_type!(T, x, ok)
+                CRustObjectResult {
+                    ok,
+                    err: ::std::mem::MaybeUninit::zeroed(),
+                    is_ok: 1,
+                }
+            }
+            Err(err) => CRustObjectResult {
+                ok: ::std::ptr::null_mut(),
+                err: ::std::mem::MaybeUninit::new(CRustString::from_string(err)),
+                is_ok: 0,
+            },
+        };
+    };
+    ($p:f_type, unique_prefix = "/*Result*/") => "/*Result*/swig_f_type!(T)"
+        "($p.is_ok != 0) ? swig_foreign_from_i_type!(T, $p.ok) : throw new RustException($p.err.ToManaged())";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Result<T, String> => CRustResult<swig_i_type!(T)> {
+        $out = match $p {
+            Ok(x) => {
+                swig_from_rust_to_i_type!(T, x, ok)
+                CRustResult {
+                    ok: ::std::mem::MaybeUninit::new(ok),
+                    err: ::std::mem::MaybeUninit::zeroed(),
+                    is_ok: 1,
+                }
+            }
+            Err(err) => CRustResult {
+                ok: ::std::mem::MaybeUninit::zeroed(),
+                err: ::std::mem::MaybeUninit::new(CRustString::from_string(err)),
+                is_ok: 0,
+            },
+        };
+    };
+    ($p:f_type, unique_prefix = "/*Result*/") => "/*Result*/swig_f_type!(T)"
+        "($p.is_ok != 0) ? swig_foreign_from_i_type!(T, $p.ok) : throw new RustException($p.err.ToManaged())";
+);
+
+/// Types which can be copied between `Vec` and C# array as is
+#[allow(dead_code)]
+pub trait SwigCSharpPrimitive: Copy {}
+
+impl SwigCSharpPrimitive for i8 {}
+impl SwigCSharpPrimitive for u8 {}
+impl SwigCSharpPrimitive for i16 {}
+impl SwigCSharpPrimitive for u16 {}
+impl SwigCSharpPrimitive for i32 {}
+impl SwigCSharpPrimitive for u32 {}
+impl SwigCSharpPrimitive for i64 {}
+impl SwigCSharpPrimitive for u64 {}
+impl SwigCSharpPrimitive for f32 {}
+impl SwigCSharpPrimitive for f64 {}
+
+/// Memory block allocated by Rust, `capacity` and `align` describe its layout
+#[allow(dead_code)]
+#[repr(C)]
+pub struct CRustVec {
+    data: *mut u8,
+    len: usize,
+    capacity: usize,
+    align: usize,
+}
+
+#[allow(dead_code)]
+impl CRustVec {
+    pub fn from_vec<T: SwigCSharpPrimitive>(v: Vec<T>) -> CRustVec {
+        let mut v = ::std::mem::ManuallyDrop::new(v);
+        CRustVec {
+            data: v.as_mut_ptr() as *mut u8,
+            len: v.len(),
+            capacity: v.capacity() * ::std::mem::size_of::<T>(),
+            align: ::std::mem::align_of::<T>(),
+        }
+    }
+    pub fn into_vec<T: SwigCSharpPrimitive>(self) -> Vec<T> {
+        let ret = if self.len == 0 {
+            Vec::new()
+        } else {
+            assert!(
+                self.align >= ::std::mem::align_of::<T>()
+                    && self.len * ::std::mem::size_of::<T>() <= self.capacity
+            );
+            unsafe { ::std::slice::from_raw_parts(self.data as *const T, self.len) }.to_vec()
+        };
+        csharp_rust_vec_free(self);
+        ret
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn csharp_rust_vec_alloc(capacity: usize, align: usize) -> *mut u8 {
+    if capacity == 0 {
+        return ::std::ptr::null_mut();
+    }
+    let layout = ::std::alloc::Layout::from_size_align(capacity, align)
+        .expect("invalid layout of array from C#");
+    let p = unsafe { ::std::alloc::alloc(layout) };
+    if p.is_null() {
+        ::std::alloc::handle_alloc_error(layout);
+    }
+    p
+}
+
+#[no_mangle]
+pub extern "C" fn csharp_rust_vec_free(x: CRustVec) {
+    if x.capacity != 0 {
+        let layout = ::std::alloc::Layout::from_size_align(x.capacity, x.align)
+            .expect("invalid layout of Rust vector");
+        unsafe { ::std::alloc::dealloc(x.data, layout) };
+    }
+}
+
+foreign_typemap!(
+    foreign_code!(module = "RustVec.cs";
+                  r##"
+namespace $RUST_SWIG_USER_NAMESPACE
+{
+    [StructLayout(LayoutKind.Sequential)]
+    internal struct RustVec
+    {
+        internal IntPtr data;
+        internal UIntPtr len;
+        internal UIntPtr capacity;
+        internal UIntPtr align;
+
+        internal T[] ToManaged<T>() where T : struct
+        {
+            try {
+                T[] ret = new T[checked((int)len.ToUInt64())];
+                int nbytes = Buffer.ByteLength(ret);
+                if (nbytes != 0) {
+                    byte[] buf = new byte[nbytes];
+                    Marshal.Copy(data, buf, 0, nbytes);
+                    Buffer.BlockCopy(buf, 0, ret, 0, nbytes);
+                }
+                return ret;
+            } finally {
+                csharp_rust_vec_free(this);
+            }
+        }
+
+        internal static RustVec FromManaged<T>(T[] arr) where T : struct
+        {
+            int nbytes = Buffer.ByteLength(arr);
+            int elem_size = Marshal.SizeOf(typeof(T));
+            RustVec ret;
+            ret.data = csharp_rust_vec_alloc((UIntPtr)nbytes, (UIntPtr)elem_size);
+            ret.len = (UIntPtr)arr.Length;
+            ret.capacity = (UIntPtr)nbytes;
+            ret.align = (UIntPtr)elem_size;
+            if (nbytes != 0) {
+                byte[] buf = new byte[nbytes];
+                Buffer.BlockCopy(arr, 0, buf, 0, nbytes);
+                Marshal.Copy(buf, 0, ret.data, nbytes);
+            }
+            return ret;
+        }
+
+        [DllImport("$RUST_SWIG_NATIVE_LIB", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr csharp_rust_vec_alloc(UIntPtr capacity, UIntPtr align);
+        [DllImport("$RUST_SWIG_NATIVE_LIB", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void csharp_rust_vec_free(RustVec v);
+    }
+}
+"##);
+    (r_type) CRustVec;
+    (f_type) "RustVec";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T: SwigTypeIsReprC> Vec<T> => CRustVec {
+        $out = CRustVec::from_vec($p);
+    };
+    ($p:f_type) => "swig_f_type!(T)[]" "$p.ToManaged<swig_f_type!(T)>()";
+    ($p:r_type) <T: SwigTypeIsReprC> Vec<T> <= CRustVec {
+        $out = $p.into_vec();
+    };
+    ($p:f_type) <= "swig_f_type!(T)[]" "RustVec.FromManaged($p)";
+);
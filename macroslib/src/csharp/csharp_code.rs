@@ -0,0 +1,273 @@
+use std::{borrow::Cow, fmt::Write};
+
+use heck::CamelCase;
+use rustc_hash::FxHashSet;
+use smol_str::SmolStr;
+
+use crate::{
+    csharp::{CSharpConverter, CSharpForeignMethodSignature},
+    error::DiagnosticError,
+    namegen::new_unique_name,
+    typemap::{TypeConvCodeSubstParam, FROM_VAR_TEMPLATE, TO_VAR_TEMPLATE, TO_VAR_TYPE_TEMPLATE},
+    types::{ForeignClassInfo, ForeignMethod, MethodAccess},
+    CSharpConfig, WRITE_TO_MEM_FAILED_MSG,
+};
+
+/// Words that can not be used as identifiers in C# without `@` prefix
+static CSHARP_KEYWORDS: [&str; 77] = [
+    "abstract",
+    "as",
+    "base",
+    "bool",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "checked",
+    "class",
+    "const",
+    "continue",
+    "decimal",
+    "default",
+    "delegate",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "event",
+    "explicit",
+    "extern",
+    "false",
+    "finally",
+    "fixed",
+    "float",
+    "for",
+    "foreach",
+    "goto",
+    "if",
+    "implicit",
+    "in",
+    "int",
+    "interface",
+    "internal",
+    "is",
+    "lock",
+    "long",
+    "namespace",
+    "new",
+    "null",
+    "object",
+    "operator",
+    "out",
+    "override",
+    "params",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "ref",
+    "return",
+    "sbyte",
+    "sealed",
+    "short",
+    "sizeof",
+    "stackalloc",
+    "static",
+    "string",
+    "struct",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "uint",
+    "ulong",
+    "unchecked",
+    "unsafe",
+    "ushort",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "while",
+];
+
+pub(in crate::csharp) fn subst_config_vars(cfg: &CSharpConfig, code: &str) -> String {
+    code.replace("$RUST_SWIG_USER_NAMESPACE", &cfg.namespace_name)
+        .replace("$RUST_SWIG_NATIVE_LIB", &cfg.native_lib_name)
+}
+
+pub(in crate::csharp) fn doc_comments_to_cs_comments(
+    doc_comments: &[String],
+    indent: &str,
+) -> String {
+    if doc_comments.is_empty() {
+        return String::new();
+    }
+    let mut comments = format!("{}/// <summary>\n", indent);
+    for comment in doc_comments {
+        let comment = comment
+            .trim()
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        writeln!(&mut comments, "{}/// {}", indent, comment).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(&mut comments, "{}/// </summary>", indent).expect(WRITE_TO_MEM_FAILED_MSG);
+    comments
+}
+
+pub(in crate::csharp) fn cs_file_name(type_name: &dyn std::fmt::Display) -> String {
+    format!("{}.cs", type_name)
+}
+
+pub(in crate::csharp) fn c_func_name(class: &ForeignClassInfo, method: &ForeignMethod) -> String {
+    format!(
+        "{access}{class_name}_{func}",
+        access = match method.access {
+            MethodAccess::Private => "private_",
+            MethodAccess::Protected => "protected_",
+            MethodAccess::Public => "",
+        },
+        class_name = class.name,
+        func = method.short_name(),
+    )
+}
+
+pub(in crate::csharp) fn cs_method_name(rust_name: &str) -> String {
+    rust_name.to_camel_case()
+}
+
+pub(in crate::csharp) fn cs_ident(name: &str) -> Cow<'_, str> {
+    if CSHARP_KEYWORDS.contains(&name) {
+        format!("@{}", name).into()
+    } else {
+        name.into()
+    }
+}
+
+pub(in crate::csharp) fn dll_import(
+    cfg: &CSharpConfig,
+    ret_type: &str,
+    func_name: &str,
+    args_with_types: &str,
+) -> String {
+    format!(
+        r#"
+        [DllImport("{lib}", CallingConvention = CallingConvention.Cdecl, CharSet = CharSet.Unicode)]
+        private static extern {ret_type} {func_name}({args_with_types});
+"#,
+        lib = cfg.native_lib_name,
+        ret_type = ret_type,
+        func_name = func_name,
+        args_with_types = args_with_types,
+    )
+}
+
+/// Arguments with types as they passed through P/Invoke
+pub(in crate::csharp) fn c_args_with_types<'a, NI: Iterator<Item = &'a str>>(
+    f_method: &CSharpForeignMethodSignature,
+    arg_name_iter: NI,
+) -> String {
+    let mut ret = String::new();
+    for (f_type_info, arg_name) in f_method.input.iter().zip(arg_name_iter) {
+        if !ret.is_empty() {
+            ret.push_str(", ");
+        }
+        write!(&mut ret, "{} {}", f_type_info.base.name, cs_ident(arg_name))
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    ret
+}
+
+/// Arguments with types as they visible to user of C# code
+pub(in crate::csharp) fn cs_args_with_types<'a, NI: Iterator<Item = &'a str>>(
+    f_method: &CSharpForeignMethodSignature,
+    arg_name_iter: NI,
+) -> String {
+    let mut ret = String::new();
+    for (f_type_info, arg_name) in f_method.input.iter().zip(arg_name_iter) {
+        if !ret.is_empty() {
+            ret.push_str(", ");
+        }
+        write!(
+            &mut ret,
+            "{} {}",
+            f_type_info.cs_typename(),
+            cs_ident(arg_name)
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    ret
+}
+
+/// Apply `conv` to `var_name`, returns statements that should be executed before
+/// and expression with result
+pub(in crate::csharp) fn convert_var(
+    conv: &CSharpConverter,
+    var_name: &str,
+    to_type_name: &str,
+    known_names: &mut FxHashSet<SmolStr>,
+) -> Result<(String, String), DiagnosticError> {
+    let to_var = if conv.converter.has_param(TO_VAR_TYPE_TEMPLATE)
+        || conv.converter.has_param(TO_VAR_TEMPLATE)
+    {
+        let to_var = new_unique_name(known_names, &format!("{}_conv", var_name));
+        known_names.insert(to_var.clone());
+        Some(to_var)
+    } else {
+        None
+    };
+    let conv_code =
+        conv.converter
+            .generate_code_with_subst_func(|param_name| match param_name {
+                TypeConvCodeSubstParam::Name(name) => {
+                    if name == FROM_VAR_TEMPLATE {
+                        Some(Cow::Borrowed(var_name))
+                    } else if name == TO_VAR_TYPE_TEMPLATE {
+                        Some(format!("{} {}", to_type_name, to_var.as_ref().unwrap()).into())
+                    } else if name == TO_VAR_TEMPLATE {
+                        Some(Cow::Borrowed(to_var.as_ref().unwrap()))
+                    } else {
+                        None
+                    }
+                }
+                TypeConvCodeSubstParam::Tmp(name_template) => {
+                    let tmp_name = new_unique_name(known_names, name_template);
+                    let tmp_name_ret = tmp_name.to_string().into();
+                    known_names.insert(tmp_name);
+                    Some(tmp_name_ret)
+                }
+            })?;
+    if conv.converter.has_param(TO_VAR_TYPE_TEMPLATE) {
+        Ok((conv_code, to_var.unwrap().to_string()))
+    } else {
+        Ok((String::new(), conv_code))
+    }
+}
+
+/// Convert arguments from types visible to user to types for P/Invoke call
+pub(in crate::csharp) fn convert_args<'a, NI: Iterator<Item = &'a str>>(
+    f_method: &CSharpForeignMethodSignature,
+    known_names: &mut FxHashSet<SmolStr>,
+    arg_name_iter: NI,
+) -> Result<(String, String), DiagnosticError> {
+    let mut conv_deps = String::new();
+    let mut converted_args = String::new();
+    for (i, (f_type_info, arg_name)) in f_method.input.iter().zip(arg_name_iter).enumerate() {
+        if i > 0 {
+            converted_args.push_str(", ");
+        }
+        let arg_name = cs_ident(arg_name);
+        if let Some(conv) = f_type_info.cs_converter.as_ref() {
+            let (deps, expr) = convert_var(conv, &arg_name, &f_type_info.base.name, known_names)?;
+            conv_deps.push_str(&deps);
+            converted_args.push_str(&expr);
+        } else {
+            converted_args.push_str(&arg_name);
+        }
+    }
+    Ok((conv_deps, converted_args))
+}
@@ -0,0 +1,729 @@
+use std::{borrow::Cow, fmt::Write};
+
+use log::debug;
+use petgraph::Direction;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use rustc_hash::FxHashSet;
+use smol_str::SmolStr;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    csharp::{
+        csharp_code::{self, cs_ident},
+        map_class_self_type::safe_handle_name,
+        map_type::map_type,
+        CSharpContext, CSharpForeignMethodSignature, CSharpForeignTypeInfo,
+    },
+    error::{panic_on_syn_error, DiagnosticError, Result},
+    extension::extend_foreign_class,
+    file_cache::FileWriteCache,
+    namegen::new_unique_name,
+    typemap::{
+        ast::{list_lifetimes, strip_lifetimes},
+        ty::RustType,
+        utils::{
+            convert_to_heap_pointer, create_suitable_types_for_constructor_and_self,
+            foreign_from_rust_convert_method_output, foreign_to_rust_convert_method_inputs,
+            unpack_from_heap_pointer,
+        },
+        ForeignTypeInfo, TypeMap, TO_VAR_TEMPLATE,
+    },
+    types::{ForeignClassInfo, ForeignMethod, MethodAccess, MethodVariant, SelfTypeVariant},
+    KNOWN_CLASS_DERIVES, WRITE_TO_MEM_FAILED_MSG,
+};
+
+struct MethodContext<'a> {
+    class: &'a ForeignClassInfo,
+    method: &'a ForeignMethod,
+    f_method: &'a CSharpForeignMethodSignature,
+    c_func_name: &'a str,
+    decl_func_args: &'a str,
+    real_output_typename: &'a str,
+    ret_name: &'a str,
+}
+
+pub(in crate::csharp) fn generate(ctx: &mut CSharpContext, class: &ForeignClassInfo) -> Result<()> {
+    debug!(
+        "generate: begin for {}, this_type_for_method {:?}",
+        class.name, class.self_desc
+    );
+    let has_methods = class
+        .methods
+        .iter()
+        .any(|m| matches!(m.variant, MethodVariant::Method(_)));
+    let has_constructor = class
+        .methods
+        .iter()
+        .any(|m| m.variant == MethodVariant::Constructor);
+
+    if has_methods && !has_constructor {
+        return Err(DiagnosticError::new(
+            class.src_id,
+            class.span(),
+            format!(
+                "namespace {}, class {}: has methods, but no constructor\n
+May be you need to use `private constructor = empty;` syntax?",
+                ctx.cfg.namespace_name, class.name
+            ),
+        ));
+    }
+
+    let m_sigs = find_suitable_foreign_types_for_methods(ctx, class)?;
+    do_generate(ctx, class, &m_sigs)
+}
+
+fn do_generate(
+    ctx: &mut CSharpContext,
+    class: &ForeignClassInfo,
+    methods_sign: &[CSharpForeignMethodSignature],
+) -> Result<()> {
+    let cs_path = ctx
+        .cfg
+        .output_dir
+        .join(csharp_code::cs_file_name(&class.name));
+    let mut cs_file = FileWriteCache::new(&cs_path, ctx.generated_foreign_files);
+
+    let static_only = class
+        .methods
+        .iter()
+        .all(|x| x.variant == MethodVariant::StaticMethod);
+    let safe_handle = safe_handle_name(class);
+
+    let dummy_ty = parse_type! { () };
+    let dummy_rust_ty = ctx.conv_map.find_or_alloc_rust_type_no_src_id(&dummy_ty);
+
+    let (this_type_for_method, code_box_this) =
+        if let Some(this_type) = class.self_desc.as_ref().map(|x| &x.constructor_ret_type) {
+            let this_type = ctx.conv_map.ty_to_rust_type(this_type);
+
+            let (this_type_for_method, code_box_this) =
+                convert_to_heap_pointer(ctx.conv_map, &this_type, "this");
+            let lifetimes = list_lifetimes(&this_type.ty);
+            let unpack_code = unpack_from_heap_pointer(&this_type, TO_VAR_TEMPLATE, true);
+            let class_name = &this_type.ty;
+            let unpack_code = unpack_code.replace(TO_VAR_TEMPLATE, "p");
+            let unpack_code: TokenStream = syn::parse_str(&unpack_code).unwrap_or_else(|err| {
+                panic_on_syn_error("internal/C# foreign class unpack code", unpack_code, err)
+            });
+            let this_type_for_method_ty = this_type_for_method.to_type_without_lifetimes();
+            ctx.rust_code.push(quote! {
+                impl<#(#lifetimes),*> SwigForeignClass for #class_name {
+                    fn c_class_name() -> *const ::std::os::raw::c_char {
+                        swig_c_str!(stringify!(#class_name))
+                    }
+                    fn box_object(this: Self) -> *mut ::std::os::raw::c_void {
+                        #code_box_this
+                        this as *mut ::std::os::raw::c_void
+                    }
+                    fn unbox_object(p: *mut ::std::os::raw::c_void) -> Self {
+                        let p = p as *mut #this_type_for_method_ty;
+                        #unpack_code
+                        p
+                    }
+                }
+            });
+            (this_type_for_method, code_box_this)
+        } else {
+            (dummy_rust_ty, TokenStream::new())
+        };
+    let no_this_info = || {
+        DiagnosticError::new(
+            class.src_id,
+            class.span(),
+            format!(
+                "Class {} has methods, but there is no constructor\n
+May be you need to use `private constructor = empty;` syntax?",
+                class.name,
+            ),
+        )
+    };
+
+    let mut need_destructor = false;
+    let mut cs_methods = String::new();
+    let mut dll_imports = String::new();
+
+    for (method, f_method) in class.methods.iter().zip(methods_sign) {
+        let c_func_name = csharp_code::c_func_name(class, method);
+        let method_access = match method.access {
+            MethodAccess::Private => "private",
+            MethodAccess::Public => "public",
+            MethodAccess::Protected if static_only => "internal",
+            MethodAccess::Protected => "protected",
+        };
+        let doc_comments =
+            csharp_code::doc_comments_to_cs_comments(&method.doc_comments, "        ");
+
+        let mut known_names: FxHashSet<SmolStr> = method
+            .arg_names_without_self()
+            .map(|x| cs_ident(x).into())
+            .collect();
+        let ret_name = new_unique_name(&known_names, "ret");
+        known_names.insert(ret_name.clone());
+
+        let cs_args_with_types =
+            csharp_code::cs_args_with_types(f_method, method.arg_names_without_self());
+        let c_args_with_types =
+            csharp_code::c_args_with_types(f_method, method.arg_names_without_self());
+        let (conv_args_code, args_for_c) =
+            csharp_code::convert_args(f_method, &mut known_names, method.arg_names_without_self())?;
+
+        let real_output_typename: Cow<str> = match method.fn_decl.output {
+            syn::ReturnType::Default => Cow::Borrowed("()"),
+            syn::ReturnType::Type(_, ref t) => {
+                let mut ty: syn::Type = (**t).clone();
+                strip_lifetimes(&mut ty);
+                ty.into_token_stream().to_string().into()
+            }
+        };
+
+        let mut rust_args_with_types = String::new();
+        for (f_type_info, arg_name) in f_method.input.iter().zip(method.arg_names_without_self()) {
+            write!(
+                &mut rust_args_with_types,
+                "{}: {}, ",
+                arg_name,
+                f_type_info.base.correspoding_rust_type.typename(),
+            )
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+        let method_ctx = MethodContext {
+            class,
+            method,
+            f_method,
+            c_func_name: &c_func_name,
+            decl_func_args: &rust_args_with_types,
+            real_output_typename: &real_output_typename,
+            ret_name: &ret_name,
+        };
+
+        let c_ret_type = f_method.output.base.name.as_str();
+        let cs_ret_type = f_method.output.cs_typename();
+        let ret_code = if c_ret_type == "void" {
+            String::new()
+        } else if let Some(cs_conv) = f_method.output.cs_converter.as_ref() {
+            let (conv_deps, conv_expr) =
+                csharp_code::convert_var(cs_conv, &ret_name, cs_ret_type, &mut known_names)?;
+            if cs_ret_type == "void" {
+                // for example `Result<(), String>`: only check for error
+                format!("{}            {};\n", conv_deps, conv_expr)
+            } else {
+                format!("{}            return {};\n", conv_deps, conv_expr)
+            }
+        } else {
+            format!("            return {};\n", ret_name)
+        };
+        let call_prefix = if c_ret_type == "void" {
+            String::new()
+        } else {
+            format!("{} {} = ", c_ret_type, ret_name)
+        };
+        let method_name = csharp_code::cs_method_name(&method.short_name());
+
+        match method.variant {
+            MethodVariant::StaticMethod => {
+                write!(
+                    &mut cs_methods,
+                    r#"
+{doc_comments}        {access} static {cs_ret_type} {method_name}({cs_args_with_types})
+        {{
+{conv_args_code}            {call_prefix}{c_func_name}({args_for_c});
+{ret_code}        }}
+"#,
+                    doc_comments = doc_comments,
+                    access = method_access,
+                    cs_ret_type = cs_ret_type,
+                    method_name = method_name,
+                    cs_args_with_types = cs_args_with_types,
+                    conv_args_code = conv_args_code,
+                    call_prefix = call_prefix,
+                    c_func_name = c_func_name,
+                    args_for_c = args_for_c,
+                    ret_code = ret_code,
+                )
+                .expect(WRITE_TO_MEM_FAILED_MSG);
+                dll_imports.push_str(&csharp_code::dll_import(
+                    ctx.cfg,
+                    c_ret_type,
+                    &c_func_name,
+                    &c_args_with_types,
+                ));
+                ctx.rust_code
+                    .append(&mut generate_static_method(ctx.conv_map, &method_ctx)?);
+            }
+            MethodVariant::Method(ref self_variant) => {
+                write!(
+                    &mut cs_methods,
+                    r#"
+{doc_comments}        {access} {cs_ret_type} {method_name}({cs_args_with_types})
+        {{
+{conv_args_code}            {call_prefix}{c_func_name}(this.handle{comma}{args_for_c});
+{ret_code}        }}
+"#,
+                    doc_comments = doc_comments,
+                    access = method_access,
+                    cs_ret_type = cs_ret_type,
+                    method_name = method_name,
+                    cs_args_with_types = cs_args_with_types,
+                    conv_args_code = conv_args_code,
+                    call_prefix = call_prefix,
+                    c_func_name = c_func_name,
+                    comma = if args_for_c.is_empty() { "" } else { ", " },
+                    args_for_c = args_for_c,
+                    ret_code = ret_code,
+                )
+                .expect(WRITE_TO_MEM_FAILED_MSG);
+                dll_imports.push_str(&csharp_code::dll_import(
+                    ctx.cfg,
+                    c_ret_type,
+                    &c_func_name,
+                    &format!(
+                        "{} self{}{}",
+                        safe_handle,
+                        if c_args_with_types.is_empty() {
+                            ""
+                        } else {
+                            ", "
+                        },
+                        c_args_with_types
+                    ),
+                ));
+                ctx.rust_code.append(&mut generate_method(
+                    ctx.conv_map,
+                    &method_ctx,
+                    class,
+                    *self_variant,
+                    &this_type_for_method,
+                )?);
+            }
+            MethodVariant::Constructor => {
+                need_destructor = true;
+                if !method.is_dummy_constructor() {
+                    write!(
+                        &mut cs_methods,
+                        r#"
+{doc_comments}        {access} {class_name}({cs_args_with_types})
+        {{
+{conv_args_code}            this.handle = {c_func_name}({args_for_c});
+        }}
+"#,
+                        doc_comments = doc_comments,
+                        access = method_access,
+                        class_name = class.name,
+                        cs_args_with_types = cs_args_with_types,
+                        conv_args_code = conv_args_code,
+                        c_func_name = c_func_name,
+                        args_for_c = args_for_c,
+                    )
+                    .expect(WRITE_TO_MEM_FAILED_MSG);
+                    dll_imports.push_str(&csharp_code::dll_import(
+                        ctx.cfg,
+                        &safe_handle,
+                        &c_func_name,
+                        &c_args_with_types,
+                    ));
+
+                    let constructor_ret_type = class
+                        .self_desc
+                        .as_ref()
+                        .map(|x| &x.constructor_ret_type)
+                        .ok_or_else(&no_this_info)?
+                        .clone();
+                    let this_type = constructor_ret_type.clone();
+                    ctx.rust_code.append(&mut generate_constructor(
+                        ctx.conv_map,
+                        &method_ctx,
+                        constructor_ret_type,
+                        this_type,
+                        &code_box_this,
+                    )?);
+                }
+            }
+        }
+    }
+
+    let class_doc_comments = csharp_code::doc_comments_to_cs_comments(&class.doc_comments, "    ");
+    let mut cs_code = format!(
+        r#"// Automatically generated by flapigen
+using System;
+using System.Runtime.InteropServices;
+
+namespace {namespace}
+{{"#,
+        namespace = ctx.cfg.namespace_name,
+    );
+
+    if need_destructor {
+        let this_type = ctx.conv_map.ty_to_rust_type(
+            class
+                .self_desc
+                .as_ref()
+                .map(|x| &x.constructor_ret_type)
+                .ok_or_else(&no_this_info)?,
+        );
+
+        let unpack_code = unpack_from_heap_pointer(&this_type, "this", false);
+        let c_destructor_name = format!("{}_delete", class.name);
+        let code = format!(
+            r#"
+#[allow(unused_variables, unused_mut, non_snake_case, unused_unsafe)]
+#[no_mangle]
+pub extern "C" fn {c_destructor_name}(this: *mut {this_type}) {{
+{unpack_code}
+    drop(this);
+}}
+"#,
+            c_destructor_name = c_destructor_name,
+            unpack_code = unpack_code,
+            this_type = this_type_for_method,
+        );
+        debug!("we generate and parse code: {}", code);
+        ctx.rust_code.push(
+            syn::parse_str(&code).unwrap_or_else(|err| {
+                panic_on_syn_error("internal C# desctructor code", code, err)
+            }),
+        );
+
+        write!(
+            &mut cs_code,
+            r#"
+    internal sealed class {safe_handle} : SafeHandle
+    {{
+        public {safe_handle}() : base(IntPtr.Zero, true)
+        {{
+        }}
+
+        internal {safe_handle}(IntPtr p, bool ownsHandle) : base(IntPtr.Zero, ownsHandle)
+        {{
+            SetHandle(p);
+        }}
+
+        public override bool IsInvalid
+        {{
+            get {{ return handle == IntPtr.Zero; }}
+        }}
+
+        protected override bool ReleaseHandle()
+        {{
+            {c_destructor_name}(handle);
+            return true;
+        }}
+{dll_import}    }}
+"#,
+            safe_handle = safe_handle,
+            c_destructor_name = c_destructor_name,
+            dll_import =
+                csharp_code::dll_import(ctx.cfg, "void", &c_destructor_name, "IntPtr self"),
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+
+    if static_only {
+        write!(
+            &mut cs_code,
+            r#"
+{doc_comments}    public static class {class_name}
+    {{"#,
+            doc_comments = class_doc_comments,
+            class_name = class.name,
+        )
+    } else {
+        write!(
+            &mut cs_code,
+            r#"
+{doc_comments}    public class {class_name} : IDisposable
+    {{
+        internal readonly {safe_handle} handle;
+
+        internal {class_name}({safe_handle} handle)
+        {{
+            this.handle = handle;
+        }}
+
+        public void Dispose()
+        {{
+            handle.Dispose();
+        }}
+
+        internal IntPtr SwigRelease()
+        {{
+            IntPtr p = handle.DangerousGetHandle();
+            handle.SetHandleAsInvalid();
+            return p;
+        }}
+"#,
+            doc_comments = class_doc_comments,
+            class_name = class.name,
+            safe_handle = safe_handle,
+        )
+    }
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    cs_code.push_str(&cs_methods);
+    cs_code.push_str(&dll_imports);
+    if !class.foreign_code.is_empty() {
+        writeln!(&mut cs_code, "\n{}", class.foreign_code).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    cs_code.push_str("    }\n}\n");
+
+    let mut cnt = cs_code.into_bytes();
+    extend_foreign_class(
+        class,
+        &mut cnt,
+        &KNOWN_CLASS_DERIVES,
+        &[],
+        ctx.class_ext_handlers,
+        ctx.method_ext_handlers,
+    )?;
+    cs_file.replace_content(cnt);
+    cs_file.update_file_if_necessary().map_err(|err| {
+        DiagnosticError::new(
+            class.src_id,
+            class.span(),
+            format!("write to {} failed: {}", cs_path.display(), err),
+        )
+    })?;
+    Ok(())
+}
+
+fn generate_static_method(conv_map: &mut TypeMap, mc: &MethodContext) -> Result<Vec<TokenStream>> {
+    let c_ret_type = mc.f_method.output.base.correspoding_rust_type.typename();
+    let (mut deps_code_out, convert_output_code) = foreign_from_rust_convert_method_output(
+        conv_map,
+        mc.class.src_id,
+        &mc.method.fn_decl.output,
+        mc.f_method.output.base.correspoding_rust_type.to_idx(),
+        mc.ret_name,
+        c_ret_type,
+    )?;
+    let (deps_code_in, convert_input_code) = foreign_to_rust_convert_method_inputs(
+        conv_map,
+        mc.class.src_id,
+        mc.method,
+        mc.f_method,
+        mc.method.arg_names_without_self(),
+        c_ret_type,
+    )?;
+    let code = format!(
+        r#"
+#[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe)]
+#[no_mangle]
+pub extern "C" fn {func_name}({decl_func_args}) -> {c_ret_type} {{
+{convert_input_code}
+    let mut {ret_name}: {real_output_typename} = {call};
+{convert_output_code}
+    {ret_name}
+}}
+"#,
+        func_name = mc.c_func_name,
+        decl_func_args = mc.decl_func_args,
+        c_ret_type = c_ret_type,
+        convert_input_code = convert_input_code,
+        convert_output_code = convert_output_code,
+        real_output_typename = mc.real_output_typename,
+        call = mc.method.generate_code_to_call_rust_func(),
+        ret_name = mc.ret_name,
+    );
+    let mut gen_code = deps_code_in;
+    gen_code.append(&mut deps_code_out);
+    gen_code.push(
+        syn::parse_str(&code)
+            .unwrap_or_else(|err| panic_on_syn_error("C# internal static method", code, err)),
+    );
+    Ok(gen_code)
+}
+
+fn generate_method(
+    conv_map: &mut TypeMap,
+    mc: &MethodContext,
+    class: &ForeignClassInfo,
+    self_variant: SelfTypeVariant,
+    this_type_for_method: &RustType,
+) -> Result<Vec<TokenStream>> {
+    let c_ret_type = mc.f_method.output.base.correspoding_rust_type.typename();
+    let (deps_code_in, convert_input_code) = foreign_to_rust_convert_method_inputs(
+        conv_map,
+        mc.class.src_id,
+        mc.method,
+        mc.f_method,
+        mc.method.arg_names_without_self(),
+        c_ret_type,
+    )?;
+    let (mut deps_code_out, convert_output_code) = foreign_from_rust_convert_method_output(
+        conv_map,
+        mc.class.src_id,
+        &mc.method.fn_decl.output,
+        mc.f_method.output.base.correspoding_rust_type.to_idx(),
+        mc.ret_name,
+        c_ret_type,
+    )?;
+    //&mut constructor_real_type -> &mut class.self_type
+    let (from_ty, to_ty): (Type, Type) = create_suitable_types_for_constructor_and_self(
+        self_variant,
+        class,
+        &this_type_for_method.ty,
+    );
+
+    let from_ty = conv_map.find_or_alloc_rust_type(&from_ty, class.src_id);
+    let to_ty = conv_map.find_or_alloc_rust_type(&to_ty, class.src_id);
+
+    let (mut deps_this, convert_this) = conv_map.convert_rust_types(
+        from_ty.to_idx(),
+        to_ty.to_idx(),
+        "this",
+        "this",
+        c_ret_type,
+        (mc.class.src_id, mc.method.span()),
+    )?;
+    let code = format!(
+        r#"
+#[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe)]
+#[no_mangle]
+pub extern "C" fn {func_name}(this: *mut {this_type}, {decl_func_args}) -> {c_ret_type} {{
+{convert_input_code}
+    let this: {this_type_ref} = unsafe {{
+        this.as_mut().unwrap()
+    }};
+{convert_this}
+    let mut {ret_name}: {real_output_typename} = {call};
+{convert_output_code}
+    {ret_name}
+}}
+"#,
+        func_name = mc.c_func_name,
+        decl_func_args = mc.decl_func_args,
+        convert_input_code = convert_input_code,
+        c_ret_type = c_ret_type,
+        this_type_ref = from_ty,
+        this_type = this_type_for_method,
+        convert_this = convert_this,
+        convert_output_code = convert_output_code,
+        real_output_typename = mc.real_output_typename,
+        call = mc.method.generate_code_to_call_rust_func(),
+        ret_name = mc.ret_name,
+    );
+
+    let mut gen_code = deps_code_in;
+    gen_code.append(&mut deps_code_out);
+    gen_code.append(&mut deps_this);
+    gen_code.push(
+        syn::parse_str(&code)
+            .unwrap_or_else(|err| panic_on_syn_error("C# internal method", code, err)),
+    );
+    Ok(gen_code)
+}
+
+fn generate_constructor(
+    conv_map: &mut TypeMap,
+    mc: &MethodContext,
+    construct_ret_type: Type,
+    this_type: Type,
+    code_box_this: &TokenStream,
+) -> Result<Vec<TokenStream>> {
+    let this_type: RustType = conv_map.ty_to_rust_type(&this_type);
+    let ret_type_name = this_type.normalized_name.as_str();
+    let (deps_code_in, convert_input_code) = foreign_to_rust_convert_method_inputs(
+        conv_map,
+        mc.class.src_id,
+        mc.method,
+        mc.f_method,
+        mc.method.arg_names_without_self(),
+        ret_type_name,
+    )?;
+    let construct_ret_type: RustType = conv_map.ty_to_rust_type(&construct_ret_type);
+    let (mut deps_this, convert_this) = conv_map.convert_rust_types(
+        construct_ret_type.to_idx(),
+        this_type.to_idx(),
+        "this",
+        "this",
+        ret_type_name,
+        (mc.class.src_id, mc.method.span()),
+    )?;
+
+    let code = format!(
+        r#"
+#[allow(unused_variables, unused_mut, non_snake_case, unused_unsafe)]
+#[no_mangle]
+pub extern "C" fn {func_name}({decl_func_args}) -> *const ::std::os::raw::c_void {{
+{convert_input_code}
+    let this: {real_output_typename} = {call};
+{convert_this}
+{box_this}
+    this as *const ::std::os::raw::c_void
+}}
+"#,
+        func_name = mc.c_func_name,
+        convert_this = convert_this,
+        decl_func_args = mc.decl_func_args,
+        convert_input_code = convert_input_code,
+        box_this = code_box_this,
+        real_output_typename = construct_ret_type,
+        call = mc.method.generate_code_to_call_rust_func(),
+    );
+    let mut gen_code = deps_code_in;
+    gen_code.append(&mut deps_this);
+    gen_code.push(
+        syn::parse_str(&code)
+            .unwrap_or_else(|err| panic_on_syn_error("C# internal constructor method", code, err)),
+    );
+    Ok(gen_code)
+}
+
+fn find_suitable_foreign_types_for_methods(
+    ctx: &mut CSharpContext,
+    class: &ForeignClassInfo,
+) -> Result<Vec<CSharpForeignMethodSignature>> {
+    let mut ret = Vec::<CSharpForeignMethodSignature>::with_capacity(class.methods.len());
+    let dummy_ty = parse_type! { () };
+    let dummy_rust_ty = ctx.conv_map.find_or_alloc_rust_type_no_src_id(&dummy_ty);
+
+    for method in &class.methods {
+        //skip self argument
+        let skip_n = match method.variant {
+            MethodVariant::Method(_) => 1,
+            _ => 0,
+        };
+        assert!(method.fn_decl.inputs.len() >= skip_n);
+        let mut input =
+            Vec::<CSharpForeignTypeInfo>::with_capacity(method.fn_decl.inputs.len() - skip_n);
+        for arg in method.fn_decl.inputs.iter().skip(skip_n) {
+            let named_arg = arg
+                .as_named_arg()
+                .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
+            let arg_rust_ty = ctx
+                .conv_map
+                .find_or_alloc_rust_type(&named_arg.ty, class.src_id);
+            input.push(map_type(
+                ctx,
+                &arg_rust_ty,
+                Direction::Incoming,
+                (class.src_id, named_arg.ty.span()),
+            )?);
+        }
+        let output: CSharpForeignTypeInfo = match method.variant {
+            MethodVariant::Constructor => ForeignTypeInfo {
+                name: "".into(),
+                correspoding_rust_type: dummy_rust_ty.clone(),
+            }
+            .into(),
+            _ => match method.fn_decl.output {
+                syn::ReturnType::Default => ForeignTypeInfo {
+                    name: "void".into(),
+                    correspoding_rust_type: dummy_rust_ty.clone(),
+                }
+                .into(),
+                syn::ReturnType::Type(_, ref rt) => {
+                    let ret_rust_ty = ctx.conv_map.find_or_alloc_rust_type(rt, class.src_id);
+                    map_type(
+                        ctx,
+                        &ret_rust_ty,
+                        Direction::Outgoing,
+                        (class.src_id, rt.span()),
+                    )?
+                }
+            },
+        };
+        ret.push(CSharpForeignMethodSignature { output, input });
+    }
+    Ok(ret)
+}
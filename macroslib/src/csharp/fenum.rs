@@ -0,0 +1,155 @@
+use log::trace;
+use quote::quote;
+use std::{fmt::Write, rc::Rc};
+use syn::Type;
+
+use crate::{
+    csharp::{csharp_code, CSharpContext},
+    error::{invalid_src_id_span, DiagnosticError, Result},
+    extension::extend_foreign_enum,
+    file_cache::FileWriteCache,
+    typemap::{
+        ast::{parse_ty_with_given_span, TypeName},
+        ty::{ForeignConversationIntermediate, ForeignConversationRule, ForeignTypeS},
+        TypeConvCode, FROM_VAR_TEMPLATE,
+    },
+    types::ForeignEnumInfo,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::csharp) fn generate_enum(
+    ctx: &mut CSharpContext,
+    fenum: &ForeignEnumInfo,
+) -> Result<()> {
+    if (fenum.items.len() as u64) >= u64::from(u32::MAX) {
+        return Err(DiagnosticError::new(
+            fenum.src_id,
+            fenum.span(),
+            "Too many items in enum",
+        ));
+    }
+
+    trace!("enum_ti: {}", fenum.name);
+    let enum_name = &fenum.name;
+    let enum_ti: Type = parse_ty_with_given_span(&enum_name.to_string(), fenum.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(fenum.src_id, err))?;
+    let enum_rty = ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &enum_ti,
+        &["SwigForeignEnum"],
+        fenum.src_id,
+    );
+
+    generate_cs_code_for_enum(ctx, fenum)
+        .map_err(|err| DiagnosticError::new(fenum.src_id, fenum.span(), err))?;
+    generate_rust_trait_for_enum(ctx, fenum);
+
+    let u32_rty = ctx
+        .conv_map
+        .find_or_alloc_rust_type_no_src_id(&parse_type! { u32 });
+
+    let enum_ftype = ForeignTypeS {
+        name: TypeName::new(fenum.name.to_string(), (fenum.src_id, fenum.name.span())),
+        provides_by_module: vec![],
+        into_from_rust: Some(ForeignConversationRule {
+            rust_ty: enum_rty.to_idx(),
+            intermediate: Some(ForeignConversationIntermediate {
+                input_to_output: false,
+                intermediate_ty: u32_rty.to_idx(),
+                conv_code: Rc::new(TypeConvCode::new(
+                    format!(
+                        "({enum_name}){var}",
+                        enum_name = fenum.name,
+                        var = FROM_VAR_TEMPLATE
+                    ),
+                    invalid_src_id_span(),
+                )),
+            }),
+        }),
+        from_into_rust: Some(ForeignConversationRule {
+            rust_ty: enum_rty.to_idx(),
+            intermediate: Some(ForeignConversationIntermediate {
+                input_to_output: false,
+                intermediate_ty: u32_rty.to_idx(),
+                conv_code: Rc::new(TypeConvCode::new(
+                    format!("(uint){}", FROM_VAR_TEMPLATE),
+                    invalid_src_id_span(),
+                )),
+            }),
+        }),
+        name_prefix: None,
+    };
+    ctx.conv_map.alloc_foreign_type(enum_ftype)?;
+    Ok(())
+}
+
+fn generate_cs_code_for_enum(
+    ctx: &mut CSharpContext,
+    enum_info: &ForeignEnumInfo,
+) -> std::result::Result<(), DiagnosticError> {
+    let cs_path = ctx
+        .cfg
+        .output_dir
+        .join(csharp_code::cs_file_name(&enum_info.name));
+    let mut file = FileWriteCache::new(&cs_path, ctx.generated_foreign_files);
+
+    let mut code = format!(
+        r#"// Automatically generated by flapigen
+namespace {namespace}
+{{
+{doc_comments}    public enum {enum_name} : uint
+    {{
+"#,
+        namespace = ctx.cfg.namespace_name,
+        doc_comments = csharp_code::doc_comments_to_cs_comments(&enum_info.doc_comments, "    "),
+        enum_name = enum_info.name,
+    );
+    for (i, item) in enum_info.items.iter().enumerate() {
+        writeln!(
+            &mut code,
+            "{doc_comments}        {item_name} = {index},",
+            doc_comments = csharp_code::doc_comments_to_cs_comments(&item.doc_comments, "        "),
+            item_name = item.name,
+            index = i,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    code.push_str("    }\n}\n");
+
+    let mut cnt = code.into_bytes();
+    extend_foreign_enum(enum_info, &mut cnt, ctx.enum_ext_handlers)?;
+    file.replace_content(cnt);
+    file.update_file_if_necessary()
+        .map_err(DiagnosticError::map_any_err_to_our_err)?;
+    Ok(())
+}
+
+fn generate_rust_trait_for_enum(ctx: &mut CSharpContext, enum_info: &ForeignEnumInfo) {
+    let mut arms_to_u32 = Vec::with_capacity(enum_info.items.len());
+    let mut arms_from_u32 = Vec::with_capacity(enum_info.items.len());
+    assert!((enum_info.items.len() as u64) <= u64::from(u32::MAX));
+    for (i, item) in enum_info.items.iter().enumerate() {
+        let item_name = &item.rust_name;
+        let idx = i as u32;
+        arms_to_u32.push(quote! { #item_name => #idx });
+        arms_from_u32.push(quote! { #idx => #item_name });
+    }
+
+    let rust_enum_name = &enum_info.name;
+
+    ctx.rust_code.push(quote! {
+        impl SwigForeignEnum for #rust_enum_name {
+            fn as_u32(&self) -> u32 {
+                match *self {
+                    #(#arms_to_u32),*
+                }
+            }
+            fn from_u32(x: u32) -> Self {
+                match x {
+                    #(#arms_from_u32),*
+                    ,
+                    _ => panic!(concat!("{} not expected for ", stringify!(#rust_enum_name)), x),
+                }
+            }
+        }
+    });
+}
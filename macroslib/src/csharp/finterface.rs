@@ -0,0 +1,541 @@
+use std::{fmt::Write, rc::Rc};
+
+use petgraph::Direction;
+use rustc_hash::FxHashSet;
+use smol_str::SmolStr;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    csharp::{
+        csharp_code::{self, cs_ident},
+        map_type::map_type,
+        CSharpContext, CSharpForeignMethodSignature, CSharpForeignTypeInfo,
+    },
+    error::{invalid_src_id_span, panic_on_syn_error, DiagnosticError, Result},
+    file_cache::FileWriteCache,
+    namegen::new_unique_name,
+    typemap::{
+        ast::{parse_ty_with_given_span, DisplayToTokens, TypeName},
+        ty::{ForeignConversationIntermediate, ForeignConversationRule, ForeignTypeS, RustType},
+        utils::rust_to_foreign_convert_method_inputs,
+        ForeignTypeInfo, TypeConvCode, FROM_VAR_TEMPLATE, TO_VAR_TEMPLATE,
+    },
+    types::ForeignInterface,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::csharp) fn generate_interface(
+    ctx: &mut CSharpContext,
+    interface: &ForeignInterface,
+) -> Result<()> {
+    let f_methods = find_suitable_ftypes_for_interace_methods(ctx, interface)?;
+    cs_code_generate_interface(ctx, interface, &f_methods)
+        .map_err(|err| DiagnosticError::new(interface.src_id, interface.span(), err))?;
+    rust_code_generate_interface(ctx, interface, &f_methods)?;
+
+    let c_struct_name = format!("C_{}", interface.name);
+    let rust_ty: Type = parse_ty_with_given_span(&c_struct_name, interface.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(interface.src_id, err))?;
+    let rust_ty = ctx.conv_map.find_or_alloc_rust_type_no_src_id(&rust_ty);
+
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(c_struct_name.clone(), interface.src_id_span()),
+        provides_by_module: vec![],
+        into_from_rust: None,
+        from_into_rust: Some(ForeignConversationRule {
+            rust_ty: rust_ty.to_idx(),
+            intermediate: None,
+        }),
+        name_prefix: None,
+    })?;
+
+    let boxed_trait_name = format!("Box<dyn {}>", DisplayToTokens(&interface.self_type));
+    let boxed_trait_rust_ty: Type =
+        parse_ty_with_given_span(&boxed_trait_name, interface.name.span())
+            .map_err(|err| DiagnosticError::from_syn_err(interface.src_id, err))?;
+    let boxed_trait_rust_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&boxed_trait_rust_ty, interface.src_id);
+
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(interface.name.to_string(), interface.src_id_span()),
+        provides_by_module: vec![],
+        into_from_rust: None,
+        from_into_rust: Some(ForeignConversationRule {
+            rust_ty: boxed_trait_rust_ty.to_idx(),
+            intermediate: Some(ForeignConversationIntermediate {
+                input_to_output: false,
+                intermediate_ty: rust_ty.to_idx(),
+                conv_code: Rc::new(TypeConvCode::new(
+                    format!("{}.Wrap({})", c_struct_name, FROM_VAR_TEMPLATE),
+                    invalid_src_id_span(),
+                )),
+            }),
+        }),
+        name_prefix: None,
+    })?;
+
+    ctx.conv_map.add_conversation_rule(
+        rust_ty.to_idx(),
+        boxed_trait_rust_ty.to_idx(),
+        TypeConvCode::new2(
+            format!(
+                "let {to_var}: {res_type} = Box::new({from_var});",
+                to_var = TO_VAR_TEMPLATE,
+                from_var = FROM_VAR_TEMPLATE,
+                res_type = DisplayToTokens(&boxed_trait_rust_ty.ty),
+            ),
+            invalid_src_id_span(),
+        )
+        .into(),
+    );
+
+    Ok(())
+}
+
+fn rust_code_generate_interface(
+    ctx: &mut CSharpContext,
+    interface: &ForeignInterface,
+    methods_sign: &[CSharpForeignMethodSignature],
+) -> Result<()> {
+    let struct_with_funcs = format!("C_{}", interface.name);
+
+    let mut code = format!(
+        r#"
+#[repr(C)]
+#[allow(non_snake_case)]
+pub struct {struct_with_funcs} {{
+    opaque: *const ::std::os::raw::c_void,
+    {struct_with_funcs}_deref:
+        extern "C" fn(_: *const ::std::os::raw::c_void),
+"#,
+        struct_with_funcs = struct_with_funcs,
+    );
+    for (method, f_method) in interface.items.iter().zip(methods_sign) {
+        let mut args = String::new();
+        for (i, f_type_info) in f_method.input.iter().enumerate() {
+            write!(
+                &mut args,
+                "a{}: {}, ",
+                i,
+                f_type_info.base.correspoding_rust_type.typename(),
+            )
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+        writeln!(
+            &mut code,
+            r#"
+{method_name}: extern "C" fn({args}_: *const ::std::os::raw::c_void) -> {ret_type},"#,
+            method_name = method.name,
+            args = args,
+            ret_type = DisplayToTokens(&f_method.output.base.correspoding_rust_type.ty),
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+
+    code.push_str(
+        r#"
+}
+"#,
+    );
+
+    ctx.rust_code.push(
+        syn::parse_str(&code)
+            .unwrap_or_else(|err| panic_on_syn_error("C# internal code", code.clone(), err)),
+    );
+
+    code.clear();
+
+    writeln!(
+        &mut code,
+        r#"
+/// It totally depends on C# implementation
+/// let's assume it safe
+unsafe impl Send for {struct_with_funcs} {{}}
+impl {trait_name} for {struct_with_funcs} {{"#,
+        trait_name = DisplayToTokens(&interface.self_type.bounds[0]),
+        struct_with_funcs = struct_with_funcs,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    for (method, f_method) in interface.items.iter().zip(methods_sign) {
+        let func_name = method
+            .rust_name
+            .segments
+            .last()
+            .ok_or_else(|| {
+                DiagnosticError::new(
+                    interface.src_id,
+                    method.rust_name.span(),
+                    "Empty trait function name",
+                )
+            })?
+            .ident
+            .to_string();
+        let rest_args_with_types: String = method
+            .fn_decl
+            .inputs
+            .iter()
+            .skip(1)
+            .enumerate()
+            .map(|(i, v)| {
+                format!(
+                    ", a{}: {}",
+                    i,
+                    DisplayToTokens(&v.as_named_arg().unwrap().ty)
+                )
+            })
+            .collect();
+        let self_arg = format!(
+            "{}",
+            method.fn_decl.inputs[0].as_self_arg(interface.src_id)?
+        );
+
+        let args_with_types: String = [self_arg, rest_args_with_types].concat();
+        assert!(!method.fn_decl.inputs.is_empty());
+        let n_args = method.fn_decl.inputs.len() - 1;
+        let (mut conv_deps, convert_args) = rust_to_foreign_convert_method_inputs(
+            ctx.conv_map,
+            interface.src_id,
+            method,
+            f_method,
+            (0..n_args).map(|v| format!("a{}", v)),
+            "()",
+        )?;
+        ctx.rust_code.append(&mut conv_deps);
+        let (real_output_typename, output_conv) = match method.fn_decl.output {
+            syn::ReturnType::Default => ("()".to_string(), String::new()),
+            syn::ReturnType::Type(_, ref ret_ty) => {
+                let real_output_type: RustType = ctx
+                    .conv_map
+                    .find_or_alloc_rust_type(ret_ty, interface.src_id);
+                let (mut conv_deps, conv_code) = ctx.conv_map.convert_rust_types(
+                    f_method.output.base.correspoding_rust_type.to_idx(),
+                    real_output_type.to_idx(),
+                    "ret",
+                    "ret",
+                    real_output_type.normalized_name.as_str(),
+                    (interface.src_id, ret_ty.span()),
+                )?;
+                ctx.rust_code.append(&mut conv_deps);
+                (real_output_type.normalized_name.to_string(), conv_code)
+            }
+        };
+        let args: String = (0..n_args).map(|v| format!("a{}, ", v)).collect();
+        writeln!(
+            &mut code,
+            r#"
+    #[allow(unused_mut)]
+    fn {func_name}({args_with_types}) -> {real_ret_type} {{
+{convert_args}
+        let ret: {ret_type} = (self.{method_name})({args}self.opaque);
+{output_conv}
+        ret
+    }}"#,
+            func_name = func_name,
+            convert_args = convert_args,
+            method_name = method.name,
+            args_with_types = args_with_types,
+            args = args,
+            real_ret_type = real_output_typename,
+            ret_type = DisplayToTokens(&f_method.output.base.correspoding_rust_type.ty),
+            output_conv = output_conv,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    code.push_str(
+        r#"
+}
+"#,
+    );
+
+    writeln!(
+        &mut code,
+        r#"
+impl Drop for {struct_with_funcs} {{
+    fn drop(&mut self) {{
+       (self.{struct_with_funcs}_deref)(self.opaque);
+    }}
+}}"#,
+        struct_with_funcs = struct_with_funcs
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    ctx.rust_code.push(
+        syn::parse_str(&code)
+            .unwrap_or_else(|err| panic_on_syn_error("C# internal code", code, err)),
+    );
+
+    Ok(())
+}
+
+fn find_suitable_ftypes_for_interace_methods(
+    ctx: &mut CSharpContext,
+    interace: &ForeignInterface,
+) -> Result<Vec<CSharpForeignMethodSignature>> {
+    let void_sym = "void";
+    let dummy_ty = parse_type! { () };
+    let dummy_rust_ty = ctx.conv_map.find_or_alloc_rust_type_no_src_id(&dummy_ty);
+    let mut f_methods = Vec::with_capacity(interace.items.len());
+
+    for method in &interace.items {
+        let mut input =
+            Vec::<CSharpForeignTypeInfo>::with_capacity(method.fn_decl.inputs.len() - 1);
+        for arg in method.fn_decl.inputs.iter().skip(1) {
+            let named_arg = arg
+                .as_named_arg()
+                .map_err(|err| DiagnosticError::from_syn_err(interace.src_id, err))?;
+            let arg_rust_ty = ctx
+                .conv_map
+                .find_or_alloc_rust_type(&named_arg.ty, interace.src_id);
+            input.push(map_type(
+                ctx,
+                &arg_rust_ty,
+                Direction::Outgoing,
+                (interace.src_id, named_arg.ty.span()),
+            )?);
+        }
+        let output = match method.fn_decl.output {
+            syn::ReturnType::Default => ForeignTypeInfo {
+                name: void_sym.into(),
+                correspoding_rust_type: dummy_rust_ty.clone(),
+            }
+            .into(),
+            syn::ReturnType::Type(_, ref ret_ty) => {
+                let ret_rust_ty = ctx
+                    .conv_map
+                    .find_or_alloc_rust_type(ret_ty, interace.src_id);
+                let output = map_type(
+                    ctx,
+                    &ret_rust_ty,
+                    Direction::Incoming,
+                    (interace.src_id, ret_ty.span()),
+                )?;
+                // marshaller allocates memory for returned string,
+                // and there is no way to free it on Rust side
+                if output.base.name == "string" {
+                    return Err(DiagnosticError::new(
+                        interace.src_id,
+                        ret_ty.span(),
+                        format!(
+                            "callback {}, method {}: return of string from C# to Rust is not supported",
+                            interace.name, method.name
+                        ),
+                    ));
+                }
+                output
+            }
+        };
+        f_methods.push(CSharpForeignMethodSignature { output, input });
+    }
+    Ok(f_methods)
+}
+
+fn cs_code_generate_interface(
+    ctx: &mut CSharpContext,
+    interface: &ForeignInterface,
+    f_methods: &[CSharpForeignMethodSignature],
+) -> std::result::Result<(), DiagnosticError> {
+    let cs_path = ctx
+        .cfg
+        .output_dir
+        .join(csharp_code::cs_file_name(&interface.name));
+    let mut file = FileWriteCache::new(&cs_path, ctx.generated_foreign_files);
+    let c_struct_name = format!("C_{}", interface.name);
+    let attr_fn_ptr =
+        "[UnmanagedFunctionPointer(CallingConvention.Cdecl, CharSet = CharSet.Unicode)]";
+
+    let mut interface_methods = String::new();
+    let mut delegates = format!(
+        r#"
+        {attr_fn_ptr}
+        internal delegate void SwigDerefDelegate(IntPtr opaque);
+"#,
+        attr_fn_ptr = attr_fn_ptr,
+    );
+    let mut fields = format!(
+        r#"
+        internal IntPtr opaque;
+        internal SwigDerefDelegate {c_struct_name}_deref;
+"#,
+        c_struct_name = c_struct_name,
+    );
+    let mut impls = r#"
+        private static readonly SwigDerefDelegate swig_deref_impl = SwigDeref;
+"#
+    .to_string();
+    let mut fill_struct = format!(
+        "            ret.{c_struct_name}_deref = swig_deref_impl;\n",
+        c_struct_name = c_struct_name,
+    );
+    let mut trampolines = String::new();
+
+    for (method, f_method) in interface.items.iter().zip(f_methods) {
+        let method_name = csharp_code::cs_method_name(&method.name.to_string());
+        let mut known_names: FxHashSet<SmolStr> = method
+            .arg_names_without_self()
+            .map(|x| cs_ident(x).into())
+            .collect();
+        let opaque_name = new_unique_name(&known_names, "opaque");
+        known_names.insert(opaque_name.clone());
+        let ret_name = new_unique_name(&known_names, "ret");
+        known_names.insert(ret_name.clone());
+
+        let c_ret_type = f_method.output.base.name.as_str();
+        let cs_ret_type = f_method.output.cs_typename();
+        let c_args_with_types =
+            csharp_code::c_args_with_types(f_method, method.arg_names_without_self());
+        let c_args_with_types = if c_args_with_types.is_empty() {
+            format!("IntPtr {}", opaque_name)
+        } else {
+            format!("{}, IntPtr {}", c_args_with_types, opaque_name)
+        };
+
+        write!(
+            &mut interface_methods,
+            r#"
+{doc_comments}        {cs_ret_type} {method_name}({args_with_types});
+"#,
+            doc_comments =
+                csharp_code::doc_comments_to_cs_comments(&method.doc_comments, "        "),
+            cs_ret_type = cs_ret_type,
+            method_name = method_name,
+            args_with_types =
+                csharp_code::cs_args_with_types(f_method, method.arg_names_without_self()),
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+
+        write!(
+            &mut delegates,
+            r#"        {attr_fn_ptr}
+        internal delegate {c_ret_type} {method_name}Delegate({c_args_with_types});
+"#,
+            attr_fn_ptr = attr_fn_ptr,
+            c_ret_type = c_ret_type,
+            method_name = method_name,
+            c_args_with_types = c_args_with_types,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(
+            &mut fields,
+            "        internal {method_name}Delegate {field};",
+            method_name = method_name,
+            field = method.name,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(
+            &mut impls,
+            "        private static readonly {method_name}Delegate {field}_impl = Call{method_name};",
+            method_name = method_name,
+            field = method.name,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(
+            &mut fill_struct,
+            "            ret.{field} = {field}_impl;",
+            field = method.name,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+
+        let mut conv_args_code = String::new();
+        let mut call_args = String::new();
+        for (f_type_info, arg_name) in f_method.input.iter().zip(method.arg_names_without_self()) {
+            if !call_args.is_empty() {
+                call_args.push_str(", ");
+            }
+            let arg_name = cs_ident(arg_name);
+            if let Some(conv) = f_type_info.cs_converter.as_ref() {
+                let (deps, expr) = csharp_code::convert_var(
+                    conv,
+                    &arg_name,
+                    f_type_info.cs_typename(),
+                    &mut known_names,
+                )?;
+                conv_args_code.push_str(&deps);
+                call_args.push_str(&expr);
+            } else {
+                call_args.push_str(&arg_name);
+            }
+        }
+        let call = format!(
+            "(({interface_name})GCHandle.FromIntPtr({opaque}).Target).{method_name}({call_args})",
+            interface_name = interface.name,
+            opaque = opaque_name,
+            method_name = method_name,
+            call_args = call_args,
+        );
+        let call_and_ret = if c_ret_type == "void" {
+            format!("            {};\n", call)
+        } else if let Some(conv) = f_method.output.cs_converter.as_ref() {
+            let (deps, expr) =
+                csharp_code::convert_var(conv, &ret_name, c_ret_type, &mut known_names)?;
+            format!(
+                "            {cs_ret_type} {ret} = {call};\n{deps}            return {expr};\n",
+                cs_ret_type = cs_ret_type,
+                ret = ret_name,
+                call = call,
+                deps = deps,
+                expr = expr,
+            )
+        } else {
+            format!("            return {};\n", call)
+        };
+        write!(
+            &mut trampolines,
+            r#"
+        private static {c_ret_type} Call{method_name}({c_args_with_types})
+        {{
+{conv_args_code}{call_and_ret}        }}
+"#,
+            c_ret_type = c_ret_type,
+            method_name = method_name,
+            c_args_with_types = c_args_with_types,
+            conv_args_code = conv_args_code,
+            call_and_ret = call_and_ret,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+
+    let code = format!(
+        r#"// Automatically generated by flapigen
+using System;
+using System.Runtime.InteropServices;
+
+namespace {namespace}
+{{
+{doc_comments}    public interface {interface_name}
+    {{{interface_methods}    }}
+
+    [StructLayout(LayoutKind.Sequential)]
+    internal struct {c_struct_name}
+    {{{delegates}{fields}{impls}
+        internal static {c_struct_name} Wrap({interface_name} obj)
+        {{
+            {c_struct_name} ret;
+            ret.opaque = GCHandle.ToIntPtr(GCHandle.Alloc(obj));
+{fill_struct}            return ret;
+        }}
+
+        private static void SwigDeref(IntPtr opaque)
+        {{
+            GCHandle.FromIntPtr(opaque).Free();
+        }}
+{trampolines}    }}
+}}
+"#,
+        namespace = ctx.cfg.namespace_name,
+        doc_comments = csharp_code::doc_comments_to_cs_comments(&interface.doc_comments, "    "),
+        interface_name = interface.name,
+        interface_methods = interface_methods,
+        c_struct_name = c_struct_name,
+        delegates = delegates,
+        fields = fields,
+        impls = impls,
+        fill_struct = fill_struct,
+        trampolines = trampolines,
+    );
+    file.replace_content(code.into_bytes());
+    file.update_file_if_necessary()
+        .map_err(DiagnosticError::map_any_err_to_our_err)?;
+    Ok(())
+}
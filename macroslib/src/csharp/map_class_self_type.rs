@@ -0,0 +1,424 @@
+use log::debug;
+use std::rc::Rc;
+use syn::spanned::Spanned;
+
+use crate::{
+    error::{invalid_src_id_span, Result},
+    typemap::{
+        ast::TypeName,
+        ty::{ForeignConversationIntermediate, ForeignConversationRule, ForeignTypeS, RustType},
+        utils::{boxed_type, unpack_from_heap_pointer},
+        RustTypeIdx, TypeConvCode, FROM_VAR_TEMPLATE, TO_VAR_TEMPLATE,
+    },
+    types::{ForeignClassInfo, SelfTypeDesc},
+    TypeMap,
+};
+
+/// Name of `SafeHandle` subclass that owns pointer to Rust object
+pub(in crate::csharp) fn safe_handle_name(class: &ForeignClassInfo) -> String {
+    format!("Safe{}Handle", class.name)
+}
+
+pub(in crate::csharp) fn register_typemap_for_self_type(
+    conv_map: &mut TypeMap,
+    class: &ForeignClassInfo,
+    this_type: RustType,
+    self_desc: &SelfTypeDesc,
+) -> Result<()> {
+    let span = this_type.ty.span();
+
+    let void_ptr_ty = parse_type_spanned_checked!(span, *mut ::std::os::raw::c_void);
+    let void_ptr_rust_ty = conv_map.find_or_alloc_rust_type_with_suffix(
+        &void_ptr_ty,
+        &this_type.normalized_name,
+        class.src_id,
+    );
+
+    let const_void_ptr_ty = parse_type_spanned_checked!(span, *const ::std::os::raw::c_void);
+    let const_void_ptr_rust_ty = conv_map.find_or_alloc_rust_type_with_suffix(
+        &const_void_ptr_ty,
+        &this_type.normalized_name,
+        class.src_id,
+    );
+
+    let this_type_inner = boxed_type(conv_map, &this_type);
+
+    let span = this_type_inner.ty.span();
+    let ty = this_type_inner.to_type_without_lifetimes();
+    let gen_ty = parse_type_spanned_checked!(span, & #ty);
+    let this_type_ref = conv_map.find_or_alloc_rust_type(&gen_ty, class.src_id);
+
+    let gen_ty = parse_type_spanned_checked!(span, &mut #ty);
+    let this_type_mut_ref = conv_map.find_or_alloc_rust_type(&gen_ty, class.src_id);
+
+    let gen_ty = parse_type_spanned_checked!(span, *mut #ty);
+    let this_type_mut_ptr = conv_map.find_or_alloc_rust_type(&gen_ty, class.src_id);
+
+    register_intermidiate_pointer_types(
+        conv_map,
+        class,
+        void_ptr_rust_ty.to_idx(),
+        const_void_ptr_rust_ty.to_idx(),
+        this_type_mut_ptr.to_idx(),
+    )?;
+    let types = SelfTypeRelatedTypes {
+        this_type: this_type.to_idx(),
+        this_type_inner: this_type_inner.to_idx(),
+        void_ptr_rust_ty: void_ptr_rust_ty.to_idx(),
+        const_void_ptr_rust_ty: const_void_ptr_rust_ty.to_idx(),
+        this_type_ref: this_type_ref.to_idx(),
+        this_type_mut_ref: this_type_mut_ref.to_idx(),
+        this_type_mut_ptr: this_type_mut_ptr.to_idx(),
+    };
+    register_rust_ty_conversation_rules(conv_map, this_type, &types);
+
+    let self_type = conv_map.find_or_alloc_rust_type(&self_desc.self_type, class.src_id);
+
+    register_main_foreign_types(conv_map, class, self_type.to_idx(), &types)?;
+    Ok(())
+}
+
+/// Rust types derived from type of `self`, that participate in conversations
+#[derive(Clone, Copy)]
+struct SelfTypeRelatedTypes {
+    this_type: RustTypeIdx,
+    this_type_inner: RustTypeIdx,
+    void_ptr_rust_ty: RustTypeIdx,
+    const_void_ptr_rust_ty: RustTypeIdx,
+    this_type_ref: RustTypeIdx,
+    this_type_mut_ref: RustTypeIdx,
+    this_type_mut_ptr: RustTypeIdx,
+}
+
+/// Types that really passed through P/Invoke: `SafeHandle` for pointers
+/// that C# side lends to Rust, and raw `IntPtr` for the rest, because
+/// `SafeHandle` can not be marshaled from unmanaged to managed in callbacks
+fn register_intermidiate_pointer_types(
+    conv_map: &mut TypeMap,
+    class: &ForeignClassInfo,
+    void_ptr_rust_ty: RustTypeIdx,
+    const_void_ptr_rust_ty: RustTypeIdx,
+    this_type_mut_ptr: RustTypeIdx,
+) -> Result<()> {
+    let safe_handle = safe_handle_name(class);
+    let class_span = (class.src_id, class.name.span());
+
+    conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(safe_handle.clone(), class_span),
+        provides_by_module: vec![],
+        into_from_rust: None,
+        from_into_rust: Some(ForeignConversationRule {
+            rust_ty: void_ptr_rust_ty,
+            intermediate: None,
+        }),
+        name_prefix: None,
+    })?;
+
+    let prefix = format!("/*mut {}*/", class.name);
+    conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(format!("{}IntPtr", prefix), class_span),
+        provides_by_module: vec![],
+        into_from_rust: Some(ForeignConversationRule {
+            rust_ty: void_ptr_rust_ty,
+            intermediate: None,
+        }),
+        from_into_rust: None,
+        name_prefix: Some(prefix.into()),
+    })?;
+
+    let prefix = "/*const*/";
+    conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(format!("{}{}", prefix, safe_handle), class_span),
+        provides_by_module: vec![],
+        into_from_rust: None,
+        from_into_rust: Some(ForeignConversationRule {
+            rust_ty: const_void_ptr_rust_ty,
+            intermediate: None,
+        }),
+        name_prefix: Some(prefix.into()),
+    })?;
+
+    let prefix = format!("/*const {}*/", class.name);
+    conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(format!("{}IntPtr", prefix), class_span),
+        provides_by_module: vec![],
+        into_from_rust: Some(ForeignConversationRule {
+            rust_ty: const_void_ptr_rust_ty,
+            intermediate: None,
+        }),
+        from_into_rust: None,
+        name_prefix: Some(prefix.into()),
+    })?;
+
+    let prefix = format!("/*{}*/", class.name);
+    conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(format!("{}IntPtr", prefix), class_span),
+        provides_by_module: vec![],
+        into_from_rust: None,
+        from_into_rust: Some(ForeignConversationRule {
+            rust_ty: this_type_mut_ptr,
+            intermediate: None,
+        }),
+        name_prefix: Some(prefix.into()),
+    })?;
+    Ok(())
+}
+
+fn register_rust_ty_conversation_rules(
+    conv_map: &mut TypeMap,
+    this_type: RustType,
+    types: &SelfTypeRelatedTypes,
+) {
+    let SelfTypeRelatedTypes {
+        this_type_inner,
+        void_ptr_rust_ty,
+        const_void_ptr_rust_ty,
+        this_type_ref,
+        this_type_mut_ref,
+        this_type_mut_ptr,
+        ..
+    } = *types;
+    // *const c_void -> &"class"
+    conv_map.add_conversation_rule(
+        const_void_ptr_rust_ty,
+        this_type_ref,
+        TypeConvCode::new2(
+            format!(
+                r#"
+    assert!(!{from_var}.is_null());
+    let {to_var}: {this_type_ref} = unsafe {{ &*({from_var} as *const {this_type_inner}) }};
+"#,
+                to_var = TO_VAR_TEMPLATE,
+                from_var = FROM_VAR_TEMPLATE,
+                this_type_ref = conv_map[this_type_ref],
+                this_type_inner = conv_map[this_type_inner],
+            ),
+            invalid_src_id_span(),
+        )
+        .into(),
+    );
+
+    // *mut c_void -> &mut "class"
+    conv_map.add_conversation_rule(
+        void_ptr_rust_ty,
+        this_type_mut_ref,
+        TypeConvCode::new2(
+            format!(
+                r#"
+    assert!(!{from_var}.is_null());
+    let {to_var}: {this_type_mut_ref} = unsafe {{ &mut *({from_var} as *mut {this_type_inner}) }};
+"#,
+                to_var = TO_VAR_TEMPLATE,
+                from_var = FROM_VAR_TEMPLATE,
+                this_type_mut_ref = conv_map[this_type_mut_ref],
+                this_type_inner = conv_map[this_type_inner],
+            ),
+            invalid_src_id_span(),
+        )
+        .into(),
+    );
+
+    // *mut "class" -> "class", C# side releases ownership before the call
+    let unpack_code = unpack_from_heap_pointer(&this_type, TO_VAR_TEMPLATE, true);
+    conv_map.add_conversation_rule(
+        this_type_mut_ptr,
+        this_type.to_idx(),
+        TypeConvCode::new(
+            format!(
+                "\n    assert!(!{from_var}.is_null());\n    let {to_var} = {from_var};\n{unpack_code}\n",
+                from_var = FROM_VAR_TEMPLATE,
+                to_var = TO_VAR_TEMPLATE,
+                unpack_code = unpack_code
+            ),
+            invalid_src_id_span(),
+        )
+        .into(),
+    );
+
+    //"class" -> *mut void
+    conv_map.add_conversation_rule(
+        this_type.to_idx(),
+        void_ptr_rust_ty,
+        TypeConvCode::new(
+            format!(
+                "let {to_var}: {ptr_type} = <{this_type}>::box_object({from_var});",
+                to_var = TO_VAR_TEMPLATE,
+                ptr_type = conv_map[void_ptr_rust_ty].typename(),
+                this_type = this_type,
+                from_var = FROM_VAR_TEMPLATE
+            ),
+            invalid_src_id_span(),
+        )
+        .into(),
+    );
+
+    //&"class" -> *const void
+    conv_map.add_conversation_rule(
+        this_type_ref,
+        const_void_ptr_rust_ty,
+        TypeConvCode::new(
+            format!(
+                "let {to_var}: {ptr_type} = ({from_var} as *const {this_type}) as {ptr_type};",
+                to_var = TO_VAR_TEMPLATE,
+                ptr_type = conv_map[const_void_ptr_rust_ty].typename(),
+                this_type = conv_map[this_type_inner],
+                from_var = FROM_VAR_TEMPLATE,
+            ),
+            invalid_src_id_span(),
+        )
+        .into(),
+    );
+}
+
+fn register_main_foreign_types(
+    conv_map: &mut TypeMap,
+    class: &ForeignClassInfo,
+    self_type: RustTypeIdx,
+    types: &SelfTypeRelatedTypes,
+) -> Result<()> {
+    let SelfTypeRelatedTypes {
+        this_type,
+        void_ptr_rust_ty,
+        const_void_ptr_rust_ty,
+        this_type_ref,
+        this_type_mut_ref,
+        this_type_mut_ptr,
+        ..
+    } = *types;
+    debug!(
+        "register_main_foreign_types: this {}, self {}",
+        conv_map[this_type], conv_map[self_type]
+    );
+    let class_span = (class.src_id, class.name.span());
+    let safe_handle = safe_handle_name(class);
+    let handle_conv = || {
+        Rc::new(TypeConvCode::new(
+            format!("{}.handle", FROM_VAR_TEMPLATE),
+            invalid_src_id_span(),
+        ))
+    };
+
+    conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(class.name.to_string(), class_span),
+        provides_by_module: vec![],
+        into_from_rust: Some(ForeignConversationRule {
+            rust_ty: this_type,
+            intermediate: Some(ForeignConversationIntermediate {
+                input_to_output: false,
+                intermediate_ty: void_ptr_rust_ty,
+                conv_code: Rc::new(TypeConvCode::new(
+                    format!(
+                        "new {class}(new {handle}({var}, true))",
+                        class = class.name,
+                        handle = safe_handle,
+                        var = FROM_VAR_TEMPLATE
+                    ),
+                    invalid_src_id_span(),
+                )),
+            }),
+        }),
+        from_into_rust: Some(ForeignConversationRule {
+            rust_ty: this_type,
+            intermediate: Some(ForeignConversationIntermediate {
+                input_to_output: false,
+                intermediate_ty: this_type_mut_ptr,
+                conv_code: Rc::new(TypeConvCode::new(
+                    format!("{}.SwigRelease()", FROM_VAR_TEMPLATE),
+                    invalid_src_id_span(),
+                )),
+            }),
+        }),
+        name_prefix: None,
+    })?;
+
+    let prefix = "/*&*/";
+    conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(format!("{}{}", prefix, class.name), class_span),
+        provides_by_module: vec![],
+        into_from_rust: Some(ForeignConversationRule {
+            rust_ty: this_type_ref,
+            intermediate: Some(ForeignConversationIntermediate {
+                input_to_output: false,
+                intermediate_ty: const_void_ptr_rust_ty,
+                conv_code: Rc::new(TypeConvCode::new(
+                    format!(
+                        "new {class}(new {handle}({var}, false))",
+                        class = class.name,
+                        handle = safe_handle,
+                        var = FROM_VAR_TEMPLATE
+                    ),
+                    invalid_src_id_span(),
+                )),
+            }),
+        }),
+        from_into_rust: Some(ForeignConversationRule {
+            rust_ty: this_type_ref,
+            intermediate: Some(ForeignConversationIntermediate {
+                input_to_output: false,
+                intermediate_ty: const_void_ptr_rust_ty,
+                conv_code: handle_conv(),
+            }),
+        }),
+        name_prefix: Some(prefix.into()),
+    })?;
+
+    let prefix = "/*&mut*/";
+    conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(format!("{}{}", prefix, class.name), class_span),
+        provides_by_module: vec![],
+        into_from_rust: None,
+        from_into_rust: Some(ForeignConversationRule {
+            rust_ty: this_type_mut_ref,
+            intermediate: Some(ForeignConversationIntermediate {
+                input_to_output: false,
+                intermediate_ty: void_ptr_rust_ty,
+                conv_code: handle_conv(),
+            }),
+        }),
+        name_prefix: Some(prefix.into()),
+    })?;
+
+    if self_type != this_type {
+        let self_type = conv_map[self_type].clone();
+        let span = self_type.ty.span();
+        let self_type_ty = self_type.to_type_without_lifetimes();
+
+        let gen_ty = parse_type_spanned_checked!(span, &mut #self_type_ty);
+        let self_type_mut_ref = conv_map.find_or_alloc_rust_type(&gen_ty, class.src_id);
+        let prefix = "/*self &mut*/";
+        conv_map.alloc_foreign_type(ForeignTypeS {
+            name: TypeName::new(format!("{}{}", prefix, class.name), class_span),
+            provides_by_module: vec![],
+            into_from_rust: None,
+            from_into_rust: Some(ForeignConversationRule {
+                rust_ty: self_type_mut_ref.to_idx(),
+                intermediate: Some(ForeignConversationIntermediate {
+                    input_to_output: false,
+                    intermediate_ty: void_ptr_rust_ty,
+                    conv_code: handle_conv(),
+                }),
+            }),
+            name_prefix: Some(prefix.into()),
+        })?;
+
+        let gen_ty = parse_type_spanned_checked!(span, & #self_type_ty);
+        let self_type_ref = conv_map.find_or_alloc_rust_type(&gen_ty, class.src_id);
+        let prefix = "/*self &*/";
+        conv_map.alloc_foreign_type(ForeignTypeS {
+            name: TypeName::new(format!("{}{}", prefix, class.name), class_span),
+            provides_by_module: vec![],
+            into_from_rust: None,
+            from_into_rust: Some(ForeignConversationRule {
+                rust_ty: self_type_ref.to_idx(),
+                intermediate: Some(ForeignConversationIntermediate {
+                    input_to_output: false,
+                    intermediate_ty: const_void_ptr_rust_ty,
+                    conv_code: handle_conv(),
+                }),
+            }),
+            name_prefix: Some(prefix.into()),
+        })?;
+    }
+
+    Ok(())
+}
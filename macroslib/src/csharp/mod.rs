@@ -0,0 +1,394 @@
+macro_rules! file_for_module {
+    ($ctx:ident, $common_files:ident, $module_name:ident) => {{
+        let output_dir = &$ctx.cfg.output_dir;
+        let generated_foreign_files = &mut $ctx.generated_foreign_files;
+        $common_files
+            .entry($module_name.clone())
+            .or_insert_with(|| {
+                let cs_path = output_dir.join($module_name.as_str());
+                let mut cs_f = FileWriteCache::new(&cs_path, *generated_foreign_files);
+                cs_f.write_all(
+                    br##"// Automatically generated by flapigen
+using System;
+using System.Runtime.InteropServices;
+using System.Text;
+"##,
+                )
+                .expect(WRITE_TO_MEM_FAILED_MSG);
+                cs_f
+            })
+    }};
+}
+
+mod csharp_code;
+mod fclass;
+mod fenum;
+mod finterface;
+mod map_class_self_type;
+mod map_type;
+
+use std::{io::Write, mem, path::PathBuf, rc::Rc};
+
+use log::{debug, trace};
+use proc_macro2::TokenStream;
+use rustc_hash::{FxHashMap, FxHashSet};
+use smol_str::SmolStr;
+
+use crate::{
+    csharp::{map_class_self_type::register_typemap_for_self_type, map_type::map_type},
+    error::{invalid_src_id_span, DiagnosticError, Result},
+    extension::{ClassExtHandlers, EnumExtHandlers, ExtHandlers, MethodExtHandlers},
+    file_cache::FileWriteCache,
+    typemap::{
+        ty::{ForeignType, RustType},
+        utils::{
+            configure_ftype_rule, remove_files_if, validate_cfg_options, ForeignMethodSignature,
+            ForeignTypeInfoT,
+        },
+        ForeignTypeInfo, TypeConvCode, TypeMapConvRuleInfo,
+    },
+    types::{ForeignClassInfo, ItemToExpand},
+    CSharpConfig, LanguageGenerator, SourceCode, TypeMap, WRITE_TO_MEM_FAILED_MSG,
+};
+
+#[derive(Debug)]
+struct CSharpConverter {
+    typename: SmolStr,
+    converter: Rc<TypeConvCode>,
+}
+
+#[derive(Debug)]
+struct CSharpForeignTypeInfo {
+    base: ForeignTypeInfo,
+    pub(in crate::csharp) cs_converter: Option<CSharpConverter>,
+}
+
+impl ForeignTypeInfoT for CSharpForeignTypeInfo {
+    fn name(&self) -> &str {
+        self.base.name.as_str()
+    }
+    fn correspoding_rust_type(&self) -> &RustType {
+        &self.base.correspoding_rust_type
+    }
+}
+
+impl CSharpForeignTypeInfo {
+    pub(in crate::csharp) fn try_new(
+        ctx: &mut CSharpContext,
+        direction: petgraph::Direction,
+        ftype_idx: ForeignType,
+    ) -> Result<Self> {
+        let ftype = &ctx.conv_map[ftype_idx];
+        let origin_ftype_span = ftype.src_id_span();
+
+        let rule = match direction {
+            petgraph::Direction::Outgoing => ftype.into_from_rust.as_ref(),
+            petgraph::Direction::Incoming => ftype.from_into_rust.as_ref(),
+        }
+        .ok_or_else(|| {
+            DiagnosticError::new2(
+                origin_ftype_span,
+                format!(
+                    "No rule to convert foreign type {} as input/output type",
+                    ftype.name
+                ),
+            )
+        })?;
+        let base_rt;
+        let base_ft_name;
+        let mut cs_converter = None;
+        if let Some(intermediate) = rule.intermediate.as_ref() {
+            if intermediate.input_to_output {
+                return Err(DiagnosticError::new2(
+                    origin_ftype_span,
+                    format!(
+                        "foreign type {}: 'input_to_output' is not supported for C#",
+                        ftype.name
+                    ),
+                ));
+            }
+            base_rt = intermediate.intermediate_ty;
+            let typename = ftype.typename();
+            let converter = intermediate.conv_code.clone();
+
+            let rty = ctx.conv_map[base_rt].clone();
+            let arg_span = intermediate.conv_code.full_span();
+            let inter_ft = map_type(ctx, &rty, direction, arg_span)?;
+            if inter_ft.cs_converter.is_some()
+                || base_rt != inter_ft.base.correspoding_rust_type.to_idx()
+            {
+                return Err(DiagnosticError::new2(
+                    origin_ftype_span,
+                    format!(
+                        "Error during conversation {} for {},\n
+                    intermidiate type '{}' can not be directly passed through P/Invoke",
+                        typename,
+                        match direction {
+                            petgraph::Direction::Outgoing => "output",
+                            petgraph::Direction::Incoming => "input",
+                        },
+                        rty
+                    ),
+                )
+                .add_span_note(
+                    invalid_src_id_span(),
+                    if let Some(cs_conv) = inter_ft.cs_converter {
+                        format!(
+                            "it requires C# code to convert from '{}' to '{}'",
+                            inter_ft.base.name, cs_conv.typename
+                        )
+                    } else {
+                        format!(
+                            "Type '{}' require conversation to type '{}' before usage in P/Invoke",
+                            ctx.conv_map[base_rt], inter_ft.base.correspoding_rust_type
+                        )
+                    },
+                ));
+            }
+            base_ft_name = inter_ft.base.name;
+            cs_converter = Some(CSharpConverter {
+                typename,
+                converter,
+            });
+        } else {
+            base_rt = rule.rust_ty;
+            base_ft_name = ftype.typename();
+        }
+        trace!(
+            "CSharpForeignTypeInfo::try_new base_ft_name {}, cs_converter {:?}",
+            base_ft_name,
+            cs_converter
+        );
+        Ok(CSharpForeignTypeInfo {
+            base: ForeignTypeInfo {
+                name: base_ft_name,
+                correspoding_rust_type: ctx.conv_map[base_rt].clone(),
+            },
+            cs_converter,
+        })
+    }
+
+    /// Type visible to the user of generated C# code
+    pub(in crate::csharp) fn cs_typename(&self) -> &str {
+        match self.cs_converter {
+            Some(ref conv) => conv.typename.as_str(),
+            None => self.base.name.as_str(),
+        }
+    }
+}
+
+impl AsRef<ForeignTypeInfo> for CSharpForeignTypeInfo {
+    fn as_ref(&self) -> &ForeignTypeInfo {
+        &self.base
+    }
+}
+
+impl From<ForeignTypeInfo> for CSharpForeignTypeInfo {
+    fn from(x: ForeignTypeInfo) -> Self {
+        CSharpForeignTypeInfo {
+            base: x,
+            cs_converter: None,
+        }
+    }
+}
+
+struct CSharpForeignMethodSignature {
+    output: CSharpForeignTypeInfo,
+    input: Vec<CSharpForeignTypeInfo>,
+}
+
+impl ForeignMethodSignature for CSharpForeignMethodSignature {
+    type FI = CSharpForeignTypeInfo;
+    fn output(&self) -> &dyn ForeignTypeInfoT {
+        &self.output.base
+    }
+    fn input(&self) -> &[CSharpForeignTypeInfo] {
+        &self.input[..]
+    }
+}
+
+impl CSharpConfig {
+    fn register_class(&self, conv_map: &mut TypeMap, class: &ForeignClassInfo) -> Result<()> {
+        class
+            .validate_class()
+            .map_err(|err| DiagnosticError::new(class.src_id, class.span(), err))?;
+        if let Some(self_desc) = class.self_desc.as_ref() {
+            let mut traits = vec!["SwigForeignClass"];
+            if class.clone_derived() {
+                traits.push("Clone");
+            }
+            if class.copy_derived() {
+                if !class.clone_derived() {
+                    traits.push("Clone");
+                }
+                traits.push("Copy");
+            }
+            let this_type = conv_map.find_or_alloc_rust_type_that_implements(
+                &self_desc.constructor_ret_type,
+                &traits,
+                class.src_id,
+            );
+            register_typemap_for_self_type(conv_map, class, this_type, self_desc)?;
+        }
+        conv_map.find_or_alloc_rust_type(&class.self_type_as_ty(), class.src_id);
+        Ok(())
+    }
+}
+
+struct CSharpContext<'a> {
+    cfg: &'a CSharpConfig,
+    conv_map: &'a mut TypeMap,
+    rust_code: &'a mut Vec<TokenStream>,
+    common_files: &'a mut FxHashMap<SmolStr, FileWriteCache>,
+    generated_foreign_files: &'a mut FxHashSet<PathBuf>,
+    class_ext_handlers: &'a ClassExtHandlers,
+    method_ext_handlers: &'a MethodExtHandlers,
+    enum_ext_handlers: &'a EnumExtHandlers,
+}
+
+impl LanguageGenerator for CSharpConfig {
+    fn expand_items(
+        &self,
+        conv_map: &mut TypeMap,
+        _target_pointer_width: usize,
+        code: &[SourceCode],
+        items: Vec<ItemToExpand>,
+        remove_not_generated_files: bool,
+        ext_handlers: ExtHandlers,
+    ) -> Result<Vec<TokenStream>> {
+        let mut ret = Vec::with_capacity(items.len());
+        let mut files = FxHashMap::<SmolStr, FileWriteCache>::default();
+        let mut generated_foreign_files = FxHashSet::default();
+        {
+            let mut ctx = CSharpContext {
+                cfg: self,
+                conv_map,
+                rust_code: &mut ret,
+                common_files: &mut files,
+                generated_foreign_files: &mut generated_foreign_files,
+                class_ext_handlers: ext_handlers.class_ext_handlers,
+                method_ext_handlers: ext_handlers.method_ext_handlers,
+                enum_ext_handlers: ext_handlers.enum_ext_handlers,
+            };
+            init(&mut ctx, code)?;
+            for item in &items {
+                if let ItemToExpand::Class(ref fclass) = item {
+                    self.register_class(ctx.conv_map, fclass)?;
+                }
+            }
+            for item in items {
+                match item {
+                    ItemToExpand::Class(fclass) => fclass::generate(&mut ctx, &fclass)?,
+                    ItemToExpand::Enum(fenum) => fenum::generate_enum(&mut ctx, &fenum)?,
+                    ItemToExpand::Interface(finterface) => {
+                        finterface::generate_interface(&mut ctx, &finterface)?
+                    }
+                }
+            }
+        }
+
+        for (module_name, cs_f) in files {
+            let cs_path = self.output_dir.join(module_name.as_str());
+            cs_f.update_file_if_necessary().map_err(|err| {
+                DiagnosticError::map_any_err_to_our_err(format!(
+                    "write to {} failed: {}",
+                    cs_path.display(),
+                    err
+                ))
+            })?;
+        }
+
+        if remove_not_generated_files {
+            remove_files_if(&self.output_dir, |path| {
+                if let Some(ext) = path.extension() {
+                    if ext == "cs" && !generated_foreign_files.contains(path) {
+                        return true;
+                    }
+                }
+                false
+            })
+            .map_err(DiagnosticError::map_any_err_to_our_err)?;
+        }
+
+        Ok(ret)
+    }
+}
+
+fn merge_rule(ctx: &mut CSharpContext, mut rule: TypeMapConvRuleInfo) -> Result<()> {
+    debug!("merge_rule begin {:?}", rule);
+    if rule.is_empty() {
+        return Err(DiagnosticError::new(
+            rule.src_id,
+            rule.span,
+            format!("rule {:?} is empty", rule),
+        ));
+    }
+    if rule.c_types.is_some() || rule.generic_c_types.is_some() {
+        return Err(DiagnosticError::new(
+            rule.src_id,
+            rule.span,
+            "define_c_type! is not supported for C#, use foreign_code! to describe structure",
+        ));
+    }
+    let no_options = FxHashSet::<&'static str>::default();
+    validate_cfg_options(&rule, &no_options)?;
+
+    let f_codes = mem::take(&mut rule.f_code);
+    for fcode in f_codes {
+        let module_name = &fcode.module_name;
+        let common_files = &mut ctx.common_files;
+        let cs_f = file_for_module!(ctx, common_files, module_name);
+        cs_f.write_all(csharp_code::subst_config_vars(ctx.cfg, &fcode.code).as_bytes())
+            .map_err(DiagnosticError::map_any_err_to_our_err)?;
+    }
+
+    configure_ftype_rule(
+        &mut rule.ftype_left_to_right,
+        "=>",
+        rule.src_id,
+        &no_options,
+    )?;
+    configure_ftype_rule(
+        &mut rule.ftype_right_to_left,
+        "<=",
+        rule.src_id,
+        &no_options,
+    )?;
+
+    ctx.conv_map.merge_conv_rule(rule.src_id, rule)?;
+    Ok(())
+}
+
+fn init(ctx: &mut CSharpContext, code: &[SourceCode]) -> Result<()> {
+    if !(ctx.cfg.output_dir.exists() && ctx.cfg.output_dir.is_dir()) {
+        return Err(DiagnosticError::map_any_err_to_our_err(format!(
+            "Path {} not exists or not directory",
+            ctx.cfg.output_dir.display()
+        )));
+    }
+    //for enum
+    ctx.conv_map
+        .find_or_alloc_rust_type_no_src_id(&parse_type! { u32 });
+
+    for cu in code {
+        let src_path = ctx.cfg.output_dir.join(&cu.id_of_code);
+        let mut src_file = FileWriteCache::new(&src_path, ctx.generated_foreign_files);
+        src_file
+            .write_all(csharp_code::subst_config_vars(ctx.cfg, &cu.code).as_bytes())
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+        src_file.update_file_if_necessary().map_err(|err| {
+            DiagnosticError::map_any_err_to_our_err(format!(
+                "update of {} failed: {}",
+                src_path.display(),
+                err
+            ))
+        })?;
+    }
+
+    let not_merged_data = ctx.conv_map.take_not_merged_not_generic_rules();
+    for rule in not_merged_data {
+        merge_rule(ctx, rule)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,321 @@
+use std::fmt::Write;
+
+use heck::{MixedCase, SnakeCase};
+
+use crate::{
+    dart::{DartContext, DartHelper},
+    error::{DiagnosticError, Result},
+    file_cache::FileWriteCache,
+    types::MethodAccess,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+/// Words that can not be used as identifiers in Dart
+static DART_KEYWORDS: [&str; 33] = [
+    "assert", "break", "case", "catch", "class", "const", "continue", "default", "do", "else",
+    "enum", "extends", "false", "final", "finally", "for", "if", "in", "is", "new", "null",
+    "rethrow", "return", "super", "switch", "this", "throw", "true", "try", "var", "void", "while",
+    "with",
+];
+
+pub(in crate::dart) fn dart_ident(name: &str) -> String {
+    if DART_KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.into()
+    }
+}
+
+/// Name of method or function, private for library if `access` is not public
+pub(in crate::dart) fn dart_func_name(access: MethodAccess, rust_name: &str) -> String {
+    match access {
+        MethodAccess::Public => dart_ident(&rust_name.to_mixed_case()),
+        MethodAccess::Private | MethodAccess::Protected => {
+            format!("_{}", rust_name.to_mixed_case())
+        }
+    }
+}
+
+pub(in crate::dart) fn dart_file_name(type_name: &dyn std::fmt::Display) -> String {
+    format!("{}.dart", type_name.to_string().to_snake_case())
+}
+
+pub(in crate::dart) fn doc_comments_to_dart_comments(
+    doc_comments: &[String],
+    indent: &str,
+) -> String {
+    let mut comments = String::new();
+    for comment in doc_comments {
+        let comment = comment.trim();
+        if comment.is_empty() {
+            writeln!(&mut comments, "{}///", indent).expect(WRITE_TO_MEM_FAILED_MSG);
+        } else {
+            writeln!(&mut comments, "{}/// {}", indent, comment).expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+    }
+    comments
+}
+
+/// Substitute variable into conversation template,
+/// `$p` - variable itself, `$p_xyz` - names for temporary values
+pub(in crate::dart) fn apply_conv(template: &str, var_name: &str) -> String {
+    template.replace("$p", var_name)
+}
+
+/// Add `prefix` to all not empty lines of `code`
+pub(in crate::dart) fn indent(code: &str, prefix: &str) -> String {
+    let mut ret = String::with_capacity(code.len());
+    for line in code.lines() {
+        if !line.is_empty() {
+            ret.push_str(prefix);
+        }
+        ret.push_str(line);
+        ret.push('\n');
+    }
+    ret
+}
+
+/// How C type is described for `dart:ffi`
+#[derive(Debug, Clone)]
+pub(in crate::dart) struct FfiType {
+    /// Type for `NativeFunction` signature, like `Int32`
+    pub(in crate::dart) native: String,
+    /// Dart type for the same value, like `int`
+    pub(in crate::dart) dart: String,
+}
+
+/// Dart class name for C structure
+pub(in crate::dart) fn dart_struct_name(c_struct_name: &str) -> String {
+    format!("_{}", c_struct_name)
+}
+
+pub(in crate::dart) fn ffi_type(c_name: &str) -> Option<FfiType> {
+    let c_name = c_name.trim();
+    let (native, dart) = match c_name {
+        "void" => ("Void", "void"),
+        "char" | "int8_t" => ("Int8", "int"),
+        "uint8_t" => ("Uint8", "int"),
+        "int16_t" => ("Int16", "int"),
+        "uint16_t" => ("Uint16", "int"),
+        "int32_t" => ("Int32", "int"),
+        "uint32_t" => ("Uint32", "int"),
+        "int64_t" => ("Int64", "int"),
+        "uint64_t" => ("Uint64", "int"),
+        "intptr_t" => ("IntPtr", "int"),
+        "uintptr_t" => ("UintPtr", "int"),
+        "float" => ("Float", "double"),
+        "double" => ("Double", "double"),
+        _ => {
+            if c_name.ends_with('*') || c_name.ends_with("* const") {
+                ("Pointer<Void>", "Pointer<Void>")
+            } else if let Some(struct_name) = c_name.strip_prefix("struct ") {
+                let name = dart_struct_name(struct_name.trim());
+                return Some(FfiType {
+                    native: name.clone(),
+                    dart: name,
+                });
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(FfiType {
+        native: native.into(),
+        dart: dart.into(),
+    })
+}
+
+/// Field of Dart class that describes C structure or union
+pub(in crate::dart) fn field_decl(ffi: &FfiType, field_name: &str) -> String {
+    if ffi.dart == "int" || ffi.dart == "double" {
+        format!(
+            "  @{native}()\n  external {dart} {field};\n",
+            native = ffi.native,
+            dart = ffi.dart,
+            field = field_name,
+        )
+    } else {
+        format!("  external {} {};\n", ffi.dart, field_name)
+    }
+}
+
+pub(in crate::dart) fn register_struct(
+    ctx: &mut DartContext,
+    dart_name: &str,
+    base_class: &str,
+    fields: &[String],
+) {
+    ctx.structs.entry(dart_name.to_string()).or_insert_with(|| {
+        format!(
+            "\nfinal class {} extends {} {{\n{}}}\n",
+            dart_name,
+            base_class,
+            fields.concat()
+        )
+    });
+}
+
+/// Declaration of top level variable `_<c_func_name>` with Dart function
+pub(in crate::dart) fn lookup_function(
+    c_func_name: &str,
+    ret: &FfiType,
+    args: &[FfiType],
+) -> String {
+    format!(
+        "final _{name} = _lib.lookupFunction<{native_ret} Function({native_args}), {dart_ret} Function({dart_args})>('{name}');\n",
+        name = c_func_name,
+        native_ret = ret.native,
+        native_args = args
+            .iter()
+            .map(|x| x.native.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        dart_ret = ret.dart,
+        dart_args = args
+            .iter()
+            .map(|x| x.dart.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+fn write_dart_file(ctx: &mut DartContext, file_name: &str, code: String) -> Result<()> {
+    let path = ctx.cfg.output_dir.join(file_name);
+    let mut file = FileWriteCache::new(&path, &mut ctx.generated_foreign_files);
+    file.replace_content(code.into_bytes());
+    file.update_file_if_necessary().map_err(|err| {
+        DiagnosticError::map_any_err_to_our_err(format!(
+            "write to {} failed: {}",
+            path.display(),
+            err
+        ))
+    })
+}
+
+/// Write part of Dart library, all parts share imports
+/// and private declarations of library file
+pub(in crate::dart) fn write_part_file(
+    ctx: &mut DartContext,
+    file_name: &str,
+    body: &str,
+) -> Result<()> {
+    let code = format!(
+        r#"// Automatically generated by flapigen
+// ignore_for_file: non_constant_identifier_names, unused_element
+part of '{library}.dart';
+{body}"#,
+        library = ctx.cfg.library_name,
+        body = body,
+    );
+    ctx.parts.push(file_name.to_string());
+    write_dart_file(ctx, file_name, code)
+}
+
+/// Library file with loading of native library, helper functions
+/// and classes for C structures that generated code uses
+pub(in crate::dart) fn generate_library_file(ctx: &mut DartContext) -> Result<()> {
+    let mut helpers: Vec<DartHelper> = ctx.helpers.iter().cloned().collect();
+    helpers.sort();
+    let uses_utf8 = helpers.iter().any(|x| *x != DartHelper::RustException);
+
+    let mut code = String::from(
+        r#"// Automatically generated by flapigen
+// ignore_for_file: non_constant_identifier_names, unused_element
+library;
+
+"#,
+    );
+    if uses_utf8 {
+        code.push_str("import 'dart:convert';\n");
+    }
+    code.push_str("import 'dart:ffi';\nimport 'dart:io';\n");
+    if ctx.uses_arena {
+        code.push_str("\nimport 'package:ffi/ffi.dart';\n");
+    }
+    let mut parts = ctx.parts.clone();
+    parts.sort();
+    if !parts.is_empty() {
+        code.push('\n');
+    }
+    for part in &parts {
+        writeln!(&mut code, "part '{}';", part).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    write!(
+        &mut code,
+        r#"
+final DynamicLibrary _lib = _openLibrary();
+
+DynamicLibrary _openLibrary() {{
+  if (Platform.isIOS) {{
+    return DynamicLibrary.process();
+  }}
+  if (Platform.isMacOS) {{
+    return DynamicLibrary.open('lib{name}.dylib');
+  }}
+  if (Platform.isWindows) {{
+    return DynamicLibrary.open('{name}.dll');
+  }}
+  return DynamicLibrary.open('lib{name}.so');
+}}
+"#,
+        name = ctx.cfg.native_lib_name,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    for helper in helpers {
+        code.push_str(match helper {
+            DartHelper::FromStrView => {
+                r#"
+String _fromCStrView(_CRustStrView s) => utf8.decode(s.data.asTypedList(s.len));
+"#
+            }
+            DartHelper::ToStrView => {
+                r#"
+_CRustStrView _toCStrView(String s, Allocator alloc) {
+  final units = utf8.encode(s);
+  final data = alloc<Uint8>(units.isEmpty ? 1 : units.length);
+  data.asTypedList(units.length).setAll(0, units);
+  final view = alloc<_CRustStrView>();
+  view.ref.data = data;
+  view.ref.len = units.length;
+  return view.ref;
+}
+"#
+            }
+            DartHelper::TakeString => {
+                r#"
+String _takeString(_CRustString s) {
+  final ret = utf8.decode(s.data.asTypedList(s.len));
+  _crust_string_free(s);
+  return ret;
+}
+"#
+            }
+            DartHelper::RustException => {
+                r#"
+/// Thrown when Rust function returns error
+class RustException implements Exception {
+  /// Error returned by Rust function
+  final Object error;
+
+  const RustException(this.error);
+
+  @override
+  String toString() => 'RustException: $error';
+}
+"#
+            }
+        });
+    }
+    if !ctx.native_funcs.is_empty() {
+        code.push('\n');
+    }
+    for native_func in ctx.native_funcs.values() {
+        code.push_str(native_func);
+    }
+    for decl in ctx.structs.values() {
+        code.push_str(decl);
+    }
+    let file_name = format!("{}.dart", ctx.cfg.library_name);
+    write_dart_file(ctx, &file_name, code)
+}
@@ -0,0 +1,287 @@
+use std::fmt::Write;
+
+use heck::MixedCase;
+use petgraph::Direction;
+use rustc_hash::FxHashSet;
+use smol_str::SmolStr;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    cpp::{c_func_name, find_suitable_foreign_types_for_methods, CppContext},
+    dart::{
+        dart_code::{
+            self, apply_conv, dart_func_name, dart_ident, ffi_type, indent, lookup_function,
+            FfiType,
+        },
+        map_type::map_type,
+        DartContext,
+    },
+    error::{DiagnosticError, Result},
+    namegen::new_unique_name,
+    types::{ForeignClassInfo, ForeignMethod, MethodAccess, MethodVariant, SelfTypeVariant},
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::dart) fn generate(
+    ctx: &mut DartContext,
+    cpp_ctx: &mut CppContext,
+    class: &ForeignClassInfo,
+) -> Result<()> {
+    let f_methods = find_suitable_foreign_types_for_methods(cpp_ctx, class)?;
+    let static_only = class
+        .methods
+        .iter()
+        .all(|x| x.variant == MethodVariant::StaticMethod);
+    let unit_ty: Type = parse_type! { () };
+    let ptr_ffi = FfiType {
+        native: "Pointer<Void>".into(),
+        dart: "Pointer<Void>".into(),
+    };
+
+    let mut lookups = String::new();
+    let mut code = String::new();
+    if static_only {
+        write!(
+            &mut code,
+            "\n{doc_comments}abstract final class {class_name} {{\n",
+            doc_comments = dart_code::doc_comments_to_dart_comments(&class.doc_comments, ""),
+            class_name = class.name,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    } else {
+        let delete_func = format!("{}_delete", class.name);
+        lookups.push_str(&lookup_function(
+            &delete_func,
+            &FfiType {
+                native: "Void".into(),
+                dart: "void".into(),
+            },
+            std::slice::from_ref(&ptr_ffi),
+        ));
+        write!(
+            &mut code,
+            r#"
+{doc_comments}final class {class_name} implements Finalizable {{
+  static final _finalizer = NativeFinalizer(
+      _lib.lookup<NativeFunction<Void Function(Pointer<Void>)>>('{delete_func}'));
+
+  Pointer<Void> _ptr;
+  bool _owned;
+
+  {class_name}._wrap(this._ptr, this._owned) {{
+    if (_owned) {{
+      _finalizer.attach(this, _ptr, detach: this);
+    }}
+  }}
+
+  /// Frees Rust object without waiting for finalizer
+  void dispose() {{
+    if (_owned && _ptr != nullptr) {{
+      _finalizer.detach(this);
+      _{delete_func}(_ptr);
+    }}
+    _ptr = nullptr;
+  }}
+
+  Pointer<Void> _release() {{
+    if (!_owned) {{
+      throw StateError('{class_name}: can not pass ownership of not owned object');
+    }}
+    final ptr = _ptr;
+    _finalizer.detach(this);
+    _ptr = nullptr;
+    _owned = false;
+    return ptr;
+  }}
+"#,
+            doc_comments = dart_code::doc_comments_to_dart_comments(&class.doc_comments, ""),
+            class_name = class.name,
+            delete_func = delete_func,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+
+    for (method, f_method) in class.methods.iter().zip(f_methods.iter()) {
+        if method.is_dummy_constructor() {
+            continue;
+        }
+        let skip_n = match method.variant {
+            MethodVariant::Method(_) => 1,
+            _ => 0,
+        };
+        let mut known_names: FxHashSet<SmolStr> = method
+            .arg_names_without_self()
+            .map(|x| SmolStr::from(dart_ident(&x.to_mixed_case())))
+            .collect();
+        known_names.insert("arena".into());
+        let ret_name = new_unique_name(&known_names, "ret");
+        known_names.insert(ret_name.clone());
+
+        let mut args_with_types = Vec::with_capacity(f_method.input.len());
+        let mut conv_deps = String::new();
+        let mut uses_arena = false;
+        let mut c_args = Vec::with_capacity(f_method.input.len() + 1);
+        let mut ffi_args = Vec::with_capacity(f_method.input.len() + 1);
+        match method.variant {
+            MethodVariant::Method(SelfTypeVariant::Rptr)
+            | MethodVariant::Method(SelfTypeVariant::RptrMut) => {
+                c_args.push("_ptr".to_string());
+                ffi_args.push(ptr_ffi.clone());
+            }
+            MethodVariant::Method(SelfTypeVariant::Default)
+            | MethodVariant::Method(SelfTypeVariant::Mut) => {
+                c_args.push("_release()".to_string());
+                ffi_args.push(ptr_ffi.clone());
+            }
+            MethodVariant::Constructor | MethodVariant::StaticMethod => {}
+        }
+        for (arg, c_type) in method
+            .fn_decl
+            .inputs
+            .iter()
+            .skip(skip_n)
+            .zip(f_method.input.iter())
+        {
+            let named_arg = arg
+                .as_named_arg()
+                .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
+            let span = (class.src_id, named_arg.ty.span());
+            let arg_ti = map_type(
+                ctx,
+                cpp_ctx,
+                c_type,
+                &named_arg.ty,
+                Direction::Incoming,
+                span,
+            )?;
+            let arg_name = dart_ident(&named_arg.name.to_mixed_case());
+            args_with_types.push(format!("{} {}", arg_ti.name, arg_name));
+            conv_deps.push_str(&apply_conv(&arg_ti.stmts, &arg_name));
+            c_args.push(apply_conv(&arg_ti.expr, &arg_name));
+            uses_arena |= arg_ti.uses_arena;
+            ffi_args.push(c_ffi_type(&c_type.base.name, span)?);
+        }
+        let c_func = c_func_name(class, method);
+        // C++ generator does not describe return type of constructor,
+        // it is always pointer to new object
+        let ffi_ret = if method.variant == MethodVariant::Constructor {
+            ptr_ffi.clone()
+        } else {
+            c_ffi_type(&f_method.output.base.name, (class.src_id, method.span()))?
+        };
+        lookups.push_str(&lookup_function(&c_func, &ffi_ret, &ffi_args));
+        let call = format!("_{}({})", c_func, c_args.join(", "));
+
+        let (ret_type, call_and_ret) = if method.variant == MethodVariant::Constructor {
+            (
+                class.name.to_string(),
+                format!(
+                    "final {ret} = {call};\nreturn {class_name}._wrap({ret}, true);\n",
+                    ret = ret_name,
+                    call = call,
+                    class_name = class.name,
+                ),
+            )
+        } else {
+            let ret_ty = match method.fn_decl.output {
+                syn::ReturnType::Default => &unit_ty,
+                syn::ReturnType::Type(_, ref ty) => &**ty,
+            };
+            let ret_ti = map_type(
+                ctx,
+                cpp_ctx,
+                &f_method.output,
+                ret_ty,
+                Direction::Outgoing,
+                (class.src_id, ret_ty.span()),
+            )?;
+            uses_arena |= ret_ti.uses_arena;
+            let call_and_ret = if ffi_ret.dart == "void" {
+                format!("{};\n", call)
+            } else if ret_ti.expr.is_empty() {
+                format!(
+                    "final {ret} = {call};\n{conv}",
+                    ret = ret_name,
+                    call = call,
+                    conv = apply_conv(&ret_ti.stmts, &ret_name),
+                )
+            } else {
+                format!(
+                    "final {ret} = {call};\n{conv}return {expr};\n",
+                    ret = ret_name,
+                    call = call,
+                    conv = apply_conv(&ret_ti.stmts, &ret_name),
+                    expr = apply_conv(&ret_ti.expr, &ret_name),
+                )
+            };
+            (ret_ti.name, call_and_ret)
+        };
+        let mut body = format!("{}{}", conv_deps, call_and_ret);
+        if uses_arena {
+            body = format!(
+                "{}using((Arena arena) {{\n{}}});\n",
+                if ret_type == "void" { "" } else { "return " },
+                indent(&body, "  ")
+            );
+        }
+        let func_decl = match method.variant {
+            MethodVariant::Constructor => format!(
+                "factory {}",
+                constructor_name(&class.name.to_string(), method)
+            ),
+            MethodVariant::StaticMethod => format!(
+                "static {} {}",
+                ret_type,
+                dart_func_name(method.access, &method.short_name())
+            ),
+            MethodVariant::Method(_) => format!(
+                "{} {}",
+                ret_type,
+                dart_func_name(method.access, &method.short_name())
+            ),
+        };
+        write!(
+            &mut code,
+            r#"
+{doc_comments}  {func_decl}({args_with_types}) {{
+{body}  }}
+"#,
+            doc_comments = dart_code::doc_comments_to_dart_comments(&method.doc_comments, "  "),
+            func_decl = func_decl,
+            args_with_types = args_with_types.join(", "),
+            body = indent(&body, "    "),
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    if !class.foreign_code.is_empty() {
+        code.push_str(&indent(&class.foreign_code, "  "));
+    }
+    code.push_str("}\n\n");
+    code.push_str(&lookups);
+
+    dart_code::write_part_file(ctx, &dart_code::dart_file_name(&class.name), &code)
+}
+
+fn c_ffi_type(c_name: &str, span: crate::error::SourceIdSpan) -> Result<FfiType> {
+    ffi_type(c_name).ok_or_else(|| {
+        DiagnosticError::new2(
+            span,
+            format!("Dart: C type {} has no dart:ffi equivalent", c_name),
+        )
+    })
+}
+
+/// Dart has no overloading, so constructors except `new`
+/// become named constructors
+fn constructor_name(class_name: &str, method: &ForeignMethod) -> String {
+    let short_name = method.short_name();
+    if short_name == "new" {
+        return class_name.to_string();
+    }
+    match method.access {
+        MethodAccess::Public => format!("{}.{}", class_name, short_name.to_mixed_case()),
+        MethodAccess::Private | MethodAccess::Protected => {
+            format!("{}._{}", class_name, short_name.to_mixed_case())
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt::Write;
+
+use heck::MixedCase;
+
+use crate::{
+    dart::{dart_code, DartContext},
+    error::Result,
+    types::ForeignEnumInfo,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::dart) fn generate_enum(ctx: &mut DartContext, fenum: &ForeignEnumInfo) -> Result<()> {
+    let mut code = String::new();
+    write!(
+        &mut code,
+        "\n{doc_comments}enum {enum_name} {{\n",
+        doc_comments = dart_code::doc_comments_to_dart_comments(&fenum.doc_comments, ""),
+        enum_name = fenum.name,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    // C++ generator uses index of item as value, the same as `Enum.index` in Dart
+    for item in &fenum.items {
+        writeln!(
+            &mut code,
+            "{doc_comments}  {item_name},",
+            doc_comments = dart_code::doc_comments_to_dart_comments(&item.doc_comments, "  "),
+            item_name = dart_code::dart_ident(&item.name.to_string().to_mixed_case()),
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    code.push_str("}\n");
+    dart_code::write_part_file(ctx, &dart_code::dart_file_name(&fenum.name), &code)
+}
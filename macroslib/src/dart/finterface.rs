@@ -0,0 +1,226 @@
+use std::fmt::Write;
+
+use heck::{CamelCase, MixedCase};
+use petgraph::Direction;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    cpp::{find_suitable_ftypes_for_interace_methods, CppContext},
+    dart::{
+        dart_code::{self, apply_conv, dart_ident, ffi_type, indent, register_struct},
+        map_type::{interface_to_c_func_name, map_type},
+        DartContext,
+    },
+    error::{DiagnosticError, Result},
+    types::ForeignInterface,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::dart) fn generate_interface(
+    ctx: &mut DartContext,
+    cpp_ctx: &mut CppContext,
+    interface: &ForeignInterface,
+) -> Result<()> {
+    let f_methods = find_suitable_ftypes_for_interace_methods(cpp_ctx, interface)?;
+    let unit_ty: Type = parse_type! { () };
+    let prefix = format!("_{}", interface.name.to_string().to_mixed_case());
+    let c_struct = format!("_C_{}", interface.name);
+    let deref_field = format!("C_{}_deref", interface.name);
+
+    let mut interface_methods = String::new();
+    let mut callbacks = String::new();
+    let mut c_fields = vec![
+        "  external Pointer<Void> opaque;\n".to_string(),
+        format!(
+            "  external Pointer<NativeFunction<Void Function(Pointer<Void>)>> {};\n",
+            deref_field
+        ),
+    ];
+    let mut c_field_values = vec![(deref_field, format!("{}DerefCallable", prefix))];
+    for (method, f_method) in interface.items.iter().zip(f_methods.iter()) {
+        let method_name = dart_ident(&method.name.to_string().to_mixed_case());
+        let callback_name = format!("{}{}", prefix, method.name.to_string().to_camel_case());
+        let mut args_with_types = Vec::with_capacity(f_method.input.len());
+        let mut c_args_with_types = Vec::with_capacity(f_method.input.len() + 1);
+        let mut native_args = Vec::with_capacity(f_method.input.len() + 1);
+        let mut conv_deps = String::new();
+        let mut call_args = Vec::with_capacity(f_method.input.len());
+        for (i, (arg, c_type)) in method
+            .fn_decl
+            .inputs
+            .iter()
+            .skip(1)
+            .zip(f_method.input.iter())
+            .enumerate()
+        {
+            let named_arg = arg
+                .as_named_arg()
+                .map_err(|err| DiagnosticError::from_syn_err(interface.src_id, err))?;
+            let span = (interface.src_id, named_arg.ty.span());
+            let arg_ti = map_type(
+                ctx,
+                cpp_ctx,
+                c_type,
+                &named_arg.ty,
+                Direction::Outgoing,
+                span,
+            )?;
+            let arg_ffi = ffi_type(&c_type.base.name).ok_or_else(|| {
+                DiagnosticError::new2(span, "Dart: type can not be used as callback argument")
+            })?;
+            if arg_ti.uses_arena {
+                return Err(DiagnosticError::new2(
+                    span,
+                    "Dart: type can not be used as callback argument",
+                ));
+            }
+            let c_arg_name = format!("a{}", i);
+            args_with_types.push(format!(
+                "{} {}",
+                arg_ti.name,
+                dart_ident(&named_arg.name.to_mixed_case())
+            ));
+            c_args_with_types.push(format!("{} {}", arg_ffi.dart, c_arg_name));
+            native_args.push(arg_ffi.native);
+            conv_deps.push_str(&apply_conv(&arg_ti.stmts, &c_arg_name));
+            call_args.push(apply_conv(&arg_ti.expr, &c_arg_name));
+        }
+        c_args_with_types.push("Pointer<Void> opaque".into());
+        native_args.push("Pointer<Void>".into());
+
+        let ret_ty = match method.fn_decl.output {
+            syn::ReturnType::Default => &unit_ty,
+            syn::ReturnType::Type(_, ref ty) => &**ty,
+        };
+        let ret_span = (interface.src_id, ret_ty.span());
+        let ret_ti = map_type(
+            ctx,
+            cpp_ctx,
+            &f_method.output,
+            ret_ty,
+            Direction::Incoming,
+            ret_span,
+        )?;
+        // value returned to Rust should not point to memory of arena
+        let ret_ffi = ffi_type(&f_method.output.base.name)
+            .filter(|x| {
+                ret_ti.stmts.is_empty()
+                    && !ret_ti.uses_arena
+                    && (x.dart == "void" || x.dart == "int" || x.dart == "double")
+            })
+            .ok_or_else(|| {
+                DiagnosticError::new2(ret_span, "Dart: type can not be returned from callback")
+            })?;
+        let call = format!("obj.{}({})", method_name, call_args.join(", "));
+        let (body, exceptional_return) = match ret_ffi.dart.as_str() {
+            "void" => (format!("{};\n", call), ""),
+            dart_type => (
+                format!(
+                    "final ret = {};\nreturn {};\n",
+                    call,
+                    apply_conv(&ret_ti.expr, "ret")
+                ),
+                if dart_type == "double" {
+                    ", exceptionalReturn: 0.0"
+                } else {
+                    ", exceptionalReturn: 0"
+                },
+            ),
+        };
+        let native_sig = format!("{} Function({})", ret_ffi.native, native_args.join(", "));
+
+        writeln!(
+            &mut interface_methods,
+            "{doc_comments}  {ret_type} {method_name}({args_with_types});",
+            doc_comments = dart_code::doc_comments_to_dart_comments(&method.doc_comments, "  "),
+            ret_type = ret_ti.name,
+            method_name = method_name,
+            args_with_types = args_with_types.join(", "),
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        write!(
+            &mut callbacks,
+            r#"
+{ret_type} {callback_name}({c_args_with_types}) {{
+  final obj = {prefix}Objects[opaque.address]!;
+{body}}}
+
+final {callback_name}Callable = NativeCallable<{native_sig}>.isolateLocal(
+    {callback_name}{exceptional_return});
+"#,
+            ret_type = ret_ffi.dart,
+            callback_name = callback_name,
+            c_args_with_types = c_args_with_types.join(", "),
+            prefix = prefix,
+            body = indent(&format!("{}{}", conv_deps, body), "  "),
+            native_sig = native_sig,
+            exceptional_return = exceptional_return,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        c_fields.push(format!(
+            "  external Pointer<NativeFunction<{}>> {};\n",
+            native_sig, method.name
+        ));
+        c_field_values.push((
+            method.name.to_string(),
+            format!("{}Callable", callback_name),
+        ));
+    }
+    register_struct(ctx, &c_struct, "Struct", &c_fields);
+
+    let mut doc_comments = interface.doc_comments.clone();
+    if !doc_comments.is_empty() {
+        doc_comments.push(String::new());
+    }
+    doc_comments.push(format!(
+        "Rust code must call methods of {} on thread of isolate",
+        interface.name
+    ));
+    doc_comments.push("that created the object, otherwise process is aborted".into());
+
+    let mut code = String::new();
+    write!(
+        &mut code,
+        r#"
+{doc_comments}abstract interface class {interface_name} {{
+{interface_methods}}}
+
+final {prefix}Objects = <int, {interface_name}>{{}};
+var {prefix}NextId = 1;
+
+void {prefix}Deref(Pointer<Void> opaque) {{
+  {prefix}Objects.remove(opaque.address);
+}}
+
+final {prefix}DerefCallable =
+    NativeCallable<Void Function(Pointer<Void>)>.isolateLocal({prefix}Deref);
+{callbacks}
+/// Creates C structure that holds id of obj,
+/// obj is forgotten when Rust side drops callback
+Pointer<{c_struct}> {to_c}({interface_name} obj, Allocator alloc) {{
+  final id = {prefix}NextId++;
+  {prefix}Objects[id] = obj;
+  final c = alloc<{c_struct}>();
+  c.ref.opaque = Pointer.fromAddress(id);
+"#,
+        doc_comments = dart_code::doc_comments_to_dart_comments(&doc_comments, ""),
+        interface_name = interface.name,
+        interface_methods = interface_methods,
+        prefix = prefix,
+        callbacks = callbacks,
+        c_struct = c_struct,
+        to_c = interface_to_c_func_name(&interface.name.to_string()),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for (field, callable) in &c_field_values {
+        writeln!(
+            &mut code,
+            "  c.ref.{} = {}.nativeFunction;",
+            field, callable
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    code.push_str("  return c;\n}\n");
+
+    dart_code::write_part_file(ctx, &dart_code::dart_file_name(&interface.name), &code)
+}
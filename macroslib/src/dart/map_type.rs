@@ -0,0 +1,561 @@
+use heck::MixedCase;
+use petgraph::Direction;
+use syn::Type;
+
+use crate::{
+    cpp::{c_class_type_name, map_type as map_c_type, CppContext, CppForeignTypeInfo},
+    dart::{
+        dart_code::{
+            apply_conv, dart_struct_name, ffi_type, field_decl, indent, lookup_function,
+            register_struct, FfiType,
+        },
+        DartContext, DartHelper,
+    },
+    error::{DiagnosticError, Result, SourceIdSpan},
+    typemap::ast::DisplayToTokens,
+};
+
+/// Dart type and templates to convert value between Dart and C,
+/// see `apply_conv` for template syntax
+#[derive(Debug)]
+pub(in crate::dart) struct DartTypeInfo {
+    pub(in crate::dart) name: String,
+    /// Statements to execute before usage of `expr`
+    pub(in crate::dart) stmts: String,
+    pub(in crate::dart) expr: String,
+    /// Conversation allocates memory with `arena`,
+    /// that freed after function call
+    pub(in crate::dart) uses_arena: bool,
+}
+
+impl DartTypeInfo {
+    fn expr(name: String, expr: String) -> Self {
+        DartTypeInfo {
+            name,
+            stmts: String::new(),
+            expr,
+            uses_arena: false,
+        }
+    }
+    fn with_stmts(name: String, stmts: String, expr: String) -> Self {
+        DartTypeInfo {
+            name,
+            stmts,
+            expr,
+            uses_arena: false,
+        }
+    }
+}
+
+/// Name of function that converts Dart object to C structure for callback
+pub(in crate::dart) fn interface_to_c_func_name(interface_name: &str) -> String {
+    format!("_{}ToC", interface_name.to_mixed_case())
+}
+
+/// Generic arguments of type like `Option<T>`, `Result<T, E>` or `&[T]`
+fn generic_args(ty: &Type) -> Vec<&Type> {
+    match ty {
+        Type::Reference(ref r) => match *r.elem {
+            Type::Slice(ref s) => vec![&*s.elem],
+            _ => Vec::new(),
+        },
+        Type::Path(ref p) => match p.path.segments.last().map(|x| &x.arguments) {
+            Some(syn::PathArguments::AngleBracketed(ref args)) => args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(ref ty) => Some(ty),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+fn is_unit_type(ty: &Type) -> bool {
+    matches!(ty, Type::Tuple(ref t) if t.elems.is_empty())
+}
+
+/// `dart:ffi` can create typed list only for numbers with fixed size
+fn has_typed_list(ffi: &FfiType) -> bool {
+    matches!(
+        ffi.native.as_str(),
+        "Int8"
+            | "Uint8"
+            | "Int16"
+            | "Uint16"
+            | "Int32"
+            | "Uint32"
+            | "Int64"
+            | "Uint64"
+            | "Float"
+            | "Double"
+    )
+}
+
+fn register_str_view(ctx: &mut DartContext) {
+    register_struct(
+        ctx,
+        "_CRustStrView",
+        "Struct",
+        &[
+            "  external Pointer<Uint8> data;\n".into(),
+            "  @UintPtr()\n  external int len;\n".into(),
+        ],
+    );
+}
+
+fn register_rust_string(ctx: &mut DartContext) {
+    register_struct(
+        ctx,
+        "_CRustString",
+        "Struct",
+        &[
+            "  external Pointer<Uint8> data;\n".into(),
+            "  @UintPtr()\n  external int len;\n".into(),
+            "  @UintPtr()\n  external int capacity;\n".into(),
+        ],
+    );
+    let ty = FfiType {
+        native: "_CRustString".into(),
+        dart: "_CRustString".into(),
+    };
+    register_native_func(ctx, "crust_string_free", &void_ffi_type(), &[ty]);
+}
+
+fn register_native_func(ctx: &mut DartContext, name: &str, ret: &FfiType, args: &[FfiType]) {
+    ctx.native_funcs
+        .entry(name.to_string())
+        .or_insert_with(|| lookup_function(name, ret, args));
+}
+
+fn void_ffi_type() -> FfiType {
+    FfiType {
+        native: "Void".into(),
+        dart: "void".into(),
+    }
+}
+
+/// Map `rust_ty` (as it written in `foreign_class!`) that C++ generator
+/// mapped to C type `c_type` to Dart type
+pub(in crate::dart) fn map_type(
+    ctx: &mut DartContext,
+    cpp_ctx: &mut CppContext,
+    c_type: &CppForeignTypeInfo,
+    rust_ty: &Type,
+    direction: Direction,
+    span: SourceIdSpan,
+) -> Result<DartTypeInfo> {
+    let c_name = c_type.base.name.as_str();
+    let cpp_name = c_type.cpp_converter.as_ref().map(|x| x.typename.as_str());
+
+    if c_name == "void" {
+        return Ok(DartTypeInfo::expr("void".into(), "$p".into()));
+    }
+    if c_name == "char" && cpp_name == Some("bool") {
+        return Ok(match direction {
+            Direction::Outgoing => DartTypeInfo::expr("bool".into(), "($p != 0)".into()),
+            Direction::Incoming => DartTypeInfo::expr("bool".into(), "($p ? 1 : 0)".into()),
+        });
+    }
+    if let Some(enum_name) = cpp_name.filter(|x| ctx.enums.contains(*x)) {
+        return Ok(DartTypeInfo::expr(
+            enum_name.into(),
+            match direction {
+                Direction::Outgoing => format!("{}.values[$p]", enum_name),
+                Direction::Incoming => "$p.index".into(),
+            },
+        ));
+    }
+    if let Some(ffi) = ffi_type(c_name).filter(|x| x.dart == "int" || x.dart == "double") {
+        return Ok(DartTypeInfo::expr(ffi.dart, "$p".into()));
+    }
+
+    let opaque_name = c_name
+        .trim_start_matches("const ")
+        .trim_end_matches('*')
+        .trim();
+    if let Some(class_name) = ctx
+        .classes
+        .iter()
+        .find(|class_name| c_class_type_name(class_name) == opaque_name)
+    {
+        let is_ref = matches!(rust_ty, Type::Reference(_));
+        let name = class_name.to_string();
+        return Ok(match direction {
+            Direction::Outgoing => DartTypeInfo::expr(
+                name.clone(),
+                format!(
+                    "{}._wrap($p, {})",
+                    name,
+                    !is_ref && !c_name.starts_with("const ")
+                ),
+            ),
+            Direction::Incoming if is_ref => DartTypeInfo::expr(name, "$p._ptr".into()),
+            Direction::Incoming => DartTypeInfo::expr(name, "$p._release()".into()),
+        });
+    }
+
+    if let Some(interface_name) = ctx
+        .interfaces
+        .iter()
+        .find(|name| c_name == format!("const struct C_{} * const", name))
+    {
+        if direction == Direction::Incoming {
+            ctx.uses_arena = true;
+            return Ok(DartTypeInfo {
+                name: interface_name.to_string(),
+                stmts: String::new(),
+                expr: format!(
+                    "{}($p, arena).cast<Void>()",
+                    interface_to_c_func_name(interface_name)
+                ),
+                uses_arena: true,
+            });
+        }
+    }
+
+    let c_struct_name = c_name.trim_start_matches("struct ").trim();
+    match (c_struct_name, direction) {
+        ("CRustStrView", Direction::Outgoing) => {
+            register_str_view(ctx);
+            ctx.helpers.insert(DartHelper::FromStrView);
+            return Ok(DartTypeInfo::expr(
+                "String".into(),
+                "_fromCStrView($p)".into(),
+            ));
+        }
+        ("CRustStrView", Direction::Incoming) => {
+            register_str_view(ctx);
+            ctx.helpers.insert(DartHelper::ToStrView);
+            ctx.uses_arena = true;
+            return Ok(DartTypeInfo {
+                name: "String".into(),
+                stmts: String::new(),
+                expr: "_toCStrView($p, arena)".into(),
+                uses_arena: true,
+            });
+        }
+        ("CRustString", Direction::Outgoing) => {
+            register_rust_string(ctx);
+            ctx.helpers.insert(DartHelper::TakeString);
+            return Ok(DartTypeInfo::expr(
+                "String".into(),
+                "_takeString($p)".into(),
+            ));
+        }
+        _ => {}
+    }
+
+    let not_supported = || {
+        DiagnosticError::new2(
+            span,
+            format!(
+                "Dart: type {} (C type {}) is not supported as {}",
+                DisplayToTokens(rust_ty),
+                c_name,
+                match direction {
+                    Direction::Outgoing => "output",
+                    Direction::Incoming => "input",
+                }
+            ),
+        )
+    };
+    let map_inner = |ctx: &mut DartContext, cpp_ctx: &mut CppContext, inner_ty: &Type| {
+        let inner_rty = cpp_ctx.conv_map.find_or_alloc_rust_type(inner_ty, span.0);
+        let inner_c_type = map_c_type(cpp_ctx, &inner_rty, direction, span)?;
+        let inner = map_type(ctx, cpp_ctx, &inner_c_type, inner_ty, direction, span)?;
+        let inner_ffi = ffi_type(&inner_c_type.base.name).ok_or_else(not_supported)?;
+        Ok((inner, inner_ffi))
+    };
+    let args = generic_args(rust_ty);
+    let dart_struct = dart_struct_name(c_struct_name);
+
+    if c_struct_name.starts_with("CRustClassOpt") && direction == Direction::Incoming {
+        let class_name = c_struct_name
+            .trim_start_matches("CRustClassOptMut")
+            .trim_start_matches("CRustClassOpt");
+        if ctx.classes.contains(class_name) {
+            register_struct(
+                ctx,
+                &dart_struct,
+                "Struct",
+                &["  external Pointer<Void> p;\n".into()],
+            );
+            ctx.uses_arena = true;
+            return Ok(DartTypeInfo {
+                name: format!("{}?", class_name),
+                stmts: format!(
+                    "final $p_c = arena<{dart_struct}>();\n$p_c.ref.p = $p?._ptr ?? nullptr;\n",
+                    dart_struct = dart_struct,
+                ),
+                expr: "$p_c.ref".into(),
+                uses_arena: true,
+            });
+        }
+    }
+
+    if c_struct_name.starts_with("CRustOption") {
+        let inner_ty = args.first().copied().ok_or_else(not_supported)?;
+        let (inner, inner_ffi) = map_inner(ctx, cpp_ctx, inner_ty)?;
+        if inner.name.ends_with('?') {
+            return Err(not_supported());
+        }
+        let union_name = format!("{}Val", dart_struct);
+        register_struct(
+            ctx,
+            &union_name,
+            "Union",
+            &[
+                field_decl(&inner_ffi, "data"),
+                "  @Uint8()\n  external int uninit;\n".into(),
+            ],
+        );
+        register_struct(
+            ctx,
+            &dart_struct,
+            "Struct",
+            &[
+                format!("  external {} val;\n", union_name),
+                "  @Uint8()\n  external int is_some;\n".into(),
+            ],
+        );
+        let name = format!("{}?", inner.name);
+        return Ok(match direction {
+            Direction::Outgoing => {
+                let mut stmts = format!(
+                    "{name} $p_v;\nif ($p.is_some != 0) {{\n  final $p_data = $p.val.data;\n",
+                    name = name
+                );
+                stmts.push_str(&indent(&apply_conv(&inner.stmts, "$p_data"), "  "));
+                stmts.push_str(&format!(
+                    "  $p_v = {};\n}}\n",
+                    apply_conv(&inner.expr, "$p_data")
+                ));
+                DartTypeInfo {
+                    name,
+                    stmts,
+                    expr: "$p_v".into(),
+                    uses_arena: inner.uses_arena,
+                }
+            }
+            Direction::Incoming => {
+                ctx.uses_arena = true;
+                let mut stmts = format!(
+                    "final $p_c = arena<{dart_struct}>();\n$p_c.ref.is_some = 0;\nfinal $p_v = $p;\nif ($p_v != null) {{\n",
+                    dart_struct = dart_struct,
+                );
+                stmts.push_str(&indent(&apply_conv(&inner.stmts, "$p_v"), "  "));
+                stmts.push_str(&format!(
+                    "  $p_c.ref.val.data = {};\n  $p_c.ref.is_some = 1;\n}}\n",
+                    apply_conv(&inner.expr, "$p_v")
+                ));
+                DartTypeInfo {
+                    name,
+                    stmts,
+                    expr: "$p_c.ref".into(),
+                    uses_arena: true,
+                }
+            }
+        });
+    }
+
+    if (c_struct_name.starts_with("CRustResult") || c_struct_name.starts_with("CRustVoidOkResult"))
+        && direction == Direction::Outgoing
+    {
+        let (ok_ty, err_ty) = match args.as_slice() {
+            [ok_ty, err_ty] => (*ok_ty, *err_ty),
+            _ => return Err(not_supported()),
+        };
+        ctx.helpers.insert(DartHelper::RustException);
+        let (err, err_ffi) = map_inner(ctx, cpp_ctx, err_ty)?;
+        let ok = if is_unit_type(ok_ty) {
+            None
+        } else {
+            Some(map_inner(ctx, cpp_ctx, ok_ty)?)
+        };
+        let union_name = format!("{}Data", dart_struct);
+        let ok_field = match ok {
+            Some((_, ref ok_ffi)) => field_decl(ok_ffi, "ok"),
+            None => "  @Uint8()\n  external int ok;\n".into(),
+        };
+        register_struct(
+            ctx,
+            &union_name,
+            "Union",
+            &[ok_field, field_decl(&err_ffi, "err")],
+        );
+        register_struct(
+            ctx,
+            &dart_struct,
+            "Struct",
+            &[
+                format!("  external {} data;\n", union_name),
+                "  @Uint8()\n  external int is_ok;\n".into(),
+            ],
+        );
+        let mut stmts = String::from("if ($p.is_ok == 0) {\n  final $p_err = $p.data.err;\n");
+        stmts.push_str(&indent(&apply_conv(&err.stmts, "$p_err"), "  "));
+        stmts.push_str(&format!(
+            "  throw RustException({});\n}}\n",
+            apply_conv(&err.expr, "$p_err")
+        ));
+        return Ok(match ok {
+            Some((ok, _)) => {
+                stmts.push_str("final $p_ok = $p.data.ok;\n");
+                stmts.push_str(&apply_conv(&ok.stmts, "$p_ok"));
+                DartTypeInfo {
+                    name: ok.name,
+                    stmts,
+                    expr: apply_conv(&ok.expr, "$p_ok"),
+                    uses_arena: ok.uses_arena || err.uses_arena,
+                }
+            }
+            None => DartTypeInfo {
+                name: "void".into(),
+                stmts,
+                expr: String::new(),
+                uses_arena: err.uses_arena,
+            },
+        });
+    }
+
+    if c_struct_name.starts_with("CRustVec") && direction == Direction::Outgoing {
+        let inner_ty = args.first().copied().ok_or_else(not_supported)?;
+        let (inner, inner_ffi) = map_inner(ctx, cpp_ctx, inner_ty)?;
+        if !has_typed_list(&inner_ffi) {
+            return Err(not_supported());
+        }
+        register_struct(
+            ctx,
+            &dart_struct,
+            "Struct",
+            &[
+                format!("  external Pointer<{}> data;\n", inner_ffi.native),
+                "  @UintPtr()\n  external int len;\n".into(),
+                "  @UintPtr()\n  external int capacity;\n".into(),
+            ],
+        );
+        let vec_ffi = FfiType {
+            native: dart_struct.clone(),
+            dart: dart_struct.clone(),
+        };
+        let free_func = format!("{}_free", c_struct_name);
+        register_native_func(ctx, &free_func, &void_ffi_type(), &[vec_ffi]);
+        return Ok(DartTypeInfo::with_stmts(
+            format!("List<{}>", inner.name),
+            format!(
+                "final $p_v = $p.data.asTypedList($p.len).toList();\n_{free}($p);\n",
+                free = free_func,
+            ),
+            "$p_v".into(),
+        ));
+    }
+
+    if c_struct_name == "CRustForeignVec" && direction == Direction::Outgoing {
+        let vec_name = cpp_name.ok_or_else(not_supported)?;
+        let inner_ty = args.first().copied().ok_or_else(not_supported)?;
+        let (inner, _) = map_inner(ctx, cpp_ctx, inner_ty)?;
+        if !ctx.classes.contains(inner.name.as_str()) {
+            return Err(not_supported());
+        }
+        register_struct(
+            ctx,
+            &dart_struct,
+            "Struct",
+            &[
+                "  external Pointer<Void> data;\n".into(),
+                "  @UintPtr()\n  external int len;\n".into(),
+                "  @UintPtr()\n  external int capacity;\n".into(),
+                "  @UintPtr()\n  external int step;\n".into(),
+            ],
+        );
+        let vec_ffi = FfiType {
+            native: dart_struct.clone(),
+            dart: dart_struct.clone(),
+        };
+        let vec_ptr_ffi = FfiType {
+            native: format!("Pointer<{}>", dart_struct),
+            dart: format!("Pointer<{}>", dart_struct),
+        };
+        let index_ffi = FfiType {
+            native: "UintPtr".into(),
+            dart: "int".into(),
+        };
+        let ptr_ffi = FfiType {
+            native: "Pointer<Void>".into(),
+            dart: "Pointer<Void>".into(),
+        };
+        let remove_func = format!("{}_remove", vec_name);
+        let free_func = format!("{}_free", vec_name);
+        register_native_func(ctx, &remove_func, &ptr_ffi, &[vec_ptr_ffi, index_ffi]);
+        register_native_func(ctx, &free_func, &void_ffi_type(), &[vec_ffi]);
+        ctx.uses_arena = true;
+        return Ok(DartTypeInfo {
+            name: format!("List<{}>", inner.name),
+            stmts: format!(
+                r#"final $p_c = arena<{dart_struct}>();
+$p_c.ref.data = $p.data;
+$p_c.ref.len = $p.len;
+$p_c.ref.capacity = $p.capacity;
+$p_c.ref.step = $p.step;
+final $p_v = <{class}>[];
+for (var i = $p.len - 1; i >= 0; i--) {{
+  $p_v.add({class}._wrap(_{remove}($p_c, i), true));
+}}
+_{free}($p_c.ref);
+"#,
+                dart_struct = dart_struct,
+                class = inner.name,
+                remove = remove_func,
+                free = free_func,
+            ),
+            expr: "$p_v.reversed.toList()".into(),
+            uses_arena: true,
+        });
+    }
+
+    if c_struct_name.starts_with("CRustSlice") {
+        let inner_ty = args.first().copied().ok_or_else(not_supported)?;
+        let (inner, inner_ffi) = map_inner(ctx, cpp_ctx, inner_ty)?;
+        if has_typed_list(&inner_ffi) {
+            register_struct(
+                ctx,
+                &dart_struct,
+                "Struct",
+                &[
+                    format!("  external Pointer<{}> data;\n", inner_ffi.native),
+                    "  @UintPtr()\n  external int len;\n".into(),
+                ],
+            );
+            let name = format!("List<{}>", inner.name);
+            return Ok(match direction {
+                Direction::Outgoing => {
+                    DartTypeInfo::expr(name, "$p.data.asTypedList($p.len).toList()".into())
+                }
+                Direction::Incoming => {
+                    ctx.uses_arena = true;
+                    DartTypeInfo {
+                        name,
+                        stmts: format!(
+                            r#"final $p_data = arena<{elem}>($p.isEmpty ? 1 : $p.length);
+$p_data.asTypedList($p.length).setAll(0, $p);
+final $p_c = arena<{dart_struct}>();
+$p_c.ref.data = $p_data;
+$p_c.ref.len = $p.length;
+"#,
+                            elem = inner_ffi.native,
+                            dart_struct = dart_struct,
+                        ),
+                        expr: "$p_c.ref".into(),
+                        uses_arena: true,
+                    }
+                }
+            });
+        }
+    }
+
+    Err(not_supported())
+}
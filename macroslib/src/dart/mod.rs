@@ -0,0 +1,131 @@
+mod dart_code;
+mod fclass;
+mod fenum;
+mod finterface;
+mod map_type;
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use proc_macro2::TokenStream;
+use rustc_hash::FxHashSet;
+use smol_str::SmolStr;
+
+use crate::{
+    cpp::CppContext,
+    error::{DiagnosticError, Result},
+    extension::ExtHandlers,
+    typemap::utils::remove_files_if,
+    types::ItemToExpand,
+    CppConfig, DartConfig, LanguageGenerator, SourceCode, TypeMap,
+};
+
+/// Helper functions that generated code may require,
+/// written into library file only if used
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+enum DartHelper {
+    FromStrView,
+    ToStrView,
+    TakeString,
+    RustException,
+}
+
+struct DartContext<'a> {
+    cfg: &'a DartConfig,
+    generated_foreign_files: FxHashSet<PathBuf>,
+    classes: FxHashSet<SmolStr>,
+    enums: FxHashSet<SmolStr>,
+    interfaces: FxHashSet<SmolStr>,
+    helpers: FxHashSet<DartHelper>,
+    /// Declarations of Dart classes for C structures, by name
+    structs: BTreeMap<String, String>,
+    /// Lookups of C functions used by helpers and conversations, by name
+    native_funcs: BTreeMap<String, String>,
+    /// Generated code uses `package:ffi` to allocate memory
+    uses_arena: bool,
+    parts: Vec<String>,
+}
+
+impl LanguageGenerator for DartConfig {
+    fn expand_items(
+        &self,
+        conv_map: &mut TypeMap,
+        target_pointer_width: usize,
+        code: &[SourceCode],
+        items: Vec<ItemToExpand>,
+        remove_not_generated_files: bool,
+        ext_handlers: ExtHandlers,
+    ) -> Result<Vec<TokenStream>> {
+        if !(self.output_dir.exists() && self.output_dir.is_dir()) {
+            return Err(DiagnosticError::map_any_err_to_our_err(format!(
+                "Path {} not exists or not directory",
+                self.output_dir.display()
+            )));
+        }
+
+        let mut ctx = DartContext {
+            cfg: self,
+            generated_foreign_files: FxHashSet::default(),
+            classes: FxHashSet::default(),
+            enums: FxHashSet::default(),
+            interfaces: FxHashSet::default(),
+            helpers: FxHashSet::default(),
+            structs: BTreeMap::new(),
+            native_funcs: BTreeMap::new(),
+            uses_arena: false,
+            parts: Vec::new(),
+        };
+        for item in &items {
+            match item {
+                ItemToExpand::Class(ref fclass) => {
+                    ctx.classes.insert(fclass.name.to_string().into());
+                }
+                ItemToExpand::Enum(ref fenum) => {
+                    ctx.enums.insert(fenum.name.to_string().into());
+                }
+                ItemToExpand::Interface(ref finterface) => {
+                    ctx.interfaces.insert(finterface.name.to_string().into());
+                }
+            }
+        }
+        let mut generated_c_files = FxHashSet::default();
+
+        // C headers are not required by `dart:ffi`, but they are useful
+        // for debugging and for tools like `ffigen`
+        let cpp_cfg = CppConfig::new(self.output_dir.clone(), self.library_name.clone());
+        let ret = cpp_cfg.expand_items_with_hook(
+            conv_map,
+            target_pointer_width,
+            code,
+            items,
+            remove_not_generated_files,
+            ext_handlers,
+            &mut generated_c_files,
+            |cpp_ctx: &mut CppContext, item: &ItemToExpand| -> Result<()> {
+                match item {
+                    ItemToExpand::Class(ref fclass) => fclass::generate(&mut ctx, cpp_ctx, fclass),
+                    ItemToExpand::Enum(ref fenum) => fenum::generate_enum(&mut ctx, fenum),
+                    ItemToExpand::Interface(ref finterface) => {
+                        finterface::generate_interface(&mut ctx, cpp_ctx, finterface)
+                    }
+                }
+            },
+        )?;
+
+        dart_code::generate_library_file(&mut ctx)?;
+
+        if remove_not_generated_files {
+            let generated_foreign_files = &ctx.generated_foreign_files;
+            remove_files_if(&self.output_dir, |path| {
+                if let Some(ext) = path.extension() {
+                    if ext == "dart" && !generated_foreign_files.contains(path) {
+                        return true;
+                    }
+                }
+                false
+            })
+            .map_err(DiagnosticError::map_any_err_to_our_err)?;
+        }
+
+        Ok(ret)
+    }
+}
@@ -6,14 +6,16 @@ use crate::{
 };
 use proc_macro2::Span;
 
-pub(crate) type SourceIdSpan = (SourceId, Span);
+pub type SourceIdSpan = (SourceId, Span);
 
 pub(crate) fn invalid_src_id_span() -> SourceIdSpan {
     (SourceId::none(), Span::call_site())
 }
 
+/// Error with positions in source code, that are printed
+/// as compiler does
 #[derive(Debug)]
-pub(crate) struct DiagnosticError {
+pub struct DiagnosticError {
     data: Vec<(SourceId, syn::Error)>,
 }
 
@@ -59,7 +61,7 @@ impl Display for DiagnosticError {
     }
 }
 
-pub(crate) type Result<T> = std::result::Result<T, DiagnosticError>;
+pub type Result<T> = std::result::Result<T, DiagnosticError>;
 
 pub(crate) fn panic_on_syn_error(id_of_code: &str, code: String, err: syn::Error) -> ! {
     let mut src_reg = SourceRegistry::default();
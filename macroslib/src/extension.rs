@@ -20,10 +20,38 @@ pub(crate) struct ExtHandlers<'a> {
     pub(crate) enum_ext_handlers: &'a EnumExtHandlers,
 }
 
+pub(crate) const RELEASE_GIL_DERIVE: &str = "ReleaseGil";
+pub(crate) const RELEASE_GIL_ATTR: &str = "release_gil";
+
+/// Derives of `foreign_class!` that only Python backend uses for special methods
+/// and release of GIL, other backends ignore them if there is no callback registered
+/// for them, so the same glue code can be used for all languages
+pub(crate) static PYTHON_CLASS_DERIVES: [&str; 6] = [
+    "Display",
+    "Debug",
+    "PartialEq",
+    "PartialOrd",
+    "Hash",
+    RELEASE_GIL_DERIVE,
+];
+
+/// Attributes of methods for Python special methods, they and `RELEASE_GIL_ATTR`
+/// are ignored by other backends the same way as `PYTHON_CLASS_DERIVES`
+pub(crate) static PYTHON_METHOD_ATTRS: [&str; 7] = [
+    "__str__",
+    "__repr__",
+    "__hash__",
+    "__len__",
+    "__getitem__",
+    "__iter__",
+    "__next__",
+];
+
 pub(crate) fn extend_foreign_class(
     class: &ForeignClassInfo,
     cnt: &mut Vec<u8>,
     reserved_class_derives: &[&str],
+    reserved_method_attrs: &[&str],
     class_ext_handlers: &ClassExtHandlers,
     method_ext_handlers: &MethodExtHandlers,
 ) -> Result<()> {
@@ -33,6 +61,8 @@ pub(crate) fn extend_foreign_class(
         }
         if let Some(cb) = class_ext_handlers.get(derive) {
             cb(cnt, &class.name.to_string());
+        } else if PYTHON_CLASS_DERIVES.iter().any(|x| x == derive) {
+            continue;
         } else {
             return Err(DiagnosticError::new(
                 class.src_id,
@@ -47,6 +77,9 @@ pub(crate) fn extend_foreign_class(
 
     for method in &class.methods {
         for attr in &method.unknown_attrs {
+            if reserved_method_attrs.iter().any(|x| x == attr) {
+                continue;
+            }
             if let Some(cb) = method_ext_handlers.get(attr) {
                 cb(
                     cnt,
@@ -56,6 +89,8 @@ pub(crate) fn extend_foreign_class(
                         variant: method.variant,
                     },
                 );
+            } else if attr == RELEASE_GIL_ATTR || PYTHON_METHOD_ATTRS.iter().any(|x| x == attr) {
+                continue;
             } else {
                 return Err(DiagnosticError::new(
                     class.src_id,
@@ -0,0 +1,231 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use heck::CamelCase;
+use petgraph::Direction;
+use rustc_hash::FxHashSet;
+use smol_str::SmolStr;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    cpp::{c_class_type_name, c_func_name, find_suitable_foreign_types_for_methods, CppContext},
+    error::{DiagnosticError, Result},
+    go::{
+        go_code::{self, apply_conv, go_func_name, go_ident, indent},
+        map_type::map_type,
+        GoContext,
+    },
+    namegen::new_unique_name,
+    types::{ForeignClassInfo, ForeignMethod, MethodAccess, MethodVariant},
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::go) fn generate(
+    ctx: &mut GoContext,
+    cpp_ctx: &mut CppContext,
+    class: &ForeignClassInfo,
+) -> Result<()> {
+    let f_methods = find_suitable_foreign_types_for_methods(cpp_ctx, class)?;
+    let static_only = class
+        .methods
+        .iter()
+        .all(|x| x.variant == MethodVariant::StaticMethod);
+    let unit_ty: Type = parse_type! { () };
+    let mut imports = BTreeSet::new();
+
+    let mut code = String::new();
+    if !static_only {
+        imports.insert("runtime");
+        write!(
+            &mut code,
+            r#"
+{doc_comments}type {class_name} struct {{
+	ptr   *C.{opaque}
+	owned bool
+}}
+
+func wrap{class_name}(ptr *C.{opaque}, owned bool) *{class_name} {{
+	obj := &{class_name}{{ptr: ptr, owned: owned}}
+	if owned {{
+		runtime.SetFinalizer(obj, (*{class_name}).Close)
+	}}
+	return obj
+}}
+
+// Close frees Rust object, it is also called by finalizer
+func (x *{class_name}) Close() {{
+	if x.owned && x.ptr != nil {{
+		C.{class_name}_delete(x.ptr)
+	}}
+	x.ptr = nil
+	runtime.SetFinalizer(x, nil)
+}}
+
+func (x *{class_name}) release() *C.{opaque} {{
+	if !x.owned {{
+		panic("{class_name}: can not pass ownership of not owned object")
+	}}
+	ptr := x.ptr
+	x.ptr = nil
+	x.owned = false
+	runtime.SetFinalizer(x, nil)
+	return ptr
+}}
+"#,
+            doc_comments = go_code::doc_comments_to_go_comments(&class.doc_comments, ""),
+            class_name = class.name,
+            opaque = c_class_type_name(&class.name.to_string()),
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+
+    for (method, f_method) in class.methods.iter().zip(f_methods.iter()) {
+        if method.is_dummy_constructor() {
+            continue;
+        }
+        let skip_n = match method.variant {
+            MethodVariant::Method(_) => 1,
+            _ => 0,
+        };
+        let mut known_names: FxHashSet<SmolStr> = method
+            .arg_names_without_self()
+            .map(go_ident)
+            .map(SmolStr::from)
+            .collect();
+        let receiver = new_unique_name(&known_names, "x");
+        known_names.insert(receiver.clone());
+        let ret_name = new_unique_name(&known_names, "ret");
+        known_names.insert(ret_name.clone());
+
+        let mut args_with_types = Vec::with_capacity(f_method.input.len());
+        let mut conv_deps = String::new();
+        let mut post_call = String::new();
+        let mut c_args = Vec::with_capacity(f_method.input.len() + 1);
+        if let MethodVariant::Method(_) = method.variant {
+            c_args.push(format!("{}.ptr", receiver));
+            writeln!(&mut post_call, "runtime.KeepAlive({})", receiver)
+                .expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+        for (arg, c_type) in method
+            .fn_decl
+            .inputs
+            .iter()
+            .skip(skip_n)
+            .zip(f_method.input.iter())
+        {
+            let named_arg = arg
+                .as_named_arg()
+                .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
+            let arg_ti = map_type(
+                ctx,
+                cpp_ctx,
+                &mut imports,
+                c_type,
+                &named_arg.ty,
+                Direction::Incoming,
+                (class.src_id, named_arg.ty.span()),
+            )?;
+            let arg_name = go_ident(&named_arg.name);
+            args_with_types.push(format!("{} {}", arg_name, arg_ti.name));
+            conv_deps.push_str(&apply_conv(&arg_ti.stmts, &arg_name));
+            c_args.push(apply_conv(&arg_ti.expr, &arg_name));
+            post_call.push_str(&apply_conv(&arg_ti.post, &arg_name));
+        }
+        let call = format!("C.{}({})", c_func_name(class, method), c_args.join(", "));
+
+        let (ret_decl, call_and_ret) = if method.variant == MethodVariant::Constructor {
+            (
+                format!(" *{}", class.name),
+                format!(
+                    "{ret} := {call}\n{post_call}return wrap{class_name}({ret}, true)\n",
+                    ret = ret_name,
+                    call = call,
+                    post_call = post_call,
+                    class_name = class.name,
+                ),
+            )
+        } else {
+            let ret_ty = match method.fn_decl.output {
+                syn::ReturnType::Default => &unit_ty,
+                syn::ReturnType::Type(_, ref ty) => &**ty,
+            };
+            let ret_ti = map_type(
+                ctx,
+                cpp_ctx,
+                &mut imports,
+                &f_method.output,
+                ret_ty,
+                Direction::Outgoing,
+                (class.src_id, ret_ty.span()),
+            )?;
+            if ret_ti.name.is_empty() {
+                (String::new(), format!("{}\n{}", call, post_call))
+            } else {
+                (
+                    format!(" {}", ret_ti.name),
+                    format!(
+                        "{ret} := {call}\n{post_call}{conv}return {expr}\n",
+                        ret = ret_name,
+                        call = call,
+                        post_call = post_call,
+                        conv = apply_conv(&ret_ti.stmts, &ret_name),
+                        expr = apply_conv(&ret_ti.expr, &ret_name),
+                    ),
+                )
+            }
+        };
+        let func_decl = match method.variant {
+            MethodVariant::Method(_) => format!(
+                "({receiver} *{class_name}) {method_name}",
+                receiver = receiver,
+                class_name = class.name,
+                method_name = go_func_name(method.access, &method.short_name()),
+            ),
+            MethodVariant::Constructor | MethodVariant::StaticMethod => {
+                package_func_name(class, method)
+            }
+        };
+        write!(
+            &mut code,
+            r#"
+{doc_comments}func {func_decl}({args_with_types}){ret_decl} {{
+{body}}}
+"#,
+            doc_comments = go_code::doc_comments_to_go_comments(&method.doc_comments, ""),
+            func_decl = func_decl,
+            args_with_types = args_with_types.join(", "),
+            ret_decl = ret_decl,
+            body = indent(&format!("{}{}", conv_deps, call_and_ret), "\t"),
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    code.push_str(&class.foreign_code);
+
+    go_code::write_go_file(
+        ctx,
+        &go_code::go_file_name(&class.name),
+        Some(""),
+        &imports,
+        &code,
+    )
+}
+
+/// Go has no static methods, so constructors and static methods
+/// become package level functions with class name inside function name
+fn package_func_name(class: &ForeignClassInfo, method: &ForeignMethod) -> String {
+    let short_name = method.short_name();
+    let name = match method.variant {
+        MethodVariant::Constructor if short_name == "new" => format!("New{}", class.name),
+        MethodVariant::Constructor => format!("New{}{}", class.name, short_name.to_camel_case()),
+        _ => format!("{}{}", class.name, short_name.to_camel_case()),
+    };
+    match method.access {
+        MethodAccess::Public => name,
+        MethodAccess::Private | MethodAccess::Protected => {
+            let mut chars = name.chars();
+            match chars.next() {
+                Some(first) => first.to_lowercase().chain(chars).collect(),
+                None => name,
+            }
+        }
+    }
+}
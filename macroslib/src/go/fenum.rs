@@ -0,0 +1,45 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use heck::CamelCase;
+
+use crate::{
+    error::Result,
+    go::{go_code, GoContext},
+    types::ForeignEnumInfo,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::go) fn generate_enum(ctx: &mut GoContext, fenum: &ForeignEnumInfo) -> Result<()> {
+    let mut code = String::new();
+    write!(
+        &mut code,
+        r#"
+{doc_comments}type {enum_name} uint32
+
+const (
+"#,
+        doc_comments = go_code::doc_comments_to_go_comments(&fenum.doc_comments, ""),
+        enum_name = fenum.name,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    // C++ generator uses index of item as value
+    for (i, item) in fenum.items.iter().enumerate() {
+        writeln!(
+            &mut code,
+            "{doc_comments}\t{enum_name}{item_name} {enum_name} = {index}",
+            doc_comments = go_code::doc_comments_to_go_comments(&item.doc_comments, "\t"),
+            enum_name = fenum.name,
+            item_name = item.name.to_string().to_camel_case(),
+            index = i,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    code.push_str(")\n");
+    go_code::write_go_file(
+        ctx,
+        &go_code::go_file_name(&fenum.name),
+        None,
+        &BTreeSet::new(),
+        &code,
+    )
+}
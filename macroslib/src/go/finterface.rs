@@ -0,0 +1,217 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use heck::CamelCase;
+use petgraph::Direction;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    cpp::{find_suitable_ftypes_for_interace_methods, CppContext},
+    error::{DiagnosticError, Result},
+    go::{
+        go_code::{self, apply_conv, go_ident},
+        map_type::{cgo_type, map_type},
+        GoContext,
+    },
+    types::ForeignInterface,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+/// Type for declaration of function exported from Go,
+/// it should be the same as cgo puts into `_cgo_export.h`
+fn c_export_type(c_name: &str) -> String {
+    c_name.replace("* const", "*").replace("const ", "")
+}
+
+pub(in crate::go) fn generate_interface(
+    ctx: &mut GoContext,
+    cpp_ctx: &mut CppContext,
+    interface: &ForeignInterface,
+) -> Result<()> {
+    let f_methods = find_suitable_ftypes_for_interace_methods(cpp_ctx, interface)?;
+    let unit_ty: Type = parse_type! { () };
+    let mut imports = BTreeSet::new();
+    imports.insert("runtime/cgo");
+    imports.insert("unsafe");
+
+    let deref_func = format!("go{}Deref", interface.name);
+    let mut preamble = format!("extern void {}(void *opaque);\n", deref_func);
+    let mut interface_methods = String::new();
+    let mut exported_funcs = String::new();
+    let mut c_fields = vec![
+        (
+            "opaque".to_string(),
+            "unsafe.Pointer(uintptr(cgo.NewHandle(obj)))".to_string(),
+        ),
+        (
+            format!("C_{}_deref", interface.name),
+            format!("(*[0]byte)(C.{})", deref_func),
+        ),
+    ];
+    for (method, f_method) in interface.items.iter().zip(f_methods.iter()) {
+        let method_name = method.name.to_string().to_camel_case();
+        let export_func = format!("go{}{}", interface.name, method_name);
+        let mut args_with_types = Vec::with_capacity(f_method.input.len());
+        let mut c_args_with_types = Vec::with_capacity(f_method.input.len() + 1);
+        let mut c_decl_args = Vec::with_capacity(f_method.input.len() + 1);
+        let mut conv_deps = String::new();
+        let mut call_args = Vec::with_capacity(f_method.input.len());
+        for (i, (arg, c_type)) in method
+            .fn_decl
+            .inputs
+            .iter()
+            .skip(1)
+            .zip(f_method.input.iter())
+            .enumerate()
+        {
+            let named_arg = arg
+                .as_named_arg()
+                .map_err(|err| DiagnosticError::from_syn_err(interface.src_id, err))?;
+            let arg_ti = map_type(
+                ctx,
+                cpp_ctx,
+                &mut imports,
+                c_type,
+                &named_arg.ty,
+                Direction::Outgoing,
+                (interface.src_id, named_arg.ty.span()),
+            )?;
+            if arg_ti.returns_early {
+                return Err(DiagnosticError::new(
+                    interface.src_id,
+                    named_arg.ty.span(),
+                    "Go: type can not be used as callback argument",
+                ));
+            }
+            let c_arg_name = format!("a{}", i);
+            args_with_types.push(format!("{} {}", go_ident(&named_arg.name), arg_ti.name));
+            c_args_with_types.push(format!("{} {}", c_arg_name, cgo_type(&c_type.base.name)));
+            c_decl_args.push(format!(
+                "{} {}",
+                c_export_type(&c_type.base.name),
+                c_arg_name
+            ));
+            conv_deps.push_str(&apply_conv(&arg_ti.stmts, &c_arg_name));
+            call_args.push(apply_conv(&arg_ti.expr, &c_arg_name));
+        }
+        c_args_with_types.push("opaque unsafe.Pointer".into());
+        c_decl_args.push("void *opaque".into());
+
+        let ret_ty = match method.fn_decl.output {
+            syn::ReturnType::Default => &unit_ty,
+            syn::ReturnType::Type(_, ref ty) => &**ty,
+        };
+        let ret_ti = map_type(
+            ctx,
+            cpp_ctx,
+            &mut imports,
+            &f_method.output,
+            ret_ty,
+            Direction::Incoming,
+            (interface.src_id, ret_ty.span()),
+        )?;
+        let call = format!("obj.{}({})", method_name, call_args.join(", "));
+        let c_ret_name = f_method.output.base.name.as_str();
+        let (ret_decl, c_ret_decl, body) = if ret_ti.name.is_empty() {
+            (String::new(), String::new(), format!("{}\n", call))
+        } else {
+            // value returned to Rust should not point to Go memory
+            if !ret_ti.stmts.is_empty()
+                || !ret_ti.post.is_empty()
+                || c_ret_name.starts_with("struct ")
+            {
+                return Err(DiagnosticError::new(
+                    interface.src_id,
+                    ret_ty.span(),
+                    "Go: type can not be returned from callback",
+                ));
+            }
+            (
+                format!(" {}", ret_ti.name),
+                format!(" {}", cgo_type(c_ret_name)),
+                format!(
+                    "ret := {}\nreturn {}\n",
+                    call,
+                    apply_conv(&ret_ti.expr, "ret")
+                ),
+            )
+        };
+
+        writeln!(
+            &mut interface_methods,
+            "{doc_comments}\t{method_name}({args_with_types}){ret_decl}",
+            doc_comments = go_code::doc_comments_to_go_comments(&method.doc_comments, "\t"),
+            method_name = method_name,
+            args_with_types = args_with_types.join(", "),
+            ret_decl = ret_decl,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(
+            &mut preamble,
+            "extern {c_ret} {export_func}({c_args});",
+            c_ret = if ret_ti.name.is_empty() {
+                "void"
+            } else {
+                c_ret_name
+            },
+            export_func = export_func,
+            c_args = c_decl_args.join(", "),
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        write!(
+            &mut exported_funcs,
+            r#"
+//export {export_func}
+func {export_func}({c_args_with_types}){c_ret_decl} {{
+	obj := cgo.Handle(uintptr(opaque)).Value().({interface_name})
+{body}}}
+"#,
+            export_func = export_func,
+            c_args_with_types = c_args_with_types.join(", "),
+            c_ret_decl = c_ret_decl,
+            interface_name = interface.name,
+            body = go_code::indent(&format!("{}{}", conv_deps, body), "\t"),
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        c_fields.push((
+            method.name.to_string(),
+            format!("(*[0]byte)(C.{})", export_func),
+        ));
+    }
+
+    let mut code = String::new();
+    write!(
+        &mut code,
+        r#"
+{doc_comments}type {interface_name} interface {{
+{interface_methods}}}
+
+//export {deref_func}
+func {deref_func}(opaque unsafe.Pointer) {{
+	cgo.Handle(uintptr(opaque)).Delete()
+}}
+{exported_funcs}
+// go{interface_name}ToC creates C structure that holds handle to obj,
+// handle deleted when Rust side drops callback
+func go{interface_name}ToC(obj {interface_name}) C.struct_C_{interface_name} {{
+	return C.struct_C_{interface_name}{{
+"#,
+        doc_comments = go_code::doc_comments_to_go_comments(&interface.doc_comments, ""),
+        interface_name = interface.name,
+        interface_methods = interface_methods,
+        deref_func = deref_func,
+        exported_funcs = exported_funcs,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for (field, value) in &c_fields {
+        writeln!(&mut code, "\t\t{}: {},", field, value).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    code.push_str("\t}\n}\n");
+
+    go_code::write_go_file(
+        ctx,
+        &go_code::go_file_name(&interface.name),
+        Some(&preamble),
+        &imports,
+        &code,
+    )
+}
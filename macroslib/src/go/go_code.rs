@@ -0,0 +1,189 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use heck::{CamelCase, MixedCase, SnakeCase};
+
+use crate::{
+    error::{DiagnosticError, Result},
+    file_cache::FileWriteCache,
+    go::{GoContext, GoHelper},
+    types::MethodAccess,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+/// Words that can not be used as identifiers in Go
+static GO_KEYWORDS: [&str; 25] = [
+    "break",
+    "case",
+    "chan",
+    "const",
+    "continue",
+    "default",
+    "defer",
+    "else",
+    "fallthrough",
+    "for",
+    "func",
+    "go",
+    "goto",
+    "if",
+    "import",
+    "interface",
+    "map",
+    "package",
+    "range",
+    "return",
+    "select",
+    "struct",
+    "switch",
+    "type",
+    "var",
+];
+
+pub(in crate::go) fn go_ident(name: &str) -> String {
+    if GO_KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.into()
+    }
+}
+
+/// Name of function or method, exported from package only if `access` is public
+pub(in crate::go) fn go_func_name(access: MethodAccess, rust_name: &str) -> String {
+    match access {
+        MethodAccess::Public => rust_name.to_camel_case(),
+        MethodAccess::Private | MethodAccess::Protected => go_ident(&rust_name.to_mixed_case()),
+    }
+}
+
+pub(in crate::go) fn go_file_name(type_name: &dyn std::fmt::Display) -> String {
+    format!("{}.go", type_name.to_string().to_snake_case())
+}
+
+pub(in crate::go) fn doc_comments_to_go_comments(doc_comments: &[String], indent: &str) -> String {
+    let mut comments = String::new();
+    for comment in doc_comments {
+        writeln!(&mut comments, "{}// {}", indent, comment.trim()).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    comments
+}
+
+/// Substitute variable into conversation template,
+/// `$p` - variable itself, `$p_xyz` - names for temporary values
+pub(in crate::go) fn apply_conv(template: &str, var_name: &str) -> String {
+    template.replace("$p", var_name)
+}
+
+/// Add `prefix` to all not empty lines of `code`
+pub(in crate::go) fn indent(code: &str, prefix: &str) -> String {
+    let mut ret = String::with_capacity(code.len());
+    for line in code.lines() {
+        if !line.is_empty() {
+            ret.push_str(prefix);
+        }
+        ret.push_str(line);
+        ret.push('\n');
+    }
+    ret
+}
+
+/// Write Go file, `preamble` is C code for cgo,
+/// if `preamble` is `None` file does not use cgo
+pub(in crate::go) fn write_go_file(
+    ctx: &mut GoContext,
+    file_name: &str,
+    preamble: Option<&str>,
+    imports: &BTreeSet<&'static str>,
+    body: &str,
+) -> Result<()> {
+    let mut code = format!(
+        "// Automatically generated by flapigen\npackage {}\n",
+        ctx.cfg.package_name
+    );
+    if let Some(preamble) = preamble {
+        write!(
+            &mut code,
+            r#"
+/*
+#include "{package}.h"
+{preamble}*/
+import "C"
+"#,
+            preamble = preamble,
+            package = ctx.cfg.package_name,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    match imports.len() {
+        0 => {}
+        1 => {
+            for import in imports {
+                write!(&mut code, "\nimport \"{}\"\n", import).expect(WRITE_TO_MEM_FAILED_MSG);
+            }
+        }
+        _ => {
+            code.push_str("\nimport (\n");
+            for import in imports {
+                writeln!(&mut code, "\t\"{}\"", import).expect(WRITE_TO_MEM_FAILED_MSG);
+            }
+            code.push_str(")\n");
+        }
+    }
+    code.push_str(body);
+
+    let path = ctx.cfg.output_dir.join(file_name);
+    let mut file = FileWriteCache::new(&path, &mut ctx.generated_foreign_files);
+    file.replace_content(code.into_bytes());
+    file.update_file_if_necessary().map_err(|err| {
+        DiagnosticError::map_any_err_to_our_err(format!(
+            "write to {} failed: {}",
+            path.display(),
+            err
+        ))
+    })
+}
+
+/// File with linker flags and helper functions that generated code uses
+pub(in crate::go) fn generate_support_file(ctx: &mut GoContext) -> Result<()> {
+    let mut helpers: Vec<GoHelper> = ctx.helpers.iter().cloned().collect();
+    helpers.sort();
+    let mut imports = BTreeSet::new();
+    let mut body = String::new();
+    for helper in helpers {
+        body.push_str(match helper {
+            GoHelper::BoolToC => {
+                r#"
+func goBoolToC(b bool) C.char {
+	if b {
+		return 1
+	}
+	return 0
+}
+"#
+            }
+            GoHelper::TakeString => {
+                r#"
+func goTakeString(s C.struct_CRustString) string {
+	ret := C.GoStringN(s.data, C.int(s.len))
+	C.crust_string_free(s)
+	return ret
+}
+"#
+            }
+            GoHelper::RustError => {
+                imports.insert("fmt");
+                r#"
+// RustError is returned when error type of Rust function is not string
+type RustError struct {
+	Value interface{}
+}
+
+func (e *RustError) Error() string {
+	return fmt.Sprint(e.Value)
+}
+"#
+            }
+        });
+    }
+    let preamble = format!("#cgo LDFLAGS: -l{}\n", ctx.cfg.native_lib_name);
+    write_go_file(ctx, "swig_support.go", Some(&preamble), &imports, &body)
+}
@@ -0,0 +1,487 @@
+use std::collections::BTreeSet;
+
+use petgraph::Direction;
+use syn::Type;
+
+use crate::{
+    cpp::{c_class_type_name, map_type as map_c_type, CppContext, CppForeignTypeInfo},
+    error::{DiagnosticError, Result, SourceIdSpan},
+    go::{
+        go_code::{apply_conv, indent},
+        GoContext, GoHelper,
+    },
+    typemap::ast::DisplayToTokens,
+};
+
+/// Go type and templates to convert value between Go and C,
+/// see `apply_conv` for template syntax
+#[derive(Debug)]
+pub(in crate::go) struct GoTypeInfo {
+    pub(in crate::go) name: String,
+    /// Statements to execute before usage of `expr`
+    pub(in crate::go) stmts: String,
+    pub(in crate::go) expr: String,
+    /// Statements to execute after C function call
+    pub(in crate::go) post: String,
+    /// `stmts` may return from function, used for `Result`
+    pub(in crate::go) returns_early: bool,
+}
+
+impl GoTypeInfo {
+    fn expr(name: String, expr: String) -> Self {
+        GoTypeInfo {
+            name,
+            stmts: String::new(),
+            expr,
+            post: String::new(),
+            returns_early: false,
+        }
+    }
+    fn with_stmts(name: String, stmts: String, expr: String) -> Self {
+        GoTypeInfo {
+            name,
+            stmts,
+            expr,
+            post: String::new(),
+            returns_early: false,
+        }
+    }
+}
+
+fn primitive_go_type(c_name: &str) -> Option<&'static str> {
+    let ty = match c_name {
+        "int8_t" => "int8",
+        "uint8_t" => "uint8",
+        "int16_t" => "int16",
+        "uint16_t" => "uint16",
+        "int32_t" => "int32",
+        "uint32_t" => "uint32",
+        "int64_t" => "int64",
+        "uint64_t" => "uint64",
+        "intptr_t" => "int",
+        "uintptr_t" => "uint",
+        "float" => "float32",
+        "double" => "float64",
+        _ => return None,
+    };
+    Some(ty)
+}
+
+/// How cgo names C type, for example `struct CRustStrView` -> `C.struct_CRustStrView`
+pub(in crate::go) fn cgo_type(c_name: &str) -> String {
+    let c_name = c_name.trim();
+    if let Some(pointee) = c_name
+        .strip_suffix("* const")
+        .or_else(|| c_name.strip_suffix('*'))
+    {
+        return format!("*{}", cgo_type(pointee));
+    }
+    let c_name = c_name.trim_start_matches("const ").trim();
+    match c_name.strip_prefix("struct ") {
+        Some(struct_name) => format!("C.struct_{}", struct_name.trim()),
+        None => format!("C.{}", c_name),
+    }
+}
+
+/// Generic arguments of type like `Option<T>`, `Result<T, E>` or `&[T]`
+fn generic_args(ty: &Type) -> Vec<&Type> {
+    match ty {
+        Type::Reference(ref r) => match *r.elem {
+            Type::Slice(ref s) => vec![&*s.elem],
+            _ => Vec::new(),
+        },
+        Type::Path(ref p) => match p.path.segments.last().map(|x| &x.arguments) {
+            Some(syn::PathArguments::AngleBracketed(ref args)) => args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(ref ty) => Some(ty),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+fn is_unit_type(ty: &Type) -> bool {
+    matches!(ty, Type::Tuple(ref t) if t.elems.is_empty())
+}
+
+/// Map `rust_ty` (as it written in `foreign_class!`) that C++ generator
+/// mapped to C type `c_type` to Go type
+pub(in crate::go) fn map_type(
+    ctx: &mut GoContext,
+    cpp_ctx: &mut CppContext,
+    imports: &mut BTreeSet<&'static str>,
+    c_type: &CppForeignTypeInfo,
+    rust_ty: &Type,
+    direction: Direction,
+    span: SourceIdSpan,
+) -> Result<GoTypeInfo> {
+    let c_name = c_type.base.name.as_str();
+    let cpp_name = c_type.cpp_converter.as_ref().map(|x| x.typename.as_str());
+
+    if c_name == "void" {
+        return Ok(GoTypeInfo::expr(String::new(), "$p".into()));
+    }
+    if c_name == "char" && cpp_name == Some("bool") {
+        return Ok(match direction {
+            Direction::Outgoing => GoTypeInfo::expr("bool".into(), "($p != 0)".into()),
+            Direction::Incoming => {
+                ctx.helpers.insert(GoHelper::BoolToC);
+                GoTypeInfo::expr("bool".into(), "goBoolToC($p)".into())
+            }
+        });
+    }
+    if let Some(enum_name) = cpp_name.filter(|x| ctx.enums.contains(*x)) {
+        return Ok(GoTypeInfo::expr(
+            enum_name.into(),
+            match direction {
+                Direction::Outgoing => format!("{}($p)", enum_name),
+                Direction::Incoming => format!("{}($p)", cgo_type(c_name)),
+            },
+        ));
+    }
+    if let Some(ty) = primitive_go_type(c_name) {
+        return Ok(GoTypeInfo::expr(
+            ty.into(),
+            match direction {
+                Direction::Outgoing => format!("{}($p)", ty),
+                Direction::Incoming => format!("{}($p)", cgo_type(c_name)),
+            },
+        ));
+    }
+
+    let opaque_name = c_name
+        .trim_start_matches("const ")
+        .trim_end_matches('*')
+        .trim();
+    if let Some(class_name) = ctx
+        .classes
+        .iter()
+        .find(|class_name| c_class_type_name(class_name) == opaque_name)
+    {
+        let is_ref = matches!(rust_ty, Type::Reference(_));
+        let name = format!("*{}", class_name);
+        return Ok(match direction {
+            Direction::Outgoing => GoTypeInfo::expr(
+                name,
+                format!(
+                    "wrap{}($p, {})",
+                    class_name,
+                    !is_ref && !c_name.starts_with("const ")
+                ),
+            ),
+            Direction::Incoming if is_ref => {
+                imports.insert("runtime");
+                GoTypeInfo {
+                    name,
+                    stmts: String::new(),
+                    expr: "$p.ptr".into(),
+                    post: "runtime.KeepAlive($p)\n".into(),
+                    returns_early: false,
+                }
+            }
+            Direction::Incoming => GoTypeInfo::expr(name, "$p.release()".into()),
+        });
+    }
+
+    if let Some(interface_name) = ctx
+        .interfaces
+        .iter()
+        .find(|name| c_name == format!("const struct C_{} * const", name))
+    {
+        if direction == Direction::Incoming {
+            return Ok(GoTypeInfo::with_stmts(
+                interface_name.to_string(),
+                format!("$p_c := go{}ToC($p)\n", interface_name),
+                "&$p_c".into(),
+            ));
+        }
+    }
+
+    let c_struct_name = c_name.trim_start_matches("struct ");
+    match (c_struct_name, direction) {
+        ("CRustStrView", Direction::Outgoing) => {
+            return Ok(GoTypeInfo::expr(
+                "string".into(),
+                "C.GoStringN($p.data, C.int($p.len))".into(),
+            ));
+        }
+        ("CRustStrView", Direction::Incoming) => {
+            imports.insert("unsafe");
+            return Ok(GoTypeInfo::expr(
+                "string".into(),
+                "C.struct_CRustStrView{data: (*C.char)(unsafe.Pointer(unsafe.StringData($p))), len: C.uintptr_t(len($p))}".into(),
+            ));
+        }
+        ("CRustString", Direction::Outgoing) => {
+            ctx.helpers.insert(GoHelper::TakeString);
+            return Ok(GoTypeInfo::expr("string".into(), "goTakeString($p)".into()));
+        }
+        _ => {}
+    }
+
+    let not_supported = || {
+        DiagnosticError::new2(
+            span,
+            format!(
+                "Go: type {} (C type {}) is not supported as {}",
+                DisplayToTokens(rust_ty),
+                c_name,
+                match direction {
+                    Direction::Outgoing => "output",
+                    Direction::Incoming => "input",
+                }
+            ),
+        )
+    };
+    let map_inner = |ctx: &mut GoContext,
+                     cpp_ctx: &mut CppContext,
+                     imports: &mut BTreeSet<&'static str>,
+                     inner_ty: &Type| {
+        let inner_rty = cpp_ctx.conv_map.find_or_alloc_rust_type(inner_ty, span.0);
+        let inner_c_type = map_c_type(cpp_ctx, &inner_rty, direction, span)?;
+        let inner = map_type(
+            ctx,
+            cpp_ctx,
+            imports,
+            &inner_c_type,
+            inner_ty,
+            direction,
+            span,
+        )?;
+        if inner.returns_early {
+            return Err(not_supported());
+        }
+        Ok((inner, cgo_type(&inner_c_type.base.name)))
+    };
+    let args = generic_args(rust_ty);
+
+    if c_struct_name.starts_with("CRustClassOpt") && direction == Direction::Incoming {
+        let class_name = c_struct_name
+            .trim_start_matches("CRustClassOptMut")
+            .trim_start_matches("CRustClassOpt");
+        if ctx.classes.contains(class_name) {
+            imports.insert("runtime");
+            imports.insert("unsafe");
+            return Ok(GoTypeInfo {
+                name: format!("*{}", class_name),
+                stmts: format!(
+                    r#"var $p_ptr *C.{opaque}
+if $p != nil {{
+	$p_ptr = $p.ptr
+}}
+"#,
+                    opaque = c_class_type_name(class_name)
+                ),
+                expr: format!("C.struct_{}{{p: unsafe.Pointer($p_ptr)}}", c_struct_name),
+                post: "runtime.KeepAlive($p)\n".into(),
+                returns_early: false,
+            });
+        }
+    }
+
+    if c_struct_name.starts_with("CRustOption") {
+        let inner_ty = args.first().copied().ok_or_else(not_supported)?;
+        let (inner, inner_cgo) = map_inner(ctx, cpp_ctx, imports, inner_ty)?;
+        imports.insert("unsafe");
+        let by_ptr = inner.name.starts_with('*');
+        let name = if by_ptr {
+            inner.name.clone()
+        } else {
+            format!("*{}", inner.name)
+        };
+        return Ok(match direction {
+            Direction::Outgoing => {
+                let mut stmts = format!(
+                    "var $p_v {name}\nif $p.is_some != 0 {{\n\t$p_data := *(*{inner_cgo})(unsafe.Pointer(&$p.val))\n",
+                    name = name,
+                    inner_cgo = inner_cgo,
+                );
+                stmts.push_str(&indent(&apply_conv(&inner.stmts, "$p_data"), "\t"));
+                let inner_expr = apply_conv(&inner.expr, "$p_data");
+                if by_ptr {
+                    stmts.push_str(&format!("\t$p_v = {}\n}}\n", inner_expr));
+                } else {
+                    stmts.push_str(&format!(
+                        "\t$p_tmp := {}\n\t$p_v = &$p_tmp\n}}\n",
+                        inner_expr
+                    ));
+                }
+                GoTypeInfo::with_stmts(name, stmts, "$p_v".into())
+            }
+            Direction::Incoming => {
+                imports.insert("runtime");
+                let mut stmts = format!(
+                    "var $p_c C.struct_{c_struct}\nif $p != nil {{\n\t$p_v := {deref}$p\n",
+                    c_struct = c_struct_name,
+                    deref = if by_ptr { "" } else { "*" },
+                );
+                stmts.push_str(&indent(&apply_conv(&inner.stmts, "$p_v"), "\t"));
+                stmts.push_str(&format!(
+                    "\t*(*{inner_cgo})(unsafe.Pointer(&$p_c.val)) = {inner_expr}\n\t$p_c.is_some = 1\n}}\n",
+                    inner_cgo = inner_cgo,
+                    inner_expr = apply_conv(&inner.expr, "$p_v"),
+                ));
+                GoTypeInfo {
+                    name,
+                    stmts,
+                    expr: "$p_c".into(),
+                    post: "runtime.KeepAlive($p)\n".into(),
+                    returns_early: false,
+                }
+            }
+        });
+    }
+
+    if (c_struct_name.starts_with("CRustResult") || c_struct_name.starts_with("CRustVoidOkResult"))
+        && direction == Direction::Outgoing
+    {
+        let (ok_ty, err_ty) = match args.as_slice() {
+            [ok_ty, err_ty] => (*ok_ty, *err_ty),
+            _ => return Err(not_supported()),
+        };
+        let (err, err_cgo) = map_inner(ctx, cpp_ctx, imports, err_ty)?;
+        imports.insert("unsafe");
+        let err_expr = apply_conv(&err.expr, "$p_err");
+        let err_expr = if err.name == "string" {
+            imports.insert("errors");
+            format!("errors.New({})", err_expr)
+        } else {
+            ctx.helpers.insert(GoHelper::RustError);
+            format!("&RustError{{Value: {}}}", err_expr)
+        };
+        let ok = if is_unit_type(ok_ty) {
+            None
+        } else {
+            Some(map_inner(ctx, cpp_ctx, imports, ok_ty)?)
+        };
+        let mut stmts = format!(
+            "if $p.is_ok == 0 {{\n\t$p_err := *(*{err_cgo})(unsafe.Pointer(&$p.data))\n",
+            err_cgo = err_cgo
+        );
+        stmts.push_str(&indent(&apply_conv(&err.stmts, "$p_err"), "\t"));
+        return Ok(match ok {
+            Some((ok, ok_cgo)) => {
+                stmts.push_str(&format!(
+                    "\treturn *new({ok_name}), {err_expr}\n}}\n$p_ok := *(*{ok_cgo})(unsafe.Pointer(&$p.data))\n",
+                    ok_name = ok.name,
+                    err_expr = err_expr,
+                    ok_cgo = ok_cgo,
+                ));
+                stmts.push_str(&apply_conv(&ok.stmts, "$p_ok"));
+                GoTypeInfo {
+                    name: format!("({}, error)", ok.name),
+                    stmts,
+                    expr: format!("{}, nil", apply_conv(&ok.expr, "$p_ok")),
+                    post: String::new(),
+                    returns_early: true,
+                }
+            }
+            None => {
+                stmts.push_str(&format!("\treturn {}\n}}\n", err_expr));
+                GoTypeInfo {
+                    name: "error".into(),
+                    stmts,
+                    expr: "nil".into(),
+                    post: String::new(),
+                    returns_early: true,
+                }
+            }
+        });
+    }
+
+    if c_struct_name.starts_with("CRustVec") && direction == Direction::Outgoing {
+        let inner_ty = args.first().copied().ok_or_else(not_supported)?;
+        let (inner, _) = map_inner(ctx, cpp_ctx, imports, inner_ty)?;
+        if !is_primitive_name(&inner.name) {
+            return Err(not_supported());
+        }
+        imports.insert("unsafe");
+        return Ok(GoTypeInfo::with_stmts(
+            format!("[]{}", inner.name),
+            format!(
+                "$p_v := append([]{elem}(nil), unsafe.Slice((*{elem})(unsafe.Pointer($p.data)), int($p.len))...)\nC.{vec}_free($p)\n",
+                elem = inner.name,
+                vec = c_struct_name,
+            ),
+            "$p_v".into(),
+        ));
+    }
+
+    if c_struct_name == "CRustForeignVec" && direction == Direction::Outgoing {
+        let vec_name = cpp_name.ok_or_else(not_supported)?;
+        let inner_ty = args.first().copied().ok_or_else(not_supported)?;
+        let (inner, _) = map_inner(ctx, cpp_ctx, imports, inner_ty)?;
+        let class_name = inner.name.trim_start_matches('*');
+        if !ctx.classes.contains(class_name) {
+            return Err(not_supported());
+        }
+        return Ok(GoTypeInfo::with_stmts(
+            format!("[]{}", inner.name),
+            format!(
+                r#"$p_v := make([]{elem}, int($p.len))
+for i := len($p_v) - 1; i >= 0; i-- {{
+	$p_v[i] = wrap{class}((*C.{opaque})(C.{vec}_remove(&$p, C.uintptr_t(i))), true)
+}}
+C.{vec}_free($p)
+"#,
+                elem = inner.name,
+                class = class_name,
+                opaque = c_class_type_name(class_name),
+                vec = vec_name,
+            ),
+            "$p_v".into(),
+        ));
+    }
+
+    if c_struct_name.starts_with("CRustSlice") {
+        let inner_ty = args.first().copied().ok_or_else(not_supported)?;
+        let (inner, inner_cgo) = map_inner(ctx, cpp_ctx, imports, inner_ty)?;
+        if is_primitive_name(&inner.name) {
+            imports.insert("unsafe");
+            let name = format!("[]{}", inner.name);
+            return Ok(match direction {
+                Direction::Outgoing => GoTypeInfo::expr(
+                    name,
+                    format!(
+                        "append([]{elem}(nil), unsafe.Slice((*{elem})(unsafe.Pointer($p.data)), int($p.len))...)",
+                        elem = inner.name
+                    ),
+                ),
+                Direction::Incoming => GoTypeInfo::expr(
+                    name,
+                    format!(
+                        "C.struct_{slice}{{data: (*{elem_cgo})(unsafe.Pointer(unsafe.SliceData($p))), len: C.uintptr_t(len($p))}}",
+                        slice = c_struct_name,
+                        elem_cgo = inner_cgo,
+                    ),
+                ),
+            });
+        }
+    }
+
+    Err(not_supported())
+}
+
+/// Go type that has the same memory layout as C type
+fn is_primitive_name(go_name: &str) -> bool {
+    matches!(
+        go_name,
+        "int8"
+            | "uint8"
+            | "int16"
+            | "uint16"
+            | "int32"
+            | "uint32"
+            | "int64"
+            | "uint64"
+            | "int"
+            | "uint"
+            | "float32"
+            | "float64"
+    )
+}
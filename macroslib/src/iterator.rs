@@ -0,0 +1,273 @@
+//! Iterators returned by `foreign_class!` methods.
+//! For every type of elements class is generated, like it was described
+//! by `foreign_class!`, it wraps boxed Rust iterator, so elements are converted
+//! to foreign language one by one, when foreign code asks for the next one.
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, quote_spanned, ToTokens};
+use rustc_hash::{FxHashMap, FxHashSet};
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    closure::{boxed_trait_object_bounds, mangle_tokens},
+    code_parse::parse_foreigner_class,
+    error::{invalid_src_id_span, DiagnosticError, Result},
+    source_registry::SourceId,
+    typemap::{
+        ast::{normalize_type, DisplayToTokens},
+        TypeConvCode, TypeMap, FROM_VAR_TEMPLATE, TO_VAR_TEMPLATE,
+    },
+    types::{ForeignClassInfo, MethodVariant},
+    LanguageConfig,
+};
+
+/// Classes generated for iterators of all `foreign_class!`,
+/// iterators with the same type share class
+#[derive(Default)]
+pub(crate) struct IteratorClasses {
+    /// boxed iterator type -> name of class
+    classes: FxHashMap<String, String>,
+    names: FxHashSet<String>,
+}
+
+/// Class for new iterator type and Rust code of its `self_type`
+pub(crate) struct IteratorClass {
+    pub(crate) class: ForeignClassInfo,
+    pub(crate) rust_code: TokenStream,
+}
+
+impl IteratorClasses {
+    /// Replace `impl Iterator` output with `Box<dyn Iterator>`, register conversion
+    /// of boxed iterators to classes and return classes for not seen before types
+    pub(crate) fn expand_class(
+        &mut self,
+        config: &LanguageConfig,
+        conv_map: &mut TypeMap,
+        fclass: &mut ForeignClassInfo,
+    ) -> Result<Vec<IteratorClass>> {
+        let src_id = fclass.src_id;
+        let mut ret = vec![];
+        for method in &mut fclass.methods {
+            if method.is_async || method.variant == MethodVariant::Constructor {
+                continue;
+            }
+            let impl_iterator = match method.fn_decl.output {
+                syn::ReturnType::Type(_, ref ty) => match **ty {
+                    Type::ImplTrait(ref impl_trait)
+                        if iterator_item(&impl_trait.bounds).is_some() =>
+                    {
+                        Some((impl_trait.bounds.clone(), impl_trait.span()))
+                    }
+                    _ => None,
+                },
+                syn::ReturnType::Default => continue,
+            };
+            if let Some((bounds, span)) = impl_iterator {
+                let boxed_ty: Type = parse_spanned!(span, Box<dyn #bounds>)
+                    .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+                // Rust function returns `impl Iterator`, so box its result
+                let call = match method.inline_block.take() {
+                    Some(block) => block.into_token_stream(),
+                    None => {
+                        let rust_path = &method.rust_id;
+                        let mut args = method
+                            .arg_names_without_self()
+                            .map(|name| Ident::new(name, span))
+                            .collect::<Vec<_>>();
+                        if let MethodVariant::Method(_) = method.variant {
+                            args.insert(0, Ident::new("this", span));
+                        }
+                        quote_spanned! { span => #rust_path(#(#args),*) }
+                    }
+                };
+                method.inline_block = Some(
+                    parse_spanned!(span, {
+                        let ret: #boxed_ty = Box::new(#call);
+                        ret
+                    })
+                    .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?,
+                );
+                if let syn::ReturnType::Type(_, ref mut ty) = method.fn_decl.output {
+                    **ty = boxed_ty;
+                }
+            }
+            let ret_ty = match method.fn_decl.output {
+                syn::ReturnType::Type(_, ref ty) => &**ty,
+                syn::ReturnType::Default => continue,
+            };
+            let bounds = match boxed_trait_object_bounds(ret_ty) {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+            let item = match iterator_item(bounds) {
+                Some(item) => item,
+                None => continue,
+            };
+            check_iterator_bounds(src_id, bounds)?;
+
+            let boxed_ty_name = normalize_type(ret_ty);
+            if self.classes.contains_key(boxed_ty_name) {
+                continue;
+            }
+            let name = self.new_class_name(item);
+            self.classes.insert(boxed_ty_name.to_string(), name.clone());
+            let iter_class = generate_class(config, src_id, &name, ret_ty, item)?;
+
+            let boxed_iter_ty = conv_map.find_or_alloc_rust_type(ret_ty, src_id);
+            let self_type = self_type_name(&name);
+            let class_ty = conv_map.find_or_alloc_rust_type(&parse_type! { #self_type }, src_id);
+            conv_map.add_conversation_rule(
+                boxed_iter_ty.to_idx(),
+                class_ty.to_idx(),
+                TypeConvCode::new2(
+                    format!(
+                        "let {to_var}: {self_type} = {self_type}({from_var}.peekable());",
+                        to_var = TO_VAR_TEMPLATE,
+                        from_var = FROM_VAR_TEMPLATE,
+                        self_type = self_type,
+                    ),
+                    invalid_src_id_span(),
+                )
+                .into(),
+            );
+            ret.push(iter_class);
+        }
+        Ok(ret)
+    }
+
+    /// Name derived from type of elements, like `IterI32` for `Iterator<Item = i32>`
+    fn new_class_name(&mut self, item: &Type) -> String {
+        let mut name = "Iter".to_string();
+        mangle_tokens(item.to_token_stream(), &mut name);
+        let mut unique_name = name.clone();
+        let mut idx = 2;
+        while self.names.contains(&unique_name) {
+            unique_name = format!("{}{}", name, idx);
+            idx += 1;
+        }
+        self.names.insert(unique_name.clone());
+        unique_name
+    }
+}
+
+fn self_type_name(name: &str) -> Ident {
+    Ident::new(&format!("Swig{}", name), proc_macro2::Span::call_site())
+}
+
+fn generate_class(
+    config: &LanguageConfig,
+    src_id: SourceId,
+    name: &str,
+    boxed_iter_ty: &Type,
+    item: &Type,
+) -> Result<IteratorClass> {
+    let span = boxed_iter_ty.span();
+    let name_ident = Ident::new(name, span);
+    let self_type = self_type_name(name);
+    // Java's `Iterator::next` returns element, so `Option` can not be used,
+    // other languages check `Option` to find end of iteration
+    let (methods, rust_methods) = match config {
+        LanguageConfig::JavaConfig(_) => (
+            quote! {
+                private fn #self_type::has_next(&mut self) -> bool; alias hasNextElement;
+                private fn #self_type::next_element(&mut self) -> #item; alias nextElement;
+            },
+            quote! {
+                fn has_next(&mut self) -> bool {
+                    self.0.peek().is_some()
+                }
+                fn next_element(&mut self) -> #item {
+                    self.0.next().expect("no more elements in iterator")
+                }
+            },
+        ),
+        _ => (
+            // attribute is used by Python, other languages ignore it
+            quote! {
+                #[__next__]
+                fn #self_type::next(&mut self) -> Option<#item>;
+            },
+            quote! {
+                fn next(&mut self) -> Option<#item> {
+                    self.0.next()
+                }
+            },
+        ),
+    };
+    let class = quote_spanned! { span =>
+        /// Rust iterator, elements are converted on demand
+        class #name_ident {
+            self_type #self_type;
+            private constructor = empty -> #self_type;
+            #methods
+        }
+    };
+    let mut class = parse_foreigner_class(src_id, config, class)?;
+    class.iterator_type = Some(boxed_iter_ty.clone());
+    let rust_code = quote! {
+        pub struct #self_type(::std::iter::Peekable<#boxed_iter_ty>);
+        impl #self_type {
+            #rust_methods
+        }
+    };
+    Ok(IteratorClass { class, rust_code })
+}
+
+/// Type of `Item` of `Iterator<Item = T>` bound
+fn iterator_item(
+    bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>,
+) -> Option<&Type> {
+    bounds.iter().find_map(|bound| match bound {
+        syn::TypeParamBound::Trait(trait_bound) => {
+            let last = trait_bound.path.segments.last()?;
+            if last.ident != "Iterator" {
+                return None;
+            }
+            match last.arguments {
+                syn::PathArguments::AngleBracketed(ref args) => {
+                    args.args.iter().find_map(|arg| match arg {
+                        syn::GenericArgument::Binding(binding) if binding.ident == "Item" => {
+                            Some(&binding.ty)
+                        }
+                        _ => None,
+                    })
+                }
+                _ => None,
+            }
+        }
+        syn::TypeParamBound::Lifetime(_) => None,
+    })
+}
+
+fn check_iterator_bounds(
+    src_id: SourceId,
+    bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>,
+) -> Result<()> {
+    for bound in bounds {
+        match bound {
+            syn::TypeParamBound::Trait(trait_bound) => {
+                let is_iterator = matches!(trait_bound.path.segments.last(),
+                                           Some(last) if last.ident == "Iterator");
+                if !is_iterator && !trait_bound.path.is_ident("Send") {
+                    return Err(DiagnosticError::new(
+                        src_id,
+                        trait_bound.span(),
+                        "Supported only Send trait as additional bound of iterator",
+                    ));
+                }
+            }
+            syn::TypeParamBound::Lifetime(lifetime) if lifetime.ident != "static" => {
+                return Err(DiagnosticError::new(
+                    src_id,
+                    lifetime.span(),
+                    format!(
+                        "Iterator is owned by foreign code, so it can not borrow data with lifetime {}",
+                        DisplayToTokens(lifetime)
+                    ),
+                ));
+            }
+            syn::TypeParamBound::Lifetime(_) => {}
+        }
+    }
+    Ok(())
+}
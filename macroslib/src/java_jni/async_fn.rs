@@ -0,0 +1,201 @@
+//! `async fn` methods: native function passes Rust future to executor
+//! and returns handle of task, result is delivered to `RustFuture`,
+//! that is subclass of `java.util.concurrent.CompletableFuture`
+
+use std::io::Write;
+
+use super::{java_class_full_name, java_code, rust_code, JavaContext, JniForeignMethodSignature};
+use crate::{
+    error::{invalid_src_id_span, panic_on_syn_error, DiagnosticError, Result},
+    file_cache::FileWriteCache,
+    types::ForeignMethod,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::java_jni) const RUST_FUTURE_CLASS: &str = "RustFuture";
+
+/// Write `RustFuture.java` and Rust code shared by all `async fn` methods
+pub(in crate::java_jni) fn generate_runtime(ctx: &mut JavaContext) -> Result<()> {
+    let src_path = ctx
+        .cfg
+        .output_dir
+        .join(format!("{}.java", RUST_FUTURE_CLASS));
+    let mut src_file = FileWriteCache::new(&src_path, ctx.generated_foreign_files);
+    writeln!(
+        src_file,
+        r#"// Automatically generated by flapigen
+package {package};
+
+/**
+ * Result of Rust async function, cancel of it drops Rust future
+ */
+public final class {class_name}<T> extends java.util.concurrent.CompletableFuture<T> {{
+    /*package*/ {class_name}(Object owner) {{
+        mOwner = owner;
+        whenComplete((value, error) -> release());
+    }}
+
+    /*package*/ synchronized void setTask(long task) {{
+        mTask = task;
+        if (isDone()) {{
+            release();
+        }}
+    }}
+
+    private synchronized void release() {{
+        if (mTask != 0) {{
+            do_release(mTask, isCancelled());
+            mTask = 0;
+        }}
+        mOwner = null;
+    }}
+    private static native void do_release(long task, boolean cancel);
+
+    /**
+     * Future completed with converted value of this one,
+     * cancel of it cancels this one
+     */
+    /*package*/ <R> java.util.concurrent.CompletableFuture<R> thenConvert(java.util.function.Function<T, R> convert) {{
+        java.util.concurrent.CompletableFuture<R> ret = new java.util.concurrent.CompletableFuture<R>() {{
+            @Override
+            public boolean cancel(boolean mayInterruptIfRunning) {{
+                {class_name}.this.cancel(mayInterruptIfRunning);
+                return super.cancel(mayInterruptIfRunning);
+            }}
+        }};
+        whenComplete((value, error) -> {{
+            if (error != null) {{
+                ret.completeExceptionally(error);
+            }} else {{
+                ret.complete(convert.apply(value));
+            }}
+        }});
+        return ret;
+    }}
+
+    private long mTask;
+    /* keep object, which method was called, alive until Rust future is done */
+    private Object mOwner;
+}}"#,
+        package = ctx.cfg.package_name,
+        class_name = RUST_FUTURE_CLASS,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    src_file.update_file_if_necessary().map_err(|err| {
+        DiagnosticError::new2(
+            invalid_src_id_span(),
+            format!("write to {} failed: {}", src_path.display(), err),
+        )
+    })?;
+
+    ctx.rust_code.push(crate::async_include_code(
+        ctx.cfg.async_executor.as_deref(),
+    )?);
+    let code = include_str!("jni-async-include.rs");
+    ctx.rust_code.push(
+        syn::parse_str(code)
+            .unwrap_or_else(|err| panic_on_syn_error("jni-async-include.rs", code.into(), err)),
+    );
+
+    let mut release_func_name = "Java_".to_string();
+    rust_code::escape_underscore(&ctx.cfg.package_name, &mut release_func_name);
+    release_func_name.push('_');
+    rust_code::escape_underscore(RUST_FUTURE_CLASS, &mut release_func_name);
+    release_func_name.push('_');
+    rust_code::escape_underscore("do_release", &mut release_func_name);
+    let code = format!(
+        r#"
+#[allow(non_snake_case, unused_variables)]
+#[no_mangle]
+pub extern "C" fn {release_func_name}(env: *mut JNIEnv, _: jclass, task: jlong, cancel: jboolean) {{
+    let task: ::std::sync::Arc<SwigAsyncTask> =
+        unsafe {{ ::std::sync::Arc::from_raw(jlong_to_pointer::<SwigAsyncTask>(task)) }};
+    if cancel != 0 {{
+        task.cancel();
+    }}
+}}
+"#,
+        release_func_name = release_func_name,
+    );
+    ctx.rust_code.push(
+        syn::parse_str(&code)
+            .unwrap_or_else(|err| panic_on_syn_error("java/jni RustFuture.do_release", code, err)),
+    );
+    Ok(())
+}
+
+/// Type of value of `CompletableFuture` for Java type returned by method
+pub(in crate::java_jni) fn future_value_type(java_type: &str) -> String {
+    let ty = java_code::filter_null_annotation(java_type);
+    java_code::boxed_type(ty.trim()).to_string()
+}
+
+/// If value needs conversion in Java code, native method completes `RustFuture`
+/// with intermediate value, so return its type and code to convert
+/// `RustFuture` into `CompletableFuture` of converted value
+pub(in crate::java_jni) fn convert_future_value(
+    f_method: &JniForeignMethodSignature,
+    ret_name: &str,
+    conv_ret: &str,
+) -> Option<(String, String)> {
+    let conv = f_method
+        .output
+        .java_converter
+        .as_ref()
+        .filter(|conv| !conv.converter.is_empty())?;
+    let (_, intermidiate_ret_type, conv_code) =
+        super::fclass::calc_output_conv(&f_method.output, conv, ret_name, conv_ret);
+    let value_type = future_value_type(intermidiate_ret_type);
+    let code = format!(
+        ".thenConvert(({value_type} {ret_name}) -> {{{conv_code}\n            return {conv_ret};\n        }})",
+        value_type = value_type,
+        ret_name = ret_name,
+        conv_code = conv_code.replace('\n', "\n    "),
+        conv_ret = conv_ret,
+    );
+    Some((value_type, code))
+}
+
+/// Suffix of JNI function name for overloaded method,
+/// because native method has additional `RustFuture` parameter
+pub(in crate::java_jni) fn jni_func_name_suffix(ctx: &JavaContext) -> String {
+    let mut ret = String::new();
+    rust_code::escape_underscore(
+        &format!(
+            "L{};",
+            java_class_full_name(&ctx.cfg.package_name, RUST_FUTURE_CLASS)
+        ),
+        &mut ret,
+    );
+    ret
+}
+
+/// Body of JNI function: convert arguments, spawn future and return
+/// handle of task, result is converted to JNI type after completion
+/// in thread of executor
+pub(in crate::java_jni) fn spawn_future_code(
+    method: &ForeignMethod,
+    ret_name: &str,
+    real_output_typename: &str,
+    jni_ret_type: &str,
+    convert_output_code: &str,
+) -> String {
+    format!(
+        r#"
+    let swig_fut = {call};
+    let swig_future = JavaCallback::new(swig_future, env);
+    let swig_task = swig_async_spawn(async move {{
+        let mut {ret_name}: {real_output_typename} = swig_fut.await;
+        swig_jni_complete_future(&swig_future, move |env: *mut JNIEnv| -> {jni_ret_type} {{
+{convert_output_code}
+            {ret_name}
+        }});
+    }});
+    ::std::sync::Arc::into_raw(swig_task) as jlong"#,
+        call = method.generate_code_to_call_rust_func(),
+        ret_name = ret_name,
+        real_output_typename = real_output_typename,
+        jni_ret_type = jni_ret_type,
+        convert_output_code = convert_output_code,
+    )
+}
@@ -1,15 +1,17 @@
 use log::{debug, trace};
 use petgraph::Direction;
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::quote;
+use quote::{quote, ToTokens};
 use rustc_hash::{FxHashMap, FxHashSet};
 use smol_str::SmolStr;
-use std::io::Write;
+use std::{borrow::Cow, io::Write};
 use syn::{spanned::Spanned, Type};
 
 use super::{
-    calc_this_type_for_method, java_class_full_name, java_class_name_to_jni, java_code,
-    map_type::map_type, method_name, rust_code, JavaContext, JavaConverter, JavaForeignTypeInfo,
+    async_fn, calc_this_type_for_method, java_class_full_name, java_class_name_to_jni, java_code,
+    kotlin_code::{self, kotlin_ident},
+    map_type::map_type,
+    method_name, rust_code, JavaContext, JavaConverter, JavaForeignTypeInfo,
     JniForeignMethodSignature, INTERNAL_PTR_MARKER, JAVA_RUST_SELF_NAME, REACHABILITY_FENCE_CLASS,
 };
 use crate::{
@@ -18,7 +20,7 @@ use crate::{
     file_cache::FileWriteCache,
     namegen::new_unique_name,
     typemap::{
-        ast::{if_result_return_ok_err_types, list_lifetimes, normalize_type},
+        ast::{if_result_return_ok_err_types, list_lifetimes, strip_lifetimes},
         ty::RustType,
         utils::{
             convert_to_heap_pointer, create_suitable_types_for_constructor_and_self,
@@ -28,8 +30,8 @@ use crate::{
         ForeignTypeInfo, FROM_VAR_TEMPLATE, TO_VAR_TEMPLATE, TO_VAR_TYPE_TEMPLATE,
     },
     types::{ForeignClassInfo, ForeignMethod, MethodAccess, MethodVariant, SelfTypeVariant},
-    JavaConfig, JavaReachabilityFence, CLONE_TRAIT, COPY_TRAIT, SMART_PTR_COPY_TRAIT,
-    WRITE_TO_MEM_FAILED_MSG,
+    JavaConfig, JavaOutputLanguage, JavaReachabilityFence, CLONE_TRAIT, COPY_TRAIT,
+    SMART_PTR_COPY_TRAIT, WRITE_TO_MEM_FAILED_MSG,
 };
 
 pub(in crate::java_jni) fn generate(ctx: &mut JavaContext, class: &ForeignClassInfo) -> Result<()> {
@@ -39,12 +41,15 @@ pub(in crate::java_jni) fn generate(ctx: &mut JavaContext, class: &ForeignClassI
     );
 
     let f_methods_sign = find_suitable_foreign_types_for_methods(ctx, class)?;
-    generate_java_code(
-        ctx,
-        class,
-        &f_methods_sign,
-        ctx.cfg.null_annotation_package.as_deref(),
-    )?;
+    match ctx.cfg.output_language {
+        JavaOutputLanguage::Java => generate_java_code(
+            ctx,
+            class,
+            &f_methods_sign,
+            ctx.cfg.null_annotation_package.as_deref(),
+        )?,
+        JavaOutputLanguage::Kotlin => generate_kotlin_code(ctx, class, &f_methods_sign)?,
+    }
     debug!("generate: java code done");
     generate_rust_code(ctx, class, &f_methods_sign)?;
 
@@ -92,16 +97,22 @@ fn generate_java_code(
     let imports = java_code::get_null_annotation_imports(null_annotation_package, methods_sign);
 
     let class_doc_comments = java_code::doc_comments_to_java_comments(&class.doc_comments, true);
+    let (implements, iterator_code) = if class.is_iterator() {
+        java_iterator_code(methods_sign)
+    } else {
+        (String::new(), String::new())
+    };
     writeln!(
         file,
         r#"// Automatically generated by flapigen
 package {package_name};
 {imports}
 {doc_comments}
-public final class {class_name} {{"#,
+public final class {class_name}{implements} {{"#,
         package_name = ctx.cfg.package_name,
         imports = imports,
         class_name = class.name,
+        implements = implements,
         doc_comments = class_doc_comments,
     )
     .expect(WRITE_TO_MEM_FAILED_MSG);
@@ -158,6 +169,10 @@ public final class {class_name} {{"#,
         known_names.insert(ret_name.clone());
         let conv_ret = new_unique_name(&known_names, "convRet");
         known_names.insert(conv_ret.clone());
+        let future_name = new_unique_name(&known_names, "future");
+        if method.is_async {
+            known_names.insert(future_name.clone());
+        }
 
         let (convert_code, args_for_call_internal, reachability_fence_code) =
             convert_code_for_method(
@@ -177,6 +192,80 @@ public final class {class_name} {{"#,
             null_annotation_package.is_some(),
         );
 
+        if method.is_async {
+            let value_type = async_fn::future_value_type(&f_method.output.base.name);
+            let (native_value_type, convert_value) =
+                async_fn::convert_future_value(f_method, &ret_name, &conv_ret)
+                    .unwrap_or_else(|| (value_type.clone(), String::new()));
+            let internal_args = java_code::args_with_java_types(
+                f_method,
+                method.arg_names_without_self(),
+                java_code::ArgsFormatFlags::INTERNAL,
+                null_annotation_package.is_some(),
+            );
+            let (modifier, owner, call_args, native_args) = match method.variant {
+                MethodVariant::Method(_) => {
+                    have_methods = true;
+                    (
+                        "final",
+                        "this",
+                        format!(
+                            "{}{}, {}",
+                            JAVA_RUST_SELF_NAME, args_for_call_internal, future_name
+                        ),
+                        if internal_args.is_empty() {
+                            "long self, ".to_string()
+                        } else {
+                            format!("long self, {}, ", internal_args)
+                        },
+                    )
+                }
+                _ => (
+                    "static",
+                    "null",
+                    if args_for_call_internal.is_empty() {
+                        future_name.to_string()
+                    } else {
+                        format!("{}, {}", args_for_call_internal, future_name)
+                    },
+                    if internal_args.is_empty() {
+                        String::new()
+                    } else {
+                        format!("{}, ", internal_args)
+                    },
+                ),
+            };
+            let convert_code = convert_code.trim_start_matches('\n');
+            writeln!(
+                file,
+                r#"
+    {method_access} {modifier} java.util.concurrent.CompletableFuture<{value_type}> {method_name}({args_with_types}) {{
+{convert_code}{nl}        {future_class}<{native_value_type}> {future} = new {future_class}<{native_value_type}>({owner});
+        {future}.setTask({func_name}({call_args}));{reachability_fence_code}
+        return {future}{convert_value};
+    }}
+    private static native long {func_name}({native_args}{future_class}<{native_value_type}> {future});"#,
+                method_access = method_access,
+                modifier = modifier,
+                value_type = value_type,
+                native_value_type = native_value_type,
+                convert_value = convert_value,
+                method_name = method.short_name(),
+                args_with_types = external_args_except_self,
+                convert_code = convert_code,
+                nl = if convert_code.is_empty() { "" } else { "\n" },
+                future_class = async_fn::RUST_FUTURE_CLASS,
+                future = future_name,
+                owner = owner,
+                func_name = func_name,
+                call_args = call_args,
+                reachability_fence_code = reachability_fence_code,
+                native_args = native_args,
+            )
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+            continue;
+        }
+
         let (ret_type, intermidiate_ret_type, ret_conv_code) = match method.variant {
             MethodVariant::StaticMethod => {
                 if let Some(conv) = f_method.output.java_converter.as_ref() {
@@ -482,6 +571,8 @@ May be you need to use `private constructor = empty;` syntax?",
         .expect(WRITE_TO_MEM_FAILED_MSG);
     }
 
+    file.write_all(iterator_code.as_bytes())
+        .expect(WRITE_TO_MEM_FAILED_MSG);
     file.write_all(class.foreign_code.as_bytes())
         .expect(WRITE_TO_MEM_FAILED_MSG);
     write!(file, "}}").expect(WRITE_TO_MEM_FAILED_MSG);
@@ -491,6 +582,355 @@ May be you need to use `private constructor = empty;` syntax?",
         class,
         &mut cnt,
         &[CLONE_TRAIT, COPY_TRAIT, SMART_PTR_COPY_TRAIT],
+        &[],
+        ctx.class_ext_handlers,
+        ctx.method_ext_handlers,
+    )?;
+    file.replace_content(cnt);
+
+    file.update_file_if_necessary()
+        .map_err(DiagnosticError::map_any_err_to_our_err)?;
+    Ok(())
+}
+
+fn generate_kotlin_code(
+    ctx: &mut JavaContext,
+    class: &ForeignClassInfo,
+    methods_sign: &[JniForeignMethodSignature],
+) -> Result<()> {
+    use std::fmt::Write;
+
+    let path = ctx.cfg.output_dir.join(format!("{}.kt", class.name));
+    let mut file = FileWriteCache::new(&path, ctx.generated_foreign_files);
+
+    let have_constructor = class
+        .methods
+        .iter()
+        .any(|x| x.variant == MethodVariant::Constructor);
+    let have_methods = class
+        .methods
+        .iter()
+        .any(|x| matches!(x.variant, MethodVariant::Method(_)));
+    if have_methods && !have_constructor {
+        return Err(DiagnosticError::new(
+            class.src_id,
+            class.span(),
+            format!(
+                "package {}, class {}: has methods, but no constructor\n
+May be you need to use `private constructor = empty;` syntax?",
+                ctx.cfg.package_name, class.name
+            ),
+        ));
+    }
+
+    let mut members = String::new();
+    let mut companion = String::new();
+    let mut natives = String::new();
+
+    for (method, f_method) in class.methods.iter().zip(methods_sign) {
+        let span = (class.src_id, method.rust_id.span());
+        let mut doc_comments =
+            java_code::doc_comments_to_java_comments(&method.doc_comments, false);
+        if !doc_comments.is_empty() {
+            doc_comments.push('\n');
+        }
+        let may_return_error = match method.fn_decl.output {
+            syn::ReturnType::Default => false,
+            syn::ReturnType::Type(_, ref ptype) => {
+                let ret_rust_ty = ctx.conv_map.find_or_alloc_rust_type(ptype, class.src_id);
+                if_result_return_ok_err_types(&ret_rust_ty).is_some()
+            }
+        };
+        let method_access = match method.access {
+            MethodAccess::Private => "private ",
+            MethodAccess::Public => "",
+            MethodAccess::Protected => "protected ",
+        };
+
+        if method.is_dummy_constructor() {
+            write!(
+                &mut members,
+                "\n{doc_comments}    {method_access}constructor() : this({internal_ptr_marker}.RAW_PTR, 0L)\n",
+                doc_comments = doc_comments,
+                method_access = method_access,
+                internal_ptr_marker = INTERNAL_PTR_MARKER,
+            )
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+            continue;
+        }
+
+        let mut known_names: FxHashSet<SmolStr> =
+            method.arg_names_without_self().map(|x| x.into()).collect();
+        if let MethodVariant::Method(_) = method.variant {
+            if known_names.contains(JAVA_RUST_SELF_NAME) {
+                return Err(DiagnosticError::new2(
+                    span,
+                    format!(
+                        "In method {} there is argument with name {}, this name reserved for generated code",
+                        method.short_name(),
+                        JAVA_RUST_SELF_NAME
+                    ),
+                ));
+            }
+            known_names.insert(JAVA_RUST_SELF_NAME.into());
+        }
+        let ret_name = new_unique_name(&known_names, "ret");
+        known_names.insert(ret_name.clone());
+        let func_name = method_name(method, f_method);
+
+        // there is no conversation for Java, so it is impossible to generate
+        // wrapper: it would conflict with native method
+        if method.variant == MethodVariant::StaticMethod && func_name == method.short_name() {
+            let args: Vec<String> = f_method
+                .input
+                .iter()
+                .zip(method.arg_names_without_self())
+                .map(|(arg, arg_name)| {
+                    format!(
+                        "{}: {}",
+                        kotlin_ident(arg_name),
+                        kotlin_code::jvm_type_of(arg)
+                    )
+                })
+                .collect();
+            write!(
+                &mut companion,
+                "\n{doc_comments}{throws}        @JvmStatic\n        {method_access}external fun {func_name}({args}){ret_type}\n",
+                doc_comments = kotlin_code::indent_doc_comments(&doc_comments, "    "),
+                throws = kotlin_code::throws_annotation(may_return_error, "        "),
+                method_access = method_access,
+                func_name = kotlin_ident(&func_name),
+                args = args.join(", "),
+                ret_type = kotlin_ret_type(&kotlin_code::jvm_type_of(&f_method.output)),
+            )
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+            continue;
+        }
+
+        let mut args = Vec::with_capacity(f_method.input.len());
+        let mut native_args = Vec::with_capacity(f_method.input.len() + 1);
+        let mut call_args = Vec::with_capacity(f_method.input.len() + 1);
+        let mut protect_args = Vec::new();
+        let mut body = String::new();
+        if let MethodVariant::Method(_) = method.variant {
+            native_args.push("self: Long".to_string());
+            call_args.push(JAVA_RUST_SELF_NAME.to_string());
+        }
+        for (i, (arg, arg_name)) in f_method
+            .input
+            .iter()
+            .zip(method.arg_names_without_self())
+            .enumerate()
+        {
+            let arg_name = kotlin_ident(arg_name);
+            let conv = kotlin_code::input_conv(arg, span)?;
+            args.push(format!("{}: {}", arg_name, conv.ty));
+            native_args.push(format!("{}: {}", arg_name, conv.native_ty));
+            if conv.conv.is_empty() {
+                call_args.push(arg_name.to_string());
+            } else {
+                let after_conv_arg_name = new_unique_name(&known_names, &format!("a{}", i));
+                known_names.insert(after_conv_arg_name.clone());
+                body.push_str(
+                    &conv
+                        .conv
+                        .replace(TO_VAR_TEMPLATE, &after_conv_arg_name)
+                        .replace(FROM_VAR_TEMPLATE, &arg_name),
+                );
+                if !java_code::is_primitive_type(&arg.base.name) {
+                    protect_args.push(arg_name.to_string());
+                }
+                call_args.push(after_conv_arg_name.to_string());
+            }
+        }
+        let protect_args: Vec<&str> = protect_args.iter().map(String::as_str).collect();
+        let reachability_fence_code = reachability_fence_code(span, ctx.cfg, &protect_args, "")?;
+
+        let (ret_type, native_ret_type, ret_conv) = match method.variant {
+            MethodVariant::Constructor => (String::new(), "Long".to_string(), String::new()),
+            MethodVariant::StaticMethod | MethodVariant::Method(_) => {
+                let conv = kotlin_code::output_conv(ctx, &f_method.output, span)?;
+                (conv.ty, conv.native_ty, conv.conv)
+            }
+        };
+        let call = format!("{}({})", kotlin_ident(&func_name), call_args.join(", "));
+        if method.variant == MethodVariant::Constructor {
+            writeln!(&mut body, "        {} = {}", JAVA_RUST_SELF_NAME, call)
+        } else if ret_type == "Unit" {
+            writeln!(&mut body, "        {}", call)
+        } else if ret_conv.is_empty() && reachability_fence_code.is_empty() {
+            writeln!(&mut body, "        return {}", call)
+        } else {
+            writeln!(&mut body, "        val {} = {}", ret_name, call)
+        }
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        if !reachability_fence_code.is_empty() {
+            body.push_str(reachability_fence_code.trim_start_matches('\n'));
+            body.push('\n');
+        }
+        if method.variant != MethodVariant::Constructor
+            && ret_type != "Unit"
+            && !(ret_conv.is_empty() && reachability_fence_code.is_empty())
+        {
+            let ret_expr = if ret_conv.is_empty() {
+                ret_name.to_string()
+            } else {
+                ret_conv.replace(FROM_VAR_TEMPLATE, &ret_name)
+            };
+            writeln!(&mut body, "        return {}", ret_expr).expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+
+        let throws = kotlin_code::throws_annotation(may_return_error, "    ");
+        match method.variant {
+            MethodVariant::Constructor => write!(
+                &mut members,
+                "\n{doc_comments}{throws}    {method_access}constructor({args}) : this({internal_ptr_marker}.RAW_PTR, 0L) {{\n{body}    }}\n",
+                doc_comments = doc_comments,
+                throws = throws,
+                method_access = method_access,
+                args = args.join(", "),
+                internal_ptr_marker = INTERNAL_PTR_MARKER,
+                body = body,
+            ),
+            MethodVariant::Method(_) => write!(
+                &mut members,
+                "\n{doc_comments}{throws}    {method_access}fun {method_name}({args}){ret_type} {{\n{body}    }}\n",
+                doc_comments = doc_comments,
+                throws = throws,
+                method_access = method_access,
+                method_name = kotlin_ident(&method.short_name()),
+                args = args.join(", "),
+                ret_type = kotlin_ret_type(&ret_type),
+                body = body,
+            ),
+            MethodVariant::StaticMethod => write!(
+                &mut companion,
+                "\n{doc_comments}{throws}        @JvmStatic\n        {method_access}fun {method_name}({args}){ret_type} {{\n{body}        }}\n",
+                doc_comments = kotlin_code::indent_doc_comments(&doc_comments, "    "),
+                throws = kotlin_code::throws_annotation(may_return_error, "        "),
+                method_access = method_access,
+                method_name = kotlin_ident(&method.short_name()),
+                args = args.join(", "),
+                ret_type = kotlin_ret_type(&ret_type),
+                body = kotlin_code::indent_doc_comments(&body, "    "),
+            ),
+        }
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        write!(
+            &mut natives,
+            "\n        @JvmStatic\n        private external fun {func_name}({args}){ret_type}\n",
+            func_name = kotlin_ident(&func_name),
+            args = native_args.join(", "),
+            ret_type = kotlin_ret_type(&native_ret_type),
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+
+    // class of Rust iterator implements `Iterator` with help of its private methods,
+    // the last one returns the next element
+    let supertypes = match methods_sign.last() {
+        Some(f_method) if class.is_iterator() => {
+            let elem_type =
+                kotlin_code::output_conv(ctx, &f_method.output, (class.src_id, class.span()))?.ty;
+            write!(
+                &mut members,
+                r#"
+    override fun hasNext(): Boolean = hasNextElement()
+
+    override fun next(): {elem} {{
+        if (!hasNextElement()) {{
+            throw NoSuchElementException()
+        }}
+        return nextElement()
+    }}
+
+    override fun iterator(): Iterator<{elem}> = this
+"#,
+                elem = elem_type
+            )
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+            format!(", Iterator<{elem}>, Iterable<{elem}>", elem = elem_type)
+        }
+        _ => String::new(),
+    };
+
+    let mut class_doc_comments =
+        java_code::doc_comments_to_java_comments(&class.doc_comments, true);
+    if !class_doc_comments.is_empty() {
+        class_doc_comments.push('\n');
+    }
+    if have_constructor {
+        write!(
+            &mut file,
+            r#"// Automatically generated by flapigen
+package {package_name}
+
+{doc_comments}class {class_name} internal constructor(marker: {internal_ptr_marker}, ptr: Long) : AutoCloseable{supertypes} {{
+    @JvmField
+    internal var {rust_self_name}: Long = ptr
+
+    init {{
+        assert(marker == {internal_ptr_marker}.RAW_PTR)
+    }}
+{members}
+    @Synchronized
+    override fun close() {{
+        if ({rust_self_name} != 0L) {{
+            do_delete({rust_self_name})
+            {rust_self_name} = 0
+        }}
+    }}
+
+    protected fun finalize() {{
+        close()
+    }}
+"#,
+            package_name = ctx.cfg.package_name,
+            doc_comments = class_doc_comments,
+            class_name = class.name,
+            internal_ptr_marker = INTERNAL_PTR_MARKER,
+            rust_self_name = JAVA_RUST_SELF_NAME,
+            supertypes = supertypes,
+            members = members,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        natives
+            .push_str("\n        @JvmStatic\n        private external fun do_delete(me: Long)\n");
+    } else {
+        //utility class, so add private constructor
+        //to prevent object creation
+        write!(
+            &mut file,
+            r#"// Automatically generated by flapigen
+package {package_name}
+
+{doc_comments}class {class_name} private constructor() {{
+{members}"#,
+            package_name = ctx.cfg.package_name,
+            doc_comments = class_doc_comments,
+            class_name = class.name,
+            members = members,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    file.write_all(class.foreign_code.as_bytes())
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    if !companion.is_empty() || !natives.is_empty() {
+        write!(
+            &mut file,
+            "\n    companion object {{{}{}    }}\n",
+            companion, natives
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(file, "}}").expect(WRITE_TO_MEM_FAILED_MSG);
+
+    let mut cnt = file.take_content();
+    extend_foreign_class(
+        class,
+        &mut cnt,
+        &[CLONE_TRAIT, COPY_TRAIT, SMART_PTR_COPY_TRAIT],
+        &[],
         ctx.class_ext_handlers,
         ctx.method_ext_handlers,
     )?;
@@ -501,6 +941,14 @@ May be you need to use `private constructor = empty;` syntax?",
     Ok(())
 }
 
+fn kotlin_ret_type(ty: &str) -> String {
+    if ty.is_empty() || ty == "Unit" {
+        String::new()
+    } else {
+        format!(": {}", ty)
+    }
+}
+
 fn generate_rust_code(
     ctx: &mut JavaContext,
     class: &ForeignClassInfo,
@@ -606,7 +1054,7 @@ May be you need to use `private constructor = empty;` syntax?",
     for (method, f_method) in class.methods.iter().zip(f_methods_sign.iter()) {
         let java_method_name = method_name(method, f_method);
         let method_overloading = gen_fnames[&java_method_name] > 1;
-        let jni_func_name = rust_code::generate_jni_func_name(
+        let mut jni_func_name = rust_code::generate_jni_func_name(
             ctx,
             &class.name.to_string(),
             (class.src_id, class.span()),
@@ -615,6 +1063,9 @@ May be you need to use `private constructor = empty;` syntax?",
             f_method,
             method_overloading,
         )?;
+        if method.is_async && method_overloading {
+            jni_func_name.push_str(&async_fn::jni_func_name_suffix(ctx));
+        }
         trace!("generate_rust_code jni name: {}", jni_func_name);
 
         let mut known_names: FxHashSet<SmolStr> =
@@ -649,9 +1100,13 @@ May be you need to use `private constructor = empty;` syntax?",
             buf
         };
 
-        let real_output_typename = match method.fn_decl.output {
-            syn::ReturnType::Default => "()",
-            syn::ReturnType::Type(_, ref ty) => normalize_type(&*ty),
+        let real_output_typename: Cow<str> = match method.fn_decl.output {
+            syn::ReturnType::Default => Cow::Borrowed("()"),
+            syn::ReturnType::Type(_, ref t) => {
+                let mut ty: Type = (**t).clone();
+                strip_lifetimes(&mut ty);
+                ty.into_token_stream().to_string().into()
+            }
         };
 
         let method_ctx = MethodContext {
@@ -726,21 +1181,26 @@ May be you need to use `private constructor = empty;` syntax?",
             },
             false,
         )?;
-        let code = format!(
+        let body = format!(
             r#"
-#[allow(unused_variables, unused_mut, non_snake_case, unused_unsafe)]
-#[no_mangle]
-pub extern "C" fn {jni_destructor_name}(env: *mut JNIEnv, _: jclass, this: jlong) {{
     let this: *mut {this_type} = unsafe {{
         jlong_to_pointer::<{this_type}>(this).as_mut().unwrap()
     }};
 {unpack_code}
-    drop(this);
+    drop(this);"#,
+            unpack_code = unpack_code,
+            this_type = this_type_for_method,
+        );
+        let code = format!(
+            r#"
+#[allow(unused_variables, unused_mut, non_snake_case, unused_unsafe)]
+#[no_mangle]
+pub extern "C" fn {jni_destructor_name}(env: *mut JNIEnv, _: jclass, this: jlong) {{
+{body}
 }}
 "#,
             jni_destructor_name = jni_destructor_name,
-            unpack_code = unpack_code,
-            this_type = this_type_for_method,
+            body = catch_panics_in_body(ctx, body),
         );
         debug!("we generate and parse code: {}", code);
         ctx.rust_code.push(
@@ -853,25 +1313,53 @@ fn generate_static_method(ctx: &mut JavaContext, mc: &MethodContext) -> Result<(
     )?;
     ctx.rust_code.append(&mut deps_code_in);
 
+    let body = if mc.method.is_async {
+        format!(
+            "\n{}{}",
+            convert_input_code,
+            async_fn::spawn_future_code(
+                mc.method,
+                mc.ret_name,
+                mc.real_output_typename,
+                jni_ret_type,
+                &convert_output_code
+            )
+        )
+    } else {
+        format!(
+            r#"
+{convert_input_code}
+    let mut {ret_name}: {real_output_typename} = {call};
+{convert_output_code}
+    {ret_name}"#,
+            convert_input_code = convert_input_code,
+            convert_output_code = convert_output_code,
+            real_output_typename = mc.real_output_typename,
+            call = mc.method.generate_code_to_call_rust_func(),
+            ret_name = mc.ret_name,
+        )
+    };
     let code = format!(
         r#"
 #[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe)]
 #[no_mangle]
-pub extern "C" fn {func_name}(env: *mut JNIEnv, _: jclass, {decl_func_args}) -> {jni_ret_type} {{
-{convert_input_code}
-    let mut {ret_name}: {real_output_typename} = {call};
-{convert_output_code}
-    {ret_name}
+pub extern "C" fn {func_name}(env: *mut JNIEnv, _: jclass, {decl_func_args}{future_arg}) -> {jni_ret_type} {{
+{body}
 }}
 "#,
         func_name = mc.jni_func_name,
         decl_func_args = mc.decl_func_args,
-        jni_ret_type = jni_ret_type,
-        convert_input_code = convert_input_code,
-        convert_output_code = convert_output_code,
-        real_output_typename = mc.real_output_typename,
-        call = mc.method.generate_code_to_call_rust_func(),
-        ret_name = mc.ret_name,
+        future_arg = if mc.method.is_async {
+            "swig_future: jobject"
+        } else {
+            ""
+        },
+        jni_ret_type = if mc.method.is_async {
+            "jlong"
+        } else {
+            jni_ret_type
+        },
+        body = catch_panics_in_body(ctx, body),
     );
 
     ctx.rust_code
@@ -918,21 +1406,14 @@ fn generate_constructor(
     )?;
     ctx.rust_code.append(&mut deps_this);
     let empty_box_this = TokenStream::new();
-    let code = format!(
+    let body = format!(
         r#"
-#[allow(unused_variables, unused_mut, non_snake_case, unused_unsafe)]
-#[no_mangle]
-pub extern "C" fn {func_name}(env: *mut JNIEnv, _: jclass, {decl_func_args}) -> jlong {{
 {convert_input_code}
     let this: {real_output_typename} = {call};
 {convert_this}
 {box_this}
-    this as jlong
-}}
-"#,
-        func_name = mc.jni_func_name,
+    this as jlong"#,
         convert_this = convert_this,
-        decl_func_args = mc.decl_func_args,
         convert_input_code = convert_input_code,
         box_this = if return_result {
             &empty_box_this
@@ -942,6 +1423,18 @@ pub extern "C" fn {func_name}(env: *mut JNIEnv, _: jclass, {decl_func_args}) ->
         real_output_typename = mc.real_output_typename,
         call = mc.method.generate_code_to_call_rust_func(),
     );
+    let code = format!(
+        r#"
+#[allow(unused_variables, unused_mut, non_snake_case, unused_unsafe)]
+#[no_mangle]
+pub extern "C" fn {func_name}(env: *mut JNIEnv, _: jclass, {decl_func_args}) -> jlong {{
+{body}
+}}
+"#,
+        func_name = mc.jni_func_name,
+        decl_func_args = mc.decl_func_args,
+        body = catch_panics_in_body(ctx, body),
+    );
 
     ctx.rust_code.push(
         syn::parse_str(&code)
@@ -1002,33 +1495,61 @@ fn generate_method(
     )?;
     ctx.rust_code.append(&mut deps_this);
 
-    let code = format!(
+    let call_code = if mc.method.is_async {
+        async_fn::spawn_future_code(
+            mc.method,
+            mc.ret_name,
+            mc.real_output_typename,
+            jni_ret_type,
+            &convert_output_code,
+        )
+    } else {
+        format!(
+            r#"
+    let mut {ret_name}: {real_output_typename} = {call};
+{convert_output_code}
+    {ret_name}"#,
+            convert_output_code = convert_output_code,
+            real_output_typename = mc.real_output_typename,
+            call = mc.method.generate_code_to_call_rust_func(),
+            ret_name = mc.ret_name,
+        )
+    };
+    let body = format!(
         r#"
-#[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe)]
-#[no_mangle]
-pub extern "C"
- fn {func_name}(env: *mut JNIEnv, _: jclass, this: jlong, {decl_func_args}) -> {jni_ret_type} {{
 {convert_input_code}
     let this: {this_type_ref} = unsafe {{
         jlong_to_pointer::<{this_type}>(this).as_mut().unwrap()
     }};
-{convert_this}
-    let mut {ret_name}: {real_output_typename} = {call};
-{convert_output_code}
-    {ret_name}
-}}
-"#,
-        func_name = mc.jni_func_name,
-        decl_func_args = mc.decl_func_args,
+{convert_this}{call_code}"#,
         convert_input_code = convert_input_code,
-        jni_ret_type = jni_ret_type,
         this_type_ref = this_type_ref,
         this_type = this_type_for_method,
         convert_this = convert_this,
-        convert_output_code = convert_output_code,
-        real_output_typename = mc.real_output_typename,
-        call = mc.method.generate_code_to_call_rust_func(),
-        ret_name = mc.ret_name,
+        call_code = call_code,
+    );
+    let code = format!(
+        r#"
+#[allow(non_snake_case, unused_variables, unused_mut, unused_unsafe)]
+#[no_mangle]
+pub extern "C"
+ fn {func_name}(env: *mut JNIEnv, _: jclass, this: jlong, {decl_func_args}{future_arg}) -> {jni_ret_type} {{
+{body}
+}}
+"#,
+        func_name = mc.jni_func_name,
+        decl_func_args = mc.decl_func_args,
+        future_arg = if mc.method.is_async {
+            "swig_future: jobject"
+        } else {
+            ""
+        },
+        jni_ret_type = if mc.method.is_async {
+            "jlong"
+        } else {
+            jni_ret_type
+        },
+        body = catch_panics_in_body(ctx, body),
     );
 
     ctx.rust_code.push(
@@ -1038,6 +1559,19 @@ pub extern "C"
     Ok(())
 }
 
+/// Wrap body of generated JNI function into `swig_jni_catch_panic`,
+/// if `JavaConfig::catch_panics` is used
+fn catch_panics_in_body(ctx: &JavaContext, body: String) -> String {
+    match ctx.cfg.catch_panics {
+        Some(catch_panics) => format!(
+            "    swig_jni_catch_panic(env, {}, || {{{}\n    }})",
+            catch_panics.with_backtrace(),
+            body
+        ),
+        None => body,
+    }
+}
+
 fn convert_code_for_method<'a, NI: Iterator<Item = &'a str>>(
     ctx_span: SourceIdSpan,
     cfg: &JavaConfig,
@@ -1046,11 +1580,8 @@ fn convert_code_for_method<'a, NI: Iterator<Item = &'a str>>(
     mut known_names: FxHashSet<SmolStr>,
     flags: java_code::ArgsFormatFlags,
 ) -> Result<(String, String, String)> {
-    use std::fmt::Write;
-
     let mut conv_code = String::new();
     let mut args_for_call_internal = String::new();
-    let mut reachability_fence_code = String::new();
 
     if flags.contains(java_code::ArgsFormatFlags::COMMA_BEFORE) && !f_method.input.is_empty() {
         args_for_call_internal.push_str(", ");
@@ -1087,16 +1618,32 @@ fn convert_code_for_method<'a, NI: Iterator<Item = &'a str>>(
             args_for_call_internal.push_str(", ");
         }
     }
+    let reachability_fence_code = reachability_fence_code(ctx_span, cfg, &protect_args, ";")?;
+
+    Ok((conv_code, args_for_call_internal, reachability_fence_code))
+}
+
+/// Code to keep objects alive during native call,
+/// `end_of_statement` is ";" for Java and "" for Kotlin
+fn reachability_fence_code(
+    ctx_span: SourceIdSpan,
+    cfg: &JavaConfig,
+    protect_args: &[&str],
+    end_of_statement: &str,
+) -> Result<String> {
+    use std::fmt::Write;
+
+    let mut reachability_fence_code = String::new();
     match cfg.reachability_fence {
         JavaReachabilityFence::Std => {
-            for arg_name in &protect_args {
+            for arg_name in protect_args {
                 if !reachability_fence_code.is_empty() {
                     reachability_fence_code.push('\n');
                 }
                 write!(
                     &mut reachability_fence_code,
-                    "        java.lang.ref.Reference.reachabilityFence({});",
-                    arg_name
+                    "        java.lang.ref.Reference.reachabilityFence({}){}",
+                    arg_name, end_of_statement
                 )
                 .expect(WRITE_TO_MEM_FAILED_MSG);
             }
@@ -1122,22 +1669,60 @@ fn convert_code_for_method<'a, NI: Iterator<Item = &'a str>>(
                 )
                 .expect(WRITE_TO_MEM_FAILED_MSG);
                 let mut first_arg = true;
-                for arg_name in &protect_args {
+                for arg_name in protect_args {
                     if !first_arg {
                         reachability_fence_code.push_str(", ");
                     }
                     reachability_fence_code.push_str(arg_name);
                     first_arg = false;
                 }
-                reachability_fence_code.push_str(");");
+                reachability_fence_code.push(')');
+                reachability_fence_code.push_str(end_of_statement);
             }
         }
     }
 
-    Ok((conv_code, args_for_call_internal, reachability_fence_code))
+    Ok(reachability_fence_code)
+}
+
+/// `implements` clause and methods of `java.util.Iterator` and `Iterable`
+/// for class generated for Rust iterator, the last method of such class
+/// returns the next element
+fn java_iterator_code(methods_sign: &[JniForeignMethodSignature]) -> (String, String) {
+    let elem_type = methods_sign
+        .last()
+        .map(|f_method| java_code::filter_null_annotation(&f_method.output.base.name))
+        .expect("Internal error: class of iterator without methods");
+    let elem_type = java_code::boxed_type(elem_type.trim());
+    (
+        format!(
+            " implements java.util.Iterator<{elem}>, Iterable<{elem}>",
+            elem = elem_type
+        ),
+        format!(
+            r#"
+    @Override
+    public boolean hasNext() {{
+        return hasNextElement();
+    }}
+    @Override
+    public {elem} next() {{
+        if (!hasNextElement()) {{
+            throw new java.util.NoSuchElementException();
+        }}
+        return nextElement();
+    }}
+    @Override
+    public java.util.Iterator<{elem}> iterator() {{
+        return this;
+    }}
+"#,
+            elem = elem_type
+        ),
+    )
 }
 
-fn calc_output_conv<'a>(
+pub(in crate::java_jni) fn calc_output_conv<'a>(
     output: &'a JavaForeignTypeInfo,
     conv: &'a JavaConverter,
     ret_name: &str,
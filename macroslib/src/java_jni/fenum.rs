@@ -1,23 +1,31 @@
 use log::trace;
+use petgraph::Direction;
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
-use std::{io::Write, rc::Rc};
-use syn::{Ident, Type};
+use std::{fmt::Write as _, io::Write, rc::Rc};
+use syn::{spanned::Spanned, Ident, Type};
 
 use super::{
-    java_class_full_name, java_class_name_to_jni, java_code::doc_comments_to_java_comments,
-    map_write_err, JavaContext,
+    java_class_full_name, java_class_name_to_jni,
+    java_code::{self, doc_comments_to_java_comments, filter_null_annotation},
+    kotlin_code,
+    map_type::map_type,
+    map_write_err,
+    rust_code::java_type_to_jni_signature,
+    JavaContext, JavaForeignTypeInfo, JniForeignMethodSignature,
 };
 use crate::{
-    error::{invalid_src_id_span, DiagnosticError, Result},
+    error::{invalid_src_id_span, panic_on_syn_error, DiagnosticError, Result},
     file_cache::FileWriteCache,
+    source_registry::SourceId,
     typemap::{
         ast::{parse_ty_with_given_span, TypeName},
         ty::{ForeignConversationIntermediate, ForeignConversationRule, ForeignTypeS},
-        RustTypeIdx, TypeConvCode, TypeConvEdge, FROM_VAR_TEMPLATE, TO_VAR_TEMPLATE,
+        ForeignTypeInfo, RustTypeIdx, TypeConvCode, TypeConvEdge, FROM_VAR_TEMPLATE,
+        TO_VAR_TEMPLATE,
     },
-    types::ForeignEnumInfo,
-    WRITE_TO_MEM_FAILED_MSG,
+    types::{ForeignEnumInfo, ForeignEnumItemField},
+    JavaOutputLanguage, WRITE_TO_MEM_FAILED_MSG,
 };
 
 const C_LIKE_ENUM_TRAIT: &str = "SwigForeignCLikeEnum";
@@ -28,6 +36,9 @@ pub(in crate::java_jni) fn generate_enum(
 ) -> Result<()> {
     let enum_name = &fenum.name;
     trace!("generate_enum: enum {}", enum_name);
+    if !fenum.is_c_like() {
+        return generate_enum_with_data(ctx, fenum);
+    }
     if (fenum.items.len() as u64) >= (i32::max_value() as u64) {
         return Err(DiagnosticError::new(
             fenum.src_id,
@@ -43,8 +54,11 @@ pub(in crate::java_jni) fn generate_enum(
         fenum.src_id,
     );
 
-    generate_java_code_for_enum(ctx, fenum)
-        .map_err(|err| DiagnosticError::new(fenum.src_id, fenum.span(), &err))?;
+    match ctx.cfg.output_language {
+        JavaOutputLanguage::Java => generate_java_code_for_enum(ctx, fenum),
+        JavaOutputLanguage::Kotlin => generate_kotlin_code_for_enum(ctx, fenum),
+    }
+    .map_err(|err| DiagnosticError::new(fenum.src_id, fenum.span(), &err))?;
     generate_rust_code_for_enum(ctx, fenum)?;
 
     let jint_rty = ctx.conv_map.ty_to_rust_type(&parse_type! { jint });
@@ -84,6 +98,7 @@ pub(in crate::java_jni) fn generate_enum(
     ctx.conv_map.alloc_foreign_type(enum_ftype)?;
 
     add_conversation_from_enum_to_jobject_for_callbacks(ctx, fenum, enum_rty.to_idx());
+    add_conversation_from_jobject_to_enum(ctx, fenum, enum_rty.to_idx());
     let enum_name = fenum.name.to_string();
     ctx.java_type_to_jni_sig_map.insert(
         enum_name.clone().into(),
@@ -176,6 +191,83 @@ public enum {enum_name} {{"#,
     Ok(())
 }
 
+fn generate_kotlin_code_for_enum(
+    ctx: &mut JavaContext,
+    fenum: &ForeignEnumInfo,
+) -> std::result::Result<(), String> {
+    let path = ctx.cfg.output_dir.join(format!("{}.kt", fenum.name));
+    let mut file = FileWriteCache::new(&path, ctx.generated_foreign_files);
+    let mut enum_doc_comments = doc_comments_to_java_comments(&fenum.doc_comments, true);
+    if !enum_doc_comments.is_empty() {
+        enum_doc_comments.push('\n');
+    }
+    writeln!(
+        file,
+        r#"// Automatically generated by flapigen
+package {package_name}
+
+{doc_comments}enum class {enum_name}(val value: Int) {{"#,
+        package_name = ctx.cfg.package_name,
+        enum_name = fenum.name,
+        doc_comments = enum_doc_comments,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    for (i, item) in fenum.items.iter().enumerate() {
+        let mut doc_comments = doc_comments_to_java_comments(&item.doc_comments, false);
+        if !doc_comments.is_empty() {
+            if !doc_comments.ends_with('\n') {
+                doc_comments.push('\n');
+            }
+            doc_comments.push_str("    ");
+        }
+        writeln!(
+            file,
+            "    {doc_comments}{item_name}({index}){separator}",
+            item_name = item.name,
+            index = i,
+            doc_comments = doc_comments,
+            separator = if i == fenum.items.len() - 1 { ';' } else { ',' },
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+
+    write!(
+        file,
+        r#"
+    companion object {{
+        @JvmStatic
+        internal fun fromInt(x: Int): {enum_name} = when (x) {{"#,
+        enum_name = fenum.name
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    for (i, item) in fenum.items.iter().enumerate() {
+        write!(
+            file,
+            r#"
+            {index} -> {item_name}"#,
+            index = i,
+            item_name = item.name
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+
+    writeln!(
+        file,
+        r#"
+            else -> throw Error("Invalid value for enum {enum_name}: " + x)
+        }}
+    }}
+}}"#,
+        enum_name = fenum.name
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    file.update_file_if_necessary().map_err(&map_write_err)?;
+    Ok(())
+}
+
 fn generate_rust_code_for_enum(ctx: &mut JavaContext, fenum: &ForeignEnumInfo) -> Result<()> {
     let mut arms_to_jint = Vec::with_capacity(fenum.items.len());
     let mut arms_from_jint = Vec::with_capacity(fenum.items.len());
@@ -282,3 +374,815 @@ fn add_conversation_from_enum_to_jobject_for_callbacks(
         ),
     );
 }
+
+/// Used for fields of enum with data, to get enum from Java object without Java code
+fn add_conversation_from_jobject_to_enum(
+    ctx: &mut JavaContext,
+    fenum: &ForeignEnumInfo,
+    fenum_rty: RustTypeIdx,
+) {
+    let java_enum_full_name = java_class_full_name(&ctx.cfg.package_name, &fenum.name.to_string());
+    let enum_class_name = java_class_name_to_jni(&java_enum_full_name);
+    let enum_type = &fenum.name;
+    let enum_id_upper = Ident::new(
+        &format!("FOREIGN_ENUM_{}", fenum.name.to_string().to_uppercase()),
+        Span::call_site(),
+    );
+    let get_value_id = Ident::new(&format!("{}_GET_VALUE", enum_id_upper), Span::call_site());
+    let trait_name = syn::Ident::new(C_LIKE_ENUM_TRAIT, Span::call_site());
+
+    ctx.rust_code.push(quote! {
+        #[allow(dead_code)]
+        impl SwigFrom<jobject> for #enum_type {
+            fn swig_from(x: jobject, env: *mut JNIEnv) -> Self {
+                assert!(!x.is_null(), concat!("null passed as ", #enum_class_name));
+                let _cls: jclass = swig_jni_find_class!(#enum_id_upper, #enum_class_name);
+                let get_value: jmethodID =
+                    swig_jni_get_method_id!(#get_value_id, #enum_id_upper, "getValue", "()I");
+                assert!(!get_value.is_null());
+                let value: jint = unsafe { (**env).CallIntMethod.unwrap()(env, x, get_value) };
+                <#enum_type as #trait_name>::from_jint(value)
+            }
+        }
+    });
+
+    let jobject_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type_no_src_id(&parse_type! { jobject });
+    ctx.conv_map.add_conversation_rule(
+        jobject_ty.to_idx(),
+        fenum_rty,
+        TypeConvEdge::new(
+            TypeConvCode::new2(
+                format!(
+                    "let mut {to_var}: {enum_type} = <{enum_type}>::swig_from({from_var}, env);",
+                    to_var = TO_VAR_TEMPLATE,
+                    from_var = FROM_VAR_TEMPLATE,
+                    enum_type = fenum.name,
+                ),
+                invalid_src_id_span(),
+            ),
+            None,
+        ),
+    );
+}
+
+/// Field of enum item with Java type and conversation code for it
+struct JavaEnumField<'a> {
+    name: &'a Ident,
+    java_ty: JavaForeignTypeInfo,
+    jni_sig: String,
+    /// Code to convert Rust field to JNI type, for `Enum` -> Java
+    to_jni: String,
+    /// Code to convert JNI type to Rust field, for Java -> `Enum`
+    from_jni: Option<String>,
+}
+
+/// Enum with data is mapped to abstract class with subclass for each item,
+/// Rust code creates objects of subclasses and reads fields of them via JNI,
+/// so Java code is not required to convert it
+fn generate_enum_with_data(ctx: &mut JavaContext, fenum: &ForeignEnumInfo) -> Result<()> {
+    let enum_ti: Type = parse_ty_with_given_span(&fenum.name.to_string(), fenum.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(fenum.src_id, err))?;
+    let enum_rty = ctx.conv_map.find_or_alloc_rust_type(&enum_ti, fenum.src_id);
+
+    let mut items_fields = Vec::with_capacity(fenum.items.len());
+    let mut from_jni_error = None;
+    for item in &fenum.items {
+        let mut fields = Vec::with_capacity(item.fields.as_slice().len());
+        for field in item.fields.as_slice() {
+            let field = map_enum_field(ctx, fenum, field)?;
+            if field.from_jni.is_none() && from_jni_error.is_none() {
+                from_jni_error = Some(format!("{}.{}", item.name, field.name));
+            }
+            fields.push(field);
+        }
+        items_fields.push(fields);
+    }
+    if let Some(field) = from_jni_error.as_ref() {
+        println!(
+            "cargo:warning=enum {} can not be used as input, because of field {}",
+            fenum.name, field
+        );
+    }
+
+    match ctx.cfg.output_language {
+        JavaOutputLanguage::Java => {
+            generate_java_code_for_enum_with_data(ctx, fenum, &items_fields)
+        }
+        JavaOutputLanguage::Kotlin => {
+            generate_kotlin_code_for_enum_with_data(ctx, fenum, &items_fields)
+        }
+    }
+    .map_err(|err| DiagnosticError::new(fenum.src_id, fenum.span(), &err))?;
+    generate_rust_code_for_enum_with_data(ctx, fenum, &items_fields, from_jni_error.is_none());
+
+    let enum_jobj_rty = ctx.conv_map.find_or_alloc_rust_type_with_suffix(
+        &parse_type! { jobject },
+        &fenum.name.to_string(),
+        SourceId::none(),
+    );
+    ctx.conv_map.add_conversation_rule(
+        enum_rty.to_idx(),
+        enum_jobj_rty.to_idx(),
+        TypeConvEdge::new(
+            TypeConvCode::new2(
+                format!(
+                    "let mut {to_var}: jobject = <jobject>::swig_from({from_var}, env);",
+                    to_var = TO_VAR_TEMPLATE,
+                    from_var = FROM_VAR_TEMPLATE,
+                ),
+                invalid_src_id_span(),
+            ),
+            None,
+        ),
+    );
+    let from_into_rust = if from_jni_error.is_none() {
+        ctx.conv_map.add_conversation_rule(
+            enum_jobj_rty.to_idx(),
+            enum_rty.to_idx(),
+            TypeConvEdge::new(
+                TypeConvCode::new2(
+                    format!(
+                        "let mut {to_var}: {enum_type} = <{enum_type}>::swig_from({from_var}, env);",
+                        to_var = TO_VAR_TEMPLATE,
+                        from_var = FROM_VAR_TEMPLATE,
+                        enum_type = fenum.name,
+                    ),
+                    invalid_src_id_span(),
+                ),
+                None,
+            ),
+        );
+        Some(ForeignConversationRule {
+            rust_ty: enum_jobj_rty.to_idx(),
+            intermediate: None,
+        })
+    } else {
+        None
+    };
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(fenum.name.to_string(), (fenum.src_id, fenum.name.span())),
+        provides_by_module: vec![],
+        into_from_rust: Some(ForeignConversationRule {
+            rust_ty: enum_jobj_rty.to_idx(),
+            intermediate: None,
+        }),
+        from_into_rust,
+        name_prefix: None,
+    })?;
+
+    add_option_of_enum_with_data(ctx, fenum, from_jni_error.is_none())?;
+
+    let enum_name = fenum.name.to_string();
+    ctx.java_type_to_jni_sig_map.insert(
+        enum_name.clone().into(),
+        format!(
+            "L{};",
+            java_class_name_to_jni(&java_class_full_name(&ctx.cfg.package_name, &enum_name))
+        )
+        .into(),
+    );
+    Ok(())
+}
+
+/// `Option<Enum>` is passed as nullable object of enum class,
+/// Java code converts it to `java.util.Optional<Enum>` for output
+fn add_option_of_enum_with_data(
+    ctx: &mut JavaContext,
+    fenum: &ForeignEnumInfo,
+    from_jni: bool,
+) -> Result<()> {
+    let enum_name = &fenum.name;
+    let opt_rty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&parse_type! { Option<#enum_name> }, fenum.src_id);
+    let opt_jobj_rty = ctx.conv_map.find_or_alloc_rust_type_with_suffix(
+        &parse_type! { jobject },
+        &format!("Option{}", enum_name),
+        SourceId::none(),
+    );
+    ctx.conv_map.add_conversation_rule(
+        opt_rty.to_idx(),
+        opt_jobj_rty.to_idx(),
+        TypeConvEdge::new(
+            TypeConvCode::new2(
+                format!(
+                    r#"let mut {to_var}: jobject = match {from_var} {{
+        Some(x) => <jobject>::swig_from(x, env),
+        None => ::std::ptr::null_mut(),
+    }};"#,
+                    to_var = TO_VAR_TEMPLATE,
+                    from_var = FROM_VAR_TEMPLATE,
+                ),
+                invalid_src_id_span(),
+            ),
+            None,
+        ),
+    );
+    if from_jni {
+        ctx.conv_map.add_conversation_rule(
+            opt_jobj_rty.to_idx(),
+            opt_rty.to_idx(),
+            TypeConvEdge::new(
+                TypeConvCode::new2(
+                    format!(
+                        r#"let mut {to_var}: Option<{enum_type}> = if !{from_var}.is_null() {{
+        Some(<{enum_type}>::swig_from({from_var}, env))
+    }} else {{
+        None
+    }};"#,
+                        to_var = TO_VAR_TEMPLATE,
+                        from_var = FROM_VAR_TEMPLATE,
+                        enum_type = enum_name,
+                    ),
+                    invalid_src_id_span(),
+                ),
+                None,
+            ),
+        );
+    }
+
+    let (nullable, non_null) = if ctx.cfg.null_annotation_package.is_some() {
+        ("@Nullable ", "@NonNull ")
+    } else {
+        ("", "")
+    };
+    let opt_jobj_rule = ForeignConversationRule {
+        rust_ty: opt_jobj_rty.to_idx(),
+        intermediate: None,
+    };
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(
+            format!("/*opt*/{}{}", nullable, enum_name),
+            (fenum.src_id, fenum.name.span()),
+        ),
+        provides_by_module: vec![],
+        into_from_rust: Some(opt_jobj_rule.clone()),
+        from_into_rust: if from_jni { Some(opt_jobj_rule) } else { None },
+        name_prefix: Some("/*opt*/".into()),
+    })?;
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(
+            format!("{}java.util.Optional<{}>", non_null, enum_name),
+            (fenum.src_id, fenum.name.span()),
+        ),
+        provides_by_module: vec![],
+        into_from_rust: Some(ForeignConversationRule {
+            rust_ty: opt_rty.to_idx(),
+            intermediate: Some(ForeignConversationIntermediate {
+                input_to_output: false,
+                intermediate_ty: opt_jobj_rty.to_idx(),
+                conv_code: Rc::new(TypeConvCode::new(
+                    format!(
+                        "        java.util.Optional<{enum_name}> {out} = java.util.Optional.ofNullable({var});",
+                        enum_name = enum_name,
+                        out = TO_VAR_TEMPLATE,
+                        var = FROM_VAR_TEMPLATE,
+                    ),
+                    invalid_src_id_span(),
+                )),
+            }),
+        }),
+        from_into_rust: None,
+        name_prefix: None,
+    })?;
+    Ok(())
+}
+
+fn map_enum_field<'a>(
+    ctx: &mut JavaContext,
+    fenum: &ForeignEnumInfo,
+    field: &'a ForeignEnumItemField,
+) -> Result<JavaEnumField<'a>> {
+    let field_span = (fenum.src_id, field.ty.span());
+    let field_rty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&field.ty, fenum.src_id);
+    let mut java_ty = map_type(ctx, &field_rty, Direction::Outgoing, field_span)?;
+    if let Some(java_conv) = java_ty.java_converter.take() {
+        // Java code can not be used here, so convert it to jobject in Rust,
+        // the same as for callbacks
+        let jobject_ty = ctx
+            .conv_map
+            .find_or_alloc_rust_type_no_src_id(&parse_type! { jobject });
+        ctx.conv_map
+            .convert_rust_types(
+                field_rty.to_idx(),
+                jobject_ty.to_idx(),
+                "x",
+                "y",
+                "ret",
+                field_span,
+            )
+            .map_err(|err| {
+                err.add_span_note(
+                    field_span,
+                    format!(
+                        "Java code required to convert type to jobject
+It is impossible to use this Java code:{}\nfor field of enum",
+                        java_conv.converter
+                    ),
+                )
+            })?;
+        java_ty.base.correspoding_rust_type = jobject_ty;
+    }
+    if java_ty.base.correspoding_rust_type.typename().contains('<') {
+        // wrappers like `JForeignObjectsArray<T>` are not JNI types
+        return Err(DiagnosticError::new2(
+            field_span,
+            format!(
+                "type {} can not be used as field of enum, it is converted to {}",
+                field_rty, java_ty.base.correspoding_rust_type
+            ),
+        ));
+    }
+    let java_name = filter_null_annotation(&java_ty.base.name);
+    let jni_sig = java_type_to_jni_signature(ctx, java_name.trim())
+        .ok_or_else(|| {
+            DiagnosticError::new2(
+                field_span,
+                format!(
+                    "Unknown type `{}`, can not generate JNI signature",
+                    java_ty.base.name
+                ),
+            )
+        })?
+        .replace('.', "/");
+
+    let (deps, to_jni) = ctx.conv_map.convert_rust_types(
+        field_rty.to_idx(),
+        java_ty.base.correspoding_rust_type.to_idx(),
+        &field.name.to_string(),
+        &field.name.to_string(),
+        "jobject",
+        field_span,
+    )?;
+    ctx.rust_code.extend(deps);
+
+    let from_jni = match map_type(ctx, &field_rty, Direction::Incoming, field_span) {
+        Ok(in_ty)
+            if in_ty.java_converter.is_none()
+                && filter_null_annotation(&in_ty.base.name) == java_name
+                && !in_ty.base.correspoding_rust_type.typename().contains('<') =>
+        {
+            match ctx.conv_map.convert_rust_types(
+                in_ty.base.correspoding_rust_type.to_idx(),
+                field_rty.to_idx(),
+                &field.name.to_string(),
+                &field.name.to_string(),
+                "Self",
+                field_span,
+            ) {
+                Ok((deps, code)) => {
+                    ctx.rust_code.extend(deps);
+                    Some((in_ty.base.correspoding_rust_type, code))
+                }
+                Err(_) => None,
+            }
+        }
+        Ok(_) | Err(_) => {
+            let jobject_ty = ctx
+                .conv_map
+                .find_or_alloc_rust_type_no_src_id(&parse_type! { jobject });
+            if java_ty.base.correspoding_rust_type.to_idx() == jobject_ty.to_idx() {
+                ctx.conv_map
+                    .convert_rust_types(
+                        jobject_ty.to_idx(),
+                        field_rty.to_idx(),
+                        &field.name.to_string(),
+                        &field.name.to_string(),
+                        "Self",
+                        field_span,
+                    )
+                    .ok()
+                    .map(|(deps, code)| {
+                        ctx.rust_code.extend(deps);
+                        (jobject_ty, code)
+                    })
+            } else {
+                None
+            }
+        }
+    };
+    let from_jni = from_jni.map(|(jni_ty, code)| {
+        format!(
+            "let {name}: {jni_ty} = unsafe {{ (**env).{getter}.unwrap()(env, x, field_id) }};\n{code}",
+            name = field.name,
+            jni_ty = jni_ty,
+            getter = jni_field_getter(&jni_sig),
+            code = code
+        )
+    });
+
+    Ok(JavaEnumField {
+        name: &field.name,
+        java_ty,
+        jni_sig,
+        to_jni,
+        from_jni,
+    })
+}
+
+/// `jvalue` union member and `Get*Field` JNI function for JNI type signature
+fn jni_value_member(jni_sig: &str) -> &'static str {
+    match jni_sig {
+        "Z" => "z",
+        "B" => "b",
+        "C" => "c",
+        "S" => "s",
+        "I" => "i",
+        "J" => "j",
+        "F" => "f",
+        "D" => "d",
+        _ => "l",
+    }
+}
+
+fn jni_field_getter(jni_sig: &str) -> &'static str {
+    match jni_sig {
+        "Z" => "GetBooleanField",
+        "B" => "GetByteField",
+        "C" => "GetCharField",
+        "S" => "GetShortField",
+        "I" => "GetIntField",
+        "J" => "GetLongField",
+        "F" => "GetFloatField",
+        "D" => "GetDoubleField",
+        _ => "GetObjectField",
+    }
+}
+
+fn enum_item_method_sign(
+    ctx: &mut JavaContext,
+    fields: &[JavaEnumField],
+) -> JniForeignMethodSignature {
+    let void_rty = ctx
+        .conv_map
+        .find_or_alloc_rust_type_no_src_id(&parse_type! { () });
+    JniForeignMethodSignature {
+        output: ForeignTypeInfo {
+            name: "void".into(),
+            correspoding_rust_type: void_rty,
+        }
+        .into(),
+        input: fields
+            .iter()
+            .map(|f| JavaForeignTypeInfo {
+                base: ForeignTypeInfo {
+                    name: f.java_ty.base.name.clone(),
+                    correspoding_rust_type: f.java_ty.base.correspoding_rust_type.clone(),
+                },
+                java_converter: None,
+                annotation: f.java_ty.annotation,
+            })
+            .collect(),
+    }
+}
+
+fn generate_java_code_for_enum_with_data(
+    ctx: &mut JavaContext,
+    fenum: &ForeignEnumInfo,
+    items_fields: &[Vec<JavaEnumField>],
+) -> std::result::Result<(), String> {
+    let use_null_annotation = ctx.cfg.null_annotation_package.is_some();
+    let items_sign: Vec<_> = items_fields
+        .iter()
+        .map(|fields| enum_item_method_sign(ctx, fields))
+        .collect();
+    let imports = java_code::get_null_annotation_imports(
+        ctx.cfg.null_annotation_package.as_deref(),
+        &items_sign,
+    );
+
+    let path = ctx.cfg.output_dir.join(format!("{}.java", fenum.name));
+    let mut file = FileWriteCache::new(&path, ctx.generated_foreign_files);
+    let enum_doc_comments = doc_comments_to_java_comments(&fenum.doc_comments, true);
+    writeln!(
+        file,
+        r#"// Automatically generated by flapigen
+package {package_name};
+{imports}
+{doc_comments}
+public abstract class {enum_name} {{
+    private {enum_name}() {{}}"#,
+        package_name = ctx.cfg.package_name,
+        enum_name = fenum.name,
+        doc_comments = enum_doc_comments,
+        imports = imports,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    for (idx, ((item, fields), item_sign)) in fenum
+        .items
+        .iter()
+        .zip(items_fields)
+        .zip(&items_sign)
+        .enumerate()
+    {
+        let mut doc_comments = doc_comments_to_java_comments(&item.doc_comments, false);
+        doc_comments.push_str(if doc_comments.is_empty() {
+            "    "
+        } else {
+            "\n    "
+        });
+        let args = java_code::args_with_java_types(
+            item_sign,
+            fields
+                .iter()
+                .map(|f| f.name.to_string())
+                .collect::<Vec<_>>()
+                .iter()
+                .map(String::as_str),
+            java_code::ArgsFormatFlags::EXTERNAL,
+            use_null_annotation,
+        );
+        let mut field_decls = String::new();
+        let mut field_inits = String::new();
+        for field in fields {
+            let field_arg = java_code::args_with_java_types(
+                &enum_item_method_sign(ctx, std::slice::from_ref(field)),
+                std::iter::once(field.name.to_string().as_str()),
+                java_code::ArgsFormatFlags::EXTERNAL,
+                use_null_annotation,
+            );
+            writeln!(&mut field_decls, "        public final {};", field_arg)
+                .expect(WRITE_TO_MEM_FAILED_MSG);
+            writeln!(
+                &mut field_inits,
+                "            this.{name} = {name};",
+                name = field.name
+            )
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+        let (equals_body, hash_code_body) = if fields.is_empty() {
+            (
+                format!("return obj instanceof {};", item.name),
+                format!("return {};", idx),
+            )
+        } else {
+            let names = fields
+                .iter()
+                .map(|f| f.name.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let other_names = fields
+                .iter()
+                .map(|f| format!("other.{}", f.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            (
+                format!(
+                    r#"if (!(obj instanceof {item_name})) {{
+                return false;
+            }}
+            {item_name} other = ({item_name}) obj;
+            return java.util.Arrays.deepEquals(new Object[] {{{names}}},
+                                               new Object[] {{{other_names}}});"#,
+                    item_name = item.name,
+                    names = names,
+                    other_names = other_names
+                ),
+                format!(
+                    "return java.util.Arrays.deepHashCode(new Object[] {{{}}});",
+                    names
+                ),
+            )
+        };
+        writeln!(
+            file,
+            r#"
+{doc_comments}public static final class {item_name} extends {enum_name} {{
+{field_decls}        public {item_name}({args}) {{
+{field_inits}        }}
+        @Override
+        public boolean equals(Object obj) {{
+            {equals_body}
+        }}
+        @Override
+        public int hashCode() {{
+            {hash_code_body}
+        }}
+    }}"#,
+            doc_comments = doc_comments,
+            item_name = item.name,
+            enum_name = fenum.name,
+            field_decls = field_decls,
+            args = args,
+            field_inits = field_inits,
+            equals_body = equals_body,
+            hash_code_body = hash_code_body,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(file, "}}").expect(WRITE_TO_MEM_FAILED_MSG);
+
+    file.update_file_if_necessary().map_err(&map_write_err)?;
+    Ok(())
+}
+
+fn generate_kotlin_code_for_enum_with_data(
+    ctx: &mut JavaContext,
+    fenum: &ForeignEnumInfo,
+    items_fields: &[Vec<JavaEnumField>],
+) -> std::result::Result<(), String> {
+    let path = ctx.cfg.output_dir.join(format!("{}.kt", fenum.name));
+    let mut file = FileWriteCache::new(&path, ctx.generated_foreign_files);
+    let mut enum_doc_comments = doc_comments_to_java_comments(&fenum.doc_comments, true);
+    if !enum_doc_comments.is_empty() {
+        enum_doc_comments.push('\n');
+    }
+    writeln!(
+        file,
+        r#"// Automatically generated by flapigen
+package {package_name}
+
+{doc_comments}sealed class {enum_name} {{"#,
+        package_name = ctx.cfg.package_name,
+        enum_name = fenum.name,
+        doc_comments = enum_doc_comments,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    for (idx, (item, fields)) in fenum.items.iter().zip(items_fields).enumerate() {
+        let mut doc_comments = doc_comments_to_java_comments(&item.doc_comments, false);
+        doc_comments.push_str(if doc_comments.is_empty() {
+            "    "
+        } else {
+            "\n    "
+        });
+        let (equals_body, hash_code_body) = if fields.is_empty() {
+            (format!("other is {}", item.name), idx.to_string())
+        } else {
+            let names = fields
+                .iter()
+                .map(|f| f.name.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let other_names = fields
+                .iter()
+                .map(|f| format!("other.{}", f.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            (
+                format!(
+                    "other is {} &&\n                java.util.Arrays.deepEquals(arrayOf<Any?>({}), arrayOf<Any?>({}))",
+                    item.name, names, other_names
+                ),
+                format!("java.util.Arrays.deepHashCode(arrayOf<Any?>({}))", names),
+            )
+        };
+        let args = fields
+            .iter()
+            .map(|f| {
+                format!(
+                    "@JvmField val {}: {}",
+                    f.name,
+                    kotlin_code::jvm_type_of(&f.java_ty)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            file,
+            r#"
+{doc_comments}class {item_name}({args}) : {enum_name}() {{
+        override fun equals(other: Any?): Boolean =
+            {equals_body}
+        override fun hashCode(): Int = {hash_code_body}
+    }}"#,
+            doc_comments = doc_comments,
+            item_name = item.name,
+            enum_name = fenum.name,
+            args = args,
+            equals_body = equals_body,
+            hash_code_body = hash_code_body,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(file, "}}").expect(WRITE_TO_MEM_FAILED_MSG);
+
+    file.update_file_if_necessary().map_err(&map_write_err)?;
+    Ok(())
+}
+
+fn generate_rust_code_for_enum_with_data(
+    ctx: &mut JavaContext,
+    fenum: &ForeignEnumInfo,
+    items_fields: &[Vec<JavaEnumField>],
+    from_jni: bool,
+) {
+    let enum_type = &fenum.name;
+    let enum_class_name = java_class_name_to_jni(&java_class_full_name(
+        &ctx.cfg.package_name,
+        &fenum.name.to_string(),
+    ));
+    let enum_id_upper = format!("FOREIGN_ENUM_{}", fenum.name.to_string().to_uppercase());
+
+    let mut arms_to_jobject = Vec::with_capacity(fenum.items.len());
+    let mut checks_from_jobject = Vec::with_capacity(fenum.items.len());
+    for (item, fields) in fenum.items.iter().zip(items_fields) {
+        let item_class_name = format!("{}${}", enum_class_name, item.name);
+        let item_id = format!("{}_{}", enum_id_upper, item.name.to_string().to_uppercase());
+        let item_class_id = Ident::new(&item_id, Span::call_site());
+        let ctor_id = Ident::new(&format!("{}_CTOR", item_id), Span::call_site());
+        let ctor_sig = format!(
+            "({})V",
+            fields
+                .iter()
+                .map(|f| f.jni_sig.as_str())
+                .collect::<String>()
+        );
+        let pattern = item.fields.rust_pattern(&item.rust_name);
+
+        let mut to_jni_code = String::new();
+        for field in fields {
+            to_jni_code.push_str(&field.to_jni);
+        }
+        let to_jni_code: TokenStream = syn::parse_str(&to_jni_code).unwrap_or_else(|err| {
+            panic_on_syn_error("java enum field conversation", to_jni_code, err)
+        });
+        let args = fields.iter().map(|f| {
+            let name = &f.name;
+            let member = Ident::new(jni_value_member(&f.jni_sig), Span::call_site());
+            quote!(jvalue { #member: #name })
+        });
+        let local_refs = fields
+            .iter()
+            .filter(|f| jni_value_member(&f.jni_sig) == "l")
+            .map(|f| &f.name);
+        arms_to_jobject.push(quote! {
+            #pattern => {
+                let cls: jclass = swig_jni_find_class!(#item_class_id, #item_class_name);
+                assert!(!cls.is_null());
+                let ctor: jmethodID =
+                    swig_jni_get_method_id!(#ctor_id, #item_class_id, "<init>", #ctor_sig);
+                assert!(!ctor.is_null());
+                #to_jni_code
+                let args = [#(#args),*];
+                let ret: jobject =
+                    unsafe { (**env).NewObjectA.unwrap()(env, cls, ctor, args.as_ptr()) };
+                assert!(!ret.is_null(), concat!("Can not create ", #item_class_name));
+                #(unsafe { (**env).DeleteLocalRef.unwrap()(env, #local_refs) };)*
+                ret
+            }
+        });
+
+        if from_jni {
+            let mut from_jni_code = Vec::with_capacity(fields.len());
+            for field in fields {
+                let field_id = Ident::new(
+                    &format!(
+                        "{}_FIELD_{}",
+                        item_id,
+                        field.name.to_string().to_uppercase()
+                    ),
+                    Span::call_site(),
+                );
+                let field_name = field.name.to_string();
+                let jni_sig = &field.jni_sig;
+                let code = field.from_jni.as_ref().expect("checked before");
+                let code: TokenStream = syn::parse_str(code).unwrap_or_else(|err| {
+                    panic_on_syn_error("java enum field conversation", code.clone(), err)
+                });
+                from_jni_code.push(quote! {
+                    let field_id: jfieldID =
+                        swig_jni_get_field_id!(#field_id, #item_class_id, #field_name, #jni_sig);
+                    assert!(!field_id.is_null());
+                    #code
+                });
+            }
+            checks_from_jobject.push(quote! {
+                let cls: jclass = swig_jni_find_class!(#item_class_id, #item_class_name);
+                assert!(!cls.is_null());
+                if unsafe { (**env).IsInstanceOf.unwrap()(env, x, cls) } != 0 {
+                    #(#from_jni_code)*
+                    return #pattern;
+                }
+            });
+        }
+    }
+
+    ctx.rust_code.push(quote! {
+        #[allow(dead_code, unused_mut)]
+        impl SwigFrom<#enum_type> for jobject {
+            fn swig_from(x: #enum_type, env: *mut JNIEnv) -> jobject {
+                match x {
+                    #(#arms_to_jobject)*
+                }
+            }
+        }
+    });
+    if from_jni {
+        ctx.rust_code.push(quote! {
+            #[allow(dead_code, unused_mut)]
+            impl SwigFrom<jobject> for #enum_type {
+                fn swig_from(x: jobject, env: *mut JNIEnv) -> Self {
+                    assert!(!x.is_null(), concat!("null passed as ", #enum_class_name));
+                    #(#checks_from_jobject)*
+                    panic!(concat!("Unknown subclass of ", #enum_class_name));
+                }
+            }
+        });
+    }
+}
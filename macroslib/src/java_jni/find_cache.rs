@@ -152,24 +152,27 @@ impl<'ast> Visit<'ast> for JniCacheMacroCallsVisitor<'ast> {
                 syn::parse2(mac.tokens.clone()).expect("Can not parse swig_jni_find_class call");
             let id = find_class.id.to_string();
             if let Some(call) = self.inner.calls.get(&id) {
-                if *call != find_class {
-                    println!(
-                        "waring=You use the same id '{}' for different classes '{}' vs '{}'",
+                // the same class may be found several times, keep
+                // methods and fields that were already found for it
+                if call.path == find_class.path {
+                    return;
+                }
+                println!(
+                    "waring=You use the same id '{}' for different classes '{}' vs '{}'",
+                    id,
+                    call.path.value(),
+                    find_class.path.value()
+                );
+                self.errors.push(syn::Error::new(
+                    mac.span(),
+                    format!(
+                        "You use the same id '{}' for different classes '{}' vs '{}'",
                         id,
                         call.path.value(),
                         find_class.path.value()
-                    );
-                    self.errors.push(syn::Error::new(
-                        mac.span(),
-                        format!(
-                            "You use the same id '{}' for different classes '{}' vs '{}'",
-                            id,
-                            call.path.value(),
-                            find_class.path.value()
-                        ),
-                    ));
-                    return;
-                }
+                    ),
+                ));
+                return;
             }
             self.inner.calls.insert(id, find_class);
         } else if mac.path.is_ident(SWIG_JNI_GET_METHOD_ID) {
@@ -7,8 +7,8 @@ use std::io::Write;
 use syn::{spanned::Spanned, Ident};
 
 use super::{
-    java_code, map_type::map_type, map_write_err, rust_code, JavaContext, JavaForeignTypeInfo,
-    JniForeignMethodSignature,
+    java_code, kotlin_code, map_type::map_type, map_write_err, rust_code, JavaContext,
+    JavaForeignTypeInfo, JniForeignMethodSignature,
 };
 use crate::{
     error::{panic_on_syn_error, DiagnosticError, Result},
@@ -21,7 +21,7 @@ use crate::{
         ForeignTypeInfo,
     },
     types::ForeignInterface,
-    WRITE_TO_MEM_FAILED_MSG,
+    JavaOutputLanguage, WRITE_TO_MEM_FAILED_MSG,
 };
 
 pub(in crate::java_jni) fn generate_interface(
@@ -29,12 +29,17 @@ pub(in crate::java_jni) fn generate_interface(
     interface: &ForeignInterface,
 ) -> Result<()> {
     let f_methods = find_suitable_ftypes_for_interace_methods(ctx, interface)?;
-    generate_java_code_for_interface(
-        ctx,
-        interface,
-        &f_methods,
-        ctx.cfg.null_annotation_package.as_deref(),
-    )
+    match ctx.cfg.output_language {
+        JavaOutputLanguage::Java => generate_java_code_for_interface(
+            ctx,
+            interface,
+            &f_methods,
+            ctx.cfg.null_annotation_package.as_deref(),
+        ),
+        JavaOutputLanguage::Kotlin => {
+            generate_kotlin_code_for_interface(ctx, interface, &f_methods)
+        }
+    }
     .map_err(|err| DiagnosticError::new(interface.src_id, interface.span(), err))?;
     generate_rust_code_for_interface(ctx, interface, &f_methods)?;
 
@@ -149,11 +154,16 @@ fn generate_java_code_for_interface(
         r#"// Automatically generated by flapigen
 package {package_name};
 {imports}
-{doc_comments}
+{doc_comments}{functional_interface}
 public interface {interface_name} {{"#,
         package_name = ctx.cfg.package_name,
         interface_name = interface.name,
         doc_comments = interface_comments,
+        functional_interface = if interface.is_closure {
+            "\n@FunctionalInterface"
+        } else {
+            ""
+        },
         imports = imports,
     )
     .expect(WRITE_TO_MEM_FAILED_MSG);
@@ -182,6 +192,79 @@ public interface {interface_name} {{"#,
     Ok(())
 }
 
+/// Rust side finds methods by JNI signatures of Java types,
+/// so arguments have exact JVM types without conversations
+fn generate_kotlin_code_for_interface(
+    ctx: &mut JavaContext,
+    interface: &ForeignInterface,
+    methods_sign: &[JniForeignMethodSignature],
+) -> std::result::Result<(), String> {
+    let path = ctx.cfg.output_dir.join(format!("{}.kt", interface.name));
+    let mut file = FileWriteCache::new(&path, ctx.generated_foreign_files);
+    let mut interface_comments =
+        java_code::doc_comments_to_java_comments(&interface.doc_comments, true);
+    if !interface_comments.is_empty() {
+        interface_comments.push('\n');
+    }
+    // SAM conversion is possible only for interface with one method
+    let interface_kind = if interface.items.len() == 1 {
+        "fun interface"
+    } else {
+        "interface"
+    };
+    writeln!(
+        file,
+        r#"// Automatically generated by flapigen
+package {package_name}
+
+{doc_comments}{interface_kind} {interface_name} {{"#,
+        package_name = ctx.cfg.package_name,
+        interface_name = interface.name,
+        interface_kind = interface_kind,
+        doc_comments = interface_comments,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    for (method, f_method) in interface.items.iter().zip(methods_sign) {
+        let args: Vec<String> = f_method
+            .input
+            .iter()
+            .zip(method.arg_names_without_self())
+            .map(|(arg, arg_name)| {
+                format!(
+                    "{}: {}",
+                    kotlin_code::kotlin_ident(arg_name),
+                    kotlin_code::jvm_type_of(arg)
+                )
+            })
+            .collect();
+        let output_type = kotlin_code::jvm_type_of(&f_method.output);
+        let mut doc_comments =
+            java_code::doc_comments_to_java_comments(&method.doc_comments, false);
+        if !doc_comments.is_empty() {
+            doc_comments.push('\n');
+        }
+        writeln!(
+            file,
+            r#"
+{doc_comments}    fun {method_name}({args}){output_type}"#,
+            method_name = kotlin_code::kotlin_ident(&method.name.to_string()),
+            doc_comments = doc_comments,
+            args = args.join(", "),
+            output_type = if output_type == "Unit" {
+                String::new()
+            } else {
+                format!(": {}", output_type)
+            },
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+
+    file.write_all(b"}\n").expect(WRITE_TO_MEM_FAILED_MSG);
+    file.update_file_if_necessary().map_err(&map_write_err)?;
+    Ok(())
+}
+
 fn generate_rust_code_for_interface(
     ctx: &mut JavaContext,
     interface: &ForeignInterface,
@@ -326,7 +409,7 @@ impl SwigFrom<jobject> for Box<dyn {trait_name}> {{
                     "jlong" => quote!{ CallLongMethod },
                     "jfloat" => quote!{ CallFloatMethod },
                     "jdouble" => quote!{ CallDoubleMethod },
-                    "jobject" => quote!{ CallObjectMethod },
+                    "jobject" | "jstring" => quote!{ CallObjectMethod },
                     _ => return Err(DiagnosticError::new2(jni_ret_type.src_id_span(),
                                                           format!("Have not idea how to handle this type `{}` as return of callback function", jni_ret_type))),
                 };
@@ -147,6 +147,22 @@ pub(in crate::java_jni) fn filter_null_annotation(type_name: &str) -> String {
     type_name.replace("@NonNull", "").replace("@Nullable", "")
 }
 
+/// Class of boxed value for primitive type, to use type as generic argument
+pub(in crate::java_jni) fn boxed_type(type_name: &str) -> &str {
+    match type_name {
+        "void" => "Void",
+        "boolean" => "Boolean",
+        "byte" => "Byte",
+        "char" => "Character",
+        "short" => "Short",
+        "int" => "Integer",
+        "long" => "Long",
+        "float" => "Float",
+        "double" => "Double",
+        _ => type_name,
+    }
+}
+
 pub(in crate::java_jni) fn is_primitive_type(type_name: &str) -> bool {
     match type_name {
         "void" | "boolean" | "byte" | "short" | "int" | "long" | "float" | "double" => true,
@@ -0,0 +1,221 @@
+
+/// Value returned by JNI conversion that can be passed
+/// to `CompletableFuture.complete`, primitive types are boxed
+#[allow(dead_code)]
+trait SwigIntoJavaObject {
+    fn into_java_object(self, env: *mut JNIEnv) -> jobject;
+}
+
+impl SwigIntoJavaObject for jobject {
+    fn into_java_object(self, _env: *mut JNIEnv) -> jobject {
+        self
+    }
+}
+
+impl SwigIntoJavaObject for () {
+    fn into_java_object(self, _env: *mut JNIEnv) -> jobject {
+        ::std::ptr::null_mut()
+    }
+}
+
+impl<T: SwigForeignClass> SwigIntoJavaObject for internal_aliases::JForeignObjectsArray<T> {
+    fn into_java_object(self, _env: *mut JNIEnv) -> jobject {
+        self.inner
+    }
+}
+
+impl SwigIntoJavaObject for jboolean {
+    fn into_java_object(self, env: *mut JNIEnv) -> jobject {
+        let class = swig_jni_find_class!(JAVA_LANG_BOOLEAN, "java/lang/Boolean");
+        let value_of = swig_jni_get_static_method_id!(
+            JAVA_LANG_BOOLEAN_VALUE_OF,
+            JAVA_LANG_BOOLEAN,
+            "valueOf",
+            "(Z)Ljava/lang/Boolean;"
+        );
+        unsafe {
+            (**env).CallStaticObjectMethod.unwrap()(
+                env,
+                class,
+                value_of,
+                ::std::os::raw::c_int::from(self),
+            )
+        }
+    }
+}
+
+impl SwigIntoJavaObject for jbyte {
+    fn into_java_object(self, env: *mut JNIEnv) -> jobject {
+        let class = swig_jni_find_class!(JAVA_LANG_BYTE, "java/lang/Byte");
+        let value_of = swig_jni_get_static_method_id!(
+            JAVA_LANG_BYTE_VALUE_OF,
+            JAVA_LANG_BYTE,
+            "valueOf",
+            "(B)Ljava/lang/Byte;"
+        );
+        unsafe {
+            (**env).CallStaticObjectMethod.unwrap()(
+                env,
+                class,
+                value_of,
+                ::std::os::raw::c_int::from(self),
+            )
+        }
+    }
+}
+
+impl SwigIntoJavaObject for jchar {
+    fn into_java_object(self, env: *mut JNIEnv) -> jobject {
+        let class = swig_jni_find_class!(JAVA_LANG_CHARACTER, "java/lang/Character");
+        let value_of = swig_jni_get_static_method_id!(
+            JAVA_LANG_CHARACTER_VALUE_OF,
+            JAVA_LANG_CHARACTER,
+            "valueOf",
+            "(C)Ljava/lang/Character;"
+        );
+        unsafe {
+            (**env).CallStaticObjectMethod.unwrap()(
+                env,
+                class,
+                value_of,
+                ::std::os::raw::c_int::from(self),
+            )
+        }
+    }
+}
+
+impl SwigIntoJavaObject for jshort {
+    fn into_java_object(self, env: *mut JNIEnv) -> jobject {
+        let class = swig_jni_find_class!(JAVA_LANG_SHORT, "java/lang/Short");
+        let value_of = swig_jni_get_static_method_id!(
+            JAVA_LANG_SHORT_VALUE_OF,
+            JAVA_LANG_SHORT,
+            "valueOf",
+            "(S)Ljava/lang/Short;"
+        );
+        unsafe {
+            (**env).CallStaticObjectMethod.unwrap()(
+                env,
+                class,
+                value_of,
+                ::std::os::raw::c_int::from(self),
+            )
+        }
+    }
+}
+
+impl SwigIntoJavaObject for jint {
+    fn into_java_object(self, env: *mut JNIEnv) -> jobject {
+        let class = swig_jni_find_class!(JAVA_LANG_INTEGER, "java/lang/Integer");
+        let value_of = swig_jni_get_static_method_id!(
+            JAVA_LANG_INTEGER_VALUE_OF,
+            JAVA_LANG_INTEGER,
+            "valueOf",
+            "(I)Ljava/lang/Integer;"
+        );
+        unsafe { (**env).CallStaticObjectMethod.unwrap()(env, class, value_of, self) }
+    }
+}
+
+impl SwigIntoJavaObject for jlong {
+    fn into_java_object(self, env: *mut JNIEnv) -> jobject {
+        let class = swig_jni_find_class!(JAVA_LANG_LONG, "java/lang/Long");
+        let value_of = swig_jni_get_static_method_id!(
+            JAVA_LANG_LONG_VALUE_OF,
+            JAVA_LANG_LONG,
+            "valueOf",
+            "(J)Ljava/lang/Long;"
+        );
+        unsafe { (**env).CallStaticObjectMethod.unwrap()(env, class, value_of, self) }
+    }
+}
+
+impl SwigIntoJavaObject for jfloat {
+    fn into_java_object(self, env: *mut JNIEnv) -> jobject {
+        let class = swig_jni_find_class!(JAVA_LANG_FLOAT, "java/lang/Float");
+        let value_of = swig_jni_get_static_method_id!(
+            JAVA_LANG_FLOAT_VALUE_OF,
+            JAVA_LANG_FLOAT,
+            "valueOf",
+            "(F)Ljava/lang/Float;"
+        );
+        unsafe {
+            (**env).CallStaticObjectMethod.unwrap()(env, class, value_of, f64::from(self))
+        }
+    }
+}
+
+impl SwigIntoJavaObject for jdouble {
+    fn into_java_object(self, env: *mut JNIEnv) -> jobject {
+        let class = swig_jni_find_class!(JAVA_LANG_DOUBLE, "java/lang/Double");
+        let value_of = swig_jni_get_static_method_id!(
+            JAVA_LANG_DOUBLE_VALUE_OF,
+            JAVA_LANG_DOUBLE,
+            "valueOf",
+            "(D)Ljava/lang/Double;"
+        );
+        unsafe { (**env).CallStaticObjectMethod.unwrap()(env, class, value_of, self) }
+    }
+}
+
+/// Complete `CompletableFuture` with value returned by `convert`,
+/// or exceptionally if `convert` leaves pending Java exception
+#[allow(dead_code)]
+fn swig_jni_complete_future<R, F>(future: &JavaCallback, convert: F)
+where
+    R: SwigIntoJavaObject,
+    F: FnOnce(*mut JNIEnv) -> R,
+{
+    let env = future.get_jni_env();
+    let env = match env.env {
+        Some(env) => env,
+        None => {
+            log::error!("swig_jni_complete_future: can not get JNIEnv");
+            return;
+        }
+    };
+    let class = swig_jni_find_class!(
+        JAVA_UTIL_CONCURRENT_COMPLETABLE_FUTURE,
+        "java/util/concurrent/CompletableFuture"
+    );
+    let complete = swig_jni_get_method_id!(
+        JAVA_UTIL_CONCURRENT_COMPLETABLE_FUTURE_COMPLETE,
+        JAVA_UTIL_CONCURRENT_COMPLETABLE_FUTURE,
+        "complete",
+        "(Ljava/lang/Object;)Z"
+    );
+    let complete_exceptionally = swig_jni_get_method_id!(
+        JAVA_UTIL_CONCURRENT_COMPLETABLE_FUTURE_COMPLETE_EXCEPTIONALLY,
+        JAVA_UTIL_CONCURRENT_COMPLETABLE_FUTURE,
+        "completeExceptionally",
+        "(Ljava/lang/Throwable;)Z"
+    );
+    assert!(!class.is_null());
+    unsafe {
+        // thread may be attached to JVM for a long time, so free local references
+        if (**env).PushLocalFrame.unwrap()(env, 16) != 0 {
+            log::error!("swig_jni_complete_future: PushLocalFrame failed");
+            return;
+        }
+        let ret = convert(env);
+        // boxing of primitive value also may throw, for example `OutOfMemoryError`
+        let obj = if (**env).ExceptionCheck.unwrap()(env) == 0 {
+            ret.into_java_object(env)
+        } else {
+            ::std::ptr::null_mut()
+        };
+        if (**env).ExceptionCheck.unwrap()(env) != 0 {
+            let ex = (**env).ExceptionOccurred.unwrap()(env);
+            (**env).ExceptionClear.unwrap()(env);
+            (**env).CallBooleanMethod.unwrap()(env, future.this, complete_exceptionally, ex);
+        } else {
+            (**env).CallBooleanMethod.unwrap()(env, future.this, complete, obj);
+        }
+        if (**env).ExceptionCheck.unwrap()(env) != 0 {
+            log::error!("swig_jni_complete_future: exception in callback of CompletableFuture");
+            (**env).ExceptionDescribe.unwrap()(env);
+            (**env).ExceptionClear.unwrap()(env);
+        }
+        (**env).PopLocalFrame.unwrap()(env, ::std::ptr::null_mut());
+    }
+}
@@ -0,0 +1,24 @@
+
+/// Call `f`, if it panics throw `java.lang.RuntimeException` with panic message
+/// and return invalid value, JVM ignores it because of pending exception
+#[allow(dead_code)]
+fn swig_jni_catch_panic<R: JniInvalidValue, F: FnOnce() -> R>(
+    env: *mut JNIEnv,
+    with_backtrace: bool,
+    f: F,
+) -> R {
+    match swig_catch_panic(with_backtrace, f) {
+        Ok(ret) => ret,
+        Err(msg) => {
+            // exception thrown by callback may cause panic, keep it
+            if unsafe { (**env).ExceptionCheck.unwrap()(env) } == 0 {
+                let exception_class = swig_jni_find_class!(
+                    JAVA_LANG_RUNTIME_EXCEPTION,
+                    "java/lang/RuntimeException"
+                );
+                jni_throw(env, exception_class, &msg);
+            }
+            <R>::jni_invalid_value()
+        }
+    }
+}
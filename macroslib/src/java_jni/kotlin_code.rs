@@ -0,0 +1,358 @@
+//! Helpers to generate Kotlin code instead of Java.
+//! Rust side is the same, so all Kotlin declarations should have
+//! the same JVM signatures as Java variant, because of that
+//! here Java types from type maps are converted to Kotlin types.
+
+use std::borrow::Cow;
+
+use super::{
+    java_code, JavaContext, JavaForeignTypeInfo, NullAnnotation, INTERNAL_PTR_MARKER,
+    JAVA_RUST_SELF_NAME,
+};
+use crate::{
+    error::{DiagnosticError, Result, SourceIdSpan},
+    typemap::{FROM_VAR_TEMPLATE, TO_VAR_TEMPLATE},
+};
+
+/// Kotlin variant of foreign type
+pub(in crate::java_jni) struct KotlinTypeConv {
+    /// type visible to users of generated class
+    pub ty: String,
+    /// type used in `external fun` declaration
+    pub native_ty: String,
+    /// For input: statements that declare `TO_VAR_TEMPLATE` from `FROM_VAR_TEMPLATE`,
+    /// for output: expression that uses `FROM_VAR_TEMPLATE`,
+    /// empty if conversion not required
+    pub conv: String,
+}
+
+pub(in crate::java_jni) fn kotlin_ident(name: &str) -> Cow<'_, str> {
+    match name {
+        "as" | "break" | "class" | "continue" | "do" | "else" | "false" | "for" | "fun" | "if"
+        | "in" | "interface" | "is" | "null" | "object" | "package" | "return" | "super"
+        | "this" | "throw" | "true" | "try" | "typealias" | "typeof" | "val" | "var" | "when"
+        | "while" => format!("`{}`", name).into(),
+        _ => name.into(),
+    }
+}
+
+/// Remove unique prefixes like `/*opt*/` and null annotations from Java type name,
+/// returns type name and is it nullable
+fn split_java_type(java_name: &str) -> (&str, bool) {
+    let mut name = java_name.trim();
+    let mut nullable = false;
+    loop {
+        if name.starts_with("/*") {
+            if let Some(end) = name.find("*/") {
+                nullable |= name[2..end].starts_with("opt");
+                name = name[end + 2..].trim_start();
+                continue;
+            }
+        }
+        if let Some(rest) = name.strip_prefix("@Nullable") {
+            nullable = true;
+            name = rest.trim_start();
+        } else if let Some(rest) = name.strip_prefix("@NonNull") {
+            name = rest.trim_start();
+        } else {
+            break;
+        }
+    }
+    (name.trim(), nullable)
+}
+
+/// Kotlin type with the same JVM signature as Java type
+pub(in crate::java_jni) fn jvm_type(java_name: &str) -> String {
+    let (name, mut nullable) = split_java_type(java_name);
+    let mut ty = if let Some(elem) = name.strip_suffix("[]") {
+        match elem.trim() {
+            "boolean" => "BooleanArray".to_string(),
+            "byte" => "ByteArray".to_string(),
+            "short" => "ShortArray".to_string(),
+            "int" => "IntArray".to_string(),
+            "long" => "LongArray".to_string(),
+            "float" => "FloatArray".to_string(),
+            "double" => "DoubleArray".to_string(),
+            elem => format!("Array<{}>", jvm_type(elem)),
+        }
+    } else {
+        let (base, boxed) = match name {
+            "void" => ("Unit", false),
+            "boolean" => ("Boolean", false),
+            "byte" => ("Byte", false),
+            "short" => ("Short", false),
+            "int" => ("Int", false),
+            "long" => ("Long", false),
+            "float" => ("Float", false),
+            "double" => ("Double", false),
+            "String" | "java.lang.String" => ("String", false),
+            "Object" | "java.lang.Object" => ("Any", true),
+            "Boolean" | "java.lang.Boolean" => ("Boolean", true),
+            "Byte" | "java.lang.Byte" => ("Byte", true),
+            "Short" | "java.lang.Short" => ("Short", true),
+            "Integer" | "java.lang.Integer" => ("Int", true),
+            "Long" | "java.lang.Long" => ("Long", true),
+            "Float" | "java.lang.Float" => ("Float", true),
+            "Double" | "java.lang.Double" => ("Double", true),
+            _ => ("", false),
+        };
+        nullable |= boxed;
+        if !base.is_empty() {
+            base.to_string()
+        } else if let (Some(start), true) = (name.find('<'), name.ends_with('>')) {
+            format!(
+                "{}<{}>",
+                &name[..start],
+                jvm_type(&name[start + 1..name.len() - 1])
+            )
+        } else {
+            name.to_string()
+        }
+    };
+    if nullable {
+        ty.push('?');
+    }
+    ty
+}
+
+/// The same as `jvm_type`, but also takes into account `Option` in Rust type
+pub(in crate::java_jni) fn jvm_type_of(ti: &JavaForeignTypeInfo) -> String {
+    let mut ty = jvm_type(&ti.base.name);
+    // `Option` is mapped to `Optional*` in this case, and it is not null
+    let optional = split_java_type(&ti.base.name)
+        .0
+        .rsplit('.')
+        .next()
+        .map(|x| x.starts_with("Optional"))
+        .unwrap_or(false);
+    if is_nullable(ti)
+        && !optional
+        && !java_code::is_primitive_type(&ti.base.name)
+        && !ty.ends_with('?')
+    {
+        ty.push('?');
+    }
+    ty
+}
+
+fn is_nullable(ti: &JavaForeignTypeInfo) -> bool {
+    matches!(ti.annotation, Some(NullAnnotation::Nullable))
+}
+
+/// Conversion of input argument, Kotlin analog of `JavaConverter`
+pub(in crate::java_jni) fn input_conv(
+    arg: &JavaForeignTypeInfo,
+    span: SourceIdSpan,
+) -> Result<KotlinTypeConv> {
+    let java_conv = match arg
+        .java_converter
+        .as_ref()
+        .filter(|x| !x.converter.trim().is_empty())
+    {
+        Some(x) => x,
+        None => {
+            let ty = jvm_type_of(arg);
+            return Ok(KotlinTypeConv {
+                native_ty: ty.clone(),
+                ty,
+                conv: String::new(),
+            });
+        }
+    };
+    let (name, nullable) = split_java_type(&arg.base.name);
+    let nullable = nullable || is_nullable(arg);
+    // Java code for input by value passes ownership to Rust side
+    let take_ownership = java_conv.converter.contains(&format!(
+        "{}.{} = 0;",
+        FROM_VAR_TEMPLATE, JAVA_RUST_SELF_NAME
+    ));
+    let native_ty = jvm_type(&java_conv.java_transition_type);
+    let ty = if nullable {
+        format!("{}?", name)
+    } else {
+        name.to_string()
+    };
+    let conv = match (native_ty.as_str(), nullable) {
+        ("Long", false) => {
+            let mut conv = format!(
+                "        val {to} = {from}.{self_name}\n",
+                to = TO_VAR_TEMPLATE,
+                from = FROM_VAR_TEMPLATE,
+                self_name = JAVA_RUST_SELF_NAME,
+            );
+            if take_ownership {
+                conv.push_str(&format!(
+                    "        {from}.{self_name} = 0\n",
+                    from = FROM_VAR_TEMPLATE,
+                    self_name = JAVA_RUST_SELF_NAME,
+                ));
+            }
+            conv
+        }
+        ("Long", true) => {
+            let mut conv = format!(
+                "        val {to} = {from}?.{self_name} ?: 0L\n",
+                to = TO_VAR_TEMPLATE,
+                from = FROM_VAR_TEMPLATE,
+                self_name = JAVA_RUST_SELF_NAME,
+            );
+            if take_ownership {
+                conv.push_str(&format!(
+                    "        {from}?.{self_name} = 0\n",
+                    from = FROM_VAR_TEMPLATE,
+                    self_name = JAVA_RUST_SELF_NAME,
+                ));
+            }
+            conv
+        }
+        ("Int", false) => format!(
+            "        val {to} = {from}.value\n",
+            to = TO_VAR_TEMPLATE,
+            from = FROM_VAR_TEMPLATE,
+        ),
+        ("Int", true) => format!(
+            "        val {to} = {from}?.value ?: -1\n",
+            to = TO_VAR_TEMPLATE,
+            from = FROM_VAR_TEMPLATE,
+        ),
+        _ => return Err(unsupported_conv(arg, span)),
+    };
+    Ok(KotlinTypeConv {
+        ty,
+        native_ty,
+        conv,
+    })
+}
+
+/// Conversion of returned value, Kotlin analog of `JavaConverter`
+pub(in crate::java_jni) fn output_conv(
+    ctx: &JavaContext,
+    output: &JavaForeignTypeInfo,
+    span: SourceIdSpan,
+) -> Result<KotlinTypeConv> {
+    let (name, _) = split_java_type(&output.base.name);
+    let optional_prefix = format!("{}.Optional", ctx.cfg.optional_package);
+    let java_conv = match output
+        .java_converter
+        .as_ref()
+        .filter(|x| !x.converter.trim().is_empty())
+    {
+        Some(x) => x,
+        None => {
+            let native_ty = jvm_type(&output.base.name);
+            let opt_value = match name.strip_prefix(optional_prefix.as_str()) {
+                Some("Int") => Some(("Int?", "asInt")),
+                Some("Long") => Some(("Long?", "asLong")),
+                Some("Double") => Some(("Double?", "asDouble")),
+                _ => None,
+            };
+            return Ok(match opt_value {
+                Some((ty, getter)) => KotlinTypeConv {
+                    ty: ty.into(),
+                    native_ty,
+                    conv: format!(
+                        "if ({var}.isPresent) {var}.{getter} else null",
+                        var = FROM_VAR_TEMPLATE,
+                        getter = getter
+                    ),
+                },
+                None => KotlinTypeConv {
+                    ty: jvm_type_of(output),
+                    native_ty,
+                    conv: String::new(),
+                },
+            });
+        }
+    };
+    let mut native_ty = jvm_type(&java_conv.java_transition_type);
+    let (ty, conv) = match name
+        .strip_prefix(optional_prefix.as_str())
+        .and_then(|x| x.strip_prefix('<'))
+        .and_then(|x| x.strip_suffix('>'))
+    {
+        Some(inner) => {
+            let inner = split_java_type(inner).0;
+            let conv = match native_ty.as_str() {
+                "Long" => format!(
+                    "if ({var} != 0L) {class_name}({marker}.RAW_PTR, {var}) else null",
+                    var = FROM_VAR_TEMPLATE,
+                    class_name = inner,
+                    marker = INTERNAL_PTR_MARKER,
+                ),
+                "Int" => format!(
+                    "if ({var} != -1) {enum_name}.fromInt({var}) else null",
+                    var = FROM_VAR_TEMPLATE,
+                    enum_name = inner,
+                ),
+                // nullable value of the same type, like `String` or enum with data
+                ty if ty.trim_end_matches('?') == jvm_type(inner) => {
+                    native_ty = format!("{}?", jvm_type(inner));
+                    String::new()
+                }
+                _ => return Err(unsupported_conv(output, span)),
+            };
+            (format!("{}?", jvm_type(inner)), conv)
+        }
+        None => {
+            let conv = match (native_ty.as_str(), name) {
+                ("Long", "java.util.Date") => format!("java.util.Date({})", FROM_VAR_TEMPLATE),
+                ("Long", _) => format!(
+                    "{class_name}({marker}.RAW_PTR, {var})",
+                    var = FROM_VAR_TEMPLATE,
+                    class_name = name,
+                    marker = INTERNAL_PTR_MARKER,
+                ),
+                ("Int", _) => format!(
+                    "{enum_name}.fromInt({var})",
+                    var = FROM_VAR_TEMPLATE,
+                    enum_name = name,
+                ),
+                _ => return Err(unsupported_conv(output, span)),
+            };
+            (name.to_string(), conv)
+        }
+    };
+    Ok(KotlinTypeConv {
+        ty,
+        native_ty,
+        conv,
+    })
+}
+
+fn unsupported_conv(ti: &JavaForeignTypeInfo, span: SourceIdSpan) -> DiagnosticError {
+    let java_code = ti
+        .java_converter
+        .as_ref()
+        .map(|x| x.converter.as_str())
+        .unwrap_or("");
+    DiagnosticError::new2(
+        span,
+        format!(
+            "Kotlin: conversion of Java type '{}' is not supported, Java code:\n```{}\n```",
+            java_code::filter_null_annotation(&ti.base.name).trim(),
+            java_code
+        ),
+    )
+}
+
+/// Kotlin has no checked exceptions, but `@Throws` is useful for Java callers
+pub(in crate::java_jni) fn throws_annotation(may_return_error: bool, indent: &str) -> String {
+    if may_return_error {
+        format!("{}@Throws(Exception::class)\n", indent)
+    } else {
+        String::new()
+    }
+}
+
+pub(in crate::java_jni) fn indent_doc_comments(doc_comments: &str, indent: &str) -> String {
+    if doc_comments.is_empty() {
+        return String::new();
+    }
+    let mut ret = String::with_capacity(doc_comments.len() + 32);
+    for line in doc_comments.lines() {
+        ret.push_str(indent);
+        ret.push_str(line);
+        ret.push('\n');
+    }
+    ret
+}
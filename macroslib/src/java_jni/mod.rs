@@ -1,8 +1,10 @@
+mod async_fn;
 mod fclass;
 mod fenum;
 mod find_cache;
 mod finterface;
 mod java_code;
+mod kotlin_code;
 mod map_class_self_type;
 mod map_type;
 mod rust_code;
@@ -32,7 +34,7 @@ use crate::{
         ForeignTypeInfo, TypeMapConvRuleInfo,
     },
     types::{ForeignClassInfo, ForeignMethod, ItemToExpand, MethodVariant},
-    JavaConfig, JavaReachabilityFence, LanguageGenerator, SourceCode, TypeMap,
+    JavaConfig, JavaOutputLanguage, JavaReachabilityFence, LanguageGenerator, SourceCode, TypeMap,
     SMART_PTR_COPY_TRAIT, WRITE_TO_MEM_FAILED_MSG,
 };
 use map_class_self_type::register_typemap_for_self_type;
@@ -116,6 +118,13 @@ impl ForeignMethodSignature for JniForeignMethodSignature {
 }
 
 impl JavaConfig {
+    fn foreign_file_ext(&self) -> &'static str {
+        match self.output_language {
+            JavaOutputLanguage::Java => "java",
+            JavaOutputLanguage::Kotlin => "kt",
+        }
+    }
+
     fn register_class(&self, ctx: &mut JavaContext, class: &ForeignClassInfo) -> Result<()> {
         class
             .validate_class()
@@ -198,6 +207,9 @@ impl LanguageGenerator for JavaConfig {
             method_ext_handlers: ext_handlers.method_ext_handlers,
         };
         init(&mut ctx, code)?;
+        if ItemToExpand::has_async_methods(&items) {
+            async_fn::generate_runtime(&mut ctx)?;
+        }
         for item in &items {
             if let ItemToExpand::Class(ref fclass) = item {
                 self.register_class(&mut ctx, fclass)?;
@@ -218,9 +230,10 @@ impl LanguageGenerator for JavaConfig {
         }
 
         if remove_not_generated_files {
+            let foreign_ext = self.foreign_file_ext();
             remove_files_if(&self.output_dir, |path| {
                 if let Some(ext) = path.extension() {
-                    if ext == "java" && !generated_foreign_files.contains(path) {
+                    if ext == foreign_ext && !generated_foreign_files.contains(path) {
                         return true;
                     }
                 }
@@ -255,7 +268,9 @@ fn method_name(method: &ForeignMethod, f_method: &JniForeignMethodSignature) ->
         .map(|x| !x.converter.is_empty())
         .unwrap_or(false);
     match method.variant {
-        MethodVariant::StaticMethod if !need_conv => method.short_name().as_str().to_string(),
+        MethodVariant::StaticMethod if !need_conv && !method.is_async => {
+            method.short_name().as_str().to_string()
+        }
         MethodVariant::Method(_) | MethodVariant::StaticMethod => {
             format!("do_{}", method.short_name())
         }
@@ -355,23 +370,37 @@ fn init(ctx: &mut JavaContext, _code: &[SourceCode]) -> Result<()> {
     for rule in not_merged_data {
         merge_rule(ctx, rule)?;
     }
+    let foreign_ext = ctx.cfg.foreign_file_ext();
     let src_path = ctx
         .cfg
         .output_dir
-        .join(&format!("{}.java", INTERNAL_PTR_MARKER));
+        .join(format!("{}.{}", INTERNAL_PTR_MARKER, foreign_ext));
     let mut src_file = FileWriteCache::new(&src_path, ctx.generated_foreign_files);
-    writeln!(
-        src_file,
-        r#"
+    match ctx.cfg.output_language {
+        JavaOutputLanguage::Java => writeln!(
+            src_file,
+            r#"
 // Automatically generated by flapigen
 package {package};
 
 /*package*/ enum {enum_name} {{
     RAW_PTR;
 }}"#,
-        package = ctx.cfg.package_name,
-        enum_name = INTERNAL_PTR_MARKER,
-    )
+            package = ctx.cfg.package_name,
+            enum_name = INTERNAL_PTR_MARKER,
+        ),
+        JavaOutputLanguage::Kotlin => writeln!(
+            src_file,
+            r#"// Automatically generated by flapigen
+package {package}
+
+internal enum class {enum_name} {{
+    RAW_PTR
+}}"#,
+            package = ctx.cfg.package_name,
+            enum_name = INTERNAL_PTR_MARKER,
+        ),
+    }
     .expect(WRITE_TO_MEM_FAILED_MSG);
     src_file.update_file_if_necessary().map_err(|err| {
         DiagnosticError::new2(
@@ -385,19 +414,30 @@ package {package};
             let src_path = ctx
                 .cfg
                 .output_dir
-                .join(&format!("{}.java", REACHABILITY_FENCE_CLASS));
+                .join(format!("{}.{}", REACHABILITY_FENCE_CLASS, foreign_ext));
             let mut src_file = FileWriteCache::new(&src_path, ctx.generated_foreign_files);
-            write!(
-                src_file,
-                r#"
+            match ctx.cfg.output_language {
+                JavaOutputLanguage::Java => write!(
+                    src_file,
+                    r#"
 // Automatically generated by flapigen
 package {package};
 
 /*package*/ final class {class_name} {{
     private {class_name}() {{}}"#,
-                package = ctx.cfg.package_name,
-                class_name = REACHABILITY_FENCE_CLASS,
-            )
+                    package = ctx.cfg.package_name,
+                    class_name = REACHABILITY_FENCE_CLASS,
+                ),
+                JavaOutputLanguage::Kotlin => write!(
+                    src_file,
+                    r#"// Automatically generated by flapigen
+package {package}
+
+internal object {class_name} {{"#,
+                    package = ctx.cfg.package_name,
+                    class_name = REACHABILITY_FENCE_CLASS,
+                ),
+            }
             .expect(WRITE_TO_MEM_FAILED_MSG);
 
             let mut f_method = JniForeignMethodSignature {
@@ -416,16 +456,32 @@ package {package};
 
             for i in 1..=max_args {
                 let java_method_name = format!("reachabilityFence{}", i);
-                write!(
-                    src_file,
-                    "\n    /*package*/ static native void {}(Object ref1",
-                    java_method_name
-                )
-                .expect(WRITE_TO_MEM_FAILED_MSG);
-                for j in 2..=i {
-                    write!(src_file, ", Object ref{}", j).expect(WRITE_TO_MEM_FAILED_MSG);
+                match ctx.cfg.output_language {
+                    JavaOutputLanguage::Java => {
+                        write!(
+                            src_file,
+                            "\n    /*package*/ static native void {}(Object ref1",
+                            java_method_name
+                        )
+                        .expect(WRITE_TO_MEM_FAILED_MSG);
+                        for j in 2..=i {
+                            write!(src_file, ", Object ref{}", j).expect(WRITE_TO_MEM_FAILED_MSG);
+                        }
+                        src_file.write_all(b");").expect(WRITE_TO_MEM_FAILED_MSG);
+                    }
+                    JavaOutputLanguage::Kotlin => {
+                        write!(
+                            src_file,
+                            "\n    @JvmStatic\n    external fun {}(ref1: Any?",
+                            java_method_name
+                        )
+                        .expect(WRITE_TO_MEM_FAILED_MSG);
+                        for j in 2..=i {
+                            write!(src_file, ", ref{}: Any?", j).expect(WRITE_TO_MEM_FAILED_MSG);
+                        }
+                        src_file.write_all(b")").expect(WRITE_TO_MEM_FAILED_MSG);
+                    }
                 }
-                src_file.write_all(b");").expect(WRITE_TO_MEM_FAILED_MSG);
 
                 f_method.input.push(JavaForeignTypeInfo {
                     base: ForeignTypeInfo {
@@ -454,7 +510,11 @@ package {package};
                     }
                 });
             }
-            src_file.write_all(b"}\n").expect(WRITE_TO_MEM_FAILED_MSG);
+            match ctx.cfg.output_language {
+                JavaOutputLanguage::Java => src_file.write_all(b"}\n"),
+                JavaOutputLanguage::Kotlin => src_file.write_all(b"\n}\n"),
+            }
+            .expect(WRITE_TO_MEM_FAILED_MSG);
 
             src_file.update_file_if_necessary().map_err(|err| {
                 DiagnosticError::new2(
@@ -45,7 +45,10 @@ pub(in crate::java_jni) fn predefined_java_type_to_jni_sig() -> FxHashMap<SmolSt
     m
 }
 
-fn java_type_to_jni_signature<'a>(ctx: &'a JavaContext, java_type: &str) -> Option<&'a str> {
+pub(in crate::java_jni) fn java_type_to_jni_signature<'a>(
+    ctx: &'a JavaContext,
+    java_type: &str,
+) -> Option<&'a str> {
     if java_type.contains("@NonNull") || java_type.contains("@Nullable") {
         let java_type = filter_null_annotation(java_type);
         ctx.java_type_to_jni_sig_map
@@ -58,6 +61,19 @@ fn java_type_to_jni_signature<'a>(ctx: &'a JavaContext, java_type: &str) -> Opti
     }
 }
 
+/// Mangle part of JNI function name
+pub(in crate::java_jni) fn escape_underscore(input: &str, output: &mut String) {
+    for c in input.chars() {
+        match c {
+            '.' => output.push('_'),
+            '[' => output.push_str("_3"),
+            '_' => output.push_str("_1"),
+            ';' => output.push_str("_2"),
+            _ => output.push(c),
+        }
+    }
+}
+
 pub(in crate::java_jni) fn generate_jni_func_name(
     ctx: &JavaContext,
     class_name: &str,
@@ -69,17 +85,6 @@ pub(in crate::java_jni) fn generate_jni_func_name(
 ) -> Result<String> {
     let mut output = String::new();
     output.push_str("Java_");
-    fn escape_underscore(input: &str, output: &mut String) {
-        for c in input.chars() {
-            match c {
-                '.' => output.push('_'),
-                '[' => output.push_str("_3"),
-                '_' => output.push_str("_1"),
-                ';' => output.push_str("_2"),
-                _ => output.push(c),
-            }
-        }
-    }
     escape_underscore(&ctx.cfg.package_name, &mut output);
     output.push_str("_");
     escape_underscore(class_name, &mut output);
@@ -136,14 +141,14 @@ pub(in crate::java_jni) fn jni_method_signature(
         ret.push_str(&sig);
     }
     ret.push(')');
-    let sig =
-        java_type_to_jni_signature(ctx, method.output.base.name.as_str()).unwrap_or_else(|| {
-            panic!(
-                "Unknown type `{}`, can not generate JNI signature",
-                method.output.base.name
-            )
-        });
-    ret.push_str(sig);
+    let java_type = filter_null_annotation(method.output.base.name.as_str());
+    let sig = java_type_to_jni_signature(ctx, java_type.trim()).unwrap_or_else(|| {
+        panic!(
+            "Unknown type `{}`, can not generate JNI signature",
+            method.output.base.name
+        )
+    });
+    ret.push_str(&sig.replace('.', "/"));
     ret
 }
 
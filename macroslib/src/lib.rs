@@ -30,18 +30,29 @@ macro_rules! parse_type_spanned_checked {
     }}
 }
 
+pub mod backend;
+mod closure;
 mod code_parse;
 mod cpp;
+mod csharp;
+mod dart;
 mod error;
 mod extension;
 pub mod file_cache;
+mod go;
+mod iterator;
 mod java_jni;
+mod lua;
 mod namegen;
+mod node;
 mod python;
+mod ruby;
 mod source_registry;
 mod str_replace;
+mod swift;
 mod typemap;
 mod types;
+mod wasm;
 
 use std::{
     env, io,
@@ -101,6 +112,17 @@ pub enum LanguageConfig {
     JavaConfig(JavaConfig),
     CppConfig(CppConfig),
     PythonConfig(PythonConfig),
+    CSharpConfig(CSharpConfig),
+    SwiftConfig(SwiftConfig),
+    GoConfig(GoConfig),
+    NodeConfig(NodeConfig),
+    LuaConfig(LuaConfig),
+    RubyConfig(RubyConfig),
+    DartConfig(DartConfig),
+    CConfig(CConfig),
+    WasmConfig(WasmConfig),
+    /// Backend implemented outside of `flapigen`, see `backend` module
+    Custom(Box<dyn backend::LanguageBackend>),
 }
 
 /// Configuration for Java binding generation
@@ -111,6 +133,9 @@ pub struct JavaConfig {
     null_annotation_package: Option<String>,
     optional_package: String,
     reachability_fence: JavaReachabilityFence,
+    output_language: JavaOutputLanguage,
+    catch_panics: Option<CatchPanics>,
+    async_executor: Option<String>,
 }
 
 impl JavaConfig {
@@ -125,6 +150,9 @@ impl JavaConfig {
             null_annotation_package: None,
             optional_package: "java.util".to_string(),
             reachability_fence: JavaReachabilityFence::GenerateFence(8),
+            output_language: JavaOutputLanguage::Java,
+            catch_panics: None,
+            async_executor: None,
         }
     }
     /// Use @NonNull for types where appropriate
@@ -172,6 +200,55 @@ impl JavaConfig {
         self.reachability_fence = reachability_fence;
         self
     }
+    /// Choose language of generated sources, by default `JavaOutputLanguage::Java`.
+    /// The Rust side is the same for both variants.
+    pub fn use_output_language(mut self, output_language: JavaOutputLanguage) -> JavaConfig {
+        self.output_language = output_language;
+        self
+    }
+    /// Catch panics in generated JNI functions and throw
+    /// `java.lang.RuntimeException` with panic message instead,
+    /// by default panic unwinding into JVM is undefined behaviour
+    pub fn catch_panics(mut self, catch_panics: CatchPanics) -> JavaConfig {
+        self.catch_panics = Some(catch_panics);
+        self
+    }
+    /// Path to function that runs futures of `async fn` methods,
+    /// see `book/src/async.md` for its signature.
+    /// By default every future is polled in its own thread
+    pub fn async_executor(mut self, async_executor: String) -> JavaConfig {
+        self.async_executor = Some(async_executor);
+        self
+    }
+}
+
+/// What to keep from Rust panic, that is caught at FFI boundary,
+/// in error passed to foreign language
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CatchPanics {
+    /// Only panic message
+    Message,
+    /// Panic message and backtrace of panic, to capture backtrace
+    /// generated code installs panic hook on first call,
+    /// previous panic hook is still called
+    MessageAndBacktrace,
+}
+
+impl CatchPanics {
+    fn with_backtrace(self) -> bool {
+        self == CatchPanics::MessageAndBacktrace
+    }
+}
+
+/// Language of sources generated by `JavaConfig`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JavaOutputLanguage {
+    /// `.java` files
+    Java,
+    /// `.kt` files: classes implement `AutoCloseable`, `Option` becomes
+    /// nullable type, `foreign_enum!` becomes `enum class` (or `sealed class`
+    /// if items have data), static methods are placed into `companion object`
+    Kotlin,
 }
 
 /// What reachability fence to use
@@ -198,6 +275,10 @@ pub struct CppConfig {
     /// Create separate *_impl.hpp files with methods implementations.
     /// Can be necessary for the project with circular dependencies between classes.
     separate_impl_headers: bool,
+    /// Write only C headers, without C++ wrappers, see `CConfig`
+    c_headers_only: bool,
+    catch_panics: Option<CatchPanics>,
+    async_executor: Option<String>,
 }
 
 /// To which `C++` type map `std::option::Option`
@@ -218,7 +299,7 @@ impl From<CppOptional> for &'static str {
     }
 }
 
-/// To which `C++` type map `std::result::Result`
+/// To which `C++` type map `std::result::Result` and `foreign_enum!` with data
 #[derive(Clone, Copy, EnumIter)]
 pub enum CppVariant {
     /// `std::variant` from C++17 standard
@@ -267,6 +348,9 @@ impl CppConfig {
             cpp_variant: CppVariant::Std17,
             cpp_str_view: CppStrView::Std17,
             separate_impl_headers: false,
+            c_headers_only: false,
+            catch_panics: None,
+            async_executor: None,
         }
     }
     pub fn cpp_optional(self, cpp_optional: CppOptional) -> CppConfig {
@@ -305,18 +389,348 @@ impl CppConfig {
             ..self
         }
     }
+    /// Catch panics in generated C functions, then function returns zero-initialized value,
+    /// and C++ wrapper throws `std::runtime_error` with panic message,
+    /// see `rust_panic.h` for C API to get the message
+    pub fn catch_panics(self, catch_panics: CatchPanics) -> CppConfig {
+        CppConfig {
+            catch_panics: Some(catch_panics),
+            ..self
+        }
+    }
+    /// Path to function that runs futures of `async fn` methods,
+    /// see `book/src/async.md` for its signature.
+    /// By default every future is polled in its own thread
+    pub fn async_executor(self, async_executor: String) -> CppConfig {
+        CppConfig {
+            async_executor: Some(async_executor),
+            ..self
+        }
+    }
+    pub(crate) fn c_headers_only(self) -> CppConfig {
+        CppConfig {
+            c_headers_only: true,
+            ..self
+        }
+    }
 }
 
-/// Configuration for Java binding generation
+/// Configuration for pure C API generation, only self-contained C headers
+/// are generated, without C++ wrappers and C++ helper headers,
+/// so the result can be used from C89/C99 code
+pub struct CConfig {
+    output_dir: PathBuf,
+    library_name: String,
+    catch_panics: Option<CatchPanics>,
+    async_executor: Option<String>,
+}
+
+impl CConfig {
+    /// Create `CConfig`
+    /// # Arguments
+    /// * `output_dir` - directory where place generated C headers
+    /// * `library_name` - name of header `<library_name>.h`
+    ///   that includes all other generated headers
+    pub fn new(output_dir: PathBuf, library_name: String) -> CConfig {
+        CConfig {
+            output_dir,
+            library_name,
+            catch_panics: None,
+            async_executor: None,
+        }
+    }
+    /// Catch panics in generated C functions, then function returns zero-initialized value,
+    /// and `flapigen_last_panic_message` from `rust_panic.h` returns panic message
+    pub fn catch_panics(self, catch_panics: CatchPanics) -> CConfig {
+        CConfig {
+            catch_panics: Some(catch_panics),
+            ..self
+        }
+    }
+    /// Path to function that runs futures of `async fn` methods,
+    /// see `book/src/async.md` for its signature.
+    /// By default every future is polled in its own thread
+    pub fn async_executor(self, async_executor: String) -> CConfig {
+        CConfig {
+            async_executor: Some(async_executor),
+            ..self
+        }
+    }
+}
+
+/// Configuration for Python binding generation
 pub struct PythonConfig {
     module_name: String,
+    python_binding: PythonBinding,
+    stubs_output_dir: Option<PathBuf>,
+    shims_output_dir: Option<PathBuf>,
+    catch_panics: Option<CatchPanics>,
+    async_executor: Option<String>,
+    return_numeric_buffers: bool,
+}
+
+/// Which Rust crate generated Python bindings use
+/// to talk to the Python interpreter
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PythonBinding {
+    /// [rust-cpython](https://github.com/dgrunwald/rust-cpython)
+    RustCPython,
+    /// [PyO3](https://github.com/PyO3/pyo3), version 0.23 or newer
+    PyO3,
 }
 
 impl PythonConfig {
     /// Create `PythonConfig`
     /// # Arguments
+    /// * `module_name` - name of the generated Python extension module
     pub fn new(module_name: String) -> PythonConfig {
-        PythonConfig { module_name }
+        PythonConfig {
+            module_name,
+            python_binding: PythonBinding::RustCPython,
+            stubs_output_dir: None,
+            shims_output_dir: None,
+            catch_panics: None,
+            async_executor: None,
+            return_numeric_buffers: false,
+        }
+    }
+    /// Generate code for the given Python binding crate,
+    /// by default `PythonBinding::RustCPython` is used
+    pub fn python_binding(self, python_binding: PythonBinding) -> PythonConfig {
+        PythonConfig {
+            python_binding,
+            ..self
+        }
+    }
+    /// Write `<module_name>.pyi` type stubs for the generated module
+    /// into `stubs_output_dir`, by default stubs are not generated
+    pub fn stubs_output_dir(self, stubs_output_dir: PathBuf) -> PythonConfig {
+        PythonConfig {
+            stubs_output_dir: Some(stubs_output_dir),
+            ..self
+        }
+    }
+    /// Write `<class_name in snake_case>.py` shim for every class and enum
+    /// into `shims_output_dir`, for example `my_class.py` for `MyClass`.
+    /// Shim imports generated type from the extension module, and the code added
+    /// by callbacks registered with `Generator::register_class_attribute_callback`
+    /// and friends goes into it. By default shims are not written,
+    /// but callbacks are still invoked
+    pub fn shims_output_dir(self, shims_output_dir: PathBuf) -> PythonConfig {
+        PythonConfig {
+            shims_output_dir: Some(shims_output_dir),
+            ..self
+        }
+    }
+    /// Turn panics in generated methods into `RuntimeError` with panic message.
+    /// Both binding crates already catch panics, but PyO3 raises `PanicException`,
+    /// that is not subclass of `Exception`, and rust-cpython loses message
+    pub fn catch_panics(self, catch_panics: CatchPanics) -> PythonConfig {
+        PythonConfig {
+            catch_panics: Some(catch_panics),
+            ..self
+        }
+    }
+    /// Path to function that runs futures of `async fn` methods,
+    /// see `book/src/async.md` for its signature.
+    /// By default every future is polled in its own thread
+    pub fn async_executor(self, async_executor: String) -> PythonConfig {
+        PythonConfig {
+            async_executor: Some(async_executor),
+            ..self
+        }
+    }
+    /// Return numeric slices and vectors, like `&[f64]` or `Vec<i32>`,
+    /// as read-only `memoryview` instead of `list`, so elements are not converted
+    /// one by one. By default `list` is returned
+    pub fn return_numeric_buffers(self, return_numeric_buffers: bool) -> PythonConfig {
+        PythonConfig {
+            return_numeric_buffers,
+            ..self
+        }
+    }
+}
+
+/// Configuration for C# binding generation, generated code
+/// calls Rust through P/Invoke
+pub struct CSharpConfig {
+    output_dir: PathBuf,
+    namespace_name: String,
+    native_lib_name: String,
+}
+
+impl CSharpConfig {
+    /// Create `CSharpConfig`
+    /// # Arguments
+    /// * `output_dir` - directory where place generated C# files
+    /// * `namespace_name` - namespace name for generated C# classes
+    /// * `native_lib_name` - name of Rust library (cdylib) for `DllImport`,
+    ///   for example "mylib" for libmylib.so/mylib.dll
+    pub fn new(
+        output_dir: PathBuf,
+        namespace_name: String,
+        native_lib_name: String,
+    ) -> CSharpConfig {
+        CSharpConfig {
+            output_dir,
+            namespace_name,
+            native_lib_name,
+        }
+    }
+}
+
+/// Configuration for Swift binding generation, Swift code
+/// wraps C API generated by C++ backend
+pub struct SwiftConfig {
+    output_dir: PathBuf,
+    c_module_name: String,
+}
+
+impl SwiftConfig {
+    /// Create `SwiftConfig`
+    /// # Arguments
+    /// * `output_dir` - directory where place generated Swift files,
+    ///   C headers and module map are placed into `output_dir/c_module_name`
+    /// * `c_module_name` - name of Clang module with C API,
+    ///   it is imported by generated Swift code
+    pub fn new(output_dir: PathBuf, c_module_name: String) -> SwiftConfig {
+        SwiftConfig {
+            output_dir,
+            c_module_name,
+        }
+    }
+}
+
+/// Configuration for Go binding generation, Go code
+/// uses C API generated by C++ backend via cgo
+pub struct GoConfig {
+    output_dir: PathBuf,
+    package_name: String,
+    native_lib_name: String,
+}
+
+impl GoConfig {
+    /// Create `GoConfig`
+    /// # Arguments
+    /// * `output_dir` - directory of Go package, generated Go files
+    ///   and C headers are placed there
+    /// * `package_name` - name of Go package
+    /// * `native_lib_name` - name of Rust library for `#cgo LDFLAGS`,
+    ///   for example "mylib" for libmylib.so/libmylib.a
+    pub fn new(output_dir: PathBuf, package_name: String, native_lib_name: String) -> GoConfig {
+        GoConfig {
+            output_dir,
+            package_name,
+            native_lib_name,
+        }
+    }
+}
+
+/// Configuration for Dart binding generation, Dart code
+/// uses C API generated by C++ backend via `dart:ffi`
+pub struct DartConfig {
+    output_dir: PathBuf,
+    library_name: String,
+    native_lib_name: String,
+}
+
+impl DartConfig {
+    /// Create `DartConfig`
+    /// # Arguments
+    /// * `output_dir` - directory where place generated Dart library
+    ///   `<library_name>.dart` with its parts and C headers
+    /// * `library_name` - name of main file of Dart library
+    /// * `native_lib_name` - name of Rust library to load,
+    ///   for example "mylib" for libmylib.so/libmylib.dylib/mylib.dll
+    pub fn new(output_dir: PathBuf, library_name: String, native_lib_name: String) -> DartConfig {
+        DartConfig {
+            output_dir,
+            library_name,
+            native_lib_name,
+        }
+    }
+}
+
+/// Configuration for Node.js binding generation, generated Rust code
+/// uses [N-API](https://nodejs.org/api/n-api.html) via `napi-sys` crate,
+/// so result should be built as `cdylib` and loaded as Node.js addon
+pub struct NodeConfig {
+    output_dir: PathBuf,
+    module_name: String,
+}
+
+impl NodeConfig {
+    /// Create `NodeConfig`
+    /// # Arguments
+    /// * `output_dir` - directory where place `<module_name>.d.ts`
+    ///   with TypeScript declarations of the addon
+    /// * `module_name` - name of the addon module
+    pub fn new(output_dir: PathBuf, module_name: String) -> NodeConfig {
+        NodeConfig {
+            output_dir,
+            module_name,
+        }
+    }
+}
+
+/// Configuration for Lua 5.4 binding generation, generated Rust code
+/// uses Lua C API directly, so Lua should be linked into result,
+/// and `luaopen_<module_name>` is called by `require`
+pub struct LuaConfig {
+    output_dir: PathBuf,
+    module_name: String,
+}
+
+impl LuaConfig {
+    /// Create `LuaConfig`
+    /// # Arguments
+    /// * `output_dir` - directory where place `<module_name>.d.lua`
+    ///   with annotations for Lua language server
+    /// * `module_name` - name of module for `require`
+    pub fn new(output_dir: PathBuf, module_name: String) -> LuaConfig {
+        LuaConfig {
+            output_dir,
+            module_name,
+        }
+    }
+}
+
+/// Configuration for Ruby binding generation, generated Rust code
+/// is C extension, that uses Ruby C API via `rb-sys` crate,
+/// and `Init_<extension_name>` is called by `require`
+pub struct RubyConfig {
+    output_dir: PathBuf,
+    extension_name: String,
+    module_name: String,
+}
+
+impl RubyConfig {
+    /// Create `RubyConfig`
+    /// # Arguments
+    /// * `output_dir` - directory where place `<extension_name>.rbs`
+    ///   with RBS signatures
+    /// * `extension_name` - name of extension for `require`
+    /// * `module_name` - name of Ruby module, that contains all classes
+    ///   and enums, and also `Error` class for errors returned by Rust code
+    pub fn new(output_dir: PathBuf, extension_name: String, module_name: String) -> RubyConfig {
+        RubyConfig {
+            output_dir,
+            extension_name,
+            module_name,
+        }
+    }
+}
+
+/// Configuration for WebAssembly binding generation, generated Rust code
+/// uses `#[wasm_bindgen]`, so JavaScript glue and TypeScript declarations
+/// are produced by `wasm-bindgen` CLI or `wasm-pack`
+#[derive(Default)]
+pub struct WasmConfig {}
+
+impl WasmConfig {
+    /// Create `WasmConfig`
+    pub fn new() -> WasmConfig {
+        WasmConfig {}
     }
 }
 
@@ -378,7 +792,10 @@ impl Generator {
                     }),
                 );
             }
-            LanguageConfig::CppConfig(..) => {
+            LanguageConfig::CppConfig(..)
+            | LanguageConfig::SwiftConfig(..)
+            | LanguageConfig::GoConfig(..)
+            | LanguageConfig::DartConfig(..) => {
                 conv_map_source.push(src_reg.register(SourceCode {
                     id_of_code: "cpp-include.rs".into(),
                     code: include_str!("cpp/cpp-include.rs").into(),
@@ -403,13 +820,118 @@ impl Generator {
                     id_of_code: "rust_slice_tmpl.hpp".into(),
                     code: include_str!("cpp/rust_slice_tmpl.hpp").into(),
                 });
+                foreign_lang_helpers.push(SourceCode {
+                    id_of_code: "rust_input_iterator.hpp".into(),
+                    code: include_str!("cpp/rust_input_iterator.hpp").into(),
+                });
             }
-            LanguageConfig::PythonConfig(..) => {
+            LanguageConfig::CConfig(..) => {
+                // C++ helper headers are not needed for pure C API
                 conv_map_source.push(src_reg.register(SourceCode {
-                    id_of_code: "python-include.rs".into(),
-                    code: include_str!("python/python-include.rs").into(),
+                    id_of_code: "cpp-include.rs".into(),
+                    code: include_str!("cpp/cpp-include.rs").into(),
+                }));
+            }
+            LanguageConfig::PythonConfig(ref python_cfg) => match python_cfg.python_binding {
+                PythonBinding::RustCPython => {
+                    conv_map_source.push(src_reg.register(SourceCode {
+                        id_of_code: "python-include.rs".into(),
+                        code: include_str!("python/python-include.rs").into(),
+                    }));
+                }
+                PythonBinding::PyO3 => {
+                    conv_map_source.push(src_reg.register(SourceCode {
+                        id_of_code: "pyo3-include.rs".into(),
+                        code: include_str!("python/pyo3-include.rs").into(),
+                    }));
+                }
+            },
+            LanguageConfig::CSharpConfig(..) => {
+                conv_map_source.push(src_reg.register(SourceCode {
+                    id_of_code: "csharp-include.rs".into(),
+                    code: include_str!("csharp/csharp-include.rs").into(),
+                }));
+            }
+            LanguageConfig::NodeConfig(..) => {
+                conv_map_source.push(src_reg.register(SourceCode {
+                    id_of_code: "node-include.rs".into(),
+                    code: include_str!("node/node-include.rs").into(),
                 }));
             }
+            LanguageConfig::LuaConfig(..) => {
+                conv_map_source.push(src_reg.register(SourceCode {
+                    id_of_code: "lua-include.rs".into(),
+                    code: include_str!("lua/lua-include.rs").into(),
+                }));
+            }
+            LanguageConfig::RubyConfig(..) => {
+                conv_map_source.push(src_reg.register(SourceCode {
+                    id_of_code: "ruby-include.rs".into(),
+                    code: include_str!("ruby/ruby-include.rs").into(),
+                }));
+            }
+            LanguageConfig::WasmConfig(..) => {
+                conv_map_source.push(src_reg.register(SourceCode {
+                    id_of_code: "wasm-include.rs".into(),
+                    code: include_str!("wasm/wasm-include.rs").into(),
+                }));
+            }
+            LanguageConfig::Custom(ref backend) => {
+                for (id_of_code, code) in backend.include_typemaps() {
+                    conv_map_source.push(src_reg.register(SourceCode { id_of_code, code }));
+                }
+            }
+        }
+        let panic_include: Option<(&str, &str)> = match config {
+            LanguageConfig::JavaConfig(JavaConfig {
+                catch_panics: Some(_),
+                ..
+            }) => Some((
+                "jni-panic-include.rs",
+                include_str!("java_jni/jni-panic-include.rs"),
+            )),
+            LanguageConfig::CppConfig(CppConfig {
+                catch_panics: Some(_),
+                ..
+            })
+            | LanguageConfig::CConfig(CConfig {
+                catch_panics: Some(_),
+                ..
+            }) => {
+                foreign_lang_helpers.push(SourceCode {
+                    id_of_code: "rust_panic.h".into(),
+                    code: include_str!("cpp/rust_panic.h").into(),
+                });
+                Some((
+                    "cpp-panic-include.rs",
+                    include_str!("cpp/cpp-panic-include.rs"),
+                ))
+            }
+            LanguageConfig::PythonConfig(PythonConfig {
+                catch_panics: Some(_),
+                python_binding,
+                ..
+            }) => Some(match python_binding {
+                PythonBinding::RustCPython => (
+                    "python-panic-include.rs",
+                    include_str!("python/python-panic-include.rs"),
+                ),
+                PythonBinding::PyO3 => (
+                    "pyo3-panic-include.rs",
+                    include_str!("python/pyo3-panic-include.rs"),
+                ),
+            }),
+            _ => None,
+        };
+        if let Some((id_of_code, code)) = panic_include {
+            conv_map_source.push(src_reg.register(SourceCode {
+                id_of_code: "panic-include.rs".into(),
+                code: include_str!("panic-include.rs").into(),
+            }));
+            conv_map_source.push(src_reg.register(SourceCode {
+                id_of_code: id_of_code.into(),
+                code: code.into(),
+            }));
         }
         Generator {
             init_done: false,
@@ -598,6 +1120,8 @@ impl Generator {
         }
 
         let mut items_to_expand = Vec::with_capacity(1000);
+        let mut closure_callbacks = closure::ClosureCallbacks::default();
+        let mut iterator_classes = iterator::IteratorClasses::default();
 
         for src_id in src_ids {
             let syn_file = syn::parse_file(self.src_reg.src(*src_id))
@@ -642,12 +1166,52 @@ impl Generator {
                                 FOREIGNER_CLASS_DEPRECATED, FOREIGN_CLASS
                             );
                         }
-                        let fclass = code_parse::parse_foreigner_class(*src_id, &self.config, tts)?;
+                        let mut fclass =
+                            code_parse::parse_foreigner_class(*src_id, &self.config, tts)?;
+                        if Generator::closures_supported(&self.config) {
+                            for callback in
+                                closure_callbacks.expand_class(&mut self.conv_map, &mut fclass)?
+                            {
+                                writeln!(&mut file, "{}", callback.trait_code)
+                                    .expect(WRITE_TO_MEM_FAILED_MSG);
+                                items_to_expand.push(ItemToExpand::Interface(callback.interface));
+                            }
+                        }
+                        if Generator::iterators_supported(&self.config) {
+                            for iter_class in iterator_classes.expand_class(
+                                &self.config,
+                                &mut self.conv_map,
+                                &mut fclass,
+                            )? {
+                                writeln!(&mut file, "{}", iter_class.rust_code)
+                                    .expect(WRITE_TO_MEM_FAILED_MSG);
+                                self.conv_map.register_foreigner_class(&iter_class.class);
+                                items_to_expand
+                                    .push(ItemToExpand::Class(Box::new(iter_class.class)));
+                            }
+                        }
+                        if let Some(method) = fclass.methods.iter().find(|m| m.is_async) {
+                            if !Generator::async_fn_supported(&self.config) {
+                                return Err(DiagnosticError::new(
+                                    *src_id,
+                                    method.span(),
+                                    "async fn is not supported for this language",
+                                ));
+                            }
+                        }
                         debug!("expand_foreigner_class: self_desc {:?}", fclass.self_desc);
                         self.conv_map.register_foreigner_class(&fclass);
                         items_to_expand.push(ItemToExpand::Class(Box::new(fclass)));
                     } else if item_macro.mac.path.is_ident(FOREIGN_ENUM) {
                         let fenum = code_parse::parse_foreign_enum(*src_id, tts)?;
+                        if !fenum.is_c_like() && !Generator::enums_with_data_supported(&self.config)
+                        {
+                            return Err(DiagnosticError::new(
+                                *src_id,
+                                fenum.span(),
+                                "enum with data is not supported for this language, only C-like enum",
+                            ));
+                        }
                         items_to_expand.push(ItemToExpand::Enum(fenum));
                     } else if item_macro.mac.path.is_ident(FOREIGN_INTERFACE_DEPRECATED)
                         || item_macro.mac.path.is_ident(FOREIGN_CALLBACK)
@@ -733,11 +1297,68 @@ impl Generator {
         Ok(self.conv_map.take_utils_code())
     }
 
+    /// Languages that can export `foreign_enum!` with data carried by items
+    fn enums_with_data_supported(cfg: &LanguageConfig) -> bool {
+        matches!(
+            cfg,
+            LanguageConfig::JavaConfig(_)
+                | LanguageConfig::CppConfig(_)
+                | LanguageConfig::CConfig(_)
+                | LanguageConfig::PythonConfig(_)
+                | LanguageConfig::Custom(_)
+        )
+    }
+
+    /// Languages that can accept foreign lambdas as closure arguments
+    fn closures_supported(cfg: &LanguageConfig) -> bool {
+        matches!(
+            cfg,
+            LanguageConfig::JavaConfig(_)
+                | LanguageConfig::CppConfig(_)
+                | LanguageConfig::CConfig(_)
+        )
+    }
+
+    /// Languages that can export Rust iterators returned by methods
+    fn iterators_supported(cfg: &LanguageConfig) -> bool {
+        matches!(
+            cfg,
+            LanguageConfig::JavaConfig(_)
+                | LanguageConfig::CppConfig(_)
+                | LanguageConfig::CConfig(_)
+                | LanguageConfig::PythonConfig(_)
+        )
+    }
+
+    /// Languages that can export `async fn` methods of `foreign_class!`
+    fn async_fn_supported(cfg: &LanguageConfig) -> bool {
+        match cfg {
+            LanguageConfig::JavaConfig(ref java_cfg) => {
+                java_cfg.output_language == JavaOutputLanguage::Java
+            }
+            LanguageConfig::CppConfig(_)
+            | LanguageConfig::CConfig(_)
+            | LanguageConfig::PythonConfig(_)
+            | LanguageConfig::Custom(_) => true,
+            _ => false,
+        }
+    }
+
     fn language_generator(cfg: &LanguageConfig) -> &dyn LanguageGenerator {
         match cfg {
             LanguageConfig::JavaConfig(ref java_cfg) => java_cfg,
             LanguageConfig::CppConfig(ref cpp_cfg) => cpp_cfg,
             LanguageConfig::PythonConfig(ref python_cfg) => python_cfg,
+            LanguageConfig::CSharpConfig(ref csharp_cfg) => csharp_cfg,
+            LanguageConfig::SwiftConfig(ref swift_cfg) => swift_cfg,
+            LanguageConfig::GoConfig(ref go_cfg) => go_cfg,
+            LanguageConfig::NodeConfig(ref node_cfg) => node_cfg,
+            LanguageConfig::LuaConfig(ref lua_cfg) => lua_cfg,
+            LanguageConfig::RubyConfig(ref ruby_cfg) => ruby_cfg,
+            LanguageConfig::DartConfig(ref dart_cfg) => dart_cfg,
+            LanguageConfig::CConfig(ref c_cfg) => c_cfg,
+            LanguageConfig::WasmConfig(ref wasm_cfg) => wasm_cfg,
+            LanguageConfig::Custom(ref backend) => backend,
         }
     }
 }
@@ -763,6 +1384,26 @@ trait LanguageGenerator {
     }
 }
 
+/// Runtime for `async fn` methods, generated only if there are such methods,
+/// `async_executor` replaces default executor
+fn async_include_code(async_executor: Option<&str>) -> Result<TokenStream> {
+    let mut code = include_str!("async-include.rs").to_string();
+    if let Some(executor) = async_executor {
+        syn::parse_str::<syn::Path>(executor).map_err(|err| {
+            DiagnosticError::new_without_src_info(format!(
+                "async_executor: can not parse '{}' as path: {}",
+                executor, err
+            ))
+        })?;
+        code = code.replace(
+            "= swig_async_default_executor;",
+            &format!("= {};", executor),
+        );
+    }
+    Ok(syn::parse_str(&code)
+        .unwrap_or_else(|err| error::panic_on_syn_error("async-include.rs", code, err)))
+}
+
 #[doc(hidden)]
 #[derive(Clone, Copy, PartialEq)]
 pub enum RustEdition {
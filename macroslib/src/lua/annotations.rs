@@ -0,0 +1,189 @@
+//! Generation of `.d.lua` file with annotations for
+//! [Lua language server](https://luals.github.io/wiki/annotations/),
+//! so IDEs know signatures of functions of the generated Lua module
+
+use super::*;
+use crate::{
+    extension::{extend_foreign_class, extend_foreign_enum},
+    file_cache::FileWriteCache,
+    KNOWN_CLASS_DERIVES,
+};
+use std::io::Write as IoWrite;
+
+/// Words that can not be used as names of parameters
+const LUA_RESERVED_WORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+pub(in crate::lua) fn write_annotations(
+    ctx: &mut LuaContext,
+    declarations: &[String],
+    exported_names: &[String],
+) -> Result<()> {
+    let path = ctx
+        .cfg
+        .output_dir
+        .join(format!("{}.d.lua", ctx.cfg.module_name));
+    let mut file = FileWriteCache::new(&path, &mut ctx.generated_foreign_files);
+    writeln!(
+        &mut file,
+        "---@meta {}\n-- Automatically generated by flapigen",
+        ctx.cfg.module_name
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for declaration in declarations {
+        writeln!(&mut file).expect(WRITE_TO_MEM_FAILED_MSG);
+        file.write_all(declaration.as_bytes())
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(&mut file, "\nreturn {{").expect(WRITE_TO_MEM_FAILED_MSG);
+    for name in exported_names {
+        writeln!(&mut file, "    {name} = {name},", name = name).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(&mut file, "}}").expect(WRITE_TO_MEM_FAILED_MSG);
+    file.update_file_if_necessary().map_err(|err| {
+        DiagnosticError::map_any_err_to_our_err(format!(
+            "write to {} failed: {}",
+            path.display(),
+            err
+        ))
+    })
+}
+
+pub(in crate::lua) fn class_declaration(
+    ctx: &LuaContext,
+    class: &ForeignClassInfo,
+    members: &[String],
+) -> Result<String> {
+    let mut out = String::new();
+    write_doc_comments(&mut out, &class.doc_comments);
+    writeln!(
+        &mut out,
+        "---@class {name}\nlocal {name} = {{}}",
+        name = class.name
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for member in members {
+        out.push('\n');
+        out.push_str(member);
+    }
+    if !class.foreign_code.is_empty() {
+        writeln!(&mut out, "\n{}", class.foreign_code).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    let mut cnt = out.into_bytes();
+    extend_foreign_class(
+        class,
+        &mut cnt,
+        &KNOWN_CLASS_DERIVES,
+        &[],
+        ctx.class_ext_handlers,
+        ctx.method_ext_handlers,
+    )?;
+    String::from_utf8(cnt).map_err(DiagnosticError::map_any_err_to_our_err)
+}
+
+pub(in crate::lua) fn enum_declaration(
+    ctx: &LuaContext,
+    fenum: &ForeignEnumInfo,
+) -> Result<String> {
+    let mut out = String::new();
+    write_doc_comments(&mut out, &fenum.doc_comments);
+    writeln!(
+        &mut out,
+        "---@enum {name}\nlocal {name} = {{",
+        name = fenum.name
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for (i, item) in fenum.items.iter().enumerate() {
+        for comment in &item.doc_comments {
+            writeln!(&mut out, "    ---{}", comment.trim_end()).expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+        writeln!(&mut out, "    {} = {},", item.name, i).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    out.push_str("}\n");
+    let mut cnt = out.into_bytes();
+    extend_foreign_enum(fenum, &mut cnt, ctx.enum_ext_handlers)?;
+    String::from_utf8(cnt).map_err(DiagnosticError::map_any_err_to_our_err)
+}
+
+pub(in crate::lua) fn interface_declaration(
+    interface: &ForeignInterface,
+    members: &[String],
+) -> String {
+    let mut out = String::new();
+    write_doc_comments(&mut out, &interface.doc_comments);
+    writeln!(&mut out, "---@class {}", interface.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    for member in members {
+        out.push_str(member);
+    }
+    out
+}
+
+/// Declaration of function of class, `name` is qualified
+/// like `Class.func` or `Class:method`
+pub(in crate::lua) fn function_declaration(
+    doc_comments: &[String],
+    private: bool,
+    name: &str,
+    args: &[(String, String)],
+    ret_type: &str,
+) -> String {
+    let mut out = String::new();
+    write_doc_comments(&mut out, doc_comments);
+    if private {
+        out.push_str("---@private\n");
+    }
+    for (arg_name, lua_type) in args {
+        writeln!(&mut out, "---@param {} {}", lua_ident(arg_name), lua_type)
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    if ret_type != "nil" {
+        writeln!(&mut out, "---@return {}", ret_type).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    let args = args
+        .iter()
+        .map(|(name, _)| lua_ident(name))
+        .collect::<Vec<_>>();
+    writeln!(&mut out, "function {}({}) end", name, args.join(", "))
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    out
+}
+
+/// Method of callback object, it is called as `object:name(...)`
+pub(in crate::lua) fn field_declaration(
+    doc_comments: &[String],
+    interface_name: &str,
+    name: &str,
+    args: &[(String, String)],
+    ret_type: &str,
+) -> String {
+    let mut out = String::new();
+    write_doc_comments(&mut out, doc_comments);
+    let mut params = vec![format!("self: {}", interface_name)];
+    params.extend(
+        args.iter()
+            .map(|(name, lua_type)| format!("{}: {}", lua_ident(name), lua_type)),
+    );
+    write!(&mut out, "---@field {} fun({})", name, params.join(", "))
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    if ret_type != "nil" {
+        write!(&mut out, ": {}", ret_type).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    out.push('\n');
+    out
+}
+
+fn lua_ident(name: &str) -> String {
+    if LUA_RESERVED_WORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn write_doc_comments(out: &mut String, doc_comments: &[String]) {
+    for comment in doc_comments {
+        writeln!(out, "---{}", comment.trim_end()).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+}
@@ -0,0 +1,663 @@
+mod swig_foreign_types_map {}
+
+/// Part of Lua 5.4 C API, that generated code uses.
+/// Functions are resolved at link time, so Lua should be linked
+/// into executable or library (for example via `mlua-sys` or `lua-src` crates).
+#[allow(dead_code, non_camel_case_types)]
+mod swig_lua_sys {
+    use std::os::raw::{c_char, c_int, c_void};
+
+    #[repr(C)]
+    pub struct lua_State {
+        _private: [u8; 0],
+    }
+
+    pub type lua_Integer = i64;
+    pub type lua_Number = f64;
+    pub type lua_Unsigned = u64;
+    pub type lua_KContext = isize;
+    pub type lua_CFunction = unsafe extern "C" fn(lua: *mut lua_State) -> c_int;
+    pub type lua_KFunction = Option<
+        unsafe extern "C" fn(lua: *mut lua_State, status: c_int, ctx: lua_KContext) -> c_int,
+    >;
+
+    // Values from `lua.h` and `luaconf.h` of Lua 5.4 with default configuration
+    pub const LUA_OK: c_int = 0;
+    pub const LUA_TNONE: c_int = -1;
+    pub const LUA_TNIL: c_int = 0;
+    pub const LUA_TBOOLEAN: c_int = 1;
+    pub const LUA_TNUMBER: c_int = 3;
+    pub const LUA_TSTRING: c_int = 4;
+    pub const LUA_TTABLE: c_int = 5;
+    pub const LUA_TFUNCTION: c_int = 6;
+    pub const LUA_TUSERDATA: c_int = 7;
+    pub const LUA_REGISTRYINDEX: c_int = -1_000_000 - 1000;
+    pub const LUA_RIDX_MAINTHREAD: lua_Integer = 1;
+
+    extern "C" {
+        pub fn lua_gettop(lua: *mut lua_State) -> c_int;
+        pub fn lua_settop(lua: *mut lua_State, idx: c_int);
+        pub fn lua_absindex(lua: *mut lua_State, idx: c_int) -> c_int;
+        pub fn lua_pushvalue(lua: *mut lua_State, idx: c_int);
+        pub fn lua_type(lua: *mut lua_State, idx: c_int) -> c_int;
+        pub fn lua_typename(lua: *mut lua_State, tp: c_int) -> *const c_char;
+        pub fn lua_isinteger(lua: *mut lua_State, idx: c_int) -> c_int;
+        pub fn lua_toboolean(lua: *mut lua_State, idx: c_int) -> c_int;
+        pub fn lua_tointegerx(lua: *mut lua_State, idx: c_int, isnum: *mut c_int) -> lua_Integer;
+        pub fn lua_tonumberx(lua: *mut lua_State, idx: c_int, isnum: *mut c_int) -> lua_Number;
+        pub fn lua_tolstring(lua: *mut lua_State, idx: c_int, len: *mut usize) -> *const c_char;
+        pub fn lua_tothread(lua: *mut lua_State, idx: c_int) -> *mut lua_State;
+        pub fn lua_touserdata(lua: *mut lua_State, idx: c_int) -> *mut c_void;
+        pub fn lua_rawlen(lua: *mut lua_State, idx: c_int) -> lua_Unsigned;
+        pub fn lua_pushnil(lua: *mut lua_State);
+        pub fn lua_pushboolean(lua: *mut lua_State, b: c_int);
+        pub fn lua_pushinteger(lua: *mut lua_State, n: lua_Integer);
+        pub fn lua_pushnumber(lua: *mut lua_State, n: lua_Number);
+        pub fn lua_pushlstring(lua: *mut lua_State, s: *const c_char, len: usize) -> *const c_char;
+        pub fn lua_pushcclosure(lua: *mut lua_State, f: lua_CFunction, n: c_int);
+        pub fn lua_createtable(lua: *mut lua_State, narr: c_int, nrec: c_int);
+        pub fn lua_newuserdatauv(lua: *mut lua_State, sz: usize, nuvalue: c_int) -> *mut c_void;
+        pub fn lua_getfield(lua: *mut lua_State, idx: c_int, k: *const c_char) -> c_int;
+        pub fn lua_setfield(lua: *mut lua_State, idx: c_int, k: *const c_char);
+        pub fn lua_rawgeti(lua: *mut lua_State, idx: c_int, n: lua_Integer) -> c_int;
+        pub fn lua_rawseti(lua: *mut lua_State, idx: c_int, n: lua_Integer);
+        pub fn lua_rawset(lua: *mut lua_State, idx: c_int);
+        pub fn lua_next(lua: *mut lua_State, idx: c_int) -> c_int;
+        pub fn lua_setmetatable(lua: *mut lua_State, objindex: c_int) -> c_int;
+        pub fn lua_pcallk(
+            lua: *mut lua_State,
+            nargs: c_int,
+            nresults: c_int,
+            errfunc: c_int,
+            ctx: lua_KContext,
+            k: lua_KFunction,
+        ) -> c_int;
+        pub fn lua_error(lua: *mut lua_State) -> c_int;
+        pub fn luaL_newmetatable(lua: *mut lua_State, tname: *const c_char) -> c_int;
+        pub fn luaL_testudata(lua: *mut lua_State, ud: c_int, tname: *const c_char) -> *mut c_void;
+        pub fn luaL_ref(lua: *mut lua_State, t: c_int) -> c_int;
+        pub fn luaL_unref(lua: *mut lua_State, t: c_int, r: c_int);
+    }
+}
+
+/// Error, that is raised as Lua error when it reaches Lua side
+#[allow(dead_code)]
+#[derive(Debug)]
+struct SwigLuaError(String);
+
+impl std::fmt::Display for SwigLuaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[allow(dead_code)]
+type SwigLuaResult<T> = Result<T, SwigLuaError>;
+
+/// Run body of function called from Lua, it returns number of pushed results.
+/// `lua_error` does `longjmp`, so it is called only after all Rust values are dropped.
+#[allow(dead_code)]
+unsafe fn swig_lua_call<F>(lua: *mut swig_lua_sys::lua_State, f: F) -> std::os::raw::c_int
+where
+    F: FnOnce() -> SwigLuaResult<std::os::raw::c_int>,
+{
+    let err = match f() {
+        Ok(nresults) => return nresults,
+        Err(err) => err,
+    };
+    swig_lua_push_str(lua, &err.0);
+    drop(err);
+    swig_lua_sys::lua_error(lua)
+}
+
+#[allow(dead_code)]
+unsafe fn swig_lua_push_str(lua: *mut swig_lua_sys::lua_State, s: &str) {
+    swig_lua_sys::lua_pushlstring(lua, s.as_ptr() as *const std::os::raw::c_char, s.len());
+}
+
+/// `name` should be NUL-terminated
+#[allow(dead_code)]
+unsafe fn swig_lua_set_field(
+    lua: *mut swig_lua_sys::lua_State,
+    idx: std::os::raw::c_int,
+    name: &str,
+) {
+    debug_assert!(name.ends_with('\0'));
+    swig_lua_sys::lua_setfield(lua, idx, name.as_ptr() as *const std::os::raw::c_char);
+}
+
+/// Set function `f` as field `name` of table on top of stack
+#[allow(dead_code)]
+unsafe fn swig_lua_set_function(
+    lua: *mut swig_lua_sys::lua_State,
+    name: &str,
+    f: swig_lua_sys::lua_CFunction,
+) {
+    swig_lua_sys::lua_pushcclosure(lua, f, 0);
+    swig_lua_set_field(lua, -2, name);
+}
+
+#[allow(dead_code)]
+unsafe fn swig_lua_is_nil(lua: *mut swig_lua_sys::lua_State, idx: std::os::raw::c_int) -> bool {
+    let ty = swig_lua_sys::lua_type(lua, idx);
+    ty == swig_lua_sys::LUA_TNIL || ty == swig_lua_sys::LUA_TNONE
+}
+
+#[allow(dead_code)]
+unsafe fn swig_lua_type_error(
+    lua: *mut swig_lua_sys::lua_State,
+    idx: std::os::raw::c_int,
+    expected: &str,
+) -> SwigLuaError {
+    let name = std::ffi::CStr::from_ptr(swig_lua_sys::lua_typename(
+        lua,
+        swig_lua_sys::lua_type(lua, idx),
+    ));
+    SwigLuaError(format!(
+        "{} expected, got {}",
+        expected,
+        name.to_string_lossy()
+    ))
+}
+
+/// Conversion from Lua value on stack to Rust
+#[allow(dead_code)]
+trait SwigFromLua: Sized {
+    unsafe fn swig_from_lua(
+        lua: *mut swig_lua_sys::lua_State,
+        idx: std::os::raw::c_int,
+    ) -> SwigLuaResult<Self>;
+}
+
+/// Conversion from Rust value to Lua value, pushed on top of stack
+#[allow(dead_code)]
+trait SwigIntoLua {
+    unsafe fn swig_push_lua(self, lua: *mut swig_lua_sys::lua_State) -> SwigLuaResult<()>;
+}
+
+impl SwigIntoLua for () {
+    unsafe fn swig_push_lua(self, _lua: *mut swig_lua_sys::lua_State) -> SwigLuaResult<()> {
+        Ok(())
+    }
+}
+
+impl SwigFromLua for bool {
+    unsafe fn swig_from_lua(
+        lua: *mut swig_lua_sys::lua_State,
+        idx: std::os::raw::c_int,
+    ) -> SwigLuaResult<Self> {
+        if swig_lua_sys::lua_type(lua, idx) != swig_lua_sys::LUA_TBOOLEAN {
+            return Err(swig_lua_type_error(lua, idx, "boolean"));
+        }
+        Ok(swig_lua_sys::lua_toboolean(lua, idx) != 0)
+    }
+}
+
+impl SwigIntoLua for bool {
+    unsafe fn swig_push_lua(self, lua: *mut swig_lua_sys::lua_State) -> SwigLuaResult<()> {
+        swig_lua_sys::lua_pushboolean(lua, self as std::os::raw::c_int);
+        Ok(())
+    }
+}
+
+// Lua 5.4 has 64-bit integer subtype of number, so integers are checked
+// to be in range and float values with fractional part are rejected
+macro_rules! swig_lua_integer_conversion {
+    ($($ty:ty),*) => {
+        $(
+            impl SwigFromLua for $ty {
+                unsafe fn swig_from_lua(
+                    lua: *mut swig_lua_sys::lua_State,
+                    idx: std::os::raw::c_int,
+                ) -> SwigLuaResult<Self> {
+                    let mut isnum = 0;
+                    let ret = swig_lua_sys::lua_tointegerx(lua, idx, &mut isnum);
+                    if isnum == 0 || swig_lua_sys::lua_type(lua, idx) != swig_lua_sys::LUA_TNUMBER {
+                        return Err(swig_lua_type_error(lua, idx, "integer"));
+                    }
+                    <$ty as std::convert::TryFrom<i64>>::try_from(ret).map_err(|_| {
+                        SwigLuaError(format!("{} is out of range for {}", ret, stringify!($ty)))
+                    })
+                }
+            }
+
+            impl SwigIntoLua for $ty {
+                unsafe fn swig_push_lua(self, lua: *mut swig_lua_sys::lua_State) -> SwigLuaResult<()> {
+                    let value = <i64 as std::convert::TryFrom<$ty>>::try_from(self).map_err(|_| {
+                        SwigLuaError(format!("{} is out of range for Lua integer", self))
+                    })?;
+                    swig_lua_sys::lua_pushinteger(lua, value);
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+swig_lua_integer_conversion!(i8, i16, i32, i64, u8, u16, u32, u64, isize, usize);
+
+impl SwigFromLua for f64 {
+    unsafe fn swig_from_lua(
+        lua: *mut swig_lua_sys::lua_State,
+        idx: std::os::raw::c_int,
+    ) -> SwigLuaResult<Self> {
+        let mut isnum = 0;
+        let ret = swig_lua_sys::lua_tonumberx(lua, idx, &mut isnum);
+        if isnum == 0 || swig_lua_sys::lua_type(lua, idx) != swig_lua_sys::LUA_TNUMBER {
+            return Err(swig_lua_type_error(lua, idx, "number"));
+        }
+        Ok(ret)
+    }
+}
+
+impl SwigIntoLua for f64 {
+    unsafe fn swig_push_lua(self, lua: *mut swig_lua_sys::lua_State) -> SwigLuaResult<()> {
+        swig_lua_sys::lua_pushnumber(lua, self);
+        Ok(())
+    }
+}
+
+impl SwigFromLua for f32 {
+    unsafe fn swig_from_lua(
+        lua: *mut swig_lua_sys::lua_State,
+        idx: std::os::raw::c_int,
+    ) -> SwigLuaResult<Self> {
+        f64::swig_from_lua(lua, idx).map(|x| x as f32)
+    }
+}
+
+impl SwigIntoLua for f32 {
+    unsafe fn swig_push_lua(self, lua: *mut swig_lua_sys::lua_State) -> SwigLuaResult<()> {
+        f64::from(self).swig_push_lua(lua)
+    }
+}
+
+// numbers are not accepted as strings, because of `lua_tolstring`
+// converts number in place and this breaks `lua_next` traversal
+impl SwigFromLua for String {
+    unsafe fn swig_from_lua(
+        lua: *mut swig_lua_sys::lua_State,
+        idx: std::os::raw::c_int,
+    ) -> SwigLuaResult<Self> {
+        if swig_lua_sys::lua_type(lua, idx) != swig_lua_sys::LUA_TSTRING {
+            return Err(swig_lua_type_error(lua, idx, "string"));
+        }
+        let mut len = 0;
+        let p = swig_lua_sys::lua_tolstring(lua, idx, &mut len);
+        let bytes = std::slice::from_raw_parts(p as *const u8, len);
+        String::from_utf8(bytes.to_vec()).map_err(|err| SwigLuaError(err.to_string()))
+    }
+}
+
+impl<'a> SwigIntoLua for &'a str {
+    unsafe fn swig_push_lua(self, lua: *mut swig_lua_sys::lua_State) -> SwigLuaResult<()> {
+        swig_lua_push_str(lua, self);
+        Ok(())
+    }
+}
+
+impl SwigIntoLua for String {
+    unsafe fn swig_push_lua(self, lua: *mut swig_lua_sys::lua_State) -> SwigLuaResult<()> {
+        self.as_str().swig_push_lua(lua)
+    }
+}
+
+/// `lua_createtable` with sizes clamped to `c_int`, sizes are only hints
+#[allow(dead_code)]
+unsafe fn swig_lua_new_table(lua: *mut swig_lua_sys::lua_State, narr: usize, nrec: usize) {
+    let max = std::os::raw::c_int::max_value() as usize;
+    swig_lua_sys::lua_createtable(
+        lua,
+        narr.min(max) as std::os::raw::c_int,
+        nrec.min(max) as std::os::raw::c_int,
+    );
+}
+
+/// Convert elements of sequence table at `idx` with `f`,
+/// element is on top of stack during call of `f`
+#[allow(dead_code)]
+unsafe fn swig_lua_table_elements<T, F>(
+    lua: *mut swig_lua_sys::lua_State,
+    idx: std::os::raw::c_int,
+    mut f: F,
+) -> SwigLuaResult<Vec<T>>
+where
+    F: FnMut(std::os::raw::c_int) -> SwigLuaResult<T>,
+{
+    if swig_lua_sys::lua_type(lua, idx) != swig_lua_sys::LUA_TTABLE {
+        return Err(swig_lua_type_error(lua, idx, "table"));
+    }
+    let idx = swig_lua_sys::lua_absindex(lua, idx);
+    let len = swig_lua_sys::lua_rawlen(lua, idx);
+    let mut elements = Vec::with_capacity(len as usize);
+    for i in 1..=len {
+        swig_lua_sys::lua_rawgeti(lua, idx, i as swig_lua_sys::lua_Integer);
+        let elem = f(swig_lua_sys::lua_gettop(lua));
+        swig_lua_sys::lua_settop(lua, -2);
+        elements.push(elem?);
+    }
+    Ok(elements)
+}
+
+/// Convert key-value pairs of table at `idx`,
+/// key and value are on top of stack during call of `f`
+#[allow(dead_code)]
+unsafe fn swig_lua_table_pairs<K, V, F>(
+    lua: *mut swig_lua_sys::lua_State,
+    idx: std::os::raw::c_int,
+    mut f: F,
+) -> SwigLuaResult<Vec<(K, V)>>
+where
+    F: FnMut(std::os::raw::c_int, std::os::raw::c_int) -> SwigLuaResult<(K, V)>,
+{
+    if swig_lua_sys::lua_type(lua, idx) != swig_lua_sys::LUA_TTABLE {
+        return Err(swig_lua_type_error(lua, idx, "table"));
+    }
+    let idx = swig_lua_sys::lua_absindex(lua, idx);
+    let mut pairs = Vec::new();
+    swig_lua_sys::lua_pushnil(lua);
+    while swig_lua_sys::lua_next(lua, idx) != 0 {
+        let top = swig_lua_sys::lua_gettop(lua);
+        match f(top - 1, top) {
+            Ok(pair) => pairs.push(pair),
+            Err(err) => {
+                swig_lua_sys::lua_settop(lua, -3);
+                return Err(err);
+            }
+        }
+        swig_lua_sys::lua_settop(lua, -2);
+    }
+    Ok(pairs)
+}
+
+/// Implemented by marker type of every generated Lua class
+#[allow(dead_code)]
+trait SwigLuaClass: 'static {
+    /// Type of Rust object, that userdata owns
+    type Storage: 'static;
+    const NAME: &'static str;
+    /// NUL-terminated name of metatable in registry
+    const METATABLE: &'static str;
+}
+
+/// Userdata holds only pointer to Rust object,
+/// pointer is null after object was collected
+#[allow(dead_code)]
+unsafe fn swig_lua_userdata_ptr<C: SwigLuaClass>(
+    lua: *mut swig_lua_sys::lua_State,
+    idx: std::os::raw::c_int,
+) -> SwigLuaResult<*mut *mut C::Storage> {
+    let p = swig_lua_sys::luaL_testudata(
+        lua,
+        idx,
+        C::METATABLE.as_ptr() as *const std::os::raw::c_char,
+    );
+    if p.is_null() {
+        return Err(swig_lua_type_error(lua, idx, C::NAME));
+    }
+    Ok(p as *mut *mut C::Storage)
+}
+
+#[allow(dead_code)]
+unsafe fn swig_lua_unwrap<'a, C: SwigLuaClass>(
+    lua: *mut swig_lua_sys::lua_State,
+    idx: std::os::raw::c_int,
+) -> SwigLuaResult<&'a mut C::Storage> {
+    let p = *swig_lua_userdata_ptr::<C>(lua, idx)?;
+    if p.is_null() {
+        return Err(SwigLuaError(format!(
+            "{} object was already freed",
+            C::NAME
+        )));
+    }
+    Ok(&mut *p)
+}
+
+/// Rust object, that becomes new userdata with metatable of class `C`
+#[allow(dead_code)]
+struct SwigLuaObject<C: SwigLuaClass>(C::Storage);
+
+impl<C: SwigLuaClass> SwigIntoLua for SwigLuaObject<C> {
+    unsafe fn swig_push_lua(self, lua: *mut swig_lua_sys::lua_State) -> SwigLuaResult<()> {
+        let p = Box::into_raw(Box::new(self.0));
+        let ud = swig_lua_sys::lua_newuserdatauv(lua, std::mem::size_of::<*mut C::Storage>(), 0)
+            as *mut *mut C::Storage;
+        ud.write(p);
+        swig_lua_sys::lua_getfield(
+            lua,
+            swig_lua_sys::LUA_REGISTRYINDEX,
+            C::METATABLE.as_ptr() as *const std::os::raw::c_char,
+        );
+        swig_lua_sys::lua_setmetatable(lua, -2);
+        Ok(())
+    }
+}
+
+/// `__gc` metamethod
+#[allow(dead_code)]
+unsafe extern "C" fn swig_lua_gc<C: SwigLuaClass>(
+    lua: *mut swig_lua_sys::lua_State,
+) -> std::os::raw::c_int {
+    if let Ok(ud) = swig_lua_userdata_ptr::<C>(lua, 1) {
+        let p = std::ptr::replace(ud, std::ptr::null_mut());
+        if !p.is_null() {
+            drop(Box::from_raw(p));
+        }
+    }
+    0
+}
+
+/// `__tostring` metamethod
+#[allow(dead_code)]
+unsafe extern "C" fn swig_lua_tostring<C: SwigLuaClass>(
+    lua: *mut swig_lua_sys::lua_State,
+) -> std::os::raw::c_int {
+    swig_lua_call(lua, || {
+        let p = *swig_lua_userdata_ptr::<C>(lua, 1)?;
+        swig_lua_push_str(lua, &format!("{}: {:p}", C::NAME, p));
+        Ok(1)
+    })
+}
+
+/// Register metatable of class `C`, table with methods is on top of stack,
+/// it is popped and becomes `__index` of metatable
+#[allow(dead_code)]
+unsafe fn swig_lua_define_metatable<C: SwigLuaClass>(lua: *mut swig_lua_sys::lua_State) {
+    swig_lua_sys::luaL_newmetatable(lua, C::METATABLE.as_ptr() as *const std::os::raw::c_char);
+    swig_lua_sys::lua_pushvalue(lua, -2);
+    swig_lua_set_field(lua, -2, "__index\0");
+    swig_lua_set_function(lua, "__gc\0", swig_lua_gc::<C>);
+    swig_lua_set_function(lua, "__tostring\0", swig_lua_tostring::<C>);
+    swig_lua_sys::lua_settop(lua, -3);
+}
+
+/// Lua object, that implements callback. Lua state is not thread-safe,
+/// so object can be used only on thread, where it was created.
+#[allow(dead_code)]
+struct SwigLuaCallback {
+    lua: *mut swig_lua_sys::lua_State,
+    object_ref: std::os::raw::c_int,
+    lua_thread: std::thread::ThreadId,
+}
+
+unsafe impl Send for SwigLuaCallback {}
+unsafe impl Sync for SwigLuaCallback {}
+
+#[allow(dead_code)]
+impl SwigLuaCallback {
+    unsafe fn new(
+        lua: *mut swig_lua_sys::lua_State,
+        idx: std::os::raw::c_int,
+        name: &str,
+    ) -> SwigLuaResult<SwigLuaCallback> {
+        let ty = swig_lua_sys::lua_type(lua, idx);
+        if ty != swig_lua_sys::LUA_TTABLE && ty != swig_lua_sys::LUA_TUSERDATA {
+            return Err(swig_lua_type_error(lua, idx, name));
+        }
+        // `lua` may be coroutine, that can be collected before callback
+        swig_lua_sys::lua_rawgeti(
+            lua,
+            swig_lua_sys::LUA_REGISTRYINDEX,
+            swig_lua_sys::LUA_RIDX_MAINTHREAD,
+        );
+        let main_state = swig_lua_sys::lua_tothread(lua, -1);
+        swig_lua_sys::lua_settop(lua, -2);
+        swig_lua_sys::lua_pushvalue(lua, idx);
+        let object_ref = swig_lua_sys::luaL_ref(lua, swig_lua_sys::LUA_REGISTRYINDEX);
+        Ok(SwigLuaCallback {
+            lua: main_state,
+            object_ref,
+            lua_thread: std::thread::current().id(),
+        })
+    }
+
+    /// Call method `name` of object, `push_args` pushes arguments,
+    /// `read_ret` converts returned value on top of stack
+    unsafe fn call_method<R, A, F>(&self, name: &str, push_args: A, read_ret: F) -> SwigLuaResult<R>
+    where
+        A: FnOnce(*mut swig_lua_sys::lua_State) -> SwigLuaResult<()>,
+        F: FnOnce(*mut swig_lua_sys::lua_State, std::os::raw::c_int) -> SwigLuaResult<R>,
+    {
+        if std::thread::current().id() != self.lua_thread {
+            return Err(SwigLuaError(format!(
+                "Lua callback {} called from other thread",
+                name
+            )));
+        }
+        let lua = self.lua;
+        let top = swig_lua_sys::lua_gettop(lua);
+        let ret = (|| {
+            swig_lua_sys::lua_rawgeti(
+                lua,
+                swig_lua_sys::LUA_REGISTRYINDEX,
+                swig_lua_sys::lua_Integer::from(self.object_ref),
+            );
+            let c_name = std::ffi::CString::new(name).expect("method name with NUL byte");
+            if swig_lua_sys::lua_getfield(lua, -1, c_name.as_ptr()) != swig_lua_sys::LUA_TFUNCTION {
+                return Err(SwigLuaError(format!(
+                    "callback object has no method {}",
+                    name
+                )));
+            }
+            // method is called as `object:name(...)`
+            swig_lua_sys::lua_pushvalue(lua, -2);
+            let func_idx = swig_lua_sys::lua_gettop(lua) - 1;
+            push_args(lua)?;
+            let nargs = swig_lua_sys::lua_gettop(lua) - func_idx;
+            if swig_lua_sys::lua_pcallk(lua, nargs, 1, 0, 0, None) != swig_lua_sys::LUA_OK {
+                let mut len = 0;
+                let p = swig_lua_sys::lua_tolstring(lua, -1, &mut len);
+                let msg = if p.is_null() {
+                    "Lua error".to_string()
+                } else {
+                    String::from_utf8_lossy(std::slice::from_raw_parts(p as *const u8, len))
+                        .into_owned()
+                };
+                return Err(SwigLuaError(msg));
+            }
+            read_ret(lua, swig_lua_sys::lua_gettop(lua))
+        })();
+        swig_lua_sys::lua_settop(lua, top);
+        ret
+    }
+}
+
+impl Drop for SwigLuaCallback {
+    fn drop(&mut self) {
+        // reference can not be released from other thread,
+        // so object stays in registry until Lua state is closed
+        if std::thread::current().id() == self.lua_thread {
+            unsafe {
+                swig_lua_sys::luaL_unref(
+                    self.lua,
+                    swig_lua_sys::LUA_REGISTRYINDEX,
+                    self.object_ref,
+                );
+            }
+        }
+    }
+}
+
+/// Lua has no character type, so `char` is passed as string with one character
+#[allow(dead_code)]
+fn swig_lua_char_from_string(s: String) -> SwigLuaResult<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Ok(ch),
+        _ => Err(SwigLuaError(format!(
+            "string with one character expected, got {:?}",
+            s
+        ))),
+    }
+}
+
+foreign_typemap!(
+    ($p:r_type) char => String {
+        $out = $p.to_string();
+    };
+    ($p:f_type) => "string";
+    ($p:r_type) char <= String {
+        $out = swig_lua_char_from_string($p)?;
+    };
+    ($p:f_type) <= "string";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Arc<Mutex<T>> => &Mutex<T> {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &Mutex<T> => MutexGuard<T> {
+        $out = $p.lock().unwrap();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> MutexGuard<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> MutexGuard<T> => &mut T {
+        $out = &mut $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Rc<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &Rc<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &RefCell<T> => Ref<T> {
+        $out = $p.borrow();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &RefCell<T> => RefMut<T> {
+        $out = $p.borrow_mut();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Ref<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> RefMut<T> => &mut T {
+        $out = &mut $p;
+    };
+);
@@ -0,0 +1,1277 @@
+mod annotations;
+
+use std::{fmt::Write, ops::Deref, path::PathBuf};
+
+use heck::SnakeCase;
+use petgraph::Direction;
+use proc_macro2::TokenStream;
+use rustc_hash::FxHashSet;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    error::{panic_on_syn_error, DiagnosticError, Result, SourceIdSpan},
+    extension::{ClassExtHandlers, EnumExtHandlers, ExtHandlers, MethodExtHandlers},
+    typemap::{
+        ast::{self, DisplayToTokens, TypeName},
+        ty::{ForeignConversationRule, ForeignTypeS, RustType},
+        utils::{create_suitable_types_for_constructor_and_self, remove_files_if},
+        MapToForeignFlag,
+    },
+    types::{
+        ForeignClassInfo, ForeignEnumInfo, ForeignInterface, ForeignInterfaceMethod, ForeignMethod,
+        ItemToExpand, MethodAccess, MethodVariant,
+    },
+    LanguageGenerator, LuaConfig, SourceCode, TypeMap, WRITE_TO_MEM_FAILED_MSG,
+};
+
+const ENUM_TRAIT_NAME: &str = "SwigForeignEnum";
+const INTERFACE_TRAIT_NAME: &str = "SwigForeignInterface";
+/// Return type of function, that generated conversion code is placed into
+const CONV_FUNC_RET_TYPE: &str = "SwigLuaResult<std::os::raw::c_int>";
+
+struct LuaContext<'a> {
+    cfg: &'a LuaConfig,
+    conv_map: &'a mut TypeMap,
+    rust_code: Vec<TokenStream>,
+    generated_foreign_files: FxHashSet<PathBuf>,
+    class_ext_handlers: &'a ClassExtHandlers,
+    method_ext_handlers: &'a MethodExtHandlers,
+    enum_ext_handlers: &'a EnumExtHandlers,
+}
+
+/// Conversion of value between Rust and Lua
+struct LuaConversion {
+    /// For input: statements, that convert stack index in variable
+    /// into Rust value in variable with the same name,
+    /// for output: statements, that push value of variable on Lua stack
+    code: String,
+    /// Type of Lua value in terms of Lua language server annotations
+    lua_type: String,
+}
+
+impl LanguageGenerator for LuaConfig {
+    fn expand_items(
+        &self,
+        conv_map: &mut TypeMap,
+        _pointer_target_width: usize,
+        _code: &[SourceCode],
+        items: Vec<ItemToExpand>,
+        remove_not_generated_files: bool,
+        ext_handlers: ExtHandlers,
+    ) -> Result<Vec<TokenStream>> {
+        if !(self.output_dir.exists() && self.output_dir.is_dir()) {
+            return Err(DiagnosticError::map_any_err_to_our_err(format!(
+                "Path {} not exists or not directory",
+                self.output_dir.display()
+            )));
+        }
+        if let Some(rule) = conv_map.take_not_merged_not_generic_rules().first() {
+            return Err(DiagnosticError::new(
+                rule.src_id,
+                rule.span,
+                "foreign_typemap! rule with code or options for foreign side is not supported for Lua",
+            ));
+        }
+        let mut ctx = LuaContext {
+            cfg: self,
+            conv_map,
+            rust_code: vec![],
+            generated_foreign_files: FxHashSet::default(),
+            class_ext_handlers: ext_handlers.class_ext_handlers,
+            method_ext_handlers: ext_handlers.method_ext_handlers,
+            enum_ext_handlers: ext_handlers.enum_ext_handlers,
+        };
+        for item in &items {
+            match item {
+                ItemToExpand::Class(ref fclass) => register_class(&mut ctx, fclass),
+                ItemToExpand::Enum(ref fenum) => register_enum(&mut ctx, fenum)?,
+                ItemToExpand::Interface(ref finterface) => {
+                    register_interface(&mut ctx, finterface)?
+                }
+            }
+        }
+        let mut code = Vec::with_capacity(items.len());
+        let mut module_initialization = Vec::with_capacity(items.len());
+        let mut declarations = Vec::with_capacity(items.len());
+        let mut exported_names = Vec::with_capacity(items.len());
+        for item in &items {
+            let (item_code, initialization, declaration) = match item {
+                ItemToExpand::Class(ref fclass) => {
+                    exported_names.push(fclass.name.to_string());
+                    generate_class(&mut ctx, fclass)?
+                }
+                ItemToExpand::Enum(ref fenum) => {
+                    exported_names.push(fenum.name.to_string());
+                    generate_enum(&mut ctx, fenum)?
+                }
+                ItemToExpand::Interface(ref finterface) => {
+                    generate_interface(&mut ctx, finterface)?
+                }
+            };
+            code.push(item_code);
+            module_initialization.push(initialization);
+            declarations.push(declaration);
+        }
+        code.push(generate_module_initialization(
+            &self.module_name,
+            &module_initialization,
+        ));
+        annotations::write_annotations(&mut ctx, &declarations, &exported_names)?;
+        if remove_not_generated_files {
+            let generated_foreign_files = &ctx.generated_foreign_files;
+            remove_files_if(&self.output_dir, |path| {
+                if let Some(name) = path.file_name().and_then(|x| x.to_str()) {
+                    if name.ends_with(".d.lua") && !generated_foreign_files.contains(path) {
+                        return true;
+                    }
+                }
+                false
+            })
+            .map_err(DiagnosticError::map_any_err_to_our_err)?;
+        }
+        code.append(&mut ctx.rust_code);
+        Ok(code)
+    }
+}
+
+fn register_class(ctx: &mut LuaContext, class: &ForeignClassInfo) {
+    if let Some(ref self_desc) = class.self_desc {
+        ctx.conv_map
+            .find_or_alloc_rust_type(&self_desc.self_type, class.src_id);
+        ctx.conv_map
+            .find_or_alloc_rust_type(&self_desc.constructor_ret_type, class.src_id);
+    }
+}
+
+fn register_enum(ctx: &mut LuaContext, fenum: &ForeignEnumInfo) -> Result<()> {
+    let enum_ty = ast::parse_ty_with_given_span(&fenum.name.to_string(), fenum.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(fenum.src_id, err))?;
+    ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &enum_ty,
+        &[ENUM_TRAIT_NAME],
+        fenum.src_id,
+    );
+    Ok(())
+}
+
+fn register_interface(ctx: &mut LuaContext, interface: &ForeignInterface) -> Result<()> {
+    let boxed_trait_ty = boxed_interface_type(interface)?;
+    let boxed_trait_rust_ty = ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &boxed_trait_ty,
+        &[INTERFACE_TRAIT_NAME],
+        interface.src_id,
+    );
+    let rule = ForeignConversationRule {
+        rust_ty: boxed_trait_rust_ty.to_idx(),
+        intermediate: None,
+    };
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(interface.name.to_string(), interface.src_id_span()),
+        provides_by_module: vec![],
+        into_from_rust: Some(rule.clone()),
+        from_into_rust: Some(rule),
+        name_prefix: None,
+    })?;
+    Ok(())
+}
+
+/// Generate module with functions of class, code to register class
+/// during module opening and annotations of class.
+/// Methods are placed into `__index` of userdata metatable,
+/// constructors and static methods into table of class.
+fn generate_class(
+    ctx: &mut LuaContext,
+    class: &ForeignClassInfo,
+) -> Result<(TokenStream, TokenStream, String)> {
+    let class_name = class.name.to_string();
+    let wrapper_mod_name = lua_wrapper_mod_name(&class_name);
+    let storage_ty = storage_type(class);
+    if storage_ty.is_none()
+        && class
+            .methods
+            .iter()
+            .any(|m| m.variant != MethodVariant::StaticMethod)
+    {
+        return Err(DiagnosticError::new(
+            class.src_id,
+            class.span(),
+            format!(
+                "Class {} has non-static methods, but no self_type",
+                class.name
+            ),
+        ));
+    }
+
+    let mut code = format!(
+        r#"
+#[allow(unused_variables, unused_mut, unused_unsafe, non_snake_case, clippy::all)]
+mod {mod_name} {{
+    use super::*;
+
+    /// Marker of Lua class {class_name}
+    pub struct LuaClass;
+
+    impl SwigLuaClass for LuaClass {{
+        type Storage = {storage};
+        const NAME: &'static str = "{class_name}";
+        const METATABLE: &'static str = "{module_name}.{class_name}\0";
+    }}
+"#,
+        mod_name = wrapper_mod_name,
+        class_name = class_name,
+        module_name = ctx.cfg.module_name,
+        storage = storage_ty
+            .as_ref()
+            .map(|ty| DisplayToTokens(ty).to_string())
+            .unwrap_or_else(|| "()".into()),
+    );
+
+    let mut lua_names = FxHashSet::default();
+    let mut methods = Vec::with_capacity(class.methods.len());
+    let mut static_methods = Vec::with_capacity(class.methods.len());
+    let mut members = Vec::with_capacity(class.methods.len());
+    for method in &class.methods {
+        if method.is_dummy_constructor() {
+            continue;
+        }
+        let is_static = !matches!(method.variant, MethodVariant::Method(_));
+        let lua_name = method.short_name();
+        if !lua_names.insert((lua_name.clone(), is_static)) {
+            return Err(DiagnosticError::new(
+                class.src_id,
+                method.span(),
+                format!(
+                    "Lua class {} already has function {}, use alias to give other name",
+                    class.name, lua_name
+                ),
+            ));
+        }
+        let rust_func_name = if is_static {
+            format!("lua_static_{}", lua_name)
+        } else {
+            format!("lua_{}", lua_name)
+        };
+        code.push_str(&generate_method(
+            ctx,
+            class,
+            method,
+            &rust_func_name,
+            &lua_name,
+            &mut members,
+        )?);
+        let registration = format!(
+            "swig_lua_set_function(swig_lua, \"{}\\0\", {});",
+            lua_name, rust_func_name
+        );
+        if is_static {
+            static_methods.push(registration);
+        } else {
+            methods.push(registration);
+        }
+    }
+    let define_metatable = if storage_ty.is_some() {
+        format!(
+            r#"swig_lua_new_table(swig_lua, 0, {methods_len});
+        {methods}
+        swig_lua_define_metatable::<LuaClass>(swig_lua);"#,
+            methods_len = methods.len(),
+            methods = methods.join("\n        "),
+        )
+    } else {
+        String::new()
+    };
+    write!(
+        &mut code,
+        r#"
+    /// Register class, module table should be on top of stack
+    pub unsafe fn define(swig_lua: *mut swig_lua_sys::lua_State) -> SwigLuaResult<()> {{
+        {define_metatable}
+        swig_lua_new_table(swig_lua, 0, {static_methods_len});
+        {static_methods}
+        swig_lua_set_field(swig_lua, -2, "{class_name}\0");
+        Ok(())
+    }}
+}}
+"#,
+        define_metatable = define_metatable,
+        static_methods_len = static_methods.len(),
+        static_methods = static_methods.join("\n        "),
+        class_name = class_name,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    let declaration = annotations::class_declaration(ctx, class, &members)?;
+    let module_initialization = parse_code(
+        &format!("{}::define(swig_lua)?;", wrapper_mod_name),
+        "lua module initialization",
+    );
+    Ok((
+        parse_code(&code, "lua class"),
+        module_initialization,
+        declaration,
+    ))
+}
+
+fn generate_method(
+    ctx: &mut LuaContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+    rust_func_name: &str,
+    lua_name: &str,
+    members: &mut Vec<String>,
+) -> Result<String> {
+    let (convert_args, lua_args) = convert_method_args(ctx, class, method)?;
+    let convert_this = if let MethodVariant::Method(self_variant) = method.variant {
+        let storage_ty = storage_type(class).expect("method without self_type");
+        let (from_ty, to_ty) =
+            create_suitable_types_for_constructor_and_self(self_variant, class, &storage_ty);
+        let from_ty = ctx.conv_map.find_or_alloc_rust_type(&from_ty, class.src_id);
+        let to_ty = ctx.conv_map.find_or_alloc_rust_type(&to_ty, class.src_id);
+        let (mut deps, convert_this) = ctx.conv_map.convert_rust_types(
+            from_ty.to_idx(),
+            to_ty.to_idx(),
+            "this",
+            "this",
+            CONV_FUNC_RET_TYPE,
+            (class.src_id, method.span()),
+        )?;
+        ctx.rust_code.append(&mut deps);
+        format!(
+            "let this: {} = swig_lua_unwrap::<LuaClass>(swig_lua, 1)?;\n{}",
+            DisplayToTokens(&from_ty.ty),
+            convert_this
+        )
+    } else {
+        String::new()
+    };
+    let ret_ty = extract_return_type(&method.fn_decl.output);
+    let (ret_ty, convert_ret, lua_ret) = if method.variant == MethodVariant::Constructor {
+        // constructor returns new userdata, even if `self_type` differs
+        // from type returned by Rust constructor
+        let storage_ty = storage_type(class).expect("constructor without self_type");
+        let call_ret_ty = ctx.conv_map.find_or_alloc_rust_type(&ret_ty, class.src_id);
+        let unwrap_result = if ast::if_result_return_ok_err_types(&call_ret_ty).is_some() {
+            "let swig_ret = match swig_ret { Ok(x) => x, Err(swig_err) => return Err(SwigLuaError(swig_err.to_string())) };\n"
+        } else {
+            ""
+        };
+        (
+            call_ret_ty,
+            format!(
+                "{}let swig_ret: {} = swig_ret;\nSwigLuaObject::<LuaClass>(swig_ret).swig_push_lua(swig_lua)?;\n",
+                unwrap_result,
+                DisplayToTokens(&storage_ty),
+            ),
+            class.name.to_string(),
+        )
+    } else {
+        let ret_ty = ctx.conv_map.find_or_alloc_rust_type(&ret_ty, class.src_id);
+        let conv = rust_to_lua(ctx, &ret_ty, "swig_ret", (class.src_id, method.span()))?;
+        (ret_ty, conv.code, conv.lua_type)
+    };
+    members.push(annotations::function_declaration(
+        &method.doc_comments,
+        method.access != MethodAccess::Public,
+        &format!(
+            "{}{}{}",
+            class.name,
+            if let MethodVariant::Method(_) = method.variant {
+                ":"
+            } else {
+                "."
+            },
+            lua_name
+        ),
+        &lua_args,
+        &lua_ret,
+    ));
+    Ok(format!(
+        r#"
+    unsafe extern "C" fn {func_name}(swig_lua: *mut swig_lua_sys::lua_State) -> std::os::raw::c_int {{
+        swig_lua_call(swig_lua, || {{
+            {convert_args}
+            {convert_this}
+            let swig_ret: {ret_type} = {call};
+            let swig_top = swig_lua_sys::lua_gettop(swig_lua);
+            {convert_ret}
+            Ok(swig_lua_sys::lua_gettop(swig_lua) - swig_top)
+        }})
+    }}
+"#,
+        func_name = rust_func_name,
+        convert_args = convert_args,
+        convert_this = convert_this,
+        ret_type = DisplayToTokens(&ret_ty.ty),
+        call = method.generate_code_to_call_rust_func(),
+        convert_ret = convert_ret,
+    ))
+}
+
+/// Code to convert arguments on Lua stack into arguments of Rust method,
+/// and arguments names with Lua types.
+/// For methods `self` is first argument, because of `object:method(...)` syntax.
+fn convert_method_args(
+    ctx: &mut LuaContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+) -> Result<(String, Vec<(String, String)>)> {
+    let skip_n = match method.variant {
+        MethodVariant::Method(_) => 1,
+        _ => 0,
+    };
+    let mut code = String::new();
+    let mut lua_args = Vec::with_capacity(method.fn_decl.inputs.len());
+    for (i, arg) in method.fn_decl.inputs.iter().enumerate().skip(skip_n) {
+        let named_arg = arg
+            .as_named_arg()
+            .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
+        let arg_ty = ctx
+            .conv_map
+            .find_or_alloc_rust_type(&named_arg.ty, class.src_id);
+        let conv = lua_to_rust(
+            ctx,
+            &arg_ty,
+            &named_arg.name,
+            (class.src_id, named_arg.ty.span()),
+        )?;
+        writeln!(
+            &mut code,
+            "let {}: std::os::raw::c_int = {};\n{}",
+            named_arg.name,
+            i + 1,
+            conv.code
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        lua_args.push((named_arg.name.to_string(), conv.lua_type));
+    }
+    Ok((code, lua_args))
+}
+
+fn generate_enum(
+    ctx: &mut LuaContext,
+    fenum: &ForeignEnumInfo,
+) -> Result<(TokenStream, TokenStream, String)> {
+    let enum_name = fenum.name.to_string();
+    let wrapper_mod_name = lua_wrapper_mod_name(&enum_name);
+    let mut from_u32_arms = String::new();
+    let mut as_u32_arms = String::new();
+    let mut define_items = String::new();
+    // the same values as C++ and Java backends use
+    for (i, item) in fenum.items.iter().enumerate() {
+        let rust_name = DisplayToTokens(&item.rust_name);
+        writeln!(&mut from_u32_arms, "{} => Ok({}),", i, rust_name).expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(&mut as_u32_arms, "{} => {},", rust_name, i).expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(
+            &mut define_items,
+            "swig_lua_sys::lua_pushinteger(swig_lua, {});\nswig_lua_set_field(swig_lua, -2, \"{}\\0\");",
+            i, item.name
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    let code = format!(
+        r#"
+mod {mod_name} {{
+    use super::*;
+
+    pub fn from_u32(value: u32) -> SwigLuaResult<{enum_name}> {{
+        match value {{
+            {from_u32_arms}
+            _ => Err(SwigLuaError(format!("{{}} is not valid value for enum {enum_name}", value))),
+        }}
+    }}
+
+    pub fn as_u32(value: &{enum_name}) -> u32 {{
+        match *value {{
+            {as_u32_arms}
+        }}
+    }}
+
+    /// Register table with values of enum, module table should be on top of stack
+    pub unsafe fn define(swig_lua: *mut swig_lua_sys::lua_State) -> SwigLuaResult<()> {{
+        swig_lua_new_table(swig_lua, 0, {items_len});
+        {define_items}
+        swig_lua_set_field(swig_lua, -2, "{enum_name}\0");
+        Ok(())
+    }}
+}}
+"#,
+        mod_name = wrapper_mod_name,
+        enum_name = enum_name,
+        from_u32_arms = from_u32_arms,
+        as_u32_arms = as_u32_arms,
+        items_len = fenum.items.len(),
+        define_items = define_items,
+    );
+    let declaration = annotations::enum_declaration(ctx, fenum)?;
+    let module_initialization = parse_code(
+        &format!("{}::define(swig_lua)?;", wrapper_mod_name),
+        "lua module initialization",
+    );
+    Ok((
+        parse_code(&code, "lua enum"),
+        module_initialization,
+        declaration,
+    ))
+}
+
+/// Generate Rust struct, that holds Lua object and implements
+/// the callback's trait by calling methods of this object
+fn generate_interface(
+    ctx: &mut LuaContext,
+    interface: &ForeignInterface,
+) -> Result<(TokenStream, TokenStream, String)> {
+    let mut methods_code = String::new();
+    let mut members = Vec::with_capacity(interface.items.len());
+    for method in &interface.items {
+        methods_code.push_str(&generate_interface_method(
+            ctx,
+            interface,
+            method,
+            &mut members,
+        )?);
+    }
+    let code = format!(
+        r#"
+#[allow(unused_variables, unused_mut, unused_unsafe, non_snake_case, clippy::all)]
+mod {mod_name} {{
+    use super::*;
+
+    pub struct LuaCallback(pub SwigLuaCallback);
+
+    impl {trait_name} for LuaCallback {{
+        {methods_code}
+    }}
+}}
+"#,
+        mod_name = lua_wrapper_mod_name(&interface.name.to_string()),
+        trait_name = DisplayToTokens(&interface.self_type.bounds[0]),
+        methods_code = methods_code,
+    );
+    Ok((
+        parse_code(&code, "lua callback"),
+        TokenStream::new(),
+        annotations::interface_declaration(interface, &members),
+    ))
+}
+
+fn generate_interface_method(
+    ctx: &mut LuaContext,
+    interface: &ForeignInterface,
+    method: &ForeignInterfaceMethod,
+    members: &mut Vec<String>,
+) -> Result<String> {
+    let src_id = interface.src_id;
+    let method_span = method.rust_name.span();
+    let rust_method_name = &method
+        .rust_name
+        .segments
+        .last()
+        .ok_or_else(|| DiagnosticError::new(src_id, method_span, "Empty trait function name"))?
+        .ident;
+    let lua_method_name = method.name.to_string();
+    let self_arg = method.fn_decl.inputs[0].as_self_arg(src_id)?;
+
+    let mut args_with_types = Vec::with_capacity(method.fn_decl.inputs.len());
+    let mut convert_args = String::new();
+    let mut lua_args = Vec::with_capacity(method.fn_decl.inputs.len());
+    for arg in method.fn_decl.inputs.iter().skip(1) {
+        let named_arg = arg
+            .as_named_arg()
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+        args_with_types.push(format!(
+            "{}: {}",
+            named_arg.name,
+            DisplayToTokens(&named_arg.ty)
+        ));
+        let arg_ty = ctx.conv_map.find_or_alloc_rust_type(&named_arg.ty, src_id);
+        let conv = rust_to_lua(ctx, &arg_ty, &named_arg.name, (src_id, named_arg.ty.span()))?;
+        convert_args.push_str(&conv.code);
+        lua_args.push((named_arg.name.to_string(), conv.lua_type));
+    }
+
+    let ret_ty = extract_return_type(&method.fn_decl.output);
+    let ok_err_types =
+        ast::if_result_return_ok_err_types(&ctx.conv_map.find_or_alloc_rust_type(&ret_ty, src_id));
+    let ok_ty = match ok_err_types {
+        Some((ref ok_ty, _)) => ok_ty.clone(),
+        None => ret_ty.clone(),
+    };
+    let unit_ty: Type = parse_type! { () };
+    let (convert_ret, lua_ret) = if ok_ty == unit_ty {
+        ("let swig_ret: () = ();".to_string(), "nil".to_string())
+    } else {
+        if let Type::Reference(_) = ok_ty {
+            return Err(DiagnosticError::new(
+                src_id,
+                method_span,
+                "Returning a reference from Lua callback is not supported",
+            ));
+        }
+        let ok_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&ok_ty, src_id);
+        let conv = lua_to_rust(ctx, &ok_ty_rust_ty, "swig_ret", (src_id, ok_ty.span()))?;
+        (conv.code, conv.lua_type)
+    };
+    let error_handling = if ok_err_types.is_some() {
+        // Lua error is converted to the error type of the callback,
+        // so the error type should implement `From<String>`
+        "swig_ret.map_err(|err| From::from(err.to_string()))".to_string()
+    } else if ok_ty == unit_ty {
+        format!(
+            r#"if let Err(err) = swig_ret {{
+                eprintln!("Lua callback {} failed: {{}}", err);
+            }}"#,
+            lua_method_name
+        )
+    } else {
+        format!(
+            r#"swig_ret.unwrap_or_else(|err| panic!("Lua callback {} failed: {{}}", err))"#,
+            lua_method_name
+        )
+    };
+    members.push(annotations::field_declaration(
+        &method.doc_comments,
+        &interface.name.to_string(),
+        &lua_method_name,
+        &lua_args,
+        &lua_ret,
+    ));
+    Ok(format!(
+        r#"
+        fn {rust_method_name}({self_arg}, {args_with_types}) {output} {{
+            let swig_ret: SwigLuaResult<{ok_ty}> = unsafe {{
+                self.0.call_method(
+                    "{lua_method_name}",
+                    |swig_lua| {{
+                        {convert_args}
+                        Ok(())
+                    }},
+                    |swig_lua, swig_ret| {{
+                        {convert_ret}
+                        Ok(swig_ret)
+                    }},
+                )
+            }};
+            {error_handling}
+        }}
+"#,
+        rust_method_name = rust_method_name,
+        self_arg = self_arg,
+        args_with_types = args_with_types.join(", "),
+        output = DisplayToTokens(&method.fn_decl.output),
+        ok_ty = DisplayToTokens(&ok_ty),
+        lua_method_name = lua_method_name,
+        convert_args = convert_args,
+        convert_ret = convert_ret,
+        error_handling = error_handling,
+    ))
+}
+
+fn generate_module_initialization(
+    module_name: &str,
+    module_initialization_code: &[TokenStream],
+) -> TokenStream {
+    // the same name that `require` uses to find open function
+    let open_func = syn::Ident::new(
+        &format!("luaopen_{}", module_name.replace('.', "_")),
+        proc_macro2::Span::call_site(),
+    );
+    let items_len = proc_macro2::Literal::usize_unsuffixed(module_initialization_code.len());
+    quote::quote! {
+        #[no_mangle]
+        pub unsafe extern "C" fn #open_func(
+            swig_lua: *mut swig_lua_sys::lua_State,
+        ) -> std::os::raw::c_int {
+            swig_lua_call(swig_lua, || {
+                swig_lua_new_table(swig_lua, 0, #items_len);
+                #( #module_initialization_code )*
+                Ok(1)
+            })
+        }
+    }
+}
+
+/// Code to convert Lua value with stack index in variable `var`
+/// into Rust value of type `rust_type`
+fn lua_to_rust(
+    ctx: &mut LuaContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<LuaConversion> {
+    let (src_id, span) = arg_ty_span;
+    // `&str` is handled below as reference to `String`
+    let is_str_ref = rust_type.normalized_name == "& str";
+    if let Some(lua_type) = lua_supported_type(rust_type).filter(|_| !is_str_ref) {
+        Ok(LuaConversion {
+            code: format!(
+                "let {var}: {ty} = SwigFromLua::swig_from_lua(swig_lua, {var})?;\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+            ),
+            lua_type: lua_type.into(),
+        })
+    } else if let Some(conv) = if_exported_class_lua_to_rust(ctx, rust_type, var, arg_ty_span)? {
+        Ok(conv)
+    } else if implements(rust_type, ENUM_TRAIT_NAME) {
+        Ok(LuaConversion {
+            code: format!(
+                "let {var}: u32 = SwigFromLua::swig_from_lua(swig_lua, {var})?;\nlet {var}: {ty} = {mod_name}::from_u32({var})?;\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                mod_name = lua_wrapper_mod_name(&rust_type.normalized_name),
+            ),
+            lua_type: rust_type.normalized_name.to_string(),
+        })
+    } else if implements(rust_type, INTERFACE_TRAIT_NAME) {
+        let interface_name = ctx
+            .conv_map
+            .find_foreign_type_related_to_rust_ty(rust_type.to_idx())
+            .map(|ftype| ctx.conv_map[ftype].typename())
+            .ok_or_else(|| {
+                DiagnosticError::new(
+                    src_id,
+                    span,
+                    format!("No callback registered for type: {}", rust_type),
+                )
+            })?;
+        Ok(LuaConversion {
+            code: format!(
+                "let {var}: {ty} = Box::new({mod_name}::LuaCallback(SwigLuaCallback::new(swig_lua, {var}, \"{name}\")?));\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                mod_name = lua_wrapper_mod_name(&interface_name),
+                name = interface_name,
+            ),
+            lua_type: interface_name.to_string(),
+        })
+    } else if let Some(conv) = map_type(ctx, rust_type, Direction::Incoming, arg_ty_span, var)? {
+        Ok(conv)
+    } else if let Some(inner) = ast::if_option_return_some_type(rust_type) {
+        let inner_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&inner, src_id);
+        let inner = lua_to_rust(ctx, &inner_rust_ty, var, arg_ty_span)?;
+        Ok(LuaConversion {
+            code: format!(
+                "let {var}: {ty} = if swig_lua_is_nil(swig_lua, {var}) {{ None }} else {{\n{inner}Some({var}) }};\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                inner = inner.code,
+            ),
+            lua_type: lua_optional_type(&inner.lua_type),
+        })
+    } else if let Some(elem) = if_vec_return_elem_type(rust_type) {
+        let elem = ctx.conv_map.find_or_alloc_rust_type(&elem, src_id);
+        let inner = lua_to_rust(ctx, &elem, var, arg_ty_span)?;
+        Ok(LuaConversion {
+            code: format!(
+                "let {var}: {ty} = swig_lua_table_elements(swig_lua, {var}, |{var}| -> SwigLuaResult<{elem}> {{\n{inner}Ok({var}) }})?;\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                elem = DisplayToTokens(&elem.ty),
+                inner = inner.code,
+            ),
+            lua_type: lua_array_type(&inner.lua_type),
+        })
+    } else if let Some((key_ty, value_ty)) = if_hash_map_return_key_value_types(rust_type) {
+        let key_ty = ctx.conv_map.find_or_alloc_rust_type(&key_ty, src_id);
+        let value_ty = ctx.conv_map.find_or_alloc_rust_type(&value_ty, src_id);
+        let key = lua_to_rust(ctx, &key_ty, "swig_key", arg_ty_span)?;
+        let value = lua_to_rust(ctx, &value_ty, "swig_value", arg_ty_span)?;
+        Ok(LuaConversion {
+            code: format!(
+                "let {var}: {ty} = swig_lua_table_pairs(swig_lua, {var}, |swig_key, swig_value| -> SwigLuaResult<({key_ty}, {value_ty})> {{\n{key}{value}Ok((swig_key, swig_value)) }})?.into_iter().collect();\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                key_ty = DisplayToTokens(&key_ty.ty),
+                value_ty = DisplayToTokens(&value_ty.ty),
+                key = key.code,
+                value = value.code,
+            ),
+            lua_type: format!("table<{}, {}>", key.lua_type, value.lua_type),
+        })
+    } else if let Type::Reference(ref reference) = rust_type.ty {
+        if reference.mutability.is_some() {
+            return Err(DiagnosticError::new(
+                src_id,
+                span,
+                "mutable reference is only supported for exported class types",
+            ));
+        }
+        // `&[T]` is converted as `Vec<T>`, `&str` as `String`
+        let owned_ty = match *reference.elem {
+            Type::Slice(ref slice) => {
+                let elem = &slice.elem;
+                parse_type! { Vec<#elem> }
+            }
+            ref elem if *elem == parse_type! { str } => parse_type! { String },
+            ref elem => elem.clone(),
+        };
+        let owned_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&owned_ty, src_id);
+        let inner = lua_to_rust(ctx, &owned_ty_rust_ty, var, arg_ty_span)?;
+        Ok(LuaConversion {
+            code: format!(
+                "{inner}let {var}: {ty} = &{var};\n",
+                inner = inner.code,
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+            ),
+            lua_type: inner.lua_type,
+        })
+    } else {
+        Err(DiagnosticError::new(
+            src_id,
+            span,
+            format!("Unsupported argument type: {}", rust_type),
+        ))
+    }
+}
+
+/// Code to push Rust value in variable `var` on Lua stack,
+/// nothing is pushed for `()`
+fn rust_to_lua(
+    ctx: &mut LuaContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<LuaConversion> {
+    let (src_id, span) = arg_ty_span;
+    let push_code = format!("{var}.swig_push_lua(swig_lua)?;\n", var = var);
+    if rust_type.ty == parse_type! { () } {
+        Ok(LuaConversion {
+            code: push_code,
+            lua_type: "nil".into(),
+        })
+    } else if let Some(lua_type) = lua_supported_type(rust_type) {
+        Ok(LuaConversion {
+            code: push_code,
+            lua_type: lua_type.into(),
+        })
+    } else if let Some(conv) = if_exported_class_rust_to_lua(ctx, rust_type, var, arg_ty_span)? {
+        Ok(conv)
+    } else if implements(rust_type, ENUM_TRAIT_NAME) {
+        Ok(LuaConversion {
+            code: format!(
+                "{mod_name}::as_u32(&{var}).swig_push_lua(swig_lua)?;\n",
+                var = var,
+                mod_name = lua_wrapper_mod_name(&rust_type.normalized_name),
+            ),
+            lua_type: rust_type.normalized_name.to_string(),
+        })
+    } else if let Some(conv) = map_type(ctx, rust_type, Direction::Outgoing, arg_ty_span, var)? {
+        Ok(conv)
+    } else if let Some(inner) = ast::if_option_return_some_type(rust_type) {
+        let inner_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&inner, src_id);
+        let inner = rust_to_lua(ctx, &inner_rust_ty, var, arg_ty_span)?;
+        Ok(LuaConversion {
+            code: format!(
+                "match {var} {{\nSome({var}) => {{\n{inner}}}\nNone => swig_lua_sys::lua_pushnil(swig_lua),\n}}\n",
+                var = var,
+                inner = inner.code,
+            ),
+            lua_type: lua_optional_type(&inner.lua_type),
+        })
+    } else if let Some(elem) = if_vec_return_elem_type(rust_type) {
+        let elem_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&elem, src_id);
+        let inner = rust_to_lua(ctx, &elem_rust_ty, var, arg_ty_span)?;
+        Ok(LuaConversion {
+            code: format!(
+                "swig_lua_new_table(swig_lua, {var}.len(), 0);\nfor (swig_i, {var}) in {var}.into_iter().enumerate() {{\n{inner}swig_lua_sys::lua_rawseti(swig_lua, -2, swig_i as swig_lua_sys::lua_Integer + 1);\n}}\n",
+                var = var,
+                inner = inner.code,
+            ),
+            lua_type: lua_array_type(&inner.lua_type),
+        })
+    } else if let Some((key_ty, value_ty)) = if_hash_map_return_key_value_types(rust_type) {
+        let key_ty = ctx.conv_map.find_or_alloc_rust_type(&key_ty, src_id);
+        let value_ty = ctx.conv_map.find_or_alloc_rust_type(&value_ty, src_id);
+        let key = rust_to_lua(ctx, &key_ty, "swig_key", arg_ty_span)?;
+        let value = rust_to_lua(ctx, &value_ty, "swig_value", arg_ty_span)?;
+        Ok(LuaConversion {
+            code: format!(
+                "swig_lua_new_table(swig_lua, 0, {var}.len());\nfor (swig_key, swig_value) in {var} {{\n{key}{value}swig_lua_sys::lua_rawset(swig_lua, -3);\n}}\n",
+                var = var,
+                key = key.code,
+                value = value.code,
+            ),
+            lua_type: format!("table<{}, {}>", key.lua_type, value.lua_type),
+        })
+    } else if let Some((ok_ty, _err_ty)) = ast::if_result_return_ok_err_types(rust_type) {
+        let ok_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&ok_ty, src_id);
+        let inner = rust_to_lua(ctx, &ok_ty_rust_ty, var, arg_ty_span)?;
+        Ok(LuaConversion {
+            code: format!(
+                "let {var}: {ok_ty} = match {var} {{\nOk(x) => x,\nErr(swig_err) => return Err(SwigLuaError(swig_err.to_string())),\n}};\n{inner}",
+                var = var,
+                ok_ty = DisplayToTokens(&ok_ty),
+                inner = inner.code,
+            ),
+            lua_type: inner.lua_type,
+        })
+    } else if let Type::Reference(ref reference) = rust_type.ty {
+        // Lua side gets copy of value
+        let (owned_ty, method) = match *reference.elem {
+            Type::Slice(ref slice) => {
+                let elem = &slice.elem;
+                (parse_type! { Vec<#elem> }, "to_vec()")
+            }
+            ref elem => (elem.clone(), "clone()"),
+        };
+        let owned_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&owned_ty, src_id);
+        let inner = rust_to_lua(ctx, &owned_ty_rust_ty, var, arg_ty_span)?;
+        Ok(LuaConversion {
+            code: format!(
+                "let {var}: {ty} = {var}.{method};\n{inner}",
+                var = var,
+                ty = DisplayToTokens(&owned_ty),
+                method = method,
+                inner = inner.code,
+            ),
+            lua_type: inner.lua_type,
+        })
+    } else {
+        Err(DiagnosticError::new(
+            src_id,
+            span,
+            format!("Unsupported return type: {}", rust_type),
+        ))
+    }
+}
+
+/// Types that runtime converts by itself with `SwigFromLua` and `SwigIntoLua`,
+/// returns name of Lua type
+fn lua_supported_type(rust_type: &RustType) -> Option<&'static str> {
+    match rust_type.normalized_name.as_str() {
+        "bool" => Some("boolean"),
+        "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" => {
+            Some("integer")
+        }
+        "f32" | "f64" => Some("number"),
+        "String" | "& str" => Some("string"),
+        _ => None,
+    }
+}
+
+/// Class exported to Lua, with type that is passed to or returned from Rust
+struct ClassUsage {
+    class: ForeignClassInfo,
+    storage_ty: Type,
+    /// `&T` or `&mut T`
+    reference: Option<bool>,
+    /// `T` without reference, it is either self type or storage type
+    unref_ty: RustType,
+}
+
+fn if_exported_class(
+    ctx: &mut LuaContext,
+    rust_type: &RustType,
+    src_id: crate::source_registry::SourceId,
+) -> Option<ClassUsage> {
+    let (reference, unref_ty) = match rust_type.ty {
+        Type::Reference(ref reference) => (
+            Some(reference.mutability.is_some()),
+            ctx.conv_map
+                .find_or_alloc_rust_type(&reference.elem, src_id),
+        ),
+        _ => (None, rust_type.clone()),
+    };
+    let class = ctx
+        .conv_map
+        .find_foreigner_class_with_such_this_type(&unref_ty.ty, |_, fc| {
+            fc.self_desc.as_ref().map(|x| x.self_type.clone())
+        })
+        .or_else(|| {
+            ctx.conv_map
+                .find_foreigner_class_with_such_this_type(&unref_ty.ty, |_, fc| storage_type(fc))
+        })?
+        .clone();
+    let storage_ty = storage_type(&class)?;
+    Some(ClassUsage {
+        class,
+        storage_ty,
+        reference,
+        unref_ty,
+    })
+}
+
+fn if_exported_class_lua_to_rust(
+    ctx: &mut LuaContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<Option<LuaConversion>> {
+    let (src_id, span) = arg_ty_span;
+    let usage = match if_exported_class(ctx, rust_type, src_id) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let class = &usage.class;
+    let storage_ty = &usage.storage_ty;
+    let mutable = usage.reference.unwrap_or(false);
+    let ref_prefix = if mutable { "&mut " } else { "&" };
+    let mut code = format!(
+        "let {var}: {ref_prefix}{storage} = swig_lua_unwrap::<{mod_name}::LuaClass>(swig_lua, {var})?;\n",
+        var = var,
+        ref_prefix = ref_prefix,
+        storage = DisplayToTokens(storage_ty),
+        mod_name = lua_wrapper_mod_name(&class.name.to_string()),
+    );
+    let storage_rust_ty = ctx.conv_map.find_or_alloc_rust_type(storage_ty, src_id);
+    if usage.unref_ty.normalized_name != storage_rust_ty.normalized_name {
+        // `&Rc<RefCell<T>>` -> `&T` and so on
+        let from_ty = ctx.conv_map.find_or_alloc_rust_type(
+            &ast::parse_ty_with_given_span(
+                &format!("{}{}", ref_prefix, DisplayToTokens(storage_ty)),
+                span,
+            )
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?,
+            src_id,
+        );
+        let to_ty = ctx.conv_map.find_or_alloc_rust_type(
+            &ast::parse_ty_with_given_span(
+                &format!("{}{}", ref_prefix, DisplayToTokens(&usage.unref_ty.ty)),
+                span,
+            )
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?,
+            src_id,
+        );
+        let (mut deps, conv_code) = ctx.conv_map.convert_rust_types(
+            from_ty.to_idx(),
+            to_ty.to_idx(),
+            var,
+            var,
+            CONV_FUNC_RET_TYPE,
+            arg_ty_span,
+        )?;
+        ctx.rust_code.append(&mut deps);
+        code.push_str(&conv_code);
+        code.push('\n');
+    }
+    if usage.reference.is_none() {
+        append_clone_if_supported(ctx, &usage, var, arg_ty_span, &mut code)?;
+    }
+    Ok(Some(LuaConversion {
+        code,
+        lua_type: class.name.to_string(),
+    }))
+}
+
+fn if_exported_class_rust_to_lua(
+    ctx: &mut LuaContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<Option<LuaConversion>> {
+    let (src_id, _) = arg_ty_span;
+    let usage = match if_exported_class(ctx, rust_type, src_id) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let mut code = String::new();
+    if usage.reference.is_some() {
+        append_clone_if_supported(ctx, &usage, var, arg_ty_span, &mut code)?;
+    }
+    let storage_rust_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&usage.storage_ty, src_id);
+    if usage.unref_ty.normalized_name != storage_rust_ty.normalized_name {
+        let (mut deps, conv_code) = ctx.conv_map.convert_rust_types(
+            usage.unref_ty.to_idx(),
+            storage_rust_ty.to_idx(),
+            var,
+            var,
+            CONV_FUNC_RET_TYPE,
+            arg_ty_span,
+        )?;
+        ctx.rust_code.append(&mut deps);
+        code.push_str(&conv_code);
+        code.push('\n');
+    }
+    writeln!(
+        &mut code,
+        "SwigLuaObject::<{mod_name}::LuaClass>({var}).swig_push_lua(swig_lua)?;",
+        var = var,
+        mod_name = lua_wrapper_mod_name(&usage.class.name.to_string()),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    Ok(Some(LuaConversion {
+        code,
+        lua_type: usage.class.name.to_string(),
+    }))
+}
+
+/// Userdata owns Rust object, so Rust code can get only copy of it
+fn append_clone_if_supported(
+    ctx: &mut LuaContext,
+    usage: &ClassUsage,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+    code: &mut String,
+) -> Result<()> {
+    let (src_id, span) = arg_ty_span;
+    let is_shared_ptr = ["Rc", "Arc"].iter().any(|smart_ptr| {
+        ast::check_if_smart_pointer_return_inner_type(&usage.unref_ty, smart_ptr).is_some()
+    });
+    if !is_shared_ptr && !usage.class.clone_derived() && !usage.class.copy_derived() {
+        return Err(DiagnosticError::new(
+            src_id,
+            span,
+            format!(
+                "Passing object of class {} by value requires that it is marked with \
+                 `#[derive(Clone)]` or `#[derive(Copy)]` inside its `foreign_class` macro",
+                usage.class.name
+            ),
+        ));
+    }
+    let unref_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&usage.unref_ty.ty, src_id);
+    writeln!(
+        code,
+        "let {var}: {ty} = {var}.clone();",
+        var = var,
+        ty = DisplayToTokens(&unref_ty.ty),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    Ok(())
+}
+
+/// Conversions of Rust types described by `foreign_typemap!` rules:
+/// Rust type is converted to the type mentioned in rule, and then to Lua.
+/// Only not generic rules are supported.
+fn map_type(
+    ctx: &mut LuaContext,
+    rust_type: &RustType,
+    direction: Direction,
+    arg_ty_span: SourceIdSpan,
+    var: &str,
+) -> Result<Option<LuaConversion>> {
+    let ftype_idx = match ctx.conv_map.map_through_conversation_to_foreign(
+        rust_type.to_idx(),
+        direction,
+        MapToForeignFlag::FastSearch,
+        arg_ty_span,
+        |_, fc| storage_type(fc),
+    ) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let ftype = &ctx.conv_map[ftype_idx];
+    let rule = match direction {
+        Direction::Outgoing => ftype.into_from_rust.as_ref(),
+        Direction::Incoming => ftype.from_into_rust.as_ref(),
+    }
+    .expect("Internal error: foreign type was found without conversion rule");
+    if rule.intermediate.is_some() {
+        return Err(DiagnosticError::new2(
+            ftype.src_id_span(),
+            format!(
+                "f_type {}: conversion code on foreign side is not supported for Lua",
+                ftype.name
+            ),
+        ));
+    }
+    let lua_type = ftype.typename().to_string();
+    let lua_rust_ty = rule.rust_ty;
+    if lua_rust_ty == rust_type.to_idx() {
+        return Ok(None);
+    }
+    let (from, to) = match direction {
+        Direction::Outgoing => (rust_type.to_idx(), lua_rust_ty),
+        Direction::Incoming => (lua_rust_ty, rust_type.to_idx()),
+    };
+    let (mut deps, conv_code) =
+        ctx.conv_map
+            .convert_rust_types(from, to, var, var, CONV_FUNC_RET_TYPE, arg_ty_span)?;
+    ctx.rust_code.append(&mut deps);
+    let lua_rust_ty = ctx.conv_map[lua_rust_ty].clone();
+    let code = match direction {
+        Direction::Outgoing => {
+            let conv = rust_to_lua(ctx, &lua_rust_ty, var, arg_ty_span)?;
+            format!("{}\n{}", conv_code, conv.code)
+        }
+        Direction::Incoming => {
+            let conv = lua_to_rust(ctx, &lua_rust_ty, var, arg_ty_span)?;
+            format!("{}{}\n", conv.code, conv_code)
+        }
+    };
+    Ok(Some(LuaConversion { code, lua_type }))
+}
+
+/// Type of Rust object, that userdata holds
+fn storage_type(class: &ForeignClassInfo) -> Option<Type> {
+    class.self_desc.as_ref().map(|x| {
+        ast::if_ty_result_return_ok_type(&x.constructor_ret_type)
+            .unwrap_or_else(|| x.constructor_ret_type.clone())
+    })
+}
+
+fn implements(rust_type: &RustType, trait_name: &str) -> bool {
+    let trait_path: syn::Path = syn::Ident::new(trait_name, proc_macro2::Span::call_site()).into();
+    rust_type.implements.contains_path(&trait_path)
+}
+
+fn lua_optional_type(inner: &str) -> String {
+    if inner.contains(' ') || inner.contains('|') {
+        format!("({})?", inner)
+    } else {
+        format!("{}?", inner)
+    }
+}
+
+fn lua_array_type(elem: &str) -> String {
+    if elem.contains(' ') || elem.contains('|') || elem.ends_with('?') {
+        format!("({})[]", elem)
+    } else {
+        format!("{}[]", elem)
+    }
+}
+
+fn if_vec_return_elem_type(ty: &RustType) -> Option<Type> {
+    ast::check_if_smart_pointer_return_inner_type(ty, "Vec")
+}
+
+/// `HashMap<K, V>` is converted to Lua table with the same keys
+fn if_hash_map_return_key_value_types(ty: &RustType) -> Option<(Type, Type)> {
+    let path = match ty.ty {
+        Type::Path(ref type_path) if type_path.qself.is_none() => &type_path.path,
+        _ => return None,
+    };
+    let last = path.segments.last()?;
+    if last.ident != "HashMap" {
+        return None;
+    }
+    let args = match last.arguments {
+        syn::PathArguments::AngleBracketed(ref args) => &args.args,
+        _ => return None,
+    };
+    let mut types = args.iter().filter_map(|arg| match arg {
+        syn::GenericArgument::Type(ref ty) => Some(ty.clone()),
+        _ => None,
+    });
+    match (types.next(), types.next(), types.next()) {
+        (Some(key), Some(value), None) => Some((key, value)),
+        _ => None,
+    }
+}
+
+fn extract_return_type(syn_return_type: &syn::ReturnType) -> Type {
+    match syn_return_type {
+        syn::ReturnType::Default => {
+            parse_type! { () }
+        }
+        syn::ReturnType::Type(_, ref ty) => ty.deref().clone(),
+    }
+}
+
+fn boxed_interface_type(interface: &ForeignInterface) -> Result<Type> {
+    let boxed_trait_name = format!("Box<dyn {}>", DisplayToTokens(&interface.self_type));
+    ast::parse_ty_with_given_span(&boxed_trait_name, interface.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(interface.src_id, err))
+}
+
+fn lua_wrapper_mod_name(type_name: &str) -> String {
+    format!("lua_{}", type_name.to_snake_case())
+}
+
+fn parse_code(code: &str, id_of_code: &str) -> TokenStream {
+    syn::parse_str(code).unwrap_or_else(|err| panic_on_syn_error(id_of_code, code.to_string(), err))
+}
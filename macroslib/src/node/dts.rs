@@ -0,0 +1,186 @@
+//! Generation of `.d.ts` file, so TypeScript compiler and IDEs
+//! know signatures of the generated Node.js module
+
+use super::*;
+use crate::{
+    extension::{extend_foreign_class, extend_foreign_enum},
+    file_cache::FileWriteCache,
+    KNOWN_CLASS_DERIVES,
+};
+use std::io::Write as IoWrite;
+
+/// Words that can not be used as names of parameters
+const TS_RESERVED_WORDS: &[&str] = &[
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "new",
+    "null",
+    "return",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+];
+
+pub(in crate::node) fn write_declarations(
+    ctx: &mut NodeContext,
+    declarations: &[String],
+) -> Result<()> {
+    let dts_path = ctx
+        .cfg
+        .output_dir
+        .join(format!("{}.d.ts", ctx.cfg.module_name));
+    let mut dts_file = FileWriteCache::new(&dts_path, &mut ctx.generated_foreign_files);
+    writeln!(&mut dts_file, "// Automatically generated by flapigen")
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    for declaration in declarations {
+        writeln!(&mut dts_file).expect(WRITE_TO_MEM_FAILED_MSG);
+        dts_file
+            .write_all(declaration.as_bytes())
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    dts_file.update_file_if_necessary().map_err(|err| {
+        DiagnosticError::map_any_err_to_our_err(format!(
+            "write to {} failed: {}",
+            dts_path.display(),
+            err
+        ))
+    })
+}
+
+pub(in crate::node) fn class_declaration(
+    ctx: &NodeContext,
+    class: &ForeignClassInfo,
+    members: &[String],
+) -> Result<String> {
+    let mut out = String::new();
+    write_doc_comments(&mut out, "", &class.doc_comments);
+    writeln!(&mut out, "export declare class {} {{", class.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    if !members.iter().any(|x| x.contains("constructor(")) {
+        // JS class can be created only by Rust code
+        out.push_str("    private constructor();\n");
+    }
+    for member in members {
+        out.push_str(member);
+    }
+    if !class.foreign_code.is_empty() {
+        writeln!(&mut out, "{}", class.foreign_code).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    out.push_str("}\n");
+    let mut cnt = out.into_bytes();
+    extend_foreign_class(
+        class,
+        &mut cnt,
+        &KNOWN_CLASS_DERIVES,
+        &[],
+        ctx.class_ext_handlers,
+        ctx.method_ext_handlers,
+    )?;
+    String::from_utf8(cnt).map_err(DiagnosticError::map_any_err_to_our_err)
+}
+
+pub(in crate::node) fn enum_declaration(
+    ctx: &NodeContext,
+    fenum: &ForeignEnumInfo,
+) -> Result<String> {
+    let mut out = String::new();
+    write_doc_comments(&mut out, "", &fenum.doc_comments);
+    writeln!(&mut out, "export declare enum {} {{", fenum.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    for (i, item) in fenum.items.iter().enumerate() {
+        write_doc_comments(&mut out, "    ", &item.doc_comments);
+        writeln!(&mut out, "    {} = {},", item.name, i).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    out.push_str("}\n");
+    let mut cnt = out.into_bytes();
+    extend_foreign_enum(fenum, &mut cnt, ctx.enum_ext_handlers)?;
+    String::from_utf8(cnt).map_err(DiagnosticError::map_any_err_to_our_err)
+}
+
+pub(in crate::node) fn interface_declaration(
+    interface: &ForeignInterface,
+    members: &[String],
+) -> String {
+    let mut out = String::new();
+    write_doc_comments(&mut out, "", &interface.doc_comments);
+    writeln!(&mut out, "export interface {} {{", interface.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    for member in members {
+        out.push_str(member);
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Declaration of class member or interface method,
+/// without return type for constructor
+pub(in crate::node) fn method_declaration(
+    doc_comments: &[String],
+    modifiers: &str,
+    name: &str,
+    args: &[(String, String)],
+    ret_type: Option<&str>,
+) -> String {
+    let mut out = String::new();
+    write_doc_comments(&mut out, "    ", doc_comments);
+    let args = args
+        .iter()
+        .map(|(name, ts_type)| format!("{}: {}", ts_ident(name), ts_type))
+        .collect::<Vec<_>>();
+    write!(&mut out, "    {}{}({})", modifiers, name, args.join(", "))
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    if let Some(ret_type) = ret_type {
+        write!(&mut out, ": {}", ret_type).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    out.push_str(";\n");
+    out
+}
+
+fn ts_ident(name: &str) -> String {
+    if TS_RESERVED_WORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn write_doc_comments(out: &mut String, indent: &str, doc_comments: &[String]) {
+    if doc_comments.is_empty() {
+        return;
+    }
+    writeln!(out, "{}/**", indent).expect(WRITE_TO_MEM_FAILED_MSG);
+    for comment in doc_comments {
+        let comment = comment.trim();
+        if comment.is_empty() {
+            writeln!(out, "{} *", indent).expect(WRITE_TO_MEM_FAILED_MSG);
+        } else {
+            writeln!(out, "{} * {}", indent, comment).expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+    }
+    writeln!(out, "{} */", indent).expect(WRITE_TO_MEM_FAILED_MSG);
+}
@@ -0,0 +1,1291 @@
+mod dts;
+
+use std::{fmt::Write, ops::Deref, path::PathBuf};
+
+use heck::SnakeCase;
+use petgraph::Direction;
+use proc_macro2::TokenStream;
+use rustc_hash::FxHashSet;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    error::{panic_on_syn_error, DiagnosticError, Result, SourceIdSpan},
+    extension::{ClassExtHandlers, EnumExtHandlers, ExtHandlers, MethodExtHandlers},
+    typemap::{
+        ast::{self, DisplayToTokens, TypeName},
+        ty::{ForeignConversationRule, ForeignTypeS, RustType},
+        utils::{create_suitable_types_for_constructor_and_self, remove_files_if},
+        MapToForeignFlag,
+    },
+    types::{
+        ForeignClassInfo, ForeignEnumInfo, ForeignInterface, ForeignInterfaceMethod, ForeignMethod,
+        ItemToExpand, MethodAccess, MethodVariant,
+    },
+    LanguageGenerator, NodeConfig, SourceCode, TypeMap, WRITE_TO_MEM_FAILED_MSG,
+};
+
+const ENUM_TRAIT_NAME: &str = "SwigForeignEnum";
+const INTERFACE_TRAIT_NAME: &str = "SwigForeignInterface";
+/// Return type of closure, that generated conversion code is placed into
+const CONV_FUNC_RET_TYPE: &str = "SwigNodeResult<napi_sys::napi_value>";
+
+struct NodeContext<'a> {
+    cfg: &'a NodeConfig,
+    conv_map: &'a mut TypeMap,
+    rust_code: Vec<TokenStream>,
+    generated_foreign_files: FxHashSet<PathBuf>,
+    class_ext_handlers: &'a ClassExtHandlers,
+    method_ext_handlers: &'a MethodExtHandlers,
+    enum_ext_handlers: &'a EnumExtHandlers,
+}
+
+/// Conversion of value between Rust and JS
+struct NodeConversion {
+    /// Statements, that convert variable into new variable with the same name
+    code: String,
+    /// TypeScript type of JS value for `.d.ts` file
+    ts_type: String,
+}
+
+impl LanguageGenerator for NodeConfig {
+    fn expand_items(
+        &self,
+        conv_map: &mut TypeMap,
+        _pointer_target_width: usize,
+        _code: &[SourceCode],
+        items: Vec<ItemToExpand>,
+        remove_not_generated_files: bool,
+        ext_handlers: ExtHandlers,
+    ) -> Result<Vec<TokenStream>> {
+        if !(self.output_dir.exists() && self.output_dir.is_dir()) {
+            return Err(DiagnosticError::map_any_err_to_our_err(format!(
+                "Path {} not exists or not directory",
+                self.output_dir.display()
+            )));
+        }
+        if let Some(rule) = conv_map.take_not_merged_not_generic_rules().first() {
+            return Err(DiagnosticError::new(
+                rule.src_id,
+                rule.span,
+                "foreign_typemap! rule with code or options for foreign side is not supported for Node.js",
+            ));
+        }
+        let mut ctx = NodeContext {
+            cfg: self,
+            conv_map,
+            rust_code: vec![],
+            generated_foreign_files: FxHashSet::default(),
+            class_ext_handlers: ext_handlers.class_ext_handlers,
+            method_ext_handlers: ext_handlers.method_ext_handlers,
+            enum_ext_handlers: ext_handlers.enum_ext_handlers,
+        };
+        for item in &items {
+            match item {
+                ItemToExpand::Class(ref fclass) => register_class(&mut ctx, fclass),
+                ItemToExpand::Enum(ref fenum) => register_enum(&mut ctx, fenum)?,
+                ItemToExpand::Interface(ref finterface) => {
+                    register_interface(&mut ctx, finterface)?
+                }
+            }
+        }
+        let mut code = Vec::with_capacity(items.len());
+        let mut module_initialization = Vec::with_capacity(items.len());
+        let mut declarations = Vec::with_capacity(items.len());
+        for item in &items {
+            let (item_code, initialization, declaration) = match item {
+                ItemToExpand::Class(ref fclass) => generate_class(&mut ctx, fclass)?,
+                ItemToExpand::Enum(ref fenum) => generate_enum(&mut ctx, fenum)?,
+                ItemToExpand::Interface(ref finterface) => {
+                    generate_interface(&mut ctx, finterface)?
+                }
+            };
+            code.push(item_code);
+            module_initialization.push(initialization);
+            declarations.push(declaration);
+        }
+        code.push(generate_module_initialization(&module_initialization));
+        dts::write_declarations(&mut ctx, &declarations)?;
+        if remove_not_generated_files {
+            let generated_foreign_files = &ctx.generated_foreign_files;
+            remove_files_if(&self.output_dir, |path| {
+                if let Some(name) = path.file_name().and_then(|x| x.to_str()) {
+                    if name.ends_with(".d.ts") && !generated_foreign_files.contains(path) {
+                        return true;
+                    }
+                }
+                false
+            })
+            .map_err(DiagnosticError::map_any_err_to_our_err)?;
+        }
+        code.append(&mut ctx.rust_code);
+        Ok(code)
+    }
+}
+
+fn register_class(ctx: &mut NodeContext, class: &ForeignClassInfo) {
+    if let Some(ref self_desc) = class.self_desc {
+        ctx.conv_map
+            .find_or_alloc_rust_type(&self_desc.self_type, class.src_id);
+        ctx.conv_map
+            .find_or_alloc_rust_type(&self_desc.constructor_ret_type, class.src_id);
+    }
+}
+
+fn register_enum(ctx: &mut NodeContext, fenum: &ForeignEnumInfo) -> Result<()> {
+    let enum_ty = ast::parse_ty_with_given_span(&fenum.name.to_string(), fenum.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(fenum.src_id, err))?;
+    ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &enum_ty,
+        &[ENUM_TRAIT_NAME],
+        fenum.src_id,
+    );
+    Ok(())
+}
+
+fn register_interface(ctx: &mut NodeContext, interface: &ForeignInterface) -> Result<()> {
+    let boxed_trait_ty = boxed_interface_type(interface)?;
+    let boxed_trait_rust_ty = ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &boxed_trait_ty,
+        &[INTERFACE_TRAIT_NAME],
+        interface.src_id,
+    );
+    let rule = ForeignConversationRule {
+        rust_ty: boxed_trait_rust_ty.to_idx(),
+        intermediate: None,
+    };
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(interface.name.to_string(), interface.src_id_span()),
+        provides_by_module: vec![],
+        into_from_rust: Some(rule.clone()),
+        from_into_rust: Some(rule),
+        name_prefix: None,
+    })?;
+    Ok(())
+}
+
+/// Generate module with JS class, code to define class during module
+/// initialization and TypeScript declaration of class
+fn generate_class(
+    ctx: &mut NodeContext,
+    class: &ForeignClassInfo,
+) -> Result<(TokenStream, TokenStream, String)> {
+    let class_name = class.name.to_string();
+    let wrapper_mod_name = node_wrapper_mod_name(&class_name);
+    let storage_ty = storage_type(class);
+    let mut constructors = class
+        .methods
+        .iter()
+        .filter(|m| m.variant == MethodVariant::Constructor && !m.is_dummy_constructor());
+    let constructor = constructors.next();
+    if let Some(second) = constructors.next() {
+        return Err(DiagnosticError::new(
+            class.src_id,
+            second.span(),
+            "JS class can have only one constructor, use static method instead",
+        ));
+    }
+    if storage_ty.is_none()
+        && class
+            .methods
+            .iter()
+            .any(|m| m.variant != MethodVariant::StaticMethod)
+    {
+        return Err(DiagnosticError::new(
+            class.src_id,
+            class.span(),
+            format!(
+                "Class {} has non-static methods, but no self_type",
+                class.name
+            ),
+        ));
+    }
+
+    let mut code = format!(
+        r#"
+#[allow(unused_variables, unused_mut, unused_unsafe, non_snake_case, clippy::all)]
+mod {mod_name} {{
+    use super::*;
+
+    /// Marker of JS class {class_name}
+    pub struct JsClass;
+
+    thread_local! {{
+        static CLASS: std::cell::Cell<napi_sys::napi_ref> = std::cell::Cell::new(std::ptr::null_mut());
+    }}
+
+    impl SwigNodeClass for JsClass {{
+        type Storage = {storage};
+        const NAME: &'static str = "{class_name}";
+        unsafe fn swig_js_class(swig_env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value> {{
+            swig_node_get_reference(swig_env, CLASS.with(|x| x.get()))
+        }}
+    }}
+"#,
+        mod_name = wrapper_mod_name,
+        class_name = class_name,
+        storage = storage_ty
+            .as_ref()
+            .map(|ty| DisplayToTokens(ty).to_string())
+            .unwrap_or_else(|| "()".into()),
+    );
+    let mut members = Vec::with_capacity(class.methods.len());
+    code.push_str(&generate_constructor(
+        ctx,
+        class,
+        constructor,
+        &mut members,
+    )?);
+
+    let mut js_names = FxHashSet::default();
+    let mut properties = Vec::with_capacity(class.methods.len());
+    for method in &class.methods {
+        if method.variant == MethodVariant::Constructor {
+            continue;
+        }
+        let is_static = method.variant == MethodVariant::StaticMethod;
+        let js_name = method.short_name();
+        if !js_names.insert((js_name.clone(), is_static)) {
+            return Err(DiagnosticError::new(
+                class.src_id,
+                method.span(),
+                format!(
+                    "JS class {} already has method {}, use alias to give other name",
+                    class.name, js_name
+                ),
+            ));
+        }
+        let rust_func_name = if is_static {
+            format!("js_static_{}", js_name)
+        } else {
+            format!("js_{}", js_name)
+        };
+        code.push_str(&generate_method(
+            ctx,
+            class,
+            method,
+            &rust_func_name,
+            &js_name,
+            &mut members,
+        )?);
+        properties.push(format!(
+            "swig_node_method(\"{}\\0\", Some({}), {}),",
+            js_name, rust_func_name, is_static
+        ));
+    }
+    write!(
+        &mut code,
+        r#"
+    pub unsafe fn define(swig_env: napi_sys::napi_env, swig_exports: napi_sys::napi_value) -> SwigNodeResult<()> {{
+        let properties: [napi_sys::napi_property_descriptor; {properties_len}] = [
+            {properties}
+        ];
+        let mut class = std::ptr::null_mut();
+        swig_napi_check(swig_env, napi_sys::napi_define_class(
+            swig_env,
+            "{class_name}".as_ptr() as *const std::os::raw::c_char,
+            {class_name_len},
+            Some(constructor),
+            std::ptr::null_mut(),
+            properties.len(),
+            properties.as_ptr(),
+            &mut class,
+        ))?;
+        let mut class_ref = std::ptr::null_mut();
+        swig_napi_check(swig_env, napi_sys::napi_create_reference(swig_env, class, 1, &mut class_ref))?;
+        CLASS.with(|x| x.set(class_ref));
+        swig_node_set_named_property(swig_env, swig_exports, "{class_name}", class)
+    }}
+}}
+"#,
+        properties_len = properties.len(),
+        properties = properties.join("\n            "),
+        class_name = class_name,
+        class_name_len = class_name.len(),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    let declaration = dts::class_declaration(ctx, class, &members)?;
+    let module_initialization = parse_code(
+        &format!("{}::define(swig_env, swig_exports)?;", wrapper_mod_name),
+        "node module initialization",
+    );
+    Ok((
+        parse_code(&code, "node class"),
+        module_initialization,
+        declaration,
+    ))
+}
+
+/// JS constructor, it either wraps object returned from Rust,
+/// or creates new Rust object
+fn generate_constructor(
+    ctx: &mut NodeContext,
+    class: &ForeignClassInfo,
+    constructor: Option<&ForeignMethod>,
+    members: &mut Vec<String>,
+) -> Result<String> {
+    let (argc, create_object) = match constructor {
+        Some(method) => {
+            let storage_ty = storage_type(class).expect("constructor without self_type");
+            let (convert_args, ts_args) = convert_method_args(ctx, class, method)?;
+            let ret_ty = extract_return_type(&method.fn_decl.output);
+            let call = method.generate_code_to_call_rust_func();
+            let call = if ast::if_ty_result_return_ok_type(&ret_ty).is_some() {
+                format!(
+                    "match {} {{ Ok(x) => x, Err(swig_err) => return Err(SwigNodeError::Message(swig_err.to_string())) }}",
+                    call
+                )
+            } else {
+                call
+            };
+            members.push(dts::method_declaration(
+                &method.doc_comments,
+                "",
+                "constructor",
+                &ts_args,
+                None,
+            ));
+            (
+                ts_args.len(),
+                format!(
+                    r#"{convert_args}
+                let this: {storage} = {call};
+                swig_node_wrap::<JsClass>(swig_env, swig_this, Box::new(this))"#,
+                    convert_args = convert_args,
+                    storage = DisplayToTokens(&storage_ty),
+                    call = call,
+                ),
+            )
+        }
+        None => (
+            0,
+            format!(
+                "Err(SwigNodeError::Message(\"{} can not be constructed from JS\".to_string()))",
+                class.name
+            ),
+        ),
+    };
+    Ok(format!(
+        r#"
+    extern "C" fn constructor(swig_env: napi_sys::napi_env, swig_info: napi_sys::napi_callback_info) -> napi_sys::napi_value {{
+        unsafe {{
+            swig_node_call(swig_env, || {{
+                let (swig_this, swig_args) = swig_node_get_args(swig_env, swig_info, {argc})?;
+                if let Some(obj) = swig_node_take_object_to_wrap::<JsClass>() {{
+                    return swig_node_wrap::<JsClass>(swig_env, swig_this, obj);
+                }}
+                {create_object}
+            }})
+        }}
+    }}
+"#,
+        argc = argc,
+        create_object = create_object,
+    ))
+}
+
+fn generate_method(
+    ctx: &mut NodeContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+    rust_func_name: &str,
+    js_name: &str,
+    members: &mut Vec<String>,
+) -> Result<String> {
+    let (convert_args, ts_args) = convert_method_args(ctx, class, method)?;
+    let convert_this = if let MethodVariant::Method(self_variant) = method.variant {
+        let storage_ty = storage_type(class).expect("method without self_type");
+        let (from_ty, to_ty) =
+            create_suitable_types_for_constructor_and_self(self_variant, class, &storage_ty);
+        let from_ty = ctx.conv_map.find_or_alloc_rust_type(&from_ty, class.src_id);
+        let to_ty = ctx.conv_map.find_or_alloc_rust_type(&to_ty, class.src_id);
+        let (mut deps, convert_this) = ctx.conv_map.convert_rust_types(
+            from_ty.to_idx(),
+            to_ty.to_idx(),
+            "this",
+            "this",
+            CONV_FUNC_RET_TYPE,
+            (class.src_id, method.span()),
+        )?;
+        ctx.rust_code.append(&mut deps);
+        format!(
+            "let this: {} = swig_node_unwrap::<JsClass>(swig_env, swig_this)?;\n{}",
+            DisplayToTokens(&from_ty.ty),
+            convert_this
+        )
+    } else {
+        String::new()
+    };
+    let ret_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&extract_return_type(&method.fn_decl.output), class.src_id);
+    let convert_ret = rust_to_js(ctx, &ret_ty, "swig_ret", (class.src_id, method.span()))?;
+    let modifiers = match (method.access, method.variant) {
+        (MethodAccess::Public, MethodVariant::StaticMethod) => "static ",
+        (MethodAccess::Public, _) => "",
+        (_, MethodVariant::StaticMethod) => "private static ",
+        (_, _) => "private ",
+    };
+    members.push(dts::method_declaration(
+        &method.doc_comments,
+        modifiers,
+        js_name,
+        &ts_args,
+        Some(&convert_ret.ts_type),
+    ));
+    Ok(format!(
+        r#"
+    extern "C" fn {func_name}(swig_env: napi_sys::napi_env, swig_info: napi_sys::napi_callback_info) -> napi_sys::napi_value {{
+        unsafe {{
+            swig_node_call(swig_env, || {{
+                let (swig_this, swig_args) = swig_node_get_args(swig_env, swig_info, {argc})?;
+                {convert_args}
+                {convert_this}
+                let swig_ret: {ret_type} = {call};
+                {convert_ret}
+                Ok(swig_ret)
+            }})
+        }}
+    }}
+"#,
+        func_name = rust_func_name,
+        argc = ts_args.len(),
+        convert_args = convert_args,
+        convert_this = convert_this,
+        ret_type = DisplayToTokens(&ret_ty.ty),
+        call = method.generate_code_to_call_rust_func(),
+        convert_ret = convert_ret.code,
+    ))
+}
+
+/// Code to convert `swig_args` into arguments of Rust method,
+/// and arguments names with TypeScript types
+fn convert_method_args(
+    ctx: &mut NodeContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+) -> Result<(String, Vec<(String, String)>)> {
+    let skip_n = match method.variant {
+        MethodVariant::Method(_) => 1,
+        _ => 0,
+    };
+    let mut code = String::new();
+    let mut ts_args = Vec::with_capacity(method.fn_decl.inputs.len());
+    for (i, arg) in method.fn_decl.inputs.iter().skip(skip_n).enumerate() {
+        let named_arg = arg
+            .as_named_arg()
+            .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
+        let arg_ty = ctx
+            .conv_map
+            .find_or_alloc_rust_type(&named_arg.ty, class.src_id);
+        let conv = js_to_rust(
+            ctx,
+            &arg_ty,
+            &named_arg.name,
+            (class.src_id, named_arg.ty.span()),
+        )?;
+        writeln!(
+            &mut code,
+            "let {} = swig_args[{}];\n{}",
+            named_arg.name, i, conv.code
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        ts_args.push((named_arg.name.to_string(), conv.ts_type));
+    }
+    Ok((code, ts_args))
+}
+
+fn generate_enum(
+    ctx: &mut NodeContext,
+    fenum: &ForeignEnumInfo,
+) -> Result<(TokenStream, TokenStream, String)> {
+    let enum_name = fenum.name.to_string();
+    let wrapper_mod_name = node_wrapper_mod_name(&enum_name);
+    let mut from_u32_arms = String::new();
+    let mut as_u32_arms = String::new();
+    let mut define_items = String::new();
+    // the same values as C++ and Java backends use
+    for (i, item) in fenum.items.iter().enumerate() {
+        let rust_name = DisplayToTokens(&item.rust_name);
+        writeln!(&mut from_u32_arms, "{} => Ok({}),", i, rust_name).expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(&mut as_u32_arms, "{} => {},", rust_name, i).expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(
+            &mut define_items,
+            "swig_node_set_named_property(swig_env, object, \"{}\", {}u32.swig_into_js(swig_env)?)?;",
+            item.name, i
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    let code = format!(
+        r#"
+mod {mod_name} {{
+    use super::*;
+
+    pub fn from_u32(value: u32) -> SwigNodeResult<{enum_name}> {{
+        match value {{
+            {from_u32_arms}
+            _ => Err(SwigNodeError::Message(format!("{{}} is not valid value for enum {enum_name}", value))),
+        }}
+    }}
+
+    pub fn as_u32(value: &{enum_name}) -> u32 {{
+        match *value {{
+            {as_u32_arms}
+        }}
+    }}
+
+    pub unsafe fn define(swig_env: napi_sys::napi_env, swig_exports: napi_sys::napi_value) -> SwigNodeResult<()> {{
+        let mut object = std::ptr::null_mut();
+        swig_napi_check(swig_env, napi_sys::napi_create_object(swig_env, &mut object))?;
+        {define_items}
+        swig_napi_check(swig_env, napi_sys::napi_object_freeze(swig_env, object))?;
+        swig_node_set_named_property(swig_env, swig_exports, "{enum_name}", object)
+    }}
+}}
+"#,
+        mod_name = wrapper_mod_name,
+        enum_name = enum_name,
+        from_u32_arms = from_u32_arms,
+        as_u32_arms = as_u32_arms,
+        define_items = define_items,
+    );
+    let declaration = dts::enum_declaration(ctx, fenum)?;
+    let module_initialization = parse_code(
+        &format!("{}::define(swig_env, swig_exports)?;", wrapper_mod_name),
+        "node module initialization",
+    );
+    Ok((
+        parse_code(&code, "node enum"),
+        module_initialization,
+        declaration,
+    ))
+}
+
+/// Generate Rust struct, that holds JS object and implements
+/// the callback's trait by calling methods of this object
+fn generate_interface(
+    ctx: &mut NodeContext,
+    interface: &ForeignInterface,
+) -> Result<(TokenStream, TokenStream, String)> {
+    let mut methods_code = String::new();
+    let mut members = Vec::with_capacity(interface.items.len());
+    for method in &interface.items {
+        methods_code.push_str(&generate_interface_method(
+            ctx,
+            interface,
+            method,
+            &mut members,
+        )?);
+    }
+    let code = format!(
+        r#"
+#[allow(unused_variables, unused_mut, unused_unsafe, non_snake_case, clippy::all)]
+mod {mod_name} {{
+    use super::*;
+
+    pub struct JsCallback(pub SwigNodeCallback);
+
+    impl {trait_name} for JsCallback {{
+        {methods_code}
+    }}
+}}
+"#,
+        mod_name = node_wrapper_mod_name(&interface.name.to_string()),
+        trait_name = DisplayToTokens(&interface.self_type.bounds[0]),
+        methods_code = methods_code,
+    );
+    Ok((
+        parse_code(&code, "node callback"),
+        TokenStream::new(),
+        dts::interface_declaration(interface, &members),
+    ))
+}
+
+fn generate_interface_method(
+    ctx: &mut NodeContext,
+    interface: &ForeignInterface,
+    method: &ForeignInterfaceMethod,
+    members: &mut Vec<String>,
+) -> Result<String> {
+    let src_id = interface.src_id;
+    let method_span = method.rust_name.span();
+    let rust_method_name = &method
+        .rust_name
+        .segments
+        .last()
+        .ok_or_else(|| DiagnosticError::new(src_id, method_span, "Empty trait function name"))?
+        .ident;
+    let js_method_name = method.name.to_string();
+    let self_arg = method.fn_decl.inputs[0].as_self_arg(src_id)?;
+
+    let mut args_with_types = Vec::with_capacity(method.fn_decl.inputs.len());
+    let mut to_owned_code = String::new();
+    let mut convert_args = String::new();
+    let mut js_args = Vec::with_capacity(method.fn_decl.inputs.len());
+    let mut ts_args = Vec::with_capacity(method.fn_decl.inputs.len());
+    for arg in method.fn_decl.inputs.iter().skip(1) {
+        let named_arg = arg
+            .as_named_arg()
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+        args_with_types.push(format!(
+            "{}: {}",
+            named_arg.name,
+            DisplayToTokens(&named_arg.ty)
+        ));
+        // arguments are moved into JS thread, so they should not borrow anything
+        let owned_ty = match callback_arg_to_owned(&named_arg.ty) {
+            Some((owned_ty, method)) => {
+                writeln!(
+                    &mut to_owned_code,
+                    "let {name}: {ty} = {name}.{method};",
+                    name = named_arg.name,
+                    ty = DisplayToTokens(&owned_ty),
+                    method = method,
+                )
+                .expect(WRITE_TO_MEM_FAILED_MSG);
+                owned_ty
+            }
+            None => named_arg.ty.clone(),
+        };
+        let owned_ty = ctx.conv_map.find_or_alloc_rust_type(&owned_ty, src_id);
+        let conv = rust_to_js(
+            ctx,
+            &owned_ty,
+            &named_arg.name,
+            (src_id, named_arg.ty.span()),
+        )?;
+        convert_args.push_str(&conv.code);
+        js_args.push(named_arg.name.clone());
+        ts_args.push((named_arg.name.to_string(), conv.ts_type));
+    }
+
+    let ret_ty = extract_return_type(&method.fn_decl.output);
+    let ok_err_types =
+        ast::if_result_return_ok_err_types(&ctx.conv_map.find_or_alloc_rust_type(&ret_ty, src_id));
+    let ok_ty = match ok_err_types {
+        Some((ref ok_ty, _)) => ok_ty.clone(),
+        None => ret_ty.clone(),
+    };
+    let unit_ty: Type = parse_type! { () };
+    let (convert_ret, ts_ret) = if ok_ty == unit_ty {
+        ("let swig_ret: () = ();".to_string(), "void".to_string())
+    } else {
+        if let Type::Reference(_) = ok_ty {
+            return Err(DiagnosticError::new(
+                src_id,
+                method_span,
+                "Returning a reference from JS callback is not supported",
+            ));
+        }
+        let ok_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&ok_ty, src_id);
+        let conv = js_to_rust(ctx, &ok_ty_rust_ty, "swig_ret", (src_id, ok_ty.span()))?;
+        (conv.code, conv.ts_type)
+    };
+    let error_handling = if ok_err_types.is_some() {
+        // JS exception is converted to the error type of the callback,
+        // so the error type should implement `From<String>`
+        "swig_ret.map_err(|err| From::from(err.to_string()))".to_string()
+    } else if ok_ty == unit_ty {
+        format!(
+            r#"if let Err(err) = swig_ret {{
+                eprintln!("JS callback {} failed: {{}}", err);
+            }}"#,
+            js_method_name
+        )
+    } else {
+        format!(
+            r#"swig_ret.unwrap_or_else(|err| panic!("JS callback {} failed: {{}}", err))"#,
+            js_method_name
+        )
+    };
+    members.push(dts::method_declaration(
+        &method.doc_comments,
+        "",
+        &js_method_name,
+        &ts_args,
+        Some(&ts_ret),
+    ));
+    Ok(format!(
+        r#"
+        fn {rust_method_name}({self_arg}, {args_with_types}) {output} {{
+            {to_owned_code}
+            let swig_ret: SwigNodeResult<{ok_ty}> = self.0.call(move |swig_env, swig_object| unsafe {{
+                {convert_args}
+                let swig_ret = swig_node_call_method(swig_env, swig_object, "{js_method_name}", &[{js_args}])?;
+                {convert_ret}
+                Ok(swig_ret)
+            }});
+            {error_handling}
+        }}
+"#,
+        rust_method_name = rust_method_name,
+        self_arg = self_arg,
+        args_with_types = args_with_types.join(", "),
+        output = DisplayToTokens(&method.fn_decl.output),
+        to_owned_code = to_owned_code,
+        ok_ty = DisplayToTokens(&ok_ty),
+        convert_args = convert_args,
+        js_method_name = js_method_name,
+        js_args = js_args.join(", "),
+        convert_ret = convert_ret,
+        error_handling = error_handling,
+    ))
+}
+
+fn generate_module_initialization(module_initialization_code: &[TokenStream]) -> TokenStream {
+    quote::quote! {
+        #[no_mangle]
+        pub unsafe extern "C" fn napi_register_module_v1(
+            swig_env: napi_sys::napi_env,
+            swig_exports: napi_sys::napi_value,
+        ) -> napi_sys::napi_value {
+            swig_node_call(swig_env, || {
+                #( #module_initialization_code )*
+                Ok(swig_exports)
+            })
+        }
+    }
+}
+
+/// Code to convert JS value in variable `var` into Rust value of type `rust_type`
+fn js_to_rust(
+    ctx: &mut NodeContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<NodeConversion> {
+    let (src_id, span) = arg_ty_span;
+    // `&str` is handled below as reference to `String`
+    let is_str_ref = rust_type.normalized_name == "& str";
+    if let Some(ts_type) = js_supported_type(rust_type).filter(|_| !is_str_ref) {
+        Ok(NodeConversion {
+            code: format!(
+                "let {var}: {ty} = SwigFromJs::swig_from_js(swig_env, {var})?;\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+            ),
+            ts_type: ts_type.into(),
+        })
+    } else if let Some(conv) = if_exported_class_js_to_rust(ctx, rust_type, var, arg_ty_span)? {
+        Ok(conv)
+    } else if implements(rust_type, ENUM_TRAIT_NAME) {
+        Ok(NodeConversion {
+            code: format!(
+                "let {var}: u32 = SwigFromJs::swig_from_js(swig_env, {var})?;\nlet {var}: {ty} = {mod_name}::from_u32({var})?;\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                mod_name = node_wrapper_mod_name(&rust_type.normalized_name),
+            ),
+            ts_type: rust_type.normalized_name.to_string(),
+        })
+    } else if implements(rust_type, INTERFACE_TRAIT_NAME) {
+        let interface_name = ctx
+            .conv_map
+            .find_foreign_type_related_to_rust_ty(rust_type.to_idx())
+            .map(|ftype| ctx.conv_map[ftype].typename())
+            .ok_or_else(|| {
+                DiagnosticError::new(
+                    src_id,
+                    span,
+                    format!("No callback registered for type: {}", rust_type),
+                )
+            })?;
+        Ok(NodeConversion {
+            code: format!(
+                "let {var}: {ty} = Box::new({mod_name}::JsCallback(SwigNodeCallback::new(swig_env, {var}, \"{name}\")?));\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                mod_name = node_wrapper_mod_name(&interface_name),
+                name = interface_name,
+            ),
+            ts_type: interface_name.to_string(),
+        })
+    } else if let Some(conv) = map_type(ctx, rust_type, Direction::Incoming, arg_ty_span, var)? {
+        Ok(conv)
+    } else if let Some(inner) = ast::if_option_return_some_type(rust_type) {
+        let inner_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&inner, src_id);
+        let inner = js_to_rust(ctx, &inner_rust_ty, var, arg_ty_span)?;
+        Ok(NodeConversion {
+            code: format!(
+                "let {var}: {ty} = if swig_node_is_nullish(swig_env, {var})? {{ None }} else {{\n{inner}Some({var}) }};\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                inner = inner.code,
+            ),
+            ts_type: format!("{} | null", inner.ts_type),
+        })
+    } else if let Some(elem) = if_vec_return_elem_type(rust_type) {
+        let elem = ctx.conv_map.find_or_alloc_rust_type(&elem, src_id);
+        let inner = js_to_rust(ctx, &elem, var, arg_ty_span)?;
+        Ok(NodeConversion {
+            code: format!(
+                "let {var}: {ty} = swig_node_array_elements(swig_env, {var})?.into_iter().map(|{var}| -> SwigNodeResult<{elem}> {{\n{inner}Ok({var}) }}).collect::<SwigNodeResult<Vec<_>>>()?;\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                elem = DisplayToTokens(&elem.ty),
+                inner = inner.code,
+            ),
+            ts_type: ts_array_type(&inner.ts_type),
+        })
+    } else if let Type::Reference(ref reference) = rust_type.ty {
+        if reference.mutability.is_some() {
+            return Err(DiagnosticError::new(
+                src_id,
+                span,
+                "mutable reference is only supported for exported class types",
+            ));
+        }
+        // `&[T]` is converted as `Vec<T>`, `&str` as `String`
+        let owned_ty = match *reference.elem {
+            Type::Slice(ref slice) => {
+                let elem = &slice.elem;
+                parse_type! { Vec<#elem> }
+            }
+            ref elem if *elem == parse_type! { str } => parse_type! { String },
+            ref elem => elem.clone(),
+        };
+        let owned_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&owned_ty, src_id);
+        let inner = js_to_rust(ctx, &owned_ty_rust_ty, var, arg_ty_span)?;
+        Ok(NodeConversion {
+            code: format!(
+                "{inner}let {var}: {ty} = &{var};\n",
+                inner = inner.code,
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+            ),
+            ts_type: inner.ts_type,
+        })
+    } else {
+        Err(DiagnosticError::new(
+            src_id,
+            span,
+            format!("Unsupported argument type: {}", rust_type),
+        ))
+    }
+}
+
+/// Code to convert Rust value in variable `var` into JS value
+fn rust_to_js(
+    ctx: &mut NodeContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<NodeConversion> {
+    let (src_id, span) = arg_ty_span;
+    let into_js_code = format!(
+        "let {var}: napi_sys::napi_value = {var}.swig_into_js(swig_env)?;\n",
+        var = var
+    );
+    if rust_type.ty == parse_type! { () } {
+        Ok(NodeConversion {
+            code: into_js_code,
+            ts_type: "void".into(),
+        })
+    } else if let Some(ts_type) = js_supported_type(rust_type) {
+        Ok(NodeConversion {
+            code: into_js_code,
+            ts_type: ts_type.into(),
+        })
+    } else if let Some(conv) = if_exported_class_rust_to_js(ctx, rust_type, var, arg_ty_span)? {
+        Ok(conv)
+    } else if implements(rust_type, ENUM_TRAIT_NAME) {
+        Ok(NodeConversion {
+            code: format!(
+                "let {var}: napi_sys::napi_value = {mod_name}::as_u32(&{var}).swig_into_js(swig_env)?;\n",
+                var = var,
+                mod_name = node_wrapper_mod_name(&rust_type.normalized_name),
+            ),
+            ts_type: rust_type.normalized_name.to_string(),
+        })
+    } else if let Some(conv) = map_type(ctx, rust_type, Direction::Outgoing, arg_ty_span, var)? {
+        Ok(conv)
+    } else if let Some(inner) = ast::if_option_return_some_type(rust_type) {
+        let inner_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&inner, src_id);
+        let inner = rust_to_js(ctx, &inner_rust_ty, var, arg_ty_span)?;
+        Ok(NodeConversion {
+            code: format!(
+                "let {var}: napi_sys::napi_value = match {var} {{\nSome({var}) => {{\n{inner}{var} }}\nNone => swig_node_null(swig_env)?,\n}};\n",
+                var = var,
+                inner = inner.code,
+            ),
+            ts_type: format!("{} | null", inner.ts_type),
+        })
+    } else if let Some(elem) = if_vec_return_elem_type(rust_type) {
+        let elem_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&elem, src_id);
+        let inner = rust_to_js(ctx, &elem_rust_ty, var, arg_ty_span)?;
+        Ok(NodeConversion {
+            code: format!(
+                "let {var}: napi_sys::napi_value = swig_node_new_array(swig_env, {var}.into_iter().map(|{var}| -> {ret_type} {{\n{inner}Ok({var}) }}).collect::<SwigNodeResult<Vec<_>>>()?)?;\n",
+                var = var,
+                ret_type = CONV_FUNC_RET_TYPE,
+                inner = inner.code,
+            ),
+            ts_type: ts_array_type(&inner.ts_type),
+        })
+    } else if let Some((ok_ty, _err_ty)) = ast::if_result_return_ok_err_types(rust_type) {
+        let ok_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&ok_ty, src_id);
+        let inner = rust_to_js(ctx, &ok_ty_rust_ty, var, arg_ty_span)?;
+        Ok(NodeConversion {
+            code: format!(
+                "let {var}: {ok_ty} = match {var} {{\nOk(x) => x,\nErr(swig_err) => return Err(SwigNodeError::Message(swig_err.to_string())),\n}};\n{inner}",
+                var = var,
+                ok_ty = DisplayToTokens(&ok_ty),
+                inner = inner.code,
+            ),
+            ts_type: inner.ts_type,
+        })
+    } else if let Type::Reference(ref reference) = rust_type.ty {
+        // JS side gets copy of value
+        let (owned_ty, method) = match *reference.elem {
+            Type::Slice(ref slice) => {
+                let elem = &slice.elem;
+                (parse_type! { Vec<#elem> }, "to_vec()")
+            }
+            ref elem => (elem.clone(), "clone()"),
+        };
+        let owned_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&owned_ty, src_id);
+        let inner = rust_to_js(ctx, &owned_ty_rust_ty, var, arg_ty_span)?;
+        Ok(NodeConversion {
+            code: format!(
+                "let {var}: {ty} = {var}.{method};\n{inner}",
+                var = var,
+                ty = DisplayToTokens(&owned_ty),
+                method = method,
+                inner = inner.code,
+            ),
+            ts_type: inner.ts_type,
+        })
+    } else {
+        Err(DiagnosticError::new(
+            src_id,
+            span,
+            format!("Unsupported return type: {}", rust_type),
+        ))
+    }
+}
+
+/// Types that runtime converts by itself with `SwigFromJs` and `SwigIntoJs`,
+/// returns TypeScript name of type
+fn js_supported_type(rust_type: &RustType) -> Option<&'static str> {
+    match rust_type.normalized_name.as_str() {
+        "bool" => Some("boolean"),
+        "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" | "f32"
+        | "f64" => Some("number"),
+        "String" | "& str" => Some("string"),
+        _ => None,
+    }
+}
+
+/// Class exported to JS, with type that is passed to or returned from Rust
+struct ClassUsage {
+    class: ForeignClassInfo,
+    storage_ty: Type,
+    /// `&T` or `&mut T`
+    reference: Option<bool>,
+    /// `T` without reference, it is either self type or storage type
+    unref_ty: RustType,
+}
+
+fn if_exported_class(
+    ctx: &mut NodeContext,
+    rust_type: &RustType,
+    src_id: crate::source_registry::SourceId,
+) -> Option<ClassUsage> {
+    let (reference, unref_ty) = match rust_type.ty {
+        Type::Reference(ref reference) => (
+            Some(reference.mutability.is_some()),
+            ctx.conv_map
+                .find_or_alloc_rust_type(&reference.elem, src_id),
+        ),
+        _ => (None, rust_type.clone()),
+    };
+    let class = ctx
+        .conv_map
+        .find_foreigner_class_with_such_this_type(&unref_ty.ty, |_, fc| {
+            fc.self_desc.as_ref().map(|x| x.self_type.clone())
+        })
+        .or_else(|| {
+            ctx.conv_map
+                .find_foreigner_class_with_such_this_type(&unref_ty.ty, |_, fc| storage_type(fc))
+        })?
+        .clone();
+    let storage_ty = storage_type(&class)?;
+    Some(ClassUsage {
+        class,
+        storage_ty,
+        reference,
+        unref_ty,
+    })
+}
+
+fn if_exported_class_js_to_rust(
+    ctx: &mut NodeContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<Option<NodeConversion>> {
+    let (src_id, span) = arg_ty_span;
+    let usage = match if_exported_class(ctx, rust_type, src_id) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let class = &usage.class;
+    let storage_ty = &usage.storage_ty;
+    let mutable = usage.reference.unwrap_or(false);
+    let ref_prefix = if mutable { "&mut " } else { "&" };
+    let mut code = format!(
+        "let {var}: {ref_prefix}{storage} = swig_node_unwrap::<{mod_name}::JsClass>(swig_env, {var})?;\n",
+        var = var,
+        ref_prefix = ref_prefix,
+        storage = DisplayToTokens(storage_ty),
+        mod_name = node_wrapper_mod_name(&class.name.to_string()),
+    );
+    let storage_rust_ty = ctx.conv_map.find_or_alloc_rust_type(storage_ty, src_id);
+    if usage.unref_ty.normalized_name == storage_rust_ty.normalized_name {
+        if usage.reference.is_none() {
+            append_clone_if_supported(ctx, &usage, var, arg_ty_span, &mut code)?;
+        }
+    } else {
+        // `&Rc<RefCell<T>>` -> `&T` and so on
+        let from_ty = ctx.conv_map.find_or_alloc_rust_type(
+            &ast::parse_ty_with_given_span(
+                &format!("{}{}", ref_prefix, DisplayToTokens(storage_ty)),
+                span,
+            )
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?,
+            src_id,
+        );
+        let to_ty = ctx.conv_map.find_or_alloc_rust_type(
+            &ast::parse_ty_with_given_span(
+                &format!("{}{}", ref_prefix, DisplayToTokens(&usage.unref_ty.ty)),
+                span,
+            )
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?,
+            src_id,
+        );
+        let (mut deps, conv_code) = ctx.conv_map.convert_rust_types(
+            from_ty.to_idx(),
+            to_ty.to_idx(),
+            var,
+            var,
+            CONV_FUNC_RET_TYPE,
+            arg_ty_span,
+        )?;
+        ctx.rust_code.append(&mut deps);
+        code.push_str(&conv_code);
+        code.push('\n');
+        if usage.reference.is_none() {
+            append_clone_if_supported(ctx, &usage, var, arg_ty_span, &mut code)?;
+        }
+    }
+    Ok(Some(NodeConversion {
+        code,
+        ts_type: class.name.to_string(),
+    }))
+}
+
+fn if_exported_class_rust_to_js(
+    ctx: &mut NodeContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<Option<NodeConversion>> {
+    let (src_id, _) = arg_ty_span;
+    let usage = match if_exported_class(ctx, rust_type, src_id) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let mut code = String::new();
+    if usage.reference.is_some() {
+        append_clone_if_supported(ctx, &usage, var, arg_ty_span, &mut code)?;
+    }
+    let storage_rust_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&usage.storage_ty, src_id);
+    if usage.unref_ty.normalized_name != storage_rust_ty.normalized_name {
+        let (mut deps, conv_code) = ctx.conv_map.convert_rust_types(
+            usage.unref_ty.to_idx(),
+            storage_rust_ty.to_idx(),
+            var,
+            var,
+            CONV_FUNC_RET_TYPE,
+            arg_ty_span,
+        )?;
+        ctx.rust_code.append(&mut deps);
+        code.push_str(&conv_code);
+        code.push('\n');
+    }
+    writeln!(
+        &mut code,
+        "let {var}: napi_sys::napi_value = SwigNodeObject::<{mod_name}::JsClass>({var}).swig_into_js(swig_env)?;",
+        var = var,
+        mod_name = node_wrapper_mod_name(&usage.class.name.to_string()),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    Ok(Some(NodeConversion {
+        code,
+        ts_type: usage.class.name.to_string(),
+    }))
+}
+
+/// JS object owns Rust object, so Rust code can get only copy of it
+fn append_clone_if_supported(
+    ctx: &mut NodeContext,
+    usage: &ClassUsage,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+    code: &mut String,
+) -> Result<()> {
+    let (src_id, span) = arg_ty_span;
+    let is_shared_ptr = ["Rc", "Arc"].iter().any(|smart_ptr| {
+        ast::check_if_smart_pointer_return_inner_type(&usage.unref_ty, smart_ptr).is_some()
+    });
+    if !is_shared_ptr && !usage.class.clone_derived() && !usage.class.copy_derived() {
+        return Err(DiagnosticError::new(
+            src_id,
+            span,
+            format!(
+                "Passing object of class {} by value requires that it is marked with \
+                 `#[derive(Clone)]` or `#[derive(Copy)]` inside its `foreign_class` macro",
+                usage.class.name
+            ),
+        ));
+    }
+    let unref_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&usage.unref_ty.ty, src_id);
+    writeln!(
+        code,
+        "let {var}: {ty} = {var}.clone();",
+        var = var,
+        ty = DisplayToTokens(&unref_ty.ty),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    Ok(())
+}
+
+/// Conversions of Rust types described by `foreign_typemap!` rules:
+/// Rust type is converted to the type mentioned in rule, and then to JS.
+/// Only not generic rules are supported.
+fn map_type(
+    ctx: &mut NodeContext,
+    rust_type: &RustType,
+    direction: Direction,
+    arg_ty_span: SourceIdSpan,
+    var: &str,
+) -> Result<Option<NodeConversion>> {
+    let ftype_idx = match ctx.conv_map.map_through_conversation_to_foreign(
+        rust_type.to_idx(),
+        direction,
+        MapToForeignFlag::FastSearch,
+        arg_ty_span,
+        |_, fc| storage_type(fc),
+    ) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let ftype = &ctx.conv_map[ftype_idx];
+    let rule = match direction {
+        Direction::Outgoing => ftype.into_from_rust.as_ref(),
+        Direction::Incoming => ftype.from_into_rust.as_ref(),
+    }
+    .expect("Internal error: foreign type was found without conversion rule");
+    if rule.intermediate.is_some() {
+        return Err(DiagnosticError::new2(
+            ftype.src_id_span(),
+            format!(
+                "f_type {}: conversion code on foreign side is not supported for Node.js",
+                ftype.name
+            ),
+        ));
+    }
+    let ts_type = ftype.typename().to_string();
+    let js_rust_ty = rule.rust_ty;
+    if js_rust_ty == rust_type.to_idx() {
+        return Ok(None);
+    }
+    let (from, to) = match direction {
+        Direction::Outgoing => (rust_type.to_idx(), js_rust_ty),
+        Direction::Incoming => (js_rust_ty, rust_type.to_idx()),
+    };
+    let (mut deps, conv_code) =
+        ctx.conv_map
+            .convert_rust_types(from, to, var, var, CONV_FUNC_RET_TYPE, arg_ty_span)?;
+    ctx.rust_code.append(&mut deps);
+    let js_rust_ty = ctx.conv_map[js_rust_ty].clone();
+    let code = match direction {
+        Direction::Outgoing => {
+            let conv = rust_to_js(ctx, &js_rust_ty, var, arg_ty_span)?;
+            format!("{}\n{}", conv_code, conv.code)
+        }
+        Direction::Incoming => {
+            let conv = js_to_rust(ctx, &js_rust_ty, var, arg_ty_span)?;
+            format!("{}{}\n", conv.code, conv_code)
+        }
+    };
+    Ok(Some(NodeConversion { code, ts_type }))
+}
+
+/// Type of Rust object, that JS object holds
+fn storage_type(class: &ForeignClassInfo) -> Option<Type> {
+    class.self_desc.as_ref().map(|x| {
+        ast::if_ty_result_return_ok_type(&x.constructor_ret_type)
+            .unwrap_or_else(|| x.constructor_ret_type.clone())
+    })
+}
+
+/// Owned type for callback argument and method to get it
+fn callback_arg_to_owned(ty: &Type) -> Option<(Type, &'static str)> {
+    if let Type::Reference(ref reference) = ty {
+        Some(match *reference.elem {
+            Type::Slice(ref slice) => {
+                let elem = &slice.elem;
+                (parse_type! { Vec<#elem> }, "to_vec()")
+            }
+            ref elem if *elem == parse_type! { str } => (parse_type! { String }, "to_owned()"),
+            ref elem => (elem.clone(), "clone()"),
+        })
+    } else {
+        None
+    }
+}
+
+fn implements(rust_type: &RustType, trait_name: &str) -> bool {
+    let trait_path: syn::Path = syn::Ident::new(trait_name, proc_macro2::Span::call_site()).into();
+    rust_type.implements.contains_path(&trait_path)
+}
+
+fn ts_array_type(elem: &str) -> String {
+    if elem.contains(' ') {
+        format!("({})[]", elem)
+    } else {
+        format!("{}[]", elem)
+    }
+}
+
+fn if_vec_return_elem_type(ty: &RustType) -> Option<Type> {
+    ast::check_if_smart_pointer_return_inner_type(ty, "Vec")
+}
+
+fn extract_return_type(syn_return_type: &syn::ReturnType) -> Type {
+    match syn_return_type {
+        syn::ReturnType::Default => {
+            parse_type! { () }
+        }
+        syn::ReturnType::Type(_, ref ty) => ty.deref().clone(),
+    }
+}
+
+fn boxed_interface_type(interface: &ForeignInterface) -> Result<Type> {
+    let boxed_trait_name = format!("Box<dyn {}>", DisplayToTokens(&interface.self_type));
+    ast::parse_ty_with_given_span(&boxed_trait_name, interface.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(interface.src_id, err))
+}
+
+fn node_wrapper_mod_name(type_name: &str) -> String {
+    format!("node_{}", type_name.to_snake_case())
+}
+
+fn parse_code(code: &str, id_of_code: &str) -> TokenStream {
+    syn::parse_str(code).unwrap_or_else(|err| panic_on_syn_error(id_of_code, code.to_string(), err))
+}
@@ -0,0 +1,795 @@
+mod swig_foreign_types_map {}
+
+// Values from `js_native_api_types.h` and `node_api_types.h`,
+// they are part of N-API ABI, so they never change.
+#[allow(dead_code)]
+const SWIG_NAPI_OK: napi_sys::napi_status = 0;
+#[allow(dead_code)]
+const SWIG_NAPI_PENDING_EXCEPTION: napi_sys::napi_status = 10;
+#[allow(dead_code)]
+const SWIG_NAPI_UNDEFINED: napi_sys::napi_valuetype = 0;
+#[allow(dead_code)]
+const SWIG_NAPI_NULL: napi_sys::napi_valuetype = 1;
+#[allow(dead_code)]
+const SWIG_NAPI_FUNCTION: napi_sys::napi_valuetype = 7;
+#[allow(dead_code)]
+const SWIG_NAPI_DEFAULT: napi_sys::napi_property_attributes = 0;
+#[allow(dead_code)]
+const SWIG_NAPI_STATIC: napi_sys::napi_property_attributes = 1 << 10;
+#[allow(dead_code)]
+const SWIG_NAPI_TSFN_RELEASE: napi_sys::napi_threadsafe_function_release_mode = 0;
+#[allow(dead_code)]
+const SWIG_NAPI_TSFN_BLOCKING: napi_sys::napi_threadsafe_function_call_mode = 1;
+
+/// Error, that is thrown as JS `Error` when it reaches JS side
+#[allow(dead_code)]
+#[derive(Debug)]
+enum SwigNodeError {
+    /// JS exception is already pending, nothing to throw
+    Pending,
+    Message(String),
+}
+
+impl std::fmt::Display for SwigNodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SwigNodeError::Pending => f.write_str("JS exception is pending"),
+            SwigNodeError::Message(msg) => f.write_str(msg),
+        }
+    }
+}
+
+#[allow(dead_code)]
+type SwigNodeResult<T> = Result<T, SwigNodeError>;
+
+#[allow(dead_code)]
+unsafe fn swig_napi_check(
+    env: napi_sys::napi_env,
+    status: napi_sys::napi_status,
+) -> SwigNodeResult<()> {
+    if status == SWIG_NAPI_OK {
+        return Ok(());
+    }
+    if status == SWIG_NAPI_PENDING_EXCEPTION {
+        return Err(SwigNodeError::Pending);
+    }
+    let mut info: *const napi_sys::napi_extended_error_info = std::ptr::null();
+    if napi_sys::napi_get_last_error_info(env, &mut info) == SWIG_NAPI_OK
+        && !info.is_null()
+        && !(*info).error_message.is_null()
+    {
+        let msg = std::ffi::CStr::from_ptr((*info).error_message);
+        return Err(SwigNodeError::Message(msg.to_string_lossy().into_owned()));
+    }
+    Err(SwigNodeError::Message(format!(
+        "N-API call failed with status {}",
+        status
+    )))
+}
+
+#[allow(dead_code)]
+unsafe fn swig_node_throw(env: napi_sys::napi_env, err: SwigNodeError) {
+    if let SwigNodeError::Message(msg) = err {
+        let msg = std::ffi::CString::new(msg.replace('\0', "\\0"))
+            .expect("NUL bytes in error message were replaced");
+        napi_sys::napi_throw_error(env, std::ptr::null(), msg.as_ptr());
+    }
+}
+
+/// Run body of function called from JS, error is thrown as JS exception
+#[allow(dead_code)]
+unsafe fn swig_node_call<F>(env: napi_sys::napi_env, f: F) -> napi_sys::napi_value
+where
+    F: FnOnce() -> SwigNodeResult<napi_sys::napi_value>,
+{
+    match f() {
+        Ok(ret) => ret,
+        Err(err) => {
+            swig_node_throw(env, err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// `this` and `argc` arguments of call, missed arguments are `undefined`
+#[allow(dead_code)]
+unsafe fn swig_node_get_args(
+    env: napi_sys::napi_env,
+    info: napi_sys::napi_callback_info,
+    argc: usize,
+) -> SwigNodeResult<(napi_sys::napi_value, Vec<napi_sys::napi_value>)> {
+    let mut this = std::ptr::null_mut();
+    let mut args = vec![std::ptr::null_mut(); argc];
+    let mut real_argc = argc;
+    swig_napi_check(
+        env,
+        napi_sys::napi_get_cb_info(
+            env,
+            info,
+            &mut real_argc,
+            args.as_mut_ptr(),
+            &mut this,
+            std::ptr::null_mut(),
+        ),
+    )?;
+    Ok((this, args))
+}
+
+#[allow(dead_code)]
+unsafe fn swig_node_typeof(
+    env: napi_sys::napi_env,
+    value: napi_sys::napi_value,
+) -> SwigNodeResult<napi_sys::napi_valuetype> {
+    let mut ty = SWIG_NAPI_UNDEFINED;
+    swig_napi_check(env, napi_sys::napi_typeof(env, value, &mut ty))?;
+    Ok(ty)
+}
+
+#[allow(dead_code)]
+unsafe fn swig_node_is_nullish(
+    env: napi_sys::napi_env,
+    value: napi_sys::napi_value,
+) -> SwigNodeResult<bool> {
+    let ty = swig_node_typeof(env, value)?;
+    Ok(ty == SWIG_NAPI_UNDEFINED || ty == SWIG_NAPI_NULL)
+}
+
+#[allow(dead_code)]
+unsafe fn swig_node_null(env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value> {
+    let mut ret = std::ptr::null_mut();
+    swig_napi_check(env, napi_sys::napi_get_null(env, &mut ret))?;
+    Ok(ret)
+}
+
+#[allow(dead_code)]
+unsafe fn swig_node_set_named_property(
+    env: napi_sys::napi_env,
+    object: napi_sys::napi_value,
+    name: &str,
+    value: napi_sys::napi_value,
+) -> SwigNodeResult<()> {
+    let name = std::ffi::CString::new(name).expect("property name with NUL byte");
+    swig_napi_check(
+        env,
+        napi_sys::napi_set_named_property(env, object, name.as_ptr(), value),
+    )
+}
+
+#[allow(dead_code)]
+unsafe fn swig_node_get_reference(
+    env: napi_sys::napi_env,
+    reference: napi_sys::napi_ref,
+) -> SwigNodeResult<napi_sys::napi_value> {
+    let mut ret = std::ptr::null_mut();
+    swig_napi_check(
+        env,
+        napi_sys::napi_get_reference_value(env, reference, &mut ret),
+    )?;
+    if ret.is_null() {
+        return Err(SwigNodeError::Message(
+            "JS object was garbage collected".to_string(),
+        ));
+    }
+    Ok(ret)
+}
+
+/// Descriptor of method for `napi_define_class`, `name` should be NUL-terminated
+#[allow(dead_code)]
+fn swig_node_method(
+    name: &'static str,
+    method: napi_sys::napi_callback,
+    is_static: bool,
+) -> napi_sys::napi_property_descriptor {
+    napi_sys::napi_property_descriptor {
+        utf8name: name.as_ptr() as *const std::os::raw::c_char,
+        name: std::ptr::null_mut(),
+        method,
+        getter: None,
+        setter: None,
+        value: std::ptr::null_mut(),
+        attributes: if is_static {
+            SWIG_NAPI_STATIC
+        } else {
+            SWIG_NAPI_DEFAULT
+        },
+        data: std::ptr::null_mut(),
+    }
+}
+
+/// Conversion from JS value to Rust
+#[allow(dead_code)]
+trait SwigFromJs: Sized {
+    unsafe fn swig_from_js(
+        env: napi_sys::napi_env,
+        value: napi_sys::napi_value,
+    ) -> SwigNodeResult<Self>;
+}
+
+/// Conversion from Rust to JS value
+#[allow(dead_code)]
+trait SwigIntoJs {
+    unsafe fn swig_into_js(self, env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value>;
+}
+
+impl SwigIntoJs for () {
+    unsafe fn swig_into_js(self, env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value> {
+        let mut ret = std::ptr::null_mut();
+        swig_napi_check(env, napi_sys::napi_get_undefined(env, &mut ret))?;
+        Ok(ret)
+    }
+}
+
+impl SwigFromJs for bool {
+    unsafe fn swig_from_js(
+        env: napi_sys::napi_env,
+        value: napi_sys::napi_value,
+    ) -> SwigNodeResult<Self> {
+        let mut ret = false;
+        swig_napi_check(env, napi_sys::napi_get_value_bool(env, value, &mut ret))?;
+        Ok(ret)
+    }
+}
+
+impl SwigIntoJs for bool {
+    unsafe fn swig_into_js(self, env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value> {
+        let mut ret = std::ptr::null_mut();
+        swig_napi_check(env, napi_sys::napi_get_boolean(env, self, &mut ret))?;
+        Ok(ret)
+    }
+}
+
+// JS has only `number`, so integers are checked during conversion,
+// integers above 2^53 lose precision
+macro_rules! swig_node_integer_conversion {
+    ($($ty:ty),*) => {
+        $(
+            impl SwigFromJs for $ty {
+                unsafe fn swig_from_js(
+                    env: napi_sys::napi_env,
+                    value: napi_sys::napi_value,
+                ) -> SwigNodeResult<Self> {
+                    let mut ret: i64 = 0;
+                    swig_napi_check(env, napi_sys::napi_get_value_int64(env, value, &mut ret))?;
+                    <$ty as std::convert::TryFrom<i64>>::try_from(ret).map_err(|_| {
+                        SwigNodeError::Message(format!(
+                            "{} is out of range for {}",
+                            ret,
+                            stringify!($ty)
+                        ))
+                    })
+                }
+            }
+
+            impl SwigIntoJs for $ty {
+                unsafe fn swig_into_js(
+                    self,
+                    env: napi_sys::napi_env,
+                ) -> SwigNodeResult<napi_sys::napi_value> {
+                    let value = <i64 as std::convert::TryFrom<$ty>>::try_from(self).map_err(|_| {
+                        SwigNodeError::Message(format!("{} is out of range for number", self))
+                    })?;
+                    let mut ret = std::ptr::null_mut();
+                    swig_napi_check(env, napi_sys::napi_create_int64(env, value, &mut ret))?;
+                    Ok(ret)
+                }
+            }
+        )*
+    };
+}
+
+swig_node_integer_conversion!(i8, i16, i32, i64, u8, u16, u32, u64, isize, usize);
+
+impl SwigFromJs for f64 {
+    unsafe fn swig_from_js(
+        env: napi_sys::napi_env,
+        value: napi_sys::napi_value,
+    ) -> SwigNodeResult<Self> {
+        let mut ret = 0.;
+        swig_napi_check(env, napi_sys::napi_get_value_double(env, value, &mut ret))?;
+        Ok(ret)
+    }
+}
+
+impl SwigIntoJs for f64 {
+    unsafe fn swig_into_js(self, env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value> {
+        let mut ret = std::ptr::null_mut();
+        swig_napi_check(env, napi_sys::napi_create_double(env, self, &mut ret))?;
+        Ok(ret)
+    }
+}
+
+impl SwigFromJs for f32 {
+    unsafe fn swig_from_js(
+        env: napi_sys::napi_env,
+        value: napi_sys::napi_value,
+    ) -> SwigNodeResult<Self> {
+        f64::swig_from_js(env, value).map(|x| x as f32)
+    }
+}
+
+impl SwigIntoJs for f32 {
+    unsafe fn swig_into_js(self, env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value> {
+        f64::from(self).swig_into_js(env)
+    }
+}
+
+impl SwigFromJs for String {
+    unsafe fn swig_from_js(
+        env: napi_sys::napi_env,
+        value: napi_sys::napi_value,
+    ) -> SwigNodeResult<Self> {
+        let mut len = 0;
+        swig_napi_check(
+            env,
+            napi_sys::napi_get_value_string_utf8(env, value, std::ptr::null_mut(), 0, &mut len),
+        )?;
+        let mut buf = vec![0u8; len + 1];
+        swig_napi_check(
+            env,
+            napi_sys::napi_get_value_string_utf8(
+                env,
+                value,
+                buf.as_mut_ptr() as *mut std::os::raw::c_char,
+                buf.len(),
+                &mut len,
+            ),
+        )?;
+        buf.truncate(len);
+        String::from_utf8(buf).map_err(|err| SwigNodeError::Message(err.to_string()))
+    }
+}
+
+impl<'a> SwigIntoJs for &'a str {
+    unsafe fn swig_into_js(self, env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value> {
+        let mut ret = std::ptr::null_mut();
+        swig_napi_check(
+            env,
+            napi_sys::napi_create_string_utf8(
+                env,
+                self.as_ptr() as *const std::os::raw::c_char,
+                self.len(),
+                &mut ret,
+            ),
+        )?;
+        Ok(ret)
+    }
+}
+
+impl SwigIntoJs for String {
+    unsafe fn swig_into_js(self, env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value> {
+        self.as_str().swig_into_js(env)
+    }
+}
+
+impl SwigIntoJs for napi_sys::napi_value {
+    unsafe fn swig_into_js(self, _env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value> {
+        Ok(self)
+    }
+}
+
+/// Elements of JS array
+#[allow(dead_code)]
+unsafe fn swig_node_array_elements(
+    env: napi_sys::napi_env,
+    array: napi_sys::napi_value,
+) -> SwigNodeResult<Vec<napi_sys::napi_value>> {
+    let mut is_array = false;
+    swig_napi_check(env, napi_sys::napi_is_array(env, array, &mut is_array))?;
+    if !is_array {
+        return Err(SwigNodeError::Message("Array expected".to_string()));
+    }
+    let mut len = 0;
+    swig_napi_check(env, napi_sys::napi_get_array_length(env, array, &mut len))?;
+    let mut elements = Vec::with_capacity(len as usize);
+    for i in 0..len {
+        let mut elem = std::ptr::null_mut();
+        swig_napi_check(env, napi_sys::napi_get_element(env, array, i, &mut elem))?;
+        elements.push(elem);
+    }
+    Ok(elements)
+}
+
+#[allow(dead_code)]
+unsafe fn swig_node_new_array(
+    env: napi_sys::napi_env,
+    elements: Vec<napi_sys::napi_value>,
+) -> SwigNodeResult<napi_sys::napi_value> {
+    let mut array = std::ptr::null_mut();
+    swig_napi_check(
+        env,
+        napi_sys::napi_create_array_with_length(env, elements.len(), &mut array),
+    )?;
+    for (i, elem) in elements.into_iter().enumerate() {
+        swig_napi_check(env, napi_sys::napi_set_element(env, array, i as u32, elem))?;
+    }
+    Ok(array)
+}
+
+/// Implemented by marker type of every generated JS class
+#[allow(dead_code)]
+trait SwigNodeClass: 'static {
+    /// Type of Rust object, that instance of JS class owns
+    type Storage: 'static;
+    const NAME: &'static str;
+    /// Constructor of JS class, created during module initialization
+    unsafe fn swig_js_class(env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value>;
+}
+
+thread_local! {
+    /// Rust object, that JS constructor should take instead of calling Rust constructor
+    static SWIG_NODE_OBJECT_TO_WRAP: std::cell::Cell<*mut std::os::raw::c_void> =
+        std::cell::Cell::new(std::ptr::null_mut());
+}
+
+/// Called by GC when JS object is collected
+#[allow(dead_code)]
+unsafe extern "C" fn swig_node_finalize<T>(
+    _env: napi_sys::napi_env,
+    data: *mut std::os::raw::c_void,
+    _hint: *mut std::os::raw::c_void,
+) {
+    drop(Box::from_raw(data as *mut T));
+}
+
+/// Attach Rust object to just created JS object `this`
+#[allow(dead_code)]
+unsafe fn swig_node_wrap<C: SwigNodeClass>(
+    env: napi_sys::napi_env,
+    this: napi_sys::napi_value,
+    obj: Box<C::Storage>,
+) -> SwigNodeResult<napi_sys::napi_value> {
+    let p = Box::into_raw(obj);
+    let status = napi_sys::napi_wrap(
+        env,
+        this,
+        p as *mut std::os::raw::c_void,
+        Some(swig_node_finalize::<C::Storage>),
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+    );
+    if status != SWIG_NAPI_OK {
+        drop(Box::from_raw(p));
+    }
+    swig_napi_check(env, status)?;
+    Ok(this)
+}
+
+/// If constructor is called to wrap object returned from Rust, take this object
+#[allow(dead_code)]
+unsafe fn swig_node_take_object_to_wrap<C: SwigNodeClass>() -> Option<Box<C::Storage>> {
+    let p = SWIG_NODE_OBJECT_TO_WRAP.with(|x| x.replace(std::ptr::null_mut()));
+    if p.is_null() {
+        None
+    } else {
+        Some(Box::from_raw(p as *mut C::Storage))
+    }
+}
+
+#[allow(dead_code)]
+unsafe fn swig_node_unwrap<'a, C: SwigNodeClass>(
+    env: napi_sys::napi_env,
+    obj: napi_sys::napi_value,
+) -> SwigNodeResult<&'a mut C::Storage> {
+    let class = C::swig_js_class(env)?;
+    let mut is_instance = false;
+    swig_napi_check(
+        env,
+        napi_sys::napi_instanceof(env, obj, class, &mut is_instance),
+    )?;
+    if !is_instance {
+        return Err(SwigNodeError::Message(format!("{} expected", C::NAME)));
+    }
+    let mut p = std::ptr::null_mut();
+    swig_napi_check(env, napi_sys::napi_unwrap(env, obj, &mut p))?;
+    Ok(&mut *(p as *mut C::Storage))
+}
+
+/// Rust object, that becomes new instance of JS class `C`
+#[allow(dead_code)]
+struct SwigNodeObject<C: SwigNodeClass>(C::Storage);
+
+impl<C: SwigNodeClass> SwigIntoJs for SwigNodeObject<C> {
+    unsafe fn swig_into_js(self, env: napi_sys::napi_env) -> SwigNodeResult<napi_sys::napi_value> {
+        let class = C::swig_js_class(env)?;
+        let p = Box::into_raw(Box::new(self.0));
+        SWIG_NODE_OBJECT_TO_WRAP.with(|x| x.set(p as *mut std::os::raw::c_void));
+        let mut obj = std::ptr::null_mut();
+        let status = napi_sys::napi_new_instance(env, class, 0, std::ptr::null(), &mut obj);
+        // if constructor was not called, object is still ours
+        drop(swig_node_take_object_to_wrap::<C>());
+        swig_napi_check(env, status)?;
+        Ok(obj)
+    }
+}
+
+/// Clear pending JS exception and convert it to error
+#[allow(dead_code)]
+unsafe fn swig_node_take_exception(env: napi_sys::napi_env) -> SwigNodeError {
+    let mut exception = std::ptr::null_mut();
+    let mut msg = std::ptr::null_mut();
+    if napi_sys::napi_get_and_clear_last_exception(env, &mut exception) == SWIG_NAPI_OK
+        && napi_sys::napi_coerce_to_string(env, exception, &mut msg) == SWIG_NAPI_OK
+    {
+        if let Ok(msg) = String::swig_from_js(env, msg) {
+            return SwigNodeError::Message(msg);
+        }
+    }
+    SwigNodeError::Message("JS exception".to_string())
+}
+
+#[allow(dead_code)]
+unsafe fn swig_node_call_method(
+    env: napi_sys::napi_env,
+    object: napi_sys::napi_value,
+    name: &str,
+    args: &[napi_sys::napi_value],
+) -> SwigNodeResult<napi_sys::napi_value> {
+    let c_name = std::ffi::CString::new(name).expect("method name with NUL byte");
+    let mut func = std::ptr::null_mut();
+    swig_napi_check(
+        env,
+        napi_sys::napi_get_named_property(env, object, c_name.as_ptr(), &mut func),
+    )?;
+    if swig_node_typeof(env, func)? != SWIG_NAPI_FUNCTION {
+        return Err(SwigNodeError::Message(format!(
+            "callback object has no method {}",
+            name
+        )));
+    }
+    let mut ret = std::ptr::null_mut();
+    let status =
+        napi_sys::napi_call_function(env, object, func, args.len(), args.as_ptr(), &mut ret);
+    if status == SWIG_NAPI_PENDING_EXCEPTION {
+        return Err(swig_node_take_exception(env));
+    }
+    swig_napi_check(env, status)?;
+    Ok(ret)
+}
+
+#[allow(dead_code)]
+type SwigNodeJob = Box<dyn FnOnce(napi_sys::napi_env) + Send>;
+
+/// Called on JS thread for every job passed to thread-safe function
+#[allow(dead_code)]
+unsafe extern "C" fn swig_node_run_job(
+    env: napi_sys::napi_env,
+    _js_callback: napi_sys::napi_value,
+    _context: *mut std::os::raw::c_void,
+    data: *mut std::os::raw::c_void,
+) {
+    let job = Box::from_raw(data as *mut SwigNodeJob);
+    // `env` is null if thread-safe function is destroyed
+    if !env.is_null() {
+        job(env);
+    }
+}
+
+/// Reference to JS object, it is used only on JS thread
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+struct SwigNodeRef(napi_sys::napi_ref);
+
+unsafe impl Send for SwigNodeRef {}
+
+impl SwigNodeRef {
+    fn get(self) -> napi_sys::napi_ref {
+        self.0
+    }
+}
+
+/// JS object, that implements callback. Methods of object can be called
+/// from any Rust thread, on JS thread they are called directly,
+/// other threads pass call into JS thread via thread-safe function
+/// and wait for result.
+#[allow(dead_code)]
+struct SwigNodeCallback {
+    env: napi_sys::napi_env,
+    object: SwigNodeRef,
+    tsfn: napi_sys::napi_threadsafe_function,
+    js_thread: std::thread::ThreadId,
+}
+
+unsafe impl Send for SwigNodeCallback {}
+unsafe impl Sync for SwigNodeCallback {}
+
+#[allow(dead_code)]
+impl SwigNodeCallback {
+    unsafe fn new(
+        env: napi_sys::napi_env,
+        object: napi_sys::napi_value,
+        name: &str,
+    ) -> SwigNodeResult<SwigNodeCallback> {
+        if swig_node_is_nullish(env, object)? {
+            return Err(SwigNodeError::Message(format!("{} expected", name)));
+        }
+        let resource_name = name.swig_into_js(env)?;
+        let mut tsfn = std::ptr::null_mut();
+        swig_napi_check(
+            env,
+            napi_sys::napi_create_threadsafe_function(
+                env,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                resource_name,
+                0,
+                1,
+                std::ptr::null_mut(),
+                None,
+                std::ptr::null_mut(),
+                Some(swig_node_run_job),
+                &mut tsfn,
+            ),
+        )?;
+        // callback should not prevent exit of Node.js process
+        napi_sys::napi_unref_threadsafe_function(env, tsfn);
+        let mut object_ref = std::ptr::null_mut();
+        let status = napi_sys::napi_create_reference(env, object, 1, &mut object_ref);
+        if status != SWIG_NAPI_OK {
+            napi_sys::napi_release_threadsafe_function(tsfn, SWIG_NAPI_TSFN_RELEASE);
+        }
+        swig_napi_check(env, status)?;
+        Ok(SwigNodeCallback {
+            env,
+            object: SwigNodeRef(object_ref),
+            tsfn,
+            js_thread: std::thread::current().id(),
+        })
+    }
+
+    fn call<R, F>(&self, f: F) -> SwigNodeResult<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(napi_sys::napi_env, napi_sys::napi_value) -> SwigNodeResult<R> + Send + 'static,
+    {
+        let object = self.object;
+        if std::thread::current().id() == self.js_thread {
+            return unsafe {
+                let mut scope = std::ptr::null_mut();
+                swig_napi_check(
+                    self.env,
+                    napi_sys::napi_open_handle_scope(self.env, &mut scope),
+                )?;
+                let ret = swig_node_get_reference(self.env, object.get())
+                    .and_then(|obj| f(self.env, obj));
+                napi_sys::napi_close_handle_scope(self.env, scope);
+                ret
+            };
+        }
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let job: SwigNodeJob = Box::new(move |env| {
+            let ret = unsafe {
+                swig_node_get_reference(env, object.get()).and_then(|obj| f(env, obj))
+            };
+            let _ = sender.send(ret);
+        });
+        let data = Box::into_raw(Box::new(job));
+        let status = unsafe {
+            napi_sys::napi_call_threadsafe_function(
+                self.tsfn,
+                data as *mut std::os::raw::c_void,
+                SWIG_NAPI_TSFN_BLOCKING,
+            )
+        };
+        if status != SWIG_NAPI_OK {
+            drop(unsafe { Box::from_raw(data) });
+            return Err(SwigNodeError::Message(format!(
+                "can not call JS from other thread, N-API status {}",
+                status
+            )));
+        }
+        receiver.recv().unwrap_or_else(|_| {
+            Err(SwigNodeError::Message(
+                "JS environment was destroyed before callback was called".to_string(),
+            ))
+        })
+    }
+}
+
+impl Drop for SwigNodeCallback {
+    fn drop(&mut self) {
+        let object = self.object;
+        unsafe {
+            if std::thread::current().id() == self.js_thread {
+                napi_sys::napi_delete_reference(self.env, object.get());
+            } else {
+                let job: SwigNodeJob = Box::new(move |env| {
+                    napi_sys::napi_delete_reference(env, object.get());
+                });
+                let data = Box::into_raw(Box::new(job));
+                if napi_sys::napi_call_threadsafe_function(
+                    self.tsfn,
+                    data as *mut std::os::raw::c_void,
+                    SWIG_NAPI_TSFN_BLOCKING,
+                ) != SWIG_NAPI_OK
+                {
+                    drop(Box::from_raw(data));
+                }
+            }
+            napi_sys::napi_release_threadsafe_function(self.tsfn, SWIG_NAPI_TSFN_RELEASE);
+        }
+    }
+}
+
+/// JS has no character type, so `char` is passed as string with one character
+#[allow(dead_code)]
+fn swig_node_char_from_string(s: String) -> SwigNodeResult<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Ok(ch),
+        _ => Err(SwigNodeError::Message(format!(
+            "string with one character expected, got {:?}",
+            s
+        ))),
+    }
+}
+
+foreign_typemap!(
+    ($p:r_type) char => String {
+        $out = $p.to_string();
+    };
+    ($p:f_type) => "string";
+    ($p:r_type) char <= String {
+        $out = swig_node_char_from_string($p)?;
+    };
+    ($p:f_type) <= "string";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Arc<Mutex<T>> => &Mutex<T> {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &Mutex<T> => MutexGuard<T> {
+        $out = $p.lock().unwrap();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> MutexGuard<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> MutexGuard<T> => &mut T {
+        $out = &mut $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Rc<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &Rc<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &RefCell<T> => Ref<T> {
+        $out = $p.borrow();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &RefCell<T> => RefMut<T> {
+        $out = $p.borrow_mut();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Ref<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> RefMut<T> => &mut T {
+        $out = &mut $p;
+    };
+);
@@ -0,0 +1,39 @@
+
+thread_local! {
+    /// Backtrace of the last panic in this thread, captured by panic hook
+    static SWIG_PANIC_BACKTRACE: ::std::cell::RefCell<Option<String>> =
+        ::std::cell::RefCell::new(None);
+}
+
+/// Call `f` and turn panic into error with panic message,
+/// message doesn't contain zero bytes, so it can be passed as C string
+#[allow(dead_code)]
+fn swig_catch_panic<R, F: FnOnce() -> R>(with_backtrace: bool, f: F) -> Result<R, String> {
+    if with_backtrace {
+        static INSTALL_HOOK: ::std::sync::Once = ::std::sync::Once::new();
+        INSTALL_HOOK.call_once(|| {
+            let prev_hook = ::std::panic::take_hook();
+            ::std::panic::set_hook(Box::new(move |info| {
+                let backtrace = ::std::backtrace::Backtrace::force_capture().to_string();
+                SWIG_PANIC_BACKTRACE.with(|x| *x.borrow_mut() = Some(backtrace));
+                prev_hook(info);
+            }));
+        });
+    }
+    ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(f)).map_err(|payload| {
+        let mut msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+            msg.to_string()
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            "Rust panic with non-string payload".to_string()
+        };
+        if with_backtrace {
+            if let Some(backtrace) = SWIG_PANIC_BACKTRACE.with(|x| x.borrow_mut().take()) {
+                msg.push('\n');
+                msg.push_str(&backtrace);
+            }
+        }
+        msg.replace('\0', "\\0")
+    })
+}
@@ -0,0 +1,154 @@
+//! `async fn` methods: Rust future is passed to executor and method returns
+//! `asyncio.Future` of running event loop, result is delivered to it
+//! via `loop.call_soon_threadsafe`. Cancel of `asyncio.Future` drops Rust future.
+
+use super::*;
+
+/// Rust code shared by all `async fn` methods
+pub(in crate::python) fn generate_runtime(ctx: &mut PythonContext) -> Result<()> {
+    ctx.rust_code.push(crate::async_include_code(
+        ctx.cfg.async_executor.as_deref(),
+    )?);
+    let (id_of_code, code) = match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => (
+            "python-async-include.rs",
+            include_str!("python-async-include.rs"),
+        ),
+        PythonBinding::PyO3 => (
+            "pyo3-async-include.rs",
+            include_str!("pyo3-async-include.rs"),
+        ),
+    };
+    ctx.rust_code.push(
+        syn::parse_str(code)
+            .unwrap_or_else(|err| crate::error::panic_on_syn_error(id_of_code, code.into(), err)),
+    );
+    Ok(())
+}
+
+/// Future must be `'static`, so arguments are converted before the call
+/// and must not borrow data owned by Python, `self` is allowed only as `&self`
+/// of class stored in `Arc<T>`, the future keeps its own clone of `Arc`.
+/// Returns statements to run before the call and the future.
+pub(in crate::python) fn generate_async_call(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+    args_names: &[Ident],
+    args_convertions: &[TokenStream],
+) -> Result<(Vec<TokenStream>, TokenStream)> {
+    let skip_args_count = if let MethodVariant::Method(_) = method.variant {
+        1
+    } else {
+        0
+    };
+    for arg in method.fn_decl.inputs.iter().skip(skip_args_count) {
+        let named_arg = arg
+            .as_named_arg()
+            .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
+        if gil::borrows(&named_arg.ty) {
+            return Err(DiagnosticError::new(
+                class.src_id,
+                method.span(),
+                format!(
+                    "class {}, async method {}: argument {} borrows data owned by Python",
+                    class.name,
+                    method.short_name(),
+                    named_arg.name
+                ),
+            ));
+        }
+    }
+    let mut prelude = args_names
+        .iter()
+        .zip(args_convertions)
+        .filter(|(name, convertion)| convertion.to_string() != name.to_string())
+        .map(|(name, convertion)| quote! { let #name = #convertion; })
+        .collect::<Vec<_>>();
+    let mut call_args = args_names
+        .iter()
+        .map(|name| name.into_token_stream())
+        .collect::<Vec<_>>();
+    if let MethodVariant::Method(self_variant) = method.variant {
+        let class_smart_pointer = storage_smart_pointer_for_class(class, ctx.conv_map)?;
+        if class_smart_pointer.pointer_type != PointerType::Arc
+            || self_variant != SelfTypeVariant::Rptr
+        {
+            return Err(DiagnosticError::new(
+                class.src_id,
+                method.span(),
+                format!(
+                    "class {}, async method {}: only `&self` of class stored in `Arc<T>` \
+                     is supported, because future can not borrow object owned by Python",
+                    class.name,
+                    method.short_name(),
+                ),
+            ));
+        }
+        let py_mod: Ident = parse(&py_wrapper_mod_name(&class.name.to_string()), class.src_id)?;
+        prelude.push(quote! {
+            let rust_instance = super::#py_mod::rust_instance(self, py).clone();
+        });
+        call_args.insert(0, quote! { (&*rust_instance) });
+    }
+    let rust_call = generate_rust_call(method, &call_args, class.src_id)?;
+    // body of inline method is the body of async block
+    let rust_call = if method.inline_block.is_some() {
+        rust_call
+    } else {
+        quote! { #rust_call.await }
+    };
+    Ok((prelude, quote! { async move { #rust_call } }))
+}
+
+/// Returns type returned to Python and code that spawns `future`,
+/// its output is converted after completion with the GIL held
+pub(in crate::python) fn generate_spawn(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+    future: TokenStream,
+) -> Result<(Type, TokenStream)> {
+    let ret_rust_type = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&extract_return_type(&method.fn_decl.output), class.src_id);
+    let (return_type, conversion) = dunder::generate_conversion_for_protocol(
+        ctx,
+        &ret_rust_type,
+        ReturnProtocol::Value,
+        method.span(),
+        class.src_id,
+        quote! { ret },
+    )?;
+    let py_result = py_result_type(ctx);
+    let py_object = py_object_type(ctx);
+    let python = match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! { cpython::Python },
+        PythonBinding::PyO3 => quote! { pyo3::Python<'_> },
+    };
+    let into_object = if return_type == parse_type! { () } {
+        // the same as for usual methods, `()` is returned as `None`, not as empty tuple
+        quote! {
+            let () = ret;
+            Ok(py.None())
+        }
+    } else {
+        match ctx.cfg.python_binding {
+            PythonBinding::RustCPython => quote! {
+                Ok(cpython::PythonObject::into_object(
+                    cpython::ToPyObject::into_py_object(ret, py),
+                ))
+            },
+            PythonBinding::PyO3 => quote! { pyo3::IntoPyObjectExt::into_py_any(ret, py) },
+        }
+    };
+    Ok((
+        py_object.clone(),
+        quote! {
+            swig_py_async_spawn(py, #future, move |py: #python, ret| -> #py_result<#py_object> {
+                let ret: #return_type = #conversion;
+                #into_object
+            })?
+        },
+    ))
+}
@@ -0,0 +1,478 @@
+//! Special ("dunder") methods of Python classes.
+//! `#[derive(Display, Debug, PartialEq, PartialOrd, Hash)]` on `foreign_class!`
+//! gives `__str__`, `__repr__`, comparison operators and `__hash__`.
+//! Methods marked with `#[__len__]`, `#[__getitem__]`, `#[__iter__]`, `#[__next__]`
+//! (or `#[__str__]`, `#[__repr__]`, `#[__hash__]`) are also exposed
+//! as the corresponding special methods.
+
+use super::*;
+use crate::extension::PYTHON_METHOD_ATTRS;
+
+/// How value returned from Rust method is passed to Python
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(in crate::python) enum ReturnProtocol {
+    Value,
+    /// Integer is converted to `usize`, as required for `__len__`
+    Len,
+    /// `None` raises `IndexError`, as required for `__getitem__`
+    IndexErrorIfNone,
+    /// `None` raises `StopIteration`, as required for `__next__`
+    StopIterationIfNone,
+    /// Nothing is returned, as required for property setter
+    Unit,
+}
+
+pub(in crate::python) enum Slot<'a> {
+    /// Method of `foreign_class!` exposed under special name
+    Method {
+        name: &'static str,
+        method: &'a ForeignMethod,
+        protocol: ReturnProtocol,
+    },
+    /// `__iter__` of iterator, it returns iterator itself
+    IterSelf,
+    /// `__str__` or `__repr__` implemented with `Display` or `Debug`
+    Format {
+        name: &'static str,
+        format: &'static str,
+    },
+    /// `__hash__` implemented with `Hash`
+    Hash,
+    /// `__eq__`, `__ne__` implemented with `PartialEq`,
+    /// plus `__lt__`, `__le__`, `__gt__`, `__ge__` implemented with `PartialOrd`
+    Compare { ordering: bool },
+}
+
+impl<'a> Slot<'a> {
+    fn names(&self) -> Vec<&'static str> {
+        match self {
+            Slot::Method { name, .. } | Slot::Format { name, .. } => vec![name],
+            Slot::IterSelf => vec!["__iter__"],
+            Slot::Hash => vec!["__hash__"],
+            Slot::Compare { ordering: false } => vec!["__eq__", "__ne__"],
+            Slot::Compare { ordering: true } => {
+                vec!["__eq__", "__ne__", "__lt__", "__le__", "__gt__", "__ge__"]
+            }
+        }
+    }
+}
+
+/// Collect special methods of class, checking that every one is defined only once
+pub(in crate::python) fn class_slots<'a>(
+    ctx: &mut PythonContext,
+    class: &'a ForeignClassInfo,
+) -> Result<Vec<Slot<'a>>> {
+    let mut slots = Vec::new();
+    for method in &class.methods {
+        if let Some(slot) = method_slot(ctx, class, method)? {
+            slots.push(slot);
+        }
+    }
+    let has_next = slots.iter().any(|slot| match slot {
+        Slot::Method { name, .. } => *name == "__next__",
+        _ => false,
+    });
+    let has_iter = slots.iter().any(|slot| match slot {
+        Slot::Method { name, .. } => *name == "__iter__",
+        _ => false,
+    });
+    if has_next && !has_iter {
+        slots.push(Slot::IterSelf);
+    }
+
+    let derived = |name: &str| class.derive_list.iter().any(|x| x == name);
+    if derived("Display") {
+        slots.push(Slot::Format {
+            name: "__str__",
+            format: "{}",
+        });
+    }
+    if derived("Debug") {
+        slots.push(Slot::Format {
+            name: "__repr__",
+            format: "{:?}",
+        });
+    }
+    if derived("PartialEq") || derived("PartialOrd") {
+        slots.push(Slot::Compare {
+            ordering: derived("PartialOrd"),
+        });
+    }
+    if derived("Hash") {
+        slots.push(Slot::Hash);
+    }
+    if class.self_desc.is_none() && slots.iter().any(|s| !matches!(s, Slot::Method { .. })) {
+        return Err(DiagnosticError::new(
+            class.src_id,
+            class.span(),
+            format!(
+                "class {}: special methods require self_type for class",
+                class.name
+            ),
+        ));
+    }
+
+    let mut defined = Vec::<String>::new();
+    for method in &class.methods {
+        if !method.is_dummy_constructor() {
+            let name = method_name(method, class.src_id)?.to_string();
+            if name.starts_with("__") {
+                defined.push(name);
+            }
+        }
+    }
+    for slot in &slots {
+        for name in slot.names() {
+            if defined.iter().any(|x| x == name) {
+                return Err(DiagnosticError::new(
+                    class.src_id,
+                    class.span(),
+                    format!(
+                        "class {}: special method {} defined more than once",
+                        class.name, name
+                    ),
+                ));
+            }
+            defined.push(name.to_string());
+        }
+    }
+    Ok(slots)
+}
+
+fn method_slot<'a>(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+    method: &'a ForeignMethod,
+) -> Result<Option<Slot<'a>>> {
+    let name = match PYTHON_METHOD_ATTRS
+        .iter()
+        .find(|name| method.unknown_attrs.iter().any(|attr| attr == *name))
+    {
+        Some(name) => *name,
+        None => return Ok(None),
+    };
+    let args_count = match method.variant {
+        MethodVariant::Method(_) => method.fn_decl.inputs.len() - 1,
+        _ => {
+            return Err(DiagnosticError::new(
+                class.src_id,
+                method.span(),
+                format!("{} can be used only for method with self argument", name),
+            ))
+        }
+    };
+    let ret_rust_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&extract_return_type(&method.fn_decl.output), class.src_id);
+    let returns_option = ast::if_option_return_some_type(&ret_rust_ty).is_some();
+    let expected_args_count = if name == "__getitem__" { 1 } else { 0 };
+    if args_count != expected_args_count {
+        return Err(DiagnosticError::new(
+            class.src_id,
+            method.span(),
+            format!(
+                "{} method should have {} argument(s) besides self",
+                name, expected_args_count
+            ),
+        ));
+    }
+    let protocol = match name {
+        "__len__" if is_integer(&ret_rust_ty) => ReturnProtocol::Len,
+        "__len__" => {
+            return Err(DiagnosticError::new(
+                class.src_id,
+                method.span(),
+                "__len__ method should return integer",
+            ))
+        }
+        "__getitem__" if returns_option => ReturnProtocol::IndexErrorIfNone,
+        "__next__" if returns_option => ReturnProtocol::StopIterationIfNone,
+        "__next__" => {
+            return Err(DiagnosticError::new(
+                class.src_id,
+                method.span(),
+                "__next__ method should return Option",
+            ))
+        }
+        _ => ReturnProtocol::Value,
+    };
+    Ok(Some(Slot::Method {
+        name,
+        method,
+        protocol,
+    }))
+}
+
+fn is_integer(rust_ty: &RustType) -> bool {
+    matches!(
+        rust_ty.normalized_name.as_str(),
+        "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "usize" | "isize"
+    )
+}
+
+/// Generate Python type and conversion of value returned from Rust,
+/// according to `protocol`
+pub(in crate::python) fn generate_conversion_for_protocol(
+    ctx: &mut PythonContext,
+    ret_rust_ty: &RustType,
+    protocol: ReturnProtocol,
+    method_span: Span,
+    src_id: SourceId,
+    rust_call: TokenStream,
+) -> Result<(Type, TokenStream)> {
+    match protocol {
+        ReturnProtocol::Value => {
+            generate_conversion_for_return(ret_rust_ty, method_span, src_id, ctx, rust_call)
+        }
+        ReturnProtocol::Len => {
+            let conversion = if ret_rust_ty.normalized_name == "usize" {
+                rust_call
+            } else {
+                quote! { (#rust_call) as usize }
+            };
+            Ok((parse_type!(usize), conversion))
+        }
+        ReturnProtocol::Unit => Ok((parse_type!(()), rust_call)),
+        ReturnProtocol::IndexErrorIfNone | ReturnProtocol::StopIterationIfNone => {
+            let inner_ty = ast::if_option_return_some_type(ret_rust_ty)
+                .expect("Internal error: protocol requires Option");
+            let inner_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&inner_ty, src_id);
+            let (py_inner_ty, inner_conversion) = generate_conversion_for_return(
+                &inner_rust_ty,
+                method_span,
+                src_id,
+                ctx,
+                quote! { x },
+            )?;
+            if protocol == ReturnProtocol::StopIterationIfNone {
+                Ok((
+                    parse_type!(Option<#py_inner_ty>),
+                    quote! {
+                        match #rust_call {
+                            Some(x) => Some(#inner_conversion),
+                            None => None,
+                        }
+                    },
+                ))
+            } else {
+                let index_error =
+                    py_err_new(ctx, index_error_type(ctx), quote! { "index out of range" });
+                Ok((
+                    py_inner_ty,
+                    quote! {
+                        match #rust_call {
+                            Some(x) => #inner_conversion,
+                            None => return Err(#index_error),
+                        }
+                    },
+                ))
+            }
+        }
+    }
+}
+
+pub(in crate::python) fn generate_slot_code(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+    slot: &Slot,
+) -> Result<TokenStream> {
+    let (name, method, protocol) = match slot {
+        Slot::Method {
+            name,
+            method,
+            protocol,
+        } => (name, method, protocol),
+        Slot::IterSelf => return Ok(generate_iter_self(ctx, class)),
+        Slot::Format { name, format } => {
+            let name: Ident = parse(name, class.src_id)?;
+            let this = self_ref(ctx, class)?;
+            return Ok(generate_slot_method(
+                ctx,
+                &name,
+                quote! { String },
+                quote! { format!(#format, #this) },
+            ));
+        }
+        Slot::Hash => {
+            let name: Ident = parse("__hash__", class.src_id)?;
+            let this = self_ref(ctx, class)?;
+            return Ok(generate_slot_method(
+                ctx,
+                &name,
+                quote! { u64 },
+                quote! {{
+                    use std::hash::{Hash, Hasher};
+                    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                    Hash::hash(#this, &mut hasher);
+                    hasher.finish()
+                }},
+            ));
+        }
+        Slot::Compare { ordering } => return generate_compare(ctx, class, *ordering),
+    };
+    let name: Ident = parse(name, class.src_id)?;
+    generate_method_code_as(class, method, ctx, name, *protocol)
+}
+
+/// `&self_type` from the Python object
+fn self_ref(ctx: &mut PythonContext, class: &ForeignClassInfo) -> Result<TokenStream> {
+    let self_type = class.self_type_as_ty();
+    let self_ref_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&parse_type! {&#self_type}, class.src_id);
+    Ok(generate_conversion_for_argument(
+        &self_ref_ty,
+        class.span(),
+        class.src_id,
+        ctx,
+        "self",
+        true,
+    )?
+    .1)
+}
+
+fn generate_slot_method(
+    ctx: &PythonContext,
+    name: &Ident,
+    ret_type: TokenStream,
+    body: TokenStream,
+) -> TokenStream {
+    let py_result = py_result_type(ctx);
+    match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! {
+            def #name(&self) -> #py_result<#ret_type> {
+                #[allow(unused)]
+                use super::*;
+                Ok(#body)
+            }
+        },
+        PythonBinding::PyO3 => quote! {
+            fn #name(&self, py: pyo3::Python<'_>) -> #py_result<#ret_type> {
+                #[allow(unused)]
+                use super::*;
+                Ok(#body)
+            }
+        },
+    }
+}
+
+fn generate_iter_self(ctx: &PythonContext, class: &ForeignClassInfo) -> TokenStream {
+    let class_name = &class.name;
+    match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! {
+            def __iter__(&self) -> cpython::PyResult<#class_name> {
+                Ok(cpython::PyClone::clone_ref(self, py))
+            }
+        },
+        PythonBinding::PyO3 => quote! {
+            fn __iter__(slf: pyo3::PyRef<'_, Self>) -> pyo3::PyRef<'_, Self> {
+                slf
+            }
+        },
+    }
+}
+
+fn generate_compare(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+    ordering: bool,
+) -> Result<TokenStream> {
+    let self_type = class.self_type_as_ty();
+    let self_ref_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&parse_type! {&#self_type}, class.src_id);
+    let this = self_ref(ctx, class)?;
+    let (other_py_type, other) = generate_conversion_for_argument(
+        &self_ref_ty,
+        class.span(),
+        class.src_id,
+        ctx,
+        "other",
+        false,
+    )?;
+    // Mutex can not be locked twice, so comparison of object with itself is handled separately
+    let py_mod: Ident = parse(&py_wrapper_mod_name(&class.name.to_string()), class.src_id)?;
+    let same_object = match storage_smart_pointer_for_class(class, ctx.conv_map)?.pointer_type {
+        PointerType::Mutex => Some(quote! {
+            std::ptr::eq(
+                super::#py_mod::rust_instance(self, py),
+                super::#py_mod::rust_instance(&other, py),
+            )
+        }),
+        PointerType::ArcMutex => Some(quote! {
+            std::sync::Arc::ptr_eq(
+                super::#py_mod::rust_instance(self, py),
+                super::#py_mod::rust_instance(&other, py),
+            )
+        }),
+        _ => None,
+    };
+    let compare = |func: TokenStream| match same_object {
+        Some(ref same_object) => quote! {
+            if #same_object {
+                let this = super::#py_mod::rust_instance(self, py).lock().unwrap();
+                #func(&*this, &*this)
+            } else {
+                #func(#this, #other)
+            }
+        },
+        None => quote! { #func(#this, #other) },
+    };
+    let mut ops = vec![
+        ("__eq__", quote! { Eq }, compare(quote! { PartialEq::eq })),
+        ("__ne__", quote! { Ne }, compare(quote! { PartialEq::ne })),
+    ];
+    if ordering {
+        ops.extend_from_slice(&[
+            ("__lt__", quote! { Lt }, compare(quote! { PartialOrd::lt })),
+            ("__le__", quote! { Le }, compare(quote! { PartialOrd::le })),
+            ("__gt__", quote! { Gt }, compare(quote! { PartialOrd::gt })),
+            ("__ge__", quote! { Ge }, compare(quote! { PartialOrd::ge })),
+        ]);
+    }
+    match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => {
+            // rust-cpython supports only `__richcmp__`
+            let arms = ops.iter().map(|(_, op, code)| {
+                quote! { cpython::CompareOp::#op => #code, }
+            });
+            let not_implemented = if ordering {
+                TokenStream::new()
+            } else {
+                quote! { _ => return Ok(py.NotImplemented()), }
+            };
+            Ok(quote! {
+                def __richcmp__(&self, other: #other_py_type, op: cpython::CompareOp)
+                    -> cpython::PyResult<cpython::PyObject> {
+                    #[allow(unused)]
+                    use super::*;
+                    let ret = match op {
+                        #( #arms )*
+                        #not_implemented
+                    };
+                    Ok(cpython::PythonObject::into_object(
+                        cpython::ToPyObject::into_py_object(ret, py),
+                    ))
+                }
+            })
+        }
+        PythonBinding::PyO3 => {
+            // PyO3 returns `NotImplemented` if `other` has another type
+            let methods = ops
+                .iter()
+                .map(|(name, _, code)| {
+                    let name: Ident = parse(name, class.src_id)?;
+                    Ok(quote! {
+                        fn #name(&self, py: pyo3::Python<'_>, other: #other_py_type)
+                            -> pyo3::PyResult<bool> {
+                            #[allow(unused)]
+                            use super::*;
+                            Ok(#code)
+                        }
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(quote! { #( #methods )* })
+        }
+    }
+}
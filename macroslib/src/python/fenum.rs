@@ -0,0 +1,271 @@
+//! `foreign_enum!` with data: class per item of enum,
+//! fields of item are available as read-only properties
+
+use super::*;
+use crate::types::{ForeignEnumItem, ForeignEnumItemField};
+
+pub(in crate::python) const ENUM_WITH_DATA_TRAIT_NAME: &str = "SwigForeignEnumWithData";
+
+/// Python class for item of enum with data
+pub(in crate::python) fn item_class_name(
+    enum_info: &ForeignEnumInfo,
+    item: &ForeignEnumItem,
+) -> Ident {
+    Ident::new(
+        &format!("{}{}", enum_info.name, item.name),
+        item.name.span(),
+    )
+}
+
+struct PyEnumField<'a> {
+    name: &'a Ident,
+    /// Type of field in Python class
+    py_ty: Type,
+    /// Module with `from_u32` for C-like enum
+    c_like_enum_mod: Option<Ident>,
+}
+
+fn map_enum_field<'a>(
+    ctx: &mut PythonContext,
+    enum_info: &ForeignEnumInfo,
+    field: &'a ForeignEnumItemField,
+) -> Result<PyEnumField<'a>> {
+    let rust_type = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&field.ty, enum_info.src_id);
+    if rust_type.normalized_name != "& str" && is_cpython_supported_type(&rust_type) {
+        return Ok(PyEnumField {
+            name: &field.name,
+            py_ty: rust_type.ty.clone(),
+            c_like_enum_mod: None,
+        });
+    }
+    if rust_type
+        .implements
+        .contains_path(&parse(ENUM_TRAIT_NAME, enum_info.src_id)?)
+    {
+        return Ok(PyEnumField {
+            name: &field.name,
+            py_ty: parse_type!(u32),
+            c_like_enum_mod: Some(parse(
+                &py_wrapper_mod_name(&rust_type.normalized_name),
+                enum_info.src_id,
+            )?),
+        });
+    }
+    Err(DiagnosticError::new(
+        enum_info.src_id,
+        field.ty.span(),
+        format!(
+            "Unsupported type of field of enum: {}, only primitive types, String \
+             and C-like enums are supported",
+            rust_type
+        ),
+    ))
+}
+
+pub(in crate::python) fn generate_enum_with_data(
+    ctx: &mut PythonContext,
+    enum_info: &ForeignEnumInfo,
+) -> Result<(TokenStream, TokenStream)> {
+    let enum_name = &enum_info.name;
+    let wrapper_mod_name = parse::<Ident>(
+        &py_wrapper_mod_name(&enum_name.to_string()),
+        enum_info.src_id,
+    )?;
+    let docstring = enum_info.doc_comments.as_slice().join("\n");
+    let enum_name_str = enum_name.to_string();
+    let type_error = py_err_new(
+        ctx,
+        type_error_type(ctx),
+        quote! { format!("object is not item of enum {}", #enum_name_str) },
+    );
+
+    let mut items_code = Vec::with_capacity(enum_info.items.len());
+    let mut arms_to_py = Vec::with_capacity(enum_info.items.len());
+    let mut checks_from_py = Vec::with_capacity(enum_info.items.len());
+    let mut module_initialization = vec![module_add_class(ctx, &wrapper_mod_name, enum_name)];
+    for item in &enum_info.items {
+        let fields = item
+            .fields
+            .as_slice()
+            .iter()
+            .map(|f| map_enum_field(ctx, enum_info, f))
+            .collect::<Result<Vec<_>>>()?;
+        let class_name = item_class_name(enum_info, item);
+        let item_docstring = item.doc_comments.as_slice().join("\n");
+        let names = fields.iter().map(|f| f.name).collect::<Vec<_>>();
+        let py_types = fields.iter().map(|f| &f.py_ty).collect::<Vec<_>>();
+        let rust_path = &item.rust_name;
+        let pattern = item.fields.rust_pattern(&parse_quote!(super::#rust_path));
+        let to_py_conv = fields.iter().map(|f| {
+            let name = f.name;
+            if f.c_like_enum_mod.is_some() {
+                quote! { let #name = #name as u32; }
+            } else {
+                TokenStream::new()
+            }
+        });
+        let from_py_conv = fields.iter().map(|f| {
+            let name = f.name;
+            match f.c_like_enum_mod {
+                Some(ref enum_mod) => {
+                    quote! { let #name = super::#enum_mod::from_u32(py, #name)?; }
+                }
+                None => TokenStream::new(),
+            }
+        });
+        match ctx.cfg.python_binding {
+            PythonBinding::RustCPython => {
+                let data_names = names
+                    .iter()
+                    .map(|name| Ident::new(&format!("swig_{}", name), name.span()))
+                    .collect::<Vec<_>>();
+                items_code.push(quote! {
+                    py_class!(pub class #class_name |py| {
+                        static __doc__  = #item_docstring;
+                        #( data #data_names: #py_types; )*
+
+                        def __new__(_cls #(, #names: #py_types )*) -> cpython::PyResult<#class_name> {
+                            #class_name::create_instance(py #(, #names )*)
+                        }
+
+                        #(
+                            @property def #names(&self) -> cpython::PyResult<#py_types> {
+                                Ok(self.#data_names(py).clone())
+                            }
+                        )*
+                    });
+                });
+                arms_to_py.push(quote! {
+                    #pattern => {
+                        #( #to_py_conv )*
+                        Ok(cpython::PythonObject::into_object(
+                            #class_name::create_instance(py #(, #names )*)?,
+                        ))
+                    }
+                });
+                checks_from_py.push(if names.is_empty() {
+                    quote! {
+                        if value.cast_as::<#class_name>(py).is_ok() {
+                            return Ok(#pattern);
+                        }
+                    }
+                } else {
+                    quote! {
+                        if let Ok(x) = value.cast_as::<#class_name>(py) {
+                            #( let #names = x.#data_names(py).clone(); )*
+                            #( #from_py_conv )*
+                            return Ok(#pattern);
+                        }
+                    }
+                });
+            }
+            PythonBinding::PyO3 => {
+                let module_name = &ctx.cfg.module_name;
+                items_code.push(quote! {
+                    #[doc = #item_docstring]
+                    #[pyo3::pyclass(module = #module_name, extends = #enum_name)]
+                    pub struct #class_name {
+                        #( #[pyo3(get)] #names: #py_types, )*
+                    }
+
+                    #[pyo3::pymethods]
+                    impl #class_name {
+                        #[new]
+                        fn new(#( #names: #py_types ),*) -> (Self, #enum_name) {
+                            (#class_name { #( #names ),* }, #enum_name {})
+                        }
+                    }
+                });
+                arms_to_py.push(quote! {
+                    #pattern => {
+                        #( #to_py_conv )*
+                        let init = pyo3::PyClassInitializer::from(#enum_name {})
+                            .add_subclass(#class_name { #( #names ),* });
+                        Ok(pyo3::Py::new(py, init)?.into_any())
+                    }
+                });
+                checks_from_py.push(if names.is_empty() {
+                    quote! {
+                        if value.bind(py).downcast::<#class_name>().is_ok() {
+                            return Ok(#pattern);
+                        }
+                    }
+                } else {
+                    quote! {
+                        if let Ok(x) = value.bind(py).downcast::<#class_name>() {
+                            let x = x.borrow();
+                            #( let #names = x.#names.clone(); )*
+                            #( #from_py_conv )*
+                            return Ok(#pattern);
+                        }
+                    }
+                });
+            }
+        }
+        module_initialization.push(module_add_class(ctx, &wrapper_mod_name, &class_name));
+    }
+
+    let py_object = py_object_type(ctx);
+    let py_result = py_result_type(ctx);
+    let python = match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! { cpython::Python },
+        PythonBinding::PyO3 => quote! { pyo3::Python },
+    };
+    let enum_class_code = match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! {
+            py_class!(pub class #enum_name |py| {
+                static __doc__  = #docstring;
+            });
+        },
+        PythonBinding::PyO3 => {
+            let module_name = &ctx.cfg.module_name;
+            quote! {
+                #[doc = #docstring]
+                #[pyo3::pyclass(module = #module_name, subclass)]
+                pub struct #enum_name {}
+            }
+        }
+    };
+    let class_code = quote! {
+        mod #wrapper_mod_name {
+            use super::*;
+            #enum_class_code
+
+            #( #items_code )*
+
+            pub fn to_py(py: #python, value: super::#enum_name) -> #py_result<#py_object> {
+                match value {
+                    #( #arms_to_py ),*
+                }
+            }
+
+            pub fn from_py(py: #python, value: #py_object) -> #py_result<super::#enum_name> {
+                #( #checks_from_py )*
+                Err(#type_error)
+            }
+        }
+    };
+
+    let enum_ti: Type =
+        ast::parse_ty_with_given_span(&enum_name.to_string(), enum_info.name.span())
+            .map_err(|err| DiagnosticError::from_syn_err(enum_info.src_id, err))?;
+    ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &enum_ti,
+        &[ENUM_WITH_DATA_TRAIT_NAME],
+        enum_info.src_id,
+    );
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(
+            enum_info.name.to_string(),
+            (enum_info.src_id, enum_info.name.span()),
+        ),
+        provides_by_module: vec![],
+        into_from_rust: None,
+        from_into_rust: None,
+        name_prefix: None,
+    })?;
+
+    Ok((class_code, quote! { #( #module_initialization )* }))
+}
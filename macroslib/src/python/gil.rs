@@ -0,0 +1,144 @@
+//! Release of the GIL during call of Rust code with `py.allow_threads`,
+//! for methods marked with `#[release_gil]`, or for all methods of class
+//! with `#[derive(ReleaseGil)]`
+
+use super::*;
+use crate::extension::{RELEASE_GIL_ATTR, RELEASE_GIL_DERIVE};
+use syn::visit::{self, Visit};
+
+/// Should the GIL be released during call of `method`.
+/// The GIL protects arguments that borrow data owned by Python, so for such methods
+/// `#[release_gil]` is an error, and `#[derive(ReleaseGil)]` on class is ignored.
+pub(in crate::python) fn release_gil_for_method(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+) -> Result<bool> {
+    let explicit = method.unknown_attrs.iter().any(|x| x == RELEASE_GIL_ATTR);
+    if !explicit && !class.derive_list.iter().any(|x| x == RELEASE_GIL_DERIVE) {
+        return Ok(false);
+    }
+    let skip_args_count = if let MethodVariant::Method(_) = method.variant {
+        1
+    } else {
+        0
+    };
+    for arg in method.fn_decl.inputs.iter().skip(skip_args_count) {
+        let named_arg = arg
+            .as_named_arg()
+            .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
+        if borrows(&named_arg.ty) {
+            if !explicit {
+                return Ok(false);
+            }
+            return Err(DiagnosticError::new(
+                class.src_id,
+                method.span(),
+                format!(
+                    "class {}, method {}: can not release the GIL, argument {} borrows data owned by Python",
+                    class.name,
+                    method.short_name(),
+                    named_arg.name
+                ),
+            ));
+        }
+    }
+    let ret_rust_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&extract_return_type(&method.fn_decl.output), class.src_id);
+    if borrows(&ret_rust_ty.ty) {
+        if !explicit {
+            return Ok(false);
+        }
+        return Err(DiagnosticError::new(
+            class.src_id,
+            method.span(),
+            format!(
+                "class {}, method {}: can not release the GIL, return type borrows data",
+                class.name,
+                method.short_name(),
+            ),
+        ));
+    }
+    Ok(true)
+}
+
+/// Arguments are converted before release of the GIL, but `self` is locked
+/// (if class is stored in `Mutex`) after, so thread that waits for the lock
+/// doesn't block other Python threads.
+/// Returns statements to run before the call and the call itself.
+pub(in crate::python) fn generate_call_without_gil(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+    args_names: &[Ident],
+    args_convertions: &[TokenStream],
+) -> Result<(Vec<TokenStream>, TokenStream)> {
+    let mut prelude = args_names
+        .iter()
+        .zip(args_convertions)
+        .filter(|(name, convertion)| convertion.to_string() != name.to_string())
+        .map(|(name, convertion)| quote! { let #name = #convertion; })
+        .collect::<Vec<_>>();
+    let mut call_args = args_names
+        .iter()
+        .map(|name| name.into_token_stream())
+        .collect::<Vec<_>>();
+    if let MethodVariant::Method(self_variant) = method.variant {
+        let self_type = class.self_type_as_ty();
+        let self_type_ty = match self_variant {
+            SelfTypeVariant::Rptr => parse_type! {&#self_type},
+            SelfTypeVariant::RptrMut => parse_type! {&mut #self_type},
+            _ => parse_type! {#self_type},
+        };
+        let self_rust_ty = ctx
+            .conv_map
+            .find_or_alloc_rust_type(&self_type_ty, class.src_id);
+        let (reference_type, self_rust_ty_unref) =
+            get_reference_info_and_inner_type(&self_rust_ty, ctx.conv_map, class.src_id);
+        let smart_pointer_info = smart_pointer(&self_rust_ty_unref, ctx.conv_map, class.src_id);
+        let class_smart_pointer = storage_smart_pointer_for_class(class, ctx.conv_map)?;
+        let py_mod: Ident = parse(&py_wrapper_mod_name(&class.name.to_string()), class.src_id)?;
+        prelude.push(quote! {
+            let rust_instance = super::#py_mod::rust_instance(self, py);
+        });
+        call_args.insert(
+            0,
+            generate_deref(
+                class,
+                class_smart_pointer.pointer_type,
+                smart_pointer_info.pointer_type,
+                reference_type,
+                quote! { rust_instance },
+                method.span(),
+                class.src_id,
+            )?,
+        );
+    }
+    let rust_call = generate_rust_call(method, &call_args, class.src_id)?;
+    Ok((prelude, quote! { py.allow_threads(move || #rust_call) }))
+}
+
+/// Does type contain reference or lifetime, like `&str` or `Option<&T>`
+pub(in crate::python) fn borrows(ty: &Type) -> bool {
+    struct CatchReference(bool);
+    impl<'ast> Visit<'ast> for CatchReference {
+        fn visit_type_reference(&mut self, reference: &'ast syn::TypeReference) {
+            match reference.lifetime {
+                Some(ref lifetime) if lifetime.ident == "static" => {
+                    visit::visit_type_reference(self, reference)
+                }
+                _ => self.0 = true,
+            }
+        }
+        fn visit_lifetime(&mut self, lifetime: &'ast syn::Lifetime) {
+            if lifetime.ident != "static" {
+                self.0 = true;
+            }
+            visit::visit_lifetime(self, lifetime)
+        }
+    }
+    let mut catch_reference = CatchReference(false);
+    catch_reference.visit_type(ty);
+    catch_reference.0
+}
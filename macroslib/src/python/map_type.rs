@@ -0,0 +1,306 @@
+//! Conversions of Rust types described by `foreign_typemap!` rules,
+//! for types that Python backend doesn't handle by itself
+
+use super::*;
+use crate::{
+    error::SourceIdSpan,
+    typemap::{
+        ast::TyParamsSubstList, ty::TraitNamesSet, ExpandedFType, FTypeLeftRightPair,
+        MapToForeignFlag, RTypeConvRule, TypeMapConvRuleInfo, TypeMapConvRuleInfoExpanderHelper,
+    },
+};
+use log::debug;
+use petgraph::Direction;
+use smol_str::SmolStr;
+use std::{collections::BTreeSet, rc::Rc};
+
+/// Result of mapping of Rust type with help of typemap rules
+pub(in crate::python) struct PyTypemapConversion {
+    /// Type that rust-cpython/PyO3 convert to/from Python object
+    pub(in crate::python) py_type: Type,
+    /// Conversion code, it takes and produces variable with the name given to `map_type`
+    pub(in crate::python) code: String,
+    /// Type annotation for `.pyi` stubs
+    pub(in crate::python) type_hint: SmolStr,
+    /// Names like "typing.Optional", required by `type_hint`
+    pub(in crate::python) req_modules: Vec<SmolStr>,
+}
+
+struct PythonContextForArg<'a, 'b> {
+    ctx: &'a mut PythonContext<'b>,
+    arg_ty_span: SourceIdSpan,
+    direction: Direction,
+}
+
+/// Find conversion for `rust_type` (from Rust to Python for `Direction::Outgoing`,
+/// and from Python to Rust for `Direction::Incoming`).
+/// At first the not generic rules are checked (like `Vec<u8>` -> `bytes`),
+/// then generic rules (like `Option<T>`).
+pub(in crate::python) fn map_type(
+    ctx: &mut PythonContext,
+    rust_type: &RustType,
+    direction: Direction,
+    arg_ty_span: SourceIdSpan,
+    var_name: &str,
+) -> Result<Option<PyTypemapConversion>> {
+    debug!("map_type: {} {:?}", rust_type, direction);
+    if let Some(ftype_idx) = ctx.conv_map.map_through_conversation_to_foreign(
+        rust_type.to_idx(),
+        direction,
+        MapToForeignFlag::FastSearch,
+        arg_ty_span,
+        calc_this_type_for_method,
+    ) {
+        let ftype = &ctx.conv_map[ftype_idx];
+        let rule = match direction {
+            Direction::Outgoing => ftype.into_from_rust.as_ref(),
+            Direction::Incoming => ftype.from_into_rust.as_ref(),
+        }
+        .expect("Internal error: foreign type was found without conversion rule");
+        if rule.intermediate.is_some() {
+            return Err(DiagnosticError::new2(
+                ftype.src_id_span(),
+                format!(
+                    "f_type {}: conversion code on foreign side is not supported for Python",
+                    ftype.name
+                ),
+            ));
+        }
+        let type_hint = ftype.typename();
+        let req_modules = ftype.provides_by_module.clone();
+        let py_rust_ty = rule.rust_ty;
+        let (from, to) = match direction {
+            Direction::Outgoing => (rust_type.to_idx(), py_rust_ty),
+            Direction::Incoming => (py_rust_ty, rust_type.to_idx()),
+        };
+        let (mut code_deps, code) =
+            ctx.conv_map
+                .convert_rust_types(from, to, var_name, var_name, "#error", arg_ty_span)?;
+        ctx.rust_code.append(&mut code_deps);
+        return Ok(Some(PyTypemapConversion {
+            py_type: ctx.conv_map[py_rust_ty].ty.clone(),
+            code,
+            type_hint,
+            req_modules,
+        }));
+    }
+
+    let idx_subst_map: Option<(Rc<TypeMapConvRuleInfo>, TyParamsSubstList)> =
+        ctx.conv_map.generic_rules().iter().find_map(|grule| {
+            grule
+                .is_ty_subst_of_my_generic_rtype(&rust_type.ty, direction, |ty, traits| -> bool {
+                    is_ty_implement_traits(ctx.conv_map, ty, traits)
+                })
+                .map(|sm| (grule.clone(), sm.into()))
+        });
+    let (grule, subst_list) = match idx_subst_map {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    debug!(
+        "map_type: we found generic rule for {}: {:?}",
+        rust_type, subst_list
+    );
+    if grule.c_types.is_some() || grule.generic_c_types.is_some() || !grule.f_code.is_empty() {
+        return Err(DiagnosticError::new(
+            grule.src_id,
+            grule.span,
+            "Can not handle C types or foreign code for Python",
+        ));
+    }
+    let subst_map = subst_list.as_slice().into();
+    let new_rule = grule
+        .subst_generic_params(
+            subst_map,
+            direction,
+            &mut PythonContextForArg {
+                ctx,
+                arg_ty_span,
+                direction,
+            },
+        )
+        .map_err(|err| {
+            err.add_span_note(
+                (grule.src_id, grule.span),
+                "subst. of generic params into rule failed",
+            )
+        })?;
+    let (rule, ftype_rules) = match direction {
+        Direction::Outgoing => (new_rule.rtype_left_to_right, new_rule.ftype_left_to_right),
+        Direction::Incoming => (new_rule.rtype_right_to_left, new_rule.ftype_right_to_left),
+    };
+    let (py_type, conv_code) = match rule {
+        Some(RTypeConvRule {
+            right_ty: Some(right_ty),
+            code: Some(code),
+            ..
+        }) => (right_ty, code),
+        _ => {
+            return Err(DiagnosticError::new(
+                grule.src_id,
+                grule.span,
+                format!(
+                    "rule for {} should have r_type with conversion code for {:?} direction",
+                    rust_type, direction
+                ),
+            ))
+        }
+    };
+    let ftype_rule = match ftype_rules.as_slice() {
+        [ftype_rule] if ftype_rule.code.is_none() && ftype_rule.cfg_option.is_none() => {
+            ftype_rule
+        }
+        _ => {
+            return Err(DiagnosticError::new(
+                grule.src_id,
+                grule.span,
+                format!(
+                    "rule for {} should have exactly one f_type without code and options for {:?} direction",
+                    rust_type, direction
+                ),
+            ))
+        }
+    };
+    let type_hint = match ftype_rule.left_right_ty {
+        FTypeLeftRightPair::OnlyRight(ref fname) => fname.name.clone(),
+        FTypeLeftRightPair::OnlyLeft(ref fname) | FTypeLeftRightPair::Both(ref fname, _) => {
+            return Err(DiagnosticError::new(
+                grule.src_id,
+                fname.sp,
+                "f_type should contain only Python type name",
+            ))
+        }
+    };
+    let to_typename = match direction {
+        Direction::Outgoing => ctx
+            .conv_map
+            .find_or_alloc_rust_type(&py_type, grule.src_id)
+            .typename()
+            .to_string(),
+        Direction::Incoming => rust_type.typename().to_string(),
+    };
+    Ok(Some(PyTypemapConversion {
+        py_type,
+        code: conv_code.generate_code(var_name, var_name, &to_typename, "#error"),
+        type_hint,
+        req_modules: ftype_rule
+            .req_modules
+            .iter()
+            .map(|m| m.name.clone())
+            .collect(),
+    }))
+}
+
+impl<'a, 'b> TypeMapConvRuleInfoExpanderHelper for PythonContextForArg<'a, 'b> {
+    fn swig_i_type(&mut self, ty: &syn::Type, _opt_arg: Option<&str>) -> Result<syn::Type> {
+        let (src_id, span) = self.arg_ty_span;
+        let rust_ty = self.ctx.conv_map.find_or_alloc_rust_type(ty, src_id);
+        let (py_type, _) = match self.direction {
+            Direction::Outgoing => {
+                generate_conversion_for_return(&rust_ty, span, src_id, self.ctx, quote! {x})?
+            }
+            Direction::Incoming => {
+                generate_conversion_for_argument(&rust_ty, span, src_id, self.ctx, "x", false)?
+            }
+        };
+        Ok(py_type)
+    }
+    fn swig_from_rust_to_i_type(
+        &mut self,
+        ty: &syn::Type,
+        in_var_name: &str,
+        out_var_name: &str,
+    ) -> Result<String> {
+        let (src_id, span) = self.arg_ty_span;
+        let rust_ty = self.ctx.conv_map.find_or_alloc_rust_type(ty, src_id);
+        let out_var: Ident = parse(out_var_name, src_id)?;
+        let (_, conversion) =
+            generate_conversion_for_return(&rust_ty, span, src_id, self.ctx, quote! {#out_var})?;
+        Ok(bind_and_convert(in_var_name, out_var_name, conversion))
+    }
+    fn swig_from_i_type_to_rust(
+        &mut self,
+        ty: &syn::Type,
+        in_var_name: &str,
+        out_var_name: &str,
+    ) -> Result<String> {
+        let (src_id, span) = self.arg_ty_span;
+        let rust_ty = self.ctx.conv_map.find_or_alloc_rust_type(ty, src_id);
+        let (_, conversion) = generate_conversion_for_argument(
+            &rust_ty,
+            span,
+            src_id,
+            self.ctx,
+            out_var_name,
+            false,
+        )?;
+        Ok(bind_and_convert(in_var_name, out_var_name, conversion))
+    }
+    fn swig_f_type(&mut self, ty: &syn::Type, param1: Option<&str>) -> Result<ExpandedFType> {
+        if let Some(param) = param1 {
+            return Err(DiagnosticError::new2(
+                self.arg_ty_span,
+                format!("Invalid argument '{}' for swig_f_type", param),
+            ));
+        }
+        let (src_id, span) = self.arg_ty_span;
+        let rust_ty = self.ctx.conv_map.find_or_alloc_rust_type(ty, src_id);
+        let mut typing_imports = BTreeSet::new();
+        let name = pyi::py_type_hint(
+            self.ctx,
+            &rust_ty,
+            span,
+            src_id,
+            self.direction,
+            &mut typing_imports,
+        )?;
+        Ok(ExpandedFType {
+            name: name.into(),
+            provides_by_module: typing_imports.into_iter().collect(),
+        })
+    }
+    fn swig_foreign_to_i_type(&mut self, _ty: &syn::Type, _var_name: &str) -> Result<String> {
+        Err(DiagnosticError::new2(
+            self.arg_ty_span,
+            "swig_foreign_to_i_type is not supported for Python",
+        ))
+    }
+    fn swig_foreign_from_i_type(&mut self, _ty: &syn::Type, _var_name: &str) -> Result<String> {
+        Err(DiagnosticError::new2(
+            self.arg_ty_span,
+            "swig_foreign_from_i_type is not supported for Python",
+        ))
+    }
+}
+
+/// `in_var_name` may be expression, like `$p.0`,
+/// so at first it is moved to `out_var_name` and then converted
+fn bind_and_convert(in_var_name: &str, out_var_name: &str, conversion: TokenStream) -> String {
+    let mut code = String::new();
+    if in_var_name != out_var_name {
+        code.push_str(&format!("let {} = {};\n", out_var_name, in_var_name));
+    }
+    code.push_str(&format!("let {} = {};\n", out_var_name, conversion));
+    code
+}
+
+fn calc_this_type_for_method(_: &TypeMap, class: &ForeignClassInfo) -> Option<Type> {
+    class.self_desc.as_ref().map(|x| x.self_type.clone())
+}
+
+fn is_ty_implement_traits(tmap: &TypeMap, ty: &syn::Type, traits: &TraitNamesSet) -> bool {
+    if let Some(rty) = tmap.ty_to_rust_type_checked(ty) {
+        for tname in traits.iter() {
+            if !rty.implements.contains_path(tname) {
+                return false;
+            }
+        }
+        true
+    } else {
+        println!(
+            "cargo:warning=mapping types: type {} unknown",
+            DisplayToTokens(ty)
+        );
+        false
+    }
+}
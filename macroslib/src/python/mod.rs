@@ -1,29 +1,57 @@
+mod async_fn;
+mod dunder;
+mod fenum;
+mod gil;
+mod map_type;
+mod pyi;
+mod shim;
+
 use crate::typemap::ty::RustType;
 use crate::{
     error::Result,
-    extension::{ClassExtHandlers, MethodExtHandlers},
+    extension::{ClassExtHandlers, EnumExtHandlers, MethodExtHandlers},
     source_registry::SourceId,
     typemap::{
-        ast::{GenericTypeConv, TypeName},
-        ty::ForeignTypeS,
+        ast::{DisplayToTokens, GenericTypeConv, TypeName},
+        ty::{ForeignConversationRule, ForeignTypeS},
+        utils::remove_files_if,
         TypeConvCode,
     },
     types::{
-        ForeignClassInfo, ForeignEnumInfo, ForeignInterface, ForeignMethod, ItemToExpand,
-        MethodVariant, SelfTypeVariant,
+        FieldAccessor, FieldAccessorKind, ForeignClassInfo, ForeignEnumInfo, ForeignInterface,
+        ForeignInterfaceMethod, ForeignMethod, ItemToExpand, MethodVariant, SelfTypeVariant,
     },
-    DiagnosticError, LanguageGenerator, PythonConfig, SourceCode, TypeMap,
+    DiagnosticError, LanguageGenerator, PythonBinding, PythonConfig, SourceCode, TypeMap,
+    CLONE_TRAIT, COPY_TRAIT,
 };
 use crate::{extension::ExtHandlers, typemap::ast};
+use dunder::ReturnProtocol;
 use heck::SnakeCase;
+use petgraph::Direction;
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use quote::ToTokens;
-use std::ops::Deref;
+use rustc_hash::FxHashSet;
+use std::{ops::Deref, path::PathBuf};
 use syn::parse_quote;
-use syn::{Ident, Type};
+use syn::{spanned::Spanned, Ident, Type};
 
 const ENUM_TRAIT_NAME: &str = "SwigForeignEnum";
+const INTERFACE_TRAIT_NAME: &str = "SwigForeignInterface";
+
+/// Derives of `foreign_class!` that Python backend handles by itself,
+/// besides `extension::PYTHON_CLASS_DERIVES`
+static PYTHON_BACKEND_DERIVES: [&str; 2] = [CLONE_TRAIT, COPY_TRAIT];
+
+struct PythonContext<'a> {
+    cfg: &'a PythonConfig,
+    conv_map: &'a mut TypeMap,
+    rust_code: Vec<TokenStream>,
+    generated_foreign_files: FxHashSet<PathBuf>,
+    class_ext_handlers: &'a ClassExtHandlers,
+    method_ext_handlers: &'a MethodExtHandlers,
+    enum_ext_handlers: &'a EnumExtHandlers,
+}
 
 impl LanguageGenerator for PythonConfig {
     fn expand_items(
@@ -32,88 +60,140 @@ impl LanguageGenerator for PythonConfig {
         _pointer_target_width: usize,
         _code: &[SourceCode],
         items: Vec<ItemToExpand>,
-        _remove_not_generated_files: bool,
+        remove_not_generated_files: bool,
         ext_handlers: ExtHandlers,
     ) -> Result<Vec<TokenStream>> {
+        if let Some(rule) = conv_map.take_not_merged_not_generic_rules().first() {
+            return Err(DiagnosticError::new(
+                rule.src_id,
+                rule.span,
+                "foreign_typemap! rule with code or options for foreign side is not supported for Python",
+            ));
+        }
+        let mut ctx = PythonContext {
+            cfg: self,
+            conv_map,
+            rust_code: vec![],
+            generated_foreign_files: FxHashSet::default(),
+            class_ext_handlers: ext_handlers.class_ext_handlers,
+            method_ext_handlers: ext_handlers.method_ext_handlers,
+            enum_ext_handlers: ext_handlers.enum_ext_handlers,
+        };
+        if ItemToExpand::has_async_methods(&items) {
+            async_fn::generate_runtime(&mut ctx)?;
+        }
         for item in &items {
-            if let ItemToExpand::Class(ref fclass) = item {
-                self.register_class(conv_map, fclass)?;
+            match item {
+                ItemToExpand::Class(ref fclass) => register_class(&mut ctx, fclass)?,
+                ItemToExpand::Interface(ref finterface) => {
+                    register_interface(&mut ctx, finterface)?
+                }
+                ItemToExpand::Enum(_) => {}
             }
         }
         let mut code = Vec::with_capacity(items.len());
         let mut module_initialization = Vec::with_capacity(items.len());
-        for item in items {
+        for item in &items {
             let (class_code, initialization) = match item {
-                ItemToExpand::Class(fclass) => self.generate_class(
-                    conv_map,
-                    &fclass,
-                    ext_handlers.class_ext_handlers,
-                    ext_handlers.method_ext_handlers,
-                )?,
-                ItemToExpand::Enum(fenum) => self.generate_enum(conv_map, &fenum)?,
-                ItemToExpand::Interface(finterface) => {
-                    self.generate_interface(conv_map, &finterface)?
+                ItemToExpand::Class(ref fclass) => generate_class(&mut ctx, fclass)?,
+                ItemToExpand::Enum(ref fenum) => generate_enum(&mut ctx, fenum)?,
+                ItemToExpand::Interface(ref finterface) => {
+                    generate_interface(&mut ctx, finterface)?
                 }
             };
             code.push(class_code);
             module_initialization.push(initialization);
         }
-        code.push(self.generate_module_initialization(&module_initialization)?);
+        code.push(generate_module_initialization(
+            &ctx,
+            &module_initialization,
+        )?);
+        if let Some(ref stubs_output_dir) = self.stubs_output_dir {
+            pyi::generate_stubs(&mut ctx, &items, stubs_output_dir)?;
+        }
+        if remove_not_generated_files {
+            if let Some(ref shims_output_dir) = self.shims_output_dir {
+                let generated_foreign_files = &ctx.generated_foreign_files;
+                remove_files_if(shims_output_dir, |path| {
+                    if let Some(ext) = path.extension() {
+                        if ext == "py" && !generated_foreign_files.contains(path) {
+                            return true;
+                        }
+                    }
+                    false
+                })
+                .map_err(DiagnosticError::map_any_err_to_our_err)?;
+            }
+        }
+        code.append(&mut ctx.rust_code);
         Ok(code)
     }
 }
 
-impl PythonConfig {
-    fn register_class(&self, conv_map: &mut TypeMap, class: &ForeignClassInfo) -> Result<()> {
-        if let Some(ref self_desc) = class.self_desc {
-            conv_map.find_or_alloc_rust_type(&self_desc.self_type, class.src_id);
-        }
-        Ok(())
+fn register_class(ctx: &mut PythonContext, class: &ForeignClassInfo) -> Result<()> {
+    if let Some(ref self_desc) = class.self_desc {
+        ctx.conv_map
+            .find_or_alloc_rust_type(&self_desc.self_type, class.src_id);
     }
+    Ok(())
+}
 
-    /// Generate class code and module initialization code for this class.
-    fn generate_class(
-        &self,
-        conv_map: &mut TypeMap,
-        class: &ForeignClassInfo,
-        class_ext_handlers: &ClassExtHandlers,
-        method_ext_handlers: &MethodExtHandlers,
-    ) -> Result<(TokenStream, TokenStream)> {
-        if !class_ext_handlers.is_empty() || !method_ext_handlers.is_empty() {
-            return Err(DiagnosticError::new(
-                class.src_id,
-                class.span(),
-                format!(
-                    "class {}: has attributes, this is not supported for python",
-                    class.name
-                ),
-            ));
-        }
+fn register_interface(ctx: &mut PythonContext, interface: &ForeignInterface) -> Result<()> {
+    let boxed_trait_ty = boxed_interface_type(interface)?;
+    let boxed_trait_rust_ty = ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &boxed_trait_ty,
+        &[INTERFACE_TRAIT_NAME],
+        interface.src_id,
+    );
+    let rule = ForeignConversationRule {
+        rust_ty: boxed_trait_rust_ty.to_idx(),
+        intermediate: None,
+    };
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(interface.name.to_string(), interface.src_id_span()),
+        provides_by_module: vec![],
+        into_from_rust: Some(rule.clone()),
+        from_into_rust: Some(rule),
+        name_prefix: None,
+    })?;
+    Ok(())
+}
 
-        let class_name = &class.name;
-        let wrapper_mod_name =
-            parse::<Ident>(&py_wrapper_mod_name(&class_name.to_string()), class.src_id)?;
-        let (rust_instance_field, rust_instance_getter) =
-            generate_rust_instance_field_and_methods(class, conv_map)?;
-        let methods_code = class
-            .methods
-            .iter()
-            .map(|m| generate_method_code(class, m, conv_map))
-            .collect::<Result<Vec<_>>>()?;
-        let mut doc_comments = class.doc_comments.clone();
-        if let Some(constructor) = class
-            .methods
-            .iter()
-            .find(|m| m.variant == MethodVariant::Constructor)
-        {
-            // Python API doesn't allow to add docstring to the special methods (slots),
-            // including __new__ and __init__.
-            // The convention is, to document the constructor in class's docstring.
-            doc_comments.push("".to_owned());
-            doc_comments.extend_from_slice(&constructor.doc_comments);
-        }
-        let docstring = doc_comments.as_slice().join("\n");
-        let class_code = quote! {
+/// Generate class code and module initialization code for this class.
+fn generate_class(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+) -> Result<(TokenStream, TokenStream)> {
+    shim::generate_class_shim(ctx, class)?;
+
+    let class_name = &class.name;
+    let wrapper_mod_name =
+        parse::<Ident>(&py_wrapper_mod_name(&class_name.to_string()), class.src_id)?;
+    let (rust_instance_field, rust_instance_getter) =
+        generate_rust_instance_field_and_methods(class, ctx)?;
+    let mut methods_code = class
+        .methods
+        .iter()
+        .map(|m| generate_method_code(class, m, ctx))
+        .collect::<Result<Vec<_>>>()?;
+    for slot in dunder::class_slots(ctx, class)? {
+        methods_code.push(dunder::generate_slot_code(ctx, class, &slot)?);
+    }
+    let mut doc_comments = class.doc_comments.clone();
+    if let Some(constructor) = class
+        .methods
+        .iter()
+        .find(|m| m.variant == MethodVariant::Constructor)
+    {
+        // Python API doesn't allow to add docstring to the special methods (slots),
+        // including __new__ and __init__.
+        // The convention is, to document the constructor in class's docstring.
+        doc_comments.push("".to_owned());
+        doc_comments.extend_from_slice(&constructor.doc_comments);
+    }
+    let docstring = doc_comments.as_slice().join("\n");
+    let class_code = match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! {
             mod #wrapper_mod_name {
                 use super::*;
                 #[allow(unused)]
@@ -127,37 +207,64 @@ impl PythonConfig {
 
                 #rust_instance_getter
             }
-        };
+        },
+        PythonBinding::PyO3 => {
+            let module_name = &ctx.cfg.module_name;
+            quote! {
+                mod #wrapper_mod_name {
+                    use super::*;
+                    #[doc = #docstring]
+                    #[pyo3::pyclass(module = #module_name)]
+                    pub struct #class_name {
+                        #rust_instance_field
+                    }
 
-        let module_initialization_code = quote! {
-            {
-                m.add_class::<#wrapper_mod_name::#class_name>(py)?;
+                    #[allow(unused)]
+                    #[pyo3::pymethods]
+                    impl #class_name {
+                        #( #methods_code )*
+                    }
+
+                    #rust_instance_getter
+                }
             }
-        };
-        Ok((class_code, module_initialization_code))
-    }
+        }
+    };
 
-    fn generate_enum(
-        &self,
-        conv_map: &mut TypeMap,
-        enum_info: &ForeignEnumInfo,
-    ) -> Result<(TokenStream, TokenStream)> {
-        let enum_name = &enum_info.name;
-        let wrapper_mod_name = parse::<Ident>(
-            &py_wrapper_mod_name(&enum_name.to_string()),
-            enum_info.src_id,
-        )?;
-        let foreign_variants = enum_info.items.iter().map(|item| &item.name);
-        let rust_variants = enum_info
-            .items
-            .iter()
-            .map(|item| &item.rust_name)
-            .collect::<Vec<_>>();
-        let rust_variants_ref_1 = &rust_variants;
-        let rust_variants_ref_2 = &rust_variants;
-        let enum_name_str = enum_name.to_string();
-        let docstring = enum_info.doc_comments.as_slice().join("\n");
-        let class_code = quote! {
+    let module_initialization_code = module_add_class(ctx, &wrapper_mod_name, class_name);
+    Ok((class_code, module_initialization_code))
+}
+
+fn generate_enum(
+    ctx: &mut PythonContext,
+    enum_info: &ForeignEnumInfo,
+) -> Result<(TokenStream, TokenStream)> {
+    shim::generate_enum_shim(ctx, enum_info)?;
+    if !enum_info.is_c_like() {
+        return fenum::generate_enum_with_data(ctx, enum_info);
+    }
+    let enum_name = &enum_info.name;
+    let wrapper_mod_name = parse::<Ident>(
+        &py_wrapper_mod_name(&enum_name.to_string()),
+        enum_info.src_id,
+    )?;
+    let foreign_variants = enum_info.items.iter().map(|item| &item.name);
+    let rust_variants = enum_info
+        .items
+        .iter()
+        .map(|item| &item.rust_name)
+        .collect::<Vec<_>>();
+    let rust_variants_ref_1 = &rust_variants;
+    let rust_variants_ref_2 = &rust_variants;
+    let enum_name_str = enum_name.to_string();
+    let docstring = enum_info.doc_comments.as_slice().join("\n");
+    let value_error = py_err_new(
+        ctx,
+        value_error_type(ctx),
+        quote! { format!("{} is not valid value for enum {}", value, #enum_name_str) },
+    );
+    let class_code = match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! {
             mod #wrapper_mod_name {
                 py_class!(pub class #enum_name |py| {
                     static __doc__  = #docstring;
@@ -166,81 +273,165 @@ impl PythonConfig {
 
                 pub fn from_u32(py: cpython::Python, value: u32) -> cpython::PyResult<super::#enum_name> {
                     #( if value == super::#rust_variants_ref_1 as u32 { return Ok(super::#rust_variants_ref_2); } )*
-                    Err(cpython::PyErr::new::<cpython::exc::ValueError, _>(
-                        py, format!("{} is not valid value for enum {}", value, #enum_name_str)
-                    ))
+                    Err(#value_error)
                 }
             }
-        };
-        let enum_ti: Type =
-            ast::parse_ty_with_given_span(&enum_name.to_string(), enum_info.name.span())
-                .map_err(|err| DiagnosticError::from_syn_err(enum_info.src_id, err))?;
-        conv_map.find_or_alloc_rust_type_that_implements(
-            &enum_ti,
-            &[ENUM_TRAIT_NAME],
-            enum_info.src_id,
-        );
-        let enum_ftype = ForeignTypeS {
-            name: TypeName::new(
-                enum_info.name.to_string(),
-                (enum_info.src_id, enum_info.name.span()),
-            ),
-            provides_by_module: vec![],
-            into_from_rust: None,
-            from_into_rust: None,
-            name_prefix: None,
-        };
-        conv_map.alloc_foreign_type(enum_ftype)?;
+        },
+        PythonBinding::PyO3 => {
+            let module_name = &ctx.cfg.module_name;
+            quote! {
+                mod #wrapper_mod_name {
+                    #[doc = #docstring]
+                    #[pyo3::pyclass(module = #module_name)]
+                    pub struct #enum_name {}
 
-        let module_initialization_code = quote! {
-            {
-                m.add_class::<#wrapper_mod_name::#enum_name>(py)?;
+                    #[allow(non_upper_case_globals)]
+                    #[pyo3::pymethods]
+                    impl #enum_name {
+                        #( #[classattr] const #foreign_variants: u32 = super::#rust_variants_ref_1 as u32; )*
+                    }
+
+                    pub fn from_u32(_py: pyo3::Python, value: u32) -> pyo3::PyResult<super::#enum_name> {
+                        #( if value == super::#rust_variants_ref_1 as u32 { return Ok(super::#rust_variants_ref_2); } )*
+                        Err(#value_error)
+                    }
+                }
             }
-        };
-        Ok((class_code, module_initialization_code))
-    }
+        }
+    };
+    let enum_ti: Type =
+        ast::parse_ty_with_given_span(&enum_name.to_string(), enum_info.name.span())
+            .map_err(|err| DiagnosticError::from_syn_err(enum_info.src_id, err))?;
+    ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &enum_ti,
+        &[ENUM_TRAIT_NAME],
+        enum_info.src_id,
+    );
+    let enum_ftype = ForeignTypeS {
+        name: TypeName::new(
+            enum_info.name.to_string(),
+            (enum_info.src_id, enum_info.name.span()),
+        ),
+        provides_by_module: vec![],
+        into_from_rust: None,
+        from_into_rust: None,
+        name_prefix: None,
+    };
+    ctx.conv_map.alloc_foreign_type(enum_ftype)?;
 
-    fn generate_interface(
-        &self,
-        _conv_map: &mut TypeMap,
-        _interface: &ForeignInterface,
-    ) -> Result<(TokenStream, TokenStream)> {
-        unimplemented!("Interfaces are currently unsupported for Python.")
-    }
+    let module_initialization_code = module_add_class(ctx, &wrapper_mod_name, enum_name);
+    Ok((class_code, module_initialization_code))
+}
 
-    fn generate_module_initialization(
-        &self,
-        module_initialization_code: &[TokenStream],
-    ) -> Result<TokenStream> {
-        let module_name = parse::<syn::Ident>(&self.module_name, SourceId::none())?;
-        let module_init =
-            parse::<syn::Ident>(&format!("init{}", &self.module_name), SourceId::none())?;
-        let module_py_init =
-            parse::<syn::Ident>(&format!("PyInit_{}", &self.module_name), SourceId::none())?;
-        let registration_code = quote! {
+/// Generate Rust struct, that holds Python object and implements
+/// the callback's trait by calling methods of this object.
+fn generate_interface(
+    ctx: &mut PythonContext,
+    interface: &ForeignInterface,
+) -> Result<(TokenStream, TokenStream)> {
+    let interface_name = &interface.name;
+    let wrapper_mod_name = parse::<Ident>(
+        &py_wrapper_mod_name(&interface_name.to_string()),
+        interface.src_id,
+    )?;
+    let trait_name = &interface.self_type.bounds[0];
+    let methods_code = interface
+        .items
+        .iter()
+        .map(|m| generate_interface_method_code(interface, m, ctx))
+        .collect::<Result<Vec<_>>>()?;
+    let py_object = py_object_type(ctx);
+    let interface_code = quote! {
+        mod #wrapper_mod_name {
+            #[allow(unused)]
+            use super::*;
+
+            pub struct #interface_name {
+                object: #py_object,
+            }
+
+            impl #interface_name {
+                pub fn new(object: #py_object) -> #interface_name {
+                    #interface_name { object }
+                }
+            }
+
+            impl super::#trait_name for #interface_name {
+                #( #methods_code )*
+            }
+        }
+    };
+    Ok((interface_code, TokenStream::new()))
+}
+
+fn generate_module_initialization(
+    ctx: &PythonContext,
+    module_initialization_code: &[TokenStream],
+) -> Result<TokenStream> {
+    let module_name = parse::<syn::Ident>(&ctx.cfg.module_name, SourceId::none())?;
+    if ctx.cfg.python_binding == PythonBinding::PyO3 {
+        return Ok(quote! {
             mod py_error {
-                py_exception!(#module_name, Error);
+                pyo3::create_exception!(#module_name, Error, pyo3::exceptions::PyException);
             }
 
-            py_module_initializer!(#module_name, #module_init, #module_py_init, |py, m| {
-                m.add(py, "Error", py_error::Error::type_object(py))?;
+            #[pyo3::pymodule]
+            fn #module_name(m: &pyo3::Bound<'_, pyo3::types::PyModule>) -> pyo3::PyResult<()> {
+                m.add("Error", m.py().get_type::<py_error::Error>())?;
                 #(#module_initialization_code)*
                 Ok(())
-            });
-        };
-        Ok(registration_code)
+            }
+        });
+    }
+    let module_init =
+        parse::<syn::Ident>(&format!("init{}", &ctx.cfg.module_name), SourceId::none())?;
+    let module_py_init = parse::<syn::Ident>(
+        &format!("PyInit_{}", &ctx.cfg.module_name),
+        SourceId::none(),
+    )?;
+    let registration_code = quote! {
+        mod py_error {
+            py_exception!(#module_name, Error);
+        }
+
+        py_module_initializer!(#module_name, #module_init, #module_py_init, |py, m| {
+            m.add(py, "Error", py_error::Error::type_object(py))?;
+            #(#module_initialization_code)*
+            Ok(())
+        });
+    };
+    Ok(registration_code)
+}
+
+fn module_add_class(
+    ctx: &PythonContext,
+    wrapper_mod_name: &Ident,
+    class_name: &Ident,
+) -> TokenStream {
+    match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! {
+            {
+                m.add_class::<#wrapper_mod_name::#class_name>(py)?;
+            }
+        },
+        PythonBinding::PyO3 => quote! {
+            {
+                m.add_class::<#wrapper_mod_name::#class_name>()?;
+            }
+        },
     }
 }
 
 fn generate_rust_instance_field_and_methods(
     class: &ForeignClassInfo,
-    conv_map: &mut TypeMap,
+    ctx: &mut PythonContext,
 ) -> Result<(TokenStream, TokenStream)> {
     if let Some(ref self_desc) = class.self_desc {
         let rust_self_type = &self_desc.self_type;
-        let storage_smart_pointer = storage_smart_pointer_for_class(class, conv_map)?;
+        let storage_smart_pointer = storage_smart_pointer_for_class(class, ctx.conv_map)?;
         if storage_smart_pointer.inner_ty.normalized_name
-            != conv_map
+            != ctx
+                .conv_map
                 .find_or_alloc_rust_type(rust_self_type, class.src_id)
                 .normalized_name
         {
@@ -253,6 +444,22 @@ fn generate_rust_instance_field_and_methods(
         let storage_type = wrap_type_for_class(&rust_self_type, storage_smart_pointer.pointer_type);
         let storage_type_ref = &storage_type;
         let class_name = &class.name;
+        if ctx.cfg.python_binding == PythonBinding::PyO3 {
+            return Ok((
+                quote! {
+                    rust_instance: #storage_type_ref,
+                },
+                quote! {
+                    pub fn rust_instance<'a>(class: &'a #class_name, _py: pyo3::Python) -> &'a #storage_type_ref {
+                        &class.rust_instance
+                    }
+
+                    pub fn create_instance(py: pyo3::Python, instance: #storage_type_ref) -> pyo3::PyResult<pyo3::Py<#class_name>> {
+                        pyo3::Py::new(py, #class_name { rust_instance: instance })
+                    }
+                },
+            ));
+        }
         Ok((
             quote! {
                 data rust_instance: #storage_type_ref;
@@ -287,13 +494,32 @@ fn generate_rust_instance_field_and_methods(
 fn generate_method_code(
     class: &ForeignClassInfo,
     method: &ForeignMethod,
-    conv_map: &mut TypeMap,
+    ctx: &mut PythonContext,
 ) -> Result<TokenStream> {
     if method.is_dummy_constructor() {
         return Ok(TokenStream::new());
     }
     let method_name = method_name(method, class.src_id)?;
-    let method_rust_path = &method.rust_id;
+    let protocol = match method.field_accessor() {
+        Some(FieldAccessor {
+            kind: FieldAccessorKind::Setter,
+            ..
+        }) => ReturnProtocol::Unit,
+        _ => ReturnProtocol::Value,
+    };
+    generate_method_code_as(class, method, ctx, method_name, protocol)
+}
+
+/// Generate code of method with given name, `protocol` describes
+/// how returned value is passed to Python
+fn generate_method_code_as(
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+    ctx: &mut PythonContext,
+    method_name: Ident,
+    protocol: ReturnProtocol,
+) -> Result<TokenStream> {
+    let is_special_method = method_name.to_string().starts_with("__");
     let skip_args_count = if let MethodVariant::Method(_) = method.variant {
         1
     } else {
@@ -309,10 +535,11 @@ fn generate_method_code(
                 .as_named_arg()
                 .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
             let (arg_type, arg_convertion) = generate_conversion_for_argument(
-                &conv_map.find_or_alloc_rust_type(&named_arg.ty, class.src_id),
+                &ctx.conv_map
+                    .find_or_alloc_rust_type(&named_arg.ty, class.src_id),
                 method.span(),
                 class.src_id,
-                conv_map,
+                ctx,
                 &named_arg.name,
                 true,
             )?;
@@ -321,39 +548,133 @@ fn generate_method_code(
         .collect::<Result<Vec<_>>>()?
         .into_iter()
         .unzip();
-    if let Some(self_convertion) = self_type_conversion(class, method, conv_map)? {
-        args_convertions.insert(0, self_convertion);
-    }
+    let args_names = args_list
+        .iter()
+        .map(|(name, _)| parse::<Ident>(name, class.src_id))
+        .collect::<Result<Vec<_>>>()?;
+    let (mut prelude, rust_call) = if method.is_async {
+        async_fn::generate_async_call(ctx, class, method, &args_names, &args_convertions)?
+    } else if gil::release_gil_for_method(ctx, class, method)? {
+        gil::generate_call_without_gil(ctx, class, method, &args_names, &args_convertions)?
+    } else {
+        if let Some(self_convertion) = self_type_conversion(class, method, ctx)? {
+            args_convertions.insert(0, self_convertion);
+        }
+        (
+            vec![],
+            generate_rust_call(method, &args_convertions, class.src_id)?,
+        )
+    };
+    let field_accessor = method.field_accessor();
+    let is_setter = matches!(
+        field_accessor,
+        Some(FieldAccessor {
+            kind: FieldAccessorKind::Setter,
+            ..
+        })
+    );
     let mut args_list_tokens = args_list
         .into_iter()
         .map(|(name, t)| {
-            parse(
-                &format!("{}: {}", name, t.into_token_stream().to_string()),
-                class.src_id,
-            )
+            let t = t.into_token_stream().to_string();
+            // rust-cpython passes `None` to setter if property is deleted
+            let t = if is_setter && ctx.cfg.python_binding == PythonBinding::RustCPython {
+                format!("Option<{}>", t)
+            } else {
+                t
+            };
+            parse(&format!("{}: {}", name, t), class.src_id)
         })
         .collect::<std::result::Result<Vec<TokenStream>, _>>()?;
-    if let MethodVariant::Method(_) = method.variant {
-        args_list_tokens.insert(0, parse("&self", class.src_id)?);
-    } else if method.variant == MethodVariant::Constructor {
-        args_list_tokens.insert(0, parse("_cls", class.src_id)?);
-    }
-    let attribute = if method.variant == MethodVariant::StaticMethod {
-        parse("@staticmethod", class.src_id)?
+    let attribute = match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => {
+            if let MethodVariant::Method(_) = method.variant {
+                args_list_tokens.insert(0, parse("&self", class.src_id)?);
+            } else if method.variant == MethodVariant::Constructor {
+                args_list_tokens.insert(0, parse("_cls", class.src_id)?);
+            }
+            match field_accessor {
+                Some(FieldAccessor {
+                    kind: FieldAccessorKind::Getter,
+                    ..
+                }) => quote! { @property },
+                Some(FieldAccessor {
+                    kind: FieldAccessorKind::Setter,
+                    name,
+                }) => {
+                    let delete_error = py_err_new(
+                        ctx,
+                        quote! { cpython::exc::AttributeError },
+                        quote! { "can't delete attribute" },
+                    );
+                    for name in &args_names {
+                        prelude.insert(
+                            0,
+                            quote! {
+                                let #name = match #name {
+                                    Some(x) => x,
+                                    None => return Err(#delete_error),
+                                };
+                            },
+                        );
+                    }
+                    quote! { @#name.setter }
+                }
+                None if method.variant == MethodVariant::StaticMethod => {
+                    parse("@staticmethod", class.src_id)?
+                }
+                None => TokenStream::new(),
+            }
+        }
+        PythonBinding::PyO3 => {
+            // PyO3 passes the GIL token only if it is requested explicitly
+            args_list_tokens.insert(0, parse("py: pyo3::Python<'_>", class.src_id)?);
+            let kind = match method.variant {
+                MethodVariant::Method(_) => {
+                    args_list_tokens.insert(0, parse("&self", class.src_id)?);
+                    match field_accessor {
+                        Some(FieldAccessor {
+                            kind: FieldAccessorKind::Getter,
+                            name,
+                        }) => quote! { #[getter(#name)] },
+                        Some(FieldAccessor {
+                            kind: FieldAccessorKind::Setter,
+                            name,
+                        }) => quote! { #[setter(#name)] },
+                        None => TokenStream::new(),
+                    }
+                }
+                MethodVariant::StaticMethod => quote! { #[staticmethod] },
+                MethodVariant::Constructor => quote! { #[new] },
+            };
+            // PyO3 doesn't allow signature for special methods and properties
+            if args_names.is_empty() || is_special_method || field_accessor.is_some() {
+                kind
+            } else {
+                // Explicit signature, otherwise PyO3 makes trailing `Option` arguments optional
+                quote! {
+                    #kind
+                    #[pyo3(signature = (#( #args_names ),*))]
+                }
+            }
+        }
+    };
+    let ret_rust_type = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&extract_return_type(&method.fn_decl.output), class.src_id);
+    let (return_type, rust_call_with_return_conversion) = if method.is_async {
+        async_fn::generate_spawn(ctx, class, method, rust_call)?
     } else {
-        TokenStream::new()
+        dunder::generate_conversion_for_protocol(
+            ctx,
+            &ret_rust_type,
+            protocol,
+            method.span(),
+            class.src_id,
+            rust_call,
+        )?
     };
-    let (return_type, rust_call_with_return_conversion) = generate_conversion_for_return(
-        &conv_map
-            .find_or_alloc_rust_type(&extract_return_type(&method.fn_decl.output), class.src_id),
-        method.span(),
-        class.src_id,
-        conv_map,
-        quote! {
-            #method_rust_path(#( #args_convertions ),*)
-        },
-    )?;
-    let docstring = if !method_name.to_string().starts_with("__") {
+    let docstring = if !is_special_method {
         parse::<TokenStream>(
             &("/// ".to_owned() + &method.doc_comments.as_slice().join("\n/// ")),
             class.src_id,
@@ -362,17 +683,207 @@ fn generate_method_code(
         // Python API doesn't support defining docstrings on the special methods (slots)
         quote! {}
     };
+    let def = match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! { def },
+        PythonBinding::PyO3 => quote! { fn },
+    };
+    let py_result = py_result_type(ctx);
+    let body = quote! {
+        #( #prelude )*
+        Ok(#rust_call_with_return_conversion)
+    };
+    let body = match ctx.cfg.catch_panics {
+        Some(catch_panics) => {
+            let with_backtrace = catch_panics.with_backtrace();
+            quote! { swig_py_catch_panic(py, #with_backtrace, || { #body }) }
+        }
+        None => body,
+    };
     Ok(quote! {
-        #docstring #attribute def #method_name(
+        #docstring #attribute #def #method_name(
             #( #args_list_tokens ),*
-        ) -> cpython::PyResult<#return_type> {
+        ) -> #py_result<#return_type> {
             #[allow(unused)]
             use super::*;
-            Ok(#rust_call_with_return_conversion)
+            #body
+        }
+    })
+}
+
+fn generate_interface_method_code(
+    interface: &ForeignInterface,
+    method: &ForeignInterfaceMethod,
+    ctx: &mut PythonContext,
+) -> Result<TokenStream> {
+    let src_id = interface.src_id;
+    let method_span = method.rust_name.span();
+    let rust_method_name = &method
+        .rust_name
+        .segments
+        .last()
+        .ok_or_else(|| DiagnosticError::new(src_id, method_span, "Empty trait function name"))?
+        .ident;
+    let py_method_name = method.name.to_string();
+    let self_arg: TokenStream = method.fn_decl.inputs[0].as_self_arg(src_id)?.into();
+    let (args_with_types, args_convertions): (Vec<_>, Vec<_>) = method
+        .fn_decl
+        .inputs
+        .iter()
+        .skip(1)
+        .enumerate()
+        .map(|(i, a)| {
+            let named_arg = a
+                .as_named_arg()
+                .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+            let arg_ident: Ident = parse(&format!("a{}", i), src_id)?;
+            let arg_ty = &named_arg.ty;
+            let (_, arg_convertion) = generate_conversion_for_return(
+                &ctx.conv_map.find_or_alloc_rust_type(arg_ty, src_id),
+                method_span,
+                src_id,
+                ctx,
+                quote! {#arg_ident},
+            )?;
+            Ok((quote! {#arg_ident: #arg_ty}, arg_convertion))
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+    let py_call = match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => {
+            let py_args = if args_convertions.is_empty() {
+                quote! {cpython::NoArgs}
+            } else {
+                quote! {( #( #args_convertions, )* )}
+            };
+            quote! { call_method(py, #py_method_name, #py_args, None) }
+        }
+        PythonBinding::PyO3 => {
+            if args_convertions.is_empty() {
+                quote! { call_method0(py, #py_method_name) }
+            } else {
+                quote! { call_method1(py, #py_method_name, ( #( #args_convertions, )* )) }
+            }
+        }
+    };
+    let rust_return_type = extract_return_type(&method.fn_decl.output);
+    let ok_err_types = ast::if_result_return_ok_err_types(
+        &ctx.conv_map
+            .find_or_alloc_rust_type(&rust_return_type, src_id),
+    );
+    let ok_type = match ok_err_types {
+        Some((ref ok_type, _)) => ok_type.clone(),
+        None => rust_return_type.clone(),
+    };
+    let call_with_return_conversion = if ok_type == parse_type! { () } {
+        quote! {
+            self.object.#py_call?;
+            Ok(())
+        }
+    } else {
+        if let Type::Reference(_) = ok_type {
+            return Err(DiagnosticError::new(
+                src_id,
+                method_span,
+                "Returning a reference from Python callback is not supported",
+            ));
+        }
+        let (ret_py_type, ret_convertion) = generate_conversion_for_argument(
+            &ctx.conv_map.find_or_alloc_rust_type(&ok_type, src_id),
+            method_span,
+            src_id,
+            ctx,
+            "ret",
+            false,
+        )?;
+        quote! {
+            let ret: #ret_py_type = self
+                .object
+                .#py_call?
+                .extract(py)?;
+            let ret = #ret_convertion;
+            Ok(ret)
+        }
+    };
+    let error_handling = if ok_err_types.is_some() {
+        // Python exception is converted to the error type of the callback,
+        // so the error type should implement `From<String>`.
+        quote! {
+            ret.map_err(|err| From::from(swig_py_err_to_string(py, err)))
+        }
+    } else if ok_type == parse_type! { () } {
+        quote! {
+            if let Err(err) = ret {
+                err.print(py);
+            }
+        }
+    } else {
+        quote! {
+            ret.unwrap_or_else(|err| {
+                panic!(
+                    "Python callback {} failed: {}",
+                    #py_method_name,
+                    swig_py_err_to_string(py, err)
+                )
+            })
         }
+    };
+    let output = &method.fn_decl.output;
+    let py_result = py_result_type(ctx);
+    let call_with_error_handling = quote! {
+        let ret: #py_result<#ok_type> = (|| {
+            #call_with_return_conversion
+        })();
+        #error_handling
+    };
+    Ok(match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! {
+            fn #rust_method_name(#self_arg, #( #args_with_types ),*) #output {
+                let gil = cpython::Python::acquire_gil();
+                let py = gil.python();
+                #call_with_error_handling
+            }
+        },
+        PythonBinding::PyO3 => quote! {
+            fn #rust_method_name(#self_arg, #( #args_with_types ),*) #output {
+                pyo3::Python::with_gil(|py| {
+                    #call_with_error_handling
+                })
+            }
+        },
     })
 }
 
+/// Call of Rust function, or of inline method with `self` in `this`
+/// and arguments in variables with the same names
+fn generate_rust_call(
+    method: &ForeignMethod,
+    args: &[TokenStream],
+    src_id: SourceId,
+) -> Result<TokenStream> {
+    match method.inline_block {
+        Some(ref block) => {
+            let mut names = method
+                .arg_names_without_self()
+                .map(|name| parse::<Ident>(name, src_id))
+                .collect::<Result<Vec<_>>>()?;
+            if let MethodVariant::Method(_) = method.variant {
+                names.insert(0, parse("this", src_id)?);
+            }
+            // `match` keeps temporaries, like lock guard of `self`, alive during the call
+            Ok(quote! {
+                match (#( #args, )*) {
+                    (#( #names, )*) => #block
+                }
+            })
+        }
+        None => {
+            let method_rust_path = &method.rust_id;
+            Ok(quote! { #method_rust_path(#( #args ),*) })
+        }
+    }
+}
+
 fn standard_method_name(method: &ForeignMethod, src_id: SourceId) -> Result<syn::Ident> {
     Ok(method
         .name_alias
@@ -385,6 +896,13 @@ fn standard_method_name(method: &ForeignMethod, src_id: SourceId) -> Result<syn:
 fn method_name(method: &ForeignMethod, src_id: SourceId) -> Result<syn::Ident> {
     if method.variant == MethodVariant::Constructor {
         parse("__new__", src_id)
+    } else if let Some(FieldAccessor {
+        kind: FieldAccessorKind::Getter,
+        name,
+    }) = method.field_accessor()
+    {
+        // rust-cpython takes name of property from name of getter
+        Ok(name.clone())
     } else {
         let name = standard_method_name(method, src_id)?;
         let name_str = name.to_string();
@@ -398,7 +916,7 @@ fn method_name(method: &ForeignMethod, src_id: SourceId) -> Result<syn::Ident> {
 fn self_type_conversion(
     class: &ForeignClassInfo,
     method: &ForeignMethod,
-    conv_map: &mut TypeMap,
+    ctx: &mut PythonContext,
 ) -> Result<Option<TokenStream>> {
     if let MethodVariant::Method(self_variant) = method.variant {
         let self_type = &class
@@ -419,10 +937,11 @@ fn self_type_conversion(
         };
         Ok(Some(
             generate_conversion_for_argument(
-                &conv_map.find_or_alloc_rust_type(&self_type_ty, class.src_id),
+                &ctx.conv_map
+                    .find_or_alloc_rust_type(&self_type_ty, class.src_id),
                 method.span(),
                 class.src_id,
-                conv_map,
+                ctx,
                 "self",
                 true,
             )?
@@ -447,16 +966,17 @@ fn generate_conversion_for_argument(
     rust_type: &RustType,
     method_span: Span,
     src_id: SourceId,
-    conv_map: &mut TypeMap,
+    ctx: &mut PythonContext,
     arg_name: &str,
     reference_allowed: bool,
 ) -> Result<(Type, TokenStream)> {
     let arg_name_ident: TokenStream = parse(arg_name, src_id)?;
+    let py_result = py_result_type(ctx);
     if is_cpython_supported_type(rust_type) {
         Ok((rust_type.ty.clone(), arg_name_ident))
     } else if let Some((ty, conversion)) = if_exported_class_generate_argument_conversion(
         rust_type,
-        conv_map,
+        ctx,
         &arg_name_ident,
         method_span,
         src_id,
@@ -474,52 +994,84 @@ fn generate_conversion_for_argument(
                 super::#enum_py_mod::from_u32(py, #arg_name_ident)?
             },
         ))
-    } else if let Some(inner) = ast::if_option_return_some_type(&rust_type) {
-        let (inner_py_type, inner_conversion) = generate_conversion_for_argument(
-            &conv_map.find_or_alloc_rust_type(&inner, src_id),
-            method_span,
-            src_id,
-            conv_map,
-            "inner",
-            false,
-        )?;
+    } else if rust_type
+        .implements
+        .contains_path(&parse(fenum::ENUM_WITH_DATA_TRAIT_NAME, src_id)?)
+    {
+        let enum_py_mod: Ident = parse(&py_wrapper_mod_name(&rust_type.normalized_name), src_id)?;
         Ok((
-            parse_type!(Option<#inner_py_type>),
+            py_object_type(ctx),
             quote! {
-                match #arg_name_ident {
-                    Some(inner) => Some(#inner_conversion),
-                    None => None,
-                }
+                super::#enum_py_mod::from_py(py, #arg_name_ident)?
             },
         ))
+    } else if rust_type
+        .implements
+        .contains_path(&parse(INTERFACE_TRAIT_NAME, src_id)?)
+    {
+        let interface_name = ctx
+            .conv_map
+            .find_foreign_type_related_to_rust_ty(rust_type.to_idx())
+            .map(|ftype| ctx.conv_map[ftype].typename())
+            .ok_or_else(|| {
+                DiagnosticError::new(
+                    src_id,
+                    method_span,
+                    format!("No callback registered for type: {}", rust_type),
+                )
+            })?;
+        let interface_py_mod: Ident = parse(&py_wrapper_mod_name(&interface_name), src_id)?;
+        let interface_ident: Ident = parse(&interface_name, src_id)?;
+        Ok((
+            py_object_type(ctx),
+            quote! {
+                Box::new(super::#interface_py_mod::#interface_ident::new(#arg_name_ident))
+            },
+        ))
+    } else if let Some(conversion) = map_type::map_type(
+        ctx,
+        rust_type,
+        Direction::Incoming,
+        (src_id, method_span),
+        arg_name,
+    )? {
+        let code: TokenStream = parse(&format!("{{ {} {} }}", conversion.code, arg_name), src_id)?;
+        Ok((conversion.py_type, code))
+    } else if let Some((elem, is_slice)) = if_numeric_buffer_return_elem_type(rust_type) {
+        let conversion = if is_slice {
+            quote! { #arg_name_ident.as_slice() }
+        } else {
+            quote! { #arg_name_ident.into_vec() }
+        };
+        Ok((parse_type!(super::SwigNumericSlice<#elem>), conversion))
     } else if let Some(inner) = if_type_slice_return_elem_type(&rust_type.ty, false) {
         let (inner_py_type, inner_conversion) = generate_conversion_for_argument(
-            &conv_map.find_or_alloc_rust_type(&inner, src_id),
+            &ctx.conv_map.find_or_alloc_rust_type(&inner, src_id),
             method_span,
             src_id,
-            conv_map,
+            ctx,
             "inner",
             false,
         )?;
         Ok((
             parse_type!(Vec<#inner_py_type>),
             quote! {
-                &#arg_name_ident.into_iter().map(|inner| Ok(#inner_conversion)).collect::<cpython::PyResult<Vec<_>>>()?
+                &#arg_name_ident.into_iter().map(|inner| Ok(#inner_conversion)).collect::<#py_result<Vec<_>>>()?
             },
         ))
     } else if let Some(inner) = if_vec_return_elem_type(&rust_type) {
         let (inner_py_type, inner_conversion) = generate_conversion_for_argument(
-            &conv_map.find_or_alloc_rust_type(&inner, src_id),
+            &ctx.conv_map.find_or_alloc_rust_type(&inner, src_id),
             method_span,
             src_id,
-            conv_map,
+            ctx,
             "inner",
             false,
         )?;
         Ok((
             parse_type!(Vec<#inner_py_type>),
             quote! {
-                #arg_name_ident.into_iter().map(|inner| Ok(#inner_conversion)).collect::<cpython::PyResult<Vec<_>>>()?
+                #arg_name_ident.into_iter().map(|inner| Ok(#inner_conversion)).collect::<#py_result<Vec<_>>>()?
             },
         ))
     } else if let Type::Reference(ref inner) = rust_type.ty {
@@ -531,10 +1083,11 @@ fn generate_conversion_for_argument(
             ));
         }
         let (inner_py_type, inner_conversion) = generate_conversion_for_argument(
-            &conv_map.find_or_alloc_rust_type(&inner.elem.deref(), src_id),
+            &ctx.conv_map
+                .find_or_alloc_rust_type(&inner.elem.deref(), src_id),
             method_span,
             src_id,
-            conv_map,
+            ctx,
             arg_name,
             false,
         )?;
@@ -557,12 +1110,13 @@ fn generate_conversion_for_return(
     rust_type: &RustType,
     method_span: Span,
     src_id: SourceId,
-    conv_map: &mut TypeMap,
+    ctx: &mut PythonContext,
     rust_call: TokenStream,
 ) -> Result<(Type, TokenStream)> {
+    let py_result = py_result_type(ctx);
     if rust_type.ty == parse_type! { () } {
         Ok((
-            parse_type!(cpython::PyObject),
+            py_object_type(ctx),
             quote! {
                 {#rust_call; py.None()}
             },
@@ -571,7 +1125,7 @@ fn generate_conversion_for_return(
         Ok((rust_type.ty.clone(), rust_call))
     } else if let Some((ty, conversion)) = if_exported_class_generate_return_conversion(
         &rust_type,
-        conv_map,
+        ctx,
         &rust_call,
         method_span,
         src_id,
@@ -587,111 +1141,138 @@ fn generate_conversion_for_return(
                 #rust_call as u32
             },
         ))
-    } else if let Some(inner) = ast::if_option_return_some_type(&rust_type) {
-        let (inner_py_type, inner_conversion) = generate_conversion_for_return(
-            &conv_map.find_or_alloc_rust_type(&inner, src_id),
+    } else if rust_type
+        .implements
+        .contains_path(&parse(fenum::ENUM_WITH_DATA_TRAIT_NAME, src_id)?)
+    {
+        let enum_py_mod: Ident = parse(&py_wrapper_mod_name(&rust_type.normalized_name), src_id)?;
+        Ok((
+            py_object_type(ctx),
+            quote! {
+                super::#enum_py_mod::to_py(py, #rust_call)?
+            },
+        ))
+    } else if let Some(iter_class) = ctx
+        .conv_map
+        .find_foreigner_class_with_such_this_type(&rust_type.ty, |_, fc| fc.iterator_type.clone())
+        .cloned()
+    {
+        // boxed Rust iterator is wrapped into class generated for it
+        let class_rust_ty = ctx
+            .conv_map
+            .find_or_alloc_rust_type(&iter_class.self_type_as_ty(), src_id);
+        let (mut code_deps, code) = ctx.conv_map.convert_rust_types(
+            rust_type.to_idx(),
+            class_rust_ty.to_idx(),
+            "ret",
+            "ret",
+            "#error",
+            (src_id, method_span),
+        )?;
+        ctx.rust_code.append(&mut code_deps);
+        let code: TokenStream = parse(&format!("{} ret", code), src_id)?;
+        generate_conversion_for_return(
+            &class_rust_ty,
             method_span,
             src_id,
-            conv_map,
-            quote! {inner},
-        )?;
+            ctx,
+            quote! {
+                {
+                    let ret = #rust_call;
+                    #code
+                }
+            },
+        )
+    } else if let Some(conversion) = map_type::map_type(
+        ctx,
+        rust_type,
+        Direction::Outgoing,
+        (src_id, method_span),
+        "ret",
+    )? {
+        let code: TokenStream = parse(&format!("{} ret", conversion.code), src_id)?;
         Ok((
-            parse_type!(Option<#inner_py_type>),
+            conversion.py_type,
             quote! {
-                match #rust_call {
-                    Some(inner) => Some(#inner_conversion),
-                    None => None
+                {
+                    let ret = #rust_call;
+                    #code
                 }
             },
         ))
+    } else if let Some((_, is_slice)) =
+        if_numeric_buffer_return_elem_type(rust_type).filter(|_| ctx.cfg.return_numeric_buffers)
+    {
+        let vec = if is_slice {
+            quote! { #rust_call.to_vec() }
+        } else {
+            rust_call
+        };
+        Ok((
+            py_object_type(ctx),
+            quote! {
+                swig_numeric_vec_to_py(py, #vec)?
+            },
+        ))
     } else if let Some(inner) = if_type_slice_return_elem_type(&rust_type.ty, false) {
         let (inner_py_type, inner_conversion) = generate_conversion_for_return(
-            &conv_map.find_or_alloc_rust_type(&inner, src_id),
+            &ctx.conv_map.find_or_alloc_rust_type(&inner, src_id),
             method_span,
             src_id,
-            conv_map,
+            ctx,
             quote! {inner},
         )?;
         Ok((
             parse_type!(Vec<#inner_py_type>),
             quote! {
-                #rust_call.iter().cloned().map(|inner| Ok(#inner_conversion)).collect::<cpython::PyResult<Vec<_>>>()?
+                #rust_call.iter().cloned().map(|inner| Ok(#inner_conversion)).collect::<#py_result<Vec<_>>>()?
             },
         ))
     } else if let Some(inner) = if_vec_return_elem_type(&rust_type) {
         let (inner_py_type, inner_conversion) = generate_conversion_for_return(
-            &conv_map.find_or_alloc_rust_type(&inner, src_id),
+            &ctx.conv_map.find_or_alloc_rust_type(&inner, src_id),
             method_span,
             src_id,
-            conv_map,
+            ctx,
             quote! {inner},
         )?;
         Ok((
             parse_type!(Vec<#inner_py_type>),
             quote! {
-                #rust_call.into_iter().map(|inner| Ok(#inner_conversion)).collect::<cpython::PyResult<Vec<_>>>()?
+                #rust_call.into_iter().map(|inner| Ok(#inner_conversion)).collect::<#py_result<Vec<_>>>()?
             },
         ))
     } else if let Some((inner_ok, _inner_err)) = ast::if_result_return_ok_err_types(&rust_type) {
         let (inner_py_type, inner_conversion) = generate_conversion_for_return(
-            &conv_map.find_or_alloc_rust_type(&inner_ok, src_id),
+            &ctx.conv_map.find_or_alloc_rust_type(&inner_ok, src_id),
             method_span,
             src_id,
-            conv_map,
+            ctx,
             quote! {ok_inner},
         )?;
+        let py_err = py_err_new(
+            ctx,
+            quote! { super::py_error::Error },
+            quote! { swig_collect_error_message(&err_inner) },
+        );
         Ok((
             parse_type!(#inner_py_type),
             quote! {
                 match #rust_call {
                     Ok(ok_inner) => #inner_conversion,
-                    Err(err_inner) => return Err(cpython::PyErr::new::<super::py_error::Error, _>(
-                        py,
-                        swig_collect_error_message(&err_inner)
-                    )),
+                    Err(err_inner) => return Err(#py_err),
                 }
             },
         ))
     } else if let Type::Reference(ref inner) = rust_type.ty {
         generate_conversion_for_return(
-            &conv_map.find_or_alloc_rust_type(&inner.elem.deref(), src_id),
+            &ctx.conv_map
+                .find_or_alloc_rust_type(&inner.elem.deref(), src_id),
             method_span,
             src_id,
-            conv_map,
+            ctx,
             quote! {(#rust_call).clone()},
         )
-    } else if let Type::Tuple(ref tuple) = rust_type.ty {
-        let (types, conversions): (Vec<_>, Vec<_>) = tuple
-            .elems
-            .iter()
-            .enumerate()
-            .map(|(i, ty)| {
-                let i_ident = syn::Index {
-                    index: i as u32,
-                    span: Span::call_site(),
-                };
-                generate_conversion_for_return(
-                    &conv_map.find_or_alloc_rust_type(ty, src_id),
-                    method_span,
-                    src_id,
-                    conv_map,
-                    quote! {tuple.#i_ident},
-                )
-            })
-            .collect::<Result<Vec<_>>>()?
-            .into_iter()
-            .unzip();
-        Ok((
-            parse_type! {( #( #types, )* )},
-            quote! {
-                {
-                    let tuple = #rust_call;
-                    (
-                        #( #conversions, )*
-                    )
-                }
-            },
-        ))
     } else {
         Err(DiagnosticError::new(
             src_id,
@@ -718,10 +1299,64 @@ fn extract_return_type(syn_return_type: &syn::ReturnType) -> Type {
     }
 }
 
+fn boxed_interface_type(interface: &ForeignInterface) -> Result<Type> {
+    let boxed_trait_name = format!("Box<dyn {}>", DisplayToTokens(&interface.self_type));
+    ast::parse_ty_with_given_span(&boxed_trait_name, interface.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(interface.src_id, err))
+}
+
 fn py_wrapper_mod_name(type_name: &str) -> String {
     format!("py_{}", type_name.to_snake_case())
 }
 
+fn py_result_type(ctx: &PythonContext) -> TokenStream {
+    match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! { cpython::PyResult },
+        PythonBinding::PyO3 => quote! { pyo3::PyResult },
+    }
+}
+
+fn py_object_type(ctx: &PythonContext) -> Type {
+    match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => parse_type!(cpython::PyObject),
+        PythonBinding::PyO3 => parse_type!(pyo3::PyObject),
+    }
+}
+
+fn value_error_type(ctx: &PythonContext) -> TokenStream {
+    match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! { cpython::exc::ValueError },
+        PythonBinding::PyO3 => quote! { pyo3::exceptions::PyValueError },
+    }
+}
+
+fn type_error_type(ctx: &PythonContext) -> TokenStream {
+    match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! { cpython::exc::TypeError },
+        PythonBinding::PyO3 => quote! { pyo3::exceptions::PyTypeError },
+    }
+}
+
+fn index_error_type(ctx: &PythonContext) -> TokenStream {
+    match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! { cpython::exc::IndexError },
+        PythonBinding::PyO3 => quote! { pyo3::exceptions::PyIndexError },
+    }
+}
+
+/// Code to create Python exception of type `exception_type`,
+/// rust-cpython requires `py` token for that, PyO3 creates exceptions lazily.
+fn py_err_new(ctx: &PythonContext, exception_type: TokenStream, msg: TokenStream) -> TokenStream {
+    match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => quote! {
+            cpython::PyErr::new::<#exception_type, _>(py, #msg)
+        },
+        PythonBinding::PyO3 => quote! {
+            pyo3::PyErr::new::<#exception_type, _>(#msg)
+        },
+    }
+}
+
 // `rust_cpython` provides access only to non-mutable reference of the wrapped Rust object.
 // What's more `rust_cpython` requires the object to be `Send + 'static`, because Python VM
 // can move it between threads without any control from Rust.
@@ -831,22 +1466,23 @@ fn get_reference_info_and_inner_type(
 
 fn if_exported_class_generate_return_conversion(
     rust_type: &RustType,
-    conv_map: &mut TypeMap,
+    ctx: &mut PythonContext,
     rust_call: &TokenStream,
     method_span: Span,
     src_id: SourceId,
 ) -> Result<Option<(Type, TokenStream)>> {
     let (reference_type, rust_type_unref) =
-        get_reference_info_and_inner_type(rust_type, conv_map, src_id);
-    let smart_pointer_info = smart_pointer(&rust_type_unref, conv_map, src_id);
-    let class = match conv_map
+        get_reference_info_and_inner_type(rust_type, ctx.conv_map, src_id);
+    let smart_pointer_info = smart_pointer(&rust_type_unref, ctx.conv_map, src_id);
+    let class = match ctx
+        .conv_map
         .find_foreigner_class_with_such_this_type(&smart_pointer_info.inner_ty.ty, |_, ft| {
             ft.self_desc.as_ref().map(|x| x.self_type.clone())
         }) {
         Some(fc) => fc.clone(),
         None => return Ok(None),
     };
-    let class_smart_pointer = storage_smart_pointer_for_class(&class, conv_map)?;
+    let class_smart_pointer = storage_smart_pointer_for_class(&class, ctx.conv_map)?;
     let rust_call_with_deref = if reference_type != Reference::None {
         if smart_pointer_info.pointer_type == PointerType::Mutex {
             return Err(DiagnosticError::new(
@@ -909,7 +1545,11 @@ Thus, the returned type must marked with `#[derive(Clone)]` or `#[derive(Copy)]`
     let conversion = quote! {
         super::#py_mod::create_instance(py, #rust_call_with_wrapper)?
     };
-    Ok(Some((parse_type!(super::#py_mod::#class_name), conversion)))
+    let py_type = match ctx.cfg.python_binding {
+        PythonBinding::RustCPython => parse_type!(super::#py_mod::#class_name),
+        PythonBinding::PyO3 => parse_type!(pyo3::Py<super::#py_mod::#class_name>),
+    };
+    Ok(Some((py_type, conversion)))
 }
 
 fn generate_wrapper_constructor_for_mutex(
@@ -1004,78 +1644,111 @@ Foreigner class {} is stored as `Box`, and can be returned eiter as `Box` or bar
 
 fn if_exported_class_generate_argument_conversion(
     rust_type: &RustType,
-    conv_map: &mut TypeMap,
+    ctx: &mut PythonContext,
     arg_name_ident: &TokenStream,
     method_span: Span,
     src_id: SourceId,
     reference_allowed: bool,
 ) -> Result<Option<(Type, TokenStream)>> {
     let (reference_type, rust_type_unref) =
-        get_reference_info_and_inner_type(rust_type, conv_map, src_id);
-    let smart_pointer_info = smart_pointer(&rust_type_unref, conv_map, src_id);
-    let class = match conv_map
+        get_reference_info_and_inner_type(rust_type, ctx.conv_map, src_id);
+    let smart_pointer_info = smart_pointer(&rust_type_unref, ctx.conv_map, src_id);
+    let class = match ctx
+        .conv_map
         .find_foreigner_class_with_such_this_type(&smart_pointer_info.inner_ty.ty, |_, ft| {
             ft.self_desc.as_ref().map(|x| x.self_type.clone())
         }) {
         Some(fc) => fc.clone(),
         None => return Ok(None),
     };
-    let class_smart_pointer = storage_smart_pointer_for_class(&class, conv_map)?;
+    let class_smart_pointer = storage_smart_pointer_for_class(&class, ctx.conv_map)?;
     let class_name = class.name.to_string();
     let py_mod_str = py_wrapper_mod_name(&class_name);
     let py_mod: Ident = parse(&py_mod_str, src_id)?;
-    let py_type: Type = if reference_allowed {
-        parse(&format!("&super::{}::{}", &py_mod_str, &class_name), src_id)?
-    } else {
-        parse(&format!("super::{}::{}", &py_mod_str, &class_name), src_id)?
+    let (py_type, rust_instance_code): (Type, TokenStream) = match ctx.cfg.python_binding {
+        PythonBinding::RustCPython if reference_allowed => (
+            parse(&format!("&super::{}::{}", &py_mod_str, &class_name), src_id)?,
+            quote! {
+                super::#py_mod::rust_instance(#arg_name_ident, py)
+            },
+        ),
+        PythonBinding::RustCPython => (
+            parse(&format!("super::{}::{}", &py_mod_str, &class_name), src_id)?,
+            quote! {
+                super::#py_mod::rust_instance(&#arg_name_ident, py)
+            },
+        ),
+        // `PyRef` holds borrow of the Python object, so it is used
+        // no matter if the reference is allowed or not.
+        PythonBinding::PyO3 => (
+            parse(
+                &format!("pyo3::PyRef<super::{}::{}>", &py_mod_str, &class_name),
+                src_id,
+            )?,
+            quote! {
+                super::#py_mod::rust_instance(&#arg_name_ident, py)
+            },
+        ),
     };
+    let deref_code = generate_deref(
+        &class,
+        class_smart_pointer.pointer_type,
+        smart_pointer_info.pointer_type,
+        reference_type,
+        rust_instance_code,
+        method_span,
+        src_id,
+    )?;
 
-    let rust_instance_code = if reference_allowed {
-        quote! {
-            super::#py_mod::rust_instance(#arg_name_ident, py)
-        }
-    } else {
-        quote! {
-            super::#py_mod::rust_instance(&#arg_name_ident, py)
-        }
-    };
-    let deref_code = match class_smart_pointer.pointer_type {
+    Ok(Some((py_type, deref_code)))
+}
+
+/// Code to get `arg_smart_pointer<arg_reference<T>>` from instance of class,
+/// stored as `class_storage_pointer<T>`
+fn generate_deref(
+    class: &ForeignClassInfo,
+    class_storage_pointer: PointerType,
+    arg_smart_pointer: PointerType,
+    arg_reference: Reference,
+    rust_instance_code: TokenStream,
+    method_span: Span,
+    src_id: SourceId,
+) -> Result<TokenStream> {
+    match class_storage_pointer {
         PointerType::ArcMutex => generate_deref_for_arc_mutex(
-            &class,
-            smart_pointer_info.pointer_type,
-            reference_type,
+            class,
+            arg_smart_pointer,
+            arg_reference,
             rust_instance_code,
             method_span,
             src_id,
-        )?,
+        ),
         PointerType::Arc => generate_deref_for_arc(
-            &class,
-            smart_pointer_info.pointer_type,
-            reference_type,
+            class,
+            arg_smart_pointer,
+            arg_reference,
             rust_instance_code,
             method_span,
             src_id,
-        )?,
+        ),
         PointerType::Mutex => generate_deref_for_mutex(
-            &class,
-            smart_pointer_info.pointer_type,
-            reference_type,
+            class,
+            arg_smart_pointer,
+            arg_reference,
             rust_instance_code,
             method_span,
             src_id,
-        )?,
+        ),
         PointerType::Box => generate_deref_for_box(
-            &class,
-            smart_pointer_info.pointer_type,
-            reference_type,
+            class,
+            arg_smart_pointer,
+            arg_reference,
             rust_instance_code,
             method_span,
             src_id,
-        )?,
+        ),
         _ => unreachable!("Class stored as None"),
-    };
-
-    Ok(Some((py_type, deref_code)))
+    }
 }
 
 fn generate_deref_for_mutex(
@@ -1298,3 +1971,25 @@ fn if_vec_return_elem_type(ty: &RustType) -> Option<Type> {
         .is_conv_possible(ty, None, |_| None)
         .map(|x| x.to_ty)
 }
+
+/// Element types of slices and `Vec`, that are passed via the buffer protocol
+/// (without conversion of each element to Python object).
+/// `u8` is not here, because `&[u8]` and `Vec<u8>` are mapped to `bytes`
+/// by the typemap rules.
+static NUMERIC_BUFFER_ELEM_TYPES: [&str; 9] =
+    ["i8", "i16", "i32", "i64", "u16", "u32", "u64", "f32", "f64"];
+
+/// If type is `&[T]` or `Vec<T>`, where `T` is numeric primitive type,
+/// return `T` and is type slice or not
+fn if_numeric_buffer_return_elem_type(ty: &RustType) -> Option<(Type, bool)> {
+    let (elem, is_slice) = match if_type_slice_return_elem_type(&ty.ty, false) {
+        Some(elem) => (elem.clone(), true),
+        None => (if_vec_return_elem_type(ty)?, false),
+    };
+    let elem_name = elem.to_token_stream().to_string();
+    if NUMERIC_BUFFER_ELEM_TYPES.contains(&elem_name.as_str()) {
+        Some((elem, is_slice))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,519 @@
+//! Generation of `.pyi` stub file, so type checkers and IDEs
+//! know signatures of the generated Python module
+
+use super::*;
+use crate::{
+    file_cache::{FileWriteCache, NoNeedFsOpsRegistration},
+    WRITE_TO_MEM_FAILED_MSG,
+};
+use petgraph::Direction;
+use smol_str::SmolStr;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    io::Write as IoWrite,
+    path::Path,
+};
+
+pub(in crate::python) fn generate_stubs(
+    ctx: &mut PythonContext,
+    items: &[ItemToExpand],
+    output_dir: &Path,
+) -> Result<()> {
+    let mut typing_imports = BTreeSet::new();
+    let mut body = String::new();
+    body.push_str("class Error(Exception): ...\n");
+    for item in items {
+        body.push('\n');
+        match item {
+            ItemToExpand::Class(ref fclass) => {
+                generate_class_stub(ctx, fclass, &mut typing_imports, &mut body)?
+            }
+            ItemToExpand::Enum(ref fenum) if !fenum.is_c_like() => {
+                generate_enum_with_data_stub(ctx, fenum, &mut typing_imports, &mut body)?
+            }
+            ItemToExpand::Enum(ref fenum) => generate_enum_stub(fenum, &mut body),
+            ItemToExpand::Interface(ref finterface) => {
+                generate_interface_stub(ctx, finterface, &mut typing_imports, &mut body)?
+            }
+        }
+    }
+
+    let stub_path = output_dir.join(format!("{}.pyi", ctx.cfg.module_name));
+    let mut stub_file = FileWriteCache::new(&stub_path, &mut NoNeedFsOpsRegistration);
+    write!(&mut stub_file, "# Automatically generated by flapigen\n\n")
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    let mut imports_by_module = BTreeMap::<&str, Vec<&str>>::new();
+    for import in &typing_imports {
+        let (module, name) = match import.rfind('.') {
+            Some(pos) => (&import[..pos], &import[pos + 1..]),
+            None => ("typing", import.as_str()),
+        };
+        imports_by_module.entry(module).or_default().push(name);
+    }
+    for (module, names) in &imports_by_module {
+        writeln!(
+            &mut stub_file,
+            "from {} import {}",
+            module,
+            names.join(", ")
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    if !imports_by_module.is_empty() {
+        writeln!(&mut stub_file).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    stub_file
+        .write_all(body.as_bytes())
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    stub_file.update_file_if_necessary().map_err(|err| {
+        DiagnosticError::map_any_err_to_our_err(format!(
+            "write to {} failed: {}",
+            stub_path.display(),
+            err
+        ))
+    })
+}
+
+fn generate_class_stub(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+    typing_imports: &mut BTreeSet<SmolStr>,
+    out: &mut String,
+) -> Result<()> {
+    writeln!(out, "class {}:", class.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    let mut doc_comments = class.doc_comments.clone();
+    if let Some(constructor) = class
+        .methods
+        .iter()
+        .find(|m| m.variant == MethodVariant::Constructor)
+    {
+        // The same as for the generated class, constructor is documented in class's docstring
+        doc_comments.push("".to_owned());
+        doc_comments.extend_from_slice(&constructor.doc_comments);
+    }
+    write_docstring(out, "    ", &doc_comments);
+    let mut has_members = false;
+    for method in &class.methods {
+        if method.is_dummy_constructor() {
+            continue;
+        }
+        has_members = true;
+        let args = method_args_hints(ctx, class, method, typing_imports)?;
+        let ret_rust_ty = ctx
+            .conv_map
+            .find_or_alloc_rust_type(&extract_return_type(&method.fn_decl.output), class.src_id);
+        let mut ret_hint = py_type_hint(
+            ctx,
+            &ret_rust_ty,
+            method.span(),
+            class.src_id,
+            Direction::Outgoing,
+            typing_imports,
+        )?;
+        if method.is_async {
+            typing_imports.insert("asyncio.Future".into());
+            ret_hint = format!("Future[{}]", ret_hint);
+        }
+        let mut method_name = method_name(method, class.src_id)?.to_string();
+        match method.field_accessor() {
+            Some(FieldAccessor {
+                kind: FieldAccessorKind::Getter,
+                ..
+            }) => out.push_str("    @property\n"),
+            Some(FieldAccessor {
+                kind: FieldAccessorKind::Setter,
+                name,
+            }) => {
+                writeln!(out, "    @{}.setter", name).expect(WRITE_TO_MEM_FAILED_MSG);
+                method_name = name.to_string();
+            }
+            None if method.variant == MethodVariant::StaticMethod => {
+                out.push_str("    @staticmethod\n")
+            }
+            None => {}
+        }
+        write!(
+            out,
+            "    def {}({}) -> {}:",
+            method_name,
+            args.join(", "),
+            ret_hint
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        if method.variant == MethodVariant::Constructor || method.doc_comments.is_empty() {
+            out.push_str(" ...\n");
+        } else {
+            out.push('\n');
+            write_docstring(out, "        ", &method.doc_comments);
+            out.push_str("        ...\n");
+        }
+    }
+    for slot in dunder::class_slots(ctx, class)? {
+        has_members = true;
+        generate_slot_stub(ctx, class, &slot, typing_imports, out)?;
+    }
+    if !has_members && class.doc_comments.is_empty() {
+        out.push_str("    ...\n");
+    }
+    Ok(())
+}
+
+fn method_args_hints(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+    typing_imports: &mut BTreeSet<SmolStr>,
+) -> Result<Vec<String>> {
+    let mut args = match method.variant {
+        MethodVariant::Constructor => vec!["cls".to_owned()],
+        MethodVariant::Method(_) => vec!["self".to_owned()],
+        MethodVariant::StaticMethod => vec![],
+    };
+    let skip_args_count = if let MethodVariant::Method(_) = method.variant {
+        1
+    } else {
+        0
+    };
+    for arg in method.fn_decl.inputs.iter().skip(skip_args_count) {
+        let named_arg = arg
+            .as_named_arg()
+            .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
+        let arg_rust_ty = ctx
+            .conv_map
+            .find_or_alloc_rust_type(&named_arg.ty, class.src_id);
+        let arg_hint = py_type_hint(
+            ctx,
+            &arg_rust_ty,
+            method.span(),
+            class.src_id,
+            Direction::Incoming,
+            typing_imports,
+        )?;
+        args.push(format!("{}: {}", named_arg.name, arg_hint));
+    }
+    Ok(args)
+}
+
+fn generate_slot_stub(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+    slot: &dunder::Slot,
+    typing_imports: &mut BTreeSet<SmolStr>,
+    out: &mut String,
+) -> Result<()> {
+    let mut write_stub = |name: &str, args: &str, ret_hint: &str| {
+        writeln!(out, "    def {}({}) -> {}: ...", name, args, ret_hint)
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+    };
+    match slot {
+        dunder::Slot::Method {
+            name,
+            method,
+            protocol,
+        } => {
+            let args = method_args_hints(ctx, class, method, typing_imports)?;
+            let mut ret_rust_ty = ctx.conv_map.find_or_alloc_rust_type(
+                &extract_return_type(&method.fn_decl.output),
+                class.src_id,
+            );
+            if *protocol != dunder::ReturnProtocol::Value {
+                if let Some(inner_ty) = ast::if_option_return_some_type(&ret_rust_ty) {
+                    ret_rust_ty = ctx
+                        .conv_map
+                        .find_or_alloc_rust_type(&inner_ty, class.src_id);
+                }
+            }
+            let ret_hint = py_type_hint(
+                ctx,
+                &ret_rust_ty,
+                method.span(),
+                class.src_id,
+                Direction::Outgoing,
+                typing_imports,
+            )?;
+            write_stub(name, &args.join(", "), &ret_hint);
+        }
+        dunder::Slot::IterSelf => write_stub("__iter__", "self", &class.name.to_string()),
+        dunder::Slot::Format { name, .. } => write_stub(name, "self", "str"),
+        dunder::Slot::Hash => write_stub("__hash__", "self", "int"),
+        dunder::Slot::Compare { ordering } => {
+            write_stub("__eq__", "self, other: object", "bool");
+            write_stub("__ne__", "self, other: object", "bool");
+            if *ordering {
+                let args = format!("self, other: {}", class.name);
+                for name in &["__lt__", "__le__", "__gt__", "__ge__"] {
+                    write_stub(name, &args, "bool");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn generate_enum_stub(enum_info: &ForeignEnumInfo, out: &mut String) {
+    writeln!(out, "class {}:", enum_info.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    write_docstring(out, "    ", &enum_info.doc_comments);
+    for item in &enum_info.items {
+        writeln!(out, "    {}: int", item.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    if enum_info.items.is_empty() && enum_info.doc_comments.is_empty() {
+        out.push_str("    ...\n");
+    }
+}
+
+/// Enum with data is base class of classes for items
+fn generate_enum_with_data_stub(
+    ctx: &mut PythonContext,
+    enum_info: &ForeignEnumInfo,
+    typing_imports: &mut BTreeSet<SmolStr>,
+    out: &mut String,
+) -> Result<()> {
+    writeln!(out, "class {}:", enum_info.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    write_docstring(out, "    ", &enum_info.doc_comments);
+    if enum_info.doc_comments.is_empty() {
+        out.push_str("    ...\n");
+    }
+    for item in &enum_info.items {
+        let class_name = fenum::item_class_name(enum_info, item);
+        writeln!(out, "\nclass {}({}):", class_name, enum_info.name)
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+        write_docstring(out, "    ", &item.doc_comments);
+        let mut args = vec!["cls".to_owned()];
+        for field in item.fields.as_slice() {
+            let field_rust_ty = ctx
+                .conv_map
+                .find_or_alloc_rust_type(&field.ty, enum_info.src_id);
+            let hint = py_type_hint(
+                ctx,
+                &field_rust_ty,
+                field.ty.span(),
+                enum_info.src_id,
+                Direction::Outgoing,
+                typing_imports,
+            )?;
+            writeln!(out, "    {}: {}", field.name, hint).expect(WRITE_TO_MEM_FAILED_MSG);
+            args.push(format!("{}: {}", field.name, hint));
+        }
+        writeln!(
+            out,
+            "    def __new__({}) -> {}: ...",
+            args.join(", "),
+            class_name
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    Ok(())
+}
+
+/// Callback is any Python object with the required methods,
+/// so it is described as `typing.Protocol`
+fn generate_interface_stub(
+    ctx: &mut PythonContext,
+    interface: &ForeignInterface,
+    typing_imports: &mut BTreeSet<SmolStr>,
+    out: &mut String,
+) -> Result<()> {
+    typing_imports.insert("typing.Protocol".into());
+    writeln!(out, "class {}(Protocol):", interface.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    write_docstring(out, "    ", &interface.doc_comments);
+    for method in &interface.items {
+        let method_span = method.rust_name.span();
+        let mut args = vec!["self".to_owned()];
+        for arg in method.fn_decl.inputs.iter().skip(1) {
+            let named_arg = arg
+                .as_named_arg()
+                .map_err(|err| DiagnosticError::from_syn_err(interface.src_id, err))?;
+            let arg_rust_ty = ctx
+                .conv_map
+                .find_or_alloc_rust_type(&named_arg.ty, interface.src_id);
+            let arg_hint = py_type_hint(
+                ctx,
+                &arg_rust_ty,
+                method_span,
+                interface.src_id,
+                Direction::Outgoing,
+                typing_imports,
+            )?;
+            args.push(format!("{}: {}", named_arg.name, arg_hint));
+        }
+        let ret_rust_ty = ctx.conv_map.find_or_alloc_rust_type(
+            &extract_return_type(&method.fn_decl.output),
+            interface.src_id,
+        );
+        let ret_hint = py_type_hint(
+            ctx,
+            &ret_rust_ty,
+            method_span,
+            interface.src_id,
+            Direction::Incoming,
+            typing_imports,
+        )?;
+        write!(
+            out,
+            "    def {}({}) -> {}:",
+            method.name,
+            args.join(", "),
+            ret_hint
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        if method.doc_comments.is_empty() {
+            out.push_str(" ...\n");
+        } else {
+            out.push('\n');
+            write_docstring(out, "        ", &method.doc_comments);
+            out.push_str("        ...\n");
+        }
+    }
+    if interface.items.is_empty() && interface.doc_comments.is_empty() {
+        out.push_str("    ...\n");
+    }
+    Ok(())
+}
+
+/// Python type of value, that is the result of conversion of `rust_type`,
+/// follows the same rules as `generate_conversion_for_argument` (`Direction::Incoming`)
+/// and `generate_conversion_for_return` (`Direction::Outgoing`)
+pub(in crate::python) fn py_type_hint(
+    ctx: &mut PythonContext,
+    rust_type: &RustType,
+    method_span: Span,
+    src_id: SourceId,
+    direction: Direction,
+    typing_imports: &mut BTreeSet<SmolStr>,
+) -> Result<String> {
+    if rust_type.ty == parse_type! { () } {
+        return Ok("None".to_owned());
+    }
+    if is_cpython_supported_type(rust_type) {
+        let hint = match rust_type.normalized_name.as_str() {
+            "bool" => "bool",
+            "f32" | "f64" => "float",
+            "String" | "& str" => "str",
+            _ => "int",
+        };
+        return Ok(hint.to_owned());
+    }
+    if let Some(class_name) = exported_class_name(ctx.conv_map, rust_type, src_id) {
+        return Ok(class_name);
+    }
+    if let Some(iter_class) = ctx
+        .conv_map
+        .find_foreigner_class_with_such_this_type(&rust_type.ty, |_, fc| fc.iterator_type.clone())
+    {
+        return Ok(iter_class.name.to_string());
+    }
+    if rust_type
+        .implements
+        .contains_path(&parse(ENUM_TRAIT_NAME, src_id)?)
+    {
+        return Ok("int".to_owned());
+    }
+    if rust_type
+        .implements
+        .contains_path(&parse(fenum::ENUM_WITH_DATA_TRAIT_NAME, src_id)?)
+    {
+        return Ok(rust_type.normalized_name.to_string());
+    }
+    if rust_type
+        .implements
+        .contains_path(&parse(INTERFACE_TRAIT_NAME, src_id)?)
+    {
+        if let Some(ftype) = ctx
+            .conv_map
+            .find_foreign_type_related_to_rust_ty(rust_type.to_idx())
+        {
+            return Ok(ctx.conv_map[ftype].typename().to_string());
+        }
+    }
+    if let Some(conversion) =
+        map_type::map_type(ctx, rust_type, direction, (src_id, method_span), "x")?
+    {
+        typing_imports.extend(conversion.req_modules);
+        return Ok(conversion.type_hint.to_string());
+    }
+    if let Some((elem, _)) = if_numeric_buffer_return_elem_type(rust_type) {
+        match direction {
+            Direction::Outgoing if ctx.cfg.return_numeric_buffers => {
+                return Ok("memoryview".to_owned());
+            }
+            Direction::Outgoing => {}
+            Direction::Incoming => {
+                let elem = ctx.conv_map.find_or_alloc_rust_type(&elem, src_id);
+                typing_imports.insert("typing.Sequence".into());
+                typing_imports.insert("typing.Union".into());
+                typing_imports.insert("typing_extensions.Buffer".into());
+                return Ok(format!(
+                    "Union[Buffer, Sequence[{}]]",
+                    py_type_hint(ctx, &elem, method_span, src_id, direction, typing_imports)?
+                ));
+            }
+        }
+    }
+    if let Some(inner) = if_type_slice_return_elem_type(&rust_type.ty, false)
+        .cloned()
+        .or_else(|| if_vec_return_elem_type(rust_type))
+    {
+        let inner = ctx.conv_map.find_or_alloc_rust_type(&inner, src_id);
+        typing_imports.insert("typing.List".into());
+        return Ok(format!(
+            "List[{}]",
+            py_type_hint(ctx, &inner, method_span, src_id, direction, typing_imports)?
+        ));
+    }
+    if let Some((inner_ok, _)) = ast::if_result_return_ok_err_types(rust_type) {
+        let inner_ok = ctx.conv_map.find_or_alloc_rust_type(&inner_ok, src_id);
+        return py_type_hint(
+            ctx,
+            &inner_ok,
+            method_span,
+            src_id,
+            direction,
+            typing_imports,
+        );
+    }
+    if let Type::Reference(ref inner) = rust_type.ty {
+        let inner = ctx.conv_map.find_or_alloc_rust_type(&inner.elem, src_id);
+        return py_type_hint(ctx, &inner, method_span, src_id, direction, typing_imports);
+    }
+    Err(DiagnosticError::new(
+        src_id,
+        method_span,
+        format!("Can not find Python type for: {}", rust_type),
+    ))
+}
+
+fn exported_class_name(
+    conv_map: &mut TypeMap,
+    rust_type: &RustType,
+    src_id: SourceId,
+) -> Option<String> {
+    let (_, rust_type_unref) = get_reference_info_and_inner_type(rust_type, conv_map, src_id);
+    let smart_pointer_info = smart_pointer(&rust_type_unref, conv_map, src_id);
+    conv_map
+        .find_foreigner_class_with_such_this_type(&smart_pointer_info.inner_ty.ty, |_, ft| {
+            ft.self_desc.as_ref().map(|x| x.self_type.clone())
+        })
+        .map(|class| class.name.to_string())
+}
+
+fn write_docstring(out: &mut String, indent: &str, doc_comments: &[String]) {
+    if doc_comments.iter().all(|line| line.trim().is_empty()) {
+        return;
+    }
+    write!(out, "{}\"\"\"", indent).expect(WRITE_TO_MEM_FAILED_MSG);
+    for (i, line) in doc_comments.iter().enumerate() {
+        let line = line.trim().replace("\"\"\"", "\\\"\\\"\\\"");
+        if i == 0 {
+            out.push_str(&line);
+        } else if line.is_empty() {
+            out.push('\n');
+        } else {
+            write!(out, "\n{}{}", indent, line).expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+    }
+    if doc_comments.len() > 1 {
+        write!(out, "\n{}", indent).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    out.push_str("\"\"\"\n");
+}
@@ -0,0 +1,86 @@
+
+/// Passed to `loop.call_soon_threadsafe` to deliver result of Rust future
+/// to `asyncio.Future` in thread of event loop
+#[pyo3::pyclass]
+struct SwigPyAsyncResult {
+    future: pyo3::PyObject,
+    result: Option<pyo3::PyResult<pyo3::PyObject>>,
+}
+
+#[pyo3::pymethods]
+impl SwigPyAsyncResult {
+    fn __call__(&mut self, py: pyo3::Python<'_>) -> pyo3::PyResult<()> {
+        let future = self.future.bind(py);
+        if future.call_method0("cancelled")?.is_truthy()? {
+            return Ok(());
+        }
+        match self.result.take() {
+            Some(Ok(value)) => {
+                future.call_method1("set_result", (value,))?;
+            }
+            Some(Err(err)) => {
+                future.call_method1("set_exception", (err.value(py),))?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// Done callback of `asyncio.Future`, drops Rust future if it is cancelled
+#[pyo3::pyclass]
+struct SwigPyAsyncCancel {
+    task: ::std::sync::Arc<SwigAsyncTask>,
+}
+
+#[pyo3::pymethods]
+impl SwigPyAsyncCancel {
+    fn __call__(&self, future: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<()> {
+        if future.call_method0("cancelled")?.is_truthy()? {
+            self.task.cancel();
+        }
+        Ok(())
+    }
+}
+
+/// Create `asyncio.Future` in running event loop and complete it with
+/// result of `future`, converted by `convert` with the GIL held
+#[allow(dead_code)]
+fn swig_py_async_spawn<T, F, C>(
+    py: pyo3::Python<'_>,
+    future: F,
+    convert: C,
+) -> pyo3::PyResult<pyo3::PyObject>
+where
+    F: ::std::future::Future<Output = T> + Send + 'static,
+    C: FnOnce(pyo3::Python<'_>, T) -> pyo3::PyResult<pyo3::PyObject> + Send + 'static,
+{
+    let event_loop = py
+        .import("asyncio")?
+        .call_method0("get_running_loop")?
+        .unbind();
+    let py_future = event_loop.call_method0(py, "create_future")?;
+    let result_future = py_future.clone_ref(py);
+    let task = swig_async_spawn(async move {
+        let ret = future.await;
+        pyo3::Python::with_gil(|py| {
+            let result = SwigPyAsyncResult {
+                future: result_future,
+                result: Some(convert(py, ret)),
+            };
+            let posted = pyo3::Py::new(py, result).and_then(|result| {
+                event_loop.call_method1(py, "call_soon_threadsafe", (result,))
+            });
+            if let Err(err) = posted {
+                // for example, event loop is already closed
+                err.print(py);
+            }
+        });
+    });
+    py_future.call_method1(
+        py,
+        "add_done_callback",
+        (pyo3::Py::new(py, SwigPyAsyncCancel { task })?,),
+    )?;
+    Ok(py_future)
+}
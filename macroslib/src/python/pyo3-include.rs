@@ -0,0 +1,405 @@
+
+#[allow(unused_imports)]
+use pyo3::types::{
+    PyAnyMethods as PyO3AnyMethods, PyBytesMethods as PyO3BytesMethods,
+    PyDictMethods as PyO3DictMethods, PyModuleMethods as PyO3ModuleMethods,
+    PySetMethods as PyO3SetMethods,
+};
+
+// It is currently unused.
+mod swig_foreign_types_map {
+    #![swig_foreigner_type = "()"]
+    #![swig_rust_type = "()"]
+    #![swig_foreigner_type = "bool"]
+    #![swig_rust_type = "bool"]
+    #![swig_foreigner_type = "i8"]
+    #![swig_rust_type = "i8"]
+    #![swig_foreigner_type = "i16"]
+    #![swig_rust_type = "i16"]
+    #![swig_foreigner_type = "i32"]
+    #![swig_rust_type = "i32"]
+    #![swig_foreigner_type = "u8"]
+    #![swig_rust_type = "u8"]
+    #![swig_foreigner_type = "u16"]
+    #![swig_rust_type = "u16"]
+    #![swig_foreigner_type = "u32"]
+    #![swig_rust_type = "u32"]
+    #![swig_foreigner_type = "f32"]
+    #![swig_rust_type = "f32"]
+    #![swig_foreigner_type = "f64"]
+    #![swig_rust_type = "f64"]
+    #![swig_foreigner_type = "String"]
+    #![swig_rust_type = "String"]
+}
+
+fn swig_collect_error_message(error: &dyn std::error::Error) -> String {
+    if let Some(source) = error.source() {
+        format!("{}\nCaused by:\n{}", error, swig_collect_error_message(source))
+    } else {
+        error.to_string()
+    }
+}
+
+fn swig_py_err_to_string(py: pyo3::Python, err: pyo3::PyErr) -> String {
+    err.value(py).to_string()
+}
+
+foreign_typemap!(
+    ($p:r_type) <T> Option<T> => Option<swig_i_type!(T)> {
+        $out = match $p {
+            Some(x) => {
+                swig_from_rust_to_i_type!(T, x, x)
+                Some(x)
+            }
+            None => None,
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Optional"]) => "Optional[swig_f_type!(T)]";
+    ($p:r_type) <T> Option<T> <= Option<swig_i_type!(T)> {
+        $out = match $p {
+            Some(x) => {
+                swig_from_i_type_to_rust!(T, x, x)
+                Some(x)
+            }
+            None => None,
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Optional"]) <= "Optional[swig_f_type!(T)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T1, T2> (T1, T2) => (swig_i_type!(T1), swig_i_type!(T2)) {
+        let (p0, p1) = $p;
+        swig_from_rust_to_i_type!(T1, p0, p0)
+        swig_from_rust_to_i_type!(T2, p1, p1)
+        $out = (p0, p1);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) => "Tuple[swig_f_type!(T1), swig_f_type!(T2)]";
+    ($p:r_type) <T1, T2> (T1, T2) <= (swig_i_type!(T1), swig_i_type!(T2)) {
+        let (p0, p1) = $p;
+        swig_from_i_type_to_rust!(T1, p0, p0)
+        swig_from_i_type_to_rust!(T2, p1, p1)
+        $out = (p0, p1);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) <= "Tuple[swig_f_type!(T1), swig_f_type!(T2)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T1, T2, T3> (T1, T2, T3) => (swig_i_type!(T1), swig_i_type!(T2), swig_i_type!(T3)) {
+        let (p0, p1, p2) = $p;
+        swig_from_rust_to_i_type!(T1, p0, p0)
+        swig_from_rust_to_i_type!(T2, p1, p1)
+        swig_from_rust_to_i_type!(T3, p2, p2)
+        $out = (p0, p1, p2);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) => "Tuple[swig_f_type!(T1), swig_f_type!(T2), swig_f_type!(T3)]";
+    ($p:r_type) <T1, T2, T3> (T1, T2, T3) <= (swig_i_type!(T1), swig_i_type!(T2), swig_i_type!(T3)) {
+        let (p0, p1, p2) = $p;
+        swig_from_i_type_to_rust!(T1, p0, p0)
+        swig_from_i_type_to_rust!(T2, p1, p1)
+        swig_from_i_type_to_rust!(T3, p2, p2)
+        $out = (p0, p1, p2);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) <= "Tuple[swig_f_type!(T1), swig_f_type!(T2), swig_f_type!(T3)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T1, T2, T3, T4> (T1, T2, T3, T4) => (swig_i_type!(T1), swig_i_type!(T2), swig_i_type!(T3), swig_i_type!(T4)) {
+        let (p0, p1, p2, p3) = $p;
+        swig_from_rust_to_i_type!(T1, p0, p0)
+        swig_from_rust_to_i_type!(T2, p1, p1)
+        swig_from_rust_to_i_type!(T3, p2, p2)
+        swig_from_rust_to_i_type!(T4, p3, p3)
+        $out = (p0, p1, p2, p3);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) => "Tuple[swig_f_type!(T1), swig_f_type!(T2), swig_f_type!(T3), swig_f_type!(T4)]";
+    ($p:r_type) <T1, T2, T3, T4> (T1, T2, T3, T4) <= (swig_i_type!(T1), swig_i_type!(T2), swig_i_type!(T3), swig_i_type!(T4)) {
+        let (p0, p1, p2, p3) = $p;
+        swig_from_i_type_to_rust!(T1, p0, p0)
+        swig_from_i_type_to_rust!(T2, p1, p1)
+        swig_from_i_type_to_rust!(T3, p2, p2)
+        swig_from_i_type_to_rust!(T4, p3, p3)
+        $out = (p0, p1, p2, p3);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) <= "Tuple[swig_f_type!(T1), swig_f_type!(T2), swig_f_type!(T3), swig_f_type!(T4)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <K, V> HashMap<K, V> => pyo3::Py<pyo3::types::PyDict> {
+        $out = {
+            let dict = pyo3::types::PyDict::new(py);
+            for (k, v) in $p {
+                swig_from_rust_to_i_type!(K, k, k)
+                swig_from_rust_to_i_type!(V, v, v)
+                dict.set_item(k, v)?;
+            }
+            dict.unbind()
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Dict"]) => "Dict[swig_f_type!(K), swig_f_type!(V)]";
+    ($p:r_type) <K, V> HashMap<K, V> <= pyo3::Bound<'_, pyo3::types::PyDict> {
+        $out = {
+            let dict = $p;
+            let mut map = std::collections::HashMap::with_capacity(dict.len());
+            for (k, v) in dict.iter() {
+                let k: swig_i_type!(K) = k.extract()?;
+                let v: swig_i_type!(V) = v.extract()?;
+                swig_from_i_type_to_rust!(K, k, k)
+                swig_from_i_type_to_rust!(V, v, v)
+                map.insert(k, v);
+            }
+            map
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Dict"]) <= "Dict[swig_f_type!(K), swig_f_type!(V)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <K, V> BTreeMap<K, V> => pyo3::Py<pyo3::types::PyDict> {
+        $out = {
+            let dict = pyo3::types::PyDict::new(py);
+            for (k, v) in $p {
+                swig_from_rust_to_i_type!(K, k, k)
+                swig_from_rust_to_i_type!(V, v, v)
+                dict.set_item(k, v)?;
+            }
+            dict.unbind()
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Dict"]) => "Dict[swig_f_type!(K), swig_f_type!(V)]";
+    ($p:r_type) <K, V> BTreeMap<K, V> <= pyo3::Bound<'_, pyo3::types::PyDict> {
+        $out = {
+            let dict = $p;
+            let mut map = std::collections::BTreeMap::new();
+            for (k, v) in dict.iter() {
+                let k: swig_i_type!(K) = k.extract()?;
+                let v: swig_i_type!(V) = v.extract()?;
+                swig_from_i_type_to_rust!(K, k, k)
+                swig_from_i_type_to_rust!(V, v, v)
+                map.insert(k, v);
+            }
+            map
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Dict"]) <= "Dict[swig_f_type!(K), swig_f_type!(V)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> HashSet<T> => pyo3::Py<pyo3::types::PySet> {
+        $out = {
+            let set = pyo3::types::PySet::empty(py)?;
+            for x in $p {
+                swig_from_rust_to_i_type!(T, x, x)
+                set.add(x)?;
+            }
+            set.unbind()
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Set"]) => "Set[swig_f_type!(T)]";
+    ($p:r_type) <T> HashSet<T> <= pyo3::Bound<'_, pyo3::PyAny> {
+        $out = {
+            let mut set = std::collections::HashSet::new();
+            for x in $p.try_iter()? {
+                let x: swig_i_type!(T) = x?.extract()?;
+                swig_from_i_type_to_rust!(T, x, x)
+                set.insert(x);
+            }
+            set
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Set"]) <= "Set[swig_f_type!(T)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> BTreeSet<T> => pyo3::Py<pyo3::types::PySet> {
+        $out = {
+            let set = pyo3::types::PySet::empty(py)?;
+            for x in $p {
+                swig_from_rust_to_i_type!(T, x, x)
+                set.add(x)?;
+            }
+            set.unbind()
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Set"]) => "Set[swig_f_type!(T)]";
+    ($p:r_type) <T> BTreeSet<T> <= pyo3::Bound<'_, pyo3::PyAny> {
+        $out = {
+            let mut set = std::collections::BTreeSet::new();
+            for x in $p.try_iter()? {
+                let x: swig_i_type!(T) = x?.extract()?;
+                swig_from_i_type_to_rust!(T, x, x)
+                set.insert(x);
+            }
+            set
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Set"]) <= "Set[swig_f_type!(T)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) Vec<u8> => pyo3::Py<pyo3::types::PyBytes> {
+        $out = pyo3::types::PyBytes::new(py, &$p).unbind();
+    };
+    ($p:f_type) => "bytes";
+    ($p:r_type) Vec<u8> <= pyo3::Bound<'_, pyo3::types::PyBytes> {
+        $out = $p.as_bytes().to_vec();
+    };
+    ($p:f_type) <= "bytes";
+);
+
+foreign_typemap!(
+    ($p:r_type) &[u8] => pyo3::Py<pyo3::types::PyBytes> {
+        $out = pyo3::types::PyBytes::new(py, $p).unbind();
+    };
+    ($p:f_type) => "bytes";
+    ($p:r_type) &[u8] <= pyo3::Bound<'_, pyo3::types::PyBytes> {
+        $out = $p.as_bytes();
+    };
+    ($p:f_type) <= "bytes";
+);
+
+/// Element of numeric slice, that is passed to/from Python via the buffer protocol
+trait SwigBufferElement: pyo3::buffer::Element + Copy + Send + Sync + 'static {
+    /// Format of the element in the `struct` module syntax, nul-terminated
+    const FORMAT: &'static [u8];
+}
+
+impl SwigBufferElement for i8 {
+    const FORMAT: &'static [u8] = b"b\0";
+}
+impl SwigBufferElement for i16 {
+    const FORMAT: &'static [u8] = b"h\0";
+}
+impl SwigBufferElement for i32 {
+    const FORMAT: &'static [u8] = b"i\0";
+}
+impl SwigBufferElement for i64 {
+    const FORMAT: &'static [u8] = b"q\0";
+}
+impl SwigBufferElement for u16 {
+    const FORMAT: &'static [u8] = b"H\0";
+}
+impl SwigBufferElement for u32 {
+    const FORMAT: &'static [u8] = b"I\0";
+}
+impl SwigBufferElement for u64 {
+    const FORMAT: &'static [u8] = b"Q\0";
+}
+impl SwigBufferElement for f32 {
+    const FORMAT: &'static [u8] = b"f\0";
+}
+impl SwigBufferElement for f64 {
+    const FORMAT: &'static [u8] = b"d\0";
+}
+
+/// Numeric slice argument: C-contiguous buffer with suitable elements
+/// (`array.array`, numpy array, `memoryview`, ...) is used without copying,
+/// any other sequence is converted element by element
+#[allow(dead_code)]
+enum SwigNumericSlice<T: SwigBufferElement> {
+    Buffer(pyo3::buffer::PyBuffer<T>),
+    Vec(Vec<T>),
+}
+
+impl<'py, T> pyo3::FromPyObject<'py> for SwigNumericSlice<T>
+where
+    T: SwigBufferElement + pyo3::FromPyObject<'py>,
+{
+    fn extract_bound(obj: &pyo3::Bound<'py, pyo3::PyAny>) -> pyo3::PyResult<Self> {
+        match pyo3::buffer::PyBuffer::<T>::get(obj) {
+            Ok(buf) if buf.is_c_contiguous() => Ok(SwigNumericSlice::Buffer(buf)),
+            _ => obj.extract().map(SwigNumericSlice::Vec),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<T: SwigBufferElement> SwigNumericSlice<T> {
+    fn as_slice(&self) -> &[T] {
+        match self {
+            SwigNumericSlice::Buffer(buf) if buf.item_count() > 0 => unsafe {
+                // type, alignment and contiguity are checked during extraction
+                std::slice::from_raw_parts(buf.buf_ptr() as *const T, buf.item_count())
+            },
+            SwigNumericSlice::Buffer(_) => &[],
+            SwigNumericSlice::Vec(v) => v,
+        }
+    }
+
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            SwigNumericSlice::Buffer(_) => self.as_slice().to_vec(),
+            SwigNumericSlice::Vec(v) => v,
+        }
+    }
+}
+
+/// Read-only buffer that owns data returned from Rust,
+/// `memoryview` and numpy use it without copying
+#[pyo3::pyclass(frozen)]
+struct SwigBuffer {
+    _data: Box<dyn std::any::Any + Send + Sync>,
+    ptr: usize,
+    shape: [pyo3::ffi::Py_ssize_t; 1],
+    strides: [pyo3::ffi::Py_ssize_t; 1],
+    format: &'static [u8],
+}
+
+#[pyo3::pymethods]
+impl SwigBuffer {
+    unsafe fn __getbuffer__(
+        slf: pyo3::Bound<'_, Self>,
+        view: *mut pyo3::ffi::Py_buffer,
+        flags: std::os::raw::c_int,
+    ) -> pyo3::PyResult<()> {
+        if (flags & pyo3::ffi::PyBUF_WRITABLE) == pyo3::ffi::PyBUF_WRITABLE {
+            return Err(pyo3::exceptions::PyBufferError::new_err(
+                "buffer returned from Rust is read-only",
+            ));
+        }
+        let this = slf.get();
+        (*view).buf = this.ptr as *mut std::os::raw::c_void;
+        (*view).len = this.shape[0] * this.strides[0];
+        (*view).readonly = 1;
+        (*view).itemsize = this.strides[0];
+        (*view).format = if (flags & pyo3::ffi::PyBUF_FORMAT) == pyo3::ffi::PyBUF_FORMAT {
+            this.format.as_ptr() as *mut std::os::raw::c_char
+        } else {
+            std::ptr::null_mut()
+        };
+        (*view).ndim = 1;
+        (*view).shape = if (flags & pyo3::ffi::PyBUF_ND) == pyo3::ffi::PyBUF_ND {
+            this.shape.as_ptr() as *mut pyo3::ffi::Py_ssize_t
+        } else {
+            std::ptr::null_mut()
+        };
+        (*view).strides = if (flags & pyo3::ffi::PyBUF_STRIDES) == pyo3::ffi::PyBUF_STRIDES {
+            this.strides.as_ptr() as *mut pyo3::ffi::Py_ssize_t
+        } else {
+            std::ptr::null_mut()
+        };
+        (*view).suboffsets = std::ptr::null_mut();
+        (*view).internal = std::ptr::null_mut();
+        (*view).obj = slf.into_any().into_ptr();
+        Ok(())
+    }
+}
+
+/// Numeric `Vec` as `memoryview`, that shares memory with the `Vec`
+#[allow(dead_code)]
+fn swig_numeric_vec_to_py<T: SwigBufferElement>(
+    py: pyo3::Python,
+    data: Vec<T>,
+) -> pyo3::PyResult<pyo3::PyObject> {
+    let buffer = SwigBuffer {
+        ptr: data.as_ptr() as usize,
+        shape: [data.len() as pyo3::ffi::Py_ssize_t],
+        strides: [std::mem::size_of::<T>() as pyo3::ffi::Py_ssize_t],
+        format: T::FORMAT,
+        _data: Box::new(data),
+    };
+    let buffer = pyo3::Bound::new(py, buffer)?;
+    Ok(pyo3::types::PyMemoryView::from(buffer.as_any())?
+        .into_any()
+        .unbind())
+}
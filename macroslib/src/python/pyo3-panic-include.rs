@@ -0,0 +1,11 @@
+
+/// Call `f`, if it panics return `RuntimeError` with panic message
+#[allow(dead_code)]
+fn swig_py_catch_panic<T, F: FnOnce() -> pyo3::PyResult<T>>(
+    _py: pyo3::Python<'_>,
+    with_backtrace: bool,
+    f: F,
+) -> pyo3::PyResult<T> {
+    swig_catch_panic(with_backtrace, f)
+        .unwrap_or_else(|msg| Err(pyo3::exceptions::PyRuntimeError::new_err(msg)))
+}
@@ -0,0 +1,93 @@
+
+// Passed to `loop.call_soon_threadsafe` to deliver result of Rust future
+// to `asyncio.Future` in thread of event loop
+py_class!(class SwigPyAsyncResult |py| {
+    data future: cpython::PyObject;
+    data result: ::std::cell::RefCell<Option<cpython::PyResult<cpython::PyObject>>>;
+
+    def __call__(&self) -> cpython::PyResult<cpython::PyObject> {
+        let future = self.future(py);
+        if future
+            .call_method(py, "cancelled", cpython::NoArgs, None)?
+            .is_true(py)?
+        {
+            return Ok(py.None());
+        }
+        let result = self.result(py).borrow_mut().take();
+        match result {
+            Some(Ok(value)) => {
+                future.call_method(py, "set_result", (value,), None)?;
+            }
+            Some(Err(mut err)) => {
+                future.call_method(py, "set_exception", (err.instance(py),), None)?;
+            }
+            None => {}
+        }
+        Ok(py.None())
+    }
+});
+
+// Done callback of `asyncio.Future`, drops Rust future if it is cancelled
+py_class!(class SwigPyAsyncCancel |py| {
+    data task: ::std::sync::Arc<SwigAsyncTask>;
+
+    def __call__(&self, future: cpython::PyObject) -> cpython::PyResult<cpython::PyObject> {
+        if future
+            .call_method(py, "cancelled", cpython::NoArgs, None)?
+            .is_true(py)?
+        {
+            self.task(py).cancel();
+        }
+        Ok(py.None())
+    }
+});
+
+/// Create `asyncio.Future` in running event loop and complete it with
+/// result of `future`, converted by `convert` with the GIL held
+#[allow(dead_code)]
+fn swig_py_async_spawn<T, F, C>(
+    py: cpython::Python,
+    future: F,
+    convert: C,
+) -> cpython::PyResult<cpython::PyObject>
+where
+    F: ::std::future::Future<Output = T> + Send + 'static,
+    C: FnOnce(cpython::Python, T) -> cpython::PyResult<cpython::PyObject> + Send + 'static,
+{
+    let event_loop = py
+        .import("asyncio")?
+        .call(py, "get_running_loop", cpython::NoArgs, None)?;
+    let py_future = event_loop.call_method(py, "create_future", cpython::NoArgs, None)?;
+    let result_future = py_future.clone_ref(py);
+    let task = swig_async_spawn(async move {
+        let ret = future.await;
+        let gil = cpython::Python::acquire_gil();
+        let py = gil.python();
+        let result = convert(py, ret);
+        let posted = SwigPyAsyncResult::create_instance(
+            py,
+            result_future,
+            ::std::cell::RefCell::new(Some(result)),
+        )
+        .and_then(|result| {
+            event_loop.call_method(
+                py,
+                "call_soon_threadsafe",
+                (cpython::PythonObject::into_object(result),),
+                None,
+            )
+        });
+        if let Err(err) = posted {
+            // for example, event loop is already closed
+            err.print(py);
+        }
+    });
+    let cancel = SwigPyAsyncCancel::create_instance(py, task)?;
+    py_future.call_method(
+        py,
+        "add_done_callback",
+        (cpython::PythonObject::into_object(cancel),),
+        None,
+    )?;
+    Ok(py_future)
+}
@@ -35,3 +35,320 @@ fn swig_collect_error_message(error: &dyn std::error::Error) -> String {
         error.to_string()
     }
 }
+
+fn swig_py_err_to_string(py: cpython::Python, mut err: cpython::PyErr) -> String {
+    match err.instance(py).str(py) {
+        Ok(msg) => msg.to_string_lossy(py).into_owned(),
+        Err(_) => "unprintable Python exception".to_owned(),
+    }
+}
+
+foreign_typemap!(
+    ($p:r_type) <T> Option<T> => Option<swig_i_type!(T)> {
+        $out = match $p {
+            Some(x) => {
+                swig_from_rust_to_i_type!(T, x, x)
+                Some(x)
+            }
+            None => None,
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Optional"]) => "Optional[swig_f_type!(T)]";
+    ($p:r_type) <T> Option<T> <= Option<swig_i_type!(T)> {
+        $out = match $p {
+            Some(x) => {
+                swig_from_i_type_to_rust!(T, x, x)
+                Some(x)
+            }
+            None => None,
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Optional"]) <= "Optional[swig_f_type!(T)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T1, T2> (T1, T2) => (swig_i_type!(T1), swig_i_type!(T2)) {
+        let (p0, p1) = $p;
+        swig_from_rust_to_i_type!(T1, p0, p0)
+        swig_from_rust_to_i_type!(T2, p1, p1)
+        $out = (p0, p1);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) => "Tuple[swig_f_type!(T1), swig_f_type!(T2)]";
+    ($p:r_type) <T1, T2> (T1, T2) <= (swig_i_type!(T1), swig_i_type!(T2)) {
+        let (p0, p1) = $p;
+        swig_from_i_type_to_rust!(T1, p0, p0)
+        swig_from_i_type_to_rust!(T2, p1, p1)
+        $out = (p0, p1);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) <= "Tuple[swig_f_type!(T1), swig_f_type!(T2)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T1, T2, T3> (T1, T2, T3) => (swig_i_type!(T1), swig_i_type!(T2), swig_i_type!(T3)) {
+        let (p0, p1, p2) = $p;
+        swig_from_rust_to_i_type!(T1, p0, p0)
+        swig_from_rust_to_i_type!(T2, p1, p1)
+        swig_from_rust_to_i_type!(T3, p2, p2)
+        $out = (p0, p1, p2);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) => "Tuple[swig_f_type!(T1), swig_f_type!(T2), swig_f_type!(T3)]";
+    ($p:r_type) <T1, T2, T3> (T1, T2, T3) <= (swig_i_type!(T1), swig_i_type!(T2), swig_i_type!(T3)) {
+        let (p0, p1, p2) = $p;
+        swig_from_i_type_to_rust!(T1, p0, p0)
+        swig_from_i_type_to_rust!(T2, p1, p1)
+        swig_from_i_type_to_rust!(T3, p2, p2)
+        $out = (p0, p1, p2);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) <= "Tuple[swig_f_type!(T1), swig_f_type!(T2), swig_f_type!(T3)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T1, T2, T3, T4> (T1, T2, T3, T4) => (swig_i_type!(T1), swig_i_type!(T2), swig_i_type!(T3), swig_i_type!(T4)) {
+        let (p0, p1, p2, p3) = $p;
+        swig_from_rust_to_i_type!(T1, p0, p0)
+        swig_from_rust_to_i_type!(T2, p1, p1)
+        swig_from_rust_to_i_type!(T3, p2, p2)
+        swig_from_rust_to_i_type!(T4, p3, p3)
+        $out = (p0, p1, p2, p3);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) => "Tuple[swig_f_type!(T1), swig_f_type!(T2), swig_f_type!(T3), swig_f_type!(T4)]";
+    ($p:r_type) <T1, T2, T3, T4> (T1, T2, T3, T4) <= (swig_i_type!(T1), swig_i_type!(T2), swig_i_type!(T3), swig_i_type!(T4)) {
+        let (p0, p1, p2, p3) = $p;
+        swig_from_i_type_to_rust!(T1, p0, p0)
+        swig_from_i_type_to_rust!(T2, p1, p1)
+        swig_from_i_type_to_rust!(T3, p2, p2)
+        swig_from_i_type_to_rust!(T4, p3, p3)
+        $out = (p0, p1, p2, p3);
+    };
+    ($p:f_type, req_modules = ["typing.Tuple"]) <= "Tuple[swig_f_type!(T1), swig_f_type!(T2), swig_f_type!(T3), swig_f_type!(T4)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <K, V> HashMap<K, V> => cpython::PyDict {
+        $out = {
+            let dict = cpython::PyDict::new(py);
+            for (k, v) in $p {
+                swig_from_rust_to_i_type!(K, k, k)
+                swig_from_rust_to_i_type!(V, v, v)
+                dict.set_item(py, k, v)?;
+            }
+            dict
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Dict"]) => "Dict[swig_f_type!(K), swig_f_type!(V)]";
+    ($p:r_type) <K, V> HashMap<K, V> <= cpython::PyDict {
+        $out = {
+            let dict = $p;
+            let mut map = std::collections::HashMap::with_capacity(dict.len(py));
+            for (k, v) in dict.items(py) {
+                let k: swig_i_type!(K) = k.extract(py)?;
+                let v: swig_i_type!(V) = v.extract(py)?;
+                swig_from_i_type_to_rust!(K, k, k)
+                swig_from_i_type_to_rust!(V, v, v)
+                map.insert(k, v);
+            }
+            map
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Dict"]) <= "Dict[swig_f_type!(K), swig_f_type!(V)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <K, V> BTreeMap<K, V> => cpython::PyDict {
+        $out = {
+            let dict = cpython::PyDict::new(py);
+            for (k, v) in $p {
+                swig_from_rust_to_i_type!(K, k, k)
+                swig_from_rust_to_i_type!(V, v, v)
+                dict.set_item(py, k, v)?;
+            }
+            dict
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Dict"]) => "Dict[swig_f_type!(K), swig_f_type!(V)]";
+    ($p:r_type) <K, V> BTreeMap<K, V> <= cpython::PyDict {
+        $out = {
+            let dict = $p;
+            let mut map = std::collections::BTreeMap::new();
+            for (k, v) in dict.items(py) {
+                let k: swig_i_type!(K) = k.extract(py)?;
+                let v: swig_i_type!(V) = v.extract(py)?;
+                swig_from_i_type_to_rust!(K, k, k)
+                swig_from_i_type_to_rust!(V, v, v)
+                map.insert(k, v);
+            }
+            map
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Dict"]) <= "Dict[swig_f_type!(K), swig_f_type!(V)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> HashSet<T> => cpython::PyObject {
+        $out = {
+            let set = py.import("builtins")?.call(py, "set", cpython::NoArgs, None)?;
+            for x in $p {
+                swig_from_rust_to_i_type!(T, x, x)
+                set.call_method(py, "add", (x,), None)?;
+            }
+            set
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Set"]) => "Set[swig_f_type!(T)]";
+    ($p:r_type) <T> HashSet<T> <= cpython::PyObject {
+        $out = {
+            let mut set = std::collections::HashSet::new();
+            for x in $p.iter(py)? {
+                let x: swig_i_type!(T) = x?.extract(py)?;
+                swig_from_i_type_to_rust!(T, x, x)
+                set.insert(x);
+            }
+            set
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Set"]) <= "Set[swig_f_type!(T)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> BTreeSet<T> => cpython::PyObject {
+        $out = {
+            let set = py.import("builtins")?.call(py, "set", cpython::NoArgs, None)?;
+            for x in $p {
+                swig_from_rust_to_i_type!(T, x, x)
+                set.call_method(py, "add", (x,), None)?;
+            }
+            set
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Set"]) => "Set[swig_f_type!(T)]";
+    ($p:r_type) <T> BTreeSet<T> <= cpython::PyObject {
+        $out = {
+            let mut set = std::collections::BTreeSet::new();
+            for x in $p.iter(py)? {
+                let x: swig_i_type!(T) = x?.extract(py)?;
+                swig_from_i_type_to_rust!(T, x, x)
+                set.insert(x);
+            }
+            set
+        };
+    };
+    ($p:f_type, req_modules = ["typing.Set"]) <= "Set[swig_f_type!(T)]";
+);
+
+foreign_typemap!(
+    ($p:r_type) Vec<u8> => cpython::PyBytes {
+        $out = cpython::PyBytes::new(py, &$p);
+    };
+    ($p:f_type) => "bytes";
+    ($p:r_type) Vec<u8> <= cpython::PyBytes {
+        $out = $p.data(py).to_vec();
+    };
+    ($p:f_type) <= "bytes";
+);
+
+foreign_typemap!(
+    ($p:r_type) &[u8] => cpython::PyBytes {
+        $out = cpython::PyBytes::new(py, $p);
+    };
+    ($p:f_type) => "bytes";
+    ($p:r_type) &[u8] <= cpython::PyBytes {
+        $out = $p.data(py);
+    };
+    ($p:f_type) <= "bytes";
+);
+
+/// Element of numeric slice, that is passed to/from Python via the buffer protocol
+trait SwigBufferElement: cpython::buffer::Element + Copy {
+    /// Type code of the element in the `array` module
+    const TYPECODE: &'static str;
+}
+
+impl SwigBufferElement for i8 {
+    const TYPECODE: &'static str = "b";
+}
+impl SwigBufferElement for i16 {
+    const TYPECODE: &'static str = "h";
+}
+impl SwigBufferElement for i32 {
+    const TYPECODE: &'static str = "i";
+}
+impl SwigBufferElement for i64 {
+    const TYPECODE: &'static str = "q";
+}
+impl SwigBufferElement for u16 {
+    const TYPECODE: &'static str = "H";
+}
+impl SwigBufferElement for u32 {
+    const TYPECODE: &'static str = "I";
+}
+impl SwigBufferElement for u64 {
+    const TYPECODE: &'static str = "Q";
+}
+impl SwigBufferElement for f32 {
+    const TYPECODE: &'static str = "f";
+}
+impl SwigBufferElement for f64 {
+    const TYPECODE: &'static str = "d";
+}
+
+/// Numeric slice argument: C-contiguous buffer with suitable elements
+/// (`array.array`, numpy array, `memoryview`, ...) is used without copying,
+/// any other sequence is converted element by element
+#[allow(dead_code)]
+enum SwigNumericSlice<T: SwigBufferElement> {
+    Buffer(cpython::buffer::PyBuffer, std::marker::PhantomData<T>),
+    Vec(Vec<T>),
+}
+
+impl<'s, T> cpython::FromPyObject<'s> for SwigNumericSlice<T>
+where
+    T: SwigBufferElement + for<'a> cpython::FromPyObject<'a>,
+{
+    fn extract(py: cpython::Python, obj: &'s cpython::PyObject) -> cpython::PyResult<Self> {
+        if let Ok(buf) = cpython::buffer::PyBuffer::get(py, obj) {
+            // checks type, alignment and contiguity
+            if buf.as_slice::<T>(py).is_some() {
+                return Ok(SwigNumericSlice::Buffer(buf, std::marker::PhantomData));
+            }
+        }
+        obj.extract(py).map(SwigNumericSlice::Vec)
+    }
+}
+
+#[allow(dead_code)]
+impl<T: SwigBufferElement> SwigNumericSlice<T> {
+    fn as_slice(&self) -> &[T] {
+        match self {
+            SwigNumericSlice::Buffer(buf, _) if buf.item_count() > 0 => unsafe {
+                std::slice::from_raw_parts(buf.buf_ptr() as *const T, buf.item_count())
+            },
+            SwigNumericSlice::Buffer(..) => &[],
+            SwigNumericSlice::Vec(v) => v,
+        }
+    }
+
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            SwigNumericSlice::Buffer(..) => self.as_slice().to_vec(),
+            SwigNumericSlice::Vec(v) => v,
+        }
+    }
+}
+
+/// Numeric `Vec` as read-only `memoryview` of `array.array`,
+/// data is copied into the array once as a whole
+#[allow(dead_code)]
+fn swig_numeric_vec_to_py<T: SwigBufferElement>(
+    py: cpython::Python,
+    data: Vec<T>,
+) -> cpython::PyResult<cpython::PyObject> {
+    let bytes = unsafe {
+        std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(&data[..]))
+    };
+    let array = py
+        .import("array")?
+        .call(py, "array", (T::TYPECODE, cpython::PyBytes::new(py, bytes)), None)?;
+    py.import("builtins")?
+        .call(py, "memoryview", (array,), None)?
+        .call_method(py, "toreadonly", cpython::NoArgs, None)
+}
@@ -0,0 +1,14 @@
+
+/// Call `f`, if it panics return `RuntimeError` with panic message
+#[allow(dead_code)]
+fn swig_py_catch_panic<T, F: FnOnce() -> cpython::PyResult<T>>(
+    py: cpython::Python,
+    with_backtrace: bool,
+    f: F,
+) -> cpython::PyResult<T> {
+    swig_catch_panic(with_backtrace, f).unwrap_or_else(|msg| {
+        Err(cpython::PyErr::new::<cpython::exc::RuntimeError, _>(
+            py, msg,
+        ))
+    })
+}
@@ -0,0 +1,80 @@
+//! `.py` shims: small Python modules (one per class/enum) that re-export
+//! generated type, the place where class/method/enum attribute callbacks
+//! registered via `Generator` can add their code
+
+use super::*;
+use crate::{
+    extension::{
+        extend_foreign_class, extend_foreign_enum, PYTHON_CLASS_DERIVES, PYTHON_METHOD_ATTRS,
+        RELEASE_GIL_ATTR,
+    },
+    file_cache::FileWriteCache,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+use std::io::Write;
+
+pub(in crate::python) fn generate_class_shim(
+    ctx: &mut PythonContext,
+    class: &ForeignClassInfo,
+) -> Result<()> {
+    let mut cnt = shim_header(ctx, &class.name.to_string());
+    let python_class_derives = PYTHON_BACKEND_DERIVES
+        .iter()
+        .chain(PYTHON_CLASS_DERIVES.iter())
+        .copied()
+        .collect::<Vec<_>>();
+    let python_method_attrs = PYTHON_METHOD_ATTRS
+        .iter()
+        .copied()
+        .chain(std::iter::once(RELEASE_GIL_ATTR))
+        .collect::<Vec<_>>();
+    extend_foreign_class(
+        class,
+        &mut cnt,
+        &python_class_derives,
+        &python_method_attrs,
+        ctx.class_ext_handlers,
+        ctx.method_ext_handlers,
+    )?;
+    write_shim(ctx, &class.name.to_string(), cnt)
+}
+
+pub(in crate::python) fn generate_enum_shim(
+    ctx: &mut PythonContext,
+    enum_info: &ForeignEnumInfo,
+) -> Result<()> {
+    let mut cnt = shim_header(ctx, &enum_info.name.to_string());
+    extend_foreign_enum(enum_info, &mut cnt, ctx.enum_ext_handlers)?;
+    write_shim(ctx, &enum_info.name.to_string(), cnt)
+}
+
+fn shim_header(ctx: &PythonContext, type_name: &str) -> Vec<u8> {
+    let mut cnt = Vec::new();
+    write!(
+        &mut cnt,
+        "# Automatically generated by flapigen\nfrom {module} import {name}\n",
+        module = ctx.cfg.module_name,
+        name = type_name,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    cnt
+}
+
+/// Without `shims_output_dir` callbacks are still invoked,
+/// so unknown attributes are reported, but result is dropped
+fn write_shim(ctx: &mut PythonContext, type_name: &str, cnt: Vec<u8>) -> Result<()> {
+    let shims_output_dir = match ctx.cfg.shims_output_dir {
+        Some(ref dir) => dir,
+        None => return Ok(()),
+    };
+    let shim_path = shims_output_dir.join(format!("{}.py", type_name.to_snake_case()));
+    let mut file = FileWriteCache::new(&shim_path, &mut ctx.generated_foreign_files);
+    file.replace_content(cnt);
+    file.update_file_if_necessary().map_err(|err| {
+        DiagnosticError::map_any_err_to_our_err(format!(
+            "write to {} failed: {}",
+            shim_path.display(),
+            err
+        ))
+    })
+}
@@ -0,0 +1,1325 @@
+mod rbs;
+
+use std::{fmt::Write, ops::Deref, path::PathBuf};
+
+use heck::SnakeCase;
+use petgraph::Direction;
+use proc_macro2::TokenStream;
+use rustc_hash::FxHashSet;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    error::{panic_on_syn_error, DiagnosticError, Result, SourceIdSpan},
+    extension::{ClassExtHandlers, EnumExtHandlers, ExtHandlers, MethodExtHandlers},
+    typemap::{
+        ast::{self, DisplayToTokens, TypeName},
+        ty::{ForeignConversationRule, ForeignTypeS, RustType},
+        utils::{create_suitable_types_for_constructor_and_self, remove_files_if},
+        MapToForeignFlag,
+    },
+    types::{
+        ForeignClassInfo, ForeignEnumInfo, ForeignInterface, ForeignInterfaceMethod, ForeignMethod,
+        ItemToExpand, MethodAccess, MethodVariant,
+    },
+    LanguageGenerator, RubyConfig, SourceCode, TypeMap, WRITE_TO_MEM_FAILED_MSG,
+};
+
+const ENUM_TRAIT_NAME: &str = "SwigForeignEnum";
+const INTERFACE_TRAIT_NAME: &str = "SwigForeignInterface";
+/// Return type of function, that generated conversion code is placed into
+const CONV_FUNC_RET_TYPE: &str = "SwigRubyResult<rb_sys::VALUE>";
+
+struct RubyContext<'a> {
+    cfg: &'a RubyConfig,
+    conv_map: &'a mut TypeMap,
+    rust_code: Vec<TokenStream>,
+    generated_foreign_files: FxHashSet<PathBuf>,
+    class_ext_handlers: &'a ClassExtHandlers,
+    method_ext_handlers: &'a MethodExtHandlers,
+    enum_ext_handlers: &'a EnumExtHandlers,
+}
+
+/// Conversion of value between Rust and Ruby
+struct RubyConversion {
+    /// For input: statements, that convert `VALUE` in variable
+    /// into Rust value in variable with the same name,
+    /// for output: the same in other direction
+    code: String,
+    /// Type of Ruby value in terms of RBS
+    rbs_type: String,
+}
+
+impl LanguageGenerator for RubyConfig {
+    fn expand_items(
+        &self,
+        conv_map: &mut TypeMap,
+        _pointer_target_width: usize,
+        _code: &[SourceCode],
+        items: Vec<ItemToExpand>,
+        remove_not_generated_files: bool,
+        ext_handlers: ExtHandlers,
+    ) -> Result<Vec<TokenStream>> {
+        if !(self.output_dir.exists() && self.output_dir.is_dir()) {
+            return Err(DiagnosticError::map_any_err_to_our_err(format!(
+                "Path {} not exists or not directory",
+                self.output_dir.display()
+            )));
+        }
+        if !is_ruby_constant_name(&self.module_name) {
+            return Err(DiagnosticError::map_any_err_to_our_err(format!(
+                "Ruby module name {} should start with uppercase letter",
+                self.module_name
+            )));
+        }
+        if let Some(rule) = conv_map.take_not_merged_not_generic_rules().first() {
+            return Err(DiagnosticError::new(
+                rule.src_id,
+                rule.span,
+                "foreign_typemap! rule with code or options for foreign side is not supported for Ruby",
+            ));
+        }
+        let mut ctx = RubyContext {
+            cfg: self,
+            conv_map,
+            rust_code: vec![],
+            generated_foreign_files: FxHashSet::default(),
+            class_ext_handlers: ext_handlers.class_ext_handlers,
+            method_ext_handlers: ext_handlers.method_ext_handlers,
+            enum_ext_handlers: ext_handlers.enum_ext_handlers,
+        };
+        for item in &items {
+            match item {
+                ItemToExpand::Class(ref fclass) => register_class(&mut ctx, fclass),
+                ItemToExpand::Enum(ref fenum) => register_enum(&mut ctx, fenum)?,
+                ItemToExpand::Interface(ref finterface) => {
+                    register_interface(&mut ctx, finterface)?
+                }
+            }
+        }
+        let mut code = Vec::with_capacity(items.len());
+        let mut module_initialization = Vec::with_capacity(items.len());
+        let mut declarations = Vec::with_capacity(items.len());
+        for item in &items {
+            let (item_code, initialization, declaration) = match item {
+                ItemToExpand::Class(ref fclass) => generate_class(&mut ctx, fclass)?,
+                ItemToExpand::Enum(ref fenum) => generate_enum(&mut ctx, fenum)?,
+                ItemToExpand::Interface(ref finterface) => {
+                    generate_interface(&mut ctx, finterface)?
+                }
+            };
+            code.push(item_code);
+            module_initialization.push(initialization);
+            declarations.push(declaration);
+        }
+        code.push(generate_module_initialization(
+            &self.extension_name,
+            &self.module_name,
+            &module_initialization,
+        ));
+        rbs::write_signatures(&mut ctx, &declarations)?;
+        if remove_not_generated_files {
+            let generated_foreign_files = &ctx.generated_foreign_files;
+            remove_files_if(&self.output_dir, |path| {
+                if let Some(ext) = path.extension() {
+                    if ext == "rbs" && !generated_foreign_files.contains(path) {
+                        return true;
+                    }
+                }
+                false
+            })
+            .map_err(DiagnosticError::map_any_err_to_our_err)?;
+        }
+        code.append(&mut ctx.rust_code);
+        Ok(code)
+    }
+}
+
+fn register_class(ctx: &mut RubyContext, class: &ForeignClassInfo) {
+    if let Some(ref self_desc) = class.self_desc {
+        ctx.conv_map
+            .find_or_alloc_rust_type(&self_desc.self_type, class.src_id);
+        ctx.conv_map
+            .find_or_alloc_rust_type(&self_desc.constructor_ret_type, class.src_id);
+    }
+}
+
+fn register_enum(ctx: &mut RubyContext, fenum: &ForeignEnumInfo) -> Result<()> {
+    let enum_ty = ast::parse_ty_with_given_span(&fenum.name.to_string(), fenum.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(fenum.src_id, err))?;
+    ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &enum_ty,
+        &[ENUM_TRAIT_NAME],
+        fenum.src_id,
+    );
+    Ok(())
+}
+
+fn register_interface(ctx: &mut RubyContext, interface: &ForeignInterface) -> Result<()> {
+    let boxed_trait_ty = boxed_interface_type(interface)?;
+    let boxed_trait_rust_ty = ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &boxed_trait_ty,
+        &[INTERFACE_TRAIT_NAME],
+        interface.src_id,
+    );
+    let rule = ForeignConversationRule {
+        rust_ty: boxed_trait_rust_ty.to_idx(),
+        intermediate: None,
+    };
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(interface.name.to_string(), interface.src_id_span()),
+        provides_by_module: vec![],
+        into_from_rust: Some(rule.clone()),
+        from_into_rust: Some(rule),
+        name_prefix: None,
+    })?;
+    Ok(())
+}
+
+/// Generate module with functions of class, code to define class
+/// during initialization of extension and RBS signature of class.
+/// Constructors and static methods become singleton methods of class.
+fn generate_class(
+    ctx: &mut RubyContext,
+    class: &ForeignClassInfo,
+) -> Result<(TokenStream, TokenStream, String)> {
+    let class_name = class.name.to_string();
+    if !is_ruby_constant_name(&class_name) {
+        return Err(DiagnosticError::new(
+            class.src_id,
+            class.span(),
+            format!(
+                "Name of Ruby class {} should start with uppercase letter",
+                class_name
+            ),
+        ));
+    }
+    let wrapper_mod_name = ruby_wrapper_mod_name(&class_name);
+    let storage_ty = storage_type(class);
+    if storage_ty.is_none()
+        && class
+            .methods
+            .iter()
+            .any(|m| m.variant != MethodVariant::StaticMethod)
+    {
+        return Err(DiagnosticError::new(
+            class.src_id,
+            class.span(),
+            format!(
+                "Class {} has non-static methods, but no self_type",
+                class.name
+            ),
+        ));
+    }
+
+    let mut code = format!(
+        r#"
+#[allow(unused_variables, unused_mut, unused_unsafe, non_snake_case, clippy::all)]
+mod {mod_name} {{
+    use super::*;
+
+    static CLASS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    static DATA_TYPE: std::sync::atomic::AtomicPtr<rb_sys::rb_data_type_t> =
+        std::sync::atomic::AtomicPtr::new(std::ptr::null_mut());
+
+    /// Marker of Ruby class {class_name}
+    pub struct RubyClass;
+
+    impl SwigRubyClass for RubyClass {{
+        type Storage = {storage};
+        const NAME: &'static str = "{class_name}";
+        fn class() -> rb_sys::VALUE {{
+            CLASS.load(std::sync::atomic::Ordering::Relaxed) as rb_sys::VALUE
+        }}
+        fn data_type() -> *const rb_sys::rb_data_type_t {{
+            DATA_TYPE.load(std::sync::atomic::Ordering::Relaxed)
+        }}
+    }}
+"#,
+        mod_name = wrapper_mod_name,
+        class_name = class_name,
+        storage = storage_ty
+            .as_ref()
+            .map(|ty| DisplayToTokens(ty).to_string())
+            .unwrap_or_else(|| "()".into()),
+    );
+
+    let mut ruby_names = FxHashSet::default();
+    let mut define_methods = Vec::with_capacity(class.methods.len());
+    let mut members = Vec::with_capacity(class.methods.len());
+    for method in &class.methods {
+        if method.is_dummy_constructor() {
+            continue;
+        }
+        let is_static = !matches!(method.variant, MethodVariant::Method(_));
+        let ruby_name = method.short_name();
+        if !ruby_names.insert((ruby_name.clone(), is_static)) {
+            return Err(DiagnosticError::new(
+                class.src_id,
+                method.span(),
+                format!(
+                    "Ruby class {} already has method {}, use alias to give other name",
+                    class.name, ruby_name
+                ),
+            ));
+        }
+        let rust_func_name = if is_static {
+            format!("ruby_static_{}", ruby_name)
+        } else {
+            format!("ruby_{}", ruby_name)
+        };
+        code.push_str(&generate_method(
+            ctx,
+            class,
+            method,
+            &rust_func_name,
+            &ruby_name,
+            &mut members,
+        )?);
+        define_methods.push(format!(
+            "{define}(swig_class, \"{ruby_name}\\0\", {func_name}, {private});",
+            define = if is_static {
+                "swig_ruby_define_singleton_method"
+            } else {
+                "swig_ruby_define_method"
+            },
+            ruby_name = ruby_name,
+            func_name = rust_func_name,
+            private = method.access != MethodAccess::Public,
+        ));
+    }
+    write!(
+        &mut code,
+        r#"
+    /// Define class inside of extension's module
+    pub unsafe fn define(swig_module: rb_sys::VALUE) -> SwigRubyResult<()> {{
+        let (swig_class, swig_data_type) = swig_ruby_define_class::<RubyClass>(swig_module, "{class_name}\0");
+        CLASS.store(swig_class as usize, std::sync::atomic::Ordering::Relaxed);
+        DATA_TYPE.store(swig_data_type, std::sync::atomic::Ordering::Relaxed);
+        {define_methods}
+        Ok(())
+    }}
+}}
+"#,
+        class_name = class_name,
+        define_methods = define_methods.join("\n        "),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+
+    let declaration = rbs::class_declaration(ctx, class, &members)?;
+    let module_initialization = parse_code(
+        &format!("{}::define(swig_module)?;", wrapper_mod_name),
+        "ruby extension initialization",
+    );
+    Ok((
+        parse_code(&code, "ruby class"),
+        module_initialization,
+        declaration,
+    ))
+}
+
+fn generate_method(
+    ctx: &mut RubyContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+    rust_func_name: &str,
+    ruby_name: &str,
+    members: &mut Vec<String>,
+) -> Result<String> {
+    let (convert_args, rbs_args) = convert_method_args(ctx, class, method)?;
+    let convert_this = if let MethodVariant::Method(self_variant) = method.variant {
+        let storage_ty = storage_type(class).expect("method without self_type");
+        let (from_ty, to_ty) =
+            create_suitable_types_for_constructor_and_self(self_variant, class, &storage_ty);
+        let from_ty = ctx.conv_map.find_or_alloc_rust_type(&from_ty, class.src_id);
+        let to_ty = ctx.conv_map.find_or_alloc_rust_type(&to_ty, class.src_id);
+        let (mut deps, convert_this) = ctx.conv_map.convert_rust_types(
+            from_ty.to_idx(),
+            to_ty.to_idx(),
+            "this",
+            "this",
+            CONV_FUNC_RET_TYPE,
+            (class.src_id, method.span()),
+        )?;
+        ctx.rust_code.append(&mut deps);
+        format!(
+            "let this: {} = swig_ruby_unwrap::<RubyClass>(swig_self)?;\n{}",
+            DisplayToTokens(&from_ty.ty),
+            convert_this
+        )
+    } else {
+        String::new()
+    };
+    let ret_ty = extract_return_type(&method.fn_decl.output);
+    let (ret_ty, convert_ret, rbs_ret) = if method.variant == MethodVariant::Constructor {
+        // constructor returns new object, even if `self_type` differs
+        // from type returned by Rust constructor
+        let storage_ty = storage_type(class).expect("constructor without self_type");
+        let call_ret_ty = ctx.conv_map.find_or_alloc_rust_type(&ret_ty, class.src_id);
+        let unwrap_result = if ast::if_result_return_ok_err_types(&call_ret_ty).is_some() {
+            "let swig_ret = match swig_ret { Ok(x) => x, Err(swig_err) => return Err(SwigRubyError::Message(swig_err.to_string())) };\n"
+        } else {
+            ""
+        };
+        (
+            call_ret_ty,
+            format!(
+                "{}let swig_ret: {} = swig_ret;\nlet swig_ret: rb_sys::VALUE = SwigRubyObject::<RubyClass>(swig_ret).swig_into_ruby()?;\n",
+                unwrap_result,
+                DisplayToTokens(&storage_ty),
+            ),
+            class.name.to_string(),
+        )
+    } else {
+        let ret_ty = ctx.conv_map.find_or_alloc_rust_type(&ret_ty, class.src_id);
+        let conv = rust_to_ruby(ctx, &ret_ty, "swig_ret", (class.src_id, method.span()))?;
+        (ret_ty, conv.code, conv.rbs_type)
+    };
+    let is_method = matches!(method.variant, MethodVariant::Method(_));
+    members.push(rbs::method_declaration(
+        &method.doc_comments,
+        method.access != MethodAccess::Public,
+        &if is_method {
+            ruby_name.to_string()
+        } else {
+            format!("self.{}", ruby_name)
+        },
+        &rbs_args,
+        &rbs_ret,
+    ));
+    Ok(format!(
+        r#"
+    unsafe extern "C" fn {func_name}(swig_argc: std::os::raw::c_int, swig_argv: *const rb_sys::VALUE, swig_self: rb_sys::VALUE) -> rb_sys::VALUE {{
+        swig_ruby_call(|| {{
+            let swig_args = swig_ruby_args(swig_argc, swig_argv, {argc})?;
+            {convert_args}
+            {convert_this}
+            let swig_ret: {ret_type} = {call};
+            {convert_ret}
+            Ok(swig_ret)
+        }})
+    }}
+"#,
+        func_name = rust_func_name,
+        argc = rbs_args.len(),
+        convert_args = convert_args,
+        convert_this = convert_this,
+        ret_type = DisplayToTokens(&ret_ty.ty),
+        call = method.generate_code_to_call_rust_func(),
+        convert_ret = convert_ret,
+    ))
+}
+
+/// Code to convert `swig_args` into arguments of Rust method,
+/// and arguments names with RBS types
+fn convert_method_args(
+    ctx: &mut RubyContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+) -> Result<(String, Vec<(String, String)>)> {
+    let skip_n = match method.variant {
+        MethodVariant::Method(_) => 1,
+        _ => 0,
+    };
+    let mut code = String::new();
+    let mut rbs_args = Vec::with_capacity(method.fn_decl.inputs.len());
+    for (i, arg) in method.fn_decl.inputs.iter().skip(skip_n).enumerate() {
+        let named_arg = arg
+            .as_named_arg()
+            .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
+        let arg_ty = ctx
+            .conv_map
+            .find_or_alloc_rust_type(&named_arg.ty, class.src_id);
+        let conv = ruby_to_rust(
+            ctx,
+            &arg_ty,
+            &named_arg.name,
+            (class.src_id, named_arg.ty.span()),
+        )?;
+        writeln!(
+            &mut code,
+            "let {}: rb_sys::VALUE = swig_args[{}];\n{}",
+            named_arg.name, i, conv.code
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        rbs_args.push((named_arg.name.to_string(), conv.rbs_type));
+    }
+    Ok((code, rbs_args))
+}
+
+/// Enum becomes module with integer constants,
+/// Rust side also accepts symbols with names of items
+fn generate_enum(
+    ctx: &mut RubyContext,
+    fenum: &ForeignEnumInfo,
+) -> Result<(TokenStream, TokenStream, String)> {
+    let enum_name = fenum.name.to_string();
+    if !is_ruby_constant_name(&enum_name) {
+        return Err(DiagnosticError::new(
+            fenum.src_id,
+            fenum.name.span(),
+            format!(
+                "Name of Ruby module {} should start with uppercase letter",
+                enum_name
+            ),
+        ));
+    }
+    let wrapper_mod_name = ruby_wrapper_mod_name(&enum_name);
+    let mut from_u32_arms = String::new();
+    let mut from_symbol_arms = String::new();
+    let mut as_u32_arms = String::new();
+    let mut define_items = String::new();
+    // the same values as C++ and Java backends use
+    for (i, item) in fenum.items.iter().enumerate() {
+        let item_name = item.name.to_string();
+        if !is_ruby_constant_name(&item_name) {
+            return Err(DiagnosticError::new(
+                fenum.src_id,
+                item.name.span(),
+                format!(
+                    "Name of Ruby constant {}::{} should start with uppercase letter",
+                    enum_name, item_name
+                ),
+            ));
+        }
+        let rust_name = DisplayToTokens(&item.rust_name);
+        writeln!(&mut from_u32_arms, "{} => Ok({}),", i, rust_name).expect(WRITE_TO_MEM_FAILED_MSG);
+        let snake_name = item_name.to_snake_case();
+        if snake_name == item_name {
+            writeln!(
+                &mut from_symbol_arms,
+                "\"{}\" => Ok({}),",
+                item_name, rust_name
+            )
+        } else {
+            writeln!(
+                &mut from_symbol_arms,
+                "\"{}\" | \"{}\" => Ok({}),",
+                item_name, snake_name, rust_name
+            )
+        }
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(&mut as_u32_arms, "{} => {},", rust_name, i).expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(
+            &mut define_items,
+            "rb_sys::rb_define_const(swig_enum, \"{}\\0\".as_ptr() as *const std::os::raw::c_char, {}u32.swig_into_ruby()?);",
+            item_name, i
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    let code = format!(
+        r#"
+mod {mod_name} {{
+    use super::*;
+
+    /// Enum is passed as value of constant or as symbol, like `:{first_item}`
+    pub unsafe fn from_ruby(value: rb_sys::VALUE) -> SwigRubyResult<{enum_name}> {{
+        if let Some(name) = swig_ruby_symbol_name(value) {{
+            return match name.as_str() {{
+                {from_symbol_arms}
+                _ => Err(SwigRubyError::ArgumentError(format!("{{}} is not valid value for enum {enum_name}", name))),
+            }};
+        }}
+        let value: u32 = SwigFromRuby::swig_from_ruby(value)?;
+        match value {{
+            {from_u32_arms}
+            _ => Err(SwigRubyError::ArgumentError(format!("{{}} is not valid value for enum {enum_name}", value))),
+        }}
+    }}
+
+    pub fn as_u32(value: &{enum_name}) -> u32 {{
+        match *value {{
+            {as_u32_arms}
+        }}
+    }}
+
+    /// Define module with constants inside of extension's module
+    pub unsafe fn define(swig_module: rb_sys::VALUE) -> SwigRubyResult<()> {{
+        let swig_enum = rb_sys::rb_define_module_under(swig_module, "{enum_name}\0".as_ptr() as *const std::os::raw::c_char);
+        {define_items}
+        Ok(())
+    }}
+}}
+"#,
+        mod_name = wrapper_mod_name,
+        enum_name = enum_name,
+        first_item = fenum
+            .items
+            .first()
+            .map(|item| item.name.to_string().to_snake_case())
+            .unwrap_or_default(),
+        from_symbol_arms = from_symbol_arms,
+        from_u32_arms = from_u32_arms,
+        as_u32_arms = as_u32_arms,
+        define_items = define_items,
+    );
+    let declaration = rbs::enum_declaration(ctx, fenum)?;
+    let module_initialization = parse_code(
+        &format!("{}::define(swig_module)?;", wrapper_mod_name),
+        "ruby extension initialization",
+    );
+    Ok((
+        parse_code(&code, "ruby enum"),
+        module_initialization,
+        declaration,
+    ))
+}
+
+/// Generate Rust struct, that holds Ruby object and implements
+/// the callback's trait by calling methods of this object
+fn generate_interface(
+    ctx: &mut RubyContext,
+    interface: &ForeignInterface,
+) -> Result<(TokenStream, TokenStream, String)> {
+    let mut methods_code = String::new();
+    let mut members = Vec::with_capacity(interface.items.len());
+    for method in &interface.items {
+        methods_code.push_str(&generate_interface_method(
+            ctx,
+            interface,
+            method,
+            &mut members,
+        )?);
+    }
+    let code = format!(
+        r#"
+#[allow(unused_variables, unused_mut, unused_unsafe, non_snake_case, clippy::all)]
+mod {mod_name} {{
+    use super::*;
+
+    pub struct RubyCallback(pub SwigRubyCallback);
+
+    impl {trait_name} for RubyCallback {{
+        {methods_code}
+    }}
+}}
+"#,
+        mod_name = ruby_wrapper_mod_name(&interface.name.to_string()),
+        trait_name = DisplayToTokens(&interface.self_type.bounds[0]),
+        methods_code = methods_code,
+    );
+    Ok((
+        parse_code(&code, "ruby callback"),
+        TokenStream::new(),
+        rbs::interface_declaration(interface, &members),
+    ))
+}
+
+fn generate_interface_method(
+    ctx: &mut RubyContext,
+    interface: &ForeignInterface,
+    method: &ForeignInterfaceMethod,
+    members: &mut Vec<String>,
+) -> Result<String> {
+    let src_id = interface.src_id;
+    let method_span = method.rust_name.span();
+    let rust_method_name = &method
+        .rust_name
+        .segments
+        .last()
+        .ok_or_else(|| DiagnosticError::new(src_id, method_span, "Empty trait function name"))?
+        .ident;
+    let ruby_method_name = method.name.to_string();
+    let self_arg = method.fn_decl.inputs[0].as_self_arg(src_id)?;
+
+    let mut args_with_types = Vec::with_capacity(method.fn_decl.inputs.len());
+    let mut convert_args = String::new();
+    let mut ruby_args = Vec::with_capacity(method.fn_decl.inputs.len());
+    let mut rbs_args = Vec::with_capacity(method.fn_decl.inputs.len());
+    for arg in method.fn_decl.inputs.iter().skip(1) {
+        let named_arg = arg
+            .as_named_arg()
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+        args_with_types.push(format!(
+            "{}: {}",
+            named_arg.name,
+            DisplayToTokens(&named_arg.ty)
+        ));
+        let arg_ty = ctx.conv_map.find_or_alloc_rust_type(&named_arg.ty, src_id);
+        let conv = rust_to_ruby(ctx, &arg_ty, &named_arg.name, (src_id, named_arg.ty.span()))?;
+        convert_args.push_str(&conv.code);
+        ruby_args.push(named_arg.name.clone());
+        rbs_args.push((named_arg.name.to_string(), conv.rbs_type));
+    }
+
+    let ret_ty = extract_return_type(&method.fn_decl.output);
+    let ok_err_types =
+        ast::if_result_return_ok_err_types(&ctx.conv_map.find_or_alloc_rust_type(&ret_ty, src_id));
+    let ok_ty = match ok_err_types {
+        Some((ref ok_ty, _)) => ok_ty.clone(),
+        None => ret_ty.clone(),
+    };
+    let unit_ty: Type = parse_type! { () };
+    let (convert_ret, rbs_ret) = if ok_ty == unit_ty {
+        ("let swig_ret: () = ();".to_string(), "void".to_string())
+    } else {
+        if let Type::Reference(_) = ok_ty {
+            return Err(DiagnosticError::new(
+                src_id,
+                method_span,
+                "Returning a reference from Ruby callback is not supported",
+            ));
+        }
+        let ok_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&ok_ty, src_id);
+        let conv = ruby_to_rust(ctx, &ok_ty_rust_ty, "swig_ret", (src_id, ok_ty.span()))?;
+        (conv.code, conv.rbs_type)
+    };
+    let error_handling = if ok_err_types.is_some() {
+        // Ruby exception is converted to the error type of the callback,
+        // so the error type should implement `From<String>`
+        "swig_ret.map_err(|err| From::from(err.to_string()))".to_string()
+    } else if ok_ty == unit_ty {
+        format!(
+            r#"if let Err(err) = swig_ret {{
+                eprintln!("Ruby callback {} failed: {{}}", err);
+            }}"#,
+            ruby_method_name
+        )
+    } else {
+        format!(
+            r#"swig_ret.unwrap_or_else(|err| panic!("Ruby callback {} failed: {{}}", err))"#,
+            ruby_method_name
+        )
+    };
+    members.push(rbs::method_declaration(
+        &method.doc_comments,
+        false,
+        &ruby_method_name,
+        &rbs_args,
+        &rbs_ret,
+    ));
+    Ok(format!(
+        r#"
+        fn {rust_method_name}({self_arg}, {args_with_types}) {output} {{
+            let swig_ret: SwigRubyResult<{ok_ty}> = (|| unsafe {{
+                {convert_args}
+                let swig_ret: rb_sys::VALUE = self.0.call_method("{ruby_method_name}", &[{ruby_args}])?;
+                {convert_ret}
+                Ok(swig_ret)
+            }})();
+            {error_handling}
+        }}
+"#,
+        rust_method_name = rust_method_name,
+        self_arg = self_arg,
+        args_with_types = args_with_types.join(", "),
+        output = DisplayToTokens(&method.fn_decl.output),
+        ok_ty = DisplayToTokens(&ok_ty),
+        convert_args = convert_args,
+        ruby_method_name = ruby_method_name,
+        ruby_args = ruby_args.join(", "),
+        convert_ret = convert_ret,
+        error_handling = error_handling,
+    ))
+}
+
+fn generate_module_initialization(
+    extension_name: &str,
+    module_name: &str,
+    module_initialization_code: &[TokenStream],
+) -> TokenStream {
+    // the same name that `require` uses to find initialization function
+    let init_func = syn::Ident::new(
+        &format!("Init_{}", extension_name),
+        proc_macro2::Span::call_site(),
+    );
+    let module_name = format!("{}\0", module_name);
+    quote::quote! {
+        #[no_mangle]
+        #[allow(non_snake_case)]
+        pub unsafe extern "C" fn #init_func() {
+            swig_ruby_call(|| {
+                let swig_module = swig_ruby_define_module(#module_name);
+                #( #module_initialization_code )*
+                Ok(SWIG_RUBY_QNIL)
+            });
+        }
+    }
+}
+
+/// Code to convert Ruby value in variable `var` into Rust value of type `rust_type`
+fn ruby_to_rust(
+    ctx: &mut RubyContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<RubyConversion> {
+    let (src_id, span) = arg_ty_span;
+    // `&str` is handled below as reference to `String`
+    let is_str_ref = rust_type.normalized_name == "& str";
+    if let Some(rbs_type) = ruby_supported_type(rust_type).filter(|_| !is_str_ref) {
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: {ty} = SwigFromRuby::swig_from_ruby({var})?;\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+            ),
+            rbs_type: rbs_type.into(),
+        })
+    } else if let Some(conv) = if_exported_class_ruby_to_rust(ctx, rust_type, var, arg_ty_span)? {
+        Ok(conv)
+    } else if implements(rust_type, ENUM_TRAIT_NAME) {
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: {ty} = {mod_name}::from_ruby({var})?;\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                mod_name = ruby_wrapper_mod_name(&rust_type.normalized_name),
+            ),
+            rbs_type: "Integer | Symbol".into(),
+        })
+    } else if implements(rust_type, INTERFACE_TRAIT_NAME) {
+        let interface_name = ctx
+            .conv_map
+            .find_foreign_type_related_to_rust_ty(rust_type.to_idx())
+            .map(|ftype| ctx.conv_map[ftype].typename())
+            .ok_or_else(|| {
+                DiagnosticError::new(
+                    src_id,
+                    span,
+                    format!("No callback registered for type: {}", rust_type),
+                )
+            })?;
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: {ty} = Box::new({mod_name}::RubyCallback(SwigRubyCallback::new({var}, \"{name}\")?));\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                mod_name = ruby_wrapper_mod_name(&interface_name),
+                name = interface_name,
+            ),
+            rbs_type: format!("_{}", interface_name),
+        })
+    } else if let Some(conv) = map_type(ctx, rust_type, Direction::Incoming, arg_ty_span, var)? {
+        Ok(conv)
+    } else if let Some(inner) = ast::if_option_return_some_type(rust_type) {
+        let inner_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&inner, src_id);
+        let inner = ruby_to_rust(ctx, &inner_rust_ty, var, arg_ty_span)?;
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: {ty} = if swig_ruby_is_nil({var}) {{ None }} else {{\n{inner}Some({var}) }};\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                inner = inner.code,
+            ),
+            rbs_type: rbs_optional_type(&inner.rbs_type),
+        })
+    } else if let Some(elem) = if_vec_return_elem_type(rust_type) {
+        let elem = ctx.conv_map.find_or_alloc_rust_type(&elem, src_id);
+        let inner = ruby_to_rust(ctx, &elem, var, arg_ty_span)?;
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: {ty} = swig_ruby_array_elements({var}, |{var}| -> SwigRubyResult<{elem}> {{\n{inner}Ok({var}) }})?;\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                elem = DisplayToTokens(&elem.ty),
+                inner = inner.code,
+            ),
+            rbs_type: format!("Array[{}]", inner.rbs_type),
+        })
+    } else if let Some((key_ty, value_ty)) = if_hash_map_return_key_value_types(rust_type) {
+        let key_ty = ctx.conv_map.find_or_alloc_rust_type(&key_ty, src_id);
+        let value_ty = ctx.conv_map.find_or_alloc_rust_type(&value_ty, src_id);
+        let key = ruby_to_rust(ctx, &key_ty, "swig_key", arg_ty_span)?;
+        let value = ruby_to_rust(ctx, &value_ty, "swig_value", arg_ty_span)?;
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: {ty} = swig_ruby_hash_pairs({var}, |swig_key, swig_value| -> SwigRubyResult<({key_ty}, {value_ty})> {{\n{key}{value}Ok((swig_key, swig_value)) }})?.into_iter().collect();\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                key_ty = DisplayToTokens(&key_ty.ty),
+                value_ty = DisplayToTokens(&value_ty.ty),
+                key = key.code,
+                value = value.code,
+            ),
+            rbs_type: format!("Hash[{}, {}]", key.rbs_type, value.rbs_type),
+        })
+    } else if let Type::Reference(ref reference) = rust_type.ty {
+        if reference.mutability.is_some() {
+            return Err(DiagnosticError::new(
+                src_id,
+                span,
+                "mutable reference is only supported for exported class types",
+            ));
+        }
+        // `&[T]` is converted as `Vec<T>`, `&str` as `String`
+        let owned_ty = match *reference.elem {
+            Type::Slice(ref slice) => {
+                let elem = &slice.elem;
+                parse_type! { Vec<#elem> }
+            }
+            ref elem if *elem == parse_type! { str } => parse_type! { String },
+            ref elem => elem.clone(),
+        };
+        let owned_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&owned_ty, src_id);
+        let inner = ruby_to_rust(ctx, &owned_ty_rust_ty, var, arg_ty_span)?;
+        Ok(RubyConversion {
+            code: format!(
+                "{inner}let {var}: {ty} = &{var};\n",
+                inner = inner.code,
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+            ),
+            rbs_type: inner.rbs_type,
+        })
+    } else {
+        Err(DiagnosticError::new(
+            src_id,
+            span,
+            format!("Unsupported argument type: {}", rust_type),
+        ))
+    }
+}
+
+/// Code to convert Rust value in variable `var` into Ruby value
+/// in variable with the same name
+fn rust_to_ruby(
+    ctx: &mut RubyContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<RubyConversion> {
+    let (src_id, span) = arg_ty_span;
+    let into_ruby_code = format!(
+        "let {var}: rb_sys::VALUE = {var}.swig_into_ruby()?;\n",
+        var = var
+    );
+    if rust_type.ty == parse_type! { () } {
+        Ok(RubyConversion {
+            code: into_ruby_code,
+            rbs_type: "void".into(),
+        })
+    } else if let Some(rbs_type) = ruby_supported_type(rust_type) {
+        Ok(RubyConversion {
+            code: into_ruby_code,
+            rbs_type: rbs_type.into(),
+        })
+    } else if let Some(conv) = if_exported_class_rust_to_ruby(ctx, rust_type, var, arg_ty_span)? {
+        Ok(conv)
+    } else if implements(rust_type, ENUM_TRAIT_NAME) {
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: rb_sys::VALUE = {mod_name}::as_u32(&{var}).swig_into_ruby()?;\n",
+                var = var,
+                mod_name = ruby_wrapper_mod_name(&rust_type.normalized_name),
+            ),
+            rbs_type: "Integer".into(),
+        })
+    } else if let Some(conv) = map_type(ctx, rust_type, Direction::Outgoing, arg_ty_span, var)? {
+        Ok(conv)
+    } else if let Some(inner) = ast::if_option_return_some_type(rust_type) {
+        let inner_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&inner, src_id);
+        let inner = rust_to_ruby(ctx, &inner_rust_ty, var, arg_ty_span)?;
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: rb_sys::VALUE = match {var} {{\nSome({var}) => {{\n{inner}{var}\n}}\nNone => SWIG_RUBY_QNIL,\n}};\n",
+                var = var,
+                inner = inner.code,
+            ),
+            rbs_type: rbs_optional_type(&inner.rbs_type),
+        })
+    } else if let Some(elem) = if_vec_return_elem_type(rust_type) {
+        let elem_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&elem, src_id);
+        let inner = rust_to_ruby(ctx, &elem_rust_ty, var, arg_ty_span)?;
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: rb_sys::VALUE = {{\nlet swig_ary = rb_sys::rb_ary_new_capa({var}.len() as std::os::raw::c_long);\nfor {var} in {var} {{\n{inner}rb_sys::rb_ary_push(swig_ary, {var});\n}}\nswig_ary\n}};\n",
+                var = var,
+                inner = inner.code,
+            ),
+            rbs_type: format!("Array[{}]", inner.rbs_type),
+        })
+    } else if let Some((key_ty, value_ty)) = if_hash_map_return_key_value_types(rust_type) {
+        let key_ty = ctx.conv_map.find_or_alloc_rust_type(&key_ty, src_id);
+        let value_ty = ctx.conv_map.find_or_alloc_rust_type(&value_ty, src_id);
+        let key = rust_to_ruby(ctx, &key_ty, "swig_key", arg_ty_span)?;
+        let value = rust_to_ruby(ctx, &value_ty, "swig_value", arg_ty_span)?;
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: rb_sys::VALUE = {{\nlet swig_hash = rb_sys::rb_hash_new();\nfor (swig_key, swig_value) in {var} {{\n{key}{value}rb_sys::rb_hash_aset(swig_hash, swig_key, swig_value);\n}}\nswig_hash\n}};\n",
+                var = var,
+                key = key.code,
+                value = value.code,
+            ),
+            rbs_type: format!("Hash[{}, {}]", key.rbs_type, value.rbs_type),
+        })
+    } else if let Some((ok_ty, _err_ty)) = ast::if_result_return_ok_err_types(rust_type) {
+        let ok_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&ok_ty, src_id);
+        let inner = rust_to_ruby(ctx, &ok_ty_rust_ty, var, arg_ty_span)?;
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: {ok_ty} = match {var} {{\nOk(x) => x,\nErr(swig_err) => return Err(SwigRubyError::Message(swig_err.to_string())),\n}};\n{inner}",
+                var = var,
+                ok_ty = DisplayToTokens(&ok_ty),
+                inner = inner.code,
+            ),
+            rbs_type: inner.rbs_type,
+        })
+    } else if let Type::Reference(ref reference) = rust_type.ty {
+        // Ruby side gets copy of value
+        let (owned_ty, method) = match *reference.elem {
+            Type::Slice(ref slice) => {
+                let elem = &slice.elem;
+                (parse_type! { Vec<#elem> }, "to_vec()")
+            }
+            ref elem => (elem.clone(), "clone()"),
+        };
+        let owned_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&owned_ty, src_id);
+        let inner = rust_to_ruby(ctx, &owned_ty_rust_ty, var, arg_ty_span)?;
+        Ok(RubyConversion {
+            code: format!(
+                "let {var}: {ty} = {var}.{method};\n{inner}",
+                var = var,
+                ty = DisplayToTokens(&owned_ty),
+                method = method,
+                inner = inner.code,
+            ),
+            rbs_type: inner.rbs_type,
+        })
+    } else {
+        Err(DiagnosticError::new(
+            src_id,
+            span,
+            format!("Unsupported return type: {}", rust_type),
+        ))
+    }
+}
+
+/// Types that runtime converts by itself with `SwigFromRuby` and `SwigIntoRuby`,
+/// returns name of RBS type
+fn ruby_supported_type(rust_type: &RustType) -> Option<&'static str> {
+    match rust_type.normalized_name.as_str() {
+        "bool" => Some("bool"),
+        "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" => {
+            Some("Integer")
+        }
+        "f32" | "f64" => Some("Float"),
+        "String" | "& str" => Some("String"),
+        _ => None,
+    }
+}
+
+/// Class exported to Ruby, with type that is passed to or returned from Rust
+struct ClassUsage {
+    class: ForeignClassInfo,
+    storage_ty: Type,
+    /// `&T` or `&mut T`
+    reference: Option<bool>,
+    /// `T` without reference, it is either self type or storage type
+    unref_ty: RustType,
+}
+
+fn if_exported_class(
+    ctx: &mut RubyContext,
+    rust_type: &RustType,
+    src_id: crate::source_registry::SourceId,
+) -> Option<ClassUsage> {
+    let (reference, unref_ty) = match rust_type.ty {
+        Type::Reference(ref reference) => (
+            Some(reference.mutability.is_some()),
+            ctx.conv_map
+                .find_or_alloc_rust_type(&reference.elem, src_id),
+        ),
+        _ => (None, rust_type.clone()),
+    };
+    let class = ctx
+        .conv_map
+        .find_foreigner_class_with_such_this_type(&unref_ty.ty, |_, fc| {
+            fc.self_desc.as_ref().map(|x| x.self_type.clone())
+        })
+        .or_else(|| {
+            ctx.conv_map
+                .find_foreigner_class_with_such_this_type(&unref_ty.ty, |_, fc| storage_type(fc))
+        })?
+        .clone();
+    let storage_ty = storage_type(&class)?;
+    Some(ClassUsage {
+        class,
+        storage_ty,
+        reference,
+        unref_ty,
+    })
+}
+
+fn if_exported_class_ruby_to_rust(
+    ctx: &mut RubyContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<Option<RubyConversion>> {
+    let (src_id, span) = arg_ty_span;
+    let usage = match if_exported_class(ctx, rust_type, src_id) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let class = &usage.class;
+    let storage_ty = &usage.storage_ty;
+    let mutable = usage.reference.unwrap_or(false);
+    let ref_prefix = if mutable { "&mut " } else { "&" };
+    let mut code = format!(
+        "let {var}: {ref_prefix}{storage} = swig_ruby_unwrap::<{mod_name}::RubyClass>({var})?;\n",
+        var = var,
+        ref_prefix = ref_prefix,
+        storage = DisplayToTokens(storage_ty),
+        mod_name = ruby_wrapper_mod_name(&class.name.to_string()),
+    );
+    let storage_rust_ty = ctx.conv_map.find_or_alloc_rust_type(storage_ty, src_id);
+    if usage.unref_ty.normalized_name != storage_rust_ty.normalized_name {
+        // `&Rc<RefCell<T>>` -> `&T` and so on
+        let from_ty = ctx.conv_map.find_or_alloc_rust_type(
+            &ast::parse_ty_with_given_span(
+                &format!("{}{}", ref_prefix, DisplayToTokens(storage_ty)),
+                span,
+            )
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?,
+            src_id,
+        );
+        let to_ty = ctx.conv_map.find_or_alloc_rust_type(
+            &ast::parse_ty_with_given_span(
+                &format!("{}{}", ref_prefix, DisplayToTokens(&usage.unref_ty.ty)),
+                span,
+            )
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?,
+            src_id,
+        );
+        let (mut deps, conv_code) = ctx.conv_map.convert_rust_types(
+            from_ty.to_idx(),
+            to_ty.to_idx(),
+            var,
+            var,
+            CONV_FUNC_RET_TYPE,
+            arg_ty_span,
+        )?;
+        ctx.rust_code.append(&mut deps);
+        code.push_str(&conv_code);
+        code.push('\n');
+    }
+    if usage.reference.is_none() {
+        append_clone_if_supported(ctx, &usage, var, arg_ty_span, &mut code)?;
+    }
+    Ok(Some(RubyConversion {
+        code,
+        rbs_type: class.name.to_string(),
+    }))
+}
+
+fn if_exported_class_rust_to_ruby(
+    ctx: &mut RubyContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<Option<RubyConversion>> {
+    let (src_id, _) = arg_ty_span;
+    let usage = match if_exported_class(ctx, rust_type, src_id) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let mut code = String::new();
+    if usage.reference.is_some() {
+        append_clone_if_supported(ctx, &usage, var, arg_ty_span, &mut code)?;
+    }
+    let storage_rust_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&usage.storage_ty, src_id);
+    if usage.unref_ty.normalized_name != storage_rust_ty.normalized_name {
+        let (mut deps, conv_code) = ctx.conv_map.convert_rust_types(
+            usage.unref_ty.to_idx(),
+            storage_rust_ty.to_idx(),
+            var,
+            var,
+            CONV_FUNC_RET_TYPE,
+            arg_ty_span,
+        )?;
+        ctx.rust_code.append(&mut deps);
+        code.push_str(&conv_code);
+        code.push('\n');
+    }
+    writeln!(
+        &mut code,
+        "let {var}: rb_sys::VALUE = SwigRubyObject::<{mod_name}::RubyClass>({var}).swig_into_ruby()?;",
+        var = var,
+        mod_name = ruby_wrapper_mod_name(&usage.class.name.to_string()),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    Ok(Some(RubyConversion {
+        code,
+        rbs_type: usage.class.name.to_string(),
+    }))
+}
+
+/// Ruby object owns Rust object, so Rust code can get only copy of it
+fn append_clone_if_supported(
+    ctx: &mut RubyContext,
+    usage: &ClassUsage,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+    code: &mut String,
+) -> Result<()> {
+    let (src_id, span) = arg_ty_span;
+    let is_shared_ptr = ["Rc", "Arc"].iter().any(|smart_ptr| {
+        ast::check_if_smart_pointer_return_inner_type(&usage.unref_ty, smart_ptr).is_some()
+    });
+    if !is_shared_ptr && !usage.class.clone_derived() && !usage.class.copy_derived() {
+        return Err(DiagnosticError::new(
+            src_id,
+            span,
+            format!(
+                "Passing object of class {} by value requires that it is marked with \
+                 `#[derive(Clone)]` or `#[derive(Copy)]` inside its `foreign_class` macro",
+                usage.class.name
+            ),
+        ));
+    }
+    let unref_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&usage.unref_ty.ty, src_id);
+    writeln!(
+        code,
+        "let {var}: {ty} = {var}.clone();",
+        var = var,
+        ty = DisplayToTokens(&unref_ty.ty),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    Ok(())
+}
+
+/// Conversions of Rust types described by `foreign_typemap!` rules:
+/// Rust type is converted to the type mentioned in rule, and then to Ruby.
+/// Only not generic rules are supported.
+fn map_type(
+    ctx: &mut RubyContext,
+    rust_type: &RustType,
+    direction: Direction,
+    arg_ty_span: SourceIdSpan,
+    var: &str,
+) -> Result<Option<RubyConversion>> {
+    let ftype_idx = match ctx.conv_map.map_through_conversation_to_foreign(
+        rust_type.to_idx(),
+        direction,
+        MapToForeignFlag::FastSearch,
+        arg_ty_span,
+        |_, fc| storage_type(fc),
+    ) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let ftype = &ctx.conv_map[ftype_idx];
+    let rule = match direction {
+        Direction::Outgoing => ftype.into_from_rust.as_ref(),
+        Direction::Incoming => ftype.from_into_rust.as_ref(),
+    }
+    .expect("Internal error: foreign type was found without conversion rule");
+    if rule.intermediate.is_some() {
+        return Err(DiagnosticError::new2(
+            ftype.src_id_span(),
+            format!(
+                "f_type {}: conversion code on foreign side is not supported for Ruby",
+                ftype.name
+            ),
+        ));
+    }
+    let rbs_type = ftype.typename().to_string();
+    let ruby_rust_ty = rule.rust_ty;
+    if ruby_rust_ty == rust_type.to_idx() {
+        return Ok(None);
+    }
+    let (from, to) = match direction {
+        Direction::Outgoing => (rust_type.to_idx(), ruby_rust_ty),
+        Direction::Incoming => (ruby_rust_ty, rust_type.to_idx()),
+    };
+    let (mut deps, conv_code) =
+        ctx.conv_map
+            .convert_rust_types(from, to, var, var, CONV_FUNC_RET_TYPE, arg_ty_span)?;
+    ctx.rust_code.append(&mut deps);
+    let ruby_rust_ty = ctx.conv_map[ruby_rust_ty].clone();
+    let code = match direction {
+        Direction::Outgoing => {
+            let conv = rust_to_ruby(ctx, &ruby_rust_ty, var, arg_ty_span)?;
+            format!("{}\n{}", conv_code, conv.code)
+        }
+        Direction::Incoming => {
+            let conv = ruby_to_rust(ctx, &ruby_rust_ty, var, arg_ty_span)?;
+            format!("{}{}\n", conv.code, conv_code)
+        }
+    };
+    Ok(Some(RubyConversion { code, rbs_type }))
+}
+
+/// Type of Rust object, that Ruby object holds
+fn storage_type(class: &ForeignClassInfo) -> Option<Type> {
+    class.self_desc.as_ref().map(|x| {
+        ast::if_ty_result_return_ok_type(&x.constructor_ret_type)
+            .unwrap_or_else(|| x.constructor_ret_type.clone())
+    })
+}
+
+fn implements(rust_type: &RustType, trait_name: &str) -> bool {
+    let trait_path: syn::Path = syn::Ident::new(trait_name, proc_macro2::Span::call_site()).into();
+    rust_type.implements.contains_path(&trait_path)
+}
+
+/// Names of Ruby classes, modules and constants should start with uppercase letter
+fn is_ruby_constant_name(name: &str) -> bool {
+    matches!(name.chars().next(), Some(ch) if ch.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+fn rbs_optional_type(inner: &str) -> String {
+    if inner.contains(' ') {
+        format!("({})?", inner)
+    } else {
+        format!("{}?", inner)
+    }
+}
+
+fn if_vec_return_elem_type(ty: &RustType) -> Option<Type> {
+    ast::check_if_smart_pointer_return_inner_type(ty, "Vec")
+}
+
+/// `HashMap<K, V>` is converted to Ruby hash with the same keys
+fn if_hash_map_return_key_value_types(ty: &RustType) -> Option<(Type, Type)> {
+    let path = match ty.ty {
+        Type::Path(ref type_path) if type_path.qself.is_none() => &type_path.path,
+        _ => return None,
+    };
+    let last = path.segments.last()?;
+    if last.ident != "HashMap" {
+        return None;
+    }
+    let args = match last.arguments {
+        syn::PathArguments::AngleBracketed(ref args) => &args.args,
+        _ => return None,
+    };
+    let mut types = args.iter().filter_map(|arg| match arg {
+        syn::GenericArgument::Type(ref ty) => Some(ty.clone()),
+        _ => None,
+    });
+    match (types.next(), types.next(), types.next()) {
+        (Some(key), Some(value), None) => Some((key, value)),
+        _ => None,
+    }
+}
+
+fn extract_return_type(syn_return_type: &syn::ReturnType) -> Type {
+    match syn_return_type {
+        syn::ReturnType::Default => {
+            parse_type! { () }
+        }
+        syn::ReturnType::Type(_, ref ty) => ty.deref().clone(),
+    }
+}
+
+fn boxed_interface_type(interface: &ForeignInterface) -> Result<Type> {
+    let boxed_trait_name = format!("Box<dyn {}>", DisplayToTokens(&interface.self_type));
+    ast::parse_ty_with_given_span(&boxed_trait_name, interface.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(interface.src_id, err))
+}
+
+fn ruby_wrapper_mod_name(type_name: &str) -> String {
+    format!("ruby_{}", type_name.to_snake_case())
+}
+
+fn parse_code(code: &str, id_of_code: &str) -> TokenStream {
+    syn::parse_str(code).unwrap_or_else(|err| panic_on_syn_error(id_of_code, code.to_string(), err))
+}
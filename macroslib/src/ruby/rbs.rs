@@ -0,0 +1,177 @@
+//! Generation of `.rbs` file with [RBS](https://github.com/ruby/rbs) signatures,
+//! so type checkers and IDEs know signatures of the generated Ruby extension
+
+use super::*;
+use crate::{
+    extension::{extend_foreign_class, extend_foreign_enum},
+    file_cache::FileWriteCache,
+    KNOWN_CLASS_DERIVES,
+};
+use std::io::Write as IoWrite;
+
+/// Words that can not be used as names of parameters
+const RBS_RESERVED_WORDS: &[&str] = &[
+    "alias",
+    "attr_accessor",
+    "attr_reader",
+    "attr_writer",
+    "bool",
+    "bot",
+    "class",
+    "def",
+    "end",
+    "extend",
+    "false",
+    "include",
+    "instance",
+    "interface",
+    "module",
+    "nil",
+    "out",
+    "prepend",
+    "private",
+    "public",
+    "self",
+    "singleton",
+    "super",
+    "top",
+    "true",
+    "type",
+    "unchecked",
+    "untyped",
+    "void",
+];
+
+pub(in crate::ruby) fn write_signatures(
+    ctx: &mut RubyContext,
+    declarations: &[String],
+) -> Result<()> {
+    let path = ctx
+        .cfg
+        .output_dir
+        .join(format!("{}.rbs", ctx.cfg.extension_name));
+    let mut file = FileWriteCache::new(&path, &mut ctx.generated_foreign_files);
+    writeln!(
+        &mut file,
+        "# Automatically generated by flapigen\nmodule {}\n  class Error < StandardError\n  end",
+        ctx.cfg.module_name
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for declaration in declarations {
+        writeln!(&mut file).expect(WRITE_TO_MEM_FAILED_MSG);
+        file.write_all(declaration.as_bytes())
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(&mut file, "end").expect(WRITE_TO_MEM_FAILED_MSG);
+    file.update_file_if_necessary().map_err(|err| {
+        DiagnosticError::map_any_err_to_our_err(format!(
+            "write to {} failed: {}",
+            path.display(),
+            err
+        ))
+    })
+}
+
+pub(in crate::ruby) fn class_declaration(
+    ctx: &RubyContext,
+    class: &ForeignClassInfo,
+    members: &[String],
+) -> Result<String> {
+    let mut out = String::new();
+    write_doc_comments(&mut out, "  ", &class.doc_comments);
+    writeln!(&mut out, "  class {}", class.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    for member in members {
+        out.push_str(member);
+    }
+    if !class.foreign_code.is_empty() {
+        writeln!(&mut out, "{}", class.foreign_code).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    out.push_str("  end\n");
+    let mut cnt = out.into_bytes();
+    extend_foreign_class(
+        class,
+        &mut cnt,
+        &KNOWN_CLASS_DERIVES,
+        &[],
+        ctx.class_ext_handlers,
+        ctx.method_ext_handlers,
+    )?;
+    String::from_utf8(cnt).map_err(DiagnosticError::map_any_err_to_our_err)
+}
+
+pub(in crate::ruby) fn enum_declaration(
+    ctx: &RubyContext,
+    fenum: &ForeignEnumInfo,
+) -> Result<String> {
+    let mut out = String::new();
+    write_doc_comments(&mut out, "  ", &fenum.doc_comments);
+    writeln!(&mut out, "  module {}", fenum.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    for item in &fenum.items {
+        write_doc_comments(&mut out, "    ", &item.doc_comments);
+        writeln!(&mut out, "    {}: Integer", item.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    out.push_str("  end\n");
+    let mut cnt = out.into_bytes();
+    extend_foreign_enum(fenum, &mut cnt, ctx.enum_ext_handlers)?;
+    String::from_utf8(cnt).map_err(DiagnosticError::map_any_err_to_our_err)
+}
+
+/// Callback is any object with such methods, so it is described as interface
+pub(in crate::ruby) fn interface_declaration(
+    interface: &ForeignInterface,
+    members: &[String],
+) -> String {
+    let mut out = String::new();
+    write_doc_comments(&mut out, "  ", &interface.doc_comments);
+    writeln!(&mut out, "  interface _{}", interface.name).expect(WRITE_TO_MEM_FAILED_MSG);
+    for member in members {
+        out.push_str(member);
+    }
+    out.push_str("  end\n");
+    out
+}
+
+/// Declaration of method, `name` is like `self.func` for singleton methods
+pub(in crate::ruby) fn method_declaration(
+    doc_comments: &[String],
+    private: bool,
+    name: &str,
+    args: &[(String, String)],
+    ret_type: &str,
+) -> String {
+    let mut out = String::new();
+    write_doc_comments(&mut out, "    ", doc_comments);
+    let args = args
+        .iter()
+        .map(|(name, rbs_type)| format!("{} {}", rbs_type, rbs_ident(name)))
+        .collect::<Vec<_>>();
+    writeln!(
+        &mut out,
+        "    {}def {}: ({}) -> {}",
+        if private { "private " } else { "" },
+        name,
+        args.join(", "),
+        ret_type
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    out
+}
+
+fn rbs_ident(name: &str) -> String {
+    if RBS_RESERVED_WORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn write_doc_comments(out: &mut String, indent: &str, doc_comments: &[String]) {
+    for comment in doc_comments {
+        let comment = comment.trim_end();
+        if comment.is_empty() {
+            writeln!(out, "{}#", indent).expect(WRITE_TO_MEM_FAILED_MSG);
+        } else {
+            writeln!(out, "{}#{}", indent, comment).expect(WRITE_TO_MEM_FAILED_MSG);
+        }
+    }
+}
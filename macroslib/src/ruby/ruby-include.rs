@@ -0,0 +1,648 @@
+mod swig_foreign_types_map {}
+
+#[allow(dead_code)]
+const SWIG_RUBY_QNIL: rb_sys::VALUE = rb_sys::Qnil as rb_sys::VALUE;
+#[allow(dead_code)]
+const SWIG_RUBY_QTRUE: rb_sys::VALUE = rb_sys::Qtrue as rb_sys::VALUE;
+#[allow(dead_code)]
+const SWIG_RUBY_QFALSE: rb_sys::VALUE = rb_sys::Qfalse as rb_sys::VALUE;
+// Values from `ruby/internal/core/rtypeddata.h` and `ruby/st.h`
+#[allow(dead_code)]
+const SWIG_RUBY_TYPED_FREE_IMMEDIATELY: rb_sys::VALUE = 1;
+#[allow(dead_code)]
+const SWIG_RUBY_ST_CONTINUE: std::os::raw::c_int = 0;
+
+/// `<Module>::Error`, it is created during initialization of extension
+static SWIG_RUBY_ERROR_CLASS: std::sync::atomic::AtomicUsize =
+    std::sync::atomic::AtomicUsize::new(0);
+
+/// Error, that is raised as Ruby exception when it reaches Ruby side
+#[allow(dead_code)]
+#[derive(Debug)]
+enum SwigRubyError {
+    /// Exception raised by Ruby code
+    Exception(rb_sys::VALUE),
+    TypeError(String),
+    ArgumentError(String),
+    /// Raised as `<Module>::Error`
+    Message(String),
+}
+
+impl std::fmt::Display for SwigRubyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SwigRubyError::Exception(exc) => {
+                let msg = unsafe {
+                    swig_ruby_protect(|| rb_sys::rb_obj_as_string(*exc))
+                        .ok()
+                        .and_then(|msg| String::swig_from_ruby(msg).ok())
+                };
+                f.write_str(msg.as_deref().unwrap_or("Ruby exception"))
+            }
+            SwigRubyError::TypeError(msg)
+            | SwigRubyError::ArgumentError(msg)
+            | SwigRubyError::Message(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl SwigRubyError {
+    unsafe fn into_exception(self) -> rb_sys::VALUE {
+        let (class, msg) = match self {
+            SwigRubyError::Exception(exc) => return exc,
+            SwigRubyError::TypeError(msg) => (rb_sys::rb_eTypeError, msg),
+            SwigRubyError::ArgumentError(msg) => (rb_sys::rb_eArgError, msg),
+            SwigRubyError::Message(msg) => {
+                let class = SWIG_RUBY_ERROR_CLASS.load(std::sync::atomic::Ordering::Relaxed);
+                if class == 0 {
+                    (rb_sys::rb_eRuntimeError, msg)
+                } else {
+                    (class as rb_sys::VALUE, msg)
+                }
+            }
+        };
+        rb_sys::rb_exc_new(
+            class,
+            msg.as_ptr() as *const std::os::raw::c_char,
+            msg.len() as std::os::raw::c_long,
+        )
+    }
+}
+
+#[allow(dead_code)]
+type SwigRubyResult<T> = Result<T, SwigRubyError>;
+
+/// Run `f` with `rb_protect`, so Ruby exception does not `longjmp` through Rust code.
+/// `f` should only call Ruby API functions.
+#[allow(dead_code)]
+unsafe fn swig_ruby_protect<T, F>(f: F) -> SwigRubyResult<T>
+where
+    F: FnOnce() -> T,
+{
+    unsafe extern "C" fn call<T, F: FnOnce() -> T>(data: rb_sys::VALUE) -> rb_sys::VALUE {
+        let data = &mut *(data as *mut (Option<F>, Option<T>));
+        if let Some(f) = data.0.take() {
+            data.1 = Some(f());
+        }
+        SWIG_RUBY_QNIL
+    }
+    let mut data: (Option<F>, Option<T>) = (Some(f), None);
+    let mut state = 0;
+    rb_sys::rb_protect(
+        Some(call::<T, F>),
+        &mut data as *mut (Option<F>, Option<T>) as rb_sys::VALUE,
+        &mut state,
+    );
+    if state != 0 {
+        let exc = rb_sys::rb_errinfo();
+        rb_sys::rb_set_errinfo(SWIG_RUBY_QNIL);
+        return Err(SwigRubyError::Exception(exc));
+    }
+    Ok(data.1.expect("rb_protect: function was not called"))
+}
+
+/// Run body of function called from Ruby, error is raised as Ruby exception.
+/// `rb_exc_raise` does `longjmp`, so it is called only after all Rust values are dropped.
+#[allow(dead_code, unreachable_code)]
+unsafe fn swig_ruby_call<F>(f: F) -> rb_sys::VALUE
+where
+    F: FnOnce() -> SwigRubyResult<rb_sys::VALUE>,
+{
+    let exc = match f() {
+        Ok(ret) => return ret,
+        Err(err) => err.into_exception(),
+    };
+    rb_sys::rb_exc_raise(exc);
+    SWIG_RUBY_QNIL
+}
+
+/// Arguments of method defined with arity -1
+#[allow(dead_code)]
+unsafe fn swig_ruby_args<'a>(
+    argc: std::os::raw::c_int,
+    argv: *const rb_sys::VALUE,
+    expected: usize,
+) -> SwigRubyResult<&'a [rb_sys::VALUE]> {
+    if argc < 0 || argc as usize != expected {
+        return Err(SwigRubyError::ArgumentError(format!(
+            "wrong number of arguments (given {}, expected {})",
+            argc, expected
+        )));
+    }
+    if expected == 0 {
+        return Ok(&[]);
+    }
+    Ok(std::slice::from_raw_parts(argv, expected))
+}
+
+#[allow(dead_code)]
+type SwigRubyMethod = unsafe extern "C" fn(
+    argc: std::os::raw::c_int,
+    argv: *const rb_sys::VALUE,
+    this: rb_sys::VALUE,
+) -> rb_sys::VALUE;
+
+/// `name` should be NUL-terminated
+#[allow(dead_code)]
+unsafe fn swig_ruby_define_method(
+    class: rb_sys::VALUE,
+    name: &str,
+    method: SwigRubyMethod,
+    private: bool,
+) {
+    let method = Some(std::mem::transmute::<
+        SwigRubyMethod,
+        unsafe extern "C" fn() -> rb_sys::VALUE,
+    >(method));
+    let name = name.as_ptr() as *const std::os::raw::c_char;
+    if private {
+        rb_sys::rb_define_private_method(class, name, method, -1);
+    } else {
+        rb_sys::rb_define_method(class, name, method, -1);
+    }
+}
+
+/// `name` should be NUL-terminated
+#[allow(dead_code)]
+unsafe fn swig_ruby_define_singleton_method(
+    class: rb_sys::VALUE,
+    name: &str,
+    method: SwigRubyMethod,
+    private: bool,
+) {
+    rb_sys::rb_define_singleton_method(
+        class,
+        name.as_ptr() as *const std::os::raw::c_char,
+        Some(std::mem::transmute::<
+            SwigRubyMethod,
+            unsafe extern "C" fn() -> rb_sys::VALUE,
+        >(method)),
+        -1,
+    );
+    if private {
+        // the same as `private_class_method :name`
+        let name = &name[..name.len() - 1];
+        let id = rb_sys::rb_intern2(
+            name.as_ptr() as *const std::os::raw::c_char,
+            name.len() as std::os::raw::c_long,
+        );
+        let args = [rb_sys::rb_id2sym(id)];
+        rb_sys::rb_funcallv(
+            class,
+            rb_sys::rb_intern2(
+                "private_class_method".as_ptr() as *const std::os::raw::c_char,
+                "private_class_method".len() as std::os::raw::c_long,
+            ),
+            1,
+            args.as_ptr(),
+        );
+    }
+}
+
+/// Define module of extension and its `Error` class, `name` should be NUL-terminated
+#[allow(dead_code)]
+unsafe fn swig_ruby_define_module(name: &str) -> rb_sys::VALUE {
+    let module = rb_sys::rb_define_module(name.as_ptr() as *const std::os::raw::c_char);
+    let error = rb_sys::rb_define_class_under(
+        module,
+        "Error\0".as_ptr() as *const std::os::raw::c_char,
+        rb_sys::rb_eStandardError,
+    );
+    SWIG_RUBY_ERROR_CLASS.store(error as usize, std::sync::atomic::Ordering::Relaxed);
+    module
+}
+
+#[allow(dead_code)]
+unsafe fn swig_ruby_is_nil(value: rb_sys::VALUE) -> bool {
+    value == SWIG_RUBY_QNIL
+}
+
+#[allow(dead_code)]
+unsafe fn swig_ruby_is_kind_of(value: rb_sys::VALUE, class: rb_sys::VALUE) -> bool {
+    rb_sys::rb_obj_is_kind_of(value, class) == SWIG_RUBY_QTRUE
+}
+
+/// Name of symbol, if `value` is symbol
+#[allow(dead_code)]
+unsafe fn swig_ruby_symbol_name(value: rb_sys::VALUE) -> Option<String> {
+    if !swig_ruby_is_kind_of(value, rb_sys::rb_cSymbol) {
+        return None;
+    }
+    let name = rb_sys::rb_id2name(rb_sys::rb_sym2id(value));
+    if name.is_null() {
+        return None;
+    }
+    Some(
+        std::ffi::CStr::from_ptr(name)
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+/// Conversion from Ruby value to Rust
+#[allow(dead_code)]
+trait SwigFromRuby: Sized {
+    unsafe fn swig_from_ruby(value: rb_sys::VALUE) -> SwigRubyResult<Self>;
+}
+
+/// Conversion from Rust to Ruby value
+#[allow(dead_code)]
+trait SwigIntoRuby {
+    unsafe fn swig_into_ruby(self) -> SwigRubyResult<rb_sys::VALUE>;
+}
+
+impl SwigIntoRuby for () {
+    unsafe fn swig_into_ruby(self) -> SwigRubyResult<rb_sys::VALUE> {
+        Ok(SWIG_RUBY_QNIL)
+    }
+}
+
+impl SwigFromRuby for bool {
+    unsafe fn swig_from_ruby(value: rb_sys::VALUE) -> SwigRubyResult<Self> {
+        if value == SWIG_RUBY_QTRUE {
+            Ok(true)
+        } else if value == SWIG_RUBY_QFALSE {
+            Ok(false)
+        } else {
+            Err(SwigRubyError::TypeError(
+                "true or false expected".to_string(),
+            ))
+        }
+    }
+}
+
+impl SwigIntoRuby for bool {
+    unsafe fn swig_into_ruby(self) -> SwigRubyResult<rb_sys::VALUE> {
+        Ok(if self {
+            SWIG_RUBY_QTRUE
+        } else {
+            SWIG_RUBY_QFALSE
+        })
+    }
+}
+
+// integers are converted via 64-bit integer and checked to be in range
+macro_rules! swig_ruby_integer_conversion {
+    ($($ty:ty),*) => {
+        $(
+            impl SwigFromRuby for $ty {
+                unsafe fn swig_from_ruby(value: rb_sys::VALUE) -> SwigRubyResult<Self> {
+                    if !swig_ruby_is_kind_of(value, rb_sys::rb_cInteger) {
+                        return Err(SwigRubyError::TypeError("Integer expected".to_string()));
+                    }
+                    let ret: i64 = swig_ruby_protect(|| rb_sys::rb_num2ll(value))?;
+                    <$ty as std::convert::TryFrom<i64>>::try_from(ret).map_err(|_| {
+                        SwigRubyError::ArgumentError(format!(
+                            "{} is out of range for {}",
+                            ret,
+                            stringify!($ty)
+                        ))
+                    })
+                }
+            }
+
+            impl SwigIntoRuby for $ty {
+                unsafe fn swig_into_ruby(self) -> SwigRubyResult<rb_sys::VALUE> {
+                    Ok(match <i64 as std::convert::TryFrom<$ty>>::try_from(self) {
+                        Ok(value) => rb_sys::rb_ll2inum(value),
+                        Err(_) => rb_sys::rb_ull2inum(self as u64),
+                    })
+                }
+            }
+        )*
+    };
+}
+
+swig_ruby_integer_conversion!(i8, i16, i32, i64, u8, u16, u32, u64, isize, usize);
+
+impl SwigFromRuby for f64 {
+    unsafe fn swig_from_ruby(value: rb_sys::VALUE) -> SwigRubyResult<Self> {
+        if !swig_ruby_is_kind_of(value, rb_sys::rb_cNumeric) {
+            return Err(SwigRubyError::TypeError("Numeric expected".to_string()));
+        }
+        swig_ruby_protect(|| rb_sys::rb_num2dbl(value))
+    }
+}
+
+impl SwigIntoRuby for f64 {
+    unsafe fn swig_into_ruby(self) -> SwigRubyResult<rb_sys::VALUE> {
+        Ok(rb_sys::rb_float_new(self))
+    }
+}
+
+impl SwigFromRuby for f32 {
+    unsafe fn swig_from_ruby(value: rb_sys::VALUE) -> SwigRubyResult<Self> {
+        f64::swig_from_ruby(value).map(|x| x as f32)
+    }
+}
+
+impl SwigIntoRuby for f32 {
+    unsafe fn swig_into_ruby(self) -> SwigRubyResult<rb_sys::VALUE> {
+        f64::from(self).swig_into_ruby()
+    }
+}
+
+impl SwigFromRuby for String {
+    unsafe fn swig_from_ruby(value: rb_sys::VALUE) -> SwigRubyResult<Self> {
+        if !swig_ruby_is_kind_of(value, rb_sys::rb_cString) {
+            return Err(SwigRubyError::TypeError("String expected".to_string()));
+        }
+        let bytes = std::slice::from_raw_parts(
+            rb_sys::RSTRING_PTR(value) as *const u8,
+            rb_sys::RSTRING_LEN(value) as usize,
+        );
+        String::from_utf8(bytes.to_vec())
+            .map_err(|err| SwigRubyError::ArgumentError(err.to_string()))
+    }
+}
+
+impl<'a> SwigIntoRuby for &'a str {
+    unsafe fn swig_into_ruby(self) -> SwigRubyResult<rb_sys::VALUE> {
+        Ok(rb_sys::rb_utf8_str_new(
+            self.as_ptr() as *const std::os::raw::c_char,
+            self.len() as std::os::raw::c_long,
+        ))
+    }
+}
+
+impl SwigIntoRuby for String {
+    unsafe fn swig_into_ruby(self) -> SwigRubyResult<rb_sys::VALUE> {
+        self.as_str().swig_into_ruby()
+    }
+}
+
+/// Convert elements of Ruby array with `f`
+#[allow(dead_code)]
+unsafe fn swig_ruby_array_elements<T, F>(array: rb_sys::VALUE, mut f: F) -> SwigRubyResult<Vec<T>>
+where
+    F: FnMut(rb_sys::VALUE) -> SwigRubyResult<T>,
+{
+    if !swig_ruby_is_kind_of(array, rb_sys::rb_cArray) {
+        return Err(SwigRubyError::TypeError("Array expected".to_string()));
+    }
+    let len = rb_sys::RARRAY_LEN(array);
+    let mut elements = Vec::with_capacity(len as usize);
+    for i in 0..len {
+        elements.push(f(rb_sys::rb_ary_entry(array, i))?);
+    }
+    Ok(elements)
+}
+
+/// Convert key-value pairs of Ruby hash with `f`,
+/// keys and values are referenced by hash, so GC does not collect them
+#[allow(dead_code)]
+unsafe fn swig_ruby_hash_pairs<K, V, F>(
+    hash: rb_sys::VALUE,
+    mut f: F,
+) -> SwigRubyResult<Vec<(K, V)>>
+where
+    F: FnMut(rb_sys::VALUE, rb_sys::VALUE) -> SwigRubyResult<(K, V)>,
+{
+    unsafe extern "C" fn collect(
+        key: rb_sys::VALUE,
+        value: rb_sys::VALUE,
+        data: rb_sys::VALUE,
+    ) -> std::os::raw::c_int {
+        let pairs = &mut *(data as *mut Vec<(rb_sys::VALUE, rb_sys::VALUE)>);
+        pairs.push((key, value));
+        SWIG_RUBY_ST_CONTINUE
+    }
+    if !swig_ruby_is_kind_of(hash, rb_sys::rb_cHash) {
+        return Err(SwigRubyError::TypeError("Hash expected".to_string()));
+    }
+    let mut values: Vec<(rb_sys::VALUE, rb_sys::VALUE)> = Vec::new();
+    rb_sys::rb_hash_foreach(
+        hash,
+        Some(collect),
+        &mut values as *mut Vec<(rb_sys::VALUE, rb_sys::VALUE)> as rb_sys::VALUE,
+    );
+    values
+        .into_iter()
+        .map(|(key, value)| f(key, value))
+        .collect()
+}
+
+/// Implemented by marker type of every generated Ruby class
+#[allow(dead_code)]
+trait SwigRubyClass: 'static {
+    /// Type of Rust object, that instance of Ruby class owns
+    type Storage: 'static;
+    const NAME: &'static str;
+    /// Ruby class, created during initialization of extension
+    fn class() -> rb_sys::VALUE;
+    fn data_type() -> *const rb_sys::rb_data_type_t;
+}
+
+/// Called by GC when Ruby object is collected
+#[allow(dead_code)]
+unsafe extern "C" fn swig_ruby_free<T>(data: *mut std::os::raw::c_void) {
+    if !data.is_null() {
+        drop(Box::from_raw(data as *mut T));
+    }
+}
+
+#[allow(dead_code)]
+unsafe extern "C" fn swig_ruby_size<T>(_data: *const std::os::raw::c_void) -> usize {
+    std::mem::size_of::<T>()
+}
+
+/// Define Ruby class, that wraps Rust object, `name` should be NUL-terminated.
+/// Returns class and description of typed data, it lives while program runs.
+#[allow(dead_code)]
+unsafe fn swig_ruby_define_class<C: SwigRubyClass>(
+    module: rb_sys::VALUE,
+    name: &'static str,
+) -> (rb_sys::VALUE, *mut rb_sys::rb_data_type_t) {
+    let class = rb_sys::rb_define_class_under(
+        module,
+        name.as_ptr() as *const std::os::raw::c_char,
+        rb_sys::rb_cObject,
+    );
+    // objects are created only by Rust code
+    rb_sys::rb_undef_alloc_func(class);
+    let mut data_type: rb_sys::rb_data_type_t = std::mem::zeroed();
+    data_type.wrap_struct_name = name.as_ptr() as *const std::os::raw::c_char;
+    data_type.function.dfree = Some(swig_ruby_free::<C::Storage>);
+    data_type.function.dsize = Some(swig_ruby_size::<C::Storage>);
+    data_type.flags = SWIG_RUBY_TYPED_FREE_IMMEDIATELY;
+    (class, Box::into_raw(Box::new(data_type)))
+}
+
+#[allow(dead_code)]
+unsafe fn swig_ruby_unwrap<'a, C: SwigRubyClass>(
+    obj: rb_sys::VALUE,
+) -> SwigRubyResult<&'a mut C::Storage> {
+    if rb_sys::rb_typeddata_is_kind_of(obj, C::data_type()) == 0 {
+        return Err(SwigRubyError::TypeError(format!("{} expected", C::NAME)));
+    }
+    let p = swig_ruby_protect(|| rb_sys::rb_check_typeddata(obj, C::data_type()))?;
+    Ok(&mut *(p as *mut C::Storage))
+}
+
+/// Rust object, that becomes new instance of Ruby class `C`
+#[allow(dead_code)]
+struct SwigRubyObject<C: SwigRubyClass>(C::Storage);
+
+impl<C: SwigRubyClass> SwigIntoRuby for SwigRubyObject<C> {
+    unsafe fn swig_into_ruby(self) -> SwigRubyResult<rb_sys::VALUE> {
+        let p = Box::into_raw(Box::new(self.0));
+        let ret = swig_ruby_protect(|| {
+            rb_sys::rb_data_typed_object_wrap(
+                C::class(),
+                p as *mut std::os::raw::c_void,
+                C::data_type(),
+            )
+        });
+        if ret.is_err() {
+            drop(Box::from_raw(p));
+        }
+        ret
+    }
+}
+
+/// Ruby object, that implements callback. It is registered in GC,
+/// so it is not collected while Rust side uses it.
+/// Ruby API can be used only from Ruby threads, so calls from other threads fail.
+#[allow(dead_code)]
+struct SwigRubyCallback {
+    object: Box<rb_sys::VALUE>,
+}
+
+unsafe impl Send for SwigRubyCallback {}
+unsafe impl Sync for SwigRubyCallback {}
+
+#[allow(dead_code)]
+impl SwigRubyCallback {
+    unsafe fn new(object: rb_sys::VALUE, name: &str) -> SwigRubyResult<SwigRubyCallback> {
+        if swig_ruby_is_nil(object) {
+            return Err(SwigRubyError::TypeError(format!("{} expected", name)));
+        }
+        let mut object = Box::new(object);
+        rb_sys::rb_gc_register_address(&mut *object);
+        Ok(SwigRubyCallback { object })
+    }
+
+    unsafe fn call_method(
+        &self,
+        name: &str,
+        args: &[rb_sys::VALUE],
+    ) -> SwigRubyResult<rb_sys::VALUE> {
+        if rb_sys::ruby_native_thread_p() == 0 {
+            return Err(SwigRubyError::Message(format!(
+                "Ruby callback {} called from not Ruby thread",
+                name
+            )));
+        }
+        let object = *self.object;
+        swig_ruby_protect(|| {
+            let method = rb_sys::rb_intern2(
+                name.as_ptr() as *const std::os::raw::c_char,
+                name.len() as std::os::raw::c_long,
+            );
+            rb_sys::rb_funcallv(
+                object,
+                method,
+                args.len() as std::os::raw::c_int,
+                args.as_ptr(),
+            )
+        })
+    }
+}
+
+impl Drop for SwigRubyCallback {
+    fn drop(&mut self) {
+        unsafe {
+            if rb_sys::ruby_native_thread_p() != 0 {
+                rb_sys::rb_gc_unregister_address(&mut *self.object);
+            } else {
+                // GC still references this address, so it should live forever
+                let object = std::mem::replace(&mut self.object, Box::new(SWIG_RUBY_QNIL));
+                std::mem::forget(object);
+            }
+        }
+    }
+}
+
+/// Ruby has no character type, so `char` is passed as string with one character
+#[allow(dead_code)]
+fn swig_ruby_char_from_string(s: String) -> SwigRubyResult<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Ok(ch),
+        _ => Err(SwigRubyError::ArgumentError(format!(
+            "string with one character expected, got {:?}",
+            s
+        ))),
+    }
+}
+
+foreign_typemap!(
+    ($p:r_type) char => String {
+        $out = $p.to_string();
+    };
+    ($p:f_type) => "String";
+    ($p:r_type) char <= String {
+        $out = swig_ruby_char_from_string($p)?;
+    };
+    ($p:f_type) <= "String";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Arc<Mutex<T>> => &Mutex<T> {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &Mutex<T> => MutexGuard<T> {
+        $out = $p.lock().unwrap();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> MutexGuard<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> MutexGuard<T> => &mut T {
+        $out = &mut $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Rc<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &Rc<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &RefCell<T> => Ref<T> {
+        $out = $p.borrow();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &RefCell<T> => RefMut<T> {
+        $out = $p.borrow_mut();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Ref<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> RefMut<T> => &mut T {
+        $out = &mut $p;
+    };
+);
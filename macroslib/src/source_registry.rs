@@ -27,8 +27,10 @@ impl SourceRegistry {
     }
 }
 
+/// Identifier of source code, that item was parsed from,
+/// used to report errors
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) struct SourceId(Option<usize>);
+pub struct SourceId(Option<usize>);
 
 impl SourceId {
     #[inline]
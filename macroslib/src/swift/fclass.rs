@@ -0,0 +1,267 @@
+use std::fmt::Write;
+
+use petgraph::Direction;
+use rustc_hash::FxHashSet;
+use smol_str::SmolStr;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    cpp::{c_func_name, find_suitable_foreign_types_for_methods, CppContext},
+    error::{DiagnosticError, Result},
+    namegen::new_unique_name,
+    swift::{
+        map_type::{map_type, SwiftConv},
+        swift_code::{self, apply_conv, swift_ident, swift_method_name},
+        SwiftContext,
+    },
+    types::{ForeignClassInfo, MethodAccess, MethodVariant},
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::swift) fn generate(
+    ctx: &mut SwiftContext,
+    cpp_ctx: &mut CppContext,
+    class: &ForeignClassInfo,
+) -> Result<()> {
+    let f_methods = find_suitable_foreign_types_for_methods(cpp_ctx, class)?;
+    let static_only = class
+        .methods
+        .iter()
+        .all(|x| x.variant == MethodVariant::StaticMethod);
+    let unit_ty: Type = parse_type! { () };
+
+    let mut methods_code = String::new();
+    for (method, f_method) in class.methods.iter().zip(f_methods.iter()) {
+        if method.is_dummy_constructor() {
+            continue;
+        }
+        let access = match method.access {
+            MethodAccess::Private => "private",
+            MethodAccess::Protected => "internal",
+            MethodAccess::Public => "public",
+        };
+        let skip_n = match method.variant {
+            MethodVariant::Method(_) => 1,
+            _ => 0,
+        };
+
+        let mut known_names: FxHashSet<SmolStr> =
+            method.arg_names_without_self().map(|x| x.into()).collect();
+        let mut args_with_types = Vec::with_capacity(f_method.input.len());
+        let mut conv_deps = String::new();
+        let mut scopes = Vec::new();
+        let mut c_args = Vec::with_capacity(f_method.input.len() + 1);
+        if let MethodVariant::Method(_) = method.variant {
+            c_args.push("cPtr".to_string());
+        }
+        for (arg, c_type) in method
+            .fn_decl
+            .inputs
+            .iter()
+            .skip(skip_n)
+            .zip(f_method.input.iter())
+        {
+            let named_arg = arg
+                .as_named_arg()
+                .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
+            let arg_ti = map_type(
+                ctx,
+                cpp_ctx,
+                c_type,
+                &named_arg.ty,
+                Direction::Incoming,
+                (class.src_id, named_arg.ty.span()),
+            )?;
+            let arg_name = swift_ident(&named_arg.name);
+            args_with_types.push(format!("{}: {}", arg_name, arg_ti.name));
+            match arg_ti.conv {
+                SwiftConv::Direct => c_args.push(arg_name.to_string()),
+                SwiftConv::Expr(ref expr) => c_args.push(apply_conv(expr, &arg_name)),
+                SwiftConv::Scoped {
+                    ref pre,
+                    ref open,
+                    ref expr,
+                } => {
+                    if !pre.is_empty() {
+                        writeln!(&mut conv_deps, "        {}", apply_conv(pre, &arg_name))
+                            .expect(WRITE_TO_MEM_FAILED_MSG);
+                    }
+                    scopes.push(apply_conv(open, &arg_name));
+                    c_args.push(apply_conv(expr, &arg_name));
+                }
+            }
+        }
+        let call = wrap_into_scopes(
+            &scopes,
+            &format!("{}({})", c_func_name(class, method), c_args.join(", ")),
+            "        ",
+        );
+        let doc_comments = swift_code::doc_comments_to_swift_comments(&method.doc_comments, "    ");
+        let args_with_types = args_with_types.join(", ");
+
+        if method.variant == MethodVariant::Constructor {
+            write!(
+                &mut methods_code,
+                r#"
+{doc_comments}    {access} convenience init({args_with_types}) {{
+{conv_deps}        let ptr = {call}
+        self.init(cPtr: ptr, owned: true)
+    }}
+"#,
+                doc_comments = doc_comments,
+                access = access,
+                args_with_types = args_with_types,
+                conv_deps = conv_deps,
+                call = call,
+            )
+            .expect(WRITE_TO_MEM_FAILED_MSG);
+            continue;
+        }
+
+        let ret_ty = match method.fn_decl.output {
+            syn::ReturnType::Default => &unit_ty,
+            syn::ReturnType::Type(_, ref ty) => &**ty,
+        };
+        let ret_ti = map_type(
+            ctx,
+            cpp_ctx,
+            &f_method.output,
+            ret_ty,
+            Direction::Outgoing,
+            (class.src_id, ret_ty.span()),
+        )?;
+        let (ret_decl, body) = if ret_ti.name == "Void" && !ret_ti.throws {
+            (String::new(), call)
+        } else {
+            let ret_name = new_unique_name(&known_names, "ret");
+            known_names.insert(ret_name.clone());
+            let ret_expr = ret_ti.conv_expr(&ret_name).ok_or_else(|| {
+                DiagnosticError::new(
+                    class.src_id,
+                    ret_ty.span(),
+                    "Swift: type can not be used as return type",
+                )
+            })?;
+            (
+                if ret_ti.name == "Void" {
+                    String::new()
+                } else {
+                    format!(" -> {}", ret_ti.name)
+                },
+                if ret_ti.name == "Void" {
+                    format!(
+                        "let {ret} = {call}\n        {ret_expr}",
+                        ret = ret_name,
+                        call = call,
+                        ret_expr = ret_expr
+                    )
+                } else if ret_expr == ret_name.as_str() {
+                    format!("return {}", call)
+                } else {
+                    format!(
+                        "let {ret} = {call}\n        return {ret_expr}",
+                        ret = ret_name,
+                        call = call,
+                        ret_expr = ret_expr
+                    )
+                },
+            )
+        };
+        write!(
+            &mut methods_code,
+            r#"
+{doc_comments}    {access} {static_}func {method_name}({args_with_types}){throws}{ret_decl} {{
+{conv_deps}        {body}
+    }}
+"#,
+            doc_comments = doc_comments,
+            access = access,
+            static_ = if method.variant == MethodVariant::StaticMethod {
+                "static "
+            } else {
+                ""
+            },
+            method_name = swift_ident(&swift_method_name(&method.short_name())),
+            args_with_types = args_with_types,
+            throws = if ret_ti.throws { " throws" } else { "" },
+            ret_decl = ret_decl,
+            conv_deps = conv_deps,
+            body = body,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+
+    let mut code = swift_code::file_header(ctx);
+    let doc_comments = swift_code::doc_comments_to_swift_comments(&class.doc_comments, "");
+    if static_only {
+        write!(
+            &mut code,
+            r#"
+{doc_comments}public enum {class_name} {{{methods_code}"#,
+            doc_comments = doc_comments,
+            class_name = class.name,
+            methods_code = methods_code,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    } else {
+        write!(
+            &mut code,
+            r#"
+{doc_comments}public final class {class_name} {{
+    internal var cPtr: OpaquePointer
+    internal var owned: Bool
+
+    internal init(cPtr: OpaquePointer, owned: Bool) {{
+        self.cPtr = cPtr
+        self.owned = owned
+    }}
+
+    deinit {{
+        if owned {{
+            {class_name}_delete(cPtr)
+        }}
+    }}
+
+    /// Pass ownership of Rust object to caller
+    internal func release() -> OpaquePointer {{
+        precondition(owned, "{class_name}: can not pass ownership of not owned object")
+        owned = false
+        return cPtr
+    }}
+{methods_code}"#,
+            doc_comments = doc_comments,
+            class_name = class.name,
+            methods_code = methods_code,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    code.push_str(&class.foreign_code);
+    code.push_str("}\n");
+    swift_code::write_swift_file(ctx, &swift_code::swift_file_name(&class.name), code)
+}
+
+/// Put `call` inside closures that keep Swift values alive,
+/// first line is not indented
+fn wrap_into_scopes(scopes: &[String], call: &str, indent: &str) -> String {
+    let mut code = String::new();
+    for (i, scope) in scopes.iter().enumerate() {
+        if i > 0 {
+            code.push_str(indent);
+            code.push_str(&"    ".repeat(i));
+        }
+        code.push_str(scope);
+        code.push('\n');
+    }
+    if !scopes.is_empty() {
+        code.push_str(indent);
+        code.push_str(&"    ".repeat(scopes.len()));
+    }
+    code.push_str(call);
+    for i in (0..scopes.len()).rev() {
+        code.push('\n');
+        code.push_str(indent);
+        code.push_str(&"    ".repeat(i));
+        code.push('}');
+    }
+    code
+}
@@ -0,0 +1,38 @@
+use std::fmt::Write;
+
+use crate::{
+    error::Result,
+    swift::{swift_code, SwiftContext},
+    types::ForeignEnumInfo,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::swift) fn generate_enum(
+    ctx: &mut SwiftContext,
+    fenum: &ForeignEnumInfo,
+) -> Result<()> {
+    let mut code = swift_code::file_header(ctx);
+    write!(
+        &mut code,
+        r#"
+{doc_comments}public enum {enum_name}: UInt32 {{
+"#,
+        doc_comments = swift_code::doc_comments_to_swift_comments(&fenum.doc_comments, ""),
+        enum_name = fenum.name,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    // C++ generator uses index of item as value
+    for (i, item) in fenum.items.iter().enumerate() {
+        writeln!(
+            &mut code,
+            "{doc_comments}    case {item_name} = {index}",
+            doc_comments = swift_code::doc_comments_to_swift_comments(&item.doc_comments, "    "),
+            item_name =
+                swift_code::swift_ident(&swift_code::swift_method_name(&item.name.to_string())),
+            index = i,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    code.push_str("}\n");
+    swift_code::write_swift_file(ctx, &swift_code::swift_file_name(&fenum.name), code)
+}
@@ -0,0 +1,144 @@
+use std::fmt::Write;
+
+use petgraph::Direction;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    cpp::{find_suitable_ftypes_for_interace_methods, CppContext},
+    error::{DiagnosticError, Result},
+    swift::{
+        map_type::map_type,
+        swift_code::{self, swift_ident},
+        SwiftContext,
+    },
+    types::ForeignInterface,
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+pub(in crate::swift) fn generate_interface(
+    ctx: &mut SwiftContext,
+    cpp_ctx: &mut CppContext,
+    interface: &ForeignInterface,
+) -> Result<()> {
+    let f_methods = find_suitable_ftypes_for_interace_methods(cpp_ctx, interface)?;
+    let unit_ty: Type = parse_type! { () };
+
+    let mut protocol_methods = String::new();
+    let mut c_fields = String::new();
+    for (method, f_method) in interface.items.iter().zip(f_methods.iter()) {
+        let method_name = swift_ident(&method.name.to_string()).into_owned();
+        let mut args_with_types = Vec::with_capacity(f_method.input.len());
+        let mut closure_args = Vec::with_capacity(f_method.input.len() + 1);
+        let mut call_args = Vec::with_capacity(f_method.input.len());
+        for (i, (arg, c_type)) in method
+            .fn_decl
+            .inputs
+            .iter()
+            .skip(1)
+            .zip(f_method.input.iter())
+            .enumerate()
+        {
+            let named_arg = arg
+                .as_named_arg()
+                .map_err(|err| DiagnosticError::from_syn_err(interface.src_id, err))?;
+            let arg_ti = map_type(
+                ctx,
+                cpp_ctx,
+                c_type,
+                &named_arg.ty,
+                Direction::Outgoing,
+                (interface.src_id, named_arg.ty.span()),
+            )?;
+            let arg_name = swift_ident(&named_arg.name);
+            let c_arg_name = format!("a{}", i);
+            let arg_expr = arg_ti.conv_expr(&c_arg_name).ok_or_else(|| {
+                DiagnosticError::new(
+                    interface.src_id,
+                    named_arg.ty.span(),
+                    "Swift: type can not be used as callback argument",
+                )
+            })?;
+            args_with_types.push(format!("{}: {}", arg_name, arg_ti.name));
+            call_args.push(format!("{}: {}", arg_name, arg_expr));
+            closure_args.push(c_arg_name);
+        }
+        closure_args.push("opaque".into());
+
+        let ret_ty = match method.fn_decl.output {
+            syn::ReturnType::Default => &unit_ty,
+            syn::ReturnType::Type(_, ref ty) => &**ty,
+        };
+        let ret_ti = map_type(
+            ctx,
+            cpp_ctx,
+            &f_method.output,
+            ret_ty,
+            Direction::Incoming,
+            (interface.src_id, ret_ty.span()),
+        )?;
+        let call = format!("obj.{}({})", method_name, call_args.join(", "));
+        let (ret_decl, body) = if ret_ti.name == "Void" {
+            (String::new(), call)
+        } else {
+            let ret_expr = ret_ti.conv_expr("ret").ok_or_else(|| {
+                DiagnosticError::new(
+                    interface.src_id,
+                    ret_ty.span(),
+                    "Swift: type can not be used as callback return type",
+                )
+            })?;
+            (
+                format!(" -> {}", ret_ti.name),
+                format!("let ret = {}\n            return {}", call, ret_expr),
+            )
+        };
+        writeln!(
+            &mut protocol_methods,
+            "{doc_comments}    func {method_name}({args_with_types}){ret_decl}",
+            doc_comments = swift_code::doc_comments_to_swift_comments(&method.doc_comments, "    "),
+            method_name = method_name,
+            args_with_types = args_with_types.join(", "),
+            ret_decl = ret_decl,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        write!(
+            &mut c_fields,
+            r#",
+        {field}: {{ {closure_args} in
+            let obj = Unmanaged<AnyObject>.fromOpaque(opaque!).takeUnretainedValue() as! {interface_name}
+            {body}
+        }}"#,
+            field = method.name,
+            closure_args = closure_args.join(", "),
+            interface_name = interface.name,
+            body = body,
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+
+    let mut code = swift_code::file_header(ctx);
+    write!(
+        &mut code,
+        r#"
+{doc_comments}public protocol {interface_name}: AnyObject {{
+{protocol_methods}}}
+
+/// Create C structure that holds strong reference to `obj`,
+/// reference released when Rust side drops callback
+internal func swig{interface_name}ToC(_ obj: {interface_name}) -> C_{interface_name} {{
+    return C_{interface_name}(
+        opaque: Unmanaged.passRetained(obj as AnyObject).toOpaque(),
+        C_{interface_name}_deref: {{ opaque in
+            Unmanaged<AnyObject>.fromOpaque(opaque!).release()
+        }}{c_fields}
+    )
+}}
+"#,
+        doc_comments = swift_code::doc_comments_to_swift_comments(&interface.doc_comments, ""),
+        interface_name = interface.name,
+        protocol_methods = protocol_methods,
+        c_fields = c_fields,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    swift_code::write_swift_file(ctx, &swift_code::swift_file_name(&interface.name), code)
+}
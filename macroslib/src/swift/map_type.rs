@@ -0,0 +1,364 @@
+use petgraph::Direction;
+use syn::Type;
+
+use crate::{
+    cpp::{c_class_type_name, map_type as map_c_type, CppContext, CppForeignTypeInfo},
+    error::{DiagnosticError, Result, SourceIdSpan},
+    swift::{swift_code::apply_conv, SwiftContext, SwiftHelper},
+    typemap::ast::DisplayToTokens,
+};
+
+/// How to convert value between Swift and C types
+#[derive(Debug)]
+pub(in crate::swift) enum SwiftConv {
+    /// Swift and C types are the same
+    Direct,
+    /// Expression, see `apply_conv` for template syntax
+    Expr(String),
+    /// Swift value should be alive during call, so conversation
+    /// happens inside closure: `pre` statements, `open` - closure start,
+    /// and `expr` is expression to use inside closure
+    Scoped {
+        pre: String,
+        open: String,
+        expr: String,
+    },
+}
+
+#[derive(Debug)]
+pub(in crate::swift) struct SwiftTypeInfo {
+    pub(in crate::swift) name: String,
+    pub(in crate::swift) conv: SwiftConv,
+    /// Conversation can throw Swift error, so function should be marked as `throws`
+    pub(in crate::swift) throws: bool,
+}
+
+impl SwiftTypeInfo {
+    fn direct(name: &str) -> Self {
+        SwiftTypeInfo {
+            name: name.into(),
+            conv: SwiftConv::Direct,
+            throws: false,
+        }
+    }
+    fn expr(name: String, expr: String) -> Self {
+        SwiftTypeInfo {
+            name,
+            conv: SwiftConv::Expr(expr),
+            throws: false,
+        }
+    }
+    /// Expression to convert `var_name`, only for not scoped conversations
+    pub(in crate::swift) fn conv_expr(&self, var_name: &str) -> Option<String> {
+        match self.conv {
+            SwiftConv::Direct => Some(var_name.into()),
+            SwiftConv::Expr(ref expr) => Some(apply_conv(expr, var_name)),
+            SwiftConv::Scoped { .. } => None,
+        }
+    }
+}
+
+fn primitive_swift_type(c_name: &str) -> Option<&'static str> {
+    let ty = match c_name {
+        "int8_t" => "Int8",
+        "uint8_t" => "UInt8",
+        "int16_t" => "Int16",
+        "uint16_t" => "UInt16",
+        "int32_t" => "Int32",
+        "uint32_t" => "UInt32",
+        "int64_t" => "Int64",
+        "uint64_t" => "UInt64",
+        "intptr_t" => "Int",
+        "uintptr_t" => "UInt",
+        "int" => "Int32",
+        "char" => "CChar",
+        "float" => "Float",
+        "double" => "Double",
+        "void" => "Void",
+        _ => return None,
+    };
+    Some(ty)
+}
+
+/// Generic argument of type like `Option<T>`, `Vec<T>` or `&[T]`
+fn inner_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Reference(ref r) => match *r.elem {
+            Type::Slice(ref s) => Some(&*s.elem),
+            _ => None,
+        },
+        Type::Path(ref p) => {
+            let last = p.path.segments.last()?;
+            match last.arguments {
+                syn::PathArguments::AngleBracketed(ref args) => {
+                    args.args.iter().find_map(|arg| match arg {
+                        syn::GenericArgument::Type(ref ty) => Some(ty),
+                        _ => None,
+                    })
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Error type of `Result<T, E>`
+fn result_err_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(ref p) => {
+            let last = p.path.segments.last()?;
+            match last.arguments {
+                syn::PathArguments::AngleBracketed(ref args) => args
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(ref ty) => Some(ty),
+                        _ => None,
+                    })
+                    .nth(1),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Map `rust_ty` (as it written in `foreign_class!`) that C++ generator
+/// mapped to C type `c_type` to Swift type
+pub(in crate::swift) fn map_type(
+    ctx: &mut SwiftContext,
+    cpp_ctx: &mut CppContext,
+    c_type: &CppForeignTypeInfo,
+    rust_ty: &Type,
+    direction: Direction,
+    span: SourceIdSpan,
+) -> Result<SwiftTypeInfo> {
+    let c_name = c_type.base.name.as_str();
+    let cpp_name = c_type.cpp_converter.as_ref().map(|x| x.typename.as_str());
+
+    if c_name == "char" && cpp_name == Some("bool") {
+        return Ok(SwiftTypeInfo::expr(
+            "Bool".into(),
+            match direction {
+                Direction::Outgoing => "($p != 0)".into(),
+                Direction::Incoming => "($p ? 1 : 0)".into(),
+            },
+        ));
+    }
+    if let Some(enum_name) = cpp_name.filter(|x| ctx.enums.contains(*x)) {
+        return Ok(SwiftTypeInfo::expr(
+            enum_name.into(),
+            match direction {
+                Direction::Outgoing => format!("{}(rawValue: $p)!", enum_name),
+                Direction::Incoming => "$p.rawValue".into(),
+            },
+        ));
+    }
+    if let Some(ty) = primitive_swift_type(c_name) {
+        return Ok(SwiftTypeInfo::direct(ty));
+    }
+
+    let opaque_name = c_name
+        .trim_start_matches("const ")
+        .trim_end_matches('*')
+        .trim();
+    if let Some(class_name) = ctx
+        .classes
+        .iter()
+        .find(|class_name| c_class_type_name(class_name) == opaque_name)
+    {
+        let is_ref = matches!(rust_ty, Type::Reference(_));
+        return Ok(SwiftTypeInfo::expr(
+            class_name.to_string(),
+            match direction {
+                Direction::Outgoing => format!(
+                    "{}(cPtr: $p, owned: {})",
+                    class_name,
+                    !is_ref && !c_name.starts_with("const ")
+                ),
+                Direction::Incoming if is_ref => "$p.cPtr".into(),
+                Direction::Incoming => "$p.release()".into(),
+            },
+        ));
+    }
+
+    if let Some(interface_name) = ctx
+        .interfaces
+        .iter()
+        .find(|name| c_name == format!("const struct C_{} * const", name))
+    {
+        if direction == Direction::Incoming {
+            return Ok(SwiftTypeInfo {
+                name: interface_name.to_string(),
+                conv: SwiftConv::Scoped {
+                    pre: format!("var $c = swig{}ToC($p)", interface_name),
+                    open: "withUnsafePointer(to: &$c) { $ptr in".into(),
+                    expr: "$ptr".into(),
+                },
+                throws: false,
+            });
+        }
+    }
+
+    let c_struct_name = c_name.trim_start_matches("struct ");
+    match (c_struct_name, direction) {
+        ("CRustStrView", Direction::Outgoing) => {
+            ctx.helpers.insert(SwiftHelper::StringFromView);
+            return Ok(SwiftTypeInfo::expr(
+                "String".into(),
+                "swigStringFromView($p)".into(),
+            ));
+        }
+        ("CRustStrView", Direction::Incoming) => {
+            return Ok(SwiftTypeInfo {
+                name: "String".into(),
+                conv: SwiftConv::Scoped {
+                    pre: String::new(),
+                    open: "$p.withCString { $ptr in".into(),
+                    expr: "CRustStrView(data: $ptr, len: UInt($p.utf8.count))".into(),
+                },
+                throws: false,
+            });
+        }
+        ("CRustString", Direction::Outgoing) => {
+            ctx.helpers.insert(SwiftHelper::StringFromView);
+            ctx.helpers.insert(SwiftHelper::TakeString);
+            return Ok(SwiftTypeInfo::expr(
+                "String".into(),
+                "swigTakeString($p)".into(),
+            ));
+        }
+        _ => {}
+    }
+
+    let not_supported = || {
+        DiagnosticError::new2(
+            span,
+            format!(
+                "Swift: type {} (C type {}) is not supported as {}",
+                DisplayToTokens(rust_ty),
+                c_name,
+                match direction {
+                    Direction::Outgoing => "output",
+                    Direction::Incoming => "input",
+                }
+            ),
+        )
+    };
+    let map_inner = |ctx: &mut SwiftContext, cpp_ctx: &mut CppContext| {
+        let inner_ty = inner_type(rust_ty).ok_or_else(not_supported)?;
+        let inner_rty = cpp_ctx.conv_map.find_or_alloc_rust_type(inner_ty, span.0);
+        let inner_c_type = map_c_type(cpp_ctx, &inner_rty, direction, span)?;
+        map_type(ctx, cpp_ctx, &inner_c_type, inner_ty, direction, span)
+    };
+
+    if c_struct_name.starts_with("CRustOption") {
+        let inner = map_inner(ctx, cpp_ctx)?;
+        let conv = match direction {
+            Direction::Outgoing => {
+                let inner_expr = inner.conv_expr("$p.val.data").ok_or_else(not_supported)?;
+                format!("($p.is_some != 0 ? {} : nil)", inner_expr)
+            }
+            Direction::Incoming => {
+                let inner_expr = inner.conv_expr("$0").ok_or_else(not_supported)?;
+                let union_name = c_struct_name.replacen("CRustOption", "CRustOptionUnion", 1);
+                format!(
+                    "($p.map {{ {opt}(val: {union}(data: {inner}), is_some: 1) }} ?? {opt}(val: {union}(uninit: 0), is_some: 0))",
+                    opt = c_struct_name,
+                    union = union_name,
+                    inner = inner_expr,
+                )
+            }
+        };
+        return Ok(SwiftTypeInfo::expr(format!("{}?", inner.name), conv));
+    }
+
+    if (c_struct_name.starts_with("CRustResult") || c_struct_name.starts_with("CRustVoidOkResult"))
+        && direction == Direction::Outgoing
+    {
+        let err_is_string = match result_err_type(rust_ty) {
+            Some(Type::Path(ref p)) => p.path.is_ident("String"),
+            _ => false,
+        };
+        if !err_is_string {
+            return Err(not_supported());
+        }
+        let inner = map_inner(ctx, cpp_ctx)?;
+        let ok_expr = if inner.name == "Void" {
+            "()".into()
+        } else {
+            inner.conv_expr("$p.data.ok").ok_or_else(not_supported)?
+        };
+        ctx.helpers.insert(SwiftHelper::StringFromView);
+        ctx.helpers.insert(SwiftHelper::TakeString);
+        ctx.helpers.insert(SwiftHelper::UnwrapResult);
+        return Ok(SwiftTypeInfo {
+            name: inner.name,
+            conv: SwiftConv::Expr(format!(
+                "try swigUnwrapResult($p.is_ok, {{ {} }}, {{ $p.data.err }})",
+                ok_expr
+            )),
+            throws: true,
+        });
+    }
+
+    if c_struct_name.starts_with("CRustVec") && direction == Direction::Outgoing {
+        let inner = map_inner(ctx, cpp_ctx)?;
+        if let SwiftConv::Direct = inner.conv {
+            ctx.helpers.insert(SwiftHelper::TakeArray);
+            return Ok(SwiftTypeInfo::expr(
+                format!("[{}]", inner.name),
+                format!(
+                    "swigTakeArray($p.data, $p.len) {{ {}_free($p) }}",
+                    c_struct_name
+                ),
+            ));
+        }
+        return Err(not_supported());
+    }
+
+    if c_struct_name == "CRustForeignVec" && direction == Direction::Outgoing {
+        let vec_name = cpp_name.ok_or_else(not_supported)?;
+        let inner = map_inner(ctx, cpp_ctx)?;
+        if !ctx.classes.contains(inner.name.as_str()) {
+            return Err(not_supported());
+        }
+        ctx.helpers.insert(SwiftHelper::TakeObjects);
+        return Ok(SwiftTypeInfo::expr(
+            format!("[{}]", inner.name),
+            format!(
+                "swigTakeObjects($p, remove: {vec}_remove, free: {vec}_free) {{ {class}(cPtr: $0, owned: true) }}",
+                vec = vec_name,
+                class = inner.name
+            ),
+        ));
+    }
+
+    if c_struct_name.starts_with("CRustSlice") {
+        let inner = map_inner(ctx, cpp_ctx)?;
+        if let SwiftConv::Direct = inner.conv {
+            let name = format!("[{}]", inner.name);
+            return Ok(match direction {
+                Direction::Outgoing => SwiftTypeInfo::expr(
+                    name,
+                    "Array(UnsafeBufferPointer(start: $p.data, count: Int($p.len)))".into(),
+                ),
+                Direction::Incoming => SwiftTypeInfo {
+                    name,
+                    conv: SwiftConv::Scoped {
+                        pre: String::new(),
+                        open: "$p.withUnsafeBufferPointer { $ptr in".into(),
+                        expr: format!(
+                            "{}(data: $ptr.baseAddress, len: UInt($ptr.count))",
+                            c_struct_name
+                        ),
+                    },
+                    throws: false,
+                },
+            });
+        }
+    }
+
+    Err(not_supported())
+}
@@ -0,0 +1,166 @@
+mod fclass;
+mod fenum;
+mod finterface;
+mod map_type;
+mod swift_code;
+
+use std::{fs, path::PathBuf};
+
+use proc_macro2::TokenStream;
+use rustc_hash::FxHashSet;
+use smol_str::SmolStr;
+
+use crate::{
+    cpp::{write_c_umbrella_header, CppContext},
+    error::{DiagnosticError, Result},
+    extension::ExtHandlers,
+    file_cache::FileWriteCache,
+    typemap::utils::remove_files_if,
+    types::ItemToExpand,
+    CppConfig, LanguageGenerator, SourceCode, SwiftConfig, TypeMap,
+};
+
+/// Helper functions that generated code may require,
+/// written into common file only if used
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+enum SwiftHelper {
+    StringFromView,
+    TakeString,
+    TakeArray,
+    TakeObjects,
+    UnwrapResult,
+}
+
+struct SwiftContext<'a> {
+    cfg: &'a SwiftConfig,
+    generated_foreign_files: FxHashSet<PathBuf>,
+    classes: FxHashSet<SmolStr>,
+    enums: FxHashSet<SmolStr>,
+    interfaces: FxHashSet<SmolStr>,
+    helpers: FxHashSet<SwiftHelper>,
+}
+
+impl SwiftConfig {
+    fn c_output_dir(&self) -> PathBuf {
+        self.output_dir.join(&self.c_module_name)
+    }
+}
+
+impl LanguageGenerator for SwiftConfig {
+    fn expand_items(
+        &self,
+        conv_map: &mut TypeMap,
+        target_pointer_width: usize,
+        code: &[SourceCode],
+        items: Vec<ItemToExpand>,
+        remove_not_generated_files: bool,
+        ext_handlers: ExtHandlers,
+    ) -> Result<Vec<TokenStream>> {
+        if !(self.output_dir.exists() && self.output_dir.is_dir()) {
+            return Err(DiagnosticError::map_any_err_to_our_err(format!(
+                "Path {} not exists or not directory",
+                self.output_dir.display()
+            )));
+        }
+        let c_output_dir = self.c_output_dir();
+        fs::create_dir_all(&c_output_dir).map_err(|err| {
+            DiagnosticError::map_any_err_to_our_err(format!(
+                "Can not create {}: {}",
+                c_output_dir.display(),
+                err
+            ))
+        })?;
+
+        let mut ctx = SwiftContext {
+            cfg: self,
+            generated_foreign_files: FxHashSet::default(),
+            classes: FxHashSet::default(),
+            enums: FxHashSet::default(),
+            interfaces: FxHashSet::default(),
+            helpers: FxHashSet::default(),
+        };
+        for item in &items {
+            match item {
+                ItemToExpand::Class(ref fclass) => {
+                    ctx.classes.insert(fclass.name.to_string().into());
+                }
+                ItemToExpand::Enum(ref fenum) => {
+                    ctx.enums.insert(fenum.name.to_string().into());
+                }
+                ItemToExpand::Interface(ref finterface) => {
+                    ctx.interfaces.insert(finterface.name.to_string().into());
+                }
+            }
+        }
+        let mut class_names = Vec::new();
+        let mut interface_names = Vec::new();
+        let umbrella_header_path = c_output_dir.join(format!("{}.h", self.c_module_name));
+        let mut generated_c_files = FxHashSet::default();
+        generated_c_files.insert(umbrella_header_path.clone());
+
+        let cpp_cfg = CppConfig::new(c_output_dir.clone(), self.c_module_name.clone());
+        let ret = cpp_cfg.expand_items_with_hook(
+            conv_map,
+            target_pointer_width,
+            code,
+            items,
+            remove_not_generated_files,
+            ext_handlers,
+            &mut generated_c_files,
+            |cpp_ctx: &mut CppContext, item: &ItemToExpand| -> Result<()> {
+                match item {
+                    ItemToExpand::Class(ref fclass) => {
+                        class_names.push(fclass.name.to_string());
+                        fclass::generate(&mut ctx, cpp_ctx, fclass)
+                    }
+                    ItemToExpand::Enum(ref fenum) => fenum::generate_enum(&mut ctx, fenum),
+                    ItemToExpand::Interface(ref finterface) => {
+                        interface_names.push(finterface.name.to_string());
+                        finterface::generate_interface(&mut ctx, cpp_ctx, finterface)
+                    }
+                }
+            },
+        )?;
+
+        write_c_umbrella_header(
+            &umbrella_header_path,
+            &mut generated_c_files,
+            &class_names,
+            &interface_names,
+        )?;
+
+        let module_map_path = c_output_dir.join("module.modulemap");
+        let mut module_map_f = FileWriteCache::new(&module_map_path, &mut generated_c_files);
+        module_map_f.replace_content(
+            format!(
+                r#"module {name} {{
+    header "{name}.h"
+    export *
+}}
+"#,
+                name = self.c_module_name
+            )
+            .into_bytes(),
+        );
+        module_map_f
+            .update_file_if_necessary()
+            .map_err(DiagnosticError::map_any_err_to_our_err)?;
+
+        swift_code::generate_helpers(&mut ctx)?;
+
+        if remove_not_generated_files {
+            let generated_foreign_files = &ctx.generated_foreign_files;
+            remove_files_if(&self.output_dir, |path| {
+                if let Some(ext) = path.extension() {
+                    if ext == "swift" && !generated_foreign_files.contains(path) {
+                        return true;
+                    }
+                }
+                false
+            })
+            .map_err(DiagnosticError::map_any_err_to_our_err)?;
+        }
+
+        Ok(ret)
+    }
+}
@@ -0,0 +1,206 @@
+use std::{borrow::Cow, fmt::Write};
+
+use heck::MixedCase;
+
+use crate::{
+    error::{DiagnosticError, Result},
+    file_cache::FileWriteCache,
+    swift::{SwiftContext, SwiftHelper},
+    WRITE_TO_MEM_FAILED_MSG,
+};
+
+/// Words that can not be used as identifiers in Swift without backticks
+static SWIFT_KEYWORDS: [&str; 49] = [
+    "associatedtype",
+    "class",
+    "deinit",
+    "enum",
+    "extension",
+    "fileprivate",
+    "func",
+    "import",
+    "init",
+    "inout",
+    "internal",
+    "let",
+    "open",
+    "operator",
+    "private",
+    "protocol",
+    "public",
+    "rethrows",
+    "static",
+    "struct",
+    "subscript",
+    "typealias",
+    "var",
+    "break",
+    "case",
+    "continue",
+    "default",
+    "defer",
+    "do",
+    "else",
+    "fallthrough",
+    "for",
+    "guard",
+    "if",
+    "in",
+    "repeat",
+    "return",
+    "switch",
+    "where",
+    "while",
+    "as",
+    "catch",
+    "false",
+    "is",
+    "nil",
+    "self",
+    "super",
+    "throw",
+    "true",
+];
+
+pub(in crate::swift) fn swift_ident(name: &str) -> Cow<'_, str> {
+    if SWIFT_KEYWORDS.contains(&name) {
+        format!("`{}`", name).into()
+    } else {
+        name.into()
+    }
+}
+
+pub(in crate::swift) fn swift_method_name(rust_name: &str) -> String {
+    rust_name.to_mixed_case()
+}
+
+pub(in crate::swift) fn swift_file_name(type_name: &dyn std::fmt::Display) -> String {
+    format!("{}.swift", type_name)
+}
+
+pub(in crate::swift) fn file_header(ctx: &SwiftContext) -> String {
+    format!(
+        "// Automatically generated by flapigen\nimport {}\n",
+        ctx.cfg.c_module_name
+    )
+}
+
+pub(in crate::swift) fn doc_comments_to_swift_comments(
+    doc_comments: &[String],
+    indent: &str,
+) -> String {
+    let mut comments = String::new();
+    for comment in doc_comments {
+        writeln!(&mut comments, "{}/// {}", indent, comment.trim()).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    comments
+}
+
+/// Substitute variable into conversation template:
+/// `$p` - variable itself, `$c` and `$ptr` - names for temporary values
+pub(in crate::swift) fn apply_conv(template: &str, var_name: &str) -> String {
+    let base_name = var_name.trim_matches('`');
+    template
+        .replace("$c", &format!("{}_c", base_name))
+        .replace("$ptr", &format!("{}_ptr", base_name))
+        .replace("$p", var_name)
+}
+
+pub(in crate::swift) fn write_swift_file(
+    ctx: &mut SwiftContext,
+    file_name: &str,
+    code: String,
+) -> Result<()> {
+    let path = ctx.cfg.output_dir.join(file_name);
+    let mut file = FileWriteCache::new(&path, &mut ctx.generated_foreign_files);
+    file.replace_content(code.into_bytes());
+    file.update_file_if_necessary().map_err(|err| {
+        DiagnosticError::map_any_err_to_our_err(format!(
+            "write to {} failed: {}",
+            path.display(),
+            err
+        ))
+    })
+}
+
+pub(in crate::swift) fn generate_helpers(ctx: &mut SwiftContext) -> Result<()> {
+    if ctx.helpers.is_empty() {
+        return Ok(());
+    }
+    let mut helpers: Vec<SwiftHelper> = ctx.helpers.iter().cloned().collect();
+    helpers.sort();
+    let mut code = file_header(ctx);
+    for helper in helpers {
+        code.push_str(match helper {
+            SwiftHelper::StringFromView => {
+                r#"
+internal func swigStringFromView(_ view: CRustStrView) -> String {
+    guard let data = view.data, view.len > 0 else {
+        return ""
+    }
+    let bytes = UnsafeRawPointer(data).assumingMemoryBound(to: UInt8.self)
+    return String(decoding: UnsafeBufferPointer(start: bytes, count: Int(view.len)), as: UTF8.self)
+}
+"#
+            }
+            SwiftHelper::TakeString => {
+                r#"
+internal func swigTakeString(_ s: CRustString) -> String {
+    defer {
+        crust_string_free(s)
+    }
+    return swigStringFromView(CRustStrView(data: s.data, len: s.len))
+}
+"#
+            }
+            SwiftHelper::TakeArray => {
+                r#"
+internal func swigTakeArray<T>(_ data: UnsafePointer<T>?, _ len: UInt, _ free: () -> Void) -> [T] {
+    defer {
+        free()
+    }
+    guard let data = data else {
+        return []
+    }
+    return Array(UnsafeBufferPointer(start: data, count: Int(len)))
+}
+"#
+            }
+            SwiftHelper::TakeObjects => {
+                r#"
+internal func swigTakeObjects<T>(
+    _ vec: CRustForeignVec,
+    remove: (UnsafeMutablePointer<CRustForeignVec>?, UInt) -> UnsafeMutableRawPointer?,
+    free: (CRustForeignVec) -> Void,
+    wrap: (OpaquePointer) -> T
+) -> [T] {
+    var vec = vec
+    var ret = [T]()
+    ret.reserveCapacity(Int(vec.len))
+    while vec.len > 0 {
+        ret.append(wrap(OpaquePointer(remove(&vec, vec.len - 1)!)))
+    }
+    free(vec)
+    return ret.reversed()
+}
+"#
+            }
+            SwiftHelper::UnwrapResult => {
+                r#"
+/// Error returned by Rust code as `Result::Err`
+public struct RustError: Error {
+    public let message: String
+}
+
+internal func swigUnwrapResult<T>(_ isOk: UInt8, _ ok: () -> T, _ err: () -> CRustString) throws -> T {
+    guard isOk != 0 else {
+        throw RustError(message: swigTakeString(err()))
+    }
+    return ok()
+}
+"#
+            }
+        });
+    }
+    write_swift_file(ctx, "SwigSupport.swift", code)
+}
@@ -33,7 +33,8 @@ use crate::{
 use ast::ConversationResult;
 
 pub(crate) use typemap_macro::{
-    CItem, CItems, ExpandedFType, TypeMapConvRuleInfo, TypeMapConvRuleInfoExpanderHelper,
+    CItem, CItems, ExpandedFType, FTypeLeftRightPair, RTypeConvRule, TypeMapConvRuleInfo,
+    TypeMapConvRuleInfoExpanderHelper,
 };
 pub(crate) static TO_VAR_TEMPLATE: &str = "{to_var}";
 pub(crate) static FROM_VAR_TEMPLATE: &str = "{from_var}";
@@ -1030,6 +1031,44 @@ impl TypeMap {
         &self.generic_rules
     }
 
+    /// There is rule from `foreign_typemap!` or foreign type,
+    /// that provides such Rust type from foreign language
+    pub(crate) fn has_rule_to_get_from_foreign(&self, ty: &Type) -> bool {
+        let name = normalize_type(ty);
+        let same_rtype = |rule: &TypeMapConvRuleInfo| {
+            rule.if_simple_rtype_ftype_map()
+                .map(|x| x.0)
+                .or_else(|| rule.rtype_right_to_left.as_ref().map(|x| &x.left_ty))
+                .map(|rtype| normalize_type(rtype) == name)
+                .unwrap_or(false)
+        };
+        if self.generic_rules.iter().any(|rule| {
+            rule.is_ty_subst_of_my_generic_rtype(ty, petgraph::Direction::Incoming, |ty, traits| {
+                self.ty_to_rust_type_checked(ty)
+                    .map(|rty| {
+                        traits
+                            .iter()
+                            .all(|tname| rty.implements.contains_path(tname))
+                    })
+                    .unwrap_or(false)
+            })
+            .is_some()
+        }) || self.not_merged_data.iter().any(same_rtype)
+        {
+            return true;
+        }
+        match self.rust_names_map.get(name) {
+            Some(rust_ty) => self.ftypes_storage.iter_enumerate().any(|(_, ftype)| {
+                ftype
+                    .from_into_rust
+                    .as_ref()
+                    .map(|rule| rule.rust_ty == *rust_ty)
+                    .unwrap_or(false)
+            }),
+            None => false,
+        }
+    }
+
     pub(crate) fn parse_foreign_typemap_macro(
         &mut self,
         src_id: SourceId,
@@ -1280,6 +1319,7 @@ mod tests {
             foreign_code: String::new(),
             doc_comments: vec![],
             derive_list: vec![],
+            iterator_type: None,
         });
 
         let rc_refcell_foo_ty = types_map
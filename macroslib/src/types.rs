@@ -11,15 +11,18 @@ use crate::{
     SMART_PTR_COPY_TRAIT,
 };
 
+/// Class described by `foreign_class!`
 #[derive(Debug, Clone)]
-pub(crate) struct ForeignClassInfo {
-    pub src_id: SourceId,
-    pub name: Ident,
-    pub methods: Vec<ForeignMethod>,
-    pub self_desc: Option<SelfTypeDesc>,
-    pub foreign_code: String,
-    pub doc_comments: Vec<String>,
-    pub derive_list: Vec<String>,
+pub struct ForeignClassInfo {
+    pub(crate) src_id: SourceId,
+    pub(crate) name: Ident,
+    pub(crate) methods: Vec<ForeignMethod>,
+    pub(crate) self_desc: Option<SelfTypeDesc>,
+    pub(crate) foreign_code: String,
+    pub(crate) doc_comments: Vec<String>,
+    pub(crate) derive_list: Vec<String>,
+    /// Boxed Rust iterator, that class generated for it wraps
+    pub(crate) iterator_type: Option<Type>,
 }
 
 /// Two types instead of one, to simplify live to developer
@@ -28,14 +31,46 @@ pub(crate) struct ForeignClassInfo {
 /// back and forth pointer to `RefCell<T>>` and `T`
 #[derive(Debug, Clone)]
 pub(crate) struct SelfTypeDesc {
-    pub self_type: Type,
-    pub constructor_ret_type: Type,
+    pub(crate) self_type: Type,
+    pub(crate) constructor_ret_type: Type,
 }
 
 impl ForeignClassInfo {
-    pub(crate) fn span(&self) -> Span {
+    pub fn src_id(&self) -> SourceId {
+        self.src_id
+    }
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+    pub fn span(&self) -> Span {
         self.name.span()
     }
+    /// `self_type` of class, `None` for class with only static methods
+    pub fn self_type(&self) -> Option<&Type> {
+        self.self_desc.as_ref().map(|x| &x.self_type)
+    }
+    /// Type returned by constructors
+    pub fn constructor_ret_type(&self) -> Option<&Type> {
+        self.self_desc.as_ref().map(|x| &x.constructor_ret_type)
+    }
+    pub fn methods(&self) -> &[ForeignMethod] {
+        &self.methods
+    }
+    /// Code from `foreign_code` of class
+    pub fn foreign_code(&self) -> &str {
+        &self.foreign_code
+    }
+    pub fn doc_comments(&self) -> &[String] {
+        &self.doc_comments
+    }
+    pub fn derive_list(&self) -> &[String] {
+        &self.derive_list
+    }
+    /// Generated for Rust iterator returned by method of `foreign_class!`,
+    /// so foreign class should implement iterator protocol of language
+    pub fn is_iterator(&self) -> bool {
+        self.iterator_type.is_some()
+    }
     pub(crate) fn self_type_as_ty(&self) -> Type {
         self.self_desc
             .as_ref()
@@ -87,7 +122,7 @@ impl ForeignClassInfo {
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct ForeignMethod {
+pub struct ForeignMethod {
     pub(crate) variant: MethodVariant,
     pub(crate) rust_id: syn::Path,
     pub(crate) fn_decl: FnDecl,
@@ -96,6 +131,32 @@ pub(crate) struct ForeignMethod {
     pub(crate) doc_comments: Vec<String>,
     pub(crate) inline_block: Option<syn::Block>,
     pub(crate) unknown_attrs: Vec<String>,
+    pub(crate) field_accessor: Option<FieldAccessor>,
+    pub(crate) is_async: bool,
+}
+
+/// Method is accessor of property, generated for `field` item
+/// or marked with `#[getter]`/`#[setter]`
+#[derive(Debug, Clone)]
+pub struct FieldAccessor {
+    pub(crate) kind: FieldAccessorKind,
+    pub(crate) name: Ident,
+}
+
+impl FieldAccessor {
+    pub fn kind(&self) -> FieldAccessorKind {
+        self.kind
+    }
+    /// Name of property
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum FieldAccessorKind {
+    Getter,
+    Setter,
 }
 
 #[derive(Debug, Clone)]
@@ -124,10 +185,22 @@ impl FnArg {
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct NamedArg {
-    pub name: SmolStr,
-    pub span: Span,
-    pub ty: syn::Type,
+pub struct NamedArg {
+    pub(crate) name: SmolStr,
+    pub(crate) span: Span,
+    pub(crate) ty: syn::Type,
+}
+
+impl NamedArg {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+    pub fn ty(&self) -> &syn::Type {
+        &self.ty
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -138,7 +211,48 @@ pub(crate) struct FnDecl {
 }
 
 impl ForeignMethod {
-    pub(crate) fn short_name(&self) -> String {
+    pub fn variant(&self) -> MethodVariant {
+        self.variant
+    }
+    /// Path of Rust function, empty for constructor without Rust function
+    pub fn rust_path(&self) -> &syn::Path {
+        &self.rust_id
+    }
+    pub fn access(&self) -> MethodAccess {
+        self.access
+    }
+    pub fn doc_comments(&self) -> &[String] {
+        &self.doc_comments
+    }
+    /// Attributes of method, that `flapigen` itself doesn't know
+    pub fn unknown_attrs(&self) -> &[String] {
+        &self.unknown_attrs
+    }
+    pub fn field_accessor(&self) -> Option<&FieldAccessor> {
+        self.field_accessor.as_ref()
+    }
+    /// Method declared as `async fn`, so call of Rust function returns future,
+    /// and `output` is type of its result
+    pub fn is_async(&self) -> bool {
+        self.is_async
+    }
+    /// Arguments of method without `self`
+    pub fn args(&self) -> impl Iterator<Item = &NamedArg> {
+        let skip = match self.variant {
+            MethodVariant::Method(_) => 1,
+            _ => 0,
+        };
+        self.fn_decl
+            .inputs
+            .iter()
+            .skip(skip)
+            .map(|x| x.as_named_arg().unwrap())
+    }
+    pub fn output(&self) -> &syn::ReturnType {
+        &self.fn_decl.output
+    }
+    /// Name of method in foreign language: alias or name of Rust function
+    pub fn short_name(&self) -> String {
         if let Some(ref name) = self.name_alias {
             name.to_string()
         } else {
@@ -149,29 +263,27 @@ impl ForeignMethod {
         }
     }
 
-    pub(crate) fn span(&self) -> Span {
+    pub fn span(&self) -> Span {
         self.rust_id.span()
     }
 
-    pub(crate) fn is_dummy_constructor(&self) -> bool {
+    pub fn is_dummy_constructor(&self) -> bool {
         self.rust_id.segments.is_empty()
     }
 
     pub(crate) fn arg_names_without_self(&self) -> impl Iterator<Item = &str> {
-        let skip = match self.variant {
-            MethodVariant::Method(_) => 1,
-            _ => 0,
-        };
-        self.fn_decl
-            .inputs
-            .iter()
-            .skip(skip)
-            .map(|x| x.as_named_arg().unwrap().name.as_str())
+        self.args().map(|x| x.name.as_str())
     }
 
-    pub(crate) fn generate_code_to_call_rust_func(&self) -> String {
+    /// Code to call Rust function with arguments in variables with
+    /// the same names as arguments, and `self` in `this` variable
+    pub fn generate_code_to_call_rust_func(&self) -> String {
         if let Some(ref code_block) = self.inline_block {
-            format!("{}", DisplayToTokens(code_block))
+            if self.is_async {
+                format!("async move {}", DisplayToTokens(code_block))
+            } else {
+                format!("{}", DisplayToTokens(code_block))
+            }
         } else {
             let args_names = self
                 .arg_names_without_self()
@@ -192,7 +304,7 @@ impl ForeignMethod {
 }
 
 #[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) enum MethodAccess {
+pub enum MethodAccess {
     Private,
     Public,
     Protected,
@@ -245,8 +357,9 @@ impl SelfTypeVariant {
     }
 }
 
+/// Enum described by `foreign_enum!`
 #[derive(Debug, Clone)]
-pub(crate) struct ForeignEnumInfo {
+pub struct ForeignEnumInfo {
     pub(crate) src_id: SourceId,
     pub(crate) name: Ident,
     pub(crate) items: Vec<ForeignEnumItem>,
@@ -255,36 +368,146 @@ pub(crate) struct ForeignEnumInfo {
 }
 
 impl ForeignEnumInfo {
-    pub(crate) fn span(&self) -> Span {
+    pub fn src_id(&self) -> SourceId {
+        self.src_id
+    }
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+    pub fn span(&self) -> Span {
         self.name.span()
     }
+    pub fn items(&self) -> &[ForeignEnumItem] {
+        &self.items
+    }
+    pub fn doc_comments(&self) -> &[String] {
+        &self.doc_comments
+    }
+    pub fn derive_list(&self) -> &[String] {
+        &self.derive_list
+    }
+    /// `true` if no item carries data, so enum can be passed as integer
+    pub fn is_c_like(&self) -> bool {
+        self.items
+            .iter()
+            .all(|x| matches!(x.fields, ForeignEnumItemFields::Unit))
+    }
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct ForeignEnumItem {
+pub struct ForeignEnumItem {
     pub(crate) name: Ident,
     pub(crate) rust_name: syn::Path,
+    pub(crate) fields: ForeignEnumItemFields,
     pub(crate) doc_comments: Vec<String>,
 }
 
-pub(crate) struct ForeignInterface {
+impl ForeignEnumItem {
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+    /// Path of Rust enum variant
+    pub fn rust_path(&self) -> &syn::Path {
+        &self.rust_name
+    }
+    pub fn doc_comments(&self) -> &[String] {
+        &self.doc_comments
+    }
+    /// Data carried by Rust enum variant
+    pub fn fields(&self) -> &ForeignEnumItemFields {
+        &self.fields
+    }
+}
+
+/// Data of enum variant, `Item = Enum::Item { a: i32 }` or `Item = Enum::Item(i32)`
+#[derive(Debug, Clone)]
+pub enum ForeignEnumItemFields {
+    Unit,
+    /// Tuple variant, fields are named `field0`, `field1` and so on
+    Unnamed(Vec<ForeignEnumItemField>),
+    Named(Vec<ForeignEnumItemField>),
+}
+
+impl ForeignEnumItemFields {
+    pub fn as_slice(&self) -> &[ForeignEnumItemField] {
+        match self {
+            ForeignEnumItemFields::Unit => &[],
+            ForeignEnumItemFields::Unnamed(fields) | ForeignEnumItemFields::Named(fields) => fields,
+        }
+    }
+    /// Pattern to match variant `rust_name` and bind fields to variables
+    /// with the same names as fields
+    pub(crate) fn rust_pattern(&self, rust_name: &syn::Path) -> TokenStream {
+        match self {
+            ForeignEnumItemFields::Unit => quote!(#rust_name),
+            ForeignEnumItemFields::Unnamed(fields) => {
+                let names = fields.iter().map(|x| &x.name);
+                quote!(#rust_name(#(#names),*))
+            }
+            ForeignEnumItemFields::Named(fields) => {
+                let names = fields.iter().map(|x| &x.name);
+                quote!(#rust_name { #(#names),* })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ForeignEnumItemField {
+    pub(crate) name: Ident,
+    pub(crate) ty: syn::Type,
+}
+
+impl ForeignEnumItemField {
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+    pub fn ty(&self) -> &syn::Type {
+        &self.ty
+    }
+}
+
+/// Callback described by `foreign_callback!`
+pub struct ForeignInterface {
     pub(crate) src_id: SourceId,
     pub(crate) name: Ident,
     pub(crate) self_type: syn::TypeTraitObject,
     pub(crate) doc_comments: Vec<String>,
     pub(crate) items: Vec<ForeignInterfaceMethod>,
+    pub(crate) is_closure: bool,
 }
 
 impl ForeignInterface {
-    pub(crate) fn span(&self) -> Span {
+    pub fn src_id(&self) -> SourceId {
+        self.src_id
+    }
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+    pub fn span(&self) -> Span {
         self.name.span()
     }
+    /// Trait object type, that callback implements
+    pub fn self_type(&self) -> &syn::TypeTraitObject {
+        &self.self_type
+    }
+    pub fn doc_comments(&self) -> &[String] {
+        &self.doc_comments
+    }
+    pub fn methods(&self) -> &[ForeignInterfaceMethod] {
+        &self.items
+    }
+    /// Generated for closure argument of `foreign_class!` method,
+    /// so it has only one method `call`
+    pub fn is_closure(&self) -> bool {
+        self.is_closure
+    }
     pub(crate) fn src_id_span(&self) -> SourceIdSpan {
         (self.src_id, self.name.span())
     }
 }
 
-pub(crate) struct ForeignInterfaceMethod {
+pub struct ForeignInterfaceMethod {
     pub(crate) name: Ident,
     pub(crate) rust_name: syn::Path,
     pub(crate) fn_decl: FnDecl,
@@ -292,17 +515,51 @@ pub(crate) struct ForeignInterfaceMethod {
 }
 
 impl ForeignInterfaceMethod {
-    pub(crate) fn arg_names_without_self(&self) -> impl Iterator<Item = &str> {
+    /// Name of method in foreign language
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+    /// Path of trait method
+    pub fn rust_path(&self) -> &syn::Path {
+        &self.rust_name
+    }
+    pub fn self_variant(&self) -> SelfTypeVariant {
+        match self.fn_decl.inputs[0] {
+            FnArg::SelfArg(_, self_variant) => self_variant,
+            FnArg::Default(_) => unreachable!("callback method without self"),
+        }
+    }
+    /// Arguments of method without `self`
+    pub fn args(&self) -> impl Iterator<Item = &NamedArg> {
         self.fn_decl
             .inputs
             .iter()
             .skip(1)
-            .map(|x| x.as_named_arg().unwrap().name.as_str())
+            .map(|x| x.as_named_arg().unwrap())
+    }
+    pub fn output(&self) -> &syn::ReturnType {
+        &self.fn_decl.output
+    }
+    pub fn doc_comments(&self) -> &[String] {
+        &self.doc_comments
+    }
+    pub(crate) fn arg_names_without_self(&self) -> impl Iterator<Item = &str> {
+        self.args().map(|x| x.name.as_str())
     }
 }
 
-pub(crate) enum ItemToExpand {
+/// Item described by `foreign_class!`, `foreign_callback!` or `foreign_enum!`
+pub enum ItemToExpand {
     Class(Box<ForeignClassInfo>),
     Interface(ForeignInterface),
     Enum(ForeignEnumInfo),
 }
+
+impl ItemToExpand {
+    pub(crate) fn has_async_methods(items: &[ItemToExpand]) -> bool {
+        items.iter().any(|item| match item {
+            ItemToExpand::Class(fclass) => fclass.methods.iter().any(|m| m.is_async),
+            _ => false,
+        })
+    }
+}
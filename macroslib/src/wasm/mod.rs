@@ -0,0 +1,1322 @@
+use std::{fmt::Write, ops::Deref};
+
+use heck::SnakeCase;
+use petgraph::Direction;
+use proc_macro2::TokenStream;
+use rustc_hash::FxHashSet;
+use syn::{spanned::Spanned, Type};
+
+use crate::{
+    error::{panic_on_syn_error, DiagnosticError, Result, SourceIdSpan},
+    extension::ExtHandlers,
+    typemap::{
+        ast::{self, DisplayToTokens, TypeName},
+        ty::{ForeignConversationRule, ForeignTypeS, RustType},
+        utils::create_suitable_types_for_constructor_and_self,
+        MapToForeignFlag,
+    },
+    types::{
+        ForeignClassInfo, ForeignEnumInfo, ForeignInterface, ForeignInterfaceMethod, ForeignMethod,
+        ItemToExpand, MethodAccess, MethodVariant,
+    },
+    LanguageGenerator, SourceCode, TypeMap, WasmConfig, WRITE_TO_MEM_FAILED_MSG,
+};
+
+const ENUM_TRAIT_NAME: &str = "SwigForeignEnum";
+const INTERFACE_TRAIT_NAME: &str = "SwigForeignInterface";
+/// Return type of function, that generated conversion code is placed into
+const CONV_FUNC_RET_TYPE: &str = "Result<wasm_bindgen::JsValue, wasm_bindgen::JsError>";
+
+struct WasmContext<'a> {
+    conv_map: &'a mut TypeMap,
+    rust_code: Vec<TokenStream>,
+}
+
+/// Conversion of value between Rust and type, that `wasm-bindgen` supports
+struct WasmConversion {
+    /// Type in signature of function, that is exported by `#[wasm_bindgen]`
+    /// or imported from JS
+    wasm_ty: String,
+    /// For input: statements, that convert value of `wasm_ty` in variable
+    /// into Rust value in variable with the same name,
+    /// for output: the same in other direction.
+    /// Empty if `wasm_ty` is the same as Rust type
+    code: String,
+    /// TypeScript type, that `wasm-bindgen` uses for `wasm_ty`
+    ts_type: String,
+    /// `wasm-bindgen` supports `Option<wasm_ty>`
+    optional: bool,
+    /// Conversion returns `Err` from function
+    fallible: bool,
+}
+
+impl WasmConversion {
+    fn same_type(rust_type: &RustType, ts_type: &str) -> WasmConversion {
+        WasmConversion {
+            wasm_ty: DisplayToTokens(&rust_type.ty).to_string(),
+            code: String::new(),
+            ts_type: ts_type.into(),
+            optional: true,
+            fallible: false,
+        }
+    }
+}
+
+impl LanguageGenerator for WasmConfig {
+    fn expand_items(
+        &self,
+        conv_map: &mut TypeMap,
+        _pointer_target_width: usize,
+        _code: &[SourceCode],
+        items: Vec<ItemToExpand>,
+        _remove_not_generated_files: bool,
+        _ext_handlers: ExtHandlers,
+    ) -> Result<Vec<TokenStream>> {
+        if let Some(rule) = conv_map.take_not_merged_not_generic_rules().first() {
+            return Err(DiagnosticError::new(
+                rule.src_id,
+                rule.span,
+                "foreign_typemap! rule with code or options for foreign side is not supported for WebAssembly",
+            ));
+        }
+        let mut ctx = WasmContext {
+            conv_map,
+            rust_code: vec![],
+        };
+        for item in &items {
+            match item {
+                ItemToExpand::Class(ref fclass) => register_class(&mut ctx, fclass),
+                ItemToExpand::Enum(ref fenum) => register_enum(&mut ctx, fenum)?,
+                ItemToExpand::Interface(ref finterface) => {
+                    register_interface(&mut ctx, finterface)?
+                }
+            }
+        }
+        let mut code = Vec::with_capacity(items.len());
+        for item in &items {
+            code.push(match item {
+                ItemToExpand::Class(ref fclass) => generate_class(&mut ctx, fclass)?,
+                ItemToExpand::Enum(ref fenum) => generate_enum(fenum),
+                ItemToExpand::Interface(ref finterface) => {
+                    generate_interface(&mut ctx, finterface)?
+                }
+            });
+        }
+        code.append(&mut ctx.rust_code);
+        Ok(code)
+    }
+}
+
+fn register_class(ctx: &mut WasmContext, class: &ForeignClassInfo) {
+    if let Some(ref self_desc) = class.self_desc {
+        ctx.conv_map
+            .find_or_alloc_rust_type(&self_desc.self_type, class.src_id);
+        ctx.conv_map
+            .find_or_alloc_rust_type(&self_desc.constructor_ret_type, class.src_id);
+    }
+}
+
+fn register_enum(ctx: &mut WasmContext, fenum: &ForeignEnumInfo) -> Result<()> {
+    let enum_ty = ast::parse_ty_with_given_span(&fenum.name.to_string(), fenum.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(fenum.src_id, err))?;
+    ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &enum_ty,
+        &[ENUM_TRAIT_NAME],
+        fenum.src_id,
+    );
+    Ok(())
+}
+
+fn register_interface(ctx: &mut WasmContext, interface: &ForeignInterface) -> Result<()> {
+    let boxed_trait_ty = boxed_interface_type(interface)?;
+    let boxed_trait_rust_ty = ctx.conv_map.find_or_alloc_rust_type_that_implements(
+        &boxed_trait_ty,
+        &[INTERFACE_TRAIT_NAME],
+        interface.src_id,
+    );
+    let rule = ForeignConversationRule {
+        rust_ty: boxed_trait_rust_ty.to_idx(),
+        intermediate: None,
+    };
+    ctx.conv_map.alloc_foreign_type(ForeignTypeS {
+        name: TypeName::new(interface.name.to_string(), interface.src_id_span()),
+        provides_by_module: vec![],
+        into_from_rust: Some(rule.clone()),
+        from_into_rust: Some(rule),
+        name_prefix: None,
+    })?;
+    Ok(())
+}
+
+/// Generate `#[wasm_bindgen]` struct, that owns Rust object,
+/// with methods, that call methods of Rust object.
+/// Not public methods are not exported.
+fn generate_class(ctx: &mut WasmContext, class: &ForeignClassInfo) -> Result<TokenStream> {
+    let class_name = class.name.to_string();
+    let storage_ty = storage_type(class);
+    if storage_ty.is_none()
+        && class
+            .methods
+            .iter()
+            .any(|m| m.variant != MethodVariant::StaticMethod)
+    {
+        return Err(DiagnosticError::new(
+            class.src_id,
+            class.span(),
+            format!(
+                "Class {} has non-static methods, but no self_type",
+                class.name
+            ),
+        ));
+    }
+    let mut js_names = FxHashSet::default();
+    let mut has_constructor = false;
+    let mut methods_code = String::new();
+    for method in &class.methods {
+        if method.is_dummy_constructor() || method.access != MethodAccess::Public {
+            continue;
+        }
+        let is_static = !matches!(method.variant, MethodVariant::Method(_));
+        // JS class has only one constructor, others are static methods
+        let is_js_constructor = method.variant == MethodVariant::Constructor && !has_constructor;
+        has_constructor |= is_js_constructor;
+        let js_name = method.short_name();
+        if !is_js_constructor && !js_names.insert((js_name.clone(), is_static)) {
+            return Err(DiagnosticError::new(
+                class.src_id,
+                method.span(),
+                format!(
+                    "JS class {} already has method {}, use alias to give other name",
+                    class.name, js_name
+                ),
+            ));
+        }
+        methods_code.push_str(&generate_method(
+            ctx,
+            class,
+            method,
+            &js_name,
+            is_js_constructor,
+        )?);
+    }
+    let foreign_code = if class.foreign_code.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "#[wasm_bindgen(typescript_custom_section)]\nconst TS_FOREIGN_CODE: &'static str = {:?};\n",
+            class.foreign_code
+        )
+    };
+    let code = format!(
+        r#"
+#[allow(unused_variables, unused_mut, non_snake_case, clippy::all)]
+mod {mod_name} {{
+    use super::*;
+    use wasm_bindgen::prelude::*;
+
+    {doc_comments}
+    #[wasm_bindgen(js_name = {class_name})]
+    pub struct {wrapper_name} {{
+        pub(crate) swig_inner: {storage},
+    }}
+
+    #[wasm_bindgen(js_class = {class_name})]
+    impl {wrapper_name} {{
+        {methods_code}
+    }}
+
+    {foreign_code}
+}}
+"#,
+        mod_name = wasm_wrapper_mod_name(&class_name),
+        doc_comments = doc_comments_attrs(&class.doc_comments),
+        class_name = class_name,
+        wrapper_name = wasm_wrapper_name(&class_name),
+        storage = storage_ty
+            .as_ref()
+            .map(|ty| DisplayToTokens(ty).to_string())
+            .unwrap_or_else(|| "()".into()),
+        methods_code = methods_code,
+        foreign_code = foreign_code,
+    );
+    Ok(parse_code(&code, "wasm class"))
+}
+
+fn generate_method(
+    ctx: &mut WasmContext,
+    class: &ForeignClassInfo,
+    method: &ForeignMethod,
+    js_name: &str,
+    is_js_constructor: bool,
+) -> Result<String> {
+    let skip_n = match method.variant {
+        MethodVariant::Method(_) => 1,
+        _ => 0,
+    };
+    let mut args = Vec::with_capacity(method.fn_decl.inputs.len());
+    let mut convert_args = String::new();
+    for arg in method.fn_decl.inputs.iter().skip(skip_n) {
+        let named_arg = arg
+            .as_named_arg()
+            .map_err(|err| DiagnosticError::from_syn_err(class.src_id, err))?;
+        let arg_ty = ctx
+            .conv_map
+            .find_or_alloc_rust_type(&named_arg.ty, class.src_id);
+        let conv = js_to_rust(
+            ctx,
+            &arg_ty,
+            &named_arg.name,
+            (class.src_id, named_arg.ty.span()),
+        )?;
+        args.push(format!("{}: {}", named_arg.name, conv.wasm_ty));
+        convert_args.push_str(&conv.code);
+    }
+    let (self_arg, convert_this) = if let MethodVariant::Method(self_variant) = method.variant {
+        let storage_ty = storage_type(class).expect("method without self_type");
+        let (from_ty, to_ty) =
+            create_suitable_types_for_constructor_and_self(self_variant, class, &storage_ty);
+        let from_ty = ctx.conv_map.find_or_alloc_rust_type(&from_ty, class.src_id);
+        let to_ty = ctx.conv_map.find_or_alloc_rust_type(&to_ty, class.src_id);
+        let (mut deps, convert_this) = ctx.conv_map.convert_rust_types(
+            from_ty.to_idx(),
+            to_ty.to_idx(),
+            "this",
+            "this",
+            CONV_FUNC_RET_TYPE,
+            (class.src_id, method.span()),
+        )?;
+        ctx.rust_code.append(&mut deps);
+        let self_arg = match from_ty.ty {
+            Type::Reference(ref reference) if reference.mutability.is_some() => "&mut self",
+            _ => "&self",
+        };
+        (
+            self_arg,
+            format!(
+                "let this: {} = {}self.swig_inner;\n{}",
+                DisplayToTokens(&from_ty.ty),
+                if self_arg == "&mut self" {
+                    "&mut "
+                } else {
+                    "&"
+                },
+                convert_this
+            ),
+        )
+    } else {
+        ("", String::new())
+    };
+    if !self_arg.is_empty() {
+        args.insert(0, self_arg.to_string());
+    }
+    let ret_ty = extract_return_type(&method.fn_decl.output);
+    let ret_ty = ctx.conv_map.find_or_alloc_rust_type(&ret_ty, class.src_id);
+    let convert_ret = if method.variant == MethodVariant::Constructor {
+        // constructor returns new wrapper, even if `self_type` differs
+        // from type returned by Rust constructor
+        let storage_ty = storage_type(class).expect("constructor without self_type");
+        let fallible = ast::if_result_return_ok_err_types(&ret_ty).is_some();
+        let unwrap_result = if fallible {
+            "let swig_ret = match swig_ret { Ok(x) => x, Err(swig_err) => return Err(wasm_bindgen::JsError::new(&swig_err.to_string())) };\n"
+        } else {
+            ""
+        };
+        WasmConversion {
+            wasm_ty: wasm_wrapper_name(&class.name.to_string()),
+            code: format!(
+                "{}let swig_ret: {} = swig_ret;\nlet swig_ret = {} {{ swig_inner: swig_ret }};\n",
+                unwrap_result,
+                DisplayToTokens(&storage_ty),
+                wasm_wrapper_name(&class.name.to_string()),
+            ),
+            ts_type: class.name.to_string(),
+            optional: true,
+            fallible,
+        }
+    } else {
+        rust_to_js(ctx, &ret_ty, "swig_ret", (class.src_id, method.span()))?
+    };
+    let (ret_wasm_ty, ret_value) = if convert_ret.fallible {
+        (
+            format!(" -> Result<{}, wasm_bindgen::JsError>", convert_ret.wasm_ty),
+            "Ok(swig_ret)",
+        )
+    } else if convert_ret.wasm_ty == "()" {
+        (String::new(), "swig_ret")
+    } else {
+        (format!(" -> {}", convert_ret.wasm_ty), "swig_ret")
+    };
+    let attr = if is_js_constructor {
+        "#[wasm_bindgen(constructor)]".to_string()
+    } else {
+        format!("#[wasm_bindgen(js_name = {})]", js_name)
+    };
+    let rust_func_name = match method.variant {
+        MethodVariant::Method(_) => format!("swig_{}", js_name),
+        _ => format!("swig_static_{}", js_name),
+    };
+    Ok(format!(
+        r#"
+        {doc_comments}
+        {attr}
+        pub fn {func_name}({args}){ret_wasm_ty} {{
+            {convert_args}
+            {convert_this}
+            let swig_ret: {ret_type} = {call};
+            {convert_ret}
+            {ret_value}
+        }}
+"#,
+        doc_comments = doc_comments_attrs(&method.doc_comments),
+        attr = attr,
+        func_name = rust_func_name,
+        args = args.join(", "),
+        ret_wasm_ty = ret_wasm_ty,
+        convert_args = convert_args,
+        convert_this = convert_this,
+        ret_type = DisplayToTokens(&ret_ty.ty),
+        call = method.generate_code_to_call_rust_func(),
+        convert_ret = convert_ret.code,
+        ret_value = ret_value,
+    ))
+}
+
+/// C-like enum with `#[wasm_bindgen]` and conversions from/into Rust enum
+fn generate_enum(fenum: &ForeignEnumInfo) -> TokenStream {
+    let enum_name = fenum.name.to_string();
+    let wrapper_name = wasm_wrapper_name(&enum_name);
+    let mut items = String::new();
+    let mut from_wasm_arms = String::new();
+    let mut into_wasm_arms = String::new();
+    // the same values as C++ and Java backends use
+    for (i, item) in fenum.items.iter().enumerate() {
+        let rust_name = DisplayToTokens(&item.rust_name);
+        writeln!(
+            &mut items,
+            "{}{} = {},",
+            doc_comments_attrs(&item.doc_comments),
+            item.name,
+            i
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(
+            &mut from_wasm_arms,
+            "{}::{} => {},",
+            wrapper_name, item.name, rust_name
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+        writeln!(
+            &mut into_wasm_arms,
+            "{} => {}::{},",
+            rust_name, wrapper_name, item.name
+        )
+        .expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    let code = format!(
+        r#"
+mod {mod_name} {{
+    use super::*;
+    use wasm_bindgen::prelude::*;
+
+    {doc_comments}
+    #[wasm_bindgen(js_name = {enum_name})]
+    #[derive(Clone, Copy)]
+    pub enum {wrapper_name} {{
+        {items}
+    }}
+
+    impl From<{wrapper_name}> for {enum_name} {{
+        fn from(value: {wrapper_name}) -> {enum_name} {{
+            match value {{
+                {from_wasm_arms}
+            }}
+        }}
+    }}
+
+    impl From<{enum_name}> for {wrapper_name} {{
+        fn from(value: {enum_name}) -> {wrapper_name} {{
+            match value {{
+                {into_wasm_arms}
+            }}
+        }}
+    }}
+}}
+"#,
+        mod_name = wasm_wrapper_mod_name(&enum_name),
+        doc_comments = doc_comments_attrs(&fenum.doc_comments),
+        enum_name = enum_name,
+        wrapper_name = wrapper_name,
+        items = items,
+        from_wasm_arms = from_wasm_arms,
+        into_wasm_arms = into_wasm_arms,
+    );
+    parse_code(&code, "wasm enum")
+}
+
+/// Generate imported JS type with methods of callback, TypeScript interface for it,
+/// and Rust struct, that implements the callback's trait by calling JS methods
+fn generate_interface(ctx: &mut WasmContext, interface: &ForeignInterface) -> Result<TokenStream> {
+    let interface_name = interface.name.to_string();
+    let mut imports = String::new();
+    let mut methods_code = String::new();
+    let mut ts_methods = String::new();
+    for method in &interface.items {
+        generate_interface_method(
+            ctx,
+            interface,
+            method,
+            &mut imports,
+            &mut methods_code,
+            &mut ts_methods,
+        )?;
+    }
+    let mut ts_interface = String::new();
+    for comment in &interface.doc_comments {
+        writeln!(&mut ts_interface, "//{}", comment.trim_end()).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    write!(
+        &mut ts_interface,
+        "export interface {} {{\n{}}}",
+        interface_name, ts_methods
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    let code = format!(
+        r#"
+#[allow(unused_variables, unused_mut, non_snake_case, clippy::all)]
+mod {mod_name} {{
+    use super::*;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_INTERFACE: &'static str = {ts_interface:?};
+
+    #[wasm_bindgen]
+    extern "C" {{
+        #[wasm_bindgen(typescript_type = "{interface_name}")]
+        pub type {js_type};
+        {imports}
+    }}
+
+    pub struct WasmCallback(pub {js_type});
+
+    // JS objects can be used only by thread, that created them,
+    // and wasm32-unknown-unknown has only one thread
+    unsafe impl Send for WasmCallback {{}}
+    unsafe impl Sync for WasmCallback {{}}
+
+    impl {trait_name} for WasmCallback {{
+        {methods_code}
+    }}
+}}
+"#,
+        mod_name = wasm_wrapper_mod_name(&interface_name),
+        ts_interface = ts_interface,
+        interface_name = interface_name,
+        js_type = js_import_name(&interface_name),
+        imports = imports,
+        trait_name = DisplayToTokens(&interface.self_type.bounds[0]),
+        methods_code = methods_code,
+    );
+    Ok(parse_code(&code, "wasm callback"))
+}
+
+fn generate_interface_method(
+    ctx: &mut WasmContext,
+    interface: &ForeignInterface,
+    method: &ForeignInterfaceMethod,
+    imports: &mut String,
+    methods_code: &mut String,
+    ts_methods: &mut String,
+) -> Result<()> {
+    let src_id = interface.src_id;
+    let method_span = method.rust_name.span();
+    let rust_method_name = &method
+        .rust_name
+        .segments
+        .last()
+        .ok_or_else(|| DiagnosticError::new(src_id, method_span, "Empty trait function name"))?
+        .ident;
+    let js_method_name = method.name.to_string();
+    let import_name = format!("swig_{}", js_method_name.to_snake_case());
+    let self_arg = method.fn_decl.inputs[0].as_self_arg(src_id)?;
+
+    let mut args_with_types = Vec::with_capacity(method.fn_decl.inputs.len());
+    let mut import_args = vec![format!(
+        "this: &{}",
+        js_import_name(&interface.name.to_string())
+    )];
+    let mut convert_args = String::new();
+    let mut js_args = Vec::with_capacity(method.fn_decl.inputs.len());
+    let mut ts_args = Vec::with_capacity(method.fn_decl.inputs.len());
+    for arg in method.fn_decl.inputs.iter().skip(1) {
+        let named_arg = arg
+            .as_named_arg()
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?;
+        let arg_span = (src_id, named_arg.ty.span());
+        args_with_types.push(format!(
+            "{}: {}",
+            named_arg.name,
+            DisplayToTokens(&named_arg.ty)
+        ));
+        let arg_ty = ctx.conv_map.find_or_alloc_rust_type(&named_arg.ty, src_id);
+        let conv = rust_to_js(ctx, &arg_ty, &named_arg.name, arg_span)?;
+        if conv.fallible {
+            return Err(DiagnosticError::new(
+                src_id,
+                named_arg.ty.span(),
+                "Result as argument of callback is not supported",
+            ));
+        }
+        convert_args.push_str(&conv.code);
+        import_args.push(format!("{}: {}", named_arg.name, conv.wasm_ty));
+        js_args.push(named_arg.name.clone());
+        ts_args.push(format!("{}: {}", named_arg.name, conv.ts_type));
+    }
+
+    let ret_ty = extract_return_type(&method.fn_decl.output);
+    let ok_err_types =
+        ast::if_result_return_ok_err_types(&ctx.conv_map.find_or_alloc_rust_type(&ret_ty, src_id));
+    let ok_ty = match ok_err_types {
+        Some((ref ok_ty, _)) => ok_ty.clone(),
+        None => ret_ty.clone(),
+    };
+    let unit_ty: Type = parse_type! { () };
+    let (import_ret, convert_ret, ts_ret) = if ok_ty == unit_ty {
+        ("()".to_string(), String::new(), "void".to_string())
+    } else {
+        if let Type::Reference(_) = ok_ty {
+            return Err(DiagnosticError::new(
+                src_id,
+                method_span,
+                "Returning a reference from JS callback is not supported",
+            ));
+        }
+        let ok_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&ok_ty, src_id);
+        let conv = js_to_rust(ctx, &ok_ty_rust_ty, "swig_ret", (src_id, ok_ty.span()))?;
+        if conv.wasm_ty.starts_with('&') {
+            return Err(DiagnosticError::new(
+                src_id,
+                ok_ty.span(),
+                format!(
+                    "Returning {} from JS callback is not supported",
+                    DisplayToTokens(&ok_ty)
+                ),
+            ));
+        }
+        (conv.wasm_ty, conv.code, conv.ts_type)
+    };
+    let error_handling = if ok_err_types.is_some() {
+        // JS exception is converted to the error type of the callback,
+        // so the error type should implement `From<String>`
+        "swig_ret.map_err(|err| From::from(swig_wasm_error_message(&err)))"
+    } else {
+        // rethrow exception to JS code, that called Rust
+        "swig_ret.unwrap_or_else(|err| wasm_bindgen::throw_val(err))"
+    };
+    writeln!(
+        imports,
+        "#[wasm_bindgen(method, catch, js_name = {js_name})]\nfn {import_name}({args}) -> Result<{ret}, JsValue>;",
+        js_name = js_method_name,
+        import_name = import_name,
+        args = import_args.join(", "),
+        ret = import_ret,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    write!(
+        methods_code,
+        r#"
+        fn {rust_method_name}({self_arg}, {args_with_types}) {output} {{
+            let swig_ret: Result<{ok_ty}, JsValue> = (|| {{
+                {convert_args}
+                let swig_ret: {import_ret} = self.0.{import_name}({js_args})?;
+                {convert_ret}
+                Ok(swig_ret)
+            }})();
+            {error_handling}
+        }}
+"#,
+        rust_method_name = rust_method_name,
+        self_arg = self_arg,
+        args_with_types = args_with_types.join(", "),
+        output = DisplayToTokens(&method.fn_decl.output),
+        ok_ty = DisplayToTokens(&ok_ty),
+        convert_args = convert_args,
+        import_ret = import_ret,
+        import_name = import_name,
+        js_args = js_args.join(", "),
+        convert_ret = convert_ret,
+        error_handling = error_handling,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    for comment in &method.doc_comments {
+        writeln!(ts_methods, "    //{}", comment.trim_end()).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    writeln!(
+        ts_methods,
+        "    {}({}): {};",
+        js_method_name,
+        ts_args.join(", "),
+        ts_ret
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    Ok(())
+}
+
+/// Code to convert value of type supported by `wasm-bindgen`
+/// in variable `var` into Rust value of type `rust_type`
+fn js_to_rust(
+    ctx: &mut WasmContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<WasmConversion> {
+    let (src_id, span) = arg_ty_span;
+    if let Some(ts_type) = wasm_supported_type(rust_type) {
+        Ok(WasmConversion::same_type(rust_type, ts_type))
+    } else if rust_type.normalized_name == "& str" {
+        Ok(WasmConversion {
+            optional: false,
+            ..WasmConversion::same_type(rust_type, "string")
+        })
+    } else if let Some(conv) = if_exported_class_js_to_rust(ctx, rust_type, var, arg_ty_span)? {
+        Ok(conv)
+    } else if implements(rust_type, ENUM_TRAIT_NAME) {
+        Ok(WasmConversion {
+            wasm_ty: enum_wrapper_path(&rust_type.normalized_name),
+            code: format!(
+                "let {var}: {ty} = {var}.into();\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+            ),
+            ts_type: rust_type.normalized_name.to_string(),
+            optional: true,
+            fallible: false,
+        })
+    } else if implements(rust_type, INTERFACE_TRAIT_NAME) {
+        let interface_name = ctx
+            .conv_map
+            .find_foreign_type_related_to_rust_ty(rust_type.to_idx())
+            .map(|ftype| ctx.conv_map[ftype].typename().to_string())
+            .ok_or_else(|| {
+                DiagnosticError::new(
+                    src_id,
+                    span,
+                    format!("No callback registered for type: {}", rust_type),
+                )
+            })?;
+        let mod_name = wasm_wrapper_mod_name(&interface_name);
+        Ok(WasmConversion {
+            wasm_ty: format!("{}::{}", mod_name, js_import_name(&interface_name)),
+            code: format!(
+                "let {var}: {ty} = Box::new({mod_name}::WasmCallback({var}));\n",
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+                mod_name = mod_name,
+            ),
+            ts_type: interface_name,
+            optional: true,
+            fallible: false,
+        })
+    } else if let Some(conv) = map_type(ctx, rust_type, Direction::Incoming, arg_ty_span, var)? {
+        Ok(conv)
+    } else if let Some(inner) = ast::if_option_return_some_type(rust_type) {
+        if inner == parse_type! { &str } {
+            return Ok(WasmConversion {
+                wasm_ty: "Option<String>".into(),
+                code: format!("let {var}: Option<&str> = {var}.as_deref();\n", var = var),
+                ts_type: "string | undefined".into(),
+                optional: false,
+                fallible: false,
+            });
+        }
+        let inner_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&inner, src_id);
+        let inner = js_to_rust(ctx, &inner_rust_ty, var, arg_ty_span)?;
+        check_optional(&inner, rust_type, arg_ty_span)?;
+        Ok(WasmConversion {
+            wasm_ty: format!("Option<{}>", inner.wasm_ty),
+            code: if inner.code.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "let {var}: {ty} = match {var} {{\nSome({var}) => {{\n{inner}Some({var})\n}}\nNone => None,\n}};\n",
+                    var = var,
+                    ty = DisplayToTokens(&rust_type.ty),
+                    inner = inner.code,
+                )
+            },
+            ts_type: format!("{} | undefined", inner.ts_type),
+            optional: false,
+            fallible: false,
+        })
+    } else if let Some(elem) = if_vec_return_elem_type(rust_type) {
+        let elem_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&elem, src_id);
+        match wasm_vec_ts_type(&elem_rust_ty) {
+            Some(ts_type) => Ok(WasmConversion {
+                ts_type,
+                ..WasmConversion::same_type(rust_type, "")
+            }),
+            None => Err(unsupported_type_err(rust_type, arg_ty_span)),
+        }
+    } else if let Type::Reference(ref reference) = rust_type.ty {
+        if let Type::Slice(ref slice) = *reference.elem {
+            // `wasm-bindgen` copies slices of numbers by itself
+            let elem_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&slice.elem, src_id);
+            if wasm_number_elem(&elem_rust_ty).is_some() {
+                return Ok(WasmConversion {
+                    ts_type: wasm_vec_ts_type(&elem_rust_ty).expect("number is supported"),
+                    optional: false,
+                    ..WasmConversion::same_type(rust_type, "")
+                });
+            }
+        }
+        if reference.mutability.is_some() {
+            return Err(DiagnosticError::new(
+                src_id,
+                span,
+                "mutable reference is only supported for exported class types and slices of numbers",
+            ));
+        }
+        // `&[T]` is converted as `Vec<T>`
+        let owned_ty = match *reference.elem {
+            Type::Slice(ref slice) => {
+                let elem = &slice.elem;
+                parse_type! { Vec<#elem> }
+            }
+            ref elem => elem.clone(),
+        };
+        let owned_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&owned_ty, src_id);
+        let inner = js_to_rust(ctx, &owned_ty_rust_ty, var, arg_ty_span)?;
+        if inner.wasm_ty.starts_with('&') {
+            return Err(unsupported_type_err(rust_type, arg_ty_span));
+        }
+        Ok(WasmConversion {
+            code: format!(
+                "{inner}let {var}: {ty} = &{var};\n",
+                inner = inner.code,
+                var = var,
+                ty = DisplayToTokens(&rust_type.ty),
+            ),
+            optional: false,
+            ..inner
+        })
+    } else {
+        Err(unsupported_type_err(rust_type, arg_ty_span))
+    }
+}
+
+/// Code to convert Rust value in variable `var` into value of type
+/// supported by `wasm-bindgen` in variable with the same name
+fn rust_to_js(
+    ctx: &mut WasmContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<WasmConversion> {
+    let (src_id, _) = arg_ty_span;
+    if rust_type.ty == parse_type! { () } {
+        Ok(WasmConversion {
+            optional: false,
+            ..WasmConversion::same_type(rust_type, "void")
+        })
+    } else if let Some(ts_type) = wasm_supported_type(rust_type) {
+        Ok(WasmConversion::same_type(rust_type, ts_type))
+    } else if let Some(conv) = if_exported_class_rust_to_js(ctx, rust_type, var, arg_ty_span)? {
+        Ok(conv)
+    } else if implements(rust_type, ENUM_TRAIT_NAME) {
+        let wasm_ty = enum_wrapper_path(&rust_type.normalized_name);
+        Ok(WasmConversion {
+            code: format!(
+                "let {var}: {wasm_ty} = {var}.into();\n",
+                var = var,
+                wasm_ty = wasm_ty,
+            ),
+            wasm_ty,
+            ts_type: rust_type.normalized_name.to_string(),
+            optional: true,
+            fallible: false,
+        })
+    } else if let Some(conv) = map_type(ctx, rust_type, Direction::Outgoing, arg_ty_span, var)? {
+        Ok(conv)
+    } else if let Some(inner) = ast::if_option_return_some_type(rust_type) {
+        let inner_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&inner, src_id);
+        let inner = rust_to_js(ctx, &inner_rust_ty, var, arg_ty_span)?;
+        check_optional(&inner, rust_type, arg_ty_span)?;
+        Ok(WasmConversion {
+            code: if inner.code.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "let {var}: Option<{wasm_ty}> = match {var} {{\nSome({var}) => {{\n{inner}Some({var})\n}}\nNone => None,\n}};\n",
+                    var = var,
+                    wasm_ty = inner.wasm_ty,
+                    inner = inner.code,
+                )
+            },
+            wasm_ty: format!("Option<{}>", inner.wasm_ty),
+            ts_type: format!("{} | undefined", inner.ts_type),
+            optional: false,
+            fallible: inner.fallible,
+        })
+    } else if let Some(elem) = if_vec_return_elem_type(rust_type) {
+        let elem_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&elem, src_id);
+        if let Some(ts_type) = wasm_vec_ts_type(&elem_rust_ty) {
+            return Ok(WasmConversion {
+                ts_type,
+                ..WasmConversion::same_type(rust_type, "")
+            });
+        }
+        let inner = rust_to_js(ctx, &elem_rust_ty, var, arg_ty_span)?;
+        if inner.fallible
+            || !(implements(&elem_rust_ty, ENUM_TRAIT_NAME) || inner.code.contains("swig_inner"))
+        {
+            return Err(unsupported_type_err(rust_type, arg_ty_span));
+        }
+        // exported classes and enums are passed as array of JS objects
+        Ok(WasmConversion {
+            wasm_ty: "Vec<wasm_bindgen::JsValue>".into(),
+            code: format!(
+                "let {var}: Vec<wasm_bindgen::JsValue> = {var}.into_iter().map(|{var}| {{\n{inner}wasm_bindgen::JsValue::from({var})\n}}).collect();\n",
+                var = var,
+                inner = inner.code,
+            ),
+            ts_type: format!("{}[]", inner.ts_type),
+            optional: true,
+            fallible: false,
+        })
+    } else if let Some((ok_ty, _err_ty)) = ast::if_result_return_ok_err_types(rust_type) {
+        let ok_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&ok_ty, src_id);
+        let inner = rust_to_js(ctx, &ok_ty_rust_ty, var, arg_ty_span)?;
+        Ok(WasmConversion {
+            code: format!(
+                "let {var}: {ok_ty} = match {var} {{\nOk(x) => x,\nErr(swig_err) => return Err(wasm_bindgen::JsError::new(&swig_err.to_string())),\n}};\n{inner}",
+                var = var,
+                ok_ty = DisplayToTokens(&ok_ty),
+                inner = inner.code,
+            ),
+            optional: false,
+            fallible: true,
+            ..inner
+        })
+    } else if let Type::Reference(ref reference) = rust_type.ty {
+        // JS side gets copy of value
+        let (owned_ty, method) = match *reference.elem {
+            Type::Slice(ref slice) => {
+                let elem = &slice.elem;
+                (parse_type! { Vec<#elem> }, "to_vec()")
+            }
+            ref elem if *elem == parse_type! { str } => (parse_type! { String }, "to_string()"),
+            ref elem => (elem.clone(), "clone()"),
+        };
+        let owned_ty_rust_ty = ctx.conv_map.find_or_alloc_rust_type(&owned_ty, src_id);
+        let inner = rust_to_js(ctx, &owned_ty_rust_ty, var, arg_ty_span)?;
+        Ok(WasmConversion {
+            code: format!(
+                "let {var}: {ty} = {var}.{method};\n{inner}",
+                var = var,
+                ty = DisplayToTokens(&owned_ty),
+                method = method,
+                inner = inner.code,
+            ),
+            ..inner
+        })
+    } else {
+        Err(unsupported_type_err(rust_type, arg_ty_span))
+    }
+}
+
+fn check_optional(
+    inner: &WasmConversion,
+    rust_type: &RustType,
+    arg_ty_span: SourceIdSpan,
+) -> Result<()> {
+    if inner.optional {
+        Ok(())
+    } else {
+        Err(unsupported_type_err(rust_type, arg_ty_span))
+    }
+}
+
+fn unsupported_type_err(rust_type: &RustType, arg_ty_span: SourceIdSpan) -> DiagnosticError {
+    DiagnosticError::new(
+        arg_ty_span.0,
+        arg_ty_span.1,
+        format!("Type {} is not supported by wasm-bindgen", rust_type),
+    )
+}
+
+/// Types that `wasm-bindgen` converts by itself, returns name of TypeScript type
+fn wasm_supported_type(rust_type: &RustType) -> Option<&'static str> {
+    match rust_type.normalized_name.as_str() {
+        "bool" => Some("boolean"),
+        "char" | "String" => Some("string"),
+        name => wasm_number_elem(rust_type).map(|_| {
+            if name == "i64" || name == "u64" {
+                "bigint"
+            } else {
+                "number"
+            }
+        }),
+    }
+}
+
+/// Number types, returns name of JS typed array for `Vec` of them
+fn wasm_number_elem(rust_type: &RustType) -> Option<&'static str> {
+    match rust_type.normalized_name.as_str() {
+        "i8" => Some("Int8Array"),
+        "u8" => Some("Uint8Array"),
+        "i16" => Some("Int16Array"),
+        "u16" => Some("Uint16Array"),
+        "i32" | "isize" => Some("Int32Array"),
+        "u32" | "usize" => Some("Uint32Array"),
+        "i64" => Some("BigInt64Array"),
+        "u64" => Some("BigUint64Array"),
+        "f32" => Some("Float32Array"),
+        "f64" => Some("Float64Array"),
+        _ => None,
+    }
+}
+
+/// Elements of `Vec`, that `wasm-bindgen` converts by itself
+fn wasm_vec_ts_type(elem: &RustType) -> Option<String> {
+    if elem.normalized_name == "String" {
+        Some("string[]".into())
+    } else {
+        wasm_number_elem(elem).map(str::to_string)
+    }
+}
+
+/// Class exported to JS, with type that is passed to or returned from Rust
+struct ClassUsage {
+    class: ForeignClassInfo,
+    storage_ty: Type,
+    /// `&T` or `&mut T`
+    reference: Option<bool>,
+    /// `T` without reference, it is either self type or storage type
+    unref_ty: RustType,
+}
+
+fn if_exported_class(
+    ctx: &mut WasmContext,
+    rust_type: &RustType,
+    src_id: crate::source_registry::SourceId,
+) -> Option<ClassUsage> {
+    let (reference, unref_ty) = match rust_type.ty {
+        Type::Reference(ref reference) => (
+            Some(reference.mutability.is_some()),
+            ctx.conv_map
+                .find_or_alloc_rust_type(&reference.elem, src_id),
+        ),
+        _ => (None, rust_type.clone()),
+    };
+    let class = ctx
+        .conv_map
+        .find_foreigner_class_with_such_this_type(&unref_ty.ty, |_, fc| {
+            fc.self_desc.as_ref().map(|x| x.self_type.clone())
+        })
+        .or_else(|| {
+            ctx.conv_map
+                .find_foreigner_class_with_such_this_type(&unref_ty.ty, |_, fc| storage_type(fc))
+        })?
+        .clone();
+    let storage_ty = storage_type(&class)?;
+    Some(ClassUsage {
+        class,
+        storage_ty,
+        reference,
+        unref_ty,
+    })
+}
+
+/// JS object keeps ownership of Rust object, so Rust code gets reference
+/// to it, or its copy
+fn if_exported_class_js_to_rust(
+    ctx: &mut WasmContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<Option<WasmConversion>> {
+    let (src_id, span) = arg_ty_span;
+    let usage = match if_exported_class(ctx, rust_type, src_id) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let class = &usage.class;
+    let storage_ty = &usage.storage_ty;
+    let mutable = usage.reference.unwrap_or(false);
+    let ref_prefix = if mutable { "&mut " } else { "&" };
+    let mut code = format!(
+        "let {var}: {ref_prefix}{storage} = {ref_prefix}{var}.swig_inner;\n",
+        var = var,
+        ref_prefix = ref_prefix,
+        storage = DisplayToTokens(storage_ty),
+    );
+    let storage_rust_ty = ctx.conv_map.find_or_alloc_rust_type(storage_ty, src_id);
+    if usage.unref_ty.normalized_name != storage_rust_ty.normalized_name {
+        // `&Rc<RefCell<T>>` -> `&T` and so on
+        let from_ty = ctx.conv_map.find_or_alloc_rust_type(
+            &ast::parse_ty_with_given_span(
+                &format!("{}{}", ref_prefix, DisplayToTokens(storage_ty)),
+                span,
+            )
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?,
+            src_id,
+        );
+        let to_ty = ctx.conv_map.find_or_alloc_rust_type(
+            &ast::parse_ty_with_given_span(
+                &format!("{}{}", ref_prefix, DisplayToTokens(&usage.unref_ty.ty)),
+                span,
+            )
+            .map_err(|err| DiagnosticError::from_syn_err(src_id, err))?,
+            src_id,
+        );
+        let (mut deps, conv_code) = ctx.conv_map.convert_rust_types(
+            from_ty.to_idx(),
+            to_ty.to_idx(),
+            var,
+            var,
+            CONV_FUNC_RET_TYPE,
+            arg_ty_span,
+        )?;
+        ctx.rust_code.append(&mut deps);
+        code.push_str(&conv_code);
+        code.push('\n');
+    }
+    if usage.reference.is_none() {
+        append_clone_if_supported(ctx, &usage, var, arg_ty_span, &mut code)?;
+    }
+    Ok(Some(WasmConversion {
+        wasm_ty: format!(
+            "{}{}::{}",
+            ref_prefix,
+            wasm_wrapper_mod_name(&class.name.to_string()),
+            wasm_wrapper_name(&class.name.to_string())
+        ),
+        code,
+        ts_type: class.name.to_string(),
+        optional: false,
+        fallible: false,
+    }))
+}
+
+fn if_exported_class_rust_to_js(
+    ctx: &mut WasmContext,
+    rust_type: &RustType,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+) -> Result<Option<WasmConversion>> {
+    let (src_id, _) = arg_ty_span;
+    let usage = match if_exported_class(ctx, rust_type, src_id) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let mut code = String::new();
+    if usage.reference.is_some() {
+        append_clone_if_supported(ctx, &usage, var, arg_ty_span, &mut code)?;
+    }
+    let storage_rust_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&usage.storage_ty, src_id);
+    if usage.unref_ty.normalized_name != storage_rust_ty.normalized_name {
+        let (mut deps, conv_code) = ctx.conv_map.convert_rust_types(
+            usage.unref_ty.to_idx(),
+            storage_rust_ty.to_idx(),
+            var,
+            var,
+            CONV_FUNC_RET_TYPE,
+            arg_ty_span,
+        )?;
+        ctx.rust_code.append(&mut deps);
+        code.push_str(&conv_code);
+        code.push('\n');
+    }
+    let class_name = usage.class.name.to_string();
+    let wasm_ty = format!(
+        "{}::{}",
+        wasm_wrapper_mod_name(&class_name),
+        wasm_wrapper_name(&class_name)
+    );
+    writeln!(
+        &mut code,
+        "let {var}: {wasm_ty} = {wasm_ty} {{ swig_inner: {var} }};",
+        var = var,
+        wasm_ty = wasm_ty,
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    Ok(Some(WasmConversion {
+        wasm_ty,
+        code,
+        ts_type: class_name,
+        optional: true,
+        fallible: false,
+    }))
+}
+
+/// JS object owns Rust object, so Rust code can get only copy of it
+fn append_clone_if_supported(
+    ctx: &mut WasmContext,
+    usage: &ClassUsage,
+    var: &str,
+    arg_ty_span: SourceIdSpan,
+    code: &mut String,
+) -> Result<()> {
+    let (src_id, span) = arg_ty_span;
+    let is_shared_ptr = ["Rc", "Arc"].iter().any(|smart_ptr| {
+        ast::check_if_smart_pointer_return_inner_type(&usage.unref_ty, smart_ptr).is_some()
+    });
+    if !is_shared_ptr && !usage.class.clone_derived() && !usage.class.copy_derived() {
+        return Err(DiagnosticError::new(
+            src_id,
+            span,
+            format!(
+                "Passing object of class {} by value requires that it is marked with \
+                 `#[derive(Clone)]` or `#[derive(Copy)]` inside its `foreign_class` macro",
+                usage.class.name
+            ),
+        ));
+    }
+    let unref_ty = ctx
+        .conv_map
+        .find_or_alloc_rust_type(&usage.unref_ty.ty, src_id);
+    writeln!(
+        code,
+        "let {var}: {ty} = {var}.clone();",
+        var = var,
+        ty = DisplayToTokens(&unref_ty.ty),
+    )
+    .expect(WRITE_TO_MEM_FAILED_MSG);
+    Ok(())
+}
+
+/// Conversions of Rust types described by `foreign_typemap!` rules:
+/// Rust type is converted to the type mentioned in rule, and then to JS.
+/// Only not generic rules are supported.
+fn map_type(
+    ctx: &mut WasmContext,
+    rust_type: &RustType,
+    direction: Direction,
+    arg_ty_span: SourceIdSpan,
+    var: &str,
+) -> Result<Option<WasmConversion>> {
+    let ftype_idx = match ctx.conv_map.map_through_conversation_to_foreign(
+        rust_type.to_idx(),
+        direction,
+        MapToForeignFlag::FastSearch,
+        arg_ty_span,
+        |_, fc| storage_type(fc),
+    ) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let ftype = &ctx.conv_map[ftype_idx];
+    let rule = match direction {
+        Direction::Outgoing => ftype.into_from_rust.as_ref(),
+        Direction::Incoming => ftype.from_into_rust.as_ref(),
+    }
+    .expect("Internal error: foreign type was found without conversion rule");
+    if rule.intermediate.is_some() {
+        return Err(DiagnosticError::new2(
+            ftype.src_id_span(),
+            format!(
+                "f_type {}: conversion code on foreign side is not supported for WebAssembly",
+                ftype.name
+            ),
+        ));
+    }
+    let js_rust_ty = rule.rust_ty;
+    if js_rust_ty == rust_type.to_idx() {
+        return Ok(None);
+    }
+    let (from, to) = match direction {
+        Direction::Outgoing => (rust_type.to_idx(), js_rust_ty),
+        Direction::Incoming => (js_rust_ty, rust_type.to_idx()),
+    };
+    let (mut deps, conv_code) =
+        ctx.conv_map
+            .convert_rust_types(from, to, var, var, CONV_FUNC_RET_TYPE, arg_ty_span)?;
+    ctx.rust_code.append(&mut deps);
+    let js_rust_ty = ctx.conv_map[js_rust_ty].clone();
+    let conv = match direction {
+        Direction::Outgoing => {
+            let conv = rust_to_js(ctx, &js_rust_ty, var, arg_ty_span)?;
+            WasmConversion {
+                code: format!("{}\n{}", conv_code, conv.code),
+                ..conv
+            }
+        }
+        Direction::Incoming => {
+            let conv = js_to_rust(ctx, &js_rust_ty, var, arg_ty_span)?;
+            WasmConversion {
+                code: format!("{}{}\n", conv.code, conv_code),
+                ..conv
+            }
+        }
+    };
+    Ok(Some(conv))
+}
+
+/// Type of Rust object, that wrapper holds
+fn storage_type(class: &ForeignClassInfo) -> Option<Type> {
+    class.self_desc.as_ref().map(|x| {
+        ast::if_ty_result_return_ok_type(&x.constructor_ret_type)
+            .unwrap_or_else(|| x.constructor_ret_type.clone())
+    })
+}
+
+fn implements(rust_type: &RustType, trait_name: &str) -> bool {
+    let trait_path: syn::Path = syn::Ident::new(trait_name, proc_macro2::Span::call_site()).into();
+    rust_type.implements.contains_path(&trait_path)
+}
+
+fn if_vec_return_elem_type(ty: &RustType) -> Option<Type> {
+    ast::check_if_smart_pointer_return_inner_type(ty, "Vec")
+}
+
+fn extract_return_type(syn_return_type: &syn::ReturnType) -> Type {
+    match syn_return_type {
+        syn::ReturnType::Default => {
+            parse_type! { () }
+        }
+        syn::ReturnType::Type(_, ref ty) => ty.deref().clone(),
+    }
+}
+
+fn boxed_interface_type(interface: &ForeignInterface) -> Result<Type> {
+    let boxed_trait_name = format!("Box<dyn {}>", DisplayToTokens(&interface.self_type));
+    ast::parse_ty_with_given_span(&boxed_trait_name, interface.name.span())
+        .map_err(|err| DiagnosticError::from_syn_err(interface.src_id, err))
+}
+
+/// `#[doc]` attributes, `wasm-bindgen` copies them into TypeScript declarations
+fn doc_comments_attrs(doc_comments: &[String]) -> String {
+    let mut out = String::new();
+    for comment in doc_comments {
+        writeln!(&mut out, "#[doc = {:?}]", comment).expect(WRITE_TO_MEM_FAILED_MSG);
+    }
+    out
+}
+
+fn enum_wrapper_path(enum_name: &str) -> String {
+    format!(
+        "{}::{}",
+        wasm_wrapper_mod_name(enum_name),
+        wasm_wrapper_name(enum_name)
+    )
+}
+
+/// Name of Rust type, that is exported to JS under name of class or enum
+fn wasm_wrapper_name(type_name: &str) -> String {
+    format!("Wasm{}", type_name)
+}
+
+/// Name of Rust type for JS object, that implements callback
+fn js_import_name(interface_name: &str) -> String {
+    format!("Js{}", interface_name)
+}
+
+fn wasm_wrapper_mod_name(type_name: &str) -> String {
+    format!("wasm_{}", type_name.to_snake_case())
+}
+
+fn parse_code(code: &str, id_of_code: &str) -> TokenStream {
+    syn::parse_str(code).unwrap_or_else(|err| panic_on_syn_error(id_of_code, code.to_string(), err))
+}
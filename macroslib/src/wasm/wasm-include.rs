@@ -0,0 +1,82 @@
+mod swig_foreign_types_map {}
+
+/// Message of JS exception, that callback threw
+#[allow(dead_code)]
+fn swig_wasm_error_message(err: &wasm_bindgen::JsValue) -> String {
+    err.as_string().unwrap_or_else(|| format!("{:?}", err))
+}
+
+// JS `Date` uses milliseconds since Unix epoch as `number`
+foreign_typemap!(
+    ($p:r_type) SystemTime => f64 {
+        let since_unix_epoch = $p
+            .duration_since(::std::time::UNIX_EPOCH)
+            .expect("SystemTime to Unix time conv. error");
+        $out = since_unix_epoch.as_secs_f64() * 1_000.;
+    };
+    ($p:f_type) => "number";
+    ($p:r_type) SystemTime <= f64 {
+        $out = ::std::time::UNIX_EPOCH + ::std::time::Duration::from_secs_f64($p / 1_000.);
+    };
+    ($p:f_type) <= "number";
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Arc<Mutex<T>> => &Mutex<T> {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &Mutex<T> => MutexGuard<T> {
+        $out = $p.lock().unwrap();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> MutexGuard<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> MutexGuard<T> => &mut T {
+        $out = &mut $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Rc<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &Rc<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &RefCell<T> => Ref<T> {
+        $out = $p.borrow();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> &RefCell<T> => RefMut<T> {
+        $out = $p.borrow_mut();
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> Ref<T> => &T {
+        $out = & $p;
+    };
+);
+
+foreign_typemap!(
+    ($p:r_type) <T> RefMut<T> => &mut T {
+        $out = &mut $p;
+    };
+);
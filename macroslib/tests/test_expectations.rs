@@ -1,11 +1,22 @@
 use std::{
     ffi::OsString,
-    fs, panic,
+    fs,
+    io::Write,
+    panic,
     path::{Path, PathBuf},
 };
 
-use flapigen::{rustfmt_cnt, CppConfig, Generator, JavaConfig, LanguageConfig, RustEdition};
+use flapigen::{
+    backend::{
+        self, BackendContext, DiagnosticError, Direction, ItemToExpand, LanguageBackend,
+        MappedForeignType, MethodVariant, SourceIdSpan,
+    },
+    rustfmt_cnt, CConfig, CSharpConfig, CatchPanics, CppConfig, DartConfig, Generator, GoConfig,
+    JavaConfig, JavaOutputLanguage, LanguageConfig, LuaConfig, NodeConfig, PythonBinding,
+    PythonConfig, RubyConfig, RustEdition, SwiftConfig, WasmConfig,
+};
 use log::warn;
+use proc_macro2::TokenStream;
 use syn::Token;
 use tempfile::tempdir;
 
@@ -230,6 +241,505 @@ foreign_class!(class TestEnumClass {
     }
 }
 
+#[test]
+fn test_foreign_enum_with_data() {
+    let _ = env_logger::try_init();
+
+    let name = "foreign_enum_with_data";
+    let src = r#"
+foreign_enum!(enum Color {
+    RED = Color::Red,
+    GREEN = Color::Green,
+});
+
+foreign_enum!(
+/// Event from network
+enum Event {
+    Connected = Event::Connected { id: u64, addr: String },
+    Failed = Event::Failed(String),
+    Painted = Event::Painted { color: Color, flag: bool },
+    Closed = Event::Closed,
+});
+
+foreign_class!(class Dispatcher {
+    fn Dispatcher::next_event() -> Event;
+    fn Dispatcher::handle(e: Event) -> bool;
+});
+"#;
+    let java_src = format!(
+        "{}{}",
+        src,
+        r#"
+foreign_class!(class History {
+    fn History::last_event() -> Option<Event>;
+    fn History::replay(e: Option<Event>);
+});
+"#
+    );
+
+    let java_code = parse_code(name, Source::Str(&java_src), ForeignLang::Java).unwrap();
+    println!("java: {}", java_code.foreign_code);
+    assert!(java_code
+        .foreign_code
+        .contains("public abstract class Event {"));
+    assert!(java_code
+        .foreign_code
+        .contains("public static final class Connected extends Event {"));
+    assert!(java_code.foreign_code.contains("public final long id;"));
+    assert!(java_code
+        .foreign_code
+        .contains("public final @NonNull String addr;"));
+    assert!(java_code
+        .foreign_code
+        .contains("public final @NonNull String field0;"));
+    assert!(java_code
+        .foreign_code
+        .contains("public static final class Closed extends Event {"));
+    assert!(java_code
+        .foreign_code
+        .contains("public static native Event next_event();"));
+    assert!(java_code.foreign_code.contains(
+        r#"    public static @NonNull java.util.Optional<Event> last_event() {
+        Event ret = do_last_event();
+        java.util.Optional<Event> convRet = java.util.Optional.ofNullable(ret);"#
+    ));
+    assert!(java_code
+        .foreign_code
+        .contains("public static native void replay(@Nullable Event e);"));
+    let rust_code: String = java_code.rust_code.split_whitespace().collect();
+    assert!(rust_code.contains("implSwigFrom<Event>forjobject{"));
+    assert!(rust_code.contains("implSwigFrom<jobject>forEvent{"));
+    assert!(rust_code.contains(r#""org/example/Event$Connected""#));
+
+    let kotlin_code =
+        parse_code(name, Source::Str(&java_src), ForeignLang::Kotlin).unwrap();
+    println!("kotlin: {}", kotlin_code.foreign_code);
+    assert!(kotlin_code.foreign_code.contains("sealed class Event {"));
+    assert!(kotlin_code
+        .foreign_code
+        .contains("class Connected(@JvmField val id: Long, @JvmField val addr: String) : Event()"));
+    assert!(kotlin_code
+        .foreign_code
+        .contains("fun last_event(): Event? {"));
+    assert!(kotlin_code
+        .foreign_code
+        .contains("external fun replay(e: Event?)"));
+
+    let cpp_code = parse_code(name, Source::Str(src), ForeignLang::Cpp).unwrap();
+    println!("c/c++: {}", cpp_code.foreign_code);
+    assert!(cpp_code.foreign_code.contains(
+        r#"struct EventConnected {
+    uint64_t id;
+    std::string addr;
+};"#
+    ));
+    assert!(cpp_code.foreign_code.contains("struct EventClosed {};"));
+    assert!(cpp_code.foreign_code.contains(
+        "/// Event from network\nusing Event = std::variant<"
+    ));
+    assert!(cpp_code.foreign_code.contains(
+        "using Event = std::variant<EventConnected, EventFailed, EventPainted, EventClosed>;"
+    ));
+    assert!(cpp_code.foreign_code.contains(
+        r#"struct CEvent {
+    uint32_t tag;
+    union CEventData data;
+};"#
+    ));
+    assert!(cpp_code.foreign_code.contains("struct CRustStrView addr;"));
+    assert!(cpp_code.foreign_code.contains("CEventTag_Closed = 3"));
+    let rust_code: String = cpp_code.rust_code.split_whitespace().collect();
+    assert!(rust_code.contains("implSwigFrom<Event>forCEvent{"));
+    assert!(rust_code.contains("implSwigFrom<CEventIn>forEvent{"));
+
+    let c_code = parse_code(name, Source::Str(src), ForeignLang::C).unwrap();
+    println!("c: {}", c_code.foreign_code);
+    assert!(c_code
+        .foreign_code
+        .contains("typedef struct CEvent CEvent;"));
+    assert!(c_code
+        .foreign_code
+        .contains("struct CEvent Dispatcher_next_event(void);"));
+    assert!(!c_code.foreign_code.contains("std::variant"));
+
+    for lang in &[ForeignLang::Python, ForeignLang::PythonPyO3] {
+        let py_code = parse_code(name, Source::Str(src), *lang).unwrap();
+        let rust_code: String = py_code.rust_code.split_whitespace().collect();
+        assert!(rust_code.contains("pubfnto_py("));
+        assert!(rust_code.contains("super::py_event::from_py(py,e)?"));
+        assert!(rust_code.contains("letcolor=super::py_color::from_u32(py,color)?;"));
+        if *lang == ForeignLang::PythonPyO3 {
+            assert!(rust_code.contains(
+                r#"#[pyo3::pyclass(module="flapigen_test",extends=Event)]pubstructEventConnected{"#
+            ));
+        } else {
+            assert!(rust_code.contains("py_class!(pubclassEventConnected|py|{"));
+        }
+    }
+}
+
+#[test]
+fn test_foreign_class_fields() {
+    let _ = env_logger::try_init();
+
+    let name = "foreign_class_fields";
+    let src = r#"
+foreign_class!(class Point {
+    self_type Point;
+    constructor Point::new() -> Point;
+    /// Coordinate x
+    field x: f64;
+    readonly field id: u32;
+    #[getter]
+    fn Point::get_len(&self) -> f64;
+    #[setter]
+    fn Point::set_len(&mut self, len: f64);
+});
+"#;
+
+    let java_code = parse_code(name, Source::Str(src), ForeignLang::Java).unwrap();
+    println!("java: {}", java_code.foreign_code);
+    assert!(java_code
+        .foreign_code
+        .contains("public final double getX() {"));
+    assert!(java_code
+        .foreign_code
+        .contains("public final void setX(double x) {"));
+    assert!(java_code
+        .foreign_code
+        .contains("public final long getId() {"));
+    assert!(!java_code.foreign_code.contains("setId"));
+    assert!(java_code
+        .foreign_code
+        .contains("public final void setLen(double len) {"));
+    let rust_code: String = java_code.rust_code.split_whitespace().collect();
+    assert!(rust_code.contains("::std::clone::Clone::clone(&this.x)"));
+    assert!(rust_code.contains("this.x=x;"));
+
+    let cpp_code = parse_code(name, Source::Str(src), ForeignLang::Cpp).unwrap();
+    println!("c/c++: {}", cpp_code.foreign_code);
+    assert!(cpp_code
+        .foreign_code
+        .contains("double get_x() const noexcept;"));
+    assert!(cpp_code
+        .foreign_code
+        .contains("void set_x(double x) noexcept;"));
+    assert!(cpp_code
+        .foreign_code
+        .contains("uint32_t get_id() const noexcept;"));
+    assert!(!cpp_code.foreign_code.contains("set_id"));
+
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::PythonPyO3).unwrap();
+    let rust_code: String = py_code.rust_code.split_whitespace().collect();
+    assert!(rust_code.contains("#[getter(x)]fnx(&self,py:pyo3::Python<'_>)->pyo3::PyResult<f64>{"));
+    assert!(rust_code
+        .contains("#[setter(x)]fnset_x(&self,py:pyo3::Python<'_>,x:f64)->pyo3::PyResult<()>{"));
+    assert!(rust_code.contains("(this,)=>{::std::clone::Clone::clone(&this.x)}"));
+    assert!(rust_code.contains("#[setter(len)]fnset_len("));
+    assert!(!rust_code.contains("set_id"));
+
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::Python).unwrap();
+    let rust_code: String = py_code.rust_code.split_whitespace().collect();
+    assert!(rust_code.contains("@propertydefx(&self)->cpython::PyResult<f64>{"));
+    assert!(rust_code.contains("@x.setterdefset_x(&self,x:Option<f64>)->cpython::PyResult<()>{"));
+}
+
+#[test]
+fn test_async_fn() {
+    let _ = env_logger::try_init();
+
+    let name = "async_fn";
+    let src = r#"
+foreign_class!(class Point {
+    self_type Point;
+    constructor Point::new() -> Point;
+});
+foreign_class!(class Loader {
+    self_type Loader;
+    constructor Loader::new() -> Arc<Loader>;
+    async fn Loader::load(&self, id: u32) -> String;
+    async fn Loader::flush(&self);
+    async fn Loader::point(&self) -> Result<Point, String>;
+    async fn sum(a: i32, b: i32) -> i32 {
+        a + b
+    }
+});
+"#;
+
+    let java_code = parse_code(name, Source::Str(src), ForeignLang::Java).unwrap();
+    println!("java: {}", java_code.foreign_code);
+    assert!(java_code.foreign_code.contains(
+        "public final java.util.concurrent.CompletableFuture<String> load(long id) {"
+    ));
+    assert!(java_code
+        .foreign_code
+        .contains("public final java.util.concurrent.CompletableFuture<Void> flush() {"));
+    assert!(java_code.foreign_code.contains(
+        "public static java.util.concurrent.CompletableFuture<Integer> sum(int a, int b) {"
+    ));
+    assert!(java_code
+        .foreign_code
+        .contains("public final class RustFuture<T> extends java.util.concurrent.CompletableFuture<T> {"));
+    assert!(java_code.foreign_code.contains(
+        r#"    public final java.util.concurrent.CompletableFuture<Point> point() {
+        RustFuture<Long> future = new RustFuture<Long>(this);
+        future.setTask(do_point(mNativeObj, future));
+        return future.thenConvert((Long ret) -> {
+            Point convRet = new Point(InternalPointerMarker.RAW_PTR, ret);
+            return convRet;
+        });
+    }"#
+    ));
+    let rust_code: String = java_code.rust_code.split_whitespace().collect();
+    assert!(rust_code.contains("letswig_task=swig_async_spawn(asyncmove{"));
+    assert!(rust_code.contains("asyncmove{a+b}"));
+
+    let cpp_code = parse_code(name, Source::Str(src), ForeignLang::Cpp).unwrap();
+    println!("c/c++: {}", cpp_code.foreign_code);
+    assert!(cpp_code.foreign_code.contains(
+        "RustAsyncTask *Loader_load(const LoaderOpaque * const self, uint32_t id, \
+         void (*on_complete)(void *opaque, struct CRustString ret), \
+         void (*free_opaque)(void *opaque), void *opaque);"
+    ));
+    assert!(cpp_code.foreign_code.contains(
+        "RustAsyncTask *Loader_sum(int32_t a, int32_t b, \
+         void (*on_complete)(void *opaque, int32_t ret), \
+         void (*free_opaque)(void *opaque), void *opaque);"
+    ));
+    assert!(cpp_code
+        .foreign_code
+        .contains("std::future<RustString> load(uint32_t id) const noexcept;"));
+    assert!(cpp_code.foreign_code.contains(
+        "AsyncTask load(uint32_t id, std::function<void(RustString)> on_complete) const noexcept;"
+    ));
+    assert!(cpp_code
+        .foreign_code
+        .contains("AsyncTask flush(std::function<void()> on_complete) const noexcept;"));
+    assert!(cpp_code
+        .foreign_code
+        .contains("static std::future<int32_t> sum(int32_t a, int32_t b) noexcept;"));
+
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::PythonPyO3).unwrap();
+    let rust_code: String = py_code.rust_code.split_whitespace().collect();
+    assert!(rust_code.contains(
+        "fnload(&self,py:pyo3::Python<'_>,id:u32)->pyo3::PyResult<pyo3::PyObject>{"
+    ));
+    assert!(rust_code.contains(
+        "letrust_instance=super::py_loader::rust_instance(self,py).clone();\
+         Ok(swig_py_async_spawn(py,asyncmove{Loader::load((&*rust_instance),id).await},"
+    ));
+
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::Python).unwrap();
+    let rust_code: String = py_code.rust_code.split_whitespace().collect();
+    assert!(rust_code.contains("defload(&self,id:u32)->cpython::PyResult<cpython::PyObject>{"));
+
+    for lang in &[ForeignLang::Cpp, ForeignLang::Java] {
+        let ret = panic::catch_unwind(|| {
+            parse_code(
+                name,
+                Source::Str(
+                    r#"
+foreign_class!(class Loader {
+    self_type Loader;
+    constructor Loader::new() -> Loader;
+    #[getter]
+    async fn Loader::get_id(&self) -> u32;
+});
+"#,
+                ),
+                *lang,
+            )
+            .expect(name)
+        });
+        assert!(ret.is_err());
+    }
+    // object stored in `Mutex` can not be borrowed by future
+    let ret = panic::catch_unwind(|| {
+        parse_code(
+            name,
+            Source::Str(
+                r#"
+foreign_class!(class Loader {
+    self_type Loader;
+    constructor Loader::new() -> Loader;
+    async fn Loader::load(&self, id: u32) -> String;
+});
+"#,
+            ),
+            ForeignLang::PythonPyO3,
+        )
+        .expect(name)
+    });
+    assert!(ret.is_err());
+}
+
+#[test]
+fn test_closure_args() {
+    let _ = env_logger::try_init();
+
+    let name = "closure_args";
+    let src = r#"
+foreign_class!(class Bus {
+    self_type Bus;
+    constructor Bus::new() -> Bus;
+    fn Bus::subscribe(&self, cb: Box<dyn Fn(i32) + Send>);
+    fn Bus::unsubscribe_all(&self, on_done: impl FnOnce(i32) + Send + 'static);
+    fn Bus::filter(&self, f: Box<dyn FnMut(&str, i32) -> bool>) -> i32;
+});
+"#;
+
+    let java_code = parse_code(name, Source::Str(src), ForeignLang::Java).unwrap();
+    println!("java: {}", java_code.foreign_code);
+    assert!(java_code.foreign_code.contains(
+        r#"@FunctionalInterface
+public interface FnI32 {
+
+
+    void call(int a0);
+
+}"#
+    ));
+    assert!(java_code
+        .foreign_code
+        .contains("public final void subscribe(@NonNull FnI32 cb) {"));
+    assert!(java_code
+        .foreign_code
+        .contains("public final void unsubscribe_all(@NonNull FnI32 on_done) {"));
+    assert!(java_code
+        .foreign_code
+        .contains("boolean call(@NonNull String a0, int a1);"));
+    let rust_code: String = java_code.rust_code.split_whitespace().collect();
+    assert!(rust_code.contains("traitSwigFnI32:Send{fncall(&self,a0:i32);}"));
+    assert!(rust_code.contains("letcb:Box<dynFn(i32)+Send>=Box::new(move|a0:i32|{cb.call(a0)});"));
+    assert!(rust_code.contains(
+        "leton_done:Box<dynFnOnce(i32)+Send+'static>=Box::new(move|a0:i32|{on_done.call(a0)});"
+    ));
+
+    let cpp_code = parse_code(name, Source::Str(src), ForeignLang::Cpp).unwrap();
+    println!("c/c++: {}", cpp_code.foreign_code);
+    assert!(cpp_code
+        .foreign_code
+        .contains("void subscribe(std::function<void(int32_t)> cb) const noexcept;"));
+    assert!(cpp_code.foreign_code.contains(
+        "int32_t filter(std::function<bool(std::string_view, int32_t)> f) const noexcept;"
+    ));
+    assert!(cpp_code.foreign_code.contains(
+        r#"    static C_FnI32 to_c_interface(std::function<void(int32_t)> f)
+    {
+        assert(f != nullptr);
+        C_FnI32 ret;
+        ret.opaque = new std::function<void(int32_t)>(std::move(f));"#
+    ));
+    assert!(cpp_code
+        .foreign_code
+        .contains("void (*call)(int32_t a0, void *opaque);"));
+
+    let ret = panic::catch_unwind(|| {
+        parse_code(
+            name,
+            Source::Str(
+                r#"
+foreign_class!(class Bus {
+    self_type Bus;
+    constructor Bus::new() -> Bus;
+    fn Bus::subscribe(&self, cb: Box<dyn Fn(i32) + Send + Sync>);
+});
+"#,
+            ),
+            ForeignLang::Java,
+        )
+        .expect(name)
+    });
+    assert!(ret.is_err());
+}
+
+#[test]
+fn test_iterator_return() {
+    let _ = env_logger::try_init();
+
+    let name = "iterator_return";
+    let src = r#"
+foreign_class!(class Numbers {
+    self_type Numbers;
+    constructor Numbers::new() -> Numbers;
+    fn Numbers::iter(&self) -> impl Iterator<Item = i32> + Send;
+    fn Numbers::names() -> Box<dyn Iterator<Item = String> + Send>;
+});
+"#;
+
+    let java_code = parse_code(name, Source::Str(src), ForeignLang::Java).unwrap();
+    println!("java: {}", java_code.foreign_code);
+    assert!(java_code.foreign_code.contains(
+        "public final class IterI32 implements java.util.Iterator<Integer>, Iterable<Integer> {"
+    ));
+    assert!(java_code.foreign_code.contains(
+        r#"    public Integer next() {
+        if (!hasNextElement()) {
+            throw new java.util.NoSuchElementException();
+        }
+        return nextElement();
+    }"#
+    ));
+    assert!(java_code
+        .foreign_code
+        .contains("public final @NonNull IterI32 iter() {"));
+    assert!(java_code
+        .foreign_code
+        .contains("public java.util.Iterator<String> iterator() {"));
+    let rust_code: String = java_code.rust_code.split_whitespace().collect();
+    assert!(rust_code.contains(
+        "structSwigIterI32(::std::iter::Peekable<Box<dynIterator<Item=i32>+Send>>);"
+    ));
+    assert!(rust_code.contains(
+        "letret:Box<dynIterator<Item=i32>+Send>=Box::new(Numbers::iter(this));"
+    ));
+    assert!(rust_code.contains("letmutret:Box<dynIterator<Item=i32>+Send>={"));
+    assert!(rust_code.contains("letmutret:Box<dynIterator<Item=String>+Send>=Numbers::names();"));
+
+    let kotlin_code = parse_code(name, Source::Str(src), ForeignLang::Kotlin).unwrap();
+    println!("kotlin: {}", kotlin_code.foreign_code);
+    assert!(kotlin_code
+        .foreign_code
+        .contains("AutoCloseable, Iterator<Int>, Iterable<Int> {"));
+    assert!(kotlin_code
+        .foreign_code
+        .contains("override fun iterator(): Iterator<String> = this"));
+
+    let cpp_code = parse_code(name, Source::Str(src), ForeignLang::Cpp).unwrap();
+    println!("c/c++: {}", cpp_code.foreign_code);
+    assert!(cpp_code.foreign_code.contains("std::optional<int32_t> next() noexcept;"));
+    assert!(cpp_code.foreign_code.contains(
+        "using iterator = RustInputIterator<IterI32Wrapper, std::optional<int32_t>>;"
+    ));
+    assert!(cpp_code.foreign_code.contains("#include \"rust_input_iterator.hpp\""));
+    assert!(cpp_code.foreign_code.contains("IterI32 iter() const noexcept;"));
+
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::PythonPyO3).unwrap();
+    let rust_code = rustfmt_without_errors(py_code.rust_code);
+    println!("rust: {}", rust_code);
+    assert!(rust_code.contains("fn __next__("));
+    assert!(rust_code.contains("fn __iter__(slf: pyo3::PyRef<'_, Self>) -> pyo3::PyRef<'_, Self>"));
+    assert!(rust_code.contains("let ret: SwigIterI32 = SwigIterI32(ret.peekable());"));
+
+    let ret = panic::catch_unwind(|| {
+        parse_code(
+            name,
+            Source::Str(
+                r#"
+foreign_class!(class Numbers {
+    self_type Numbers;
+    constructor Numbers::new() -> Numbers;
+    fn Numbers::iter(&self) -> impl Iterator<Item = i32> + '_;
+});
+"#,
+            ),
+            ForeignLang::Java,
+        )
+        .expect(name)
+    });
+    assert!(ret.is_err());
+}
+
 #[test]
 fn test_return_result_type_with_object() {
     let _ = env_logger::try_init();
@@ -355,123 +865,2239 @@ foreign_interface!(interface RepoChangedCallback {
 }
 
 #[test]
-fn test_derive_extension_usage() {
+fn test_foreign_interface_python() {
     let _ = env_logger::try_init();
-    let rust_src = r#"
-foreign_class!(
-#[derive(QObject)]
-class MyObj {
-    self_type MyObj;
-    private constructor MyObj::new() -> MyObj;
-    #[Q_INVOKABLE]
-    fn MyObj::f();
+
+    let name = "foreign_interface_python";
+    let src = r#"
+foreign_class!(class Uuid {
+    self_type Uuid;
+    constructor Uuid::new_v4() -> Uuid;
 });
 
-foreign_enum!(
-#[derive(EnumClass)]
-/// enum comment
-enum MyEnum {
-  A = MyEnum::A,
-  B = MyEnum::B,
-}
-);
-"#;
-    let tmp_dir = tempdir().expect("Can not create tmp directory");
-    let swig_gen = Generator::new(LanguageConfig::CppConfig(CppConfig::new(
-        tmp_dir.path().into(),
-        "org_examples".into(),
-    )))
-    .with_pointer_target_width(64)
-    .register_class_attribute_callback("QObject", |code, class_name| {
-        println!("class attribute callback class_name {}", class_name);
-        let include = b"#include";
-        let mut last_pos = 0;
-        let mut search_shift = 0;
-        while let Some(pos) = find_subsequence(&code[(last_pos + search_shift)..], include) {
-            last_pos += pos + search_shift;
-            search_shift = 1;
-        }
-        let last_include_pos = last_pos;
-        let new_line = &code[last_include_pos..].iter().position(|x| *x == b'\n');
-        let new_line_pos = last_include_pos + new_line.unwrap();
-        let addon = br##"
-#include <QObject>
-"##;
-        code.splice(new_line_pos..new_line_pos, addon.iter().copied());
+foreign_callback!(callback RepoChangedCallback {
+    self_type RepoChangedCallback;
+    onSave = RepoChangedCallback::on_save(&self, uuid: &str, count: i32);
+    onCheck = RepoChangedCallback::on_check(&self) -> Result<bool, String>;
+});
 
-        let needle = format!("class {}Wrapper {{", class_name);
-        let class_pos = find_subsequence(&code, needle.as_bytes()).unwrap();
-        let end_pos = class_pos + needle.as_bytes().len();
-        let new_code = format!(
-            r#"class {}Wrapper : public QObject {{
-    Q_OBJECT"#,
-            class_name
-        );
-        code.splice(class_pos..end_pos, new_code.as_bytes().iter().copied());
-    })
-    .register_method_attribute_callback("Q_INVOKABLE", |code, ctx| {
-        println!(
-            "method attribute callback class {}, method {}",
-            ctx.class_name, ctx.method_name
-        );
-        let needle = match ctx.variant {
-            flapigen::MethodVariant::Constructor => {
-                panic!("unsupported");
-            }
-            flapigen::MethodVariant::Method(_) => format!("void {}(", ctx.method_name),
-            flapigen::MethodVariant::StaticMethod => format!("static void {}(", ctx.method_name),
-        };
-        let pos = find_subsequence(&code, needle.as_bytes()).unwrap();
-        code.splice(pos..pos, b"Q_INVOKABLE ".iter().copied());
-    })
-    .register_enum_attribute_callback("EnumClass", |code, ctx| {
-        println!("EnumClass callback, ctx {}", ctx);
-        let needle = format!("enum {}", ctx);
-        let pos = find_subsequence(&code, needle.as_bytes()).unwrap();
-        code.splice((pos + 5)..(pos + 5), b"class ".iter().copied());
-    });
-    let rust_code_path = tmp_dir.path().join("test.rs");
+foreign_class!(class Repo {
+    self_type Repo;
+    constructor Repo::new() -> Repo;
+    fn Repo::subscribe(&mut self, cb: Box<dyn RepoChangedCallback>);
+});
+"#;
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::Python).unwrap();
+    let rust_code = rustfmt_without_errors(py_code.rust_code);
+    println!("rust: {}", rust_code);
+    assert!(rust_code.contains("impl super::RepoChangedCallback for RepoChangedCallback {"));
+    assert!(rust_code.contains("fn on_save(&self, a0: &str, a1: i32) {"));
+    assert!(rust_code.contains("let gil = cpython::Python::acquire_gil();"));
+    assert!(rust_code.contains(r#"self.object.call_method(py, "onSave", (a0, a1), None)?;"#));
+    assert!(rust_code.contains("fn on_check(&self) -> Result<bool, String> {"));
+    assert!(rust_code.contains("ret.map_err(|err| From::from(swig_py_err_to_string(py, err)))"));
+    assert!(rust_code.contains(
+        "Box :: new (super :: py_repo_changed_callback :: RepoChangedCallback :: new (cb))"
+    ));
+}
 
-    let rust_src_path = tmp_dir.path().join("src.rs");
-    fs::write(&rust_src_path, rust_src).unwrap();
-    swig_gen.expand("derive_extension_usage", rust_src_path, &rust_code_path);
+#[test]
+fn test_python_typemap_conversions() {
+    let _ = env_logger::try_init();
 
-    let _rust_code = fs::read_to_string(rust_code_path).unwrap();
-    let foreign_code = collect_code_in_dir(tmp_dir.path(), &[".h", ".hpp"]).unwrap();
-    println!("foreign_code: {}", foreign_code);
-    assert!(foreign_code.contains(
-        r##"#include <QObject>
-"##
+    let name = "python_typemap_conversions";
+    let src = r#"
+foreign_class!(class Storage {
+    self_type Storage;
+    constructor Storage::new() -> Storage;
+    fn Storage::find(&self, key: Option<&str>) -> Option<Storage>;
+    fn Storage::pair(&self, p: (i32, String)) -> (String, i32);
+    fn Storage::counts(&self) -> HashMap<String, u32>;
+    fn Storage::set_counts(&mut self, counts: BTreeMap<String, u32>);
+    fn Storage::tags(&self, tags: HashSet<String>) -> BTreeSet<String>;
+    fn Storage::load(&mut self, data: &[u8]) -> Vec<u8>;
+});
+"#;
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::Python).unwrap();
+    let rust_code = rustfmt_without_errors(py_code.rust_code);
+    println!("rust: {}", rust_code);
+    assert!(rust_code.contains(
+        "def find (& self , key : Option < & str >) -> cpython :: PyResult < Option < super :: py_storage :: Storage > >"
     ));
-    assert!(foreign_code.contains(
-        r#"
-class MyObjWrapper : public QObject {
-    Q_OBJECT
-"#
+    assert!(rust_code.contains(
+        "def pair (& self , p : (i32 , String)) -> cpython :: PyResult < (String , i32) >"
     ));
-    assert!(foreign_code.contains("Q_INVOKABLE static void f()"));
-    assert!(foreign_code.contains(
-        r#"//enum comment
-enum class MyEnum {
-A = 0,
-B = 1"#
+    assert!(rust_code.contains("def counts (& self) -> cpython :: PyResult < cpython :: PyDict >"));
+    assert!(rust_code.contains("def set_counts (& self , counts : cpython :: PyDict)"));
+    assert!(rust_code.contains(
+        "def tags (& self , tags : cpython :: PyObject) -> cpython :: PyResult < cpython :: PyObject >"
     ));
-    tmp_dir.close().unwrap();
+    assert!(rust_code.contains(
+        "def load (& self , data : cpython :: PyBytes) -> cpython :: PyResult < cpython :: PyBytes >"
+    ));
+    assert!(rust_code.contains("let mut data : & [u8] = data . data (py) ;"));
 }
 
-fn find_subsequence<T>(haystack: &[T], needle: &[T]) -> Option<usize>
-where
-    for<'a> &'a [T]: PartialEq,
-{
-    haystack
-        .windows(needle.len())
-        .position(|window| window == needle)
-}
+#[test]
+fn test_python_pyo3_binding() {
+    let _ = env_logger::try_init();
 
-#[derive(PartialEq, Debug, Clone, Copy)]
-enum ForeignLang {
-    Java,
-    Cpp,
+    let name = "python_pyo3_binding";
+    let src = r#"
+foreign_enum!(enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_class!(class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Arc<Mutex<Counter>>;
+    fn Counter::increment(&mut self) -> Result<i32, String>;
+    fn Counter::set_color(&mut self, color: Color);
+    fn Counter::merge(&mut self, other: &Counter);
+    fn Counter::limit(&self, max: Option<i32>) -> Option<i32>;
+    fn Counter::to_string(&self) -> String;
+});
+"#;
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::PythonPyO3).unwrap();
+    let rust_code = rustfmt_without_errors(py_code.rust_code);
+    println!("rust: {}", rust_code);
+    assert!(!rust_code.contains("cpython"));
+    assert!(rust_code.contains("#[pyo3::pymodule]"));
+    assert!(rust_code.contains("fn flapigen_test(m: &pyo3::Bound<'_, pyo3::types::PyModule>)"));
+    assert!(rust_code.contains("m.add_class::<py_counter::Counter>()?;"));
+    assert!(rust_code.contains("m.add_class::<py_color::Color>()?;"));
+    assert!(rust_code.contains("pub struct Counter {"));
+    assert!(rust_code.contains("rust_instance: std::sync::Arc<std::sync::Mutex<super::Counter>>,"));
+    assert!(rust_code.contains("#[classattr]"));
+    assert!(rust_code.contains("pyo3::PyErr::new::<pyo3::exceptions::PyValueError, _>"));
+    assert!(rust_code.contains("pyo3::PyErr::new::<super::py_error::Error, _>"));
+    assert!(rust_code.contains("#[new]"));
+    assert!(rust_code.contains("fn merge("));
+    assert!(rust_code.contains("other: pyo3::PyRef<super::py_counter::Counter>"));
+    assert!(
+        rust_code.contains("fn __repr__(&self, py: pyo3::Python<'_>) -> pyo3::PyResult<String>")
+    );
+}
+
+#[test]
+fn test_python_special_methods() {
+    let _ = env_logger::try_init();
+
+    let name = "python_special_methods";
+    let src = r#"
+foreign_class!(
+#[derive(Display, Debug, PartialEq, Hash)]
+class Point {
+    self_type Point;
+    constructor Point::new(x: i32, y: i32) -> Point;
+});
+
+foreign_class!(class Path {
+    self_type Path;
+    constructor Path::new() -> Path;
+    #[__len__]
+    fn Path::len(&self) -> u32;
+    #[__getitem__]
+    fn Path::get(&self, i: usize) -> Option<Point>;
+    #[__iter__]
+    fn Path::points(&self) -> PathIter;
+});
+
+foreign_class!(class PathIter {
+    self_type PathIter;
+    private constructor = empty -> PathIter;
+    #[__next__]
+    fn PathIter::next(&mut self) -> Option<Point>;
+});
+"#;
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::Python).unwrap();
+    let rust_code = rustfmt_without_errors(py_code.rust_code);
+    println!("rust: {}", rust_code);
+    assert!(rust_code.contains("def __str__ (& self) -> cpython :: PyResult < String >"));
+    assert!(rust_code.contains("def __repr__ (& self) -> cpython :: PyResult < String >"));
+    assert!(rust_code.contains("def __hash__ (& self) -> cpython :: PyResult < u64 >"));
+    assert!(rust_code.contains(
+        "def __richcmp__ (& self , other : super :: py_point :: Point , op : cpython :: CompareOp)"
+    ));
+    assert!(rust_code.contains("_ => return Ok (py . NotImplemented ()) ,"));
+    assert!(rust_code.contains("def __len__ (& self) -> cpython :: PyResult < usize >"));
+    assert!(rust_code.contains(") as usize"));
+    assert!(rust_code.contains(
+        "def __getitem__ (& self , i : usize) -> cpython :: PyResult < super :: py_point :: Point >"
+    ));
+    assert!(rust_code.contains("cpython :: PyErr :: new :: < cpython :: exc :: IndexError , _ >"));
+    assert!(rust_code.contains(
+        "def __iter__ (& self) -> cpython :: PyResult < super :: py_path_iter :: PathIter >"
+    ));
+    assert!(rust_code.contains(
+        "def __next__ (& self) -> cpython :: PyResult < Option < super :: py_point :: Point > >"
+    ));
+    assert!(rust_code.contains("def __iter__ (& self) -> cpython :: PyResult < PathIter >"));
+
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::PythonPyO3).unwrap();
+    let rust_code = rustfmt_without_errors(py_code.rust_code);
+    println!("rust: {}", rust_code);
+    assert!(rust_code.contains("fn __eq__("));
+    assert!(rust_code.contains("fn __ne__("));
+    assert!(!rust_code.contains("fn __lt__("));
+    assert!(rust_code.contains("fn __iter__(slf: pyo3::PyRef<'_, Self>) -> pyo3::PyRef<'_, Self>"));
+
+    // the same glue code is used for other languages, they ignore Python derives and attributes
+    let java_code = parse_code(name, Source::Str(src), ForeignLang::Java).unwrap();
+    assert!(java_code
+        .foreign_code
+        .contains("public final @NonNull PathIter points() {"));
+    assert!(!java_code.foreign_code.contains("__"));
+    let cpp_code = parse_code(name, Source::Str(src), ForeignLang::Cpp).unwrap();
+    assert!(cpp_code.foreign_code.contains("PathIter points() const noexcept;"));
+
+    // without attributes methods keep their names
+    let py_code = parse_code(
+        name,
+        Source::Str(
+            r#"
+foreign_class!(class Counter {
+    self_type Counter;
+    constructor Counter::new() -> Counter;
+    fn Counter::len(&self) -> u32;
+    fn Counter::get(&self, i: usize) -> Option<u32>;
+    fn Counter::next(&mut self) -> Option<u32>;
+});
+"#,
+        ),
+        ForeignLang::Python,
+    )
+    .unwrap();
+    let rust_code = rustfmt_without_errors(py_code.rust_code);
+    println!("rust: {}", rust_code);
+    assert!(rust_code.contains("def len (& self)"));
+    assert!(rust_code.contains("def next (& self)"));
+    assert!(!rust_code.contains("__len__"));
+    assert!(!rust_code.contains("__getitem__"));
+    assert!(!rust_code.contains("__next__"));
+    assert!(!rust_code.contains("__iter__"));
+
+    let result = panic::catch_unwind(|| {
+        parse_code(
+            name,
+            Source::Str(
+                r#"
+foreign_class!(
+#[derive(Debug)]
+class Point {
+    self_type Point;
+    constructor Point::new(x: i32, y: i32) -> Point;
+    fn Point::to_string(&self) -> String;
+});
+"#,
+            ),
+            ForeignLang::Python,
+        )
+        .unwrap()
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_python_release_gil() {
+    let _ = env_logger::try_init();
+
+    let name = "python_release_gil";
+    let src = r#"
+foreign_class!(
+#[derive(ReleaseGil)]
+class Worker {
+    self_type Worker;
+    constructor Worker::new() -> Arc<Mutex<Worker>>;
+    fn Worker::compute(&mut self, n: u64, label: String) -> u64;
+    fn Worker::compute_str(&mut self, text: &str) -> u64;
+});
+
+foreign_class!(class Math {
+    #[release_gil]
+    fn Math::sum(v: Vec<f64>) -> f64;
+});
+"#;
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::Python).unwrap();
+    let rust_code = rustfmt_without_errors(py_code.rust_code);
+    println!("rust: {}", rust_code);
+    assert!(rust_code.contains(
+        "let rust_instance = super :: py_worker :: rust_instance (self , py) ; \
+Ok (py . allow_threads (move || Worker :: compute ((& mut * rust_instance . lock () . unwrap ()) , n , label)))"
+    ));
+    assert!(rust_code.contains(
+        "Ok (Worker :: compute_str ((& mut * super :: py_worker :: rust_instance (self , py) . lock () . unwrap ()) , text))"
+    ));
+    assert!(rust_code.contains("py . allow_threads (move || Math :: sum (v))"));
+
+    // other languages ignore `release_gil`, so the same glue code can be used for them
+    let src = r#"
+foreign_class!(
+#[derive(ReleaseGil)]
+class Worker {
+    self_type Worker;
+    constructor Worker::new() -> Worker;
+    #[release_gil]
+    fn Worker::compute(&self, n: u64) -> u64;
+});
+"#;
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::Python).unwrap();
+    assert!(py_code.rust_code.contains("allow_threads"));
+    let java_code = parse_code(name, Source::Str(src), ForeignLang::Java).unwrap();
+    assert!(java_code
+        .foreign_code
+        .contains("public final long compute(long n) {"));
+    let cpp_code = parse_code(name, Source::Str(src), ForeignLang::Cpp).unwrap();
+    assert!(cpp_code
+        .foreign_code
+        .contains("uint64_t compute(uint64_t n) const noexcept;"));
+
+    let result = panic::catch_unwind(|| {
+        parse_code(
+            name,
+            Source::Str(
+                r#"
+foreign_class!(class Math {
+    #[release_gil]
+    fn Math::sum(v: &[f64]) -> f64;
+});
+"#,
+            ),
+            ForeignLang::Python,
+        )
+        .unwrap()
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_python_numeric_buffers() {
+    let _ = env_logger::try_init();
+
+    let name = "python_numeric_buffers";
+    let src = r#"
+foreign_class!(class Dsp {
+    fn Dsp::filter(samples: &[f32], taps: Vec<f64>) -> Vec<f32>;
+    fn Dsp::window() -> &'static [f64];
+    fn Dsp::count(v: Vec<usize>) -> usize;
+});
+"#;
+    let py_code = parse_code(name, Source::Str(src), ForeignLang::Python).unwrap();
+    let rust_code = rustfmt_without_errors(py_code.rust_code);
+    println!("rust: {}", rust_code);
+    assert!(rust_code.contains(
+        "samples : super :: SwigNumericSlice < f32 > , taps : super :: SwigNumericSlice < f64 >"
+    ));
+    assert!(!rust_code.contains("swig_numeric_vec_to_py (py ,"));
+    assert!(rust_code.contains("v : Vec < usize >"));
+
+    let tmp_dir = tempdir().expect("Can not create tmp directory");
+    let swig_gen = Generator::new(LanguageConfig::PythonConfig(
+        PythonConfig::new("flapigen_test".into())
+            .return_numeric_buffers(true)
+            .stubs_output_dir(tmp_dir.path().into()),
+    ))
+    .with_pointer_target_width(64);
+    let rust_code_path = tmp_dir.path().join("test.rs");
+    let rust_src_path = tmp_dir.path().join("src.rs");
+    fs::write(&rust_src_path, src).unwrap();
+    swig_gen.expand(name, rust_src_path, &rust_code_path);
+    let rust_code = rustfmt_without_errors(fs::read_to_string(rust_code_path).unwrap());
+    println!("rust: {}", rust_code);
+    assert!(rust_code.contains(
+        "Ok (swig_numeric_vec_to_py (py , Dsp :: filter (samples . as_slice () , taps . into_vec ())) ?)"
+    ));
+    assert!(rust_code.contains("Ok (swig_numeric_vec_to_py (py , Dsp :: window () . to_vec ()) ?)"));
+    let stub = fs::read_to_string(tmp_dir.path().join("flapigen_test.pyi")).unwrap();
+    println!("stub: {}", stub);
+    assert!(stub.contains("def window() -> memoryview: ..."));
+}
+
+#[test]
+fn test_python_stubs() {
+    let _ = env_logger::try_init();
+    let rust_src = r#"
+foreign_enum!(
+/// Colors
+enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_callback!(callback Observer {
+    self_type Observer;
+    onChange = Observer::on_change(&self, color: Color, names: Vec<String>) -> bool;
+});
+
+foreign_class!(
+/// Counter of things
+class Counter {
+    self_type Counter;
+    /// Create counter
+    constructor Counter::new(start: i32) -> Counter;
+    /// Increment value
+    fn Counter::increment(&mut self) -> Result<i32, String>;
+    fn Counter::limit(&self, max: Option<f64>) -> Option<(u8, String)>;
+    fn Counter::merge(&mut self, other: &Counter, values: &[i64]);
+    fn Counter::subscribe(&mut self, observer: Box<dyn Observer>);
+    fn Counter::to_string(&self) -> String;
+    fn Counter::create(name: &str) -> Counter;
+});
+"#;
+    let tmp_dir = tempdir().expect("Can not create tmp directory");
+    let swig_gen = Generator::new(LanguageConfig::PythonConfig(
+        PythonConfig::new("flapigen_test".into()).stubs_output_dir(tmp_dir.path().into()),
+    ))
+    .with_pointer_target_width(64);
+    let rust_code_path = tmp_dir.path().join("test.rs");
+    let rust_src_path = tmp_dir.path().join("src.rs");
+    fs::write(&rust_src_path, rust_src).unwrap();
+    swig_gen.expand("python_stubs", rust_src_path, &rust_code_path);
+
+    let stub = fs::read_to_string(tmp_dir.path().join("flapigen_test.pyi")).unwrap();
+    println!("stub: {}", stub);
+    assert!(stub.contains("from typing import List, Optional, Protocol, Sequence, Tuple, Union\n"));
+    assert!(stub.contains("from typing_extensions import Buffer\n"));
+    assert!(stub.contains("class Error(Exception): ...\n"));
+    assert!(stub.contains(
+        r#"class Color:
+    """Colors"""
+    Red: int
+    Green: int
+"#
+    ));
+    assert!(stub.contains(
+        r#"class Observer(Protocol):
+    def onChange(self, color: int, names: List[str]) -> bool: ...
+"#
+    ));
+    assert!(stub.contains(
+        r#"class Counter:
+    """Counter of things
+
+    Create counter
+    """
+    def __new__(cls, start: int) -> Counter: ...
+    def increment(self) -> int:
+        """Increment value"""
+        ...
+    def limit(self, max: Optional[float]) -> Optional[Tuple[int, str]]: ...
+    def merge(self, other: Counter, values: Union[Buffer, Sequence[int]]) -> None: ...
+    def subscribe(self, observer: Observer) -> None: ...
+    def __repr__(self) -> str: ...
+    @staticmethod
+    def create(name: str) -> Counter: ...
+"#
+    ));
+}
+
+#[test]
+fn test_csharp_option() {
+    let _ = env_logger::try_init();
+
+    let name = "csharp_option";
+    let src = r#"
+foreign_enum!(
+enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_class!(
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::find(&self, x: Option<i32>) -> Option<f64>;
+    fn Counter::flag(&self) -> Option<bool>;
+    fn Counter::paint(&self, c: Option<Color>) -> Option<Color>;
+    fn Counter::label(&self, s: Option<String>) -> Option<String>;
+    fn Counter::child(&self, c: Option<Counter>) -> Option<Counter>;
+});
+"#;
+    let cs_code = parse_code(name, Source::Str(src), ForeignLang::CSharp).unwrap();
+    let rust_code = rustfmt_without_errors(cs_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("c#: {}", cs_code.foreign_code);
+    assert!(rust_code.contains(
+        "pub extern \"C\" fn Counter_find(this: *mut Counter, x: CRustOption<i32>) -> CRustOption<f64> {"
+    ));
+    assert!(cs_code.foreign_code.contains(
+        r#"        public double? Find(int? x)
+        {
+            RustOptioni32 x_conv = default(RustOptioni32);
+            if (x.HasValue) {
+                x_conv.val = x.Value;
+                x_conv.is_some = 1;
+            }"#
+    ));
+    assert!(cs_code.foreign_code.contains("internal struct RustOptioni32"));
+    assert!(cs_code
+        .foreign_code
+        .contains("return (ret.is_some != 0) ? (bool?)(ret.val != 0) : null;"));
+    assert!(cs_code.foreign_code.contains("public Color? Paint(Color? c)"));
+    assert!(cs_code.foreign_code.contains("public string Label(string s)"));
+    assert!(cs_code
+        .foreign_code
+        .contains("return (ret.is_some != 0) ? ret.val.ToManaged() : null;"));
+    assert!(cs_code.foreign_code.contains(
+        "IntPtr ret = Counter_child(this.handle, (c != null) ? c.SwigRelease() : IntPtr.Zero);"
+    ));
+}
+
+#[test]
+fn test_csharp_vec() {
+    let _ = env_logger::try_init();
+
+    let name = "csharp_vec";
+    let src = r#"
+foreign_class!(
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::values(&self, x: Vec<u32>) -> Vec<f64>;
+});
+"#;
+    let cs_code = parse_code(name, Source::Str(src), ForeignLang::CSharp).unwrap();
+    let rust_code = rustfmt_without_errors(cs_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("c#: {}", cs_code.foreign_code);
+    assert!(rust_code.contains("let mut x: Vec<u32> = x.into_vec();"));
+    assert!(rust_code.contains("let mut ret: CRustVec = CRustVec::from_vec(ret);"));
+    assert!(cs_code.foreign_code.contains("public double[] Values(uint[] x)"));
+    assert!(cs_code
+        .foreign_code
+        .contains("RustVec ret = Counter_values(this.handle, RustVec.FromManaged(x));"));
+    assert!(cs_code.foreign_code.contains("return ret.ToManaged<double>();"));
+}
+
+#[test]
+fn test_csharp_result() {
+    let _ = env_logger::try_init();
+
+    let name = "csharp_result";
+    let src = r#"
+foreign_enum!(
+enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_class!(
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::check(&self) -> Result<(), String>;
+    fn Counter::parse(s: &str) -> Result<i32, String>;
+    fn Counter::parse_color(s: &str) -> Result<Color, String>;
+    fn Counter::parse_name(s: &str) -> Result<String, String>;
+    fn Counter::open(s: &str) -> Result<Counter, String>;
+});
+"#;
+    let cs_code = parse_code(name, Source::Str(src), ForeignLang::CSharp).unwrap();
+    let rust_code = rustfmt_without_errors(cs_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("c#: {}", cs_code.foreign_code);
+    assert!(rust_code.contains(
+        "pub extern \"C\" fn Counter_parse(s: internal_aliases::CSharpUtf16Str) -> CRustResult<i32> {"
+    ));
+    assert!(cs_code.foreign_code.contains(
+        r#"        public void Check()
+        {
+            RustVoidResult ret = Counter_check(this.handle);
+            ret.Check();
+        }"#
+    ));
+    assert!(cs_code.foreign_code.contains(
+        "return (ret.is_ok != 0) ? ret.ok : throw new RustException(ret.err.ToManaged());"
+    ));
+    assert!(cs_code.foreign_code.contains(
+        "return (ret.is_ok != 0) ? (Color)ret.ok : throw new RustException(ret.err.ToManaged());"
+    ));
+    assert!(cs_code.foreign_code.contains(
+        "return (ret.is_ok != 0) ? ret.ok.ToManaged() : throw new RustException(ret.err.ToManaged());"
+    ));
+    assert!(cs_code.foreign_code.contains(
+        "return (ret.is_ok != 0) ? new Counter(new SafeCounterHandle(ret.ok, true)) : throw new RustException(ret.err.ToManaged());"
+    ));
+    assert!(cs_code
+        .foreign_code
+        .contains("public class RustException : Exception"));
+}
+
+#[test]
+fn test_csharp_binding() {
+    let _ = env_logger::try_init();
+
+    let name = "csharp_binding";
+    let src = r#"
+foreign_enum!(
+/// Colors
+enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_callback!(callback Observer {
+    self_type Observer;
+    onChange = Observer::on_change(&self, color: Color, count: i32) -> bool;
+});
+
+foreign_class!(
+/// Counter of things
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::set_color(&mut self, color: Color);
+    fn Counter::merge(&mut self, other: &Counter);
+    fn Counter::subscribe(&mut self, observer: Box<dyn Observer>);
+    fn Counter::name(&self) -> String;
+    fn Counter::rename(&mut self, name: &str);
+    fn Counter::create(name: String) -> Counter;
+});
+"#;
+    let cs_code = parse_code(name, Source::Str(src), ForeignLang::CSharp).unwrap();
+    let rust_code = rustfmt_without_errors(cs_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("c#: {}", cs_code.foreign_code);
+    assert!(rust_code.contains(
+        "pub extern \"C\" fn Counter_new(start: i32) -> *const ::std::os::raw::c_void {"
+    ));
+    assert!(rust_code.contains("pub extern \"C\" fn Counter_delete(this: *mut Counter) {"));
+    assert!(rust_code.contains(
+        "pub extern \"C\" fn Counter_subscribe(this: *mut Counter, observer: C_Observer) -> () {"
+    ));
+    assert!(rust_code.contains("let mut name: String = swig_utf16_str_to_string(name);"));
+    assert!(rust_code.contains("impl SwigForeignEnum for Color {"));
+
+    let cs = &cs_code.foreign_code;
+    assert!(cs.contains("internal sealed class SafeCounterHandle : SafeHandle"));
+    assert!(cs.contains("public class Counter : IDisposable"));
+    assert!(cs.contains(
+        r#"        public Counter(int start)
+        {
+            this.handle = Counter_new(start);
+        }"#
+    ));
+    assert!(cs.contains("public void SetColor(Color color)"));
+    assert!(cs.contains("Counter_merge(this.handle, other.handle);"));
+    assert!(cs.contains("Counter_subscribe(this.handle, C_Observer.Wrap(observer));"));
+    assert!(cs.contains("return ret.ToManaged();"));
+    assert!(cs.contains("public static Counter Create(string name)"));
+    assert!(cs.contains("return new Counter(new SafeCounterHandle(ret, true));"));
+    assert!(cs.contains(
+        r#"[DllImport("flapigen_test", CallingConvention = CallingConvention.Cdecl, CharSet = CharSet.Unicode)]
+        private static extern void Counter_rename(SafeCounterHandle self, string name);"#
+    ));
+    assert!(cs.contains(
+        r#"    public interface Observer
+    {
+        bool OnChange(Color color, int count);
+    }"#
+    ));
+    assert!(cs.contains(
+        "internal delegate byte OnChangeDelegate(uint color, int count, IntPtr opaque);"
+    ));
+    assert!(cs.contains(
+        r#"    public enum Color : uint
+    {
+        Red = 0,
+        Green = 1,
+    }"#
+    ));
+}
+
+#[test]
+fn test_swift_binding() {
+    let _ = env_logger::try_init();
+
+    let name = "swift_binding";
+    let src = r#"
+foreign_enum!(
+/// Colors
+enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_callback!(callback Observer {
+    self_type Observer;
+    onChange = Observer::on_change(&self, color: Color, count: i32) -> bool;
+    onName = Observer::on_name(&self, name: &str);
+});
+
+foreign_class!(
+/// Counter of things
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::set_color(&mut self, color: Color);
+    fn Counter::merge(&mut self, other: &Counter);
+    fn Counter::subscribe(&mut self, observer: Box<dyn Observer>);
+    fn Counter::name(&self) -> String;
+    fn Counter::rename(&mut self, name: &str);
+    fn Counter::nick(&self) -> Option<String>;
+    fn Counter::history(&self) -> Vec<i32>;
+    fn Counter::add_all(&mut self, values: &[i32]);
+    fn Counter::create(name: String) -> Counter;
+    fn Counter::split(&self) -> Vec<Counter>;
+});
+"#;
+    let swift_code = parse_code(name, Source::Str(src), ForeignLang::Swift).unwrap();
+    let rust_code = rustfmt_without_errors(swift_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("swift: {}", swift_code.foreign_code);
+    assert!(rust_code.contains("pub extern \"C\" fn Counter_delete(this: *mut Counter) {"));
+
+    let swift = &swift_code.foreign_code;
+    assert!(swift.contains("import RustFFI"));
+    assert!(swift.contains(
+        r#"/// Counter of things
+public final class Counter {"#
+    ));
+    assert!(swift.contains(
+        r#"    deinit {
+        if owned {
+            Counter_delete(cPtr)
+        }
+    }"#
+    ));
+    assert!(swift.contains(
+        r#"    public convenience init(start: Int32) {
+        let ptr = Counter_new(start)
+        self.init(cPtr: ptr, owned: true)
+    }"#
+    ));
+    assert!(swift.contains("public func increment() -> Int32 {"));
+    assert!(swift.contains("Counter_set_color(cPtr, color.rawValue)"));
+    assert!(swift.contains("Counter_merge(cPtr, other.cPtr)"));
+    assert!(swift.contains(
+        r#"        var observer_c = swigObserverToC(observer)
+        withUnsafePointer(to: &observer_c) { observer_ptr in
+            Counter_subscribe(cPtr, observer_ptr)
+        }"#
+    ));
+    assert!(swift.contains("return swigTakeString(ret)"));
+    assert!(swift.contains("name.withCString { name_ptr in"));
+    assert!(swift.contains("public func nick() -> String? {"));
+    assert!(swift.contains("public func history() -> [Int32] {"));
+    assert!(swift.contains("values.withUnsafeBufferPointer { values_ptr in"));
+    assert!(swift.contains("public static func create(name: String) -> Counter {"));
+    assert!(swift.contains("return Counter(cPtr: ret, owned: true)"));
+    assert!(swift.contains("public func split() -> [Counter] {"));
+    assert!(swift.contains(
+        r#"public protocol Observer: AnyObject {
+    func onChange(color: Color, count: Int32) -> Bool
+    func onName(name: String)
+}"#
+    ));
+    assert!(swift.contains("internal func swigObserverToC(_ obj: Observer) -> C_Observer {"));
+    assert!(swift.contains("let ret = obj.onChange(color: Color(rawValue: a0)!, count: a1)"));
+    assert!(swift.contains(
+        r#"/// Colors
+public enum Color: UInt32 {
+    case red = 0
+    case green = 1
+}"#
+    ));
+    assert!(swift.contains("internal func swigStringFromView(_ view: CRustStrView) -> String {"));
+}
+
+#[test]
+fn test_swift_result() {
+    let _ = env_logger::try_init();
+
+    let name = "swift_result";
+    let src = r#"
+foreign_class!(
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::check(&self) -> Result<(), String>;
+    fn Counter::parse(s: &str) -> Result<i32, String>;
+    fn Counter::load(name: &str) -> Result<Counter, String>;
+});
+"#;
+    let swift_code = parse_code(name, Source::Str(src), ForeignLang::Swift).unwrap();
+    println!("swift: {}", swift_code.foreign_code);
+
+    let swift = &swift_code.foreign_code;
+    assert!(swift.contains(
+        r#"    public func check() throws {
+        let ret = Counter_check(cPtr)
+        try swigUnwrapResult(ret.is_ok, { () }, { ret.data.err })
+    }"#
+    ));
+    assert!(swift.contains("public static func parse(s: String) throws -> Int32 {"));
+    assert!(swift.contains("return try swigUnwrapResult(ret.is_ok, { ret.data.ok }, { ret.data.err })"));
+    assert!(swift.contains("public static func load(name: String) throws -> Counter {"));
+    assert!(swift.contains(
+        "return try swigUnwrapResult(ret.is_ok, { Counter(cPtr: ret.data.ok, owned: true) }, { ret.data.err })"
+    ));
+    assert!(swift.contains("public struct RustError: Error {"));
+    assert!(swift.contains("throw RustError(message: swigTakeString(err()))"));
+}
+
+#[test]
+fn test_go_binding() {
+    let _ = env_logger::try_init();
+
+    let name = "go_binding";
+    let src = r#"
+foreign_enum!(
+/// Colors
+enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_callback!(callback Observer {
+    self_type Observer;
+    onChange = Observer::on_change(&self, color: Color, count: i32) -> bool;
+    onName = Observer::on_name(&self, name: &str);
+});
+
+foreign_class!(
+/// Counter of things
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::set_color(&mut self, color: Color);
+    fn Counter::merge(&mut self, other: &Counter);
+    fn Counter::subscribe(&mut self, observer: Box<dyn Observer>);
+    fn Counter::name(&self) -> String;
+    fn Counter::rename(&mut self, name: &str);
+    fn Counter::nick(&self) -> Option<String>;
+    fn Counter::history(&self) -> Vec<i32>;
+    fn Counter::add_all(&mut self, values: &[i32]);
+    fn Counter::parse(text: &str) -> Result<Counter, String>;
+    fn Counter::check(&self) -> Result<(), String>;
+    fn Counter::split(&self) -> Vec<Counter>;
+});
+"#;
+    let go_code = parse_code(name, Source::Str(src), ForeignLang::Go).unwrap();
+    let rust_code = rustfmt_without_errors(go_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("go: {}", go_code.foreign_code);
+    assert!(rust_code.contains("pub extern \"C\" fn Counter_delete(this: *mut Counter) {"));
+
+    let go = &go_code.foreign_code;
+    assert!(go.contains("package rustlib"));
+    assert!(go.contains("#cgo LDFLAGS: -lflapigen_test"));
+    assert!(go.contains(
+        r#"// Counter of things
+type Counter struct {
+	ptr   *C.CounterOpaque
+	owned bool
+}"#
+    ));
+    assert!(go.contains("runtime.SetFinalizer(obj, (*Counter).Close)"));
+    assert!(go.contains(
+        r#"func NewCounter(start int32) *Counter {
+	ret := C.Counter_new(C.int32_t(start))
+	return wrapCounter(ret, true)
+}"#
+    ));
+    assert!(go.contains(
+        r#"func (x *Counter) Increment() int32 {
+	ret := C.Counter_increment(x.ptr)
+	runtime.KeepAlive(x)
+	return int32(ret)
+}"#
+    ));
+    assert!(go.contains("C.Counter_set_color(x.ptr, C.uint32_t(color))"));
+    assert!(go.contains("C.Counter_merge(x.ptr, other.ptr)"));
+    assert!(go.contains("observer_c := goObserverToC(observer)"));
+    assert!(go.contains("return goTakeString(ret)"));
+    assert!(go.contains("func (x *Counter) Nick() *string {"));
+    assert!(go.contains("func (x *Counter) History() []int32 {"));
+    assert!(go.contains("unsafe.SliceData(values)"));
+    assert!(go.contains("func CounterParse(text string) (*Counter, error) {"));
+    assert!(go.contains("return *new(*Counter), errors.New(goTakeString(ret_err))"));
+    assert!(go.contains("func (x *Counter) Check() error {"));
+    assert!(go.contains("func (x *Counter) Split() []*Counter {"));
+    assert!(go.contains(
+        r#"type Observer interface {
+	OnChange(color Color, count int32) bool
+	OnName(name string)
+}"#
+    ));
+    assert!(go.contains("extern char goObserverOnChange(uint32_t a0, int32_t a1, void *opaque);"));
+    assert!(go.contains("//export goObserverOnChange"));
+    assert!(go.contains("obj := cgo.Handle(uintptr(opaque)).Value().(Observer)"));
+    assert!(go.contains("onChange: (*[0]byte)(C.goObserverOnChange),"));
+    assert!(go.contains(
+        r#"// Colors
+type Color uint32
+
+const (
+	ColorRed Color = 0
+	ColorGreen Color = 1
+)"#
+    ));
+}
+
+#[test]
+fn test_node_binding() {
+    let _ = env_logger::try_init();
+
+    let name = "node_binding";
+    let src = r#"
+foreign_enum!(
+/// Colors
+enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_callback!(callback Observer {
+    self_type Observer;
+    onChange = Observer::on_change(&self, color: Color, count: i32) -> bool;
+    onName = Observer::on_name(&self, name: &str);
+});
+
+foreign_class!(
+/// Counter of things
+#[derive(Clone)]
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::set_color(&mut self, color: Color);
+    fn Counter::merge(&mut self, other: &Counter);
+    fn Counter::subscribe(&mut self, observer: Box<dyn Observer>);
+    fn Counter::name(&self) -> String;
+    fn Counter::rename(&mut self, name: &str);
+    fn Counter::nick(&self) -> Option<String>;
+    fn Counter::history(&self) -> Vec<i32>;
+    fn Counter::add_all(&mut self, values: &[i32]);
+    fn Counter::parse(text: &str) -> Result<Counter, String>;
+    fn Counter::check(&self) -> Result<(), String>;
+    fn Counter::split(&self) -> Vec<Counter>;
+    fn Counter::initial(&self) -> char;
+});
+"#;
+    let node_code = parse_code(name, Source::Str(src), ForeignLang::Node).unwrap();
+    let rust_code = rustfmt_without_errors(node_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("d.ts: {}", node_code.foreign_code);
+    assert!(rust_code.contains("pub unsafe extern \"C\" fn napi_register_module_v1("));
+    assert!(rust_code.contains("node_counter::define(swig_env, swig_exports)?;"));
+    assert!(rust_code.contains("node_color::define(swig_env, swig_exports)?;"));
+    assert!(rust_code.contains("impl SwigNodeClass for JsClass {"));
+    assert!(rust_code.contains("let name: &str = &name;"));
+    assert!(rust_code.contains(
+        "let other: &Counter = swig_node_unwrap::<node_counter::JsClass>(swig_env, other)?;"
+    ));
+    assert!(rust_code.contains("impl Observer for JsCallback {"));
+    assert!(rust_code.contains("swig_node_call_method(swig_env, swig_object, \"onChange\""));
+    assert!(rust_code.contains("SwigNodeCallback::new(swig_env, observer, \"Observer\")?"));
+    assert!(rust_code
+        .contains("swig_napi_check(swig_env, napi_sys::napi_object_freeze(swig_env, object))?;"));
+    assert!(rust_code
+        .contains("Err(swig_err) => return Err(SwigNodeError::Message(swig_err.to_string()))"));
+
+    let dts = &node_code.foreign_code;
+    assert!(dts.contains(
+        r#"/**
+ * Counter of things
+ */
+export declare class Counter {
+    constructor(start: number);
+    increment(): number;
+    set_color(color: Color): void;
+    merge(other: Counter): void;
+    subscribe(observer: Observer): void;
+    name(): string;
+    rename(name: string): void;
+    nick(): string | null;
+    history(): number[];
+    add_all(values: number[]): void;
+    static parse(text: string): Counter;
+    check(): void;
+    split(): Counter[];
+    initial(): string;
+}"#
+    ));
+    assert!(dts.contains(
+        r#"export interface Observer {
+    onChange(color: Color, count: number): boolean;
+    onName(name: string): void;
+}"#
+    ));
+    assert!(dts.contains(
+        r#"/**
+ * Colors
+ */
+export declare enum Color {
+    Red = 0,
+    Green = 1,
+}"#
+    ));
+}
+
+#[test]
+fn test_lua_binding() {
+    let _ = env_logger::try_init();
+
+    let name = "lua_binding";
+    let src = r#"
+foreign_enum!(
+/// Colors
+enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_callback!(callback Observer {
+    self_type Observer;
+    onChange = Observer::on_change(&self, color: Color, count: i32) -> bool;
+    onName = Observer::on_name(&self, name: &str);
+});
+
+foreign_class!(
+/// Counter of things
+#[derive(Clone)]
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::set_color(&mut self, color: Color);
+    fn Counter::merge(&mut self, other: &Counter);
+    fn Counter::subscribe(&mut self, observer: Box<dyn Observer>);
+    fn Counter::name(&self) -> String;
+    fn Counter::rename(&mut self, name: &str);
+    fn Counter::nick(&self) -> Option<String>;
+    fn Counter::set_nick(&mut self, nick: Option<String>);
+    fn Counter::history(&self) -> Vec<i32>;
+    fn Counter::add_all(&mut self, values: &[i32]);
+    fn Counter::labels(&self) -> HashMap<String, f64>;
+    fn Counter::set_labels(&mut self, labels: HashMap<String, f64>);
+    fn Counter::parse(text: &str) -> Result<Counter, String>;
+    fn Counter::check(&self) -> Result<(), String>;
+    fn Counter::split(&self) -> Vec<Counter>;
+    fn Counter::initial(&self) -> char;
+});
+"#;
+    let lua_code = parse_code(name, Source::Str(src), ForeignLang::Lua).unwrap();
+    let rust_code = rustfmt_without_errors(lua_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("d.lua: {}", lua_code.foreign_code);
+    assert!(rust_code.contains("pub unsafe extern \"C\" fn luaopen_flapigen_test("));
+    assert!(rust_code.contains("lua_counter::define(swig_lua)?;"));
+    assert!(rust_code.contains("lua_color::define(swig_lua)?;"));
+    assert!(rust_code.contains("impl SwigLuaClass for LuaClass {"));
+    assert!(rust_code.contains("const METATABLE: &'static str = \"flapigen_test.Counter\\0\";"));
+    assert!(rust_code.contains("swig_lua_define_metatable::<LuaClass>(swig_lua);"));
+    assert!(rust_code.contains("swig_lua_set_function(swig_lua, \"new\\0\", lua_static_new);"));
+    assert!(
+        rust_code.contains("let this: &mut Counter = swig_lua_unwrap::<LuaClass>(swig_lua, 1)?;")
+    );
+    assert!(rust_code.contains(
+        "let other: &Counter = swig_lua_unwrap::<lua_counter::LuaClass>(swig_lua, other)?;"
+    ));
+    assert!(rust_code.contains("let nick: Option<String> = if swig_lua_is_nil(swig_lua, nick) {"));
+    assert!(rust_code.contains("swig_lua_table_elements(swig_lua, values, |values|"));
+    assert!(rust_code.contains("swig_lua_sys::lua_rawset(swig_lua, -3);"));
+    assert!(rust_code.contains("impl Observer for LuaCallback {"));
+    assert!(rust_code.contains("SwigLuaCallback::new(swig_lua, observer, \"Observer\")?"));
+    assert!(rust_code.contains("Err(swig_err) => return Err(SwigLuaError(swig_err.to_string())),"));
+
+    let annotations = &lua_code.foreign_code;
+    assert!(annotations.contains(
+        r#"--- Counter of things
+---@class Counter
+local Counter = {}
+
+---@param start integer
+---@return Counter
+function Counter.new(start) end
+
+---@return integer
+function Counter:increment() end
+"#
+    ));
+    assert!(annotations.contains(
+        r#"---@param nick string?
+function Counter:set_nick(nick) end
+
+---@return integer[]
+function Counter:history() end
+"#
+    ));
+    assert!(annotations.contains(
+        r#"---@return table<string, number>
+function Counter:labels() end
+"#
+    ));
+    assert!(annotations.contains(
+        r#"---@class Observer
+---@field onChange fun(self: Observer, color: Color, count: integer): boolean
+---@field onName fun(self: Observer, name: string)
+"#
+    ));
+    assert!(annotations.contains(
+        r#"--- Colors
+---@enum Color
+local Color = {
+    Red = 0,
+    Green = 1,
+}
+"#
+    ));
+}
+
+#[test]
+fn test_ruby_binding() {
+    let _ = env_logger::try_init();
+
+    let name = "ruby_binding";
+    let src = r#"
+foreign_enum!(
+/// Colors
+enum Color {
+    Red = Color::Red,
+    DarkGreen = Color::DarkGreen,
+});
+
+foreign_callback!(callback Observer {
+    self_type Observer;
+    on_change = Observer::on_change(&self, color: Color, count: i32) -> bool;
+    on_name = Observer::on_name(&self, name: &str);
+});
+
+foreign_class!(
+/// Counter of things
+#[derive(Clone)]
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::set_color(&mut self, color: Color);
+    fn Counter::color(&self) -> Color;
+    fn Counter::merge(&mut self, other: &Counter);
+    fn Counter::subscribe(&mut self, observer: Box<dyn Observer>);
+    fn Counter::name(&self) -> String;
+    fn Counter::rename(&mut self, name: &str);
+    fn Counter::nick(&self) -> Option<String>;
+    fn Counter::set_nick(&mut self, nick: Option<String>);
+    fn Counter::history(&self) -> Vec<i32>;
+    fn Counter::add_all(&mut self, values: &[i32]);
+    fn Counter::labels(&self) -> HashMap<String, f64>;
+    fn Counter::set_labels(&mut self, labels: HashMap<String, f64>);
+    fn Counter::parse(text: &str) -> Result<Counter, String>;
+    fn Counter::check(&self) -> Result<(), String>;
+    fn Counter::split(&self) -> Vec<Counter>;
+    fn Counter::initial(&self) -> char;
+    private fn Counter::reset(&mut self);
+});
+"#;
+    let ruby_code = parse_code(name, Source::Str(src), ForeignLang::Ruby).unwrap();
+    let rust_code = rustfmt_without_errors(ruby_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("rbs: {}", ruby_code.foreign_code);
+    assert!(rust_code.contains("pub unsafe extern \"C\" fn Init_flapigen_test() {"));
+    assert!(rust_code.contains("let swig_module = swig_ruby_define_module(\"FlapigenTest\\0\");"));
+    assert!(rust_code.contains("ruby_counter::define(swig_module)?;"));
+    assert!(rust_code.contains("ruby_color::define(swig_module)?;"));
+    assert!(rust_code.contains("impl SwigRubyClass for RubyClass {"));
+    assert!(rust_code.contains(
+        "swig_ruby_define_singleton_method(swig_class, \"new\\0\", ruby_static_new, false);"
+    ));
+    assert!(
+        rust_code.contains("swig_ruby_define_method(swig_class, \"reset\\0\", ruby_reset, true);")
+    );
+    assert!(
+        rust_code.contains("let this: &mut Counter = swig_ruby_unwrap::<RubyClass>(swig_self)?;")
+    );
+    assert!(rust_code
+        .contains("let other: &Counter = swig_ruby_unwrap::<ruby_counter::RubyClass>(other)?;"));
+    assert!(rust_code.contains("let color: Color = ruby_color::from_ruby(color)?;"));
+    assert!(rust_code.contains("\"Red\" | \"red\" => Ok(Color::Red),"));
+    assert!(rust_code.contains("\"DarkGreen\" | \"dark_green\" => Ok(Color::DarkGreen),"));
+    assert!(rust_code.contains("let nick: Option<String> = if swig_ruby_is_nil(nick) {"));
+    assert!(rust_code.contains("swig_ruby_array_elements(values, |values|"));
+    assert!(rust_code.contains("rb_sys::rb_hash_aset(swig_hash, swig_key, swig_value);"));
+    assert!(rust_code.contains("impl Observer for RubyCallback {"));
+    assert!(rust_code.contains("SwigRubyCallback::new(observer, \"Observer\")?"));
+    assert!(rust_code
+        .contains("Err(swig_err) => return Err(SwigRubyError::Message(swig_err.to_string())),"));
+
+    let rbs = &ruby_code.foreign_code;
+    assert!(rbs.contains(
+        r#"module FlapigenTest
+  class Error < StandardError
+  end
+"#
+    ));
+    assert!(rbs.contains(
+        r#"  # Counter of things
+  class Counter
+    def self.new: (Integer start) -> Counter
+    def increment: () -> Integer
+    def set_color: (Integer | Symbol color) -> void
+    def color: () -> Integer
+"#
+    ));
+    assert!(rbs.contains(
+        r#"    def nick: () -> String?
+    def set_nick: (String? nick) -> void
+    def history: () -> Array[Integer]
+    def add_all: (Array[Integer] values) -> void
+    def labels: () -> Hash[String, Float]
+"#
+    ));
+    assert!(rbs.contains("    def subscribe: (_Observer observer) -> void\n"));
+    assert!(rbs.contains("    private def reset: () -> void\n"));
+    assert!(rbs.contains(
+        r#"  interface _Observer
+    def on_change: (Integer color, Integer count) -> bool
+    def on_name: (String name) -> void
+  end
+"#
+    ));
+    assert!(rbs.contains(
+        r#"  # Colors
+  module Color
+    Red: Integer
+    DarkGreen: Integer
+  end
+"#
+    ));
+}
+
+#[test]
+fn test_wasm_binding() {
+    let _ = env_logger::try_init();
+
+    let name = "wasm_binding";
+    let src = r#"
+foreign_enum!(
+/// Colors
+enum Color {
+    Red = Color::Red,
+    DarkGreen = Color::DarkGreen,
+});
+
+foreign_callback!(callback Observer {
+    self_type Observer;
+    onChange = Observer::on_change(&self, color: Color, count: i32) -> bool;
+    onName = Observer::on_name(&self, name: &str);
+    load = Observer::load(&self, key: String) -> Result<Option<String>, String>;
+});
+
+foreign_class!(
+/// Counter of things
+#[derive(Clone)]
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    constructor Counter::with_name(name: &str) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::set_color(&mut self, color: Color);
+    fn Counter::color(&self) -> Color;
+    fn Counter::merge(&mut self, other: &Counter);
+    fn Counter::subscribe(&mut self, observer: Box<dyn Observer>);
+    fn Counter::name(&self) -> &str;
+    fn Counter::rename(&mut self, name: &str);
+    fn Counter::nick(&self) -> Option<String>;
+    fn Counter::set_nick(&mut self, nick: Option<&str>);
+    fn Counter::history(&self) -> Vec<i32>;
+    fn Counter::add_all(&mut self, values: &[i32]);
+    fn Counter::tags(&self) -> Vec<String>;
+    fn Counter::set_tags(&mut self, tags: &[String]);
+    fn Counter::total(&self) -> u64;
+    fn Counter::updated_at(&self) -> SystemTime;
+    fn Counter::parse(text: &str) -> Result<Counter, String>;
+    fn Counter::check(&self) -> Result<(), String>;
+    fn Counter::split(&self) -> Vec<Counter>;
+    fn Counter::parent(&self) -> Option<Counter>;
+    fn Counter::initial(&self) -> char;
+    private fn Counter::reset(&mut self);
+});
+
+foreign_class!(class Session {
+    self_type Session;
+    constructor Session::new() -> Rc<RefCell<Session>>;
+    fn Session::counter(&self) -> Counter;
+    fn Session::close(&mut self);
+});
+"#;
+    let wasm_code = parse_code(name, Source::Str(src), ForeignLang::Wasm).unwrap();
+    let rust_code = rustfmt_without_errors(wasm_code.rust_code);
+    println!("rust: {}", rust_code);
+    assert!(wasm_code.foreign_code.is_empty());
+    assert!(
+        rust_code.contains("# [wasm_bindgen (js_name = Counter)]\n    pub struct WasmCounter {")
+    );
+    assert!(rust_code.contains("# [wasm_bindgen (js_class = Counter)]\n    impl WasmCounter {"));
+    assert!(rust_code.contains(
+        "#[wasm_bindgen(constructor)]\n        pub fn swig_static_new(start: i32) -> WasmCounter {"
+    ));
+    assert!(rust_code.contains(
+        "# [wasm_bindgen (js_name = with_name)]\n        pub fn swig_static_with_name(name: &str) -> WasmCounter {"
+    ));
+    assert!(rust_code.contains("pub fn swig_increment(&mut self) -> i32 {"));
+    assert!(!rust_code.contains("fn swig_reset("));
+    assert!(rust_code.contains("let color: Color = color.into();"));
+    assert!(rust_code.contains("pub fn swig_merge(&mut self, other: &wasm_counter::WasmCounter) {"));
+    assert!(rust_code.contains(
+        "let observer: Box<dyn Observer> = Box::new(wasm_observer::WasmCallback(observer));"
+    ));
+    assert!(rust_code.contains("pub fn swig_name(&self) -> String {"));
+    assert!(rust_code.contains("let nick: Option<&str> = nick.as_deref();"));
+    assert!(rust_code.contains("pub fn swig_add_all(&mut self, values: &[i32]) {"));
+    assert!(rust_code.contains("pub fn swig_set_tags(&mut self, tags: Vec<String>) {"));
+    assert!(rust_code.contains(
+        "pub fn swig_static_parse(\n            text: &str,\n        ) -> Result<wasm_counter::WasmCounter, wasm_bindgen::JsError> {"
+    ));
+    assert!(rust_code.contains(
+        "Err(swig_err) => return Err(wasm_bindgen::JsError::new(&swig_err.to_string())),"
+    ));
+    assert!(rust_code.contains("pub fn swig_split(&self) -> Vec<wasm_bindgen::JsValue> {"));
+    assert!(rust_code.contains("pub fn swig_parent(&self) -> Option<wasm_counter::WasmCounter> {"));
+    assert!(rust_code.contains("pub(crate) swig_inner: Rc<RefCell<Session>>,"));
+    assert!(rust_code.contains("let mut this: RefMut<Session> = this.borrow_mut();"));
+    assert!(rust_code.contains("let mut swig_ret: f64 = since_unix_epoch.as_secs_f64() * 1_000.;"));
+
+    assert!(rust_code.contains("pub enum WasmColor {"));
+    assert!(rust_code.contains("impl From<WasmColor> for Color {"));
+
+    assert!(rust_code.contains("pub type JsObserver;"));
+    assert!(rust_code.contains(
+        "fn swig_on_change(\n            this: &JsObserver,\n            color: wasm_color::WasmColor,\n            count: i32,\n        ) -> Result<bool, JsValue>;"
+    ));
+    assert!(rust_code.contains("# [wasm_bindgen (method , catch , js_name = onChange)]"));
+    assert!(rust_code.contains("impl Observer for WasmCallback {"));
+    assert!(rust_code.contains("swig_ret.map_err(|err| From::from(swig_wasm_error_message(&err)))"));
+    assert!(rust_code.contains(
+        r#"export interface Observer {\n    onChange(color: Color, count: number): boolean;\n    onName(name: string): void;\n    load(key: string): string | undefined;\n}"#
+    ));
+
+    for unsupported in &[
+        "fn Foo::labels() -> HashMap<String, f64>;",
+        "fn Foo::choose(colors: Vec<Color>);",
+        "fn Foo::nested() -> Option<Option<i32>>;",
+    ] {
+        let src = format!(
+            r#"
+foreign_enum!(enum Color {{
+    Red = Color::Red,
+}});
+
+foreign_class!(class Foo {{
+    {}
+}});
+"#,
+            unsupported
+        );
+        let result =
+            panic::catch_unwind(|| parse_code(name, Source::Str(&src), ForeignLang::Wasm).unwrap());
+        assert!(result.is_err(), "{} should be rejected", unsupported);
+    }
+}
+
+#[test]
+fn test_custom_backend() {
+    let _ = env_logger::try_init();
+
+    let name = "custom_backend";
+    let src = r#"
+foreign_enum!(enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_class!(class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::is_zero(&self) -> bool;
+    fn Counter::color(&self) -> Color;
+    fn Counter::set_color(&mut self, color: Color);
+    #[toy_trace]
+    fn Counter::reset(&mut self);
+});
+"#;
+    let toy_code = parse_code(name, Source::Str(src), ForeignLang::Custom).unwrap();
+    let rust_code = rustfmt_without_errors(toy_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("toy: {}", toy_code.foreign_code);
+    assert!(rust_code.contains(
+        r#"pub fn toy_counter_is_zero(this: &Counter) -> u8 {
+    let ret: bool = Counter::is_zero(this);
+    let mut ret: u8 = if ret { 1 } else { 0 };
+    ret
+}"#
+    ));
+    assert!(rust_code.contains("pub fn toy_counter_color(this: &Counter) -> Color {"));
+    assert!(toy_code.foreign_code.contains(
+        r#"class Counter
+  new(start: int) -> Counter
+  increment() -> int
+  is_zero() -> bool = (ret != 0)
+  color() -> Color
+  set_color(color: Color) -> void
+  reset() -> void
+trace Counter.reset
+"#
+    ));
+
+    let result = panic::catch_unwind(|| {
+        parse_code(
+            name,
+            Source::Str(
+                r#"
+foreign_class!(class Counter {
+    self_type Counter;
+    constructor Counter::new() -> Counter;
+    fn Counter::history(&self) -> Vec<i32>;
+});
+"#,
+            ),
+            ForeignLang::Custom,
+        )
+        .unwrap()
+    });
+    assert!(result.is_err());
+}
+
+/// Backend for `test_custom_backend`: writes list of methods into `.toy` file
+/// and generates functions, that convert returned values to FFI types
+struct ToyBackend {
+    output_dir: PathBuf,
+}
+
+impl LanguageBackend for ToyBackend {
+    fn include_typemaps(&self) -> Vec<(String, String)> {
+        vec![(
+            "toy-include.rs".into(),
+            r#"
+foreign_typemap!(
+    (r_type) i32;
+    (f_type) "int";
+);
+
+foreign_typemap!(
+    ($p:r_type) bool => u8 {
+        $out = if $p { 1 } else { 0 };
+    };
+    ($p:f_type) => "bool" "($p != 0)";
+);
+"#
+            .into(),
+        )]
+    }
+
+    fn expand_items(
+        &self,
+        ctx: &mut BackendContext,
+        items: Vec<ItemToExpand>,
+    ) -> backend::Result<Vec<TokenStream>> {
+        let mut rust_code = vec![];
+        let mut file = ctx.create_file(self.output_dir.join("bindings.toy"));
+        for item in &items {
+            if let ItemToExpand::Enum(ref fenum) = item {
+                let enum_ty: syn::Type = syn::parse_str(&fenum.name().to_string()).unwrap();
+                ctx.add_foreign_type(
+                    &fenum.name().to_string(),
+                    &enum_ty,
+                    (fenum.src_id(), fenum.span()),
+                )?;
+            }
+        }
+        for item in &items {
+            let class = match item {
+                ItemToExpand::Class(ref class) => class,
+                _ => continue,
+            };
+            writeln!(&mut file, "class {}", class.name()).unwrap();
+            for method in class.methods() {
+                let src_id_span = (class.src_id(), method.span());
+                let args = method
+                    .args()
+                    .map(|arg| {
+                        let ftype =
+                            self.map_type(ctx, arg.ty(), Direction::Incoming, src_id_span)?;
+                        Ok(format!("{}: {}", arg.name(), ftype.name))
+                    })
+                    .collect::<backend::Result<Vec<_>>>()?;
+                let ret_ty: syn::Type = match method.output() {
+                    syn::ReturnType::Default => syn::parse_quote! { () },
+                    syn::ReturnType::Type(_, ref ty) => (**ty).clone(),
+                };
+                let ret = if method.variant() == MethodVariant::Constructor {
+                    class.name().to_string()
+                } else if ret_ty == syn::parse_quote! { () } {
+                    "void".into()
+                } else {
+                    let ftype = self.map_type(ctx, &ret_ty, Direction::Outgoing, src_id_span)?;
+                    if let MethodVariant::Method(_) = method.variant() {
+                        let ffi_ty = match ftype.foreign_conv {
+                            Some(ref conv) => &conv.intermediate_ty,
+                            None => &ftype.rust_ty,
+                        };
+                        let conv = ctx.convert_rust_types(
+                            &ret_ty,
+                            ffi_ty,
+                            "ret",
+                            "ret",
+                            "()",
+                            src_id_span,
+                        )?;
+                        let code = format!(
+                            "pub fn toy_{class}_{name}(this: &{class_ty}) -> {ffi_ty} {{\n let ret: {ret_ty} = {call};\n{conv}\n ret\n}}",
+                            class = class.name().to_string().to_lowercase(),
+                            name = method.short_name(),
+                            class_ty = class.name(),
+                            ffi_ty = quote::quote!(#ffi_ty),
+                            ret_ty = quote::quote!(#ret_ty),
+                            call = method.generate_code_to_call_rust_func(),
+                            conv = conv,
+                        );
+                        rust_code.push(syn::parse_str(&code).unwrap());
+                    }
+                    match ftype.foreign_conv {
+                        Some(conv) => format!(
+                            "{} = {}",
+                            ftype.name,
+                            conv.code.replace("{from_var}", "ret")
+                        ),
+                        None => ftype.name,
+                    }
+                };
+                writeln!(
+                    &mut file,
+                    "  {}({}) -> {}",
+                    method.short_name(),
+                    args.join(", "),
+                    ret
+                )
+                .unwrap();
+            }
+            let mut ext = vec![];
+            ctx.extend_foreign_class(class, &mut ext, &[], &[])?;
+            file.write_all(&ext).unwrap();
+        }
+        file.update_file_if_necessary().unwrap();
+        ctx.remove_not_generated_files(&self.output_dir, ".toy")?;
+        Ok(rust_code)
+    }
+}
+
+impl ToyBackend {
+    fn map_type(
+        &self,
+        ctx: &mut BackendContext,
+        ty: &syn::Type,
+        direction: Direction,
+        src_id_span: SourceIdSpan,
+    ) -> backend::Result<MappedForeignType> {
+        ctx.map_through_conversation_to_foreign(ty, direction, src_id_span, |fc| {
+            fc.self_type().cloned()
+        })
+        .ok_or_else(|| {
+            DiagnosticError::new(
+                src_id_span.0,
+                src_id_span.1,
+                format!("No toy type for {}", quote::quote!(#ty)),
+            )
+        })
+    }
+}
+
+#[test]
+fn test_catch_panics() {
+    let _ = env_logger::try_init();
+    let rust_src = r#"
+foreign_class!(class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::parse(text: &str) -> Result<Counter, String>;
+});
+"#;
+    let expand = |name: &str, config: LanguageConfig| -> (String, String) {
+        let tmp_dir = tempdir().expect("Can not create tmp directory");
+        let config = match config {
+            LanguageConfig::JavaConfig(_) => LanguageConfig::JavaConfig(
+                JavaConfig::new(tmp_dir.path().into(), "org.example".into())
+                    .catch_panics(CatchPanics::Message),
+            ),
+            LanguageConfig::CppConfig(_) => LanguageConfig::CppConfig(
+                CppConfig::new(tmp_dir.path().into(), "org_examples".into())
+                    .catch_panics(CatchPanics::MessageAndBacktrace),
+            ),
+            LanguageConfig::CConfig(_) => LanguageConfig::CConfig(
+                CConfig::new(tmp_dir.path().into(), "flapigen_test".into())
+                    .catch_panics(CatchPanics::Message),
+            ),
+            config => config,
+        };
+        let swig_gen = Generator::new(config).with_pointer_target_width(64);
+        let rust_code_path = tmp_dir.path().join("test.rs");
+        let rust_src_path = tmp_dir.path().join("src.rs");
+        fs::write(&rust_src_path, rust_src).unwrap();
+        swig_gen.expand(name, rust_src_path, &rust_code_path);
+        let rust_code = rustfmt_without_errors(fs::read_to_string(rust_code_path).unwrap());
+        let foreign_code = collect_code_in_dir(tmp_dir.path(), &[".h", ".hpp"]).unwrap();
+        (rust_code, foreign_code)
+    };
+
+    let dummy_dir = PathBuf::new();
+    let (rust_code, _) = expand(
+        "catch_panics_java",
+        LanguageConfig::JavaConfig(JavaConfig::new(dummy_dir.clone(), String::new())),
+    );
+    println!("java/rust: {}", rust_code);
+    assert!(rust_code.contains("fn swig_catch_panic<R, F: FnOnce() -> R>("));
+    assert!(rust_code.contains("static mut JAVA_LANG_RUNTIME_EXCEPTION: jclass"));
+    assert!(rust_code.contains("swig_jni_catch_panic(env, false, || {"));
+    assert_eq!(
+        rust_code
+            .matches("swig_jni_catch_panic(env, false, || {")
+            .count(),
+        4
+    );
+
+    let (rust_code, c_code) = expand(
+        "catch_panics_cpp",
+        LanguageConfig::CppConfig(CppConfig::new(dummy_dir.clone(), String::new())),
+    );
+    println!("cpp/rust: {}", rust_code);
+    println!("cpp/c: {}", c_code);
+    assert!(rust_code.contains("pub extern \"C\" fn flapigen_set_panic_callback("));
+    assert!(rust_code.contains("pub extern \"C\" fn flapigen_last_panic_message("));
+    assert_eq!(
+        rust_code.matches("swig_c_catch_panic(true, || {").count(),
+        4
+    );
+    assert!(c_code
+        .contains("void flapigen_set_panic_callback(RustPanicCallback callback, void *opaque);"));
+    assert!(c_code.contains("const char *flapigen_last_panic_message(void);"));
+    assert!(c_code.contains("#include \"rust_panic.h\""));
+    assert!(c_code.contains(
+        r#"    CounterWrapper(int32_t start) noexcept(false)
+    {
+
+        this->self_ = Counter_new(start);
+        flapigen_check_panic();"#
+    ));
+    assert!(c_code.contains("int32_t increment() noexcept(false);"));
+    assert!(c_code.contains(
+        r#"        int32_t ret = Counter_increment(this->self_);
+        flapigen_check_panic();"#
+    ));
+
+    let (rust_code, c_code) = expand(
+        "catch_panics_c",
+        LanguageConfig::CConfig(CConfig::new(dummy_dir, String::new())),
+    );
+    println!("c/rust: {}", rust_code);
+    println!("c/c: {}", c_code);
+    assert_eq!(
+        rust_code.matches("swig_c_catch_panic(false, || {").count(),
+        4
+    );
+    assert!(c_code.contains("#include \"rust_panic.h\""));
+    assert!(c_code.contains("const char *flapigen_last_panic_message(void);"));
+    assert!(!c_code.contains("noexcept"));
+
+    for python_binding in &[PythonBinding::RustCPython, PythonBinding::PyO3] {
+        let (rust_code, _) = expand(
+            "catch_panics_python",
+            LanguageConfig::PythonConfig(
+                PythonConfig::new("flapigen_test".into())
+                    .python_binding(*python_binding)
+                    .catch_panics(CatchPanics::Message),
+            ),
+        );
+        println!("python/rust: {}", rust_code);
+        assert!(rust_code.contains("fn swig_py_catch_panic<T, F: FnOnce() ->"));
+        // rust-cpython methods are inside of `py_class!`, rustfmt doesn't format them
+        let rust_code: String = rust_code.split_whitespace().collect();
+        assert_eq!(
+            rust_code
+                .matches("swig_py_catch_panic(py,false,||{")
+                .count(),
+            3
+        );
+    }
+
+    let (rust_code, _) = expand(
+        "catch_panics_python",
+        LanguageConfig::PythonConfig(PythonConfig::new("flapigen_test".into())),
+    );
+    assert!(!rust_code.contains("swig_catch_panic"));
+}
+
+#[test]
+fn test_dart_binding() {
+    let _ = env_logger::try_init();
+
+    let name = "dart_binding";
+    let src = r#"
+foreign_enum!(
+/// Colors
+enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_callback!(callback Observer {
+    self_type Observer;
+    onChange = Observer::on_change(&self, color: Color, count: i32) -> bool;
+    onName = Observer::on_name(&self, name: &str);
+});
+
+foreign_class!(
+/// Counter of things
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::set_color(&mut self, color: Color);
+    fn Counter::merge(&mut self, other: &Counter);
+    fn Counter::subscribe(&mut self, observer: Box<dyn Observer>);
+    fn Counter::name(&self) -> String;
+    fn Counter::rename(&mut self, name: &str);
+    fn Counter::nick(&self) -> Option<String>;
+    fn Counter::history(&self) -> Vec<i32>;
+    fn Counter::add_all(&mut self, values: &[i32]);
+    fn Counter::parse(text: &str) -> Result<Counter, String>;
+    fn Counter::check(&self) -> Result<(), String>;
+    fn Counter::split(&self) -> Vec<Counter>;
+});
+"#;
+    let dart_code = parse_code(name, Source::Str(src), ForeignLang::Dart).unwrap();
+    let rust_code = rustfmt_without_errors(dart_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("dart: {}", dart_code.foreign_code);
+    assert!(rust_code.contains("pub extern \"C\" fn Counter_increment("));
+
+    let dart = &dart_code.foreign_code;
+    assert!(dart.contains("part 'counter.dart';"));
+    assert!(dart.contains(
+        r#"/// Counter of things
+final class Counter implements Finalizable {
+  static final _finalizer = NativeFinalizer(
+      _lib.lookup<NativeFunction<Void Function(Pointer<Void>)>>('Counter_delete'));"#
+    ));
+    assert!(dart.contains(
+        r#"  factory Counter(int start) {
+    final ret = _Counter_new(start);
+    return Counter._wrap(ret, true);
+  }"#
+    ));
+    assert!(dart.contains(
+        r#"  void rename(String name) {
+    using((Arena arena) {
+      _Counter_rename(_ptr, _toCStrView(name, arena));
+    });
+  }"#
+    ));
+    assert!(dart.contains("  String? nick() {"));
+    assert!(dart.contains("  List<int> history() {"));
+    assert!(dart.contains("  void addAll(List<int> values) {"));
+    assert!(dart.contains("  static Counter parse(String text) {"));
+    assert!(dart.contains("throw RustException(_takeString(ret_err));"));
+    assert!(dart.contains("  List<Counter> split() {"));
+    assert!(dart.contains(
+        "final _Counter_set_color = _lib.lookupFunction<Void Function(Pointer<Void>, Uint32), void Function(Pointer<Void>, int)>('Counter_set_color');"
+    ));
+    assert!(dart.contains(
+        r#"abstract interface class Observer {
+  bool onChange(Color color, int count);
+  void onName(String name);
+}"#
+    ));
+    assert!(
+        dart.contains("NativeCallable<Int8 Function(Uint32, Int32, Pointer<Void>)>.isolateLocal(")
+    );
+    assert!(dart.contains(
+        r#"/// Colors
+enum Color {
+  red,
+  green,
+}"#
+    ));
+}
+
+#[test]
+fn test_c_headers_only() {
+    let _ = env_logger::try_init();
+
+    let name = "c_headers_only";
+    let src = r#"
+foreign_enum!(
+/// Colors
+enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_callback!(callback Observer {
+    self_type Observer;
+    onChange = Observer::on_change(&self, color: Color, count: i32) -> bool;
+    onName = Observer::on_name(&self, name: &str);
+});
+
+foreign_class!(
+/// Counter of things
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::set_color(&mut self, color: Color);
+    fn Counter::merge(&mut self, other: &Counter);
+    fn Counter::subscribe(&mut self, observer: Box<dyn Observer>);
+    fn Counter::name(&self) -> String;
+    fn Counter::rename(&mut self, name: &str);
+    fn Counter::nick(&self) -> Option<String>;
+    fn Counter::history(&self) -> Vec<i32>;
+    fn Counter::add_all(&mut self, values: &[i32]);
+    fn Counter::parse(text: &str) -> Result<Counter, String>;
+    fn Counter::check(&self) -> Result<(), String>;
+    fn Counter::split(&self) -> Vec<Counter>;
+});
+
+foreign_class!(class Registry {
+    self_type Registry;
+    constructor Registry::new() -> Registry;
+    fn Registry::first(&self) -> Counter;
+});
+"#;
+    let c_code = parse_code(name, Source::Str(src), ForeignLang::C).unwrap();
+    let rust_code = rustfmt_without_errors(c_code.rust_code);
+    println!("rust: {}", rust_code);
+    println!("c: {}", c_code.foreign_code);
+    assert!(rust_code.contains("pub extern \"C\" fn Counter_increment("));
+
+    let c = &c_code.foreign_code;
+    for cpp_only in &[
+        "namespace",
+        "template",
+        "static_assert",
+        "rust_vec_impl.hpp",
+        "<string>",
+        "<optional>",
+        "<variant>",
+        "//",
+    ] {
+        assert!(!c.contains(cpp_only), "{} in C headers", cpp_only);
+    }
+    assert!(c.contains(
+        r#"#include "c_Color.h"
+#include "c_Observer.h"
+#include "c_Counter.h"
+#include "c_Registry.h""#
+    ));
+    assert!(c.contains(
+        r#"/**
+ * Counter of things
+ *
+ * Opaque handle of Rust object, must be freed with Counter_delete
+ */
+#ifndef CounterOpaque_DEFINED
+#define CounterOpaque_DEFINED
+typedef struct CounterOpaque CounterOpaque;
+#endif"#
+    ));
+    assert!(c.contains(
+        r#"    /** Returned object must be freed with Counter_delete */
+    CounterOpaque *Counter_new(int32_t start);"#
+    ));
+    assert!(c.contains(
+        r#"    /** Free object, it can not be used after this call */
+    void Counter_delete(const CounterOpaque *self);"#
+    ));
+    assert!(c.contains("    RegistryOpaque *Registry_new(void);"));
+    assert!(c.contains("    CounterOpaque * Registry_first(const RegistryOpaque * const self);"));
+    assert!(c.contains(
+        r#"/** Colors */
+enum Color {
+    Red = 0,
+    Green = 1
+};"#
+    ));
+    assert!(c.contains("    uint8_t is_ok;"));
+    assert!(c.contains("typedef struct CRustForeignVec CRustForeignVec;"));
+    assert!(c.contains("void RustForeignVecCounter_push(CRustForeignVec * v, void * e);"));
+    assert!(c.contains("    char (*onChange)(uint32_t color, int32_t count, void *opaque);"));
+}
+
+#[test]
+fn test_kotlin_binding() {
+    let _ = env_logger::try_init();
+
+    let name = "kotlin_binding";
+    let src = r#"
+foreign_enum!(
+/// Colors
+enum Color {
+    Red = Color::Red,
+    Green = Color::Green,
+});
+
+foreign_callback!(callback Observer {
+    self_type Observer;
+    onChange = Observer::on_change(&self, color: Color, count: i32) -> bool;
+    onName = Observer::on_name(&self, name: &str);
+});
+
+foreign_callback!(callback OnDone {
+    self_type OnDone;
+    done = OnDone::done(&self, code: i32);
+});
+
+foreign_class!(
+/// Counter of things
+class Counter {
+    self_type Counter;
+    constructor Counter::new(start: i32) -> Counter;
+    fn Counter::increment(&mut self) -> i32;
+    fn Counter::set_color(&mut self, color: Color);
+    fn Counter::merge(&mut self, other: &Counter);
+    fn Counter::take(&mut self, other: Counter);
+    fn Counter::subscribe(&mut self, observer: Box<dyn Observer>);
+    fn Counter::name(&self) -> String;
+    fn Counter::rename(&mut self, name: &str);
+    fn Counter::nick(&self) -> Option<String>;
+    fn Counter::set_nick(&mut self, nick: Option<&str>);
+    fn Counter::history(&self) -> Vec<i32>;
+    fn Counter::add_all(&mut self, values: &[i32]);
+    fn Counter::parse(text: &str) -> Result<Counter, String>;
+    fn Counter::check(&self) -> Result<(), String>;
+    fn Counter::split(&self) -> Vec<Counter>;
+    fn Counter::maybe(&self) -> Option<i32>;
+    fn Counter::find(&self) -> Option<Counter>;
+    fn Counter::opt_color(&self) -> Option<Color>;
+    fn Counter::total() -> u64;
+});
+"#;
+    let kotlin_code = parse_code(name, Source::Str(src), ForeignLang::Kotlin).unwrap();
+    println!("kotlin: {}", kotlin_code.foreign_code);
+    let kt = &kotlin_code.foreign_code;
+    assert!(!kt.contains(".java"));
+
+    // Rust side should be the same as for Java,
+    // except order of cached JNI ids
+    let java_code = parse_code(name, Source::Str(src), ForeignLang::Java).unwrap();
+    let sorted_lines = |code: String| {
+        let code = rustfmt_without_errors(code);
+        let mut lines: Vec<String> = code.lines().map(str::to_string).collect();
+        lines.sort();
+        lines
+    };
+    assert_eq!(
+        sorted_lines(kotlin_code.rust_code),
+        sorted_lines(java_code.rust_code)
+    );
+
+    assert!(kt.contains(
+        r#"/**
+ * Counter of things
+ */
+class Counter internal constructor(marker: InternalPointerMarker, ptr: Long) : AutoCloseable {
+    @JvmField
+    internal var mNativeObj: Long = ptr"#
+    ));
+    assert!(kt.contains(
+        r#"    constructor(start: Int) : this(InternalPointerMarker.RAW_PTR, 0L) {
+        mNativeObj = init(start)
+    }"#
+    ));
+    assert!(kt.contains(
+        r#"    fun set_color(color: Color) {
+        val a0 = color.value
+        do_set_color(mNativeObj, a0)
+        JNIReachabilityFence.reachabilityFence1(color)
+    }"#
+    ));
+    assert!(kt.contains(
+        r#"    fun merge(other: Counter) {
+        val a0 = other.mNativeObj
+        do_merge(mNativeObj, a0)"#
+    ));
+    assert!(kt.contains(
+        r#"    fun take(other: Counter) {
+        val a0 = other.mNativeObj
+        other.mNativeObj = 0
+        do_take(mNativeObj, a0)"#
+    ));
+    assert!(kt.contains("    fun nick(): String? {"));
+    assert!(kt.contains("    fun set_nick(nick: String?) {"));
+    assert!(kt.contains("    fun history(): IntArray {"));
+    assert!(kt.contains(
+        r#"    @Throws(Exception::class)
+    fun check() {"#
+    ));
+    assert!(kt.contains("    fun split(): Array<Counter> {"));
+    assert!(kt.contains("        return if (ret.isPresent) ret.asInt else null"));
+    assert!(kt.contains(
+        "        return if (ret != 0L) Counter(InternalPointerMarker.RAW_PTR, ret) else null"
+    ));
+    assert!(kt.contains("        return if (ret != -1) Color.fromInt(ret) else null"));
+    assert!(kt.contains(
+        r#"    @Synchronized
+    override fun close() {
+        if (mNativeObj != 0L) {
+            do_delete(mNativeObj)
+            mNativeObj = 0
+        }
+    }"#
+    ));
+    assert!(kt.contains(
+        r#"    companion object {
+        @Throws(Exception::class)
+        @JvmStatic
+        fun parse(text: String): Counter {
+            val ret = do_parse(text)
+            return Counter(InternalPointerMarker.RAW_PTR, ret)
+        }
+
+        @JvmStatic
+        external fun total(): Long
+"#
+    ));
+    assert!(kt.contains(
+        r#"        @JvmStatic
+        private external fun do_increment(self: Long): Int
+"#
+    ));
+    assert!(kt.contains(
+        r#"/**
+ * Colors
+ */
+enum class Color(val value: Int) {
+    Red(0),
+    Green(1);
+"#
+    ));
+    assert!(kt.contains(
+        r#"interface Observer {
+
+    fun onChange(color: Color, count: Int): Boolean
+
+    fun onName(name: String)
+}"#
+    ));
+    assert!(kt.contains(
+        r#"fun interface OnDone {
+
+    fun done(code: Int)
+}"#
+    ));
+    assert!(kt.contains("internal enum class InternalPointerMarker {"));
+    assert!(kt.contains("internal object JNIReachabilityFence {"));
+}
+
+#[test]
+fn test_derive_extension_usage() {
+    let _ = env_logger::try_init();
+    let rust_src = r#"
+foreign_class!(
+#[derive(QObject)]
+class MyObj {
+    self_type MyObj;
+    private constructor MyObj::new() -> MyObj;
+    #[Q_INVOKABLE]
+    fn MyObj::f();
+});
+
+foreign_enum!(
+#[derive(EnumClass)]
+/// enum comment
+enum MyEnum {
+  A = MyEnum::A,
+  B = MyEnum::B,
+}
+);
+"#;
+    let tmp_dir = tempdir().expect("Can not create tmp directory");
+    let swig_gen = Generator::new(LanguageConfig::CppConfig(CppConfig::new(
+        tmp_dir.path().into(),
+        "org_examples".into(),
+    )))
+    .with_pointer_target_width(64)
+    .register_class_attribute_callback("QObject", |code, class_name| {
+        println!("class attribute callback class_name {}", class_name);
+        let include = b"#include";
+        let mut last_pos = 0;
+        let mut search_shift = 0;
+        while let Some(pos) = find_subsequence(&code[(last_pos + search_shift)..], include) {
+            last_pos += pos + search_shift;
+            search_shift = 1;
+        }
+        let last_include_pos = last_pos;
+        let new_line = &code[last_include_pos..].iter().position(|x| *x == b'\n');
+        let new_line_pos = last_include_pos + new_line.unwrap();
+        let addon = br##"
+#include <QObject>
+"##;
+        code.splice(new_line_pos..new_line_pos, addon.iter().copied());
+
+        let needle = format!("class {}Wrapper {{", class_name);
+        let class_pos = find_subsequence(&code, needle.as_bytes()).unwrap();
+        let end_pos = class_pos + needle.as_bytes().len();
+        let new_code = format!(
+            r#"class {}Wrapper : public QObject {{
+    Q_OBJECT"#,
+            class_name
+        );
+        code.splice(class_pos..end_pos, new_code.as_bytes().iter().copied());
+    })
+    .register_method_attribute_callback("Q_INVOKABLE", |code, ctx| {
+        println!(
+            "method attribute callback class {}, method {}",
+            ctx.class_name, ctx.method_name
+        );
+        let needle = match ctx.variant {
+            flapigen::MethodVariant::Constructor => {
+                panic!("unsupported");
+            }
+            flapigen::MethodVariant::Method(_) => format!("void {}(", ctx.method_name),
+            flapigen::MethodVariant::StaticMethod => format!("static void {}(", ctx.method_name),
+        };
+        let pos = find_subsequence(&code, needle.as_bytes()).unwrap();
+        code.splice(pos..pos, b"Q_INVOKABLE ".iter().copied());
+    })
+    .register_enum_attribute_callback("EnumClass", |code, ctx| {
+        println!("EnumClass callback, ctx {}", ctx);
+        let needle = format!("enum {}", ctx);
+        let pos = find_subsequence(&code, needle.as_bytes()).unwrap();
+        code.splice((pos + 5)..(pos + 5), b"class ".iter().copied());
+    });
+    let rust_code_path = tmp_dir.path().join("test.rs");
+
+    let rust_src_path = tmp_dir.path().join("src.rs");
+    fs::write(&rust_src_path, rust_src).unwrap();
+    swig_gen.expand("derive_extension_usage", rust_src_path, &rust_code_path);
+
+    let _rust_code = fs::read_to_string(rust_code_path).unwrap();
+    let foreign_code = collect_code_in_dir(tmp_dir.path(), &[".h", ".hpp"]).unwrap();
+    println!("foreign_code: {}", foreign_code);
+    assert!(foreign_code.contains(
+        r##"#include <QObject>
+"##
+    ));
+    assert!(foreign_code.contains(
+        r#"
+class MyObjWrapper : public QObject {
+    Q_OBJECT
+"#
+    ));
+    assert!(foreign_code.contains("Q_INVOKABLE static void f()"));
+    assert!(foreign_code.contains(
+        r#"//enum comment
+enum class MyEnum {
+A = 0,
+B = 1"#
+    ));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_python_derive_extension_usage() {
+    let _ = env_logger::try_init();
+    let rust_src = r#"
+foreign_class!(
+#[derive(Registered)]
+class MyObj {
+    self_type MyObj;
+    constructor MyObj::new() -> MyObj;
+    #[deprecated_in_python]
+    fn MyObj::f(&self);
+});
+
+foreign_enum!(
+#[derive(Registered)]
+enum MyEnum {
+  A = MyEnum::A,
+  B = MyEnum::B,
+}
+);
+"#;
+    let tmp_dir = tempdir().expect("Can not create tmp directory");
+    let swig_gen = Generator::new(LanguageConfig::PythonConfig(
+        PythonConfig::new("flapigen_test".into()).shims_output_dir(tmp_dir.path().into()),
+    ))
+    .with_pointer_target_width(64)
+    .register_class_attribute_callback("Registered", |code, class_name| {
+        code.extend_from_slice(format!("REGISTRY.append({})\n", class_name).as_bytes());
+    })
+    .register_method_attribute_callback("deprecated_in_python", |code, ctx| {
+        code.extend_from_slice(
+            format!(
+                "{}.{} = deprecated({0}.{1})\n",
+                ctx.class_name, ctx.method_name
+            )
+            .as_bytes(),
+        );
+    })
+    .register_enum_attribute_callback("Registered", |code, enum_name| {
+        code.extend_from_slice(format!("REGISTRY.append({})\n", enum_name).as_bytes());
+    });
+    let rust_code_path = tmp_dir.path().join("test.rs");
+    let rust_src_path = tmp_dir.path().join("src.rs");
+    fs::write(&rust_src_path, rust_src).unwrap();
+    swig_gen.expand(
+        "python_derive_extension_usage",
+        rust_src_path,
+        &rust_code_path,
+    );
+
+    let class_shim = fs::read_to_string(tmp_dir.path().join("my_obj.py")).unwrap();
+    println!("class_shim: {}", class_shim);
+    assert_eq!(
+        r#"# Automatically generated by flapigen
+from flapigen_test import MyObj
+REGISTRY.append(MyObj)
+MyObj.f = deprecated(MyObj.f)
+"#,
+        class_shim
+    );
+    let enum_shim = fs::read_to_string(tmp_dir.path().join("my_enum.py")).unwrap();
+    println!("enum_shim: {}", enum_shim);
+    assert_eq!(
+        r#"# Automatically generated by flapigen
+from flapigen_test import MyEnum
+REGISTRY.append(MyEnum)
+"#,
+        enum_shim
+    );
+    tmp_dir.close().unwrap();
+}
+
+fn find_subsequence<T>(haystack: &[T], needle: &[T]) -> Option<usize>
+where
+    for<'a> &'a [T]: PartialEq,
+{
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum ForeignLang {
+    Java,
+    Cpp,
+    Python,
+    PythonPyO3,
+    CSharp,
+    Swift,
+    Go,
+    Node,
+    Lua,
+    Ruby,
+    Wasm,
+    Dart,
+    C,
+    Kotlin,
+    Custom,
 }
 
 #[derive(Clone)]
@@ -606,6 +3232,113 @@ fn parse_code(test_name: &str, rust_src: Source, lang: ForeignLang) -> Result<Co
             .with_pointer_target_width(64);
             (swig_gen, &[".h", ".hpp"])
         }
+        ForeignLang::Python => {
+            let swig_gen = Generator::new(LanguageConfig::PythonConfig(PythonConfig::new(
+                "flapigen_test".into(),
+            )))
+            .with_pointer_target_width(64);
+            (swig_gen, &[])
+        }
+        ForeignLang::PythonPyO3 => {
+            let swig_gen = Generator::new(LanguageConfig::PythonConfig(
+                PythonConfig::new("flapigen_test".into()).python_binding(PythonBinding::PyO3),
+            ))
+            .with_pointer_target_width(64);
+            (swig_gen, &[])
+        }
+        ForeignLang::CSharp => {
+            let swig_gen = Generator::new(LanguageConfig::CSharpConfig(CSharpConfig::new(
+                tmp_dir.path().into(),
+                "org_examples".into(),
+                "flapigen_test".into(),
+            )))
+            .with_pointer_target_width(64);
+            (swig_gen, &[".cs"])
+        }
+        ForeignLang::Swift => {
+            let swig_gen = Generator::new(LanguageConfig::SwiftConfig(SwiftConfig::new(
+                tmp_dir.path().into(),
+                "RustFFI".into(),
+            )))
+            .with_pointer_target_width(64);
+            (swig_gen, &[".swift"])
+        }
+        ForeignLang::Go => {
+            let swig_gen = Generator::new(LanguageConfig::GoConfig(GoConfig::new(
+                tmp_dir.path().into(),
+                "rustlib".into(),
+                "flapigen_test".into(),
+            )))
+            .with_pointer_target_width(64);
+            (swig_gen, &[".go"])
+        }
+        ForeignLang::Node => {
+            let swig_gen = Generator::new(LanguageConfig::NodeConfig(NodeConfig::new(
+                tmp_dir.path().into(),
+                "flapigen_test".into(),
+            )))
+            .with_pointer_target_width(64);
+            (swig_gen, &[".d.ts"])
+        }
+        ForeignLang::Lua => {
+            let swig_gen = Generator::new(LanguageConfig::LuaConfig(LuaConfig::new(
+                tmp_dir.path().into(),
+                "flapigen_test".into(),
+            )))
+            .with_pointer_target_width(64);
+            (swig_gen, &[".d.lua"])
+        }
+        ForeignLang::Ruby => {
+            let swig_gen = Generator::new(LanguageConfig::RubyConfig(RubyConfig::new(
+                tmp_dir.path().into(),
+                "flapigen_test".into(),
+                "FlapigenTest".into(),
+            )))
+            .with_pointer_target_width(64);
+            (swig_gen, &[".rbs"])
+        }
+        ForeignLang::Wasm => {
+            let swig_gen = Generator::new(LanguageConfig::WasmConfig(WasmConfig::new()))
+                .with_pointer_target_width(64);
+            (swig_gen, &[])
+        }
+        ForeignLang::Dart => {
+            let swig_gen = Generator::new(LanguageConfig::DartConfig(DartConfig::new(
+                tmp_dir.path().into(),
+                "flapigen_test".into(),
+                "flapigen_test".into(),
+            )))
+            .with_pointer_target_width(64);
+            (swig_gen, &[".dart"])
+        }
+        ForeignLang::C => {
+            let swig_gen = Generator::new(LanguageConfig::CConfig(CConfig::new(
+                tmp_dir.path().into(),
+                "flapigen_test".into(),
+            )))
+            .with_pointer_target_width(64);
+            // C++ headers should not be generated, collect them to check this
+            (swig_gen, &[".h", ".hpp"])
+        }
+        ForeignLang::Kotlin => {
+            let swig_gen = Generator::new(LanguageConfig::JavaConfig(
+                JavaConfig::new(tmp_dir.path().into(), "org.example".into())
+                    .use_output_language(JavaOutputLanguage::Kotlin),
+            ))
+            .with_pointer_target_width(64);
+            // Java sources should not be generated, collect them to check this
+            (swig_gen, &[".kt", ".java"])
+        }
+        ForeignLang::Custom => {
+            let swig_gen = Generator::new(LanguageConfig::Custom(Box::new(ToyBackend {
+                output_dir: tmp_dir.path().into(),
+            })))
+            .with_pointer_target_width(64)
+            .register_method_attribute_callback("toy_trace", |code, info| {
+                writeln!(code, "trace {}.{}", info.class_name, info.method_name).unwrap();
+            });
+            (swig_gen, &[".toy"])
+        }
     };
 
     let rust_code_path = tmp_dir.path().join("test.rs");
@@ -658,6 +3391,18 @@ fn check_expectation(test_name: &str, test_case: &Path, lang: ForeignLang) -> bo
     let (main_ext, rust_ext) = match lang {
         ForeignLang::Cpp => (".cpp", ".cpp_rs"),
         ForeignLang::Java => (".java", ".java_rs"),
+        ForeignLang::Python | ForeignLang::PythonPyO3 => (".py", ".py_rs"),
+        ForeignLang::CSharp => (".cs", ".cs_rs"),
+        ForeignLang::Swift => (".swift", ".swift_rs"),
+        ForeignLang::Go => (".go", ".go_rs"),
+        ForeignLang::Node => (".d.ts", ".node_rs"),
+        ForeignLang::Lua => (".d.lua", ".lua_rs"),
+        ForeignLang::Ruby => (".rbs", ".ruby_rs"),
+        ForeignLang::Wasm => (".wasm_ts", ".wasm_rs"),
+        ForeignLang::Dart => (".dart", ".dart_rs"),
+        ForeignLang::C => (".c", ".c_rs"),
+        ForeignLang::Kotlin => (".kt", ".kt_rs"),
+        ForeignLang::Custom => (".toy", ".toy_rs"),
     };
     let main_expectation = new_path(test_case, main_ext);
     if main_expectation.exists() {
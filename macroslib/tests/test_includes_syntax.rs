@@ -12,5 +12,9 @@ mod cpp {
     include!(concat!(env!("OUT_DIR"), "/cpp-include.rs"));
 }
 
+mod csharp {
+    include!(concat!(env!("OUT_DIR"), "/csharp-include.rs"));
+}
+
 #[test]
 fn test_includes_syntax_ok() {}
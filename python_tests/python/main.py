@@ -1,6 +1,10 @@
 #!/usr/bin/python3
 
-from flapigen_test_python import TestStaticClass, TestEnum, TestClass, TestArc, TestArcMutex, TestBox, Error as TestError
+import array
+import threading
+import time
+
+from flapigen_test_python import TestStaticClass, TestEnum, TestClass, TestArc, TestArcMutex, TestBox, TestPoint, TestSequence, Error as TestError
 
 def test_static_methods():
     assert TestStaticClass.hello() == "Hello from rust"
@@ -40,12 +44,36 @@ def test_options():
     test_class = TestClass()
     assert test_class.maybe_add(1) == 1
     assert test_class.maybe_add(None) == None
+    assert TestStaticClass.maybe_class(True).get() == 0
+    assert TestStaticClass.maybe_class(False) == None
+
+def test_tuples():
+    assert TestStaticClass.swap_tuple((1, "a", True)) == (True, "a", 1)
+
+def test_collections():
+    assert TestStaticClass.count_words("a b a") == {"a": 2, "b": 1}
+    assert TestStaticClass.sum_values({"a": 1, "b": None, "c": 2}) == 3
+    assert TestStaticClass.unique_numbers([1, 2, 1]) == {1, 2}
+    assert TestStaticClass.count_unique({1, 2, 3}) == 3
+
+def test_bytes():
+    assert TestStaticClass.reverse_bytes(b"abc") == b"cba"
+    assert TestStaticClass.bytes_len(b"abcd") == 4
 
 def test_arrays():
     assert TestStaticClass.increment_vec([1, 2]) == [2, 3]
     assert TestStaticClass.return_slice([3, 4]) == [3, 4]
     assert TestStaticClass.count_slice_of_objects([TestClass(), TestClass()]) == 2
 
+def test_numeric_buffers():
+    assert TestStaticClass.sum_samples(array.array('d', [0.5, 1.5, 2.0])) == 4.0
+    assert TestStaticClass.sum_samples(memoryview(array.array('d', [1.0, 2.0, 3.0, 4.0]))[::2]) == 4.0
+    assert TestStaticClass.sum_samples([1, 2.5]) == 3.5
+    assert TestStaticClass.sum_samples([]) == 0.0
+    assert TestStaticClass.return_slice(array.array('f', [3, 4])) == [3, 4]
+    assert TestStaticClass.ramp(4) == [0, 1, 2, 3]
+    assert TestStaticClass.ramp(0) == []
+
 def test_results():
     TestStaticClass.test_result_ok()
     exception_occured = False
@@ -68,19 +96,93 @@ def test_arc_mutex():
     assert TestArcMutex.to_string_arc(arc) == "1"
     assert TestArcMutex.to_string_ref_arc(arc) == "1"
 
+def test_release_gil():
+    # Rust code runs without the GIL, so the threads sleep in parallel
+    threads = [threading.Thread(target=TestStaticClass.sleep_ms, args=(300,)) for _ in range(4)]
+    start = time.monotonic()
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+    assert time.monotonic() - start < 1.0
+
+    arc = TestArcMutex()
+    threads = [threading.Thread(target=arc.add_slowly, args=(i, 10)) for i in range(10)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+    assert str(arc) == "45"
+
+    cb = TestCallback()
+    assert TestStaticClass.call_test_callback_without_gil(cb, 3) == 6
+    assert cb.value == 3
+
+class TestCallback:
+    def __init__(self):
+        self.value = None
+
+    def on_value(self, value):
+        self.value = value
+
+    def compute(self, a):
+        if a < 0:
+            raise ValueError("negative value")
+        return a * 2
+
+def test_callback():
+    cb = TestCallback()
+    assert TestStaticClass.call_test_callback(cb, 2) == 4
+    assert cb.value == 2
+    # Python exception is returned to Rust as `Err`
+    assert TestStaticClass.call_test_callback(cb, -1) == -1
+    assert cb.value == -1
+
 def test_box():
     box = TestBox()
     assert str(box) == "0"
 
+def test_special_methods():
+    point = TestPoint(1, 2)
+    assert str(point) == "(1, 2)"
+    assert repr(point) == "TestPoint { x: 1, y: 2 }"
+    assert point == TestPoint(1, 2)
+    assert point == point
+    assert point != TestPoint(2, 1)
+    assert point != "(1, 2)"
+    assert point < TestPoint(1, 3)
+    assert TestPoint(2, 0) >= point
+    assert hash(point) == hash(TestPoint(1, 2))
+    assert len({point, TestPoint(1, 2), TestPoint(3, 4)}) == 2
+
+    seq = TestSequence(3)
+    assert len(seq) == 3
+    assert seq[1] == "item1"
+    try:
+        seq[3]
+        assert False
+    except IndexError:
+        pass
+    assert list(seq) == ["item0", "item1", "item2"]
+    assert [item for item in seq.iter()] == ["item0", "item1", "item2"]
+    assert seq.len() == 3
+
 print("Testing python API")
 test_enum()
 test_static_methods()
 test_class()
 test_options()
+test_tuples()
+test_collections()
+test_bytes()
 test_arrays()
+test_numeric_buffers()
 test_results()
 test_arc()
 test_arc_mutex()
 test_box()
+test_callback()
+test_special_methods()
+test_release_gil()
 
 print("Testing python API successful")